@@ -1,16 +1,24 @@
 //! Book-related commands
 
-use crate::formatter::{format_books, OutputFormat};
+use crate::formatter::format_books;
 use crate::helpers::{
-    get_library_path, parse_date_to_timestamp, timestamp_days_ago, timestamp_months_ago,
+    check_filters, filter_errors, get_library_path, library_columns, parse_date_to_timestamp,
+    parse_filter_query, resolve_book_preset, timestamp_days_ago, timestamp_months_ago,
+    AttributeFilterArgs, OutputArgs,
 };
 use ritmo_config::{detect_portable_library, AppSettings};
 use ritmo_core::service::{
-    batch_import, delete_book, import_book, update_book, BookImportMetadata, BookUpdateMetadata,
+    attach_file_to_book, batch_import, delete_book, delete_book_file, get_shelf, import_book,
+    shelf_filters, update_book, BookAttachTarget, BookImportMetadata, BookUpdateMetadata,
     DeleteOptions,
 };
 use ritmo_core::dto::BatchImportInput;
-use ritmo_db_core::{execute_books_query, BookFilters, BookSortField, LibraryConfig};
+use ritmo_db::{Book, BookFile, Format};
+use ritmo_db_core::filters::validate_book_filters;
+use ritmo_db_core::isbn::IsbnPolicy;
+use ritmo_db_core::{
+    execute_books_query, BookFilters, BookSortField, LibraryConfig, QueryTarget,
+};
 use ritmo_errors::reporter::SilentReporter;
 use std::path::PathBuf;
 
@@ -20,6 +28,7 @@ pub async fn cmd_list_books(
     cli_library: &Option<PathBuf>,
     app_settings: &AppSettings,
     preset: Option<String>,
+    shelf: Option<String>,
     author: Option<String>,
     publisher: Option<String>,
     series: Option<String>,
@@ -27,15 +36,17 @@ pub async fn cmd_list_books(
     year: Option<i32>,
     isbn: Option<String>,
     search: Option<String>,
+    query: Option<String>,
     acquired_after: Option<String>,
     acquired_before: Option<String>,
     last_days: Option<i64>,
     last_months: Option<i64>,
     recent_count: Option<i64>,
-    sort: String,
+    attributes: AttributeFilterArgs,
+    sort: Option<String>,
     limit: Option<i64>,
     offset: i64,
-    output: String,
+    output: OutputArgs,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Determina quale libreria usare
     let library_path = get_library_path(cli_library, app_settings)?;
@@ -72,42 +83,23 @@ pub async fn cmd_list_books(
 
     // Gestisci recent_count: override sort e limit
     let (final_sort, final_limit) = if let Some(count) = recent_count {
-        ("date_added".to_string(), Some(count))
+        (Some("date_added".to_string()), Some(count))
     } else {
         (sort, limit)
     };
 
-    // Carica preset della libreria per resolution
-    let library_presets = config.load_library_presets().ok();
-
     // Costruisci filtri usando builder pattern
     let mut filters = BookFilters::default();
 
-    // Se c'è un preset, carica i suoi valori come base
+    // Se c'è un preset o uno scaffale, i suoi filtri fanno da base
+    // (i parametri CLI sovrascriveranno questi)
     if let Some(preset_name) = preset {
         // Risolvi preset: library > global
-        let preset = if let Some(ref lib_presets) = library_presets {
-            lib_presets
-                .get_book_preset(&preset_name)
-                .or_else(|| app_settings.presets.get_book_preset(&preset_name))
-        } else {
-            app_settings.presets.get_book_preset(&preset_name)
-        }
-        .ok_or_else(|| format!("Preset '{}' non trovato", preset_name))?;
-
-        // Applica valori dal preset (i parametri CLI sovrascriveranno questi)
-        filters = filters
-            .set_author_opt(preset.filters.author.clone())
-            .set_publisher_opt(preset.filters.publisher.clone())
-            .set_series_opt(preset.filters.series.clone())
-            .set_format_opt(preset.filters.format.clone());
-
-        filters.year = preset.filters.year;
-        filters.isbn = preset.filters.isbn.clone();
-        filters.search = preset.filters.search.clone();
-        filters.acquired_after = preset.filters.acquired_after;
-        filters.acquired_before = preset.filters.acquired_before;
-        filters.limit = preset.filters.limit;
+        let preset = resolve_book_preset(&config, app_settings, &preset_name)?;
+        filters = BookFilters::from_preset(&preset).map_err(filter_errors)?;
+    } else if let Some(shelf_name) = shelf {
+        let shelf = get_shelf(&pool, &shelf_name).await?;
+        filters = shelf_filters(&shelf)?;
     }
 
     // Applica parametri CLI (hanno priorità su preset)
@@ -132,17 +124,25 @@ pub async fn cmd_list_books(
     if let Some(ab) = acquired_before_ts {
         filters.acquired_before = Some(ab);
     }
+    if let Some(q) = query {
+        filters.query = Some(parse_filter_query(&q, QueryTarget::Books)?);
+    }
+    attributes.apply_to_book_filters(&mut filters)?;
 
-    filters.sort = BookSortField::from_str(&final_sort);
-    filters.limit = final_limit;
+    if let Some(sort) = final_sort {
+        filters.sort = BookSortField::from_str(&sort);
+    }
+    filters.limit = final_limit.or(filters.limit);
     filters.offset = offset;
+    check_filters(validate_book_filters(&filters))?;
 
     // Esegui query
     let books = execute_books_query(&pool, &filters).await?;
 
     // Formatta output
-    let output_format = OutputFormat::from_str(&output);
-    let formatted = format_books(&books, &output_format);
+    let output_options =
+        output.to_options(QueryTarget::Books, &library_columns(&config, QueryTarget::Books))?;
+    let formatted = format_books(&books, &output_options);
 
     println!("{}", formatted);
 
@@ -167,6 +167,7 @@ pub async fn cmd_update_book(
     notes: Option<String>,
     pages: Option<i64>,
     tags: Vec<String>,
+    strict_isbn: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let library_path = get_library_path(cli_library, app_settings)?;
 
@@ -222,8 +223,12 @@ pub async fn cmd_update_book(
         },
     };
 
-    match update_book(&pool, book_id, metadata).await {
-        Ok(_) => {
+    let isbn_policy = IsbnPolicy::from_strict(strict_isbn || app_settings.preferences.strict_isbn);
+    match update_book(&pool, book_id, metadata, isbn_policy).await {
+        Ok(warnings) => {
+            for warning in warnings {
+                println!("⚠ {}", warning);
+            }
             println!("✓ Libro aggiornato con successo!");
         }
         Err(e) => {
@@ -273,6 +278,135 @@ pub async fn cmd_delete_book(
     Ok(())
 }
 
+/// Comando: attach-file - Aggiunge un file a un libro esistente
+pub async fn cmd_attach_file(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    file: PathBuf,
+    book_id: Option<i64>,
+    isbn: Option<String>,
+    format: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let library_path = get_library_path(cli_library, app_settings)?;
+
+    let config = LibraryConfig::new(&library_path);
+    if !config.exists() {
+        return Err(format!("La libreria non esiste: {}", library_path.display()).into());
+    }
+
+    let target = match (book_id, isbn) {
+        (Some(id), _) => BookAttachTarget::Id(id),
+        (None, Some(isbn)) => BookAttachTarget::Isbn(isbn),
+        (None, None) => return Err("Specificare --book-id oppure --isbn".into()),
+    };
+
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+
+    println!("Aggiunta file: {}", file.display());
+
+    match attach_file_to_book(&config, &pool, &file, &target, format).await {
+        Ok(book_id) => {
+            println!("✓ File aggiunto al libro ID {}", book_id);
+        }
+        Err(e) => {
+            println!("✗ Errore durante l'aggiunta del file: {}", e);
+            return Err(e.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Comando: list-files - Lista i file associati a un libro
+pub async fn cmd_list_files(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    book_id: i64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let library_path = get_library_path(cli_library, app_settings)?;
+
+    let config = LibraryConfig::new(&library_path);
+    if !config.exists() {
+        return Err(format!("La libreria non esiste: {}", library_path.display()).into());
+    }
+
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+
+    let book = Book::get(&pool, book_id)
+        .await?
+        .ok_or_else(|| format!("Libro con ID {} non trovato", book_id))?;
+    let files = BookFile::list_for_book(&pool, book_id).await?;
+
+    println!("File del libro [{}] {}:", book_id, book.name);
+    if files.is_empty() {
+        println!("  Nessun file");
+        return Ok(());
+    }
+
+    println!("{:<5} {:<15} {:<10} Percorso", "ID", "Formato", "KB");
+    for file in files {
+        let format = match file.format_id {
+            Some(id) => Format::get(&pool, id).await?.map(|f| f.key).unwrap_or_default(),
+            None => String::new(),
+        };
+        let primary = if book.file_link.as_deref() == Some(file.file_link.as_str()) {
+            " *"
+        } else {
+            ""
+        };
+        println!(
+            "{:<5} {:<15} {:<10} {}{}",
+            file.id.unwrap_or(0),
+            format,
+            file.file_size.unwrap_or(0) / 1024,
+            file.file_link,
+            primary
+        );
+    }
+
+    Ok(())
+}
+
+/// Comando: delete-file - Rimuove un singolo file da un libro
+pub async fn cmd_delete_file(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    file_id: i64,
+    delete_file: bool,
+    force: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let library_path = get_library_path(cli_library, app_settings)?;
+
+    let config = LibraryConfig::new(&library_path);
+    if !config.exists() {
+        return Err(format!("La libreria non esiste: {}", library_path.display()).into());
+    }
+
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+
+    println!("Rimozione file ID {}...", file_id);
+    if delete_file {
+        println!("  ⚠ Il file fisico verrà eliminato");
+    }
+
+    let options = DeleteOptions { delete_file, force };
+
+    match delete_book_file(&config, &pool, file_id, &options, &mut reporter).await {
+        Ok(_) => {
+            println!("✓ File rimosso con successo!");
+        }
+        Err(e) => {
+            println!("✗ Errore durante la rimozione: {}", e);
+            return Err(e.into());
+        }
+    }
+
+    Ok(())
+}
+
 /// Comando: add - Importa un libro nella libreria
 #[allow(clippy::too_many_arguments)]
 pub async fn cmd_add(
@@ -291,6 +425,7 @@ pub async fn cmd_add(
     pages: Option<i64>,
     notes: Option<String>,
     tags: Vec<String>,
+    strict: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Determina quale libreria usare
     let library_path = if let Some(path) = cli_library {
@@ -366,6 +501,8 @@ pub async fn cmd_add(
         } else {
             Some(tags)
         },
+        attach_to: None,
+        strict_validation: strict,
     };
 
     // Importa il libro
@@ -389,6 +526,8 @@ pub async fn cmd_add_batch(
     input: Option<PathBuf>,
     continue_on_error: bool,
     dry_run: bool,
+    strict: bool,
+    strict_isbn: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Determina quale libreria usare
     let library_path = if let Some(path) = cli_library {
@@ -433,7 +572,7 @@ pub async fn cmd_add_batch(
     };
 
     // Deserializza JSON
-    let batch_input: BatchImportInput = match serde_json::from_str(&json_content) {
+    let mut batch_input: BatchImportInput = match serde_json::from_str(&json_content) {
         Ok(input) => input,
         Err(e) => {
             println!("✗ Errore nel parsing JSON: {}", e);
@@ -457,17 +596,28 @@ pub async fn cmd_add_batch(
     );
     println!();
 
+    let isbn_policy = IsbnPolicy::from_strict(strict_isbn || app_settings.preferences.strict_isbn);
+
     // Dry-run: valida senza importare
     if dry_run {
         println!("🔍 Validazione metadata...\n");
         let mut validation_errors = 0;
 
-        for (idx, import_obj) in batch_input.iter().enumerate() {
-            print!("[{}/{}] Validating: {} ... ", idx + 1, batch_input.len(), import_obj.file_path);
+        let total = batch_input.len();
+        for (idx, import_obj) in batch_input.iter_mut().enumerate() {
+            print!("[{}/{}] Validating: {} ... ", idx + 1, total, import_obj.file_path);
 
             // Valida usando la funzione interna del service
-            match ritmo_core::service::batch_import_service::validate_import_object(import_obj) {
-                Ok(_) => println!("✓ OK"),
+            match ritmo_core::service::batch_import_service::validate_import_object(
+                import_obj,
+                isbn_policy,
+            ) {
+                Ok(warnings) => {
+                    println!("✓ OK");
+                    for warning in warnings {
+                        println!("      ⚠ {}", warning);
+                    }
+                }
                 Err(e) => {
                     println!("✗ ERROR");
                     println!("      {}", e);
@@ -493,7 +643,15 @@ pub async fn cmd_add_batch(
     // Esegui batch import
     println!("📥 Importazione libri...\n");
 
-    let summary = batch_import(&config, &pool, batch_input, !continue_on_error).await?;
+    let summary = batch_import(
+        &config,
+        &pool,
+        batch_input,
+        !continue_on_error,
+        strict,
+        isbn_policy,
+    )
+    .await?;
 
     // Mostra progresso durante l'import
     for (idx, result) in summary.results.iter().enumerate() {
@@ -501,6 +659,9 @@ pub async fn cmd_add_batch(
 
         if result.success {
             println!("✓ {} (ID: {})", result.file_path, result.book_id.unwrap());
+            for warning in &result.warnings {
+                println!("      ⚠ {}", warning);
+            }
         } else {
             println!("✗ {}", result.file_path);
             if let Some(ref err) = result.error_message {
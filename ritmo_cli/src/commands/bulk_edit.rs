@@ -0,0 +1,326 @@
+//! Bulk edit of the books selected by a filter
+
+use crate::helpers::{
+    check_filters, filter_errors, get_library_path, parse_date_to_timestamp, parse_filter_query,
+    resolve_book_preset, timestamp_days_ago, timestamp_months_ago, AttributeFilterArgs,
+};
+use clap::Args;
+use ritmo_config::AppSettings;
+use ritmo_core::service::{apply_bulk_edit, get_shelf, plan_bulk_edit, shelf_filters, BulkEdit};
+use ritmo_db_core::filters::{validate_book_filters, PersonRoleFilter};
+use ritmo_db_core::{execute_books_query, BookFilters, BookSortField, LibraryConfig, QueryTarget};
+use ritmo_errors::reporter::SilentReporter;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+
+/// Selezione dei libri da modificare (stessi filtri di list-books)
+#[derive(Args, Debug, Default)]
+pub struct BookSelectionArgs {
+    /// Seleziona con un preset salvato
+    #[arg(long, short = 'p')]
+    pub preset: Option<String>,
+
+    /// Seleziona i libri di uno scaffale
+    #[arg(long, conflicts_with = "preset")]
+    pub shelf: Option<String>,
+
+    /// Seleziona per ID (ripetibile; in AND con gli altri filtri)
+    #[arg(long = "id")]
+    pub ids: Vec<i64>,
+
+    #[arg(long)]
+    pub author: Option<String>,
+
+    #[arg(long)]
+    pub publisher: Option<String>,
+
+    #[arg(long)]
+    pub series: Option<String>,
+
+    #[arg(long)]
+    pub format: Option<String>,
+
+    #[arg(long)]
+    pub year: Option<i32>,
+
+    #[arg(long)]
+    pub isbn: Option<String>,
+
+    #[arg(long, short)]
+    pub search: Option<String>,
+
+    /// Query booleana, es. "series:dune AND NOT tag:read"
+    #[arg(long, short = 'q')]
+    pub query: Option<String>,
+
+    #[arg(long)]
+    pub acquired_after: Option<String>,
+
+    #[arg(long)]
+    pub acquired_before: Option<String>,
+
+    #[arg(long, conflicts_with = "acquired_after")]
+    pub last_days: Option<i64>,
+
+    #[arg(long, conflicts_with = "acquired_after")]
+    pub last_months: Option<i64>,
+
+    #[command(flatten)]
+    pub attributes: AttributeFilterArgs,
+
+    /// Ordine dei libri (conta per --renumber-series): title, author, year, date_added
+    #[arg(long)]
+    pub sort: Option<String>,
+
+    /// Seleziona tutti i libri della libreria
+    #[arg(long)]
+    pub all: bool,
+}
+
+impl BookSelectionArgs {
+    fn is_empty(&self) -> bool {
+        self.preset.is_none()
+            && self.shelf.is_none()
+            && self.ids.is_empty()
+            && self.author.is_none()
+            && self.publisher.is_none()
+            && self.series.is_none()
+            && self.format.is_none()
+            && self.year.is_none()
+            && self.isbn.is_none()
+            && self.search.is_none()
+            && self.query.is_none()
+            && self.acquired_after.is_none()
+            && self.acquired_before.is_none()
+            && self.last_days.is_none()
+            && self.last_months.is_none()
+            && self.attributes.is_empty()
+    }
+}
+
+/// Modifiche da applicare ai libri selezionati
+#[derive(Args, Debug, Default)]
+pub struct BulkEditArgs {
+    /// Imposta l'editore
+    #[arg(long)]
+    pub set_publisher: Option<String>,
+
+    /// Imposta la serie
+    #[arg(long)]
+    pub set_series: Option<String>,
+
+    /// Imposta l'anno di pubblicazione
+    #[arg(long)]
+    pub set_year: Option<i32>,
+
+    /// Imposta il numero di pagine
+    #[arg(long)]
+    pub set_pages: Option<i64>,
+
+    /// Imposta le note
+    #[arg(long)]
+    pub set_notes: Option<String>,
+
+    /// Aggiunge un tag (ripetibile)
+    #[arg(long)]
+    pub add_tag: Vec<String>,
+
+    /// Rimuove un tag (ripetibile)
+    #[arg(long)]
+    pub remove_tag: Vec<String>,
+
+    /// Aggiunge una persona con ruolo, formato "Nome:ruolo" (ripetibile)
+    #[arg(long)]
+    pub add_person: Vec<String>,
+
+    /// Rimuove una persona con ruolo, formato "Nome:ruolo" (ripetibile)
+    #[arg(long)]
+    pub remove_person: Vec<String>,
+
+    /// Rinumera la serie nell'ordine della selezione, a partire da N (default 1)
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "1")]
+    pub renumber_series: Option<i64>,
+}
+
+impl BulkEditArgs {
+    fn into_bulk_edit(self) -> Result<BulkEdit, Box<dyn std::error::Error>> {
+        let parse_people = |values: Vec<String>| -> Result<Vec<(String, String)>, String> {
+            values
+                .iter()
+                .map(|value| {
+                    PersonRoleFilter::parse(value)
+                        .map(|p| (p.name, p.role))
+                        .ok_or_else(|| {
+                            format!(
+                                "Formato persona non valido: '{}'. Formato richiesto: 'Nome:ruolo'",
+                                value
+                            )
+                        })
+                })
+                .collect()
+        };
+
+        if let Some(start) = self.renumber_series {
+            if start < 1 {
+                return Err("--renumber-series deve partire da 1 o più".into());
+            }
+        }
+
+        Ok(BulkEdit {
+            publisher: self.set_publisher,
+            series: self.set_series,
+            year: self.set_year,
+            pages: self.set_pages,
+            notes: self.set_notes,
+            add_tags: self.add_tag,
+            remove_tags: self.remove_tag,
+            add_people: parse_people(self.add_person)?,
+            remove_people: parse_people(self.remove_person)?,
+            renumber_series_from: self.renumber_series,
+        })
+    }
+}
+
+/// Comando: bulk-edit - Modifica in blocco i libri selezionati da un filtro
+pub async fn cmd_bulk_edit(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    selection: BookSelectionArgs,
+    edit: BulkEditArgs,
+    dry_run: bool,
+    yes: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if selection.is_empty() && !selection.all {
+        return Err(
+            "Indica quali libri modificare (filtri, --preset, --shelf, --id) oppure --all".into(),
+        );
+    }
+    let edit = edit.into_bulk_edit()?;
+    if edit.is_empty() {
+        return Err(
+            "Nessuna modifica indicata (es. --set-publisher, --add-tag, --renumber-series)".into(),
+        );
+    }
+
+    let library_path = get_library_path(cli_library, app_settings)?;
+    let config = LibraryConfig::new(&library_path);
+    if !config.exists() {
+        return Err(format!("La libreria non esiste: {}", library_path.display()).into());
+    }
+
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+
+    // Selezione: preset o scaffale come base, poi i filtri espliciti
+    let mut filters = BookFilters::default();
+    if let Some(preset_name) = &selection.preset {
+        let preset = resolve_book_preset(&config, app_settings, preset_name)?;
+        filters = BookFilters::from_preset(&preset).map_err(filter_errors)?;
+    } else if let Some(shelf_name) = &selection.shelf {
+        let shelf = get_shelf(&pool, shelf_name).await?;
+        filters = shelf_filters(&shelf)?;
+    }
+
+    filters = filters
+        .set_author_opt(selection.author)
+        .set_publisher_opt(selection.publisher)
+        .set_series_opt(selection.series)
+        .set_format_opt(selection.format);
+    if let Some(y) = selection.year {
+        filters.year = Some(y);
+    }
+    if let Some(i) = selection.isbn {
+        filters.isbn = Some(i);
+    }
+    if let Some(s) = selection.search {
+        filters.search = Some(s);
+    }
+    if let Some(days) = selection.last_days {
+        filters.acquired_after = Some(timestamp_days_ago(days));
+    } else if let Some(months) = selection.last_months {
+        filters.acquired_after = Some(timestamp_months_ago(months));
+    } else if let Some(date) = &selection.acquired_after {
+        filters.acquired_after = Some(parse_date_to_timestamp(date)?);
+    }
+    if let Some(date) = &selection.acquired_before {
+        filters.acquired_before = Some(parse_date_to_timestamp(date)?);
+    }
+    if let Some(q) = &selection.query {
+        filters.query = Some(parse_filter_query(q, QueryTarget::Books)?);
+    }
+    selection.attributes.apply_to_book_filters(&mut filters)?;
+    if let Some(sort) = &selection.sort {
+        filters.sort = BookSortField::from_str(sort);
+    }
+    check_filters(validate_book_filters(&filters))?;
+
+    let mut books = execute_books_query(&pool, &filters).await?;
+    if !selection.ids.is_empty() {
+        for id in &selection.ids {
+            if !books.iter().any(|b| b.id == *id) {
+                return Err(format!(
+                    "Il libro {} non esiste o non corrisponde ai filtri indicati",
+                    id
+                )
+                .into());
+            }
+        }
+        books.retain(|b| selection.ids.contains(&b.id));
+    }
+
+    let book_ids: Vec<i64> = books.iter().map(|b| b.id).collect();
+    let plans = plan_bulk_edit(&pool, &book_ids, &edit).await?;
+
+    // Anteprima delle differenze
+    println!(
+        "Libri selezionati: {} — da modificare: {}\n",
+        book_ids.len(),
+        plans.len()
+    );
+    for plan in &plans {
+        println!("[{}] {}", plan.book_id, plan.title);
+        for change in &plan.changes {
+            println!("    {}", change);
+        }
+    }
+
+    if plans.is_empty() {
+        println!("Nessuna modifica necessaria.");
+        return Ok(());
+    }
+    if dry_run {
+        println!("\n(dry-run: nessuna modifica salvata)");
+        return Ok(());
+    }
+
+    if !yes {
+        if !std::io::stdin().is_terminal() {
+            return Err(
+                "Conferma richiesta: usa --yes per applicare le modifiche senza chiedere".into(),
+            );
+        }
+        print!("\nApplicare le modifiche a {} libri? [s/N] ", plans.len());
+        std::io::stdout().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if !matches!(
+            answer.trim().to_lowercase().as_str(),
+            "s" | "si" | "sì" | "y" | "yes"
+        ) {
+            println!("Operazione annullata.");
+            return Ok(());
+        }
+    }
+
+    let result = apply_bulk_edit(&pool, &plans, &edit).await?;
+    println!("\n✓ Aggiornati {} libri", result.updated_book_ids.len());
+    if !result.marked_for_sync.is_empty() {
+        println!(
+            "📝 {} libri segnati per la sincronizzazione dei metadati",
+            result.marked_for_sync.len()
+        );
+        println!("   Esegui 'ritmo sync-metadata' per aggiornare i file");
+    }
+
+    Ok(())
+}
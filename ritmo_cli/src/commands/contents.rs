@@ -1,13 +1,19 @@
 //! Content-related commands
 
-use crate::formatter::{format_contents, OutputFormat};
-use crate::helpers::get_library_path;
+use crate::formatter::format_contents;
+use crate::helpers::{
+    check_filters, filter_errors, get_library_path, library_columns, parse_filter_query,
+    AttributeFilterArgs, OutputArgs,
+};
 use ritmo_config::AppSettings;
 use ritmo_core::service::{
     create_content, delete_content, link_content_to_book, unlink_content_from_book,
     update_content, ContentCreateMetadata, ContentUpdateMetadata,
 };
-use ritmo_db_core::{execute_contents_query, ContentFilters, ContentSortField, LibraryConfig};
+use ritmo_db_core::filters::validate_content_filters;
+use ritmo_db_core::{
+    execute_contents_query, ContentFilters, ContentSortField, LibraryConfig, QueryTarget,
+};
 use ritmo_errors::reporter::SilentReporter;
 use std::path::PathBuf;
 
@@ -21,10 +27,12 @@ pub async fn cmd_list_contents(
     content_type: Option<String>,
     year: Option<i32>,
     search: Option<String>,
+    query: Option<String>,
+    attributes: AttributeFilterArgs,
     sort: String,
     limit: Option<i64>,
     offset: i64,
-    output: String,
+    output: OutputArgs,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Determina quale libreria usare
     let library_path = get_library_path(cli_library, app_settings)?;
@@ -58,13 +66,7 @@ pub async fn cmd_list_contents(
         .ok_or_else(|| format!("Preset '{}' non trovato", preset_name))?;
 
         // Applica valori dal preset
-        filters = filters
-            .set_author_opt(preset.filters.author.clone())
-            .set_content_type_opt(preset.filters.content_type.clone());
-
-        filters.year = preset.filters.year;
-        filters.search = preset.filters.search.clone();
-        filters.limit = preset.filters.limit;
+        filters = ContentFilters::from_preset(&preset.filters).map_err(filter_errors)?;
     }
 
     // Applica parametri CLI (hanno priorità su preset)
@@ -78,17 +80,25 @@ pub async fn cmd_list_contents(
     if let Some(s) = search {
         filters.search = Some(s);
     }
+    if let Some(q) = query {
+        filters.query = Some(parse_filter_query(&q, QueryTarget::Contents)?);
+    }
+    attributes.apply_to_content_filters(&mut filters)?;
 
     filters.sort = ContentSortField::from_str(&sort);
     filters.limit = limit;
     filters.offset = offset;
+    check_filters(validate_content_filters(&filters))?;
 
     // Esegui query
     let contents = execute_contents_query(&pool, &filters).await?;
 
     // Formatta output
-    let output_format = OutputFormat::from_str(&output);
-    let formatted = format_contents(&contents, &output_format);
+    let output_options = output.to_options(
+        QueryTarget::Contents,
+        &library_columns(&config, QueryTarget::Contents),
+    )?;
+    let formatted = format_contents(&contents, &output_options);
 
     println!("{}", formatted);
 
@@ -5,12 +5,124 @@ use ritmo_config::AppSettings;
 use ritmo_db::mark_books_for_sync;
 use ritmo_db_core::LibraryConfig;
 use ritmo_errors::reporter::SilentReporter;
+use ritmo_errors::RitmoResult;
 use ritmo_ml::deduplication::{
-    deduplicate_people, deduplicate_publishers, deduplicate_roles, deduplicate_series,
-    deduplicate_tags, DeduplicationConfig, DeduplicationResult,
+    deduplicate_contents, deduplicate_people_with_rules, deduplicate_publishers,
+    deduplicate_roles, deduplicate_series, deduplicate_tags, DeduplicationConfig,
+    DeduplicationResult, MatchEvidence,
 };
+use ritmo_ml::merge_journal::{merge_history, undo_merge, JournalOperation, MergeEntity};
+use ritmo_ml::people::name_rules::NameParsingRules;
 use std::path::PathBuf;
 
+/// Name parsing rules with the extra particles from `settings.toml`
+fn name_rules(app_settings: &AppSettings) -> NameParsingRules {
+    NameParsingRules::default().with_particles(&app_settings.preferences.name_particles)
+}
+
+/// Options shared by the deduplicate-* commands
+#[derive(Debug, Clone, Copy)]
+pub struct DeduplicateArgs {
+    pub threshold: f64,
+    pub min_frequency: usize,
+    pub auto_merge: bool,
+    pub dry_run: bool,
+    pub json: bool,
+}
+
+/// Print deduplication results as JSON or as a table
+fn report_deduplication_results(
+    result: &DeduplicationResult,
+    entity_type: &str,
+    args: &DeduplicateArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(result)?);
+    } else {
+        print_deduplication_results(result, entity_type, args.dry_run);
+    }
+    Ok(())
+}
+
+/// One-line summary of why a duplicate was proposed
+fn format_evidence(evidence: &MatchEvidence) -> String {
+    let mut parts = Vec::new();
+    if (evidence.score - evidence.jaro_winkler).abs() > f64::EPSILON {
+        parts.push(format!("score {:.2}", evidence.score));
+    }
+    parts.push(format!("jaro-winkler {:.2}", evidence.jaro_winkler));
+    if evidence.relational != 0.0 {
+        parts.push(format!("relational {:+.2}", evidence.relational));
+    }
+    let learned = if evidence.learned_pattern {
+        ", learned"
+    } else {
+        ""
+    };
+    parts.push(format!(
+        "{:?} (confidence {:.2}, frequency {}{})",
+        evidence.pattern_type, evidence.pattern_confidence, evidence.pattern_frequency, learned
+    ));
+    if evidence.same_normalized_key {
+        parts.push("same key".to_string());
+    }
+    if evidence.alias_hit {
+        parts.push("alias".to_string());
+    }
+    parts.push(format!(
+        "shared books {} ({:.0}%)",
+        evidence.shared_books,
+        evidence.book_overlap * 100.0
+    ));
+    parts.join(" · ")
+}
+
+/// Notice printed after marking merged books for sync (on stderr with --json)
+fn print_sync_notice(count: usize, json: bool) {
+    let lines = [
+        format!("\n📝 Marked {} books for metadata sync", count),
+        "   Run 'ritmo sync-metadata' to update EPUB files with new metadata".to_string(),
+    ];
+    for line in lines {
+        if json {
+            eprintln!("{}", line);
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+/// Section header of deduplicate-all (omitted with --json)
+fn print_section_header(header: &str, first: bool, args: &DeduplicateArgs) {
+    if args.json {
+        return;
+    }
+    if !first {
+        println!();
+    }
+    println!("═══════════════════════════════════════════════════════");
+    println!("{}", header);
+    println!("═══════════════════════════════════════════════════════");
+}
+
+/// Print one section of deduplicate-all, or add it to the JSON document
+fn collect_section(
+    json_results: &mut serde_json::Map<String, serde_json::Value>,
+    key: &str,
+    entity_type: &str,
+    result: RitmoResult<DeduplicationResult>,
+    args: &DeduplicateArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match result {
+        Ok(result) if args.json => {
+            json_results.insert(key.to_string(), serde_json::to_value(&result)?);
+        }
+        Ok(result) => print_deduplication_results(&result, entity_type, args.dry_run),
+        Err(e) => eprintln!("✗ Error deduplicating {}: {}", key, e),
+    }
+    Ok(())
+}
+
 /// Print deduplication results in a user-friendly format
 fn print_deduplication_results(result: &DeduplicationResult, entity_type: &str, dry_run: bool) {
     println!("📊 Deduplication Results for {}:", entity_type);
@@ -41,6 +153,9 @@ fn print_deduplication_results(result: &DeduplicationResult, entity_type: &str,
             .enumerate()
         {
             println!("       {}. {} (ID: {})", j + 1, dup_name, dup_id);
+            if let Some(evidence) = group.evidence.iter().find(|e| e.duplicate_id == *dup_id) {
+                println!("          {}", format_evidence(evidence));
+            }
         }
     }
 
@@ -53,14 +168,16 @@ fn print_deduplication_results(result: &DeduplicationResult, entity_type: &str,
         println!("\n✓ Merged {} groups:", result.merged_groups.len());
         for (i, stats) in result.merged_groups.iter().enumerate() {
             println!(
-                "   {}. Primary ID {}: merged {} duplicates ({} books, {} contents updated)",
+                "   {}. Primary ID {}: merged {} duplicates ({} books, {} contents updated) [merge #{}]",
                 i + 1,
                 stats.primary_id,
                 stats.merged_ids.len(),
                 stats.books_updated,
-                stats.contents_updated
+                stats.contents_updated,
+                stats.merge_id
             );
         }
+        println!("   Run 'ritmo dedupe undo <merge-id>' to split a wrong merge back apart");
     } else {
         println!("\n⚠️  No auto-merge performed (use --auto-merge to enable)");
     }
@@ -77,9 +194,7 @@ fn print_deduplication_results(result: &DeduplicationResult, entity_type: &str,
 pub async fn cmd_deduplicate_people(
     cli_library: &Option<PathBuf>,
     app_settings: &AppSettings,
-    threshold: f64,
-    auto_merge: bool,
-    dry_run: bool,
+    args: DeduplicateArgs,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let library_path = get_library_path(cli_library, app_settings)?;
 
@@ -91,25 +206,28 @@ pub async fn cmd_deduplicate_people(
     let mut reporter = SilentReporter;
     let pool = config.create_pool(&mut reporter).await?;
 
-    println!("🔍 Searching for duplicate people...");
+    if !args.json {
+        println!("🔍 Searching for duplicate people...");
+    }
 
     // Default to dry-run mode for safety (invert the flag logic)
-    let actual_dry_run = if auto_merge && !dry_run {
+    let actual_dry_run = if args.auto_merge && !args.dry_run {
         false  // Only disable dry-run if auto-merge is requested AND --dry-run was NOT passed
     } else {
         true   // Default to dry-run in all other cases
     };
 
     let dedup_config = DeduplicationConfig {
-        min_confidence: threshold,
-        min_frequency: 2,
-        auto_merge,
+        min_confidence: args.threshold,
+        min_frequency: args.min_frequency,
+        auto_merge: args.auto_merge,
         dry_run: actual_dry_run,
+        ..Default::default()
     };
 
-    match deduplicate_people(&pool, &dedup_config).await {
+    match deduplicate_people_with_rules(&pool, &dedup_config, &name_rules(app_settings)).await {
         Ok(result) => {
-            print_deduplication_results(&result, "People", dry_run);
+            report_deduplication_results(&result, "People", &args)?;
 
             // Mark affected books for sync if not dry-run
             if !actual_dry_run && !result.merged_groups.is_empty() {
@@ -122,8 +240,7 @@ pub async fn cmd_deduplicate_people(
 
                 if !all_affected_books.is_empty() {
                     mark_books_for_sync(&pool, &all_affected_books, "author_deduplicate").await?;
-                    println!("\n📝 Marked {} books for metadata sync", all_affected_books.len());
-                    println!("   Run 'ritmo sync-metadata' to update EPUB files with new metadata");
+                    print_sync_notice(all_affected_books.len(), args.json);
                 }
             }
 
@@ -140,9 +257,7 @@ pub async fn cmd_deduplicate_people(
 pub async fn cmd_deduplicate_publishers(
     cli_library: &Option<PathBuf>,
     app_settings: &AppSettings,
-    threshold: f64,
-    auto_merge: bool,
-    dry_run: bool,
+    args: DeduplicateArgs,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let library_path = get_library_path(cli_library, app_settings)?;
 
@@ -154,25 +269,28 @@ pub async fn cmd_deduplicate_publishers(
     let mut reporter = SilentReporter;
     let pool = config.create_pool(&mut reporter).await?;
 
-    println!("🔍 Searching for duplicate publishers...");
+    if !args.json {
+        println!("🔍 Searching for duplicate publishers...");
+    }
 
     // Default to dry-run mode for safety (invert the flag logic)
-    let actual_dry_run = if auto_merge && !dry_run {
+    let actual_dry_run = if args.auto_merge && !args.dry_run {
         false  // Only disable dry-run if auto-merge is requested AND --dry-run was NOT passed
     } else {
         true   // Default to dry-run in all other cases
     };
 
     let dedup_config = DeduplicationConfig {
-        min_confidence: threshold,
-        min_frequency: 2,
-        auto_merge,
+        min_confidence: args.threshold,
+        min_frequency: args.min_frequency,
+        auto_merge: args.auto_merge,
         dry_run: actual_dry_run,
+        ..Default::default()
     };
 
     match deduplicate_publishers(&pool, &dedup_config).await {
         Ok(result) => {
-            print_deduplication_results(&result, "Publishers", dry_run);
+            report_deduplication_results(&result, "Publishers", &args)?;
 
             // Mark affected books for sync if not dry-run
             if !actual_dry_run && !result.merged_groups.is_empty() {
@@ -185,8 +303,7 @@ pub async fn cmd_deduplicate_publishers(
 
                 if !all_affected_books.is_empty() {
                     mark_books_for_sync(&pool, &all_affected_books, "publisher_deduplicate").await?;
-                    println!("\n📝 Marked {} books for metadata sync", all_affected_books.len());
-                    println!("   Run 'ritmo sync-metadata' to update EPUB files with new metadata");
+                    print_sync_notice(all_affected_books.len(), args.json);
                 }
             }
 
@@ -203,9 +320,7 @@ pub async fn cmd_deduplicate_publishers(
 pub async fn cmd_deduplicate_series(
     cli_library: &Option<PathBuf>,
     app_settings: &AppSettings,
-    threshold: f64,
-    auto_merge: bool,
-    dry_run: bool,
+    args: DeduplicateArgs,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let library_path = get_library_path(cli_library, app_settings)?;
 
@@ -217,25 +332,28 @@ pub async fn cmd_deduplicate_series(
     let mut reporter = SilentReporter;
     let pool = config.create_pool(&mut reporter).await?;
 
-    println!("🔍 Searching for duplicate series...");
+    if !args.json {
+        println!("🔍 Searching for duplicate series...");
+    }
 
     // Default to dry-run mode for safety (invert the flag logic)
-    let actual_dry_run = if auto_merge && !dry_run {
+    let actual_dry_run = if args.auto_merge && !args.dry_run {
         false  // Only disable dry-run if auto-merge is requested AND --dry-run was NOT passed
     } else {
         true   // Default to dry-run in all other cases
     };
 
     let dedup_config = DeduplicationConfig {
-        min_confidence: threshold,
-        min_frequency: 2,
-        auto_merge,
+        min_confidence: args.threshold,
+        min_frequency: args.min_frequency,
+        auto_merge: args.auto_merge,
         dry_run: actual_dry_run,
+        ..Default::default()
     };
 
     match deduplicate_series(&pool, &dedup_config).await {
         Ok(result) => {
-            print_deduplication_results(&result, "Series", dry_run);
+            report_deduplication_results(&result, "Series", &args)?;
 
             // Mark affected books for sync if not dry-run
             if !actual_dry_run && !result.merged_groups.is_empty() {
@@ -248,8 +366,7 @@ pub async fn cmd_deduplicate_series(
 
                 if !all_affected_books.is_empty() {
                     mark_books_for_sync(&pool, &all_affected_books, "series_deduplicate").await?;
-                    println!("\n📝 Marked {} books for metadata sync", all_affected_books.len());
-                    println!("   Run 'ritmo sync-metadata' to update EPUB files with new metadata");
+                    print_sync_notice(all_affected_books.len(), args.json);
                 }
             }
 
@@ -266,9 +383,7 @@ pub async fn cmd_deduplicate_series(
 pub async fn cmd_deduplicate_tags(
     cli_library: &Option<PathBuf>,
     app_settings: &AppSettings,
-    threshold: f64,
-    auto_merge: bool,
-    dry_run: bool,
+    args: DeduplicateArgs,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let library_path = get_library_path(cli_library, app_settings)?;
 
@@ -280,25 +395,28 @@ pub async fn cmd_deduplicate_tags(
     let mut reporter = SilentReporter;
     let pool = config.create_pool(&mut reporter).await?;
 
-    println!("🔍 Searching for duplicate tags...");
+    if !args.json {
+        println!("🔍 Searching for duplicate tags...");
+    }
 
     // Default to dry-run mode for safety (invert the flag logic)
-    let actual_dry_run = if auto_merge && !dry_run {
+    let actual_dry_run = if args.auto_merge && !args.dry_run {
         false  // Only disable dry-run if auto-merge is requested AND --dry-run was NOT passed
     } else {
         true   // Default to dry-run in all other cases
     };
 
     let dedup_config = DeduplicationConfig {
-        min_confidence: threshold,
-        min_frequency: 2,
-        auto_merge,
+        min_confidence: args.threshold,
+        min_frequency: args.min_frequency,
+        auto_merge: args.auto_merge,
         dry_run: actual_dry_run,
+        ..Default::default()
     };
 
     match deduplicate_tags(&pool, &dedup_config).await {
         Ok(result) => {
-            print_deduplication_results(&result, "Tags", dry_run);
+            report_deduplication_results(&result, "Tags", &args)?;
 
             // Mark affected books for sync if not dry-run
             if !actual_dry_run && !result.merged_groups.is_empty() {
@@ -311,8 +429,7 @@ pub async fn cmd_deduplicate_tags(
 
                 if !all_affected_books.is_empty() {
                     mark_books_for_sync(&pool, &all_affected_books, "tag_deduplicate").await?;
-                    println!("\n📝 Marked {} books for metadata sync", all_affected_books.len());
-                    println!("   Run 'ritmo sync-metadata' to update EPUB files with new metadata");
+                    print_sync_notice(all_affected_books.len(), args.json);
                 }
             }
 
@@ -329,9 +446,7 @@ pub async fn cmd_deduplicate_tags(
 pub async fn cmd_deduplicate_roles(
     cli_library: &Option<PathBuf>,
     app_settings: &AppSettings,
-    threshold: f64,
-    auto_merge: bool,
-    dry_run: bool,
+    args: DeduplicateArgs,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let library_path = get_library_path(cli_library, app_settings)?;
 
@@ -343,25 +458,28 @@ pub async fn cmd_deduplicate_roles(
     let mut reporter = SilentReporter;
     let pool = config.create_pool(&mut reporter).await?;
 
-    println!("🔍 Searching for duplicate roles...");
+    if !args.json {
+        println!("🔍 Searching for duplicate roles...");
+    }
 
     // Default to dry-run mode for safety (invert the flag logic)
-    let actual_dry_run = if auto_merge && !dry_run {
+    let actual_dry_run = if args.auto_merge && !args.dry_run {
         false  // Only disable dry-run if auto-merge is requested AND --dry-run was NOT passed
     } else {
         true   // Default to dry-run in all other cases
     };
 
     let dedup_config = DeduplicationConfig {
-        min_confidence: threshold,
-        min_frequency: 2,
-        auto_merge,
+        min_confidence: args.threshold,
+        min_frequency: args.min_frequency,
+        auto_merge: args.auto_merge,
         dry_run: actual_dry_run,
+        ..Default::default()
     };
 
     match deduplicate_roles(&pool, &dedup_config).await {
         Ok(result) => {
-            print_deduplication_results(&result, "Roles", dry_run);
+            report_deduplication_results(&result, "Roles", &args)?;
 
             // Mark affected books for sync if not dry-run
             if !actual_dry_run && !result.merged_groups.is_empty() {
@@ -374,8 +492,73 @@ pub async fn cmd_deduplicate_roles(
 
                 if !all_affected_books.is_empty() {
                     mark_books_for_sync(&pool, &all_affected_books, "role_deduplicate").await?;
-                    println!("\n📝 Marked {} books for metadata sync", all_affected_books.len());
-                    println!("   Run 'ritmo sync-metadata' to update EPUB files with new metadata");
+                    print_sync_notice(all_affected_books.len(), args.json);
+                }
+            }
+
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("✗ Error during deduplication: {}", e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Command: deduplicate-contents - Find and merge duplicate contents (works)
+///
+/// The same work re-created for each imported edition: book links, people,
+/// tags and languages are moved onto the surviving content.
+pub async fn cmd_deduplicate_contents(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    args: DeduplicateArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let library_path = get_library_path(cli_library, app_settings)?;
+
+    let config = LibraryConfig::new(&library_path);
+    if !config.exists() {
+        return Err(format!("Library does not exist: {}", library_path.display()).into());
+    }
+
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+
+    if !args.json {
+        println!("🔍 Searching for duplicate contents...");
+    }
+
+    // Default to dry-run mode for safety (invert the flag logic)
+    let actual_dry_run = if args.auto_merge && !args.dry_run {
+        false  // Only disable dry-run if auto-merge is requested AND --dry-run was NOT passed
+    } else {
+        true   // Default to dry-run in all other cases
+    };
+
+    let dedup_config = DeduplicationConfig {
+        min_confidence: args.threshold,
+        min_frequency: args.min_frequency,
+        auto_merge: args.auto_merge,
+        dry_run: actual_dry_run,
+        ..Default::default()
+    };
+
+    match deduplicate_contents(&pool, &dedup_config).await {
+        Ok(result) => {
+            report_deduplication_results(&result, "Contents", &args)?;
+
+            // Mark affected books for sync if not dry-run
+            if !actual_dry_run && !result.merged_groups.is_empty() {
+                let mut all_affected_books = Vec::new();
+                for stats in &result.merged_groups {
+                    all_affected_books.extend(&stats.affected_book_ids);
+                }
+                all_affected_books.sort();
+                all_affected_books.dedup();
+
+                if !all_affected_books.is_empty() {
+                    mark_books_for_sync(&pool, &all_affected_books, "content_deduplicate").await?;
+                    print_sync_notice(all_affected_books.len(), args.json);
                 }
             }
 
@@ -392,9 +575,7 @@ pub async fn cmd_deduplicate_roles(
 pub async fn cmd_deduplicate_all(
     cli_library: &Option<PathBuf>,
     app_settings: &AppSettings,
-    threshold: f64,
-    auto_merge: bool,
-    dry_run: bool,
+    args: DeduplicateArgs,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let library_path = get_library_path(cli_library, app_settings)?;
 
@@ -406,68 +587,162 @@ pub async fn cmd_deduplicate_all(
     let mut reporter = SilentReporter;
     let pool = config.create_pool(&mut reporter).await?;
 
-    println!("🔍 Searching for duplicates across all entity types...\n");
+    if !args.json {
+        println!("🔍 Searching for duplicates across all entity types...\n");
+    }
 
     // Default to dry-run mode for safety (invert the flag logic)
-    let actual_dry_run = if auto_merge && !dry_run {
+    let actual_dry_run = if args.auto_merge && !args.dry_run {
         false  // Only disable dry-run if auto-merge is requested AND --dry-run was NOT passed
     } else {
         true   // Default to dry-run in all other cases
     };
 
     let dedup_config = DeduplicationConfig {
-        min_confidence: threshold,
-        min_frequency: 2,
-        auto_merge,
+        min_confidence: args.threshold,
+        min_frequency: args.min_frequency,
+        auto_merge: args.auto_merge,
         dry_run: actual_dry_run,
+        ..Default::default()
     };
 
+    let mut json_results = serde_json::Map::new();
+
     // Deduplicate people (authors, translators, etc.)
-    println!("═══════════════════════════════════════════════════════");
-    println!("👥 PEOPLE");
-    println!("═══════════════════════════════════════════════════════");
-    match deduplicate_people(&pool, &dedup_config).await {
-        Ok(result) => print_deduplication_results(&result, "People", dry_run),
-        Err(e) => eprintln!("✗ Error deduplicating people: {}", e),
-    }
+    print_section_header("👥 PEOPLE", true, &args);
+    let result = deduplicate_people_with_rules(&pool, &dedup_config, &name_rules(app_settings)).await;
+    collect_section(&mut json_results, "people", "People", result, &args)?;
 
     // Deduplicate publishers
-    println!("\n═══════════════════════════════════════════════════════");
-    println!("🏢 PUBLISHERS");
-    println!("═══════════════════════════════════════════════════════");
-    match deduplicate_publishers(&pool, &dedup_config).await {
-        Ok(result) => print_deduplication_results(&result, "Publishers", dry_run),
-        Err(e) => eprintln!("✗ Error deduplicating publishers: {}", e),
-    }
+    print_section_header("🏢 PUBLISHERS", false, &args);
+    let result = deduplicate_publishers(&pool, &dedup_config).await;
+    collect_section(&mut json_results, "publishers", "Publishers", result, &args)?;
 
     // Deduplicate series
-    println!("\n═══════════════════════════════════════════════════════");
-    println!("📚 SERIES");
-    println!("═══════════════════════════════════════════════════════");
-    match deduplicate_series(&pool, &dedup_config).await {
-        Ok(result) => print_deduplication_results(&result, "Series", dry_run),
-        Err(e) => eprintln!("✗ Error deduplicating series: {}", e),
-    }
+    print_section_header("📚 SERIES", false, &args);
+    let result = deduplicate_series(&pool, &dedup_config).await;
+    collect_section(&mut json_results, "series", "Series", result, &args)?;
 
     // Deduplicate tags
-    println!("\n═══════════════════════════════════════════════════════");
-    println!("🏷️  TAGS");
-    println!("═══════════════════════════════════════════════════════");
-    match deduplicate_tags(&pool, &dedup_config).await {
-        Ok(result) => print_deduplication_results(&result, "Tags", dry_run),
-        Err(e) => eprintln!("✗ Error deduplicating tags: {}", e),
-    }
+    print_section_header("🏷️  TAGS", false, &args);
+    let result = deduplicate_tags(&pool, &dedup_config).await;
+    collect_section(&mut json_results, "tags", "Tags", result, &args)?;
 
     // Deduplicate roles
-    println!("\n═══════════════════════════════════════════════════════");
-    println!("🎭 ROLES");
-    println!("═══════════════════════════════════════════════════════");
-    match deduplicate_roles(&pool, &dedup_config).await {
-        Ok(result) => print_deduplication_results(&result, "Roles", dry_run),
-        Err(e) => eprintln!("✗ Error deduplicating roles: {}", e),
+    print_section_header("🎭 ROLES", false, &args);
+    let result = deduplicate_roles(&pool, &dedup_config).await;
+    collect_section(&mut json_results, "roles", "Roles", result, &args)?;
+
+    // Deduplicate contents (works)
+    print_section_header("📖 CONTENTS", false, &args);
+    let result = deduplicate_contents(&pool, &dedup_config).await;
+    collect_section(&mut json_results, "contents", "Contents", result, &args)?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&json_results)?);
+    } else {
+        println!("\n✓ Deduplication complete for all entity types!");
+    }
+
+    Ok(())
+}
+
+/// Command: dedupe history - List past merges with their statistics
+pub async fn cmd_dedupe_history(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    entity_type: Option<String>,
+    limit: Option<i64>,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let library_path = get_library_path(cli_library, app_settings)?;
+
+    let config = LibraryConfig::new(&library_path);
+    if !config.exists() {
+        return Err(format!("Library does not exist: {}", library_path.display()).into());
     }
 
-    println!("\n✓ Deduplication complete for all entity types!");
+    let entity = entity_type
+        .as_deref()
+        .map(str::parse::<MergeEntity>)
+        .transpose()?;
+
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+
+    let entries = merge_history(&pool, entity, limit).await?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No merges recorded");
+        return Ok(());
+    }
+
+    println!("📜 Merge history ({} entries):", entries.len());
+    for entry in &entries {
+        let date = chrono::DateTime::from_timestamp(entry.created_at, 0)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| entry.created_at.to_string());
+        let status = if entry.undone_at.is_some() { " (undone)" } else { "" };
+        println!(
+            "\n   #{} {} {}{}",
+            entry.id, date, entry.entity_type, status
+        );
+        if entry.operation == JournalOperation::Split {
+            println!(
+                "     Split: links moved from ID {:?} to ID {}",
+                entry.stats.merged_ids, entry.stats.primary_id
+            );
+        } else {
+            println!("     Primary ID: {}", entry.stats.primary_id);
+            for (dup_id, name) in entry.stats.merged_ids.iter().zip(&entry.merged_names) {
+                println!("     Merged: {} (ID: {})", name, dup_id);
+            }
+        }
+        println!(
+            "     {} books, {} contents updated; {} books affected",
+            entry.stats.books_updated,
+            entry.stats.contents_updated,
+            entry.stats.affected_book_ids.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Command: dedupe undo - Split the entities of a past merge back apart
+pub async fn cmd_dedupe_undo(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    merge_id: i64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let library_path = get_library_path(cli_library, app_settings)?;
+
+    let config = LibraryConfig::new(&library_path);
+    if !config.exists() {
+        return Err(format!("Library does not exist: {}", library_path.display()).into());
+    }
+
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+
+    let stats = undo_merge(&pool, merge_id).await?;
+
+    println!(
+        "✓ Undid merge #{}: restored {} records split from primary ID {}",
+        merge_id,
+        stats.merged_ids.len(),
+        stats.primary_id
+    );
+
+    if !stats.affected_book_ids.is_empty() {
+        mark_books_for_sync(&pool, &stats.affected_book_ids, "merge_undo").await?;
+        print_sync_notice(stats.affected_book_ids.len(), false);
+    }
 
     Ok(())
 }
@@ -0,0 +1,148 @@
+//! Near-duplicate book detection and merge command
+
+use crate::helpers::get_library_path;
+use ritmo_config::AppSettings;
+use ritmo_core::service::{find_duplicate_books, merge_books, DuplicateBookGroup};
+use ritmo_db_core::LibraryConfig;
+use ritmo_errors::reporter::SilentReporter;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+
+/// Opzioni di find-duplicate-books
+pub struct FindDuplicateBooksArgs {
+    pub threshold: f64,
+    pub json: bool,
+    pub merge: bool,
+    pub yes: bool,
+    pub delete_files: bool,
+}
+
+/// Command: find-duplicate-books
+///
+/// Elenca i gruppi di libri che sembrano la stessa edizione: stesso ISBN,
+/// stesso titolo e stesse persone, o EPUB con testo simile (anche se l'hash
+/// del file è diverso). Con `--merge` unisce ogni gruppo nel libro più vecchio;
+/// sul terminale, senza `--yes`, chiede conferma gruppo per gruppo.
+pub async fn cmd_find_duplicate_books(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    args: FindDuplicateBooksArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !(0.0..=1.0).contains(&args.threshold) {
+        return Err("La soglia deve essere compresa tra 0.0 e 1.0".into());
+    }
+
+    let library_path = get_library_path(cli_library, app_settings)?;
+    let config = LibraryConfig::new(&library_path);
+    if !config.exists() {
+        return Err(format!("La libreria non esiste: {}", library_path.display()).into());
+    }
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+
+    let report = find_duplicate_books(&config, &pool, args.threshold).await?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        if !args.merge {
+            return Ok(());
+        }
+    } else {
+        if report.fingerprinted > 0 {
+            println!("🔎 Impronte del testo calcolate: {}", report.fingerprinted);
+        }
+        for file_link in &report.unreadable {
+            eprintln!("⚠ Testo non leggibile: {}", file_link);
+        }
+        if report.groups.is_empty() {
+            println!("✓ Nessun libro duplicato trovato");
+            return Ok(());
+        }
+        for group in &report.groups {
+            print_group(group);
+        }
+        println!("\n📊 Gruppi di duplicati: {}", report.groups.len());
+    }
+
+    if !args.merge {
+        println!("Usa --merge per unire ogni gruppo nel libro principale");
+        return Ok(());
+    }
+
+    let interactive = !args.yes && std::io::stdin().is_terminal();
+    if !args.yes && !interactive {
+        return Err("Conferma richiesta: usa --yes per unire i duplicati senza chiedere".into());
+    }
+
+    let mut merged = 0;
+    for group in &report.groups {
+        let duplicates: Vec<i64> = group
+            .books
+            .iter()
+            .map(|b| b.book_id)
+            .filter(|&id| id != group.primary_id)
+            .collect();
+        if interactive && !confirm_merge(group.primary_id, &duplicates)? {
+            continue;
+        }
+        let result = merge_books(
+            &config,
+            &pool,
+            group.primary_id,
+            &duplicates,
+            args.delete_files,
+        )
+        .await?;
+        merged += result.merged_ids.len();
+        println!(
+            "✓ Uniti {:?} in [{}] (file spostati: {}, eliminati: {})",
+            result.merged_ids, result.primary_id, result.moved_files, result.deleted_files
+        );
+    }
+
+    if merged > 0 {
+        println!("\n✓ Libri uniti: {}", merged);
+        println!("📝 Esegui 'ritmo sync-metadata' per aggiornare i metadati dei file");
+    }
+    Ok(())
+}
+
+fn print_group(group: &DuplicateBookGroup) {
+    println!();
+    for book in &group.books {
+        let marker = if book.book_id == group.primary_id {
+            "★"
+        } else {
+            " "
+        };
+        let people = if book.people.is_empty() {
+            String::new()
+        } else {
+            format!(" — {}", book.people.join(", "))
+        };
+        let isbn = book
+            .isbn
+            .as_deref()
+            .map(|i| format!(" (ISBN {})", i))
+            .unwrap_or_default();
+        println!(
+            "{} [{}] {}{}{} — file: {}",
+            marker, book.book_id, book.title, people, isbn, book.file_count
+        );
+    }
+    for m in &group.matches {
+        let reasons: Vec<String> = m.reasons.iter().map(|r| r.to_string()).collect();
+        println!("    {} ↔ {}: {}", m.book_a, m.book_b, reasons.join(", "));
+    }
+}
+
+fn confirm_merge(primary_id: i64, duplicates: &[i64]) -> Result<bool, Box<dyn std::error::Error>> {
+    print!("Unire {:?} in [{}]? [s/N] ", duplicates, primary_id);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(
+        answer.trim().to_lowercase().as_str(),
+        "s" | "si" | "sì" | "y" | "yes"
+    ))
+}
@@ -0,0 +1,110 @@
+//! ISBN check command
+
+use crate::helpers::get_library_path;
+use ritmo_config::AppSettings;
+use ritmo_core::service::{check_library_isbns, normalize_library_isbns};
+use ritmo_db_core::isbn::Isbn;
+use ritmo_db_core::LibraryConfig;
+use ritmo_errors::reporter::SilentReporter;
+use std::path::PathBuf;
+
+/// Comando: isbn-check - Verifica gli ISBN
+///
+/// Con dei valori sulla riga di comando li valida e mostra le forme ISBN-10/13;
+/// altrimenti elenca gli ISBN non validi o duplicati della libreria. Con `--fix`
+/// normalizza gli ISBN validi (trattini e ISBN-13 canonico).
+/// Termina con errore se trova ISBN non validi o duplicati.
+pub async fn cmd_isbn_check(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    values: Vec<String>,
+    fix: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !values.is_empty() {
+        return check_values(&values);
+    }
+
+    let library_path = get_library_path(cli_library, app_settings)?;
+    let config = LibraryConfig::new(&library_path);
+    if !config.exists() {
+        return Err(format!("La libreria non esiste: {}", library_path.display()).into());
+    }
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+
+    if fix {
+        let updated = normalize_library_isbns(&pool).await?;
+        println!("✓ ISBN normalizzati: {}\n", updated);
+    }
+
+    let report = check_library_isbns(&pool).await?;
+
+    if !report.invalid.is_empty() {
+        println!("✗ ISBN non validi:");
+        for item in &report.invalid {
+            println!(
+                "  [{}] {} — '{}': {}",
+                item.book_id, item.title, item.isbn, item.reason
+            );
+        }
+        println!();
+    }
+
+    if !report.duplicates.is_empty() {
+        println!("⚠ ISBN duplicati:");
+        for group in &report.duplicates {
+            println!("  {}", group.isbn13);
+            for book in &group.books {
+                println!("    [{}] {} ({})", book.book_id, book.title, book.isbn);
+            }
+        }
+        println!();
+    }
+
+    println!("📊 Libri con ISBN: {}", report.checked);
+    println!("  ✗ Non validi: {}", report.invalid.len());
+    println!("  ⚠ Duplicati: {}", report.duplicates.len());
+    if !report.not_normalized.is_empty() {
+        println!(
+            "  • Da normalizzare: {} (esegui 'ritmo isbn-check --fix')",
+            report.not_normalized.len()
+        );
+    }
+
+    if report.has_problems() {
+        return Err(format!(
+            "{} ISBN non validi, {} duplicati",
+            report.invalid.len(),
+            report.duplicates.len()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Valida i valori indicati e mostra le forme ISBN-10/13
+fn check_values(values: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut invalid = 0;
+    for value in values {
+        match Isbn::parse(value) {
+            Ok(isbn) => {
+                let isbn10 = isbn.to_isbn10().unwrap_or_else(|| "-".to_string());
+                println!(
+                    "✓ {}  ISBN-13: {}  ISBN-10: {}",
+                    value,
+                    isbn.to_isbn13(),
+                    isbn10
+                );
+            }
+            Err(e) => {
+                println!("✗ {}  {}", value, e);
+                invalid += 1;
+            }
+        }
+    }
+
+    if invalid > 0 {
+        return Err(format!("{} ISBN non validi", invalid).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,147 @@
+//! Manual merge and split commands for people, publishers, series, tags, roles and contents
+
+use crate::helpers::get_library_path;
+use clap::Args;
+use ritmo_config::AppSettings;
+use ritmo_db::mark_books_for_sync;
+use ritmo_db_core::LibraryConfig;
+use ritmo_errors::reporter::SilentReporter;
+use ritmo_ml::merge::{
+    merge_contents, merge_people, merge_publishers, merge_roles, merge_series, merge_tags,
+    split_person,
+};
+use ritmo_ml::merge_journal::MergeEntity;
+use std::path::PathBuf;
+
+/// Entità da unire
+#[derive(Args, Debug)]
+pub struct MergeArgs {
+    /// ID delle entità da unire (almeno due)
+    #[arg(required = true, num_args = 2..)]
+    pub ids: Vec<i64>,
+
+    /// ID da mantenere (default: il primo)
+    #[arg(long)]
+    pub keep: Option<i64>,
+}
+
+/// Command: merge <tipo> - Unisce manualmente entità dello stesso tipo
+///
+/// Usa le stesse operazioni transazionali della deduplicazione: i riferimenti
+/// passano all'entità mantenuta, le altre vengono eliminate (per le persone
+/// i nomi scartati diventano alias) e l'unione è annullabile con
+/// `ritmo dedupe undo`.
+pub async fn cmd_merge(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    entity: MergeEntity,
+    args: MergeArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let keep = args.keep.unwrap_or(args.ids[0]);
+    if !args.ids.contains(&keep) {
+        return Err(format!("L'ID da mantenere ({}) non è tra quelli da unire", keep).into());
+    }
+    let mut duplicate_ids: Vec<i64> = Vec::new();
+    for &id in &args.ids {
+        if id != keep && !duplicate_ids.contains(&id) {
+            duplicate_ids.push(id);
+        }
+    }
+    if duplicate_ids.is_empty() {
+        return Err("Indica almeno due ID diversi".into());
+    }
+
+    let library_path = get_library_path(cli_library, app_settings)?;
+    let config = LibraryConfig::new(&library_path);
+    if !config.exists() {
+        return Err(format!("La libreria non esiste: {}", library_path.display()).into());
+    }
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+
+    let stats = match entity {
+        MergeEntity::People => merge_people(&pool, keep, &duplicate_ids).await?,
+        MergeEntity::Publishers => merge_publishers(&pool, keep, &duplicate_ids).await?,
+        MergeEntity::Series => merge_series(&pool, keep, &duplicate_ids).await?,
+        MergeEntity::Tags => merge_tags(&pool, keep, &duplicate_ids).await?,
+        MergeEntity::Roles => merge_roles(&pool, keep, &duplicate_ids).await?,
+        MergeEntity::Contents => merge_contents(&pool, keep, &duplicate_ids).await?,
+    };
+
+    println!(
+        "✓ Uniti {:?} in {} (ID {}): {} libri, {} contenuti aggiornati",
+        stats.merged_ids,
+        entity.as_str(),
+        stats.primary_id,
+        stats.books_updated,
+        stats.contents_updated
+    );
+    println!(
+        "   Unione #{}: 'ritmo dedupe undo {}' per annullarla",
+        stats.merge_id, stats.merge_id
+    );
+
+    if !stats.affected_book_ids.is_empty() {
+        mark_books_for_sync(&pool, &stats.affected_book_ids, "manual_merge").await?;
+        println!(
+            "📝 {} libri da sincronizzare: esegui 'ritmo sync-metadata'",
+            stats.affected_book_ids.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Command: split person - Separa da una persona i libri e i contenuti attribuiti per errore
+///
+/// I legami della persona con i libri e i contenuti indicati (e con i
+/// contenuti presenti solo in quei libri) passano alla persona `new_name`,
+/// creata se non esiste. Come le unioni, la separazione è annullabile con
+/// `ritmo dedupe undo`.
+pub async fn cmd_split_person(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    person_id: i64,
+    book_ids: Vec<i64>,
+    content_ids: Vec<i64>,
+    new_name: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let library_path = get_library_path(cli_library, app_settings)?;
+    let config = LibraryConfig::new(&library_path);
+    if !config.exists() {
+        return Err(format!("La libreria non esiste: {}", library_path.display()).into());
+    }
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+
+    let stats = split_person(&pool, person_id, &book_ids, &content_ids, &new_name).await?;
+
+    let target = if stats.target_created {
+        "nuova persona"
+    } else {
+        "persona esistente"
+    };
+    println!(
+        "✓ Spostati da ID {} a '{}' ({}, ID {}): {} legami con libri, {} con contenuti",
+        stats.source_id,
+        new_name.trim(),
+        target,
+        stats.target_id,
+        stats.books_moved,
+        stats.contents_moved
+    );
+    println!(
+        "   Separazione #{}: 'ritmo dedupe undo {}' per annullarla",
+        stats.merge_id, stats.merge_id
+    );
+
+    if !stats.affected_book_ids.is_empty() {
+        mark_books_for_sync(&pool, &stats.affected_book_ids, "person_split").await?;
+        println!(
+            "📝 {} libri da sincronizzare: esegui 'ritmo sync-metadata'",
+            stats.affected_book_ids.len()
+        );
+    }
+
+    Ok(())
+}
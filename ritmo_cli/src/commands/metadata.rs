@@ -0,0 +1,263 @@
+use crate::helpers::get_library_path;
+use ritmo_config::AppSettings;
+use ritmo_core::extractors::extract_metadata;
+use ritmo_core::providers::{
+    provider_by_name, GoogleBooksProvider, HttpClient, MetadataProvider, ProviderQuery, UreqClient,
+    PROVIDER_NAMES,
+};
+use ritmo_core::service::{enrich_import_object, lookup_metadata, LookupOptions};
+use ritmo_core::BatchImportInput;
+use ritmo_db::clear_provider_cache;
+use ritmo_db_core::LibraryConfig;
+use ritmo_errors::reporter::SilentReporter;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Variabile d'ambiente con la chiave API di Google Books (facoltativa)
+const GOOGLE_BOOKS_API_KEY_ENV: &str = "RITMO_GOOGLE_BOOKS_API_KEY";
+
+/// Opzioni comuni per la ricerca online
+pub struct OnlineArgs {
+    /// Fornitori da interrogare (vuoto: tutti)
+    pub providers: Vec<String>,
+    /// Ignora la cache
+    pub refresh: bool,
+}
+
+/// Command: extract-metadata
+///
+/// Estrae i metadati dai file (EPUB, PDF, MOBI/AZW3, FB2) e produce il JSON
+/// di `add-batch`, con i punteggi di confidenza. Senza `--output` il JSON va
+/// su stdout e i messaggi su stderr, così da poterlo passare in pipe:
+/// `ritmo extract-metadata *.fb2 | ritmo add-batch`.
+///
+/// Con `online` completa i metadati presso i fornitori online; le risposte
+/// sono in cache nel database della libreria.
+pub async fn cmd_extract_metadata(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    files: Vec<PathBuf>,
+    output: Option<PathBuf>,
+    online: Option<OnlineArgs>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut objects: BatchImportInput = Vec::new();
+    let mut failed = 0;
+
+    for file in &files {
+        match extract_metadata(file) {
+            Ok(object) => objects.push(object),
+            Err(e) => {
+                failed += 1;
+                eprintln!("✗ {}: {}", file.display(), e);
+            }
+        }
+    }
+
+    if let Some(online) = online {
+        let providers = build_providers(&online.providers)?;
+        let config = library_config(cli_library, app_settings)?;
+        let mut reporter = SilentReporter;
+        let pool = config.create_pool(&mut reporter).await?;
+        let options = LookupOptions {
+            refresh: online.refresh,
+            ..Default::default()
+        };
+        for object in &mut objects {
+            let (lookup, changed) =
+                enrich_import_object(&pool, &providers, object, &options).await?;
+            for (provider, error) in &lookup.failures {
+                eprintln!("⚠ {} ({}): {}", object.file_path, provider, error);
+            }
+            if changed.is_empty() {
+                eprintln!("• {}: nessun campo aggiunto", object.file_path);
+            } else {
+                eprintln!("✓ {}: {}", object.file_path, changed.join(", "));
+            }
+        }
+    }
+
+    let json = serde_json::to_string_pretty(&objects)?;
+    if let Some(output_path) = output {
+        std::fs::write(&output_path, json)?;
+        println!(
+            "✓ Metadati estratti da {} file su {}: {}",
+            objects.len(),
+            files.len(),
+            output_path.display()
+        );
+    } else {
+        println!("{}", json);
+        if failed > 0 {
+            eprintln!("⚠ {} file non elaborati", failed);
+        }
+    }
+
+    Ok(())
+}
+
+/// Command: lookup
+///
+/// Cerca un libro presso i fornitori online, per ISBN oppure per titolo e
+/// autore, e mostra i risultati di ciascun fornitore con la loro confidenza.
+pub async fn cmd_lookup(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    isbn: Option<String>,
+    title: Option<String>,
+    author: Option<String>,
+    online: OnlineArgs,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let query = match (isbn, title) {
+        (Some(isbn), _) => ProviderQuery::isbn(&isbn)?,
+        (None, Some(title)) => ProviderQuery::title(&title, author.as_deref()),
+        (None, None) => return Err("Indicare --isbn oppure --title".into()),
+    };
+    let providers = build_providers(&online.providers)?;
+    let config = library_config(cli_library, app_settings)?;
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+    let options = LookupOptions {
+        refresh: online.refresh,
+        ..Default::default()
+    };
+
+    let lookup = lookup_metadata(&pool, &providers, &query, &options).await?;
+
+    if json {
+        let results: Vec<serde_json::Value> = lookup
+            .matches
+            .iter()
+            .map(|m| {
+                serde_json::json!({
+                    "confidence": (m.confidence as f64 * 100.0).round() / 100.0,
+                    "cached": lookup.from_cache.contains(&m.record.provider),
+                    "record": m.record,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&results)?);
+    } else {
+        for m in &lookup.matches {
+            let record = &m.record;
+            let cached = if lookup.from_cache.contains(&record.provider) {
+                " [cache]"
+            } else {
+                ""
+            };
+            println!(
+                "📚 {} (confidenza {:.2}){}",
+                record.provider, m.confidence, cached
+            );
+            print_field("Titolo", record.title.as_deref());
+            if !record.authors.is_empty() {
+                print_field("Autori", Some(&record.authors.join(", ")));
+            }
+            print_field("Editore", record.publisher.as_deref());
+            print_field("Anno", record.year.map(|y| y.to_string()).as_deref());
+            print_field("ISBN", record.isbn.as_deref());
+            print_field("Pagine", record.pages.map(|p| p.to_string()).as_deref());
+            if let Some(series) = &record.series {
+                let index = record
+                    .series_index
+                    .map(|i| format!(" #{}", i))
+                    .unwrap_or_default();
+                print_field("Serie", Some(&format!("{}{}", series, index)));
+            }
+            if !record.languages.is_empty() {
+                print_field("Lingue", Some(&record.languages.join(", ")));
+            }
+            if !record.subjects.is_empty() {
+                print_field("Soggetti", Some(&record.subjects.join(", ")));
+            }
+            print_field("URL", record.url.as_deref());
+            println!();
+        }
+        if lookup.matches.is_empty() {
+            println!("Nessun risultato per '{}'", query.cache_key());
+        }
+    }
+
+    for (provider, error) in &lookup.failures {
+        eprintln!("⚠ {}: {}", provider, error);
+    }
+    Ok(())
+}
+
+/// Command: lookup-cache-clear - Svuota la cache delle ricerche online
+pub async fn cmd_lookup_cache_clear(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    provider: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(name) = &provider {
+        check_provider_name(name)?;
+    }
+    let config = library_config(cli_library, app_settings)?;
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+    let removed = clear_provider_cache(&pool, provider.as_deref()).await?;
+    println!("✓ Risposte eliminate dalla cache: {}", removed);
+    Ok(())
+}
+
+fn print_field(label: &str, value: Option<&str>) {
+    if let Some(value) = value {
+        println!("  {:<9} {}", format!("{}:", label), value);
+    }
+}
+
+fn check_provider_name(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if PROVIDER_NAMES.contains(&name) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Fornitore sconosciuto '{}' (disponibili: {})",
+            name,
+            PROVIDER_NAMES.join(", ")
+        )
+        .into())
+    }
+}
+
+/// Crea i fornitori richiesti (tutti se la lista è vuota), nell'ordine indicato
+fn build_providers(
+    names: &[String],
+) -> Result<Vec<Arc<dyn MetadataProvider>>, Box<dyn std::error::Error>> {
+    let client: Arc<dyn HttpClient> = Arc::new(UreqClient::new());
+    let names: Vec<&str> = if names.is_empty() {
+        PROVIDER_NAMES.to_vec()
+    } else {
+        names.iter().map(String::as_str).collect()
+    };
+
+    let mut providers = Vec::new();
+    for name in names {
+        check_provider_name(name)?;
+        let provider: Arc<dyn MetadataProvider> = if name == "googlebooks" {
+            let api_key = std::env::var(GOOGLE_BOOKS_API_KEY_ENV).ok();
+            Arc::new(GoogleBooksProvider::new(client.clone()).with_api_key(api_key))
+        } else {
+            provider_by_name(name, client.clone()).ok_or("Fornitore non disponibile")?
+        };
+        providers.push(provider);
+    }
+    Ok(providers)
+}
+
+/// Il database della libreria ospita la cache delle ricerche online
+fn library_config(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+) -> Result<LibraryConfig, Box<dyn std::error::Error>> {
+    let library_path = get_library_path(cli_library, app_settings)?;
+    let config = LibraryConfig::new(&library_path);
+    if !config.exists() {
+        return Err(format!(
+            "La libreria non esiste: {} (serve per la cache delle ricerche online)",
+            library_path.display()
+        )
+        .into());
+    }
+    Ok(config)
+}
@@ -0,0 +1,285 @@
+//! Comparison and reconciliation of file (OPF) and database metadata
+
+use crate::helpers::get_library_path;
+use ritmo_config::AppSettings;
+use ritmo_core::service::{
+    books_with_epub, diff_book_metadata, pull_book_metadata, BookMetadataDiff, ChangeOrigin,
+    FieldDiff, Resolution, DIFF_FIELDS, LIST_FIELDS,
+};
+use ritmo_db_core::LibraryConfig;
+use ritmo_errors::reporter::SilentReporter;
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
+
+/// Scelte per campo indicate da riga di comando
+pub struct PullChoices {
+    pub take_file: Vec<String>,
+    pub keep_db: Vec<String>,
+    pub merge: Vec<String>,
+}
+
+/// Command: metadata diff
+///
+/// Confronta campo per campo i metadati del database con l'OPF del file EPUB.
+/// Con `all` confronta tutti i libri che hanno un EPUB e mostra solo quelli
+/// diversi.
+pub async fn cmd_metadata_diff(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    book_id: Option<i64>,
+    all: bool,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let library_path = get_library_path(cli_library, app_settings)?;
+    let config = LibraryConfig::new(&library_path);
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+
+    let book_ids = match (book_id, all) {
+        (Some(id), false) => vec![id],
+        (None, true) => books_with_epub(&pool).await?,
+        _ => return Err("Indicare l'ID di un libro oppure --all".into()),
+    };
+
+    let mut diffs = Vec::new();
+    let mut failed = 0;
+    for id in &book_ids {
+        match diff_book_metadata(&config, &pool, *id).await {
+            Ok(diff) => diffs.push(diff),
+            // Con un solo libro l'errore è l'esito del comando
+            Err(e) if !all => return Err(e.into()),
+            Err(e) => {
+                failed += 1;
+                eprintln!("✗ [{}] {}", id, e);
+            }
+        }
+    }
+
+    if json {
+        let differing: Vec<&BookMetadataDiff> =
+            diffs.iter().filter(|d| !all || !d.is_empty()).collect();
+        println!("{}", serde_json::to_string_pretty(&differing)?);
+        return Ok(());
+    }
+
+    let mut differing = 0;
+    for diff in &diffs {
+        if diff.is_empty() {
+            if !all {
+                println!(
+                    "✓ [{}] {}: file e database coincidono",
+                    diff.book_id, diff.title
+                );
+            }
+            continue;
+        }
+        differing += 1;
+        print_diff(diff);
+    }
+
+    if all {
+        println!(
+            "\n📊 Libri confrontati: {}, con differenze: {}{}",
+            diffs.len(),
+            differing,
+            if failed > 0 {
+                format!(", non leggibili: {}", failed)
+            } else {
+                String::new()
+            }
+        );
+    }
+    if differing > 0 {
+        println!("Usa 'ritmo metadata pull <ID>' per riportare nel database i valori del file");
+    }
+    Ok(())
+}
+
+/// Command: metadata pull
+///
+/// Risolve le differenze tra file e database campo per campo: mantiene il
+/// database, prende il valore del file o unisce le liste (persone, tag,
+/// lingue). I campi senza scelta esplicita usano la scelta proposta dal
+/// confronto con l'ultima sincronizzazione; sul terminale, senza `--yes`,
+/// viene chiesto campo per campo.
+pub async fn cmd_metadata_pull(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    book_id: i64,
+    choices: PullChoices,
+    dry_run: bool,
+    yes: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut resolutions = HashMap::new();
+    for (fields, resolution) in [
+        (&choices.keep_db, Resolution::KeepDb),
+        (&choices.take_file, Resolution::TakeFile),
+        (&choices.merge, Resolution::Merge),
+    ] {
+        for field in fields {
+            check_field(field, resolution)?;
+            if resolutions.insert(field.clone(), resolution).is_some() {
+                return Err(format!("Campo '{}' indicato più volte", field).into());
+            }
+        }
+    }
+
+    let library_path = get_library_path(cli_library, app_settings)?;
+    let config = LibraryConfig::new(&library_path);
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+
+    let diff = diff_book_metadata(&config, &pool, book_id).await?;
+    if diff.is_empty() {
+        println!(
+            "✓ [{}] {}: file e database coincidono",
+            diff.book_id, diff.title
+        );
+        return Ok(());
+    }
+    print_diff(&diff);
+
+    let interactive = !yes && !dry_run && std::io::stdin().is_terminal();
+    for field_diff in &diff.fields {
+        if resolutions.contains_key(field_diff.field) {
+            continue;
+        }
+        let resolution = if interactive {
+            ask_resolution(field_diff)?
+        } else {
+            field_diff.suggested.unwrap_or(Resolution::KeepDb)
+        };
+        resolutions.insert(field_diff.field.to_string(), resolution);
+    }
+
+    println!("\nScelte:");
+    for field_diff in &diff.fields {
+        let resolution = resolutions[field_diff.field];
+        let conflict = if field_diff.suggested.is_none() && resolution == Resolution::KeepDb {
+            " (conflitto: resta il valore del database)"
+        } else {
+            ""
+        };
+        println!(
+            "  {:<13} {}{}",
+            field_diff.field,
+            resolution_label(resolution),
+            conflict
+        );
+    }
+
+    if dry_run {
+        println!("\n(dry-run: nessuna modifica salvata)");
+        return Ok(());
+    }
+    if !yes && !interactive {
+        return Err("Conferma richiesta: usa --yes per applicare le scelte senza chiedere".into());
+    }
+
+    let result = pull_book_metadata(&pool, &diff, &resolutions).await?;
+    for warning in &result.warnings {
+        eprintln!("⚠ {}", warning);
+    }
+    if result.updated.is_empty() {
+        println!("\n✓ Database invariato");
+    } else {
+        println!(
+            "\n✓ Campi aggiornati nel database: {}",
+            result.updated.join(", ")
+        );
+    }
+    if result.marked_for_sync {
+        println!(
+            "📝 Il file verrà riscritto con i valori del database: esegui 'ritmo sync-metadata'"
+        );
+    } else {
+        println!("✓ File e database ora coincidono");
+    }
+    Ok(())
+}
+
+fn print_diff(diff: &BookMetadataDiff) {
+    println!("\n📖 [{}] {}", diff.book_id, diff.title);
+    println!("   File: {}", diff.file_path.display());
+    match diff.synced_at {
+        Some(ts) => println!(
+            "   Ultima sincronizzazione: {}",
+            chrono::DateTime::from_timestamp(ts, 0)
+                .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| ts.to_string())
+        ),
+        None => println!("   Mai sincronizzato: confronto senza base comune"),
+    }
+    for field_diff in &diff.fields {
+        let origin = match field_diff.origin {
+            ChangeOrigin::File => "modificato nel file",
+            ChangeOrigin::Database => "modificato nel database",
+            ChangeOrigin::Both => "modificato da entrambe le parti",
+            ChangeOrigin::Unknown => "diverso",
+        };
+        let suggested = field_diff
+            .suggested
+            .map(|r| format!(", proposta: {}", resolution_label(r)))
+            .unwrap_or_default();
+        println!("  • {} ({}{})", field_diff.field, origin, suggested);
+        println!("      database: {}", field_diff.db);
+        println!("      file:     {}", field_diff.file);
+    }
+}
+
+fn ask_resolution(field_diff: &FieldDiff) -> Result<Resolution, Box<dyn std::error::Error>> {
+    let is_list = LIST_FIELDS.contains(&field_diff.field);
+    let options = if is_list {
+        "[d]atabase / [f]ile / [m]erge"
+    } else {
+        "[d]atabase / [f]ile"
+    };
+    let default = field_diff.suggested.unwrap_or(Resolution::KeepDb);
+    loop {
+        print!(
+            "{}: {} (invio: {}) ",
+            field_diff.field,
+            options,
+            resolution_label(default)
+        );
+        std::io::stdout().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        match answer.trim().to_lowercase().as_str() {
+            "" => return Ok(default),
+            "d" | "db" | "database" => return Ok(Resolution::KeepDb),
+            "f" | "file" => return Ok(Resolution::TakeFile),
+            "m" | "merge" if is_list => return Ok(Resolution::Merge),
+            _ => println!("Scelta non valida"),
+        }
+    }
+}
+
+fn check_field(field: &str, resolution: Resolution) -> Result<(), Box<dyn std::error::Error>> {
+    if !DIFF_FIELDS.contains(&field) {
+        return Err(format!(
+            "Campo sconosciuto '{}' (disponibili: {})",
+            field,
+            DIFF_FIELDS.join(", ")
+        )
+        .into());
+    }
+    if resolution == Resolution::Merge && !LIST_FIELDS.contains(&field) {
+        return Err(format!(
+            "--merge vale solo per {} (non per '{}')",
+            LIST_FIELDS.join(", "),
+            field
+        )
+        .into());
+    }
+    Ok(())
+}
+
+fn resolution_label(resolution: Resolution) -> &'static str {
+    match resolution {
+        Resolution::KeepDb => "mantieni database",
+        Resolution::TakeFile => "prendi dal file",
+        Resolution::Merge => "unisci",
+    }
+}
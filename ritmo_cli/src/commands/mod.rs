@@ -5,28 +5,56 @@
 
 // Command modules
 pub mod books;
+pub mod bulk_edit;
 pub mod cleanup;
 pub mod contents;
 pub mod deduplication;
+pub mod duplicate_books;
 pub mod init;
+pub mod isbn;
 pub mod language;
 pub mod libraries;
+pub mod merge;
+pub mod metadata;
+pub mod metadata_diff;
 pub mod presets;
+pub mod shelves;
 pub mod sync;
+pub mod validate;
+pub mod vocabulary;
 
 // Re-export command functions for convenience
-pub use books::{cmd_add, cmd_add_batch, cmd_delete_book, cmd_list_books, cmd_update_book};
+pub use books::{
+    cmd_add, cmd_add_batch, cmd_attach_file, cmd_delete_book, cmd_delete_file, cmd_list_books,
+    cmd_list_files, cmd_update_book,
+};
+pub use bulk_edit::{cmd_bulk_edit, BookSelectionArgs, BulkEditArgs};
 pub use cleanup::cmd_cleanup;
 pub use contents::{
     cmd_add_content, cmd_delete_content, cmd_link_content, cmd_list_contents,
     cmd_unlink_content, cmd_update_content,
 };
 pub use deduplication::{
-    cmd_deduplicate_all, cmd_deduplicate_people, cmd_deduplicate_publishers,
-    cmd_deduplicate_roles, cmd_deduplicate_series, cmd_deduplicate_tags,
+    cmd_dedupe_history, cmd_dedupe_undo, cmd_deduplicate_all, cmd_deduplicate_contents, cmd_deduplicate_people,
+    cmd_deduplicate_publishers, cmd_deduplicate_roles, cmd_deduplicate_series,
+    cmd_deduplicate_tags, DeduplicateArgs,
 };
+pub use duplicate_books::{cmd_find_duplicate_books, FindDuplicateBooksArgs};
 pub use init::cmd_init;
+pub use isbn::cmd_isbn_check;
 pub use language::{cmd_get_language, cmd_set_language};
 pub use libraries::{cmd_info, cmd_list_libraries, cmd_set_library};
-pub use presets::{cmd_delete_preset, cmd_list_presets, cmd_save_preset, cmd_set_default_filter};
+pub use merge::{cmd_merge, cmd_split_person, MergeArgs};
+pub use metadata::{cmd_extract_metadata, cmd_lookup, cmd_lookup_cache_clear, OnlineArgs};
+pub use metadata_diff::{cmd_metadata_diff, cmd_metadata_pull, PullChoices};
+pub use presets::{
+    cmd_delete_preset, cmd_list_presets, cmd_preset_demote, cmd_preset_export, cmd_preset_import,
+    cmd_preset_promote, cmd_save_preset, cmd_set_default_columns, cmd_set_default_filter,
+};
+pub use shelves::{
+    cmd_shelf_add, cmd_shelf_create, cmd_shelf_delete, cmd_shelf_export, cmd_shelf_list,
+    cmd_shelf_remove, cmd_shelf_show, ShelfFilterOptions,
+};
 pub use sync::{cmd_sync_dry_run, cmd_sync_metadata, cmd_sync_status};
+pub use validate::cmd_validate;
+pub use vocabulary::cmd_vocabulary_check;
@@ -1,8 +1,16 @@
 //! Preset management commands
 
-use crate::helpers::{get_library_path, parse_date_to_timestamp};
-use ritmo_config::{AppSettings, BookFilterPreset, ContentFilterPreset, NamedPreset, PresetType};
-use ritmo_db_core::LibraryConfig;
+use crate::formatter::parse_columns;
+use crate::helpers::{
+    check_filters, filter_errors, get_library_path, parse_date_to_timestamp, parse_filter_query,
+    AttributeFilterArgs,
+};
+use ritmo_config::{
+    insert_with_policy, AppSettings, BookFilterPreset, ConflictPolicy, ContentFilterPreset,
+    MergeOutcome, NamedPreset, PresetBundle, PresetType,
+};
+use ritmo_db_core::filters::{validate_book_filters, validate_content_filters};
+use ritmo_db_core::{BookFilters, ContentFilters, LibraryConfig, QueryTarget};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
@@ -28,9 +36,40 @@ fn format_book_filters(filters: &BookFilterPreset) -> String {
     if let Some(i) = &filters.isbn {
         parts.push(format!("isbn={}", i));
     }
+    if !filters.publisher_countries.is_empty() {
+        parts.push(format!("paese_editore={}", filters.publisher_countries.join("|")));
+    }
     if let Some(s) = &filters.search {
         parts.push(format!("ricerca={}", s));
     }
+    if let Some(q) = &filters.query {
+        parts.push(format!("query={}", q));
+    }
+    if !filters.tags.is_empty() {
+        let separator = if filters.all_tags { "+" } else { "|" };
+        parts.push(format!("tag={}", filters.tags.join(separator)));
+    }
+    if !filters.languages.is_empty() {
+        parts.push(format!("lingua={}", filters.languages.join("|")));
+    }
+    if let Some(r) = &filters.language_role {
+        parts.push(format!("ruolo_lingua={}", r));
+    }
+    for p in &filters.people {
+        parts.push(format!("persona={}", p));
+    }
+    if !filters.has.is_empty() {
+        parts.push(format!("presenti={}", filters.has.join(",")));
+    }
+    if !filters.missing.is_empty() {
+        parts.push(format!("mancanti={}", filters.missing.join(",")));
+    }
+    if let Some(p) = filters.pages_min {
+        parts.push(format!("pagine>={}", p));
+    }
+    if let Some(p) = filters.pages_max {
+        parts.push(format!("pagine<={}", p));
+    }
     parts.push(format!("ordina={}", filters.sort));
     if let Some(l) = filters.limit {
         parts.push(format!("limite={}", l));
@@ -55,6 +94,34 @@ fn format_content_filters(filters: &ContentFilterPreset) -> String {
     if let Some(s) = &filters.search {
         parts.push(format!("ricerca={}", s));
     }
+    if let Some(q) = &filters.query {
+        parts.push(format!("query={}", q));
+    }
+    if !filters.tags.is_empty() {
+        let separator = if filters.all_tags { "+" } else { "|" };
+        parts.push(format!("tag={}", filters.tags.join(separator)));
+    }
+    if !filters.languages.is_empty() {
+        parts.push(format!("lingua={}", filters.languages.join("|")));
+    }
+    if let Some(r) = &filters.language_role {
+        parts.push(format!("ruolo_lingua={}", r));
+    }
+    for p in &filters.people {
+        parts.push(format!("persona={}", p));
+    }
+    if !filters.has.is_empty() {
+        parts.push(format!("presenti={}", filters.has.join(",")));
+    }
+    if !filters.missing.is_empty() {
+        parts.push(format!("mancanti={}", filters.missing.join(",")));
+    }
+    if let Some(p) = filters.pages_min {
+        parts.push(format!("pagine>={}", p));
+    }
+    if let Some(p) = filters.pages_max {
+        parts.push(format!("pagine<={}", p));
+    }
     parts.push(format!("ordina={}", filters.sort));
     if let Some(l) = filters.limit {
         parts.push(format!("limite={}", l));
@@ -128,9 +195,11 @@ pub fn cmd_save_preset(
     year: Option<i32>,
     isbn: Option<String>,
     search: Option<String>,
+    query: Option<String>,
     acquired_after: Option<String>,
     acquired_before: Option<String>,
     content_type: Option<String>,
+    attributes: AttributeFilterArgs,
     sort: String,
     limit: Option<i64>,
     offset: i64,
@@ -142,6 +211,29 @@ pub fn cmd_save_preset(
         )
     })?;
 
+    // La query viene salvata come testo, ma solo se è valida per il tipo di preset
+    if let Some(q) = &query {
+        let target = match preset_type_enum {
+            PresetType::Books => QueryTarget::Books,
+            PresetType::Contents => QueryTarget::Contents,
+        };
+        parse_filter_query(q, target)?;
+    }
+
+    // Anche i filtri su tag, lingue, persone e presenza vengono verificati prima del salvataggio
+    match preset_type_enum {
+        PresetType::Books => {
+            let mut filters = BookFilters::default();
+            attributes.apply_to_book_filters(&mut filters)?;
+            check_filters(validate_book_filters(&filters))?;
+        }
+        PresetType::Contents => {
+            let mut filters = ContentFilters::default();
+            attributes.apply_to_content_filters(&mut filters)?;
+            check_filters(validate_content_filters(&filters))?;
+        }
+    }
+
     // Converti date da stringa a timestamp se presenti
     let acquired_after_ts = if let Some(date_str) = &acquired_after {
         Some(parse_date_to_timestamp(date_str)?)
@@ -168,7 +260,7 @@ pub fn cmd_save_preset(
 
         match preset_type_enum {
             PresetType::Books => {
-                let filters = BookFilterPreset {
+                let mut filters = BookFilterPreset {
                     author,
                     publisher,
                     series,
@@ -176,12 +268,15 @@ pub fn cmd_save_preset(
                     year,
                     isbn,
                     search,
+                    query,
                     acquired_after: acquired_after_ts,
                     acquired_before: acquired_before_ts,
                     sort,
                     limit,
                     offset,
+                    ..Default::default()
                 };
+                attributes.clone().fill_book_preset(&mut filters);
 
                 let preset = NamedPreset {
                     name: name.clone(),
@@ -195,15 +290,18 @@ pub fn cmd_save_preset(
                 println!("✓ Preset '{}' salvato nella libreria per libri", name);
             }
             PresetType::Contents => {
-                let filters = ContentFilterPreset {
+                let mut filters = ContentFilterPreset {
                     author,
                     content_type,
                     year,
                     search,
+                    query,
                     sort,
                     limit,
                     offset,
+                    ..Default::default()
                 };
+                attributes.clone().fill_content_preset(&mut filters);
 
                 let preset = NamedPreset {
                     name: name.clone(),
@@ -221,7 +319,7 @@ pub fn cmd_save_preset(
         // Salva nei preset globali
         match preset_type_enum {
             PresetType::Books => {
-                let filters = BookFilterPreset {
+                let mut filters = BookFilterPreset {
                     author,
                     publisher,
                     series,
@@ -229,12 +327,15 @@ pub fn cmd_save_preset(
                     year,
                     isbn,
                     search,
+                    query,
                     acquired_after: acquired_after_ts,
                     acquired_before: acquired_before_ts,
                     sort,
                     limit,
                     offset,
+                    ..Default::default()
                 };
+                attributes.clone().fill_book_preset(&mut filters);
 
                 let preset = NamedPreset {
                     name: name.clone(),
@@ -248,15 +349,18 @@ pub fn cmd_save_preset(
                 println!("✓ Preset '{}' salvato globalmente per libri", name);
             }
             PresetType::Contents => {
-                let filters = ContentFilterPreset {
+                let mut filters = ContentFilterPreset {
                     author,
                     content_type,
                     year,
                     search,
+                    query,
                     sort,
                     limit,
                     offset,
+                    ..Default::default()
                 };
+                attributes.clone().fill_content_preset(&mut filters);
 
                 let preset = NamedPreset {
                     name: name.clone(),
@@ -459,3 +563,346 @@ pub fn cmd_set_default_filter(
 
     Ok(())
 }
+
+/// Comando: set-default-columns - Imposta le colonne di default per una libreria
+pub fn cmd_set_default_columns(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    preset_type: String,
+    columns: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let preset_type_enum = PresetType::from_str(&preset_type).ok_or_else(|| {
+        format!(
+            "Tipo preset non valido: '{}'. Usa 'books' o 'contents'",
+            preset_type
+        )
+    })?;
+    let target = match preset_type_enum {
+        PresetType::Books => QueryTarget::Books,
+        PresetType::Contents => QueryTarget::Contents,
+    };
+
+    let columns = if columns.to_lowercase() == "none" {
+        Vec::new()
+    } else {
+        parse_columns(&columns, target)?
+    };
+
+    let config = existing_library(cli_library, app_settings)?;
+    let mut library_presets = config.load_library_presets()?;
+
+    match preset_type_enum {
+        PresetType::Books => library_presets.set_default_books_columns(columns.clone()),
+        PresetType::Contents => library_presets.set_default_contents_columns(columns.clone()),
+    }
+    config.save_library_presets(&library_presets)?;
+
+    if columns.is_empty() {
+        println!(
+            "✓ Colonne di default ripristinate per {}",
+            preset_type_enum.as_str()
+        );
+    } else {
+        println!(
+            "✓ Colonne di default per {}: {}",
+            preset_type_enum.as_str(),
+            columns.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Helper: configurazione della libreria corrente, che deve esistere
+fn existing_library(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+) -> Result<LibraryConfig, Box<dyn std::error::Error>> {
+    let library_path = get_library_path(cli_library, app_settings)?;
+    let config = LibraryConfig::new(&library_path);
+    if !config.exists() {
+        return Err(format!("La libreria non esiste: {}", library_path.display()).into());
+    }
+    Ok(config)
+}
+
+/// Helper: tipo di preset opzionale (books o contents)
+fn parse_preset_type(
+    preset_type: Option<String>,
+) -> Result<Option<PresetType>, Box<dyn std::error::Error>> {
+    preset_type
+        .map(|t| {
+            PresetType::from_str(&t).ok_or_else(|| {
+                format!("Tipo preset non valido: '{}'. Usa 'books' o 'contents'", t).into()
+            })
+        })
+        .transpose()
+}
+
+/// Helper: politica sui conflitti di nome
+fn parse_conflict_policy(on_conflict: &str) -> Result<ConflictPolicy, Box<dyn std::error::Error>> {
+    Ok(on_conflict.parse::<ConflictPolicy>()?)
+}
+
+/// Helper: estrae da una collezione il preset con il nome indicato
+///
+/// Se il nome esiste sia tra i preset libri che tra quelli contenuti serve `preset_type`.
+fn select_preset(
+    books: &HashMap<String, NamedPreset<BookFilterPreset>>,
+    contents: &HashMap<String, NamedPreset<ContentFilterPreset>>,
+    name: &str,
+    preset_type: Option<PresetType>,
+) -> Result<PresetBundle, Box<dyn std::error::Error>> {
+    let book = books
+        .get(name)
+        .filter(|_| preset_type != Some(PresetType::Contents));
+    let content = contents
+        .get(name)
+        .filter(|_| preset_type != Some(PresetType::Books));
+
+    match (book, content) {
+        (Some(_), Some(_)) => Err(format!(
+            "'{}' esiste sia tra i preset libri che tra quelli contenuti: indica --type",
+            name
+        )
+        .into()),
+        (Some(preset), None) => Ok(PresetBundle::from_book_preset(preset.clone())),
+        (None, Some(preset)) => Ok(PresetBundle::from_content_preset(preset.clone())),
+        (None, None) => Err(format!("Preset '{}' non trovato", name).into()),
+    }
+}
+
+/// Helper: separa i preset validi da quelli con filtri non validi
+///
+/// Restituisce i preset validi e, per ogni preset scartato, nome e motivo.
+fn validate_bundle(bundle: PresetBundle) -> (PresetBundle, Vec<(String, String)>) {
+    let mut valid = PresetBundle::new();
+    let mut rejected = Vec::new();
+
+    for (name, preset) in bundle.books {
+        let result = BookFilters::from_preset(&preset.filters)
+            .and_then(|filters| validate_book_filters(&filters));
+        match result {
+            Ok(()) => valid.add_book_preset(preset),
+            Err(errors) => rejected.push((name, filter_errors(errors).to_string())),
+        }
+    }
+    for (name, preset) in bundle.contents {
+        let result = ContentFilters::from_preset(&preset.filters)
+            .and_then(|filters| validate_content_filters(&filters));
+        match result {
+            Ok(()) => valid.add_content_preset(preset),
+            Err(errors) => rejected.push((name, filter_errors(errors).to_string())),
+        }
+    }
+
+    rejected.sort();
+    (valid, rejected)
+}
+
+/// Helper: inserisce i preset del bundle nelle collezioni di destinazione
+fn merge_bundle(
+    bundle: PresetBundle,
+    books: &mut HashMap<String, NamedPreset<BookFilterPreset>>,
+    contents: &mut HashMap<String, NamedPreset<ContentFilterPreset>>,
+    policy: ConflictPolicy,
+) -> Vec<MergeOutcome> {
+    let mut book_presets: Vec<_> = bundle.books.into_values().collect();
+    book_presets.sort_by(|a, b| a.name.cmp(&b.name));
+    let mut content_presets: Vec<_> = bundle.contents.into_values().collect();
+    content_presets.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut outcomes = Vec::new();
+    for preset in book_presets {
+        outcomes.push(insert_with_policy(books, preset, policy));
+    }
+    for preset in content_presets {
+        outcomes.push(insert_with_policy(contents, preset, policy));
+    }
+    outcomes
+}
+
+/// Helper: valida e inserisce un bundle nei preset globali o della libreria
+fn store_bundle(
+    cli_library: &Option<PathBuf>,
+    app_settings: &mut AppSettings,
+    settings_path: &PathBuf,
+    bundle: PresetBundle,
+    in_library: bool,
+    policy: ConflictPolicy,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (valid, rejected) = validate_bundle(bundle);
+    for (name, reason) in &rejected {
+        eprintln!("✗ Preset '{}' rifiutato: {}", name, reason);
+    }
+    if valid.is_empty() {
+        return Err("Nessun preset valido da importare".into());
+    }
+
+    let (outcomes, scope) = if in_library {
+        let config = existing_library(cli_library, app_settings)?;
+        let mut library_presets = config.load_library_presets()?;
+        let outcomes = merge_bundle(
+            valid,
+            &mut library_presets.books,
+            &mut library_presets.contents,
+            policy,
+        );
+        config.save_library_presets(&library_presets)?;
+        (outcomes, "nella libreria")
+    } else {
+        let outcomes = merge_bundle(
+            valid,
+            &mut app_settings.presets.books,
+            &mut app_settings.presets.contents,
+            policy,
+        );
+        app_settings.save(settings_path)?;
+        (outcomes, "globalmente")
+    };
+
+    for outcome in &outcomes {
+        let symbol = match outcome {
+            MergeOutcome::Skipped(_) => "-",
+            _ => "✓",
+        };
+        println!("{} Preset {} ({})", symbol, outcome, scope);
+    }
+
+    if !rejected.is_empty() {
+        return Err(format!("{} preset rifiutati perché non validi", rejected.len()).into());
+    }
+    Ok(())
+}
+
+/// Comando: preset export - Esporta uno o tutti i preset in formato TOML su stdout
+pub fn cmd_preset_export(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    name: Option<String>,
+    all: bool,
+    preset_type: Option<String>,
+    in_library: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let preset_type = parse_preset_type(preset_type)?;
+
+    let source = if in_library {
+        let library_presets = existing_library(cli_library, app_settings)?.load_library_presets()?;
+        PresetBundle {
+            books: library_presets.books,
+            contents: library_presets.contents,
+        }
+    } else {
+        app_settings.presets.clone()
+    };
+
+    let mut bundle = match name {
+        Some(name) => select_preset(&source.books, &source.contents, &name, preset_type)?,
+        None if all => source,
+        None => return Err("Indica il nome di un preset oppure --all".into()),
+    };
+
+    match preset_type {
+        Some(PresetType::Books) => bundle.contents.clear(),
+        Some(PresetType::Contents) => bundle.books.clear(),
+        None => {}
+    }
+
+    if bundle.is_empty() {
+        return Err("Nessun preset da esportare".into());
+    }
+
+    print!("{}", bundle.to_toml()?);
+    Ok(())
+}
+
+/// Comando: preset import - Importa i preset da un file TOML
+pub fn cmd_preset_import(
+    cli_library: &Option<PathBuf>,
+    app_settings: &mut AppSettings,
+    settings_path: &PathBuf,
+    file: PathBuf,
+    in_library: bool,
+    on_conflict: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let policy = parse_conflict_policy(&on_conflict)?;
+
+    let content = std::fs::read_to_string(&file)
+        .map_err(|e| format!("Impossibile leggere {}: {}", file.display(), e))?;
+    let bundle = PresetBundle::from_toml(&content)
+        .map_err(|e| format!("File di preset non valido {}: {}", file.display(), e))?;
+
+    if bundle.is_empty() {
+        return Err(format!("Nessun preset trovato in {}", file.display()).into());
+    }
+
+    store_bundle(
+        cli_library,
+        app_settings,
+        settings_path,
+        bundle,
+        in_library,
+        policy,
+    )
+}
+
+/// Comando: preset promote - Copia un preset della libreria tra i preset globali
+pub fn cmd_preset_promote(
+    cli_library: &Option<PathBuf>,
+    app_settings: &mut AppSettings,
+    settings_path: &PathBuf,
+    name: String,
+    preset_type: Option<String>,
+    on_conflict: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let preset_type = parse_preset_type(preset_type)?;
+    let policy = parse_conflict_policy(&on_conflict)?;
+
+    let library_presets = existing_library(cli_library, app_settings)?.load_library_presets()?;
+    let bundle = select_preset(
+        &library_presets.books,
+        &library_presets.contents,
+        &name,
+        preset_type,
+    )
+    .map_err(|e| format!("{} nella libreria", e))?;
+
+    store_bundle(
+        cli_library,
+        app_settings,
+        settings_path,
+        bundle,
+        false,
+        policy,
+    )
+}
+
+/// Comando: preset demote - Copia un preset globale nella libreria corrente
+pub fn cmd_preset_demote(
+    cli_library: &Option<PathBuf>,
+    app_settings: &mut AppSettings,
+    settings_path: &PathBuf,
+    name: String,
+    preset_type: Option<String>,
+    on_conflict: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let preset_type = parse_preset_type(preset_type)?;
+    let policy = parse_conflict_policy(&on_conflict)?;
+
+    let bundle = select_preset(
+        &app_settings.presets.books,
+        &app_settings.presets.contents,
+        &name,
+        preset_type,
+    )
+    .map_err(|e| format!("{} tra i preset globali", e))?;
+
+    store_bundle(
+        cli_library,
+        app_settings,
+        settings_path,
+        bundle,
+        true,
+        policy,
+    )
+}
@@ -0,0 +1,454 @@
+//! Shelf commands (smart and manual collections)
+
+use crate::formatter::{format_books, OutputFormat};
+use crate::helpers::{
+    get_library_path, library_columns, parse_date_to_timestamp, resolve_book_preset,
+    AttributeFilterArgs, OutputArgs,
+};
+use clap::Args;
+use ritmo_config::{AppSettings, BookFilterPreset};
+use ritmo_core::service::{
+    add_books_to_shelf, create_manual_shelf, create_smart_shelf, delete_shelf, get_shelf,
+    list_shelves, remove_books_from_shelf, shelf_books, ShelfEntry,
+};
+use ritmo_db::Shelf;
+use ritmo_db_core::{BookResult, LibraryConfig, QueryTarget};
+use ritmo_errors::reporter::SilentReporter;
+use std::path::PathBuf;
+
+/// Filtri per la creazione di uno scaffale smart (stessi nomi di list-books)
+#[derive(Args, Debug, Default)]
+pub struct ShelfFilterOptions {
+    /// Parte dai filtri di un preset libri salvato
+    #[arg(long)]
+    pub from_preset: Option<String>,
+
+    #[arg(long)]
+    pub author: Option<String>,
+
+    #[arg(long)]
+    pub publisher: Option<String>,
+
+    #[arg(long)]
+    pub series: Option<String>,
+
+    #[arg(long)]
+    pub format: Option<String>,
+
+    #[arg(long)]
+    pub year: Option<i32>,
+
+    #[arg(long)]
+    pub isbn: Option<String>,
+
+    #[arg(long)]
+    pub search: Option<String>,
+
+    /// Query booleana (es. "author:king AND NOT tag:read")
+    #[arg(long, short = 'q')]
+    pub query: Option<String>,
+
+    #[arg(long)]
+    pub acquired_after: Option<String>,
+
+    #[arg(long)]
+    pub acquired_before: Option<String>,
+
+    #[command(flatten)]
+    pub attributes: AttributeFilterArgs,
+
+    #[arg(long)]
+    pub sort: Option<String>,
+
+    #[arg(long)]
+    pub limit: Option<i64>,
+}
+
+impl ShelfFilterOptions {
+    /// Nessun filtro indicato: lo scaffale sarà manuale
+    fn is_empty(&self) -> bool {
+        self.from_preset.is_none()
+            && self.author.is_none()
+            && self.publisher.is_none()
+            && self.series.is_none()
+            && self.format.is_none()
+            && self.year.is_none()
+            && self.isbn.is_none()
+            && self.search.is_none()
+            && self.query.is_none()
+            && self.acquired_after.is_none()
+            && self.acquired_before.is_none()
+            && self.attributes.is_empty()
+            && self.sort.is_none()
+            && self.limit.is_none()
+    }
+
+    /// Preset di partenza (da --from-preset) con i filtri indicati sovrapposti
+    fn into_preset(
+        self,
+        config: &LibraryConfig,
+        app_settings: &AppSettings,
+    ) -> Result<BookFilterPreset, Box<dyn std::error::Error>> {
+        let mut preset = match &self.from_preset {
+            Some(name) => resolve_book_preset(config, app_settings, name)?,
+            None => BookFilterPreset::default(),
+        };
+
+        preset.author = self.author.or(preset.author);
+        preset.publisher = self.publisher.or(preset.publisher);
+        preset.series = self.series.or(preset.series);
+        preset.format = self.format.or(preset.format);
+        preset.year = self.year.or(preset.year);
+        preset.isbn = self.isbn.or(preset.isbn);
+        preset.search = self.search.or(preset.search);
+        preset.query = self.query.or(preset.query);
+        if let Some(date) = &self.acquired_after {
+            preset.acquired_after = Some(parse_date_to_timestamp(date)?);
+        }
+        if let Some(date) = &self.acquired_before {
+            preset.acquired_before = Some(parse_date_to_timestamp(date)?);
+        }
+        if let Some(sort) = self.sort {
+            preset.sort = sort;
+        }
+        preset.limit = self.limit.or(preset.limit);
+        // La paginazione non ha senso per uno scaffale
+        preset.offset = 0;
+        self.attributes.fill_book_preset(&mut preset);
+
+        Ok(preset)
+    }
+}
+
+/// Comando: shelf create - Crea uno scaffale (smart se sono indicati filtri, altrimenti manuale)
+pub async fn cmd_shelf_create(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    name: String,
+    description: Option<String>,
+    filters: ShelfFilterOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let library_path = get_library_path(cli_library, app_settings)?;
+    let config = LibraryConfig::new(&library_path);
+    if !config.exists() {
+        return Err(format!("La libreria non esiste: {}", library_path.display()).into());
+    }
+
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+
+    if filters.is_empty() {
+        let id = create_manual_shelf(&pool, &name, description).await?;
+        println!("✓ Scaffale manuale '{}' creato (ID: {})", name, id);
+        println!(
+            "  Aggiungi libri con: ritmo shelf add \"{}\" <ID_LIBRO>...",
+            name
+        );
+    } else {
+        let preset = filters.into_preset(&config, app_settings)?;
+        let id = create_smart_shelf(&pool, &name, description, &preset).await?;
+        println!("✓ Scaffale smart '{}' creato (ID: {})", name, id);
+    }
+
+    Ok(())
+}
+
+/// Comando: shelf add - Aggiunge libri a uno scaffale manuale
+pub async fn cmd_shelf_add(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    name: String,
+    book_ids: Vec<i64>,
+    position: Option<i64>,
+    note: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let library_path = get_library_path(cli_library, app_settings)?;
+    let config = LibraryConfig::new(&library_path);
+    if !config.exists() {
+        return Err(format!("La libreria non esiste: {}", library_path.display()).into());
+    }
+
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+
+    let positions = add_books_to_shelf(&pool, &name, &book_ids, position, note.as_deref()).await?;
+    for (book_id, position) in book_ids.iter().zip(positions) {
+        println!(
+            "✓ Libro {} aggiunto a '{}' in posizione {}",
+            book_id, name, position
+        );
+    }
+
+    Ok(())
+}
+
+/// Comando: shelf remove - Rimuove libri da uno scaffale manuale
+pub async fn cmd_shelf_remove(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    name: String,
+    book_ids: Vec<i64>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let library_path = get_library_path(cli_library, app_settings)?;
+    let config = LibraryConfig::new(&library_path);
+    if !config.exists() {
+        return Err(format!("La libreria non esiste: {}", library_path.display()).into());
+    }
+
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+
+    let removed = remove_books_from_shelf(&pool, &name, &book_ids).await?;
+    println!("✓ {} libri rimossi da '{}'", removed, name);
+    if (removed as usize) < book_ids.len() {
+        println!("  (alcuni libri indicati non erano nello scaffale)");
+    }
+
+    Ok(())
+}
+
+/// Comando: shelf list - Lista gli scaffali con il numero di libri
+pub async fn cmd_shelf_list(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let library_path = get_library_path(cli_library, app_settings)?;
+    let config = LibraryConfig::new(&library_path);
+    if !config.exists() {
+        return Err(format!("La libreria non esiste: {}", library_path.display()).into());
+    }
+
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+
+    let summaries = list_shelves(&pool).await?;
+    if summaries.is_empty() {
+        println!("Nessuno scaffale. Creane uno con: ritmo shelf create <NOME>");
+        return Ok(());
+    }
+
+    println!("{:<30} {:<8} {:>6}", "Scaffale", "Tipo", "Libri");
+    println!("{}", "-".repeat(46));
+    for summary in &summaries {
+        println!(
+            "{:<30} {:<8} {:>6}",
+            summary.shelf.name, summary.shelf.kind, summary.book_count
+        );
+        if let Some(desc) = &summary.shelf.description {
+            println!("  {}", desc);
+        }
+    }
+
+    Ok(())
+}
+
+/// Comando: shelf show - Mostra i libri di uno scaffale
+pub async fn cmd_shelf_show(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    name: String,
+    output: OutputArgs,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let library_path = get_library_path(cli_library, app_settings)?;
+    let config = LibraryConfig::new(&library_path);
+    if !config.exists() {
+        return Err(format!("La libreria non esiste: {}", library_path.display()).into());
+    }
+
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+
+    let shelf = get_shelf(&pool, &name).await?;
+    let entries = shelf_books(&pool, &shelf).await?;
+    let explicit_layout = output.columns.is_some() || output.template.is_some();
+    let output_options =
+        output.to_options(QueryTarget::Books, &library_columns(&config, QueryTarget::Books))?;
+
+    if matches!(output_options.format, OutputFormat::Json) && !explicit_layout {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&shelf_json(&shelf, &entries)?)?
+        );
+        return Ok(());
+    }
+
+    // Template e formati per altri programmi: solo i libri, nell'ordine dello scaffale
+    if output_options.template.is_some()
+        || !matches!(
+            output_options.format,
+            OutputFormat::Table | OutputFormat::Simple
+        )
+    {
+        let books: Vec<BookResult> = entries.into_iter().map(|e| e.book).collect();
+        println!("{}", format_books(&books, &output_options));
+        return Ok(());
+    }
+
+    println!("Scaffale: {} ({})", shelf.name, shelf.kind);
+    if let Some(desc) = &shelf.description {
+        println!("Descrizione: {}", desc);
+    }
+    if let Some(filter) = &shelf.filter_json {
+        println!("Filtro: {}", filter);
+    }
+    println!();
+
+    if shelf.is_smart() || explicit_layout {
+        let books: Vec<BookResult> = entries.into_iter().map(|e| e.book).collect();
+        println!("{}", format_books(&books, &output_options));
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("Lo scaffale è vuoto.");
+        return Ok(());
+    }
+    for entry in &entries {
+        println!(
+            "{:>3}. [{}] {}",
+            entry.position.unwrap_or_default(),
+            entry.book.id,
+            entry.book.name
+        );
+        if let Some(notes) = &entry.notes {
+            println!("     Nota: {}", notes);
+        }
+    }
+    println!("\nTotale: {} libri", entries.len());
+
+    Ok(())
+}
+
+/// Comando: shelf export - Esporta uno scaffale in JSON o CSV
+pub async fn cmd_shelf_export(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    name: String,
+    format: String,
+    file: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let library_path = get_library_path(cli_library, app_settings)?;
+    let config = LibraryConfig::new(&library_path);
+    if !config.exists() {
+        return Err(format!("La libreria non esiste: {}", library_path.display()).into());
+    }
+
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+
+    let shelf = get_shelf(&pool, &name).await?;
+    let entries = shelf_books(&pool, &shelf).await?;
+
+    let content = match format.to_lowercase().as_str() {
+        "json" => serde_json::to_string_pretty(&shelf_json(&shelf, &entries)?)?,
+        "csv" => shelf_csv(&entries),
+        other => {
+            return Err(format!("Formato di export non valido: '{}'. Usa json o csv", other).into())
+        }
+    };
+
+    match file {
+        Some(path) => {
+            std::fs::write(&path, content)?;
+            println!(
+                "✓ Scaffale '{}' esportato in {} ({} libri)",
+                shelf.name,
+                path.display(),
+                entries.len()
+            );
+        }
+        None => println!("{}", content),
+    }
+
+    Ok(())
+}
+
+/// Comando: shelf delete - Elimina uno scaffale (i libri restano nella libreria)
+pub async fn cmd_shelf_delete(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    name: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let library_path = get_library_path(cli_library, app_settings)?;
+    let config = LibraryConfig::new(&library_path);
+    if !config.exists() {
+        return Err(format!("La libreria non esiste: {}", library_path.display()).into());
+    }
+
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+
+    delete_shelf(&pool, &name).await?;
+    println!("✓ Scaffale '{}' eliminato", name);
+
+    Ok(())
+}
+
+/// Rappresentazione JSON di uno scaffale e dei suoi libri
+fn shelf_json(
+    shelf: &Shelf,
+    entries: &[ShelfEntry],
+) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    let filter = match &shelf.filter_json {
+        Some(json) => serde_json::from_str(json)?,
+        None => serde_json::Value::Null,
+    };
+
+    let books = entries
+        .iter()
+        .map(|entry| {
+            let mut book = serde_json::to_value(&entry.book)?;
+            if let serde_json::Value::Object(map) = &mut book {
+                if let Some(position) = entry.position {
+                    map.insert("position".to_string(), position.into());
+                }
+                if let Some(notes) = &entry.notes {
+                    map.insert("notes".to_string(), notes.clone().into());
+                }
+            }
+            Ok(book)
+        })
+        .collect::<Result<Vec<_>, serde_json::Error>>()?;
+
+    Ok(serde_json::json!({
+        "name": shelf.name,
+        "description": shelf.description,
+        "kind": shelf.kind,
+        "filter": filter,
+        "books": books,
+    }))
+}
+
+fn shelf_csv(entries: &[ShelfEntry]) -> String {
+    let mut output = String::from("position,id,title,publisher,format,year,isbn,notes\n");
+    for entry in entries {
+        let year = entry
+            .book
+            .formatted_publication_date()
+            .and_then(|d| d.split('-').next().map(String::from))
+            .unwrap_or_default();
+        let fields = [
+            entry.position.map(|p| p.to_string()).unwrap_or_default(),
+            entry.book.id.to_string(),
+            entry.book.name.clone(),
+            entry.book.publisher_name.clone().unwrap_or_default(),
+            entry.book.format_key.clone().unwrap_or_default(),
+            year,
+            entry.book.isbn.clone().unwrap_or_default(),
+            entry.notes.clone().unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        output.push_str(&row.join(","));
+        output.push('\n');
+    }
+    output
+}
+
+/// Campo CSV con virgolette quando contiene separatori, virgolette o a capo
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -23,7 +23,7 @@ pub async fn cmd_sync_status(
         println!("✓ No books pending metadata sync");
     } else {
         println!("📊 Books pending metadata sync: {}", count);
-        println!("\nRun 'ritmo sync-metadata' to sync EPUB files with database metadata");
+        println!("\nRun 'ritmo sync-metadata' to sync EPUB/PDF/CBZ files with database metadata");
         println!("Run 'ritmo sync-metadata --dry-run' to preview changes");
     }
 
@@ -101,17 +101,21 @@ pub async fn cmd_sync_metadata(
         match sync_book_metadata(&config, &pool, *book_id).await {
             Ok(result) => {
                 println!("✓");
-                println!("  Old hash: {}", &result.old_hash[..16]);
-                println!("  New hash: {}", &result.new_hash[..16]);
-                if result.old_hash != result.new_hash {
+                for file in &result.files {
+                    println!("  Old hash: {}", &file.old_hash[..16]);
+                    println!("  New hash: {}", &file.new_hash[..16]);
+                    if file.old_hash != file.new_hash {
+                        println!(
+                            "  Moved: {} → {}",
+                            file.old_path.file_name().unwrap().to_string_lossy(),
+                            file.new_path.file_name().unwrap().to_string_lossy()
+                        );
+                    }
+                }
+                for skipped in &result.skipped {
                     println!(
-                        "  Moved: {} → {}",
-                        result
-                            .old_path
-                            .file_name()
-                            .unwrap()
-                            .to_string_lossy(),
-                        result.new_path.file_name().unwrap().to_string_lossy()
+                        "  Skipped (format not writable): {}",
+                        skipped.file_name().unwrap().to_string_lossy()
                     );
                 }
                 success_count += 1;
@@ -0,0 +1,77 @@
+//! EPUB validation command
+
+use crate::helpers::get_library_path;
+use ritmo_config::AppSettings;
+use ritmo_core::epub_validator::{validate_epub, ValidationReport};
+use ritmo_core::service::validate_library_epubs;
+use ritmo_db_core::LibraryConfig;
+use ritmo_errors::reporter::SilentReporter;
+use std::path::{Path, PathBuf};
+
+/// Comando: validate - Verifica la struttura degli EPUB
+///
+/// Valida i file indicati oppure, con `--book-id` o `--all`, gli EPUB della libreria.
+/// Termina con errore se almeno un file non è valido.
+pub async fn cmd_validate(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    files: Vec<PathBuf>,
+    book_id: Option<i64>,
+    all: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut checked = 0;
+    let mut invalid = 0;
+
+    for file in &files {
+        let report = validate_epub(file)?;
+        print_report(file, None, &report);
+        checked += 1;
+        if !report.is_valid() {
+            invalid += 1;
+        }
+    }
+
+    if book_id.is_some() || all {
+        let library_path = get_library_path(cli_library, app_settings)?;
+        let config = LibraryConfig::new(&library_path);
+        if !config.exists() {
+            return Err(format!("La libreria non esiste: {}", library_path.display()).into());
+        }
+        let mut reporter = SilentReporter;
+        let pool = config.create_pool(&mut reporter).await?;
+
+        for result in validate_library_epubs(&config, &pool, book_id).await? {
+            let label = format!("[{}] {}", result.book_id, result.book_name);
+            print_report(&result.path, Some(&label), &result.report);
+            checked += 1;
+            if !result.report.is_valid() {
+                invalid += 1;
+            }
+        }
+    }
+
+    if checked == 0 {
+        println!("Nessun EPUB da validare");
+        return Ok(());
+    }
+
+    println!("\n📊 EPUB validati: {}", checked);
+    println!("  ✓ Validi: {}", checked - invalid);
+    println!("  ✗ Non validi: {}", invalid);
+
+    if invalid > 0 {
+        return Err(format!("{} EPUB non validi", invalid).into());
+    }
+    Ok(())
+}
+
+fn print_report(path: &Path, label: Option<&str>, report: &ValidationReport) {
+    let symbol = if report.is_valid() { "✓" } else { "✗" };
+    match label {
+        Some(label) => println!("{} {} ({})", symbol, label, path.display()),
+        None => println!("{} {}", symbol, path.display()),
+    }
+    for issue in &report.issues {
+        println!("    {}", issue);
+    }
+}
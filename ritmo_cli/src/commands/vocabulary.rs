@@ -0,0 +1,140 @@
+//! Language and format vocabulary check command
+
+use crate::helpers::get_library_path;
+use ritmo_config::AppSettings;
+use ritmo_core::service::{
+    check_library_vocabularies, normalize_library_vocabularies, VariantGroup, VocabularyRow,
+};
+use ritmo_db::i18n_utils::get_locale;
+use ritmo_db::mark_books_for_sync;
+use ritmo_db_core::vocabulary::{lookup_format, lookup_language};
+use ritmo_db_core::LibraryConfig;
+use ritmo_errors::reporter::SilentReporter;
+use std::path::PathBuf;
+
+/// Comando: vocabulary-check - Verifica lingue e formati rispetto ai vocabolari
+///
+/// Con dei valori sulla riga di comando mostra la lingua ISO 639 e il formato
+/// a cui vengono ricondotti; altrimenti elenca le varianti presenti nella
+/// libreria ("ita", "Italian", "EPUB", "epub3"...). Con `--fix` le unisce
+/// sulle righe canoniche.
+pub async fn cmd_vocabulary_check(
+    cli_library: &Option<PathBuf>,
+    app_settings: &AppSettings,
+    values: Vec<String>,
+    fix: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !values.is_empty() {
+        return check_values(&values);
+    }
+
+    let library_path = get_library_path(cli_library, app_settings)?;
+    let config = LibraryConfig::new(&library_path);
+    if !config.exists() {
+        return Err(format!("La libreria non esiste: {}", library_path.display()).into());
+    }
+    let mut reporter = SilentReporter;
+    let pool = config.create_pool(&mut reporter).await?;
+
+    if fix {
+        let stats = normalize_library_vocabularies(&pool).await?;
+        println!(
+            "✓ Lingue: {} varianti unite, {} righe normalizzate",
+            stats.languages_merged, stats.languages_updated
+        );
+        println!(
+            "✓ Formati: {} varianti unite, {} righe normalizzate",
+            stats.formats_merged, stats.formats_updated
+        );
+        if !stats.affected_book_ids.is_empty() {
+            mark_books_for_sync(&pool, &stats.affected_book_ids, "vocabulary_merge").await?;
+            println!(
+                "📝 {} libri da sincronizzare: esegui 'ritmo sync-metadata'",
+                stats.affected_book_ids.len()
+            );
+        }
+        println!();
+    }
+
+    let report = check_library_vocabularies(&pool).await?;
+
+    print_groups("Lingue da normalizzare", &report.languages);
+    print_groups("Formati da normalizzare", &report.formats);
+    print_unknown("Lingue non riconosciute", &report.unknown_languages);
+    print_unknown("Formati non riconosciuti", &report.unknown_formats);
+
+    println!("📊 Vocabolari:");
+    println!("  • Lingue da normalizzare: {}", report.languages.len());
+    println!("  • Formati da normalizzare: {}", report.formats.len());
+    println!(
+        "  ? Non riconosciuti: {} lingue, {} formati",
+        report.unknown_languages.len(),
+        report.unknown_formats.len()
+    );
+    if report.has_variants() {
+        println!("  Esegui 'ritmo vocabulary-check --fix' per unire le varianti");
+    } else if !fix {
+        println!("\n✓ Lingue e formati già nella forma canonica");
+    }
+
+    Ok(())
+}
+
+fn print_groups(title: &str, groups: &[VariantGroup]) {
+    if groups.is_empty() {
+        return;
+    }
+    println!("⚠ {}:", title);
+    for group in groups {
+        println!("  → {}", group.canonical);
+        for row in &group.rows {
+            println!("    [{}] {} ({} usi)", row.id, row.value, row.usage);
+        }
+    }
+    println!();
+}
+
+fn print_unknown(title: &str, rows: &[VocabularyRow]) {
+    if rows.is_empty() {
+        return;
+    }
+    println!("? {}:", title);
+    for row in rows {
+        println!("  [{}] {} ({} usi)", row.id, row.value, row.usage);
+    }
+    println!();
+}
+
+/// Mostra a quale lingua e a quale formato viene ricondotto ogni valore
+fn check_values(values: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let locale = get_locale();
+    let mut unknown = 0;
+    for value in values {
+        let language = lookup_language(value);
+        let format = lookup_format(value);
+        if let Some(lang) = language {
+            println!(
+                "✓ {}  lingua: {} ({}, 639-3 {})",
+                value,
+                lang.code(),
+                lang.name(&locale),
+                lang.iso639_3
+            );
+        }
+        if let Some(format) = format {
+            println!(
+                "✓ {}  formato: {} ({})",
+                value, format.key, format.mime_type
+            );
+        }
+        if language.is_none() && format.is_none() {
+            println!("✗ {}  né lingua né formato riconosciuti", value);
+            unknown += 1;
+        }
+    }
+
+    if unknown > 0 {
+        return Err(format!("{} valori non riconosciuti", unknown).into());
+    }
+    Ok(())
+}
@@ -1,5 +1,6 @@
-use ritmo_db_core::{BookResult, ContentResult};
+use ritmo_db_core::{BookResult, ContentResult, QueryTarget};
 use serde_json;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 /// Formato di output
 #[derive(Debug, Clone)]
@@ -7,6 +8,9 @@ pub enum OutputFormat {
     Table,
     Json,
     Simple,
+    Csv,
+    Tsv,
+    Markdown,
 }
 
 impl OutputFormat {
@@ -14,68 +18,500 @@ impl OutputFormat {
         match s.to_lowercase().as_str() {
             "json" => Self::Json,
             "simple" => Self::Simple,
+            "csv" => Self::Csv,
+            "tsv" => Self::Tsv,
+            "markdown" | "md" => Self::Markdown,
             _ => Self::Table,
         }
     }
 }
 
+/// Colonne disponibili per i libri: (chiave, intestazione)
+pub const BOOK_COLUMNS: &[(&str, &str)] = &[
+    ("id", "ID"),
+    ("title", "Titolo"),
+    ("original_title", "Titolo originale"),
+    ("authors", "Autori"),
+    ("publisher", "Editore"),
+    ("series", "Serie"),
+    ("series_index", "N."),
+    ("year", "Anno"),
+    ("date", "Data"),
+    ("format", "Formato"),
+    ("isbn", "ISBN"),
+    ("pages", "Pagine"),
+    ("tags", "Tag"),
+    ("size", "Dimensione"),
+    ("added", "Aggiunto"),
+    ("file", "File"),
+];
+
+/// Colonne disponibili per i contenuti: (chiave, intestazione)
+pub const CONTENT_COLUMNS: &[(&str, &str)] = &[
+    ("id", "ID"),
+    ("title", "Titolo"),
+    ("original_title", "Titolo originale"),
+    ("authors", "Autori"),
+    ("type", "Tipo"),
+    ("year", "Anno"),
+    ("date", "Data"),
+    ("pages", "Pagine"),
+    ("tags", "Tag"),
+    ("added", "Aggiunto"),
+];
+
+const DEFAULT_BOOK_COLUMNS: &[&str] = &["id", "title", "publisher", "format", "year"];
+const DEFAULT_CONTENT_COLUMNS: &[&str] = &["id", "title", "type", "year"];
+
+fn available_columns(target: QueryTarget) -> &'static [(&'static str, &'static str)] {
+    match target {
+        QueryTarget::Books => BOOK_COLUMNS,
+        QueryTarget::Contents => CONTENT_COLUMNS,
+    }
+}
+
+fn column_header(target: QueryTarget, key: &str) -> &'static str {
+    available_columns(target)
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, header)| *header)
+        .unwrap_or("")
+}
+
+/// Interpreta una lista di colonne separate da virgola (es. "title,authors,year")
+pub fn parse_columns(spec: &str, target: QueryTarget) -> Result<Vec<String>, String> {
+    let available = available_columns(target);
+    let mut columns = Vec::new();
+
+    for key in spec.split(',').map(|c| c.trim().to_lowercase()) {
+        if key.is_empty() {
+            continue;
+        }
+        if !available.iter().any(|(k, _)| *k == key) {
+            let keys: Vec<&str> = available.iter().map(|(k, _)| *k).collect();
+            return Err(format!(
+                "Colonna sconosciuta: '{}'. Disponibili: {}",
+                key,
+                keys.join(", ")
+            ));
+        }
+        columns.push(key);
+    }
+
+    if columns.is_empty() {
+        return Err("Nessuna colonna indicata".to_string());
+    }
+    Ok(columns)
+}
+
+/// Un risultato da cui estrarre il valore di una colonna
+trait Row {
+    /// Valore della colonna; `raw` evita le forme "umane" (es. dimensione in byte)
+    fn field(&self, key: &str, raw: bool) -> Option<String>;
+}
+
+fn year_of(date: Option<String>) -> Option<String> {
+    date.and_then(|d| d.split('-').next().map(String::from))
+}
+
+fn date_of(timestamp: i64) -> String {
+    chrono::DateTime::<chrono::Utc>::from_timestamp(timestamp, 0)
+        .map(|dt| dt.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+impl Row for BookResult {
+    fn field(&self, key: &str, raw: bool) -> Option<String> {
+        match key {
+            "id" => Some(self.id.to_string()),
+            "title" => Some(self.name.clone()),
+            "original_title" => self.original_title.clone(),
+            "authors" => self.authors.clone(),
+            "publisher" => self.publisher_name.clone(),
+            "series" => self.series_name.clone(),
+            "series_index" => self.series_index.map(|i| i.to_string()),
+            "year" => year_of(self.formatted_publication_date()),
+            "date" => self.formatted_publication_date(),
+            "format" => self.format_key.clone(),
+            "isbn" => self.isbn.clone(),
+            "pages" => self.pages.map(|p| p.to_string()),
+            "tags" => self.tags.clone(),
+            "size" if raw => self.file_size.map(|s| s.to_string()),
+            "size" => self.file_size.map(human_size),
+            "added" => Some(date_of(self.created_at)),
+            "file" => self.file_link.clone(),
+            _ => None,
+        }
+    }
+}
+
+impl Row for ContentResult {
+    fn field(&self, key: &str, _raw: bool) -> Option<String> {
+        match key {
+            "id" => Some(self.id.to_string()),
+            "title" => Some(self.name.clone()),
+            "original_title" => self.original_title.clone(),
+            "authors" => self.authors.clone(),
+            "type" => self.type_key.clone(),
+            "year" => year_of(self.formatted_publication_date()),
+            "date" => self.formatted_publication_date(),
+            "pages" => self.pages.map(|p| p.to_string()),
+            "tags" => self.tags.clone(),
+            "added" => Some(date_of(self.created_at)),
+            _ => None,
+        }
+    }
+}
+
+/// Dimensione leggibile (1.2 MB)
+fn human_size(bytes: i64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Template di riga: testo libero con segnaposto `{colonna}` o `{colonna|alternativa}`
+///
+/// `{{` e `}}` producono parentesi graffe letterali.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    parts: Vec<TemplatePart>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TemplatePart {
+    Text(String),
+    Field {
+        key: String,
+        fallback: Option<String>,
+    },
+}
+
+impl Template {
+    pub fn parse(template: &str, target: QueryTarget) -> Result<Self, String> {
+        let available = available_columns(target);
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => placeholder.push(c),
+                            None => {
+                                return Err(format!(
+                                    "Segnaposto non chiuso nel template: '{{{}'",
+                                    placeholder
+                                ))
+                            }
+                        }
+                    }
+                    let (key, fallback) = match placeholder.split_once('|') {
+                        Some((key, fallback)) => (key, Some(fallback.to_string())),
+                        None => (placeholder.as_str(), None),
+                    };
+                    let key = key.trim().to_lowercase();
+                    if !available.iter().any(|(k, _)| *k == key) {
+                        return Err(format!("Campo sconosciuto nel template: '{}'", key));
+                    }
+                    if !text.is_empty() {
+                        parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(TemplatePart::Field { key, fallback });
+                }
+                '}' => return Err("'}' senza '{' corrispondente nel template".to_string()),
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(TemplatePart::Text(text));
+        }
+
+        Ok(Self { parts })
+    }
+
+    fn render(&self, row: &dyn Row) -> String {
+        let mut output = String::new();
+        for part in &self.parts {
+            match part {
+                TemplatePart::Text(text) => output.push_str(text),
+                TemplatePart::Field { key, fallback } => {
+                    let value = row.field(key, false).filter(|v| !v.is_empty());
+                    if let Some(value) = value.or_else(|| fallback.clone()) {
+                        output.push_str(&value);
+                    }
+                }
+            }
+        }
+        output
+    }
+}
+
+/// Opzioni di output: formato, colonne e template
+#[derive(Debug, Clone)]
+pub struct OutputOptions {
+    pub format: OutputFormat,
+    /// Colonne da mostrare; `None` usa le colonne standard (e il JSON completo)
+    pub columns: Option<Vec<String>>,
+    /// Se presente sostituisce il formato: una riga per risultato
+    pub template: Option<Template>,
+}
+
+impl OutputOptions {
+    pub fn new(format: OutputFormat) -> Self {
+        Self {
+            format,
+            columns: None,
+            template: None,
+        }
+    }
+}
+
 /// Formatta i risultati dei libri
-pub fn format_books(books: &[BookResult], format: &OutputFormat) -> String {
-    match format {
-        OutputFormat::Json => format_books_json(books),
-        OutputFormat::Table => format_books_table(books),
+pub fn format_books(books: &[BookResult], options: &OutputOptions) -> String {
+    if let Some(template) = &options.template {
+        return render_template(books, template);
+    }
+    match options.format {
+        OutputFormat::Json => format_json(books, options.columns.as_deref()),
         OutputFormat::Simple => format_books_simple(books),
+        _ => {
+            let columns = selected_columns(options, DEFAULT_BOOK_COLUMNS);
+            let rows: Vec<&dyn Row> = books.iter().map(|b| b as &dyn Row).collect();
+            let output = format_rows(&rows, &columns, QueryTarget::Books, &options.format);
+            match options.format {
+                OutputFormat::Table if books.is_empty() => "Nessun libro trovato.".to_string(),
+                OutputFormat::Table => format!("{}\nTotale: {} libri", output, books.len()),
+                _ => output,
+            }
+        }
     }
 }
 
 /// Formatta i risultati dei contenuti
-pub fn format_contents(contents: &[ContentResult], format: &OutputFormat) -> String {
-    match format {
-        OutputFormat::Json => format_contents_json(contents),
-        OutputFormat::Table => format_contents_table(contents),
+pub fn format_contents(contents: &[ContentResult], options: &OutputOptions) -> String {
+    if let Some(template) = &options.template {
+        return render_template(contents, template);
+    }
+    match options.format {
+        OutputFormat::Json => format_json(contents, options.columns.as_deref()),
         OutputFormat::Simple => format_contents_simple(contents),
+        _ => {
+            let columns = selected_columns(options, DEFAULT_CONTENT_COLUMNS);
+            let rows: Vec<&dyn Row> = contents.iter().map(|c| c as &dyn Row).collect();
+            let output = format_rows(&rows, &columns, QueryTarget::Contents, &options.format);
+            match options.format {
+                OutputFormat::Table if contents.is_empty() => {
+                    "Nessun contenuto trovato.".to_string()
+                }
+                OutputFormat::Table => {
+                    format!("{}\nTotale: {} contenuti", output, contents.len())
+                }
+                _ => output,
+            }
+        }
     }
 }
 
-fn format_books_json(books: &[BookResult]) -> String {
-    serde_json::to_string_pretty(books).unwrap_or_else(|e| format!("Errore JSON: {}", e))
+fn selected_columns(options: &OutputOptions, defaults: &[&str]) -> Vec<String> {
+    options
+        .columns
+        .clone()
+        .unwrap_or_else(|| defaults.iter().map(|c| c.to_string()).collect())
 }
 
-fn format_books_table(books: &[BookResult]) -> String {
-    if books.is_empty() {
-        return "Nessun libro trovato.".to_string();
+fn render_template<T: Row>(items: &[T], template: &Template) -> String {
+    items
+        .iter()
+        .map(|item| template.render(item))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// JSON completo, oppure ridotto alle colonne richieste
+fn format_json<T: Row + serde::Serialize>(items: &[T], columns: Option<&[String]>) -> String {
+    let result = match columns {
+        None => serde_json::to_string_pretty(items),
+        Some(columns) => {
+            let objects: Vec<serde_json::Map<String, serde_json::Value>> = items
+                .iter()
+                .map(|item| {
+                    columns
+                        .iter()
+                        .map(|key| {
+                            let value = item
+                                .field(key, true)
+                                .map(serde_json::Value::String)
+                                .unwrap_or(serde_json::Value::Null);
+                            (key.clone(), value)
+                        })
+                        .collect()
+                })
+                .collect();
+            serde_json::to_string_pretty(&objects)
+        }
+    };
+    result.unwrap_or_else(|e| format!("Errore JSON: {}", e))
+}
+
+fn format_rows(
+    rows: &[&dyn Row],
+    columns: &[String],
+    target: QueryTarget,
+    format: &OutputFormat,
+) -> String {
+    let raw = matches!(format, OutputFormat::Csv | OutputFormat::Tsv);
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            columns
+                .iter()
+                .map(|key| row.field(key, raw).unwrap_or_default())
+                .collect()
+        })
+        .collect();
+
+    match format {
+        OutputFormat::Csv => {
+            let mut lines = vec![columns.iter().map(|c| csv_escape(c)).collect::<Vec<_>>()];
+            lines.extend(
+                cells
+                    .iter()
+                    .map(|row| row.iter().map(|c| csv_escape(c)).collect()),
+            );
+            lines
+                .iter()
+                .map(|l| l.join(","))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        OutputFormat::Tsv => {
+            let mut lines = vec![columns.join("\t")];
+            lines.extend(cells.iter().map(|row| {
+                row.iter()
+                    .map(|c| tsv_escape(c))
+                    .collect::<Vec<_>>()
+                    .join("\t")
+            }));
+            lines.join("\n")
+        }
+        OutputFormat::Markdown => {
+            let headers: Vec<String> = columns
+                .iter()
+                .map(|c| markdown_escape(column_header(target, c)))
+                .collect();
+            let mut lines = vec![
+                format!("| {} |", headers.join(" | ")),
+                format!("|{}|", vec!["---"; columns.len()].join("|")),
+            ];
+            lines.extend(cells.iter().map(|row| {
+                let escaped: Vec<String> = row.iter().map(|c| markdown_escape(c)).collect();
+                format!("| {} |", escaped.join(" | "))
+            }));
+            lines.join("\n")
+        }
+        _ => {
+            let headers: Vec<String> = columns
+                .iter()
+                .map(|c| column_header(target, c).to_string())
+                .collect();
+            format_table(&headers, &cells, terminal_width())
+        }
     }
+}
 
-    let mut output = String::new();
+/// Larghezza del terminale, se l'output è un terminale o è impostato `COLUMNS`
+fn terminal_width() -> Option<usize> {
+    if let Some((terminal_size::Width(width), _)) = terminal_size::terminal_size() {
+        return Some(width as usize);
+    }
+    std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok())
+}
 
-    // Header
-    output.push_str(&format!(
-        "{:<5} {:<40} {:<20} {:<15} {:<10}\n",
-        "ID", "Titolo", "Editore", "Formato", "Anno"
-    ));
-    output.push_str(&"-".repeat(95));
-    output.push('\n');
+/// Tabella con colonne larghe quanto il contenuto, ristrette se superano `max_width`
+fn format_table(headers: &[String], rows: &[Vec<String>], max_width: Option<usize>) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.width()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.width());
+        }
+    }
 
-    // Rows
-    for book in books {
-        let title = truncate(&book.name, 38);
-        let publisher = truncate(&book.publisher_name.clone().unwrap_or_default(), 18);
-        let format = book.format_key.clone().unwrap_or_default();
-        let year = book
-            .formatted_publication_date()
-            .and_then(|d| d.split('-').next().map(String::from))
-            .unwrap_or_default();
-
-        output.push_str(&format!(
-            "{:<5} {:<40} {:<20} {:<15} {:<10}\n",
-            book.id, title, publisher, format, year
-        ));
+    if let Some(max_width) = max_width {
+        let separators = widths.len().saturating_sub(1);
+        // Restringe la colonna più larga finché la riga non entra nel terminale
+        while widths.iter().sum::<usize>() + separators > max_width {
+            let (index, widest) = widths
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, w)| **w)
+                .map(|(i, w)| (i, *w))
+                .unwrap_or((0, 0));
+            if widest <= 8 {
+                break;
+            }
+            widths[index] -= 1;
+        }
     }
 
-    output.push_str(&format!("\nTotale: {} libri", books.len()));
+    let format_line = |cells: &[String]| {
+        let line: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| pad(&truncate(cell, *width), *width))
+            .collect();
+        line.join(" ").trim_end().to_string()
+    };
+
+    let mut output = String::new();
+    output.push_str(&format_line(headers));
+    output.push('\n');
+    output.push_str(&"-".repeat(widths.iter().sum::<usize>() + widths.len().saturating_sub(1)));
+    output.push('\n');
+    for row in rows {
+        output.push_str(&format_line(row));
+        output.push('\n');
+    }
     output
 }
 
+fn csv_escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn tsv_escape(value: &str) -> String {
+    value.replace(['\t', '\n', '\r'], " ")
+}
+
+fn markdown_escape(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
 fn format_books_simple(books: &[BookResult]) -> String {
     if books.is_empty() {
         return "Nessun libro trovato.".to_string();
@@ -105,44 +541,6 @@ fn format_books_simple(books: &[BookResult]) -> String {
     output
 }
 
-fn format_contents_json(contents: &[ContentResult]) -> String {
-    serde_json::to_string_pretty(contents).unwrap_or_else(|e| format!("Errore JSON: {}", e))
-}
-
-fn format_contents_table(contents: &[ContentResult]) -> String {
-    if contents.is_empty() {
-        return "Nessun contenuto trovato.".to_string();
-    }
-
-    let mut output = String::new();
-
-    // Header
-    output.push_str(&format!(
-        "{:<5} {:<45} {:<20} {:<10}\n",
-        "ID", "Titolo", "Tipo", "Anno"
-    ));
-    output.push_str(&"-".repeat(85));
-    output.push('\n');
-
-    // Rows
-    for content in contents {
-        let title = truncate(&content.name, 43);
-        let content_type = truncate(&content.type_key.clone().unwrap_or_default(), 18);
-        let year = content
-            .formatted_publication_date()
-            .and_then(|d| d.split('-').next().map(String::from))
-            .unwrap_or_default();
-
-        output.push_str(&format!(
-            "{:<5} {:<45} {:<20} {:<10}\n",
-            content.id, title, content_type, year
-        ));
-    }
-
-    output.push_str(&format!("\nTotale: {} contenuti", contents.len()));
-    output
-}
-
 fn format_contents_simple(contents: &[ContentResult]) -> String {
     if contents.is_empty() {
         return "Nessun contenuto trovato.".to_string();
@@ -168,23 +566,63 @@ fn format_contents_simple(contents: &[ContentResult]) -> String {
     output
 }
 
-/// Tronca una stringa alla lunghezza specificata aggiungendo "..."
-fn truncate(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
-        s.to_string()
-    } else {
-        format!("{}...", &s[..max_len.saturating_sub(3)])
+/// Tronca una stringa alla larghezza (in colonne di terminale) specificata aggiungendo "..."
+fn truncate(s: &str, max_width: usize) -> String {
+    if s.width() <= max_width {
+        return s.to_string();
+    }
+
+    let budget = max_width.saturating_sub(3);
+    let mut output = String::new();
+    let mut width = 0;
+    for c in s.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if width + char_width > budget {
+            break;
+        }
+        width += char_width;
+        output.push(c);
     }
+    output.push_str(&".".repeat(max_width.min(3)));
+    output
+}
+
+/// Allinea a sinistra riempiendo fino alla larghezza indicata
+fn pad(s: &str, width: usize) -> String {
+    format!("{}{}", s, " ".repeat(width.saturating_sub(s.width())))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn book(id: i64, name: &str) -> BookResult {
+        BookResult {
+            id,
+            name: name.to_string(),
+            original_title: None,
+            publisher_name: Some("Einaudi".to_string()),
+            format_key: Some("epub".to_string()),
+            series_name: None,
+            series_index: None,
+            publication_date: Some(1262304000), // 2010-01-01
+            isbn: None,
+            pages: None,
+            file_link: None,
+            created_at: 1609459200,
+            authors: Some("Italo Calvino".to_string()),
+            tags: None,
+            file_size: Some(1536),
+        }
+    }
+
     #[test]
     fn test_truncate() {
         assert_eq!(truncate("Short", 10), "Short");
         assert_eq!(truncate("Very long string here", 10), "Very lo...");
+        // I caratteri CJK occupano due colonne
+        assert_eq!(truncate("吾輩は猫である", 8), "吾輩...");
+        assert_eq!(pad("猫", 4), "猫  ");
     }
 
     #[test]
@@ -198,5 +636,68 @@ mod tests {
             OutputFormat::from_str("simple"),
             OutputFormat::Simple
         ));
+        assert!(matches!(OutputFormat::from_str("CSV"), OutputFormat::Csv));
+        assert!(matches!(OutputFormat::from_str("tsv"), OutputFormat::Tsv));
+        assert!(matches!(
+            OutputFormat::from_str("md"),
+            OutputFormat::Markdown
+        ));
+    }
+
+    #[test]
+    fn test_parse_columns() {
+        assert_eq!(
+            parse_columns("title, Authors,year", QueryTarget::Books).unwrap(),
+            vec!["title", "authors", "year"]
+        );
+        assert!(parse_columns("title,nope", QueryTarget::Books).is_err());
+        assert!(parse_columns("series", QueryTarget::Contents).is_err());
+        assert!(parse_columns(" , ", QueryTarget::Books).is_err());
+    }
+
+    #[test]
+    fn test_template() {
+        let template =
+            Template::parse("{authors} - {title} ({year}) {{{series|n.d.}}}", QueryTarget::Books)
+                .unwrap();
+        assert_eq!(
+            template.render(&book(1, "Il barone rampante")),
+            "Italo Calvino - Il barone rampante (2010) {n.d.}"
+        );
+        assert!(Template::parse("{nope}", QueryTarget::Books).is_err());
+        assert!(Template::parse("{title", QueryTarget::Books).is_err());
+        assert!(Template::parse("title}", QueryTarget::Books).is_err());
+    }
+
+    #[test]
+    fn test_csv_tsv_markdown() {
+        let books = vec![book(1, "Uno, due \"tre\""), book(2, "A|B")];
+        let mut options = OutputOptions::new(OutputFormat::Csv);
+        options.columns = Some(vec!["id".to_string(), "title".to_string(), "size".to_string()]);
+
+        assert_eq!(
+            format_books(&books, &options),
+            "id,title,size\n1,\"Uno, due \"\"tre\"\"\",1536\n2,A|B,1536"
+        );
+
+        options.format = OutputFormat::Tsv;
+        assert!(format_books(&books, &options).starts_with("id\ttitle\tsize\n1\tUno"));
+
+        options.format = OutputFormat::Markdown;
+        let markdown = format_books(&books, &options);
+        assert!(markdown.starts_with("| ID | Titolo | Dimensione |\n|---|---|---|\n"));
+        assert!(markdown.contains("| 2 | A\\|B | 1.5 KB |"));
+    }
+
+    #[test]
+    fn test_table_fits_width() {
+        let headers = vec!["ID".to_string(), "Titolo".to_string()];
+        let rows = vec![vec!["1".to_string(), "x".repeat(50)]];
+        let table = format_table(&headers, &rows, Some(30));
+        assert!(table.lines().all(|line| line.width() <= 30));
+        assert!(table.contains("..."));
+
+        let table = format_table(&headers, &rows, None);
+        assert!(table.contains(&"x".repeat(50)));
     }
 }
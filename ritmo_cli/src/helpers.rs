@@ -1,6 +1,13 @@
 //! Helper functions used across commands
 
-use ritmo_config::{detect_portable_library, AppSettings};
+use crate::formatter::{parse_columns, OutputFormat, OutputOptions, Template};
+use clap::Args;
+use ritmo_config::{detect_portable_library, AppSettings, BookFilterPreset, ContentFilterPreset};
+use ritmo_db_core::filters::{
+    normalize_language_role, parse_query, validate_query, BookFilters, ContentFilters,
+    PersonRoleFilter, PresenceField, QueryExpr, QueryTarget, TagMatch, ValidationError,
+};
+use ritmo_db_core::LibraryConfig;
 use std::path::PathBuf;
 
 /// Helper: determina il path della libreria da usare
@@ -19,6 +26,21 @@ pub fn get_library_path(
     }
 }
 
+/// Helper: risolve un preset libri per nome (libreria > globale)
+pub fn resolve_book_preset(
+    config: &LibraryConfig,
+    app_settings: &AppSettings,
+    name: &str,
+) -> Result<BookFilterPreset, Box<dyn std::error::Error>> {
+    let library_presets = config.load_library_presets().ok();
+    library_presets
+        .as_ref()
+        .and_then(|p| p.get_book_preset(name))
+        .or_else(|| app_settings.presets.get_book_preset(name))
+        .map(|p| p.filters.clone())
+        .ok_or_else(|| format!("Preset '{}' non trovato", name).into())
+}
+
 /// Helper: converte data YYYY-MM-DD in timestamp UNIX
 pub fn parse_date_to_timestamp(date_str: &str) -> Result<i64, Box<dyn std::error::Error>> {
     use chrono::NaiveDate;
@@ -30,6 +52,346 @@ pub fn parse_date_to_timestamp(date_str: &str) -> Result<i64, Box<dyn std::error
     Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp())
 }
 
+/// Helper: analizza e valida una query booleana (`--query`) per libri o contenuti
+pub fn parse_filter_query(
+    query: &str,
+    target: QueryTarget,
+) -> Result<QueryExpr, Box<dyn std::error::Error>> {
+    let expr = parse_query(query).map_err(|e| format!("Query non valida: {}", e))?;
+
+    validate_query(&expr, target).map_err(|errors| {
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        format!("Query non valida: {}", messages.join("; "))
+    })?;
+
+    Ok(expr)
+}
+
+/// Helper: converte gli errori di validazione dei filtri in un unico messaggio
+pub fn check_filters(result: Result<(), Vec<ValidationError>>) -> Result<(), Box<dyn std::error::Error>> {
+    result.map_err(filter_errors)
+}
+
+/// Helper: messaggio unico per una lista di errori sui filtri
+pub fn filter_errors(errors: Vec<ValidationError>) -> Box<dyn std::error::Error> {
+    let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+    format!("Filtri non validi: {}", messages.join("; ")).into()
+}
+
+/// Opzioni di output condivise da list-books, list-contents e shelf show
+#[derive(Args, Debug, Clone)]
+pub struct OutputArgs {
+    /// Formato output (table, json, simple, csv, tsv, markdown)
+    #[arg(long, short = 'o', default_value = "table")]
+    pub output: String,
+
+    /// Colonne da mostrare, separate da virgola (es. title,authors,series,series_index,year,tags,size)
+    #[arg(long)]
+    pub columns: Option<String>,
+
+    /// Template di riga, es. '{authors} - {title} ({year})' (sostituisce --output)
+    #[arg(long, conflicts_with = "columns")]
+    pub template: Option<String>,
+}
+
+impl OutputArgs {
+    /// Risolve le opzioni di output
+    ///
+    /// Senza --columns si usano le colonne di default della libreria, se impostate
+    /// (non per il JSON, che senza --columns resta completo).
+    pub fn to_options(
+        &self,
+        target: QueryTarget,
+        library_columns: &[String],
+    ) -> Result<OutputOptions, Box<dyn std::error::Error>> {
+        let mut options = OutputOptions::new(OutputFormat::from_str(&self.output));
+
+        if let Some(template) = &self.template {
+            options.template = Some(Template::parse(template, target)?);
+        } else if let Some(columns) = &self.columns {
+            options.columns = Some(parse_columns(columns, target)?);
+        } else if !library_columns.is_empty() && !matches!(options.format, OutputFormat::Json) {
+            let columns = parse_columns(&library_columns.join(","), target)
+                .map_err(|e| format!("Colonne di default della libreria non valide: {}", e))?;
+            options.columns = Some(columns);
+        }
+
+        Ok(options)
+    }
+}
+
+/// Helper: colonne di default della libreria per libri o contenuti
+pub fn library_columns(config: &LibraryConfig, target: QueryTarget) -> Vec<String> {
+    config
+        .load_library_presets()
+        .map(|presets| match target {
+            QueryTarget::Books => presets.default_books_columns,
+            QueryTarget::Contents => presets.default_contents_columns,
+        })
+        .unwrap_or_default()
+}
+
+/// Filtri su tag, lingue, persone con ruolo, presenza di campi e pagine
+/// (condivisi da list-books, list-contents e save-preset)
+#[derive(Args, Debug, Clone, Default)]
+pub struct AttributeFilterArgs {
+    /// Filtra per tag (ripetibile; basta uno dei tag, tutti con --all-tags)
+    #[arg(long = "tag")]
+    pub tags: Vec<String>,
+
+    /// Richiede tutti i tag indicati con --tag
+    #[arg(long)]
+    pub all_tags: bool,
+
+    /// Filtra per lingua, codice ISO o nome (ripetibile; per i libri: lingue dei contenuti)
+    #[arg(long = "language")]
+    pub languages: Vec<String>,
+
+    /// Ruolo della lingua: original, source o actual
+    #[arg(long)]
+    pub language_role: Option<String>,
+
+    /// Persona con ruolo, formato "Nome:ruolo" (es. "Fruttero:translator", ripetibile)
+    #[arg(long = "person")]
+    pub people: Vec<String>,
+
+    /// Paese dell'editore (solo libri, ripetibile)
+    #[arg(long = "publisher-country")]
+    pub publisher_countries: Vec<String>,
+
+    /// Campi che devono essere presenti (isbn, cover, file, series, publisher, author, language, type, pages)
+    #[arg(long, value_delimiter = ',')]
+    pub has: Vec<String>,
+
+    /// Campi che devono mancare (stessi valori di --has)
+    #[arg(long, value_delimiter = ',')]
+    pub missing: Vec<String>,
+
+    /// Numero minimo di pagine
+    #[arg(long)]
+    pub pages_min: Option<i64>,
+
+    /// Numero massimo di pagine
+    #[arg(long)]
+    pub pages_max: Option<i64>,
+}
+
+impl AttributeFilterArgs {
+    /// Applica i filtri (i valori indicati sostituiscono quelli già presenti)
+    pub fn apply_to_book_filters(
+        &self,
+        filters: &mut BookFilters,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let parsed = self.parse()?;
+        if !self.tags.is_empty() {
+            filters.tags = self.tags.clone();
+        }
+        if self.all_tags {
+            filters.tag_match = TagMatch::All;
+        }
+        if !self.languages.is_empty() {
+            filters.languages = self.languages.clone();
+        }
+        if parsed.language_role.is_some() {
+            filters.language_role = parsed.language_role;
+        }
+        if !parsed.people_roles.is_empty() {
+            filters.people_roles = parsed.people_roles;
+        }
+        if !self.publisher_countries.is_empty() {
+            filters.publisher_countries = self.publisher_countries.clone();
+        }
+        if !parsed.has.is_empty() {
+            filters.has = parsed.has;
+        }
+        if !parsed.missing.is_empty() {
+            filters.missing = parsed.missing;
+        }
+        if self.pages_min.is_some() {
+            filters.pages_min = self.pages_min;
+        }
+        if self.pages_max.is_some() {
+            filters.pages_max = self.pages_max;
+        }
+        Ok(())
+    }
+
+    /// Applica i filtri ai contenuti (i valori indicati sostituiscono quelli già presenti)
+    pub fn apply_to_content_filters(
+        &self,
+        filters: &mut ContentFilters,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.publisher_countries.is_empty() {
+            return Err("--publisher-country si applica solo ai libri".into());
+        }
+        let parsed = self.parse()?;
+        if !self.tags.is_empty() {
+            filters.tags = self.tags.clone();
+        }
+        if self.all_tags {
+            filters.tag_match = TagMatch::All;
+        }
+        if !self.languages.is_empty() {
+            filters.languages = self.languages.clone();
+        }
+        if parsed.language_role.is_some() {
+            filters.language_role = parsed.language_role;
+        }
+        if !parsed.people_roles.is_empty() {
+            filters.people_roles = parsed.people_roles;
+        }
+        if !parsed.has.is_empty() {
+            filters.has = parsed.has;
+        }
+        if !parsed.missing.is_empty() {
+            filters.missing = parsed.missing;
+        }
+        if self.pages_min.is_some() {
+            filters.pages_min = self.pages_min;
+        }
+        if self.pages_max.is_some() {
+            filters.pages_max = self.pages_max;
+        }
+        Ok(())
+    }
+
+    /// Nessun filtro indicato
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+            && !self.all_tags
+            && self.languages.is_empty()
+            && self.language_role.is_none()
+            && self.people.is_empty()
+            && self.publisher_countries.is_empty()
+            && self.has.is_empty()
+            && self.missing.is_empty()
+            && self.pages_min.is_none()
+            && self.pages_max.is_none()
+    }
+
+    /// Copia i filtri indicati in un preset libri (ruolo lingua normalizzato)
+    pub fn fill_book_preset(self, preset: &mut BookFilterPreset) {
+        if !self.publisher_countries.is_empty() {
+            preset.publisher_countries = self.publisher_countries;
+        }
+        if self.language_role.is_some() {
+            preset.language_role = self
+                .language_role
+                .as_deref()
+                .and_then(normalize_language_role);
+        }
+        if !self.tags.is_empty() {
+            preset.tags = self.tags;
+        }
+        if self.all_tags {
+            preset.all_tags = true;
+        }
+        if !self.languages.is_empty() {
+            preset.languages = self.languages;
+        }
+        if !self.people.is_empty() {
+            preset.people = self.people;
+        }
+        if !self.has.is_empty() {
+            preset.has = self.has;
+        }
+        if !self.missing.is_empty() {
+            preset.missing = self.missing;
+        }
+        if self.pages_min.is_some() {
+            preset.pages_min = self.pages_min;
+        }
+        if self.pages_max.is_some() {
+            preset.pages_max = self.pages_max;
+        }
+    }
+
+    /// Copia i filtri indicati in un preset contenuti (ruolo lingua normalizzato)
+    pub fn fill_content_preset(self, preset: &mut ContentFilterPreset) {
+        if self.language_role.is_some() {
+            preset.language_role = self
+                .language_role
+                .as_deref()
+                .and_then(normalize_language_role);
+        }
+        if !self.tags.is_empty() {
+            preset.tags = self.tags;
+        }
+        if self.all_tags {
+            preset.all_tags = true;
+        }
+        if !self.languages.is_empty() {
+            preset.languages = self.languages;
+        }
+        if !self.people.is_empty() {
+            preset.people = self.people;
+        }
+        if !self.has.is_empty() {
+            preset.has = self.has;
+        }
+        if !self.missing.is_empty() {
+            preset.missing = self.missing;
+        }
+        if self.pages_min.is_some() {
+            preset.pages_min = self.pages_min;
+        }
+        if self.pages_max.is_some() {
+            preset.pages_max = self.pages_max;
+        }
+    }
+
+    /// Analizza ruolo lingua, persone e campi di presenza
+    fn parse(&self) -> Result<ParsedAttributeFilters, Box<dyn std::error::Error>> {
+        let language_role = match &self.language_role {
+            Some(role) => Some(normalize_language_role(role).ok_or_else(|| {
+                format!(
+                    "Ruolo lingua non valido: '{}'. Usa original, source o actual",
+                    role
+                )
+            })?),
+            None => None,
+        };
+
+        let people_roles = self
+            .people
+            .iter()
+            .map(|p| {
+                PersonRoleFilter::parse(p).ok_or_else(|| {
+                    format!(
+                        "Formato persona non valido: '{}'. Formato richiesto: 'Nome:ruolo'",
+                        p
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let parse_fields = |values: &[String]| {
+            values
+                .iter()
+                .map(|v| {
+                    v.trim()
+                        .parse::<PresenceField>()
+                        .map_err(|_| format!("Campo non valido per --has/--missing: '{}'", v))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        Ok(ParsedAttributeFilters {
+            language_role,
+            people_roles,
+            has: parse_fields(&self.has)?,
+            missing: parse_fields(&self.missing)?,
+        })
+    }
+}
+
+struct ParsedAttributeFilters {
+    language_role: Option<String>,
+    people_roles: Vec<PersonRoleFilter>,
+    has: Vec<PresenceField>,
+    missing: Vec<PresenceField>,
+}
+
 /// Helper: calcola timestamp di N giorni fa
 pub fn timestamp_days_ago(days: i64) -> i64 {
     use chrono::{Duration, Utc};
@@ -7,8 +7,10 @@ mod helpers;
 
 use clap::{Parser, Subcommand};
 use commands::*;
+use helpers::{AttributeFilterArgs, OutputArgs};
 use ritmo_config::{settings_file, AppSettings};
 use ritmo_db::i18n_utils;
+use ritmo_ml::merge_journal::MergeEntity;
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -82,6 +84,10 @@ enum Commands {
         #[arg(long)]
         search: Option<String>,
 
+        /// Query booleana (es. "author:king AND NOT tag:read")
+        #[arg(long)]
+        query: Option<String>,
+
         #[arg(long)]
         acquired_after: Option<String>,
 
@@ -92,6 +98,9 @@ enum Commands {
         #[arg(long)]
         content_type: Option<String>,
 
+        #[command(flatten)]
+        attributes: AttributeFilterArgs,
+
         #[arg(long, default_value = "title")]
         sort: String,
 
@@ -126,12 +135,25 @@ enum Commands {
         preset_name: String,
     },
 
+    /// Imposta le colonne di default di list-books/list-contents per la libreria corrente
+    SetDefaultColumns {
+        /// Tipo: books o contents
+        preset_type: String,
+
+        /// Colonne separate da virgola, es. title,authors,year (usa 'none' per ripristinare)
+        columns: String,
+    },
+
     /// Lista libri con filtri
     ListBooks {
         /// Usa un preset salvato
         #[arg(long, short = 'p')]
         preset: Option<String>,
 
+        /// Limita ai libri di uno scaffale (smart o manuale)
+        #[arg(long, conflicts_with = "preset")]
+        shelf: Option<String>,
+
         /// Filtra per autore
         #[arg(long)]
         author: Option<String>,
@@ -160,6 +182,11 @@ enum Commands {
         #[arg(long, short)]
         search: Option<String>,
 
+        /// Query booleana, es. "author:king AND (format:epub OR format:pdf) AND year:1980..1990"
+        /// (campi: title, author, publisher, series, format, tag, language, isbn, year, pages)
+        #[arg(long, short = 'q')]
+        query: Option<String>,
+
         /// Filtra libri acquisiti dopo questa data (YYYY-MM-DD)
         #[arg(long)]
         acquired_after: Option<String>,
@@ -180,9 +207,12 @@ enum Commands {
         #[arg(long)]
         recent_count: Option<i64>,
 
-        /// Ordina per campo (title, author, year, date_added)
-        #[arg(long, default_value = "title")]
-        sort: String,
+        #[command(flatten)]
+        attributes: AttributeFilterArgs,
+
+        /// Ordina per campo (title, author, year, date_added; default: title o quello del preset/scaffale)
+        #[arg(long)]
+        sort: Option<String>,
 
         /// Limita numero risultati
         #[arg(long)]
@@ -192,9 +222,20 @@ enum Commands {
         #[arg(long, default_value = "0")]
         offset: i64,
 
-        /// Formato output (table, json, simple)
-        #[arg(long, short = 'o', default_value = "table")]
-        output: String,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Esporta, importa e sposta preset tra librerie e configurazione globale
+    Preset {
+        #[command(subcommand)]
+        action: PresetCommands,
+    },
+
+    /// Gestisce gli scaffali (collezioni smart e manuali)
+    Shelf {
+        #[command(subcommand)]
+        action: ShelfCommands,
     },
 
     /// Lista contenuti con filtri
@@ -219,6 +260,14 @@ enum Commands {
         #[arg(long, short)]
         search: Option<String>,
 
+        /// Query booleana, es. "type:romanzo AND NOT language:en AND pages:<200"
+        /// (campi: title, author, type, tag, language, year, pages)
+        #[arg(long, short = 'q')]
+        query: Option<String>,
+
+        #[command(flatten)]
+        attributes: AttributeFilterArgs,
+
         /// Ordina per campo (title, author, year, type)
         #[arg(long, default_value = "title")]
         sort: String,
@@ -231,9 +280,8 @@ enum Commands {
         #[arg(long, default_value = "0")]
         offset: i64,
 
-        /// Formato output (table, json, simple)
-        #[arg(long, short = 'o', default_value = "table")]
-        output: String,
+        #[command(flatten)]
+        output: OutputArgs,
     },
 
     /// Importa un libro nella libreria
@@ -288,6 +336,48 @@ enum Commands {
         /// Tags (può essere specificato più volte)
         #[arg(long)]
         tags: Vec<String>,
+
+        /// Rifiuta gli EPUB con errori strutturali (default: importa con un avviso)
+        #[arg(long)]
+        strict: bool,
+    },
+
+    /// Aggiunge un file (altro formato) a un libro esistente, identificato per ID o ISBN
+    AttachFile {
+        /// Percorso del file da agganciare
+        file: PathBuf,
+
+        /// ID del libro a cui agganciare il file
+        #[arg(long, conflicts_with = "isbn", required_unless_present = "isbn")]
+        book_id: Option<i64>,
+
+        /// ISBN del libro a cui agganciare il file
+        #[arg(long)]
+        isbn: Option<String>,
+
+        /// Formato (epub, pdf, mobi, etc.) - rilevato automaticamente se omesso
+        #[arg(long, short = 'f')]
+        format: Option<String>,
+    },
+
+    /// Lista i file (formati) associati a un libro
+    ListFiles {
+        /// ID del libro
+        book_id: i64,
+    },
+
+    /// Rimuove un singolo file da un libro (il libro resta nel database)
+    DeleteFile {
+        /// ID del file (vedi list-files)
+        id: i64,
+
+        /// Elimina anche il file fisico dallo storage (default: mantiene il file)
+        #[arg(long)]
+        delete_file: bool,
+
+        /// Forza l'eliminazione anche in caso di errori filesystem
+        #[arg(long)]
+        force: bool,
     },
 
     /// Importa libri in batch da file JSON
@@ -303,6 +393,77 @@ enum Commands {
         /// Modalità dry-run: valida il JSON senza importare
         #[arg(long)]
         dry_run: bool,
+
+        /// Rifiuta gli EPUB con errori strutturali (default: importa con un avviso)
+        #[arg(long)]
+        strict: bool,
+
+        /// Rifiuta gli ISBN non validi (default: importa con un avviso)
+        #[arg(long)]
+        strict_isbn: bool,
+    },
+
+    /// Estrae i metadati da file EPUB/PDF/MOBI/AZW3/FB2/CBZ nel formato JSON di add-batch
+    ExtractMetadata {
+        /// File da analizzare
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// File JSON di output (default: stdout)
+        #[arg(long, short = 'o')]
+        output: Option<PathBuf>,
+
+        /// Completa i metadati con i fornitori online (Open Library, Google Books, Wikidata)
+        #[arg(long)]
+        online: bool,
+
+        /// Fornitore da interrogare, ripetibile (openlibrary, googlebooks, wikidata; default: tutti)
+        #[arg(long = "provider", requires = "online")]
+        providers: Vec<String>,
+
+        /// Ignora le risposte in cache e interroga di nuovo i fornitori
+        #[arg(long, requires = "online")]
+        refresh: bool,
+    },
+
+    /// Cerca un libro presso i fornitori di metadati online
+    Lookup {
+        /// ISBN-10 o ISBN-13
+        #[arg(long, conflicts_with_all = ["title", "author"])]
+        isbn: Option<String>,
+
+        /// Titolo
+        #[arg(long, required_unless_present = "isbn")]
+        title: Option<String>,
+
+        /// Autore (con --title)
+        #[arg(long)]
+        author: Option<String>,
+
+        /// Fornitore da interrogare, ripetibile (openlibrary, googlebooks, wikidata; default: tutti)
+        #[arg(long = "provider")]
+        providers: Vec<String>,
+
+        /// Ignora le risposte in cache e interroga di nuovo i fornitori
+        #[arg(long)]
+        refresh: bool,
+
+        /// Output JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Svuota la cache delle ricerche online
+    LookupCacheClear {
+        /// Solo le risposte di questo fornitore
+        #[arg(long)]
+        provider: Option<String>,
+    },
+
+    /// Confronta i metadati del database con quelli dei file EPUB e risolve le differenze
+    Metadata {
+        #[command(subcommand)]
+        action: MetadataCommands,
     },
 
     /// Aggiorna metadati di un libro esistente
@@ -330,10 +491,14 @@ enum Commands {
         #[arg(long)]
         year: Option<i32>,
 
-        /// Nuovo ISBN
+        /// Nuovo ISBN (ISBN-10 o ISBN-13; stringa vuota per cancellarlo)
         #[arg(long)]
         isbn: Option<String>,
 
+        /// Rifiuta un ISBN non valido (default: salva con un avviso)
+        #[arg(long)]
+        strict_isbn: bool,
+
         /// Nuovo formato
         #[arg(long)]
         format: Option<String>,
@@ -359,6 +524,23 @@ enum Commands {
         tags: Vec<String>,
     },
 
+    /// Modifica in blocco i libri selezionati da filtri, preset, scaffale o ID
+    BulkEdit {
+        #[command(flatten)]
+        selection: BookSelectionArgs,
+
+        #[command(flatten)]
+        edit: BulkEditArgs,
+
+        /// Mostra solo l'anteprima delle modifiche
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Applica senza chiedere conferma
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+
     /// Elimina un libro dal database (rimuove automaticamente tutte le associazioni)
     DeleteBook {
         /// ID del libro da eliminare
@@ -499,6 +681,10 @@ enum Commands {
         #[arg(long, short = 't', default_value = "0.85")]
         threshold: f64,
 
+        /// Minimum frequency for a variant pattern to count as learned
+        #[arg(long, default_value = "2")]
+        min_frequency: usize,
+
         /// Automatically merge high-confidence duplicates
         #[arg(long)]
         auto_merge: bool,
@@ -506,6 +692,10 @@ enum Commands {
         /// Show what would be merged without making changes (default: true)
         #[arg(long)]
         dry_run: bool,
+
+        /// Output groups and their evidence as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// Find and merge duplicate publishers using ML
@@ -514,6 +704,10 @@ enum Commands {
         #[arg(long, short = 't', default_value = "0.85")]
         threshold: f64,
 
+        /// Minimum frequency for a variant pattern to count as learned
+        #[arg(long, default_value = "2")]
+        min_frequency: usize,
+
         /// Automatically merge high-confidence duplicates
         #[arg(long)]
         auto_merge: bool,
@@ -521,6 +715,10 @@ enum Commands {
         /// Show what would be merged without making changes (default: true)
         #[arg(long)]
         dry_run: bool,
+
+        /// Output groups and their evidence as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// Find and merge duplicate series using ML
@@ -529,6 +727,10 @@ enum Commands {
         #[arg(long, short = 't', default_value = "0.85")]
         threshold: f64,
 
+        /// Minimum frequency for a variant pattern to count as learned
+        #[arg(long, default_value = "2")]
+        min_frequency: usize,
+
         /// Automatically merge high-confidence duplicates
         #[arg(long)]
         auto_merge: bool,
@@ -536,6 +738,10 @@ enum Commands {
         /// Show what would be merged without making changes (default: true)
         #[arg(long)]
         dry_run: bool,
+
+        /// Output groups and their evidence as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// Find and merge duplicate tags using ML
@@ -544,6 +750,10 @@ enum Commands {
         #[arg(long, short = 't', default_value = "0.85")]
         threshold: f64,
 
+        /// Minimum frequency for a variant pattern to count as learned
+        #[arg(long, default_value = "2")]
+        min_frequency: usize,
+
         /// Automatically merge high-confidence duplicates
         #[arg(long)]
         auto_merge: bool,
@@ -551,6 +761,10 @@ enum Commands {
         /// Show what would be merged without making changes (default: true)
         #[arg(long)]
         dry_run: bool,
+
+        /// Output groups and their evidence as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// Find and merge duplicate roles using ML
@@ -559,6 +773,10 @@ enum Commands {
         #[arg(long, short = 't', default_value = "0.85")]
         threshold: f64,
 
+        /// Minimum frequency for a variant pattern to count as learned
+        #[arg(long, default_value = "2")]
+        min_frequency: usize,
+
         /// Automatically merge high-confidence duplicates
         #[arg(long)]
         auto_merge: bool,
@@ -566,14 +784,45 @@ enum Commands {
         /// Show what would be merged without making changes (default: true)
         #[arg(long)]
         dry_run: bool,
+
+        /// Output groups and their evidence as JSON
+        #[arg(long)]
+        json: bool,
     },
 
-    /// Find and merge all duplicate entities (authors, publishers, series, tags, roles) using ML
+    /// Find and merge duplicate contents (the same work imported once per edition)
+    DeduplicateContents {
+        /// Minimum confidence threshold (0.0-1.0)
+        #[arg(long, short = 't', default_value = "0.85")]
+        threshold: f64,
+
+        /// Minimum frequency for a variant pattern to count as learned
+        #[arg(long, default_value = "2")]
+        min_frequency: usize,
+
+        /// Automatically merge high-confidence duplicates
+        #[arg(long)]
+        auto_merge: bool,
+
+        /// Show what would be merged without making changes (default: true)
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Output groups and their evidence as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Find and merge all duplicate entities (authors, publishers, series, tags, roles, contents) using ML
     DeduplicateAll {
         /// Minimum confidence threshold (0.0-1.0)
         #[arg(long, short = 't', default_value = "0.85")]
         threshold: f64,
 
+        /// Minimum frequency for a variant pattern to count as learned
+        #[arg(long, default_value = "2")]
+        min_frequency: usize,
+
         /// Automatically merge high-confidence duplicates
         #[arg(long)]
         auto_merge: bool,
@@ -581,9 +830,31 @@ enum Commands {
         /// Show what would be merged without making changes (default: true)
         #[arg(long)]
         dry_run: bool,
+
+        /// Output groups and their evidence as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Unisce manualmente due o più persone, editori, serie, tag, ruoli o contenuti
+    Merge {
+        #[command(subcommand)]
+        action: MergeCommands,
     },
 
-    /// Sync EPUB metadata with database
+    /// Separa da una persona libri e contenuti attribuiti per errore
+    Split {
+        #[command(subcommand)]
+        action: SplitCommands,
+    },
+
+    /// Merge history of the deduplicate-* commands, with undo
+    Dedupe {
+        #[command(subcommand)]
+        action: DedupeCommands,
+    },
+
+    /// Sync EPUB/PDF/CBZ metadata with database
     SyncMetadata {
         /// Show count of pending books
         #[arg(long)]
@@ -598,6 +869,63 @@ enum Commands {
         library: Option<PathBuf>,
     },
 
+    /// Verifica la struttura di file EPUB (mimetype, container, OPF, manifest, spine, nav/NCX)
+    Validate {
+        /// File EPUB da validare
+        files: Vec<PathBuf>,
+
+        /// Valida gli EPUB del libro con questo ID nella libreria
+        #[arg(long, conflicts_with = "all")]
+        book_id: Option<i64>,
+
+        /// Valida tutti gli EPUB della libreria
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Verifica gli ISBN: elenca quelli non validi o duplicati nella libreria
+    IsbnCheck {
+        /// ISBN da validare e convertire (senza valori controlla la libreria)
+        values: Vec<String>,
+
+        /// Normalizza gli ISBN validi (trattini e ISBN-13 canonico)
+        #[arg(long, conflicts_with = "values")]
+        fix: bool,
+    },
+
+    /// Verifica lingue e formati rispetto ai vocabolari ISO 639 e dei formati
+    VocabularyCheck {
+        /// Codici o nomi da ricondurre al vocabolario (senza valori controlla la libreria)
+        values: Vec<String>,
+
+        /// Unisce le varianti ("ita", "Italian", "EPUB", "epub3"...) sulle righe canoniche
+        #[arg(long, conflicts_with = "values")]
+        fix: bool,
+    },
+
+    /// Cerca libri duplicati (stesso ISBN, stesso titolo e autori, testo EPUB simile) e li unisce
+    FindDuplicateBooks {
+        /// Somiglianza minima del testo EPUB (0.0-1.0)
+        #[arg(long, short = 't', default_value = "0.8")]
+        threshold: f64,
+
+        /// Output JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Unisce ogni gruppo nel libro più vecchio
+        #[arg(long)]
+        merge: bool,
+
+        /// Unisce senza chiedere conferma
+        #[arg(long, short = 'y', requires = "merge")]
+        yes: bool,
+
+        /// Elimina dallo storage i file dei duplicati invece di spostarli sul libro principale
+        #[arg(long, requires = "merge")]
+        delete_files: bool,
+    },
+
     /// Set the preferred language for the application
     SetLanguage {
         /// Language code (e.g., "en", "it")
@@ -608,6 +936,252 @@ enum Commands {
     GetLanguage,
 }
 
+#[derive(Subcommand)]
+enum PresetCommands {
+    /// Esporta preset in formato TOML su standard output
+    Export {
+        /// Nome del preset da esportare
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        name: Option<String>,
+
+        /// Esporta tutti i preset
+        #[arg(long)]
+        all: bool,
+
+        /// Tipo di preset: books o contents
+        #[arg(long = "type")]
+        preset_type: Option<String>,
+
+        /// Esporta i preset della libreria corrente invece di quelli globali
+        #[arg(long)]
+        in_library: bool,
+    },
+
+    /// Importa preset da un file TOML (i preset con filtri non validi sono rifiutati)
+    Import {
+        /// File TOML prodotto da 'ritmo preset export'
+        file: PathBuf,
+
+        /// Importa nella libreria corrente invece che globalmente
+        #[arg(long)]
+        in_library: bool,
+
+        /// Cosa fare se esiste già un preset con lo stesso nome (rename, overwrite, skip)
+        #[arg(long, default_value = "rename")]
+        on_conflict: String,
+    },
+
+    /// Copia un preset della libreria corrente tra i preset globali
+    Promote {
+        /// Nome del preset
+        name: String,
+
+        /// Tipo di preset: books o contents
+        #[arg(long = "type")]
+        preset_type: Option<String>,
+
+        /// Cosa fare se esiste già un preset con lo stesso nome (rename, overwrite, skip)
+        #[arg(long, default_value = "rename")]
+        on_conflict: String,
+    },
+
+    /// Copia un preset globale nella libreria corrente
+    Demote {
+        /// Nome del preset
+        name: String,
+
+        /// Tipo di preset: books o contents
+        #[arg(long = "type")]
+        preset_type: Option<String>,
+
+        /// Cosa fare se esiste già un preset con lo stesso nome (rename, overwrite, skip)
+        #[arg(long, default_value = "rename")]
+        on_conflict: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum MergeCommands {
+    /// Unisce persone: i nomi scartati diventano alias
+    Person(MergeArgs),
+    /// Unisce editori
+    Publisher(MergeArgs),
+    /// Unisce serie
+    Series(MergeArgs),
+    /// Unisce tag
+    Tag(MergeArgs),
+    /// Unisce ruoli
+    Role(MergeArgs),
+    /// Unisce contenuti (la stessa opera)
+    Content(MergeArgs),
+}
+
+#[derive(Subcommand)]
+enum SplitCommands {
+    /// Sposta libri e contenuti di una persona a un'altra persona (creata se non esiste)
+    Person {
+        /// ID della persona da separare
+        id: i64,
+
+        /// ID dei libri da spostare
+        #[arg(long, value_delimiter = ',', required_unless_present = "contents")]
+        books: Vec<i64>,
+
+        /// ID dei contenuti da spostare
+        #[arg(long, value_delimiter = ',')]
+        contents: Vec<i64>,
+
+        /// Nome della persona che riceve libri e contenuti
+        #[arg(long = "as")]
+        new_name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum DedupeCommands {
+    /// List past merges with their statistics, most recent first
+    History {
+        /// Only merges of this entity type (people, publishers, series, tags, roles, contents)
+        #[arg(long = "type")]
+        entity_type: Option<String>,
+
+        /// Maximum number of merges to show
+        #[arg(long)]
+        limit: Option<i64>,
+
+        /// Output JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Undo a merge, splitting the merged entities back apart
+    Undo {
+        /// Merge ID (see 'dedupe history')
+        merge_id: i64,
+    },
+}
+
+#[derive(Subcommand)]
+enum MetadataCommands {
+    /// Mostra i campi in cui l'OPF del file EPUB e il database differiscono
+    Diff {
+        /// ID del libro
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        book_id: Option<i64>,
+
+        /// Confronta tutti i libri con un file EPUB
+        #[arg(long)]
+        all: bool,
+
+        /// Output JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Riporta nel database i metadati del file, scegliendo campo per campo
+    Pull {
+        /// ID del libro
+        book_id: i64,
+
+        /// Campi da prendere dal file (title, publisher, year, isbn, series, series_index, people, tags, languages)
+        #[arg(long = "take-file", value_delimiter = ',')]
+        take_file: Vec<String>,
+
+        /// Campi per cui mantenere il valore del database
+        #[arg(long = "keep-db", value_delimiter = ',')]
+        keep_db: Vec<String>,
+
+        /// Campi lista da unire (people, tags, languages)
+        #[arg(long, value_delimiter = ',')]
+        merge: Vec<String>,
+
+        /// Mostra le scelte senza salvare
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Applica le scelte proposte senza chiedere
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
+enum ShelfCommands {
+    /// Crea uno scaffale: smart se sono indicati filtri o --from-preset, altrimenti manuale
+    Create {
+        /// Nome dello scaffale
+        name: String,
+
+        /// Descrizione opzionale
+        #[arg(long)]
+        description: Option<String>,
+
+        #[command(flatten)]
+        filters: ShelfFilterOptions,
+    },
+
+    /// Aggiunge libri a uno scaffale manuale
+    Add {
+        /// Nome dello scaffale
+        name: String,
+
+        /// ID dei libri da aggiungere
+        #[arg(required = true)]
+        book_ids: Vec<i64>,
+
+        /// Posizione di inserimento (1 = in testa; default: in coda)
+        #[arg(long)]
+        position: Option<i64>,
+
+        /// Nota per i libri aggiunti
+        #[arg(long)]
+        note: Option<String>,
+    },
+
+    /// Rimuove libri da uno scaffale manuale
+    Remove {
+        /// Nome dello scaffale
+        name: String,
+
+        /// ID dei libri da rimuovere
+        #[arg(required = true)]
+        book_ids: Vec<i64>,
+    },
+
+    /// Lista gli scaffali con il numero di libri
+    List,
+
+    /// Mostra i libri di uno scaffale
+    Show {
+        /// Nome dello scaffale
+        name: String,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Esporta uno scaffale
+    Export {
+        /// Nome dello scaffale
+        name: String,
+
+        /// Formato di export (json, csv)
+        #[arg(long, default_value = "json")]
+        format: String,
+
+        /// File di destinazione (default: standard output)
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+
+    /// Elimina uno scaffale (i libri restano nella libreria)
+    Delete {
+        /// Nome dello scaffale
+        name: String,
+    },
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
@@ -644,9 +1218,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             year,
             isbn,
             search,
+            query,
             acquired_after,
             acquired_before,
             content_type,
+            attributes,
             sort,
             limit,
             offset,
@@ -666,9 +1242,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 year,
                 isbn,
                 search,
+                query,
                 acquired_after,
                 acquired_before,
                 content_type,
+                attributes,
                 sort,
                 limit,
                 offset,
@@ -686,8 +1264,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         } => {
             cmd_set_default_filter(&cli.library, &app_settings, preset_type, preset_name)?;
         }
+        Commands::SetDefaultColumns {
+            preset_type,
+            columns,
+        } => {
+            cmd_set_default_columns(&cli.library, &app_settings, preset_type, columns)?;
+        }
         Commands::ListBooks {
             preset,
+            shelf,
             author,
             publisher,
             series,
@@ -695,11 +1280,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             year,
             isbn,
             search,
+            query,
             acquired_after,
             acquired_before,
             last_days,
             last_months,
             recent_count,
+            attributes,
             sort,
             limit,
             offset,
@@ -709,6 +1296,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 &cli.library,
                 &app_settings,
                 preset,
+                shelf,
                 author,
                 publisher,
                 series,
@@ -716,11 +1304,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 year,
                 isbn,
                 search,
+                query,
                 acquired_after,
                 acquired_before,
                 last_days,
                 last_months,
                 recent_count,
+                attributes,
                 sort,
                 limit,
                 offset,
@@ -728,12 +1318,105 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             )
             .await?;
         }
+        Commands::Preset { action } => match action {
+            PresetCommands::Export {
+                name,
+                all,
+                preset_type,
+                in_library,
+            } => {
+                cmd_preset_export(
+                    &cli.library,
+                    &app_settings,
+                    name,
+                    all,
+                    preset_type,
+                    in_library,
+                )?;
+            }
+            PresetCommands::Import {
+                file,
+                in_library,
+                on_conflict,
+            } => {
+                cmd_preset_import(
+                    &cli.library,
+                    &mut app_settings,
+                    &settings_path,
+                    file,
+                    in_library,
+                    on_conflict,
+                )?;
+            }
+            PresetCommands::Promote {
+                name,
+                preset_type,
+                on_conflict,
+            } => {
+                cmd_preset_promote(
+                    &cli.library,
+                    &mut app_settings,
+                    &settings_path,
+                    name,
+                    preset_type,
+                    on_conflict,
+                )?;
+            }
+            PresetCommands::Demote {
+                name,
+                preset_type,
+                on_conflict,
+            } => {
+                cmd_preset_demote(
+                    &cli.library,
+                    &mut app_settings,
+                    &settings_path,
+                    name,
+                    preset_type,
+                    on_conflict,
+                )?;
+            }
+        },
+        Commands::Shelf { action } => match action {
+            ShelfCommands::Create {
+                name,
+                description,
+                filters,
+            } => {
+                cmd_shelf_create(&cli.library, &app_settings, name, description, filters).await?;
+            }
+            ShelfCommands::Add {
+                name,
+                book_ids,
+                position,
+                note,
+            } => {
+                cmd_shelf_add(&cli.library, &app_settings, name, book_ids, position, note).await?;
+            }
+            ShelfCommands::Remove { name, book_ids } => {
+                cmd_shelf_remove(&cli.library, &app_settings, name, book_ids).await?;
+            }
+            ShelfCommands::List => {
+                cmd_shelf_list(&cli.library, &app_settings).await?;
+            }
+            ShelfCommands::Show { name, output } => {
+                cmd_shelf_show(&cli.library, &app_settings, name, output).await?;
+            }
+            ShelfCommands::Export { name, format, file } => {
+                cmd_shelf_export(&cli.library, &app_settings, name, format, file).await?;
+            }
+            ShelfCommands::Delete { name } => {
+                cmd_shelf_delete(&cli.library, &app_settings, name).await?;
+            }
+        },
         Commands::ListContents {
             preset,
             author,
             content_type,
             year,
             search,
+            query,
+            attributes,
             sort,
             limit,
             offset,
@@ -747,6 +1430,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 content_type,
                 year,
                 search,
+                query,
+                attributes,
                 sort,
                 limit,
                 offset,
@@ -768,6 +1453,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             pages,
             notes,
             tags,
+            strict,
         } => {
             cmd_add(
                 &cli.library,
@@ -785,6 +1471,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 pages,
                 notes,
                 tags,
+                strict,
             )
             .await?;
         }
@@ -792,9 +1479,73 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             input,
             continue_on_error,
             dry_run,
+            strict,
+            strict_isbn,
+        } => {
+            cmd_add_batch(
+                &cli.library,
+                &app_settings,
+                input,
+                continue_on_error,
+                dry_run,
+                strict,
+                strict_isbn,
+            )
+            .await?;
+        }
+        Commands::ExtractMetadata {
+            files,
+            output,
+            online,
+            providers,
+            refresh,
         } => {
-            cmd_add_batch(&cli.library, &app_settings, input, continue_on_error, dry_run).await?;
+            let online = online.then_some(OnlineArgs { providers, refresh });
+            cmd_extract_metadata(&cli.library, &app_settings, files, output, online).await?;
         }
+        Commands::Lookup {
+            isbn,
+            title,
+            author,
+            providers,
+            refresh,
+            json,
+        } => {
+            cmd_lookup(
+                &cli.library,
+                &app_settings,
+                isbn,
+                title,
+                author,
+                OnlineArgs { providers, refresh },
+                json,
+            )
+            .await?;
+        }
+        Commands::LookupCacheClear { provider } => {
+            cmd_lookup_cache_clear(&cli.library, &app_settings, provider).await?;
+        }
+        Commands::Metadata { action } => match action {
+            MetadataCommands::Diff { book_id, all, json } => {
+                cmd_metadata_diff(&cli.library, &app_settings, book_id, all, json).await?;
+            }
+            MetadataCommands::Pull {
+                book_id,
+                take_file,
+                keep_db,
+                merge,
+                dry_run,
+                yes,
+            } => {
+                let choices = PullChoices {
+                    take_file,
+                    keep_db,
+                    merge,
+                };
+                cmd_metadata_pull(&cli.library, &app_settings, book_id, choices, dry_run, yes)
+                    .await?;
+            }
+        },
         Commands::UpdateBook {
             id,
             title,
@@ -809,6 +1560,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             notes,
             pages,
             tags,
+            strict_isbn,
         } => {
             cmd_update_book(
                 &cli.library,
@@ -826,9 +1578,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 notes,
                 pages,
                 tags,
+                strict_isbn,
             )
             .await?;
         }
+        Commands::BulkEdit {
+            selection,
+            edit,
+            dry_run,
+            yes,
+        } => {
+            cmd_bulk_edit(&cli.library, &app_settings, selection, edit, dry_run, yes).await?;
+        }
         Commands::DeleteBook {
             id,
             delete_file,
@@ -836,6 +1597,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         } => {
             cmd_delete_book(&cli.library, &app_settings, id, delete_file, force).await?;
         }
+        Commands::AttachFile {
+            file,
+            book_id,
+            isbn,
+            format,
+        } => {
+            cmd_attach_file(&cli.library, &app_settings, file, book_id, isbn, format).await?;
+        }
+        Commands::ListFiles { book_id } => {
+            cmd_list_files(&cli.library, &app_settings, book_id).await?;
+        }
+        Commands::DeleteFile {
+            id,
+            delete_file,
+            force,
+        } => {
+            cmd_delete_file(&cli.library, &app_settings, id, delete_file, force).await?;
+        }
         Commands::AddContent {
             title,
             original_title,
@@ -912,58 +1691,149 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
         Commands::DeduplicatePeople {
             threshold,
+            min_frequency,
             auto_merge,
             dry_run,
+            json,
         } => {
-            cmd_deduplicate_people(&cli.library, &app_settings, threshold, auto_merge, dry_run)
-                .await?;
+            let args = DeduplicateArgs {
+                threshold,
+                min_frequency,
+                auto_merge,
+                dry_run,
+                json,
+            };
+            cmd_deduplicate_people(&cli.library, &app_settings, args).await?;
         }
         Commands::DeduplicatePublishers {
             threshold,
+            min_frequency,
             auto_merge,
             dry_run,
+            json,
         } => {
-            cmd_deduplicate_publishers(
-                &cli.library,
-                &app_settings,
+            let args = DeduplicateArgs {
                 threshold,
+                min_frequency,
                 auto_merge,
                 dry_run,
-            )
-            .await?;
+                json,
+            };
+            cmd_deduplicate_publishers(&cli.library, &app_settings, args).await?;
         }
         Commands::DeduplicateSeries {
             threshold,
+            min_frequency,
             auto_merge,
             dry_run,
+            json,
         } => {
-            cmd_deduplicate_series(&cli.library, &app_settings, threshold, auto_merge, dry_run)
-                .await?;
+            let args = DeduplicateArgs {
+                threshold,
+                min_frequency,
+                auto_merge,
+                dry_run,
+                json,
+            };
+            cmd_deduplicate_series(&cli.library, &app_settings, args).await?;
         }
         Commands::DeduplicateTags {
             threshold,
+            min_frequency,
             auto_merge,
             dry_run,
+            json,
         } => {
-            cmd_deduplicate_tags(&cli.library, &app_settings, threshold, auto_merge, dry_run)
-                .await?;
+            let args = DeduplicateArgs {
+                threshold,
+                min_frequency,
+                auto_merge,
+                dry_run,
+                json,
+            };
+            cmd_deduplicate_tags(&cli.library, &app_settings, args).await?;
         }
         Commands::DeduplicateRoles {
             threshold,
+            min_frequency,
+            auto_merge,
+            dry_run,
+            json,
+        } => {
+            let args = DeduplicateArgs {
+                threshold,
+                min_frequency,
+                auto_merge,
+                dry_run,
+                json,
+            };
+            cmd_deduplicate_roles(&cli.library, &app_settings, args).await?;
+        }
+        Commands::DeduplicateContents {
+            threshold,
+            min_frequency,
             auto_merge,
             dry_run,
+            json,
         } => {
-            cmd_deduplicate_roles(&cli.library, &app_settings, threshold, auto_merge, dry_run)
-                .await?;
+            let args = DeduplicateArgs {
+                threshold,
+                min_frequency,
+                auto_merge,
+                dry_run,
+                json,
+            };
+            cmd_deduplicate_contents(&cli.library, &app_settings, args).await?;
         }
         Commands::DeduplicateAll {
             threshold,
+            min_frequency,
             auto_merge,
             dry_run,
+            json,
         } => {
-            cmd_deduplicate_all(&cli.library, &app_settings, threshold, auto_merge, dry_run)
-                .await?;
+            let args = DeduplicateArgs {
+                threshold,
+                min_frequency,
+                auto_merge,
+                dry_run,
+                json,
+            };
+            cmd_deduplicate_all(&cli.library, &app_settings, args).await?;
+        }
+        Commands::Merge { action } => {
+            let (entity, args) = match action {
+                MergeCommands::Person(args) => (MergeEntity::People, args),
+                MergeCommands::Publisher(args) => (MergeEntity::Publishers, args),
+                MergeCommands::Series(args) => (MergeEntity::Series, args),
+                MergeCommands::Tag(args) => (MergeEntity::Tags, args),
+                MergeCommands::Role(args) => (MergeEntity::Roles, args),
+                MergeCommands::Content(args) => (MergeEntity::Contents, args),
+            };
+            cmd_merge(&cli.library, &app_settings, entity, args).await?;
         }
+        Commands::Split { action } => match action {
+            SplitCommands::Person {
+                id,
+                books,
+                contents,
+                new_name,
+            } => {
+                cmd_split_person(&cli.library, &app_settings, id, books, contents, new_name).await?;
+            }
+        },
+        Commands::Dedupe { action } => match action {
+            DedupeCommands::History {
+                entity_type,
+                limit,
+                json,
+            } => {
+                cmd_dedupe_history(&cli.library, &app_settings, entity_type, limit, json).await?;
+            }
+            DedupeCommands::Undo { merge_id } => {
+                cmd_dedupe_undo(&cli.library, &app_settings, merge_id).await?;
+            }
+        },
         Commands::SyncMetadata {
             status,
             dry_run,
@@ -977,6 +1847,35 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 cmd_sync_metadata(&library, &app_settings).await?;
             }
         }
+        Commands::Validate {
+            files,
+            book_id,
+            all,
+        } => {
+            cmd_validate(&cli.library, &app_settings, files, book_id, all).await?;
+        }
+        Commands::IsbnCheck { values, fix } => {
+            cmd_isbn_check(&cli.library, &app_settings, values, fix).await?;
+        }
+        Commands::VocabularyCheck { values, fix } => {
+            cmd_vocabulary_check(&cli.library, &app_settings, values, fix).await?;
+        }
+        Commands::FindDuplicateBooks {
+            threshold,
+            json,
+            merge,
+            yes,
+            delete_files,
+        } => {
+            let args = FindDuplicateBooksArgs {
+                threshold,
+                json,
+                merge,
+                yes,
+                delete_files,
+            };
+            cmd_find_duplicate_books(&cli.library, &app_settings, args).await?;
+        }
         Commands::SetLanguage { language } => {
             cmd_set_language(language, &mut app_settings, &settings_path)?;
         }
@@ -33,6 +33,16 @@ pub struct Preferences {
     /// Tema UI (es. "light", "dark")
     #[serde(default = "default_theme")]
     pub ui_theme: String,
+
+    /// Se true, un ISBN non valido in import e aggiornamento è un errore
+    /// invece di un avviso
+    #[serde(default)]
+    pub strict_isbn: bool,
+
+    /// Particelle del cognome aggiuntive per il parsing dei nomi
+    /// (es. ["af", "ap"]), oltre a quelle predefinite ("della", "van", "de"...)
+    #[serde(default)]
+    pub name_particles: Vec<String>,
 }
 
 fn default_language() -> String {
@@ -48,6 +58,8 @@ impl Default for Preferences {
         Self {
             ui_language: default_language(),
             ui_theme: default_theme(),
+            strict_isbn: false,
+            name_particles: Vec::new(),
         }
     }
 }
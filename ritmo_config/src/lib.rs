@@ -1,11 +1,13 @@
 mod app_settings;
 mod portable;
 pub mod preset_resolver;
+pub mod preset_transfer;
 pub mod presets;
 
 pub use app_settings::AppSettings;
 pub use portable::{detect_portable_library, is_running_portable};
 pub use preset_resolver::{LibraryPresetsHolder, PresetResolver, PresetSource};
+pub use preset_transfer::{insert_with_policy, ConflictPolicy, MergeOutcome, PresetBundle};
 pub use presets::{BookFilterPreset, ContentFilterPreset, GlobalPresets, NamedPreset, PresetType};
 pub use ritmo_errors::RitmoErr;
 
@@ -0,0 +1,231 @@
+use crate::presets::{BookFilterPreset, ContentFilterPreset, GlobalPresets, NamedPreset};
+use std::collections::HashMap;
+
+/// Cosa fare quando un preset importato ha lo stesso nome di uno esistente
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictPolicy {
+    /// Salva con un nuovo nome (`nome_2`, `nome_3`, ...)
+    #[default]
+    Rename,
+    /// Sostituisce il preset esistente
+    Overwrite,
+    /// Mantiene il preset esistente e ignora quello importato
+    Skip,
+}
+
+impl std::str::FromStr for ConflictPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "rename" => Ok(Self::Rename),
+            "overwrite" => Ok(Self::Overwrite),
+            "skip" => Ok(Self::Skip),
+            _ => Err(format!(
+                "Politica non valida: '{}'. Usa 'rename', 'overwrite' o 'skip'",
+                s
+            )),
+        }
+    }
+}
+
+impl ConflictPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Rename => "rename",
+            Self::Overwrite => "overwrite",
+            Self::Skip => "skip",
+        }
+    }
+}
+
+/// Esito dell'inserimento di un preset in una collezione
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeOutcome {
+    Added(String),
+    Renamed { from: String, to: String },
+    Overwritten(String),
+    Skipped(String),
+}
+
+impl std::fmt::Display for MergeOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Added(name) => write!(f, "'{}' aggiunto", name),
+            Self::Renamed { from, to } => {
+                write!(f, "'{}' già presente, salvato come '{}'", from, to)
+            }
+            Self::Overwritten(name) => write!(f, "'{}' sovrascritto", name),
+            Self::Skipped(name) => write!(f, "'{}' già presente, ignorato", name),
+        }
+    }
+}
+
+/// Inserisce un preset applicando la politica sui conflitti di nome
+pub fn insert_with_policy<T>(
+    presets: &mut HashMap<String, NamedPreset<T>>,
+    mut preset: NamedPreset<T>,
+    policy: ConflictPolicy,
+) -> MergeOutcome {
+    let name = preset.name.clone();
+    if !presets.contains_key(&name) {
+        presets.insert(name.clone(), preset);
+        return MergeOutcome::Added(name);
+    }
+
+    match policy {
+        ConflictPolicy::Skip => MergeOutcome::Skipped(name),
+        ConflictPolicy::Overwrite => {
+            presets.insert(name.clone(), preset);
+            MergeOutcome::Overwritten(name)
+        }
+        ConflictPolicy::Rename => {
+            let new_name = (2..)
+                .map(|n| format!("{}_{}", name, n))
+                .find(|candidate| !presets.contains_key(candidate))
+                .expect("sequenza di nomi infinita");
+            preset.name = new_name.clone();
+            presets.insert(new_name.clone(), preset);
+            MergeOutcome::Renamed {
+                from: name,
+                to: new_name,
+            }
+        }
+    }
+}
+
+/// File di scambio dei preset: stessa struttura di `[presets]` in settings.toml
+///
+/// ```toml
+/// [books.recent_epub]
+/// name = "recent_epub"
+///
+/// [books.recent_epub.filters]
+/// format = "epub"
+/// sort = "date_added"
+/// ```
+pub type PresetBundle = GlobalPresets;
+
+impl GlobalPresets {
+    /// Bundle con un solo preset libri
+    pub fn from_book_preset(preset: NamedPreset<BookFilterPreset>) -> Self {
+        let mut bundle = Self::new();
+        bundle.add_book_preset(preset);
+        bundle
+    }
+
+    /// Bundle con un solo preset contenuti
+    pub fn from_content_preset(preset: NamedPreset<ContentFilterPreset>) -> Self {
+        let mut bundle = Self::new();
+        bundle.add_content_preset(preset);
+        bundle
+    }
+
+    pub fn to_toml(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string_pretty(self)
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self, toml::de::Error> {
+        let mut bundle: Self = toml::from_str(content)?;
+        // Il nome del preset è quello della chiave, anche se il campo `name` differisce
+        for (key, preset) in bundle.books.iter_mut() {
+            preset.name = key.clone();
+        }
+        for (key, preset) in bundle.contents.iter_mut() {
+            preset.name = key.clone();
+        }
+        Ok(bundle)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.books.is_empty() && self.contents.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book_preset(name: &str, format: &str) -> NamedPreset<BookFilterPreset> {
+        NamedPreset {
+            name: name.to_string(),
+            description: None,
+            filters: BookFilterPreset {
+                format: Some(format.to_string()),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_conflict_policy_from_str() {
+        assert_eq!("Rename".parse(), Ok(ConflictPolicy::Rename));
+        assert_eq!("overwrite".parse(), Ok(ConflictPolicy::Overwrite));
+        assert_eq!("skip".parse(), Ok(ConflictPolicy::Skip));
+        assert!("merge".parse::<ConflictPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_insert_with_policy() {
+        let mut presets = HashMap::new();
+        assert_eq!(
+            insert_with_policy(
+                &mut presets,
+                book_preset("epub", "epub"),
+                ConflictPolicy::Skip
+            ),
+            MergeOutcome::Added("epub".to_string())
+        );
+
+        let outcome = insert_with_policy(
+            &mut presets,
+            book_preset("epub", "pdf"),
+            ConflictPolicy::Skip,
+        );
+        assert_eq!(outcome, MergeOutcome::Skipped("epub".to_string()));
+        assert_eq!(presets["epub"].filters.format.as_deref(), Some("epub"));
+
+        insert_with_policy(
+            &mut presets,
+            book_preset("epub", "pdf"),
+            ConflictPolicy::Overwrite,
+        );
+        assert_eq!(presets["epub"].filters.format.as_deref(), Some("pdf"));
+
+        insert_with_policy(
+            &mut presets,
+            book_preset("epub", "mobi"),
+            ConflictPolicy::Rename,
+        );
+        let outcome = insert_with_policy(
+            &mut presets,
+            book_preset("epub", "cbz"),
+            ConflictPolicy::Rename,
+        );
+        assert_eq!(
+            outcome,
+            MergeOutcome::Renamed {
+                from: "epub".to_string(),
+                to: "epub_3".to_string()
+            }
+        );
+        assert_eq!(presets["epub_2"].name, "epub_2");
+        assert_eq!(presets["epub_3"].filters.format.as_deref(), Some("cbz"));
+    }
+
+    #[test]
+    fn test_bundle_toml_roundtrip() {
+        let bundle = PresetBundle::from_book_preset(book_preset("epub", "epub"));
+        let toml = bundle.to_toml().unwrap();
+        let parsed = PresetBundle::from_toml(&toml).unwrap();
+        assert_eq!(parsed.books["epub"].filters, bundle.books["epub"].filters);
+        assert!(parsed.contents.is_empty());
+
+        // La chiave prevale sul campo name
+        let parsed = PresetBundle::from_toml(
+            "[contents.novels]\nname = \"other\"\n\n[contents.novels.filters]\ncontent_type = \"Romanzo\"\n",
+        )
+        .unwrap();
+        assert_eq!(parsed.contents["novels"].name, "novels");
+    }
+}
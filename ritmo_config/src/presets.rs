@@ -49,12 +49,49 @@ pub struct BookFilterPreset {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub search: Option<String>,
 
+    /// Query booleana (es. `author:king AND NOT tag:read`), validata al momento dell'uso
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub acquired_after: Option<i64>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub acquired_before: Option<i64>,
 
+    /// Tag richiesti (`all_tags`: tutti invece di almeno uno)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub all_tags: bool,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language_role: Option<String>,
+
+    /// Persone con ruolo, formato "Nome:ruolo" (es. "Fruttero:translator")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub people: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub publisher_countries: Vec<String>,
+
+    /// Campi presenti/assenti (isbn, cover, file, series, publisher, author, language, type, pages)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub has: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pages_min: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pages_max: Option<i64>,
+
     #[serde(default = "default_sort")]
     pub sort: String,
 
@@ -80,6 +117,40 @@ pub struct ContentFilterPreset {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub search: Option<String>,
 
+    /// Query booleana (es. `author:king AND NOT tag:read`), validata al momento dell'uso
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+
+    /// Tag richiesti (`all_tags`: tutti invece di almeno uno)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub all_tags: bool,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language_role: Option<String>,
+
+    /// Persone con ruolo, formato "Nome:ruolo" (es. "Fruttero:translator")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub people: Vec<String>,
+
+    /// Campi presenti/assenti (isbn, cover, file, series, publisher, author, language, type, pages)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub has: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub missing: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pages_min: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pages_max: Option<i64>,
+
     #[serde(default = "default_sort")]
     pub sort: String,
 
@@ -104,8 +175,19 @@ impl Default for BookFilterPreset {
             year: None,
             isbn: None,
             search: None,
+            query: None,
             acquired_after: None,
             acquired_before: None,
+            tags: Vec::new(),
+            all_tags: false,
+            languages: Vec::new(),
+            language_role: None,
+            people: Vec::new(),
+            publisher_countries: Vec::new(),
+            has: Vec::new(),
+            missing: Vec::new(),
+            pages_min: None,
+            pages_max: None,
             sort: default_sort(),
             limit: None,
             offset: 0,
@@ -120,6 +202,16 @@ impl Default for ContentFilterPreset {
             content_type: None,
             year: None,
             search: None,
+            query: None,
+            tags: Vec::new(),
+            all_tags: false,
+            languages: Vec::new(),
+            language_role: None,
+            people: Vec::new(),
+            has: Vec::new(),
+            missing: Vec::new(),
+            pages_min: None,
+            pages_max: None,
             sort: default_sort(),
             limit: None,
             offset: 0,
@@ -141,10 +233,10 @@ pub struct NamedPreset<T> {
 /// Collezione di preset globali (salvati in ~/.config/ritmo/settings.toml)
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GlobalPresets {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub books: HashMap<String, NamedPreset<BookFilterPreset>>,
 
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub contents: HashMap<String, NamedPreset<ContentFilterPreset>>,
 }
 
@@ -218,6 +310,32 @@ mod tests {
         assert!(preset.author.is_none());
     }
 
+    #[test]
+    fn test_book_filter_preset_toml_roundtrip() {
+        let preset = BookFilterPreset {
+            tags: vec!["horror".to_string(), "read".to_string()],
+            all_tags: true,
+            languages: vec!["en".to_string()],
+            language_role: Some("language_role.original".to_string()),
+            people: vec!["Fruttero:translator".to_string()],
+            publisher_countries: vec!["IT".to_string()],
+            missing: vec!["isbn".to_string()],
+            pages_min: Some(400),
+            ..Default::default()
+        };
+
+        let toml = toml::to_string(&preset).unwrap();
+        assert!(toml.contains("all_tags = true"));
+        assert!(!toml.contains("has ="));
+        assert_eq!(toml::from_str::<BookFilterPreset>(&toml).unwrap(), preset);
+
+        // Preset salvati prima dei nuovi campi
+        let old: ContentFilterPreset = toml::from_str("author = \"Calvino\"").unwrap();
+        assert!(old.tags.is_empty());
+        assert!(!old.all_tags);
+        assert_eq!(old.sort, "title");
+    }
+
     #[test]
     fn test_global_presets_add_and_get() {
         let mut presets = GlobalPresets::new();
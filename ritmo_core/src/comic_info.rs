@@ -0,0 +1,666 @@
+use crate::dto::ContentInput;
+use crate::service::book_import_service::BookImportMetadata;
+use quick_xml::escape::{partial_escape, unescape};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use ritmo_errors::{RitmoErr, RitmoResult};
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::path::Path;
+use zip::{ZipArchive, ZipWriter};
+
+/// Nome del file dei metadati nella radice dell'archivio
+pub const COMIC_INFO_FILE: &str = "ComicInfo.xml";
+
+/// Elementi ComicInfo che contengono persone, con il ruolo Ritmo corrispondente
+///
+/// I valori sono liste separate da virgole (`<Writer>Alan Moore, Neil Gaiman</Writer>`).
+pub const CREATOR_ELEMENTS: [(&str, &str); 8] = [
+    ("Writer", "role.writer"),
+    ("Penciller", "role.penciller"),
+    ("Inker", "role.inker"),
+    ("Colorist", "role.colorist"),
+    ("Letterer", "role.letterer"),
+    ("CoverArtist", "role.cover_artist"),
+    ("Editor", "role.editor"),
+    ("Translator", "role.translator"),
+];
+
+/// Ordine degli elementi nello schema ComicInfo v2.1 (`xs:sequence`)
+const SCHEMA_ORDER: [&str; 44] = [
+    "Title", "Series", "Number", "Count", "Volume", "AlternateSeries", "AlternateNumber",
+    "AlternateCount", "Summary", "Notes", "Year", "Month", "Day", "Writer", "Penciller", "Inker",
+    "Colorist", "Letterer", "CoverArtist", "Editor", "Translator", "Publisher", "Imprint", "Genre",
+    "Tags", "Web", "PageCount", "LanguageISO", "Format", "BlackAndWhite", "Manga", "Characters",
+    "Teams", "Locations", "ScanInformation", "StoryArc", "StoryArcNumber", "SeriesGroup",
+    "AgeRating", "Pages", "CommunityRating", "MainCharacterOrTeam", "Review", "GTIN",
+];
+
+/// Estensioni considerate pagine del fumetto
+const IMAGE_EXTENSIONS: [&str; 7] = ["jpg", "jpeg", "png", "gif", "webp", "bmp", "avif"];
+
+/// Metadati di un fumetto (`ComicInfo.xml`, schema Anansi/ComicRack)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ComicInfo {
+    pub title: Option<String>,
+    pub series: Option<String>,
+    /// Numero dell'albo: stringa libera ("1", "1.5", "Annual 1")
+    pub number: Option<String>,
+    pub volume: Option<i64>,
+    pub summary: Option<String>,
+    pub year: Option<i32>,
+    /// Persone con ruolo Ritmo (`role.writer`, `role.penciller`, ...)
+    pub people: Vec<(String, String)>,
+    pub publisher: Option<String>,
+    pub genres: Vec<String>,
+    pub tags: Vec<String>,
+    pub language: Option<String>,
+    /// GTIN (ISBN-13/EAN)
+    pub gtin: Option<String>,
+    pub page_count: Option<i64>,
+}
+
+impl ComicInfo {
+    /// Indice nella serie: parte intera di `Number`, altrimenti `Volume`
+    pub fn series_index(&self) -> Option<i64> {
+        self.number
+            .as_deref()
+            .and_then(|n| n.trim().parse::<f64>().ok())
+            .map(|n| n as i64)
+            .or(self.volume)
+    }
+}
+
+/// Contenuto di un archivio CBZ
+#[derive(Debug, Clone, Default)]
+pub struct ComicArchive {
+    /// Metadati, se l'archivio contiene `ComicInfo.xml`
+    pub info: Option<ComicInfo>,
+    /// Numero di immagini (pagine)
+    pub page_count: i64,
+    /// Nome della prima pagina, usata come copertina
+    pub cover: Option<String>,
+}
+
+/// Elemento figlio di `<ComicInfo>`, conservato così com'è per la riscrittura
+struct ComicElement {
+    name: String,
+    raw: String,
+    text: Option<String>,
+}
+
+/// Analizza un documento `ComicInfo.xml`
+pub fn parse_comic_info(xml: &str) -> RitmoResult<ComicInfo> {
+    let mut info = ComicInfo::default();
+
+    for element in parse_elements(xml)? {
+        let Some(text) = element.text.as_deref().map(str::trim).filter(|t| !t.is_empty()) else {
+            continue;
+        };
+        let value = Some(text.to_string());
+        match element.name.as_str() {
+            "Title" => info.title = value,
+            "Series" => info.series = value,
+            "Number" => info.number = value,
+            "Volume" => info.volume = text.parse().ok(),
+            "Summary" => info.summary = value,
+            "Year" => info.year = text.parse().ok().filter(|y| *y > 0),
+            "Publisher" => info.publisher = value,
+            "Genre" => info.genres = split_list(text),
+            "Tags" => info.tags = split_list(text),
+            "LanguageISO" => info.language = value,
+            "GTIN" => info.gtin = value,
+            "PageCount" => info.page_count = text.parse().ok().filter(|p| *p > 0),
+            name => {
+                if let Some((_, role)) = CREATOR_ELEMENTS.iter().find(|(e, _)| *e == name) {
+                    for person in split_list(text) {
+                        info.people.push((person, role.to_string()));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(info)
+}
+
+/// Genera `ComicInfo.xml` unendo i nuovi metadati a quelli esistenti
+///
+/// I campi vuoti in `info` mantengono il valore già presente; gli elementi
+/// non gestiti da Ritmo (`Pages`, `Web`, `AgeRating`, ...) vengono copiati
+/// senza modifiche. Gli elementi seguono l'ordine dello schema.
+pub fn write_comic_info(existing: Option<&str>, info: &ComicInfo) -> RitmoResult<String> {
+    let existing = match existing {
+        Some(xml) => parse_elements(xml)?,
+        None => Vec::new(),
+    };
+
+    let mut managed: Vec<(&str, String)> = Vec::new();
+    let mut set = |name: &'static str, value: Option<String>| {
+        if let Some(value) = value.filter(|v| !v.trim().is_empty()) {
+            managed.push((name, value));
+        }
+    };
+    set("Title", info.title.clone());
+    set("Series", info.series.clone());
+    set("Number", info.number.clone());
+    set("Volume", info.volume.map(|v| v.to_string()));
+    set("Summary", info.summary.clone());
+    set("Year", info.year.map(|y| y.to_string()));
+    for (element, role) in CREATOR_ELEMENTS {
+        let names: Vec<&str> = info
+            .people
+            .iter()
+            .filter(|(_, r)| r == role)
+            .map(|(n, _)| n.as_str())
+            .collect();
+        set(element, Some(names.join(", ")));
+    }
+    set("Publisher", info.publisher.clone());
+    set("Genre", Some(info.genres.join(", ")));
+    set("Tags", Some(info.tags.join(", ")));
+    set("PageCount", info.page_count.map(|p| p.to_string()));
+    set("LanguageISO", info.language.clone());
+    set("GTIN", info.gtin.clone());
+
+    let mut body = String::new();
+    let mut push_element = |name: &str| {
+        if let Some((_, value)) = managed.iter().find(|(n, _)| *n == name) {
+            body.push_str(&format!("  <{0}>{1}</{0}>\n", name, partial_escape(value.as_str())));
+        } else {
+            for element in existing.iter().filter(|e| e.name == name) {
+                body.push_str(&format!("  {}\n", element.raw.trim()));
+            }
+        }
+    };
+    for name in SCHEMA_ORDER {
+        push_element(name);
+    }
+    // Elementi fuori schema (estensioni di altri programmi)
+    for element in existing.iter().filter(|e| !SCHEMA_ORDER.contains(&e.name.as_str())) {
+        body.push_str(&format!("  {}\n", element.raw.trim()));
+    }
+
+    Ok(format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+         <ComicInfo xmlns:xsd=\"http://www.w3.org/2001/XMLSchema\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\">\n{}</ComicInfo>\n",
+        body
+    ))
+}
+
+/// Costruisce i metadati ComicInfo a partire dai metadati del libro e delle opere
+///
+/// Le persone di libro e opere vengono unite: `role.author` diventa Writer e
+/// `role.illustrator` Penciller; gli altri ruoli senza elemento ComicInfo sono ignorati.
+pub fn build_comic_info(metadata: &BookImportMetadata, contents: &[ContentInput]) -> ComicInfo {
+    let mut people: Vec<(String, String)> = Vec::new();
+    let all_people = metadata
+        .people
+        .iter()
+        .flatten()
+        .cloned()
+        .chain(contents.iter().flat_map(|c| {
+            c.people.iter().map(|p| (p.name.clone(), p.role.clone()))
+        }));
+    for (name, role) in all_people {
+        let role = match role.as_str() {
+            "role.author" => "role.writer".to_string(),
+            "role.illustrator" => "role.penciller".to_string(),
+            _ => role,
+        };
+        let known = CREATOR_ELEMENTS.iter().any(|(_, r)| *r == role);
+        if known && !people.iter().any(|(n, r)| *n == name && *r == role) {
+            people.push((name, role));
+        }
+    }
+
+    let language = contents
+        .iter()
+        .flat_map(|c| c.languages.iter())
+        .find(|l| l.role == "language_role.actual")
+        .map(|l| l.code.clone());
+
+    ComicInfo {
+        title: Some(metadata.title.clone()),
+        series: metadata.series.clone(),
+        number: metadata.series_index.map(|i| i.to_string()),
+        volume: None,
+        summary: metadata.notes.clone(),
+        year: metadata.year,
+        people,
+        publisher: metadata.publisher.clone(),
+        genres: Vec::new(),
+        tags: metadata.tags.clone().unwrap_or_default(),
+        language,
+        gtin: metadata.isbn.clone(),
+        page_count: None,
+    }
+}
+
+/// Legge metadati, numero di pagine e copertina da un CBZ (o da un CBR in formato ZIP)
+pub fn read_comic_archive(path: &Path) -> RitmoResult<ComicArchive> {
+    let mut archive = open_comic_archive(path)?;
+    let pages = list_pages(&archive);
+
+    let info = match read_comic_info_xml(&mut archive)? {
+        Some(xml) => Some(parse_comic_info(&xml)?),
+        None => None,
+    };
+
+    Ok(ComicArchive {
+        info,
+        page_count: pages.len() as i64,
+        cover: pages.into_iter().next(),
+    })
+}
+
+/// Estrae la prima pagina dell'archivio come copertina
+///
+/// # Returns
+/// Contenuto dell'immagine ed estensione (in minuscolo), `None` se l'archivio non contiene immagini
+pub fn extract_comic_cover(path: &Path) -> RitmoResult<Option<(Vec<u8>, String)>> {
+    let mut archive = open_comic_archive(path)?;
+    let Some(name) = list_pages(&archive).into_iter().next() else {
+        return Ok(None);
+    };
+
+    let mut entry = archive
+        .by_name(&name)
+        .map_err(|e| RitmoErr::Generic(format!("Errore lettura '{}': {}", name, e)))?;
+    let mut data = Vec::new();
+    entry
+        .read_to_end(&mut data)
+        .map_err(|e| RitmoErr::Generic(format!("Errore lettura '{}': {}", name, e)))?;
+
+    Ok(Some((data, page_extension(&name).unwrap_or_default())))
+}
+
+/// Scrive `ComicInfo.xml` in un CBZ
+///
+/// Le pagine vengono copiate senza ricompressione; il `ComicInfo.xml`
+/// esistente viene unito ai nuovi metadati (vedi `write_comic_info`).
+///
+/// # Arguments
+/// * `cbz_path` - Path al CBZ originale
+/// * `output_path` - Path dove scrivere il CBZ modificato
+/// * `info` - Metadati da applicare
+pub fn modify_cbz_metadata(cbz_path: &Path, output_path: &Path, info: &ComicInfo) -> RitmoResult<()> {
+    let mut archive = open_comic_archive(cbz_path)?;
+    let existing = read_comic_info_xml(&mut archive)?;
+    let comic_info = write_comic_info(existing.as_deref(), info)?;
+
+    let output_file = File::create(output_path)?;
+    let mut zip_writer = ZipWriter::new(output_file);
+
+    for i in 0..archive.len() {
+        let file = archive
+            .by_index_raw(i)
+            .map_err(|e| RitmoErr::Generic(format!("Errore lettura voce ZIP {}: {}", i, e)))?;
+        if file.name().eq_ignore_ascii_case(COMIC_INFO_FILE) {
+            continue;
+        }
+        let name = file.name().to_string();
+        zip_writer.raw_copy_file(file).map_err(|e| {
+            RitmoErr::Generic(format!("Errore copia '{}' nel CBZ: {}", name, e))
+        })?;
+    }
+
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    zip_writer
+        .start_file(COMIC_INFO_FILE, options)
+        .map_err(|e| RitmoErr::Generic(format!("Errore scrittura {}: {}", COMIC_INFO_FILE, e)))?;
+    zip_writer
+        .write_all(comic_info.as_bytes())
+        .map_err(|e| RitmoErr::Generic(format!("Errore scrittura {}: {}", COMIC_INFO_FILE, e)))?;
+    zip_writer
+        .finish()
+        .map_err(|e| RitmoErr::Generic(format!("Errore finalizzazione CBZ: {}", e)))?;
+
+    Ok(())
+}
+
+/// Vero se il file è un archivio RAR (CBR non leggibile da ritmo)
+pub fn is_rar_archive(path: &Path) -> bool {
+    let mut magic = [0u8; 4];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok()
+        && &magic == b"Rar!"
+}
+
+/// Apre un archivio di fumetti come ZIP
+///
+/// I CBR in formato RAR non sono supportati; molti CBR sono però ZIP
+/// rinominati, e vengono letti normalmente.
+fn open_comic_archive(path: &Path) -> RitmoResult<ZipArchive<BufReader<File>>> {
+    if is_rar_archive(path) {
+        return Err(RitmoErr::Generic(format!(
+            "CBR in formato RAR non supportato (solo CBZ/CBR in formato ZIP): {}",
+            path.display()
+        )));
+    }
+
+    let file = File::open(path)?;
+    ZipArchive::new(BufReader::new(file)).map_err(|e| {
+        RitmoErr::Generic(format!(
+            "Impossibile aprire {} come ZIP: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+fn read_comic_info_xml(archive: &mut ZipArchive<BufReader<File>>) -> RitmoResult<Option<String>> {
+    let Some(name) = archive
+        .file_names()
+        .find(|n| n.eq_ignore_ascii_case(COMIC_INFO_FILE))
+        .map(|n| n.to_string())
+    else {
+        return Ok(None);
+    };
+
+    let mut entry = archive
+        .by_name(&name)
+        .map_err(|e| RitmoErr::Generic(format!("Errore lettura {}: {}", name, e)))?;
+    let mut data = Vec::new();
+    entry
+        .read_to_end(&mut data)
+        .map_err(|e| RitmoErr::Generic(format!("Errore lettura {}: {}", name, e)))?;
+
+    Ok(Some(String::from_utf8_lossy(&data).trim_start_matches('\u{feff}').to_string()))
+}
+
+/// Immagini dell'archivio in ordine naturale (`page2` prima di `page10`)
+fn list_pages(archive: &ZipArchive<BufReader<File>>) -> Vec<String> {
+    let mut pages: Vec<String> = archive
+        .file_names()
+        .filter(|n| !n.starts_with("__MACOSX/") && !n.rsplit('/').next().unwrap_or(n).starts_with('.'))
+        .filter(|n| page_extension(n).is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.as_str())))
+        .map(|n| n.to_string())
+        .collect();
+    pages.sort_by(|a, b| natural_cmp(a, b));
+    pages
+}
+
+fn page_extension(name: &str) -> Option<String> {
+    Path::new(name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+}
+
+/// Confronto "naturale": le sequenze di cifre sono confrontate come numeri
+fn natural_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return std::cmp::Ordering::Equal,
+            (None, Some(_)) => return std::cmp::Ordering::Less,
+            (Some(_), None) => return std::cmp::Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let na = take_number(&mut a);
+                let nb = take_number(&mut b);
+                let ord = na.cmp(&nb);
+                if ord != std::cmp::Ordering::Equal {
+                    return ord;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let ord = x.to_ascii_lowercase().cmp(&y.to_ascii_lowercase());
+                if ord != std::cmp::Ordering::Equal {
+                    return ord;
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+fn take_number(chars: &mut std::iter::Peekable<std::str::Chars>) -> u64 {
+    let mut n: u64 = 0;
+    while let Some(d) = chars.peek().and_then(|c| c.to_digit(10)) {
+        n = n.saturating_mul(10).saturating_add(d as u64);
+        chars.next();
+    }
+    n
+}
+
+fn split_list(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Elementi figli della radice, con il loro XML originale e il testo (se semplice)
+fn parse_elements(xml: &str) -> RitmoResult<Vec<ComicElement>> {
+    let xml_err = |e: quick_xml::Error| RitmoErr::Generic(format!("Errore parsing ComicInfo.xml: {}", e));
+    let mut reader = Reader::from_str(xml);
+    let mut elements = Vec::new();
+    let mut in_root = false;
+
+    loop {
+        let start = reader.buffer_position() as usize;
+        match reader.read_event().map_err(xml_err)? {
+            Event::Start(_) if !in_root => in_root = true,
+            Event::Start(e) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                let span = reader.read_to_end(e.name()).map_err(xml_err)?;
+                let end = reader.buffer_position() as usize;
+                let inner = &xml[span.start as usize..span.end as usize];
+                let text = if let Some(cdata) = inner
+                    .trim()
+                    .strip_prefix("<![CDATA[")
+                    .and_then(|s| s.strip_suffix("]]>"))
+                {
+                    Some(cdata.to_string())
+                } else if inner.contains('<') {
+                    None
+                } else {
+                    unescape(inner).ok().map(|t| t.to_string())
+                };
+                elements.push(ComicElement {
+                    name,
+                    raw: xml[start..end].to_string(),
+                    text,
+                });
+            }
+            Event::Empty(e) if in_root => {
+                let end = reader.buffer_position() as usize;
+                elements.push(ComicElement {
+                    name: String::from_utf8_lossy(e.local_name().as_ref()).to_string(),
+                    raw: xml[start..end].to_string(),
+                    text: None,
+                });
+            }
+            Event::End(_) | Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(elements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const COMIC_INFO: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<ComicInfo xmlns:xsd="http://www.w3.org/2001/XMLSchema" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <Title>The Sound and the Fury</Title>
+  <Series>Sandman</Series>
+  <Number>19</Number>
+  <Volume>1989</Volume>
+  <Summary>Dream &amp; Shakespeare</Summary>
+  <Year>1990</Year>
+  <Writer>Neil Gaiman</Writer>
+  <Penciller>Charles Vess</Penciller>
+  <Inker>Charles Vess</Inker>
+  <Colorist>Steve Oliff, Robbie Busch</Colorist>
+  <Publisher>DC Comics</Publisher>
+  <Genre>Fantasy</Genre>
+  <Web>https://example.com/sandman-19</Web>
+  <PageCount>3</PageCount>
+  <LanguageISO>en</LanguageISO>
+  <Pages>
+    <Page Image="0" Type="FrontCover"/>
+  </Pages>
+</ComicInfo>"#;
+
+    fn create_test_cbz(dir: &TempDir, comic_info: Option<&str>) -> std::path::PathBuf {
+        let path = dir.path().join("test.cbz");
+        let mut zip = ZipWriter::new(File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        for name in ["page10.jpg", "page2.jpg", "page1.png", "__MACOSX/._page1.png"] {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(name.as_bytes()).unwrap();
+        }
+        if let Some(xml) = comic_info {
+            zip.start_file(COMIC_INFO_FILE, options).unwrap();
+            zip.write_all(xml.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+        path
+    }
+
+    #[test]
+    fn test_parse_comic_info() {
+        let info = parse_comic_info(COMIC_INFO).unwrap();
+
+        assert_eq!(info.title.as_deref(), Some("The Sound and the Fury"));
+        assert_eq!(info.series.as_deref(), Some("Sandman"));
+        assert_eq!(info.number.as_deref(), Some("19"));
+        assert_eq!(info.volume, Some(1989));
+        assert_eq!(info.series_index(), Some(19));
+        assert_eq!(info.summary.as_deref(), Some("Dream & Shakespeare"));
+        assert_eq!(info.year, Some(1990));
+        assert_eq!(info.publisher.as_deref(), Some("DC Comics"));
+        assert_eq!(info.language.as_deref(), Some("en"));
+        assert_eq!(info.page_count, Some(3));
+        assert!(info
+            .people
+            .contains(&("Neil Gaiman".to_string(), "role.writer".to_string())));
+        assert!(info
+            .people
+            .contains(&("Charles Vess".to_string(), "role.inker".to_string())));
+        assert!(info
+            .people
+            .contains(&("Robbie Busch".to_string(), "role.colorist".to_string())));
+    }
+
+    #[test]
+    fn test_write_comic_info_preserves_unknown_elements() {
+        let info = ComicInfo {
+            title: Some("Nuovo titolo".to_string()),
+            people: vec![("Neil Gaiman".to_string(), "role.writer".to_string())],
+            ..Default::default()
+        };
+        let xml = write_comic_info(Some(COMIC_INFO), &info).unwrap();
+        let parsed = parse_comic_info(&xml).unwrap();
+
+        assert_eq!(parsed.title.as_deref(), Some("Nuovo titolo"));
+        // Campi non forniti: mantenuti
+        assert_eq!(parsed.series.as_deref(), Some("Sandman"));
+        assert_eq!(parsed.publisher.as_deref(), Some("DC Comics"));
+        // Elementi non gestiti: copiati così come sono
+        assert!(xml.contains("<Web>https://example.com/sandman-19</Web>"));
+        assert!(xml.contains(r#"<Page Image="0" Type="FrontCover"/>"#));
+        // Ordine dello schema
+        assert!(xml.find("<Title>").unwrap() < xml.find("<Series>").unwrap());
+        assert!(xml.find("<Pages>").unwrap() > xml.find("<LanguageISO>").unwrap());
+    }
+
+    #[test]
+    fn test_build_comic_info_maps_roles() {
+        let metadata = BookImportMetadata {
+            title: "Watchmen".to_string(),
+            original_title: None,
+            people: Some(vec![
+                ("Alan Moore".to_string(), "role.author".to_string()),
+                ("Dave Gibbons".to_string(), "role.illustrator".to_string()),
+                ("Mario Rossi".to_string(), "role.narrator".to_string()),
+            ]),
+            publisher: Some("DC Comics".to_string()),
+            year: Some(1987),
+            isbn: None,
+            format: Some("cbz".to_string()),
+            series: Some("Watchmen".to_string()),
+            series_index: Some(1),
+            pages: None,
+            notes: None,
+            tags: None,
+            attach_to: None,
+            strict_validation: false,
+        };
+        let info = build_comic_info(&metadata, &[]);
+
+        assert_eq!(
+            info.people,
+            vec![
+                ("Alan Moore".to_string(), "role.writer".to_string()),
+                ("Dave Gibbons".to_string(), "role.penciller".to_string()),
+            ]
+        );
+        assert_eq!(info.number.as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn test_read_comic_archive_and_cover() {
+        let dir = TempDir::new().unwrap();
+        let path = create_test_cbz(&dir, Some(COMIC_INFO));
+
+        let archive = read_comic_archive(&path).unwrap();
+        assert_eq!(archive.page_count, 3);
+        assert_eq!(archive.cover.as_deref(), Some("page1.png"));
+        assert_eq!(archive.info.unwrap().series.as_deref(), Some("Sandman"));
+
+        let (cover, ext) = extract_comic_cover(&path).unwrap().unwrap();
+        assert_eq!(cover, b"page1.png");
+        assert_eq!(ext, "png");
+    }
+
+    #[test]
+    fn test_modify_cbz_metadata() {
+        let dir = TempDir::new().unwrap();
+        let path = create_test_cbz(&dir, None);
+        let output = dir.path().join("out.cbz");
+
+        let info = ComicInfo {
+            title: Some("Sandman #1".to_string()),
+            series: Some("Sandman".to_string()),
+            number: Some("1".to_string()),
+            ..Default::default()
+        };
+        modify_cbz_metadata(&path, &output, &info).unwrap();
+
+        let archive = read_comic_archive(&output).unwrap();
+        assert_eq!(archive.page_count, 3);
+        let written = archive.info.unwrap();
+        assert_eq!(written.title.as_deref(), Some("Sandman #1"));
+        assert_eq!(written.series_index(), Some(1));
+    }
+
+    #[test]
+    fn test_rar_archive_rejected() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("test.cbr");
+        std::fs::write(&path, b"Rar!\x1a\x07\x00rest").unwrap();
+        assert!(is_rar_archive(&path));
+
+        let err = read_comic_archive(&path).unwrap_err();
+        assert!(format!("{}", err).contains("RAR"));
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        let mut names = vec!["p10.jpg", "p2.jpg", "P1.jpg"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(names, vec!["P1.jpg", "p2.jpg", "p10.jpg"]);
+    }
+}
@@ -11,9 +11,14 @@ pub struct ImportObject {
     /// Path to the book file (absolute or relative)
     pub file_path: String,
 
-    /// Book-level metadata (physical edition)
+    /// Book-level metadata (physical edition), ignored when `attach_to` is set
+    #[serde(default)]
     pub book: BookInput,
 
+    /// Attach the file to an existing book instead of creating a new one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attach_to: Option<AttachToInput>,
+
     /// Contents contained in this book (literary works)
     #[serde(default)]
     pub contents: Vec<ContentInput>,
@@ -21,10 +26,28 @@ pub struct ImportObject {
     /// Confidence scores for extracted fields (Level 3 output, ignored on import)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub confidence: Option<HashMap<String, f32>>,
+
+    /// Origin of each field filled or confirmed by an online provider, with the
+    /// same keys as `confidence` (e.g. "file,openlibrary"; ignored on import)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sources: Option<HashMap<String, String>>,
 }
 
-/// Book-level metadata (physical edition)
+/// Existing book to attach a file to (e.g. the PDF of a book already imported as EPUB).
+/// Exactly one of `book_id` and `isbn` must be set.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachToInput {
+    /// ID of the existing book
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub book_id: Option<i64>,
+
+    /// ISBN of the existing book
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub isbn: Option<String>,
+}
+
+/// Book-level metadata (physical edition)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct BookInput {
     /// Book title (required)
     pub title: String,
@@ -109,6 +132,11 @@ pub struct PersonInput {
 
     /// Role i18n key (required): "role.author", "role.translator", etc.
     pub role: String,
+
+    /// Where the person comes from when not from the file (e.g. "openlibrary"),
+    /// stored in `people.source` when the person is created
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
 /// Language object
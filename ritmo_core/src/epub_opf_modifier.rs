@@ -1,7 +1,10 @@
 use crate::dto::ContentInput;
 use crate::service::book_import_service::BookImportMetadata;
 use ritmo_errors::{RitmoErr, RitmoResult};
-use std::collections::HashSet;
+use quick_xml::escape::unescape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::{HashMap, HashSet};
 use std::fs::{File};
 use std::io::{BufReader, Read, Write};
 use std::path::Path;
@@ -44,7 +47,7 @@ pub struct OPFIdentifier {
 /// Maps Ritmo role keys to OPF role codes
 ///
 /// MARC relator codes: https://www.loc.gov/marc/relators/relaterm.html
-fn map_ritmo_role_to_opf(ritmo_role: &str) -> &'static str {
+pub(crate) fn map_ritmo_role_to_opf(ritmo_role: &str) -> &'static str {
     match ritmo_role {
         "role.author" => "aut",
         "role.translator" => "trl",
@@ -53,6 +56,10 @@ fn map_ritmo_role_to_opf(ritmo_role: &str) -> &'static str {
         "role.narrator" => "nrt",
         "role.contributor" => "ctb",
         "role.preface" => "aui",  // Author of introduction
+        "role.writer" => "aut",
+        "role.penciller" => "art",  // Artist
+        "role.colorist" => "clr",
+        "role.cover_artist" => "cov",  // Cover designer
         _ => "ctb",  // default: contributor
     }
 }
@@ -171,14 +178,19 @@ pub fn build_opf_metadata(
 
 /// Modifies OPF XML with new metadata
 ///
-/// This function parses the original OPF XML, updates the metadata section,
-/// and preserves all other parts (manifest, spine, guide).
+/// Event-based editor: the OPF is read with quick-xml and only the `<metadata>`
+/// children owned by ritmo are replaced; every other byte of the document
+/// (manifest, spine, guide, comments, unknown metadata) is copied unchanged.
 ///
 /// # Strategy
-/// - Parse XML with namespace support
-/// - Modify only <metadata> section
-/// - Preserve manifest, spine, guide sections
-/// - Preserve existing elements for None fields
+/// - Owned fields are always replaced: None / empty removes the existing elements, so a
+///   value cleared in the database is cleared in the file too. Title and language are
+///   required by the EPUB spec and are kept when there is no new value
+/// - Removing an element also removes the `<meta refines="#id">` that point to it
+/// - The identifier referenced by `unique-identifier` and custom identifiers are always kept
+/// - People get `role` and `file-as` (EPUB3 `refines`, EPUB2 `opf:` attributes)
+/// - Series are written as Calibre meta and, in EPUB3, as `belongs-to-collection` + `group-position`
+/// - EPUB3: `dcterms:modified` is set to the current time
 ///
 /// # Arguments
 /// * `original_opf` - Original OPF XML content as string
@@ -190,99 +202,455 @@ pub fn modify_opf_xml(
     original_opf: &str,
     metadata: &OPFMetadata,
 ) -> RitmoResult<String> {
-    // For now, return a minimal implementation that just adds/replaces metadata
-    // A full XML parser implementation would be more robust but also more complex
+    let modified = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    edit_opf(original_opf, metadata, &modified)
+}
 
-    // This is a simplified implementation that uses string manipulation
-    // TODO: Implement proper XML parsing with quick-xml for better robustness
+/// A direct child of `<metadata>`, with its byte range in the original OPF
+struct MetadataChild {
+    /// Qualified name (`dc:title`, `meta`, ...)
+    name: String,
+    attrs: HashMap<String, String>,
+    text: String,
+    /// Start of the element, including the whitespace that precedes it
+    start: usize,
+    end: usize,
+}
 
-    // Find the metadata section
-    let metadata_start = original_opf.find("<metadata")
-        .ok_or_else(|| RitmoErr::Generic("No <metadata> section found in OPF".to_string()))?;
+impl MetadataChild {
+    fn local_name(&self) -> &str {
+        self.name.rsplit(':').next().unwrap_or(&self.name)
+    }
 
-    let metadata_end = original_opf.find("</metadata>")
-        .ok_or_else(|| RitmoErr::Generic("No </metadata> closing tag found in OPF".to_string()))?;
+    fn attr(&self, local: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(key, _)| key.rsplit(':').next() == Some(local))
+            .map(|(_, value)| value.as_str())
+    }
 
-    // Extract parts: before metadata, metadata section, after metadata
-    let before_metadata = &original_opf[..metadata_start];
-    let after_metadata = &original_opf[metadata_end + "</metadata>".len()..];
+    /// Id targeted by a `refines="#id"` attribute
+    fn refines(&self) -> Option<&str> {
+        self.attrs
+            .get("refines")
+            .map(|target| target.trim_start_matches('#'))
+    }
+}
 
-    // Extract the opening metadata tag with all its attributes and namespaces
-    let metadata_opening_end = original_opf[metadata_start..]
-        .find('>')
-        .ok_or_else(|| RitmoErr::Generic("Malformed <metadata> tag".to_string()))?;
-    let metadata_opening_tag = &original_opf[metadata_start..metadata_start + metadata_opening_end + 1];
+/// Structure of an OPF as seen by the editor
+#[derive(Default)]
+struct OpfLayout {
+    epub3: bool,
+    unique_identifier: Option<String>,
+    /// Namespace prefixes declared on `<package>` or `<metadata>`
+    declared_prefixes: HashSet<String>,
+    ids: HashSet<String>,
+    children: Vec<MetadataChild>,
+    /// Position of `>` closing the `<metadata>` start tag
+    metadata_tag_end: usize,
+    /// Where new elements are inserted (before the whitespace preceding `</metadata>`)
+    insert_at: usize,
+    /// Whitespace used before each child of `<metadata>`
+    indent: String,
+}
 
-    // Build new metadata content
-    let mut new_metadata_content = String::new();
+fn edit_opf(original_opf: &str, metadata: &OPFMetadata, modified: &str) -> RitmoResult<String> {
+    let layout = scan_opf(original_opf)?;
+
+    // Decide which children to drop, then the refinements of the dropped ones
+    let mut drop: Vec<bool> = layout
+        .children
+        .iter()
+        .map(|child| child.refines().is_none() && is_replaced(child, metadata, &layout))
+        .collect();
+    let dropped_ids: HashSet<&str> = layout
+        .children
+        .iter()
+        .zip(&drop)
+        .filter(|(_, dropped)| **dropped)
+        .filter_map(|(child, _)| child.attrs.get("id").map(|id| id.as_str()))
+        .collect();
+    for (child, dropped) in layout.children.iter().zip(drop.iter_mut()) {
+        if let Some(target) = child.refines() {
+            *dropped = dropped_ids.contains(target) || is_replaced(child, metadata, &layout);
+        }
+    }
 
-    // Add title
-    if let Some(title) = &metadata.title {
-        new_metadata_content.push_str(&format!("\n    <dc:title>{}</dc:title>", escape_xml(title)));
+    // Ids of removed elements can be reused: editing twice gives the same OPF
+    let used_ids: HashSet<String> = layout
+        .ids
+        .iter()
+        .filter(|id| !dropped_ids.contains(id.as_str()))
+        .cloned()
+        .collect();
+    let new_elements = render_metadata(metadata, &layout, used_ids, modified);
+
+    // Splice: copy the original, skipping dropped ranges and inserting the new elements
+    let mut output = String::with_capacity(original_opf.len() + 1024);
+    output.push_str(&original_opf[..layout.metadata_tag_end]);
+
+    // Namespaces used by the new elements (opf: attributes are only written in EPUB2)
+    for prefix in ["dc", "opf"] {
+        let needed = new_elements
+            .iter()
+            .any(|e| e.starts_with(&format!("<{}:", prefix)) || e.contains(&format!(" {}:", prefix)));
+        if needed && !layout.declared_prefixes.contains(prefix) {
+            output.push_str(&format!(" xmlns:{}=\"{}\"", prefix, namespace_uri(prefix)));
+        }
     }
 
-    // Add creators (authors)
-    for creator in &metadata.creators {
-        new_metadata_content.push_str(&format!(
-            "\n    <dc:creator opf:role=\"{}\">{}</dc:creator>",
-            creator.role, escape_xml(&creator.name)
-        ));
+    let mut cursor = layout.metadata_tag_end;
+    for (child, _) in layout.children.iter().zip(&drop).filter(|(_, d)| **d) {
+        output.push_str(&original_opf[cursor..child.start]);
+        cursor = child.end;
+    }
+    output.push_str(&original_opf[cursor..layout.insert_at]);
+    cursor = layout.insert_at;
+    for element in new_elements {
+        output.push_str(&layout.indent);
+        output.push_str(&element);
     }
+    output.push_str(&original_opf[cursor..]);
 
-    // Add contributors (translators, editors, etc.)
-    for contributor in &metadata.contributors {
-        new_metadata_content.push_str(&format!(
-            "\n    <dc:contributor opf:role=\"{}\">{}</dc:contributor>",
-            contributor.role, escape_xml(&contributor.name)
-        ));
+    Ok(output)
+}
+
+/// Reads the OPF once, recording the `<metadata>` children and their byte ranges
+fn scan_opf(opf: &str) -> RitmoResult<OpfLayout> {
+    let parse_error = |e: quick_xml::Error| RitmoErr::Generic(format!("Failed to parse OPF: {}", e));
+
+    let mut reader = Reader::from_str(opf);
+    let mut layout = OpfLayout::default();
+    let mut in_metadata = false;
+    let mut found_metadata = false;
+    // Start of the whitespace run preceding the current position (inside <metadata>)
+    let mut whitespace_start: Option<usize> = None;
+
+    loop {
+        let start = reader.buffer_position() as usize;
+        let event = reader.read_event().map_err(parse_error)?;
+        let end = reader.buffer_position() as usize;
+
+        let (e, is_empty) = match event {
+            Event::Eof => break,
+            Event::Start(e) => (e, false),
+            Event::Empty(e) => (e, true),
+            Event::Text(e) if in_metadata => {
+                if e.iter().all(|b| b.is_ascii_whitespace()) {
+                    whitespace_start.get_or_insert(start);
+                } else {
+                    whitespace_start = None;
+                }
+                continue;
+            }
+            Event::End(e) if in_metadata && e.local_name().as_ref() == b"metadata" => {
+                layout.insert_at = whitespace_start.unwrap_or(start);
+                in_metadata = false;
+                continue;
+            }
+            _ => {
+                whitespace_start = None;
+                continue;
+            }
+        };
+
+        let attrs = raw_attributes(&e);
+        if let Some(id) = attrs.get("id") {
+            layout.ids.insert(id.clone());
+        }
+
+        if !in_metadata {
+            match e.local_name().as_ref() {
+                b"package" => {
+                    layout.epub3 = attrs
+                        .get("version")
+                        .is_some_and(|v| v.trim().starts_with('3'));
+                    layout.unique_identifier = attrs.get("unique-identifier").cloned();
+                    collect_prefixes(&attrs, &mut layout.declared_prefixes);
+                }
+                b"metadata" if is_empty => {
+                    return Err(RitmoErr::Generic("Empty <metadata/> section in OPF".to_string()));
+                }
+                b"metadata" => {
+                    collect_prefixes(&attrs, &mut layout.declared_prefixes);
+                    layout.metadata_tag_end = end - 1;
+                    in_metadata = true;
+                    found_metadata = true;
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        // Direct child of <metadata>: read its text up to the closing tag
+        let (text, end) = if is_empty {
+            (String::new(), end)
+        } else {
+            let raw = reader.read_text(e.name()).map_err(parse_error)?;
+            let text = unescape(&raw).map(|t| t.trim().to_string()).unwrap_or_default();
+            (text, reader.buffer_position() as usize)
+        };
+        if layout.indent.is_empty() {
+            if let Some(ws) = whitespace_start {
+                layout.indent = opf[ws..start].to_string();
+            }
+        }
+        layout.children.push(MetadataChild {
+            name: String::from_utf8_lossy(e.name().as_ref()).to_string(),
+            attrs,
+            text,
+            start: whitespace_start.unwrap_or(start),
+            end,
+        });
+        whitespace_start = None;
     }
 
-    // Add publisher
-    if let Some(publisher) = &metadata.publisher {
-        new_metadata_content.push_str(&format!("\n    <dc:publisher>{}</dc:publisher>", escape_xml(publisher)));
+    if !found_metadata {
+        return Err(RitmoErr::Generic("No <metadata> section found in OPF".to_string()));
+    }
+    if layout.indent.is_empty() {
+        layout.indent = "\n    ".to_string();
+    }
+    Ok(layout)
+}
+
+/// Attributes with their qualified names (`opf:role`, `xmlns:dc`)
+fn raw_attributes(e: &BytesStart) -> HashMap<String, String> {
+    e.attributes()
+        .flatten()
+        .filter_map(|attr| {
+            let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
+            let value = attr.unescape_value().ok()?.to_string();
+            Some((key, value))
+        })
+        .collect()
+}
+
+fn collect_prefixes(attrs: &HashMap<String, String>, prefixes: &mut HashSet<String>) {
+    for key in attrs.keys() {
+        if let Some(prefix) = key.strip_prefix("xmlns:") {
+            prefixes.insert(prefix.to_string());
+        }
+    }
+}
+
+fn namespace_uri(prefix: &str) -> &'static str {
+    match prefix {
+        "dc" => "http://purl.org/dc/elements/1.1/",
+        _ => "http://www.idpf.org/2007/opf",
+    }
+}
+
+/// Whether an existing `<metadata>` child is replaced by the new metadata
+///
+/// Every element ritmo writes is replaced, even when the new value is missing
+/// (the element is then just removed); only title and language, required by the
+/// EPUB spec, need a new value to be replaced.
+fn is_replaced(child: &MetadataChild, metadata: &OPFMetadata, layout: &OpfLayout) -> bool {
+    let is_dc = child.name.starts_with("dc:");
+    match (is_dc, child.local_name()) {
+        (true, "title") => metadata.title.is_some(),
+        (true, "language") => !metadata.languages.is_empty(),
+        (true, "creator" | "contributor" | "publisher" | "date" | "subject") => true,
+        (true, "identifier") => {
+            let is_unique = child.attrs.get("id").is_some_and(|id| {
+                layout.unique_identifier.as_deref() == Some(id.as_str())
+            });
+            !is_unique
+                && (identifier_has_scheme(child, "ISBN")
+                    || metadata
+                        .identifiers
+                        .iter()
+                        .any(|identifier| identifier_has_scheme(child, &identifier.scheme)))
+        }
+        (false, "meta") => {
+            if let Some(name) = child.attrs.get("name") {
+                return matches!(name.as_str(), "calibre:series" | "calibre:series_index");
+            }
+            match child.attrs.get("property").map(|p| p.as_str()) {
+                Some("dcterms:modified") => layout.epub3 && child.refines().is_none(),
+                Some("belongs-to-collection") => is_series_collection(child, layout),
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// `belongs-to-collection` without `collection-type`, or with `collection-type` = series
+fn is_series_collection(child: &MetadataChild, layout: &OpfLayout) -> bool {
+    let Some(id) = child.attrs.get("id") else {
+        return true;
+    };
+    layout
+        .children
+        .iter()
+        .filter(|c| c.refines() == Some(id.as_str()))
+        .find(|c| c.attrs.get("property").map(|p| p.as_str()) == Some("collection-type"))
+        .is_none_or(|c| c.text == "series")
+}
+
+/// `opf:scheme="ISBN"` (EPUB2) or `urn:isbn:...` (EPUB3)
+fn identifier_has_scheme(child: &MetadataChild, scheme: &str) -> bool {
+    child
+        .attr("scheme")
+        .is_some_and(|s| s.eq_ignore_ascii_case(scheme))
+        || child
+            .text
+            .to_lowercase()
+            .starts_with(&format!("urn:{}:", scheme.to_lowercase()))
+}
+
+/// Renders the new `<metadata>` children, one string per element
+fn render_metadata(
+    metadata: &OPFMetadata,
+    layout: &OpfLayout,
+    used_ids: HashSet<String>,
+    modified: &str,
+) -> Vec<String> {
+    let mut elements = Vec::new();
+    let mut ids = IdGenerator { used: used_ids };
+
+    if let Some(title) = &metadata.title {
+        elements.push(format!("<dc:title>{}</dc:title>", escape_xml(title)));
     }
 
-    // Add date
+    let people = metadata
+        .creators
+        .iter()
+        .map(|p| ("creator", p))
+        .chain(metadata.contributors.iter().map(|p| ("contributor", p)));
+    for (element, person) in people {
+        let name = escape_xml(&person.name);
+        let file_as = escape_xml(&file_as(&person.name));
+        if layout.epub3 {
+            let id = ids.next(element);
+            elements.push(format!("<dc:{0} id=\"{1}\">{2}</dc:{0}>", element, id, name));
+            elements.push(format!(
+                "<meta refines=\"#{}\" property=\"role\" scheme=\"marc:relators\">{}</meta>",
+                id,
+                escape_xml(&person.role)
+            ));
+            elements.push(format!(
+                "<meta refines=\"#{}\" property=\"file-as\">{}</meta>",
+                id, file_as
+            ));
+        } else {
+            elements.push(format!(
+                "<dc:{0} opf:role=\"{1}\" opf:file-as=\"{2}\">{3}</dc:{0}>",
+                element,
+                escape_xml(&person.role),
+                file_as,
+                name
+            ));
+        }
+    }
+
+    if let Some(publisher) = &metadata.publisher {
+        elements.push(format!("<dc:publisher>{}</dc:publisher>", escape_xml(publisher)));
+    }
     if let Some(date) = &metadata.date {
-        new_metadata_content.push_str(&format!("\n    <dc:date>{}</dc:date>", escape_xml(date)));
+        elements.push(format!("<dc:date>{}</dc:date>", escape_xml(date)));
     }
 
-    // Add identifiers
+    let unique_value = layout
+        .children
+        .iter()
+        .find(|c| c.local_name() == "identifier" && c.attrs.get("id") == layout.unique_identifier.as_ref())
+        .map(|c| c.text.as_str());
     for identifier in &metadata.identifiers {
-        new_metadata_content.push_str(&format!(
-            "\n    <dc:identifier opf:scheme=\"{}\">{}</dc:identifier>",
-            identifier.scheme, escape_xml(&identifier.value)
-        ));
+        let urn = format!("urn:{}:{}", identifier.scheme.to_lowercase(), identifier.value);
+        if unique_value == Some(identifier.value.as_str()) || unique_value == Some(urn.as_str()) {
+            continue;
+        }
+        if layout.epub3 {
+            elements.push(format!("<dc:identifier>{}</dc:identifier>", escape_xml(&urn)));
+        } else {
+            elements.push(format!(
+                "<dc:identifier opf:scheme=\"{}\">{}</dc:identifier>",
+                escape_xml(&identifier.scheme),
+                escape_xml(&identifier.value)
+            ));
+        }
     }
 
-    // Add languages
     for language in &metadata.languages {
-        new_metadata_content.push_str(&format!("\n    <dc:language>{}</dc:language>", language));
+        elements.push(format!("<dc:language>{}</dc:language>", escape_xml(language)));
     }
-
-    // Add subjects (tags)
     for subject in &metadata.subjects {
-        new_metadata_content.push_str(&format!("\n    <dc:subject>{}</dc:subject>", escape_xml(subject)));
+        elements.push(format!("<dc:subject>{}</dc:subject>", escape_xml(subject)));
     }
 
-    // Add Calibre meta tags for series
     if let Some(series) = &metadata.series {
-        new_metadata_content.push_str(&format!("\n    <meta name=\"calibre:series\" content=\"{}\"/>", escape_xml(series)));
+        if layout.epub3 {
+            let id = ids.next("series");
+            elements.push(format!(
+                "<meta property=\"belongs-to-collection\" id=\"{}\">{}</meta>",
+                id,
+                escape_xml(series)
+            ));
+            elements.push(format!(
+                "<meta refines=\"#{}\" property=\"collection-type\">series</meta>",
+                id
+            ));
+            if let Some(index) = metadata.series_index {
+                elements.push(format!(
+                    "<meta refines=\"#{}\" property=\"group-position\">{}</meta>",
+                    id, index
+                ));
+            }
+        }
+        // Calibre reads its own meta in both EPUB2 and EPUB3
+        elements.push(format!(
+            "<meta name=\"calibre:series\" content=\"{}\"/>",
+            escape_xml(series)
+        ));
+        if let Some(index) = metadata.series_index {
+            elements.push(format!(
+                "<meta name=\"calibre:series_index\" content=\"{}\"/>",
+                index
+            ));
+        }
     }
-    if let Some(series_index) = metadata.series_index {
-        new_metadata_content.push_str(&format!("\n    <meta name=\"calibre:series_index\" content=\"{}\"/>", series_index));
+
+    if layout.epub3 {
+        elements.push(format!(
+            "<meta property=\"dcterms:modified\">{}</meta>",
+            modified
+        ));
     }
 
-    // Construct the new OPF
-    let new_opf = format!(
-        "{}{}{}\n  </metadata>{}",
-        before_metadata,
-        metadata_opening_tag,
-        new_metadata_content,
-        after_metadata
-    );
+    elements
+}
+
+/// Generates document-unique ids (`creator01`, `creator02`, ...)
+struct IdGenerator {
+    used: HashSet<String>,
+}
+
+impl IdGenerator {
+    fn next(&mut self, prefix: &str) -> String {
+        let id = (1..)
+            .map(|n| format!("{}{:02}", prefix, n))
+            .find(|candidate| !self.used.contains(candidate))
+            .unwrap_or_default();
+        self.used.insert(id.clone());
+        id
+    }
+}
 
-    Ok(new_opf)
+/// Sort form of a person name for `file-as`: "Umberto Eco" → "Eco, Umberto"
+///
+/// Names already containing a comma are assumed to be in sort form.
+fn file_as(name: &str) -> String {
+    let name = name.trim();
+    if name.contains(',') {
+        return name.to_string();
+    }
+    match name.rsplit_once(' ') {
+        Some((given, surname)) => format!("{}, {}", surname, given.trim()),
+        None => name.to_string(),
+    }
 }
 
 /// Helper function to escape XML special characters
@@ -448,6 +816,79 @@ mod tests {
         assert_eq!(escape_xml("Quote \" here"), "Quote &quot; here");
     }
 
+    #[test]
+    fn test_modify_opf_keeps_unique_identifier_and_language() {
+        let original = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0" unique-identifier="BookId">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:title>Old</dc:title>
+    <dc:identifier id="BookId">urn:uuid:1234</dc:identifier>
+    <dc:identifier>other</dc:identifier>
+    <dc:language>it</dc:language>
+  </metadata>
+  <manifest/>
+</package>"#;
+        let metadata = OPFMetadata {
+            title: Some("New".to_string()),
+            ..empty_opf_metadata()
+        };
+
+        let modified = modify_opf_xml(original, &metadata).unwrap();
+        assert!(modified.contains(r#"<dc:identifier id="BookId">urn:uuid:1234</dc:identifier>"#));
+        assert!(modified.contains("<dc:identifier>other</dc:identifier>"));
+        assert!(modified.contains("<dc:language>it</dc:language>"));
+        assert!(modified.contains("<dc:title>New</dc:title>"));
+    }
+
+    #[test]
+    fn test_file_as() {
+        assert_eq!(file_as("Umberto Eco"), "Eco, Umberto");
+        assert_eq!(file_as("Gabriel García Márquez"), "Márquez, Gabriel García");
+        assert_eq!(file_as("Eco, Umberto"), "Eco, Umberto");
+        assert_eq!(file_as("Omero"), "Omero");
+    }
+
+    #[test]
+    fn test_edit_opf_keeps_isbn_unique_identifier() {
+        let original = r#"<package version="3.0" unique-identifier="id">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="id">urn:isbn:9788804668237</dc:identifier>
+    <dc:identifier>urn:isbn:9780000000002</dc:identifier>
+  </metadata>
+</package>"#;
+        let metadata = OPFMetadata {
+            identifiers: vec![OPFIdentifier {
+                scheme: "ISBN".to_string(),
+                value: "9788804668237".to_string(),
+            }],
+            ..empty_opf_metadata()
+        };
+
+        let edited = edit_opf(original, &metadata, "2026-01-01T00:00:00Z").unwrap();
+        assert!(edited.contains(r#"<dc:identifier id="id">urn:isbn:9788804668237</dc:identifier>"#));
+        // The old secondary ISBN is replaced, the unique one is not duplicated
+        assert!(!edited.contains("9780000000002"));
+        assert_eq!(edited.matches("9788804668237").count(), 1);
+        assert!(edited.contains(r#"<meta property="dcterms:modified">2026-01-01T00:00:00Z</meta>"#));
+    }
+
+    fn empty_opf_metadata() -> OPFMetadata {
+        OPFMetadata {
+            title: None,
+            creators: vec![],
+            contributors: vec![],
+            publisher: None,
+            date: None,
+            identifiers: vec![],
+            subjects: vec![],
+            languages: vec![],
+            series: None,
+            series_index: None,
+            pages: None,
+            notes: None,
+        }
+    }
+
     #[test]
     fn test_build_opf_metadata_minimal() {
         let book_metadata = BookImportMetadata {
@@ -463,6 +904,8 @@ mod tests {
             pages: None,
             notes: None,
             tags: None,
+            attach_to: None,
+            strict_validation: false,
         };
 
         let opf = build_opf_metadata(&book_metadata, &[]);
@@ -491,6 +934,8 @@ mod tests {
             pages: None,
             notes: None,
             tags: Some(vec!["fiction".to_string(), "test".to_string()]),
+            attach_to: None,
+            strict_validation: false,
         };
 
         let opf = build_opf_metadata(&book_metadata, &[]);
@@ -38,7 +38,9 @@ pub fn extract_opf(epub_path: &Path) -> RitmoResult<String> {
 }
 
 /// Cerca il path del file OPF nel META-INF/container.xml
-fn find_opf_path_in_container(archive: &mut ZipArchive<BufReader<File>>) -> RitmoResult<String> {
+pub(crate) fn find_opf_path_in_container(
+    archive: &mut ZipArchive<BufReader<File>>,
+) -> RitmoResult<String> {
     // Leggi META-INF/container.xml
     let container_content = {
         let mut container_file = archive
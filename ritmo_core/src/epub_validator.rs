@@ -0,0 +1,694 @@
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use ritmo_errors::RitmoResult;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use zip::{CompressionMethod, ZipArchive};
+
+const EPUB_MIMETYPE: &str = "application/epub+zip";
+const OPF_MEDIA_TYPE: &str = "application/oebps-package+xml";
+const NCX_MEDIA_TYPE: &str = "application/x-dtbncx+xml";
+
+/// Gravità di un problema trovato dalla validazione
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// L'EPUB non è conforme e può non aprirsi nei lettori
+    Error,
+    /// Anomalia tollerata dalla maggior parte dei lettori
+    Warning,
+}
+
+/// Singolo problema strutturale di un EPUB
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self.severity {
+            Severity::Error => "errore",
+            Severity::Warning => "avviso",
+        };
+        write!(f, "{}: {}", label, self.message)
+    }
+}
+
+/// Risultato della validazione strutturale di un EPUB
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// true se non ci sono errori (gli avvisi sono ammessi)
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues.iter().filter(|i| i.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues.iter().filter(|i| i.severity == Severity::Warning)
+    }
+
+    /// Errori presenti in questo report ma non in `before`
+    ///
+    /// Usato dopo la riscrittura di un EPUB: un file già difettoso prima della
+    /// modifica non deve far fallire la sincronizzazione, un errore nuovo sì.
+    pub fn new_errors_since<'a>(&'a self, before: &ValidationReport) -> Vec<&'a ValidationIssue> {
+        self.errors()
+            .filter(|issue| !before.issues.contains(issue))
+            .collect()
+    }
+
+    /// Messaggi di errore uniti in una riga (per i messaggi di `RitmoErr`)
+    pub fn error_summary(&self) -> String {
+        self.errors()
+            .map(|i| i.message.as_str())
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    pub(crate) fn error(&mut self, message: impl Into<String>) {
+        self.issues.push(ValidationIssue {
+            severity: Severity::Error,
+            message: message.into(),
+        });
+    }
+
+    pub(crate) fn warning(&mut self, message: impl Into<String>) {
+        self.issues.push(ValidationIssue {
+            severity: Severity::Warning,
+            message: message.into(),
+        });
+    }
+}
+
+/// Elemento `<item>` del manifest
+pub(crate) struct ManifestItem {
+    pub(crate) id: String,
+    pub(crate) href: String,
+    pub(crate) media_type: String,
+    properties: String,
+}
+
+/// Dati dell'OPF necessari alla validazione
+#[derive(Default)]
+pub(crate) struct OpfPackage {
+    version: Option<String>,
+    unique_identifier: Option<String>,
+    identifier_ids: Vec<String>,
+    has_identifier: bool,
+    has_title: bool,
+    has_language: bool,
+    pub(crate) manifest: Vec<ManifestItem>,
+    spine_toc: Option<String>,
+    pub(crate) spine: Vec<String>,
+    has_spine: bool,
+}
+
+/// Valida la struttura di un EPUB
+///
+/// Controlli eseguiti:
+/// - l'archivio è uno ZIP leggibile
+/// - `mimetype` è la prima voce, non compressa, con contenuto `application/epub+zip`
+/// - `META-INF/container.xml` esiste e punta a un OPF presente nell'archivio
+/// - l'OPF è XML valido con `<metadata>`, `<manifest>` e `<spine>`
+/// - `unique-identifier` punta a un `dc:identifier` esistente, gli id del manifest sono unici
+/// - ogni voce del manifest esiste nell'archivio, ogni `itemref` della spine è nel manifest
+/// - EPUB 2: NCX presente e referenziato da `spine@toc`; EPUB 3: documento nav presente
+///
+/// # Returns
+/// Il report con errori e avvisi; `Err` solo se il file non è leggibile
+pub fn validate_epub(epub_path: &Path) -> RitmoResult<ValidationReport> {
+    let mut report = ValidationReport::default();
+
+    let file = File::open(epub_path)?;
+    let mut archive = match ZipArchive::new(BufReader::new(file)) {
+        Ok(archive) => archive,
+        Err(e) => {
+            report.error(format!("il file non è un archivio ZIP valido: {}", e));
+            return Ok(report);
+        }
+    };
+
+    check_mimetype(&mut archive, &mut report);
+
+    let names: HashSet<String> = archive.file_names().map(|n| n.to_string()).collect();
+
+    let Some(opf_path) = check_container(&mut archive, &names, &mut report) else {
+        return Ok(report);
+    };
+
+    let opf = match read_entry(&mut archive, &opf_path) {
+        Ok(content) => content,
+        Err(e) => {
+            report.error(format!("impossibile leggere l'OPF '{}': {}", opf_path, e));
+            return Ok(report);
+        }
+    };
+
+    let package = match parse_package(&opf) {
+        Ok(package) => package,
+        Err(e) => {
+            report.error(format!("OPF '{}' non è XML valido: {}", opf_path, e));
+            return Ok(report);
+        }
+    };
+
+    check_package(&package, &opf_path, &names, &mut report);
+
+    Ok(report)
+}
+
+fn check_mimetype(archive: &mut ZipArchive<BufReader<File>>, report: &mut ValidationReport) {
+    let first = match archive.by_index(0) {
+        Ok(mut entry) => {
+            let mut content = String::new();
+            let _ = entry.read_to_string(&mut content);
+            Some((entry.name().to_string(), entry.compression(), content))
+        }
+        Err(_) => None,
+    };
+
+    match first {
+        Some((name, compression, content)) if name == "mimetype" => {
+            if compression != CompressionMethod::Stored {
+                report.error("la voce 'mimetype' è compressa (deve essere salvata senza compressione)");
+            }
+            if content.trim() != EPUB_MIMETYPE {
+                report.error(format!(
+                    "contenuto di 'mimetype' non valido: '{}' (atteso '{}')",
+                    content.trim(),
+                    EPUB_MIMETYPE
+                ));
+            } else if content != EPUB_MIMETYPE {
+                report.warning("'mimetype' contiene spazi o a capo in eccesso");
+            }
+        }
+        _ => {
+            if archive.by_name("mimetype").is_ok() {
+                report.error("'mimetype' non è la prima voce dell'archivio");
+            } else {
+                report.error("voce 'mimetype' mancante");
+            }
+        }
+    }
+}
+
+/// Verifica `META-INF/container.xml` e restituisce il path dell'OPF
+fn check_container(
+    archive: &mut ZipArchive<BufReader<File>>,
+    names: &HashSet<String>,
+    report: &mut ValidationReport,
+) -> Option<String> {
+    let container = match read_entry(archive, "META-INF/container.xml") {
+        Ok(content) => content,
+        Err(_) => {
+            report.error("META-INF/container.xml mancante");
+            return None;
+        }
+    };
+
+    let mut reader = Reader::from_str(&container);
+    let mut rootfiles: Vec<(String, String)> = Vec::new();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) if local_name(&e) == "rootfile" => {
+                let attrs = attributes(&e);
+                rootfiles.push((
+                    attrs.get("full-path").cloned().unwrap_or_default(),
+                    attrs.get("media-type").cloned().unwrap_or_default(),
+                ));
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                report.error(format!("META-INF/container.xml non è XML valido: {}", e));
+                return None;
+            }
+            _ => {}
+        }
+    }
+
+    let Some((opf_path, _)) = rootfiles
+        .iter()
+        .find(|(path, media_type)| !path.is_empty() && media_type == OPF_MEDIA_TYPE)
+    else {
+        report.error(format!(
+            "META-INF/container.xml non contiene un rootfile di tipo {}",
+            OPF_MEDIA_TYPE
+        ));
+        return None;
+    };
+
+    if !names.contains(opf_path) {
+        report.error(format!(
+            "l'OPF '{}' indicato in container.xml non esiste nell'archivio",
+            opf_path
+        ));
+        return None;
+    }
+
+    Some(opf_path.clone())
+}
+
+fn check_package(
+    package: &OpfPackage,
+    opf_path: &str,
+    names: &HashSet<String>,
+    report: &mut ValidationReport,
+) {
+    // Identificatori
+    match &package.unique_identifier {
+        Some(id) if !package.identifier_ids.contains(id) => report.error(format!(
+            "unique-identifier '{}' non corrisponde a nessun dc:identifier",
+            id
+        )),
+        Some(_) => {}
+        None => report.error("attributo unique-identifier mancante in <package>"),
+    }
+    if !package.has_identifier {
+        report.error("nessun dc:identifier nei metadati");
+    }
+    if !package.has_title {
+        report.error("dc:title mancante nei metadati");
+    }
+    if !package.has_language {
+        report.error("dc:language mancante nei metadati");
+    }
+
+    // Manifest: id unici e file esistenti
+    let opf_dir = opf_path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+    let mut ids: HashMap<&str, &ManifestItem> = HashMap::new();
+    for item in &package.manifest {
+        if item.id.is_empty() {
+            report.error(format!("voce del manifest senza id: '{}'", item.href));
+        } else if ids.insert(item.id.as_str(), item).is_some() {
+            report.error(format!("id duplicato nel manifest: '{}'", item.id));
+        }
+
+        if item.href.contains("://") {
+            continue; // Risorse remote (EPUB 3)
+        }
+        let path = resolve_href(opf_dir, &item.href);
+        if !names.contains(&path) {
+            report.error(format!(
+                "la voce del manifest '{}' punta a un file inesistente: {}",
+                item.id, path
+            ));
+        }
+    }
+
+    // Spine
+    if !package.has_spine {
+        report.error("<spine> mancante nell'OPF");
+    } else if package.spine.is_empty() {
+        report.error("la spine non contiene itemref");
+    }
+    for idref in &package.spine {
+        if !ids.contains_key(idref.as_str()) {
+            report.error(format!("itemref '{}' della spine non esiste nel manifest", idref));
+        }
+    }
+
+    // Navigazione: NCX (EPUB 2) o nav (EPUB 3)
+    let is_epub3 = package
+        .version
+        .as_deref()
+        .is_some_and(|v| v.trim().starts_with('3'));
+    let has_nav = package
+        .manifest
+        .iter()
+        .any(|i| i.properties.split_whitespace().any(|p| p == "nav"));
+    let toc_item = package.spine_toc.as_deref().and_then(|toc| ids.get(toc));
+
+    if let Some(toc) = &package.spine_toc {
+        match toc_item {
+            Some(item) if item.media_type != NCX_MEDIA_TYPE => report.error(format!(
+                "spine@toc '{}' non punta a un documento NCX ({})",
+                toc, item.media_type
+            )),
+            Some(_) => {}
+            None => report.error(format!("spine@toc '{}' non esiste nel manifest", toc)),
+        }
+    }
+
+    if is_epub3 {
+        if !has_nav {
+            report.error("EPUB 3 senza documento di navigazione (item con properties=\"nav\")");
+        }
+    } else if package.spine_toc.is_none() {
+        let has_ncx = package.manifest.iter().any(|i| i.media_type == NCX_MEDIA_TYPE);
+        if has_ncx {
+            report.warning("NCX presente nel manifest ma non referenziato da spine@toc");
+        } else {
+            report.error("EPUB 2 senza NCX (spine@toc mancante)");
+        }
+    }
+}
+
+pub(crate) fn parse_package(opf: &str) -> Result<OpfPackage, quick_xml::Error> {
+    let mut reader = Reader::from_str(opf);
+    let mut package = OpfPackage::default();
+    let mut in_metadata = false;
+
+    loop {
+        let (e, is_empty) = match reader.read_event()? {
+            Event::Start(e) => (e, false),
+            Event::Empty(e) => (e, true),
+            Event::End(e) => {
+                if e.local_name().as_ref() == b"metadata" {
+                    in_metadata = false;
+                }
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+
+        let attrs = attributes(&e);
+        match local_name(&e).as_str() {
+            "package" => {
+                package.version = attrs.get("version").cloned();
+                package.unique_identifier = attrs.get("unique-identifier").cloned();
+            }
+            "metadata" if !is_empty => in_metadata = true,
+            "identifier" if in_metadata => {
+                package.has_identifier = true;
+                if let Some(id) = attrs.get("id") {
+                    package.identifier_ids.push(id.clone());
+                }
+            }
+            "title" if in_metadata => package.has_title = true,
+            "language" if in_metadata => package.has_language = true,
+            "item" => package.manifest.push(ManifestItem {
+                id: attrs.get("id").cloned().unwrap_or_default(),
+                href: attrs.get("href").cloned().unwrap_or_default(),
+                media_type: attrs.get("media-type").cloned().unwrap_or_default(),
+                properties: attrs.get("properties").cloned().unwrap_or_default(),
+            }),
+            "spine" => {
+                package.has_spine = true;
+                package.spine_toc = attrs.get("toc").cloned();
+            }
+            "itemref" => {
+                if let Some(idref) = attrs.get("idref") {
+                    package.spine.push(idref.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(package)
+}
+
+/// Risolve un href del manifest rispetto alla cartella dell'OPF
+///
+/// Decodifica i caratteri `%XX`, rimuove il frammento e normalizza `.` e `..`.
+pub(crate) fn resolve_href(opf_dir: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or(href);
+    let href = percent_decode(href);
+
+    let mut parts: Vec<&str> = if opf_dir.is_empty() {
+        Vec::new()
+    } else {
+        opf_dir.split('/').collect()
+    };
+    for part in href.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let Some(byte) = value
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+pub(crate) fn read_entry(archive: &mut ZipArchive<BufReader<File>>, name: &str) -> Result<String, String> {
+    let mut entry = archive.by_name(name).map_err(|e| e.to_string())?;
+    let mut content = String::new();
+    entry
+        .read_to_string(&mut content)
+        .map_err(|e| e.to_string())?;
+    Ok(content)
+}
+
+fn local_name(e: &BytesStart) -> String {
+    String::from_utf8_lossy(e.local_name().as_ref()).to_string()
+}
+
+fn attributes(e: &BytesStart) -> HashMap<String, String> {
+    e.attributes()
+        .flatten()
+        .filter_map(|attr| {
+            let key = String::from_utf8_lossy(attr.key.local_name().as_ref()).to_string();
+            let value = attr.unescape_value().ok()?.to_string();
+            Some((key, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    const CONTAINER: &str = r#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>"#;
+
+    const OPF_EPUB3: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="uid">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="uid">urn:uuid:1234</dc:identifier>
+    <dc:title>Test</dc:title>
+    <dc:language>it</dc:language>
+  </metadata>
+  <manifest>
+    <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+    <item id="ch1" href="Text/chapter%201.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine>
+    <itemref idref="ch1"/>
+  </spine>
+</package>"#;
+
+    struct EpubSpec<'a> {
+        mimetype_first: bool,
+        mimetype_stored: bool,
+        opf: &'a str,
+        files: &'a [&'a str],
+    }
+
+    fn create_epub(dir: &TempDir, spec: EpubSpec) -> std::path::PathBuf {
+        let path = dir.path().join("test.epub");
+        let mut zip = ZipWriter::new(File::create(&path).unwrap());
+        let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        let mimetype_options = if spec.mimetype_stored { stored } else { deflated };
+
+        if spec.mimetype_first {
+            zip.start_file("mimetype", mimetype_options).unwrap();
+            zip.write_all(EPUB_MIMETYPE.as_bytes()).unwrap();
+        }
+        zip.start_file("META-INF/container.xml", deflated).unwrap();
+        zip.write_all(CONTAINER.as_bytes()).unwrap();
+        zip.start_file("OEBPS/content.opf", deflated).unwrap();
+        zip.write_all(spec.opf.as_bytes()).unwrap();
+        for name in spec.files {
+            zip.start_file(*name, deflated).unwrap();
+            zip.write_all(b"<html/>").unwrap();
+        }
+        if !spec.mimetype_first {
+            zip.start_file("mimetype", mimetype_options).unwrap();
+            zip.write_all(EPUB_MIMETYPE.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+        path
+    }
+
+    fn valid_spec<'a>() -> EpubSpec<'a> {
+        EpubSpec {
+            mimetype_first: true,
+            mimetype_stored: true,
+            opf: OPF_EPUB3,
+            files: &["OEBPS/nav.xhtml", "OEBPS/Text/chapter 1.xhtml"],
+        }
+    }
+
+    #[test]
+    fn test_valid_epub3() {
+        let dir = TempDir::new().unwrap();
+        let path = create_epub(&dir, valid_spec());
+
+        let report = validate_epub(&path).unwrap();
+        assert!(report.is_valid(), "{:?}", report.issues);
+        assert!(report.issues.is_empty());
+    }
+
+    #[test]
+    fn test_mimetype_checks() {
+        let dir = TempDir::new().unwrap();
+        let path = create_epub(
+            &dir,
+            EpubSpec {
+                mimetype_stored: false,
+                ..valid_spec()
+            },
+        );
+        let report = validate_epub(&path).unwrap();
+        assert!(report.error_summary().contains("compressa"));
+
+        let dir = TempDir::new().unwrap();
+        let path = create_epub(
+            &dir,
+            EpubSpec {
+                mimetype_first: false,
+                ..valid_spec()
+            },
+        );
+        let report = validate_epub(&path).unwrap();
+        assert!(report.error_summary().contains("prima voce"));
+    }
+
+    #[test]
+    fn test_manifest_and_spine_references() {
+        let opf = OPF_EPUB3
+            .replace(r#"<itemref idref="ch1"/>"#, r#"<itemref idref="ch1"/><itemref idref="ch2"/>"#);
+        let dir = TempDir::new().unwrap();
+        let path = create_epub(
+            &dir,
+            EpubSpec {
+                opf: &opf,
+                files: &["OEBPS/nav.xhtml"],
+                ..valid_spec()
+            },
+        );
+
+        let report = validate_epub(&path).unwrap();
+        let summary = report.error_summary();
+        assert!(summary.contains("OEBPS/Text/chapter 1.xhtml"), "{}", summary);
+        assert!(summary.contains("itemref 'ch2'"), "{}", summary);
+    }
+
+    #[test]
+    fn test_unique_identifier_and_nav() {
+        let opf = OPF_EPUB3
+            .replace(r#"id="uid""#, r#"id="other""#)
+            .replace(r#" properties="nav""#, "");
+        let dir = TempDir::new().unwrap();
+        let path = create_epub(
+            &dir,
+            EpubSpec {
+                opf: &opf,
+                ..valid_spec()
+            },
+        );
+
+        let report = validate_epub(&path).unwrap();
+        let summary = report.error_summary();
+        assert!(summary.contains("unique-identifier 'uid'"), "{}", summary);
+        assert!(summary.contains("documento di navigazione"), "{}", summary);
+    }
+
+    #[test]
+    fn test_epub2_requires_ncx() {
+        let opf = OPF_EPUB3
+            .replace(r#"version="3.0""#, r#"version="2.0""#)
+            .replace(r#" properties="nav""#, "");
+        let dir = TempDir::new().unwrap();
+        let path = create_epub(
+            &dir,
+            EpubSpec {
+                opf: &opf,
+                ..valid_spec()
+            },
+        );
+        let report = validate_epub(&path).unwrap();
+        assert!(report.error_summary().contains("NCX"));
+
+        let opf = opf
+            .replace(
+                "</manifest>",
+                r#"<item id="ncx" href="toc.ncx" media-type="application/x-dtbncx+xml"/></manifest>"#,
+            )
+            .replace("<spine>", r#"<spine toc="ncx">"#);
+        let dir = TempDir::new().unwrap();
+        let path = create_epub(
+            &dir,
+            EpubSpec {
+                opf: &opf,
+                files: &["OEBPS/nav.xhtml", "OEBPS/Text/chapter 1.xhtml", "OEBPS/toc.ncx"],
+                ..valid_spec()
+            },
+        );
+        let report = validate_epub(&path).unwrap();
+        assert!(report.is_valid(), "{:?}", report.issues);
+    }
+
+    #[test]
+    fn test_not_a_zip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("fake.epub");
+        std::fs::write(&path, b"not a zip").unwrap();
+
+        let report = validate_epub(&path).unwrap();
+        assert!(!report.is_valid());
+    }
+
+    #[test]
+    fn test_new_errors_since() {
+        let mut before = ValidationReport::default();
+        before.error("vecchio");
+        let mut after = before.clone();
+        after.error("nuovo");
+
+        let new_errors = after.new_errors_since(&before);
+        assert_eq!(new_errors.len(), 1);
+        assert_eq!(new_errors[0].message, "nuovo");
+    }
+
+    #[test]
+    fn test_resolve_href() {
+        assert_eq!(resolve_href("OEBPS", "Text/a%20b.xhtml#x"), "OEBPS/Text/a b.xhtml");
+        assert_eq!(resolve_href("OEBPS/sub", "../img.png"), "OEBPS/img.png");
+        assert_eq!(resolve_href("", "content.xhtml"), "content.xhtml");
+    }
+}
@@ -0,0 +1,84 @@
+use super::{parse_isbn, ExtractedMetadata, CONFIDENCE_METADATA};
+use crate::comic_info::read_comic_archive;
+use ritmo_errors::RitmoResult;
+use std::path::Path;
+
+/// Legge i metadati di un fumetto (CBZ, o CBR in formato ZIP) da `ComicInfo.xml`
+///
+/// Senza `ComicInfo.xml` viene restituito solo il numero di pagine.
+pub fn read_comic_metadata(path: &Path) -> RitmoResult<ExtractedMetadata> {
+    let archive = read_comic_archive(path)?;
+    let mut meta = ExtractedMetadata::default();
+
+    if archive.page_count > 0 {
+        meta.pages = Some(archive.page_count);
+        meta.score("book.pages", 1.0);
+    }
+
+    let Some(info) = archive.info else {
+        return Ok(meta);
+    };
+
+    meta.series_index = info.series_index();
+    if let Some(series) = info.series.clone() {
+        meta.series = Some(series);
+        meta.score("book.series", 0.90);
+    }
+
+    match (&info.title, &meta.series, &info.number) {
+        (Some(title), _, _) => {
+            meta.title = Some(title.clone());
+            meta.score("book.title", CONFIDENCE_METADATA);
+        }
+        // Albi senza titolo: "Serie #numero"
+        (None, Some(series), Some(number)) => {
+            meta.title = Some(format!("{} #{}", series, number));
+            meta.score("book.title", 0.70);
+        }
+        _ => {}
+    }
+
+    for (name, role) in &info.people {
+        meta.add_person(name, role);
+    }
+    if !meta.people.is_empty() {
+        meta.score("contents[0].people", 0.90);
+    }
+    if !meta.book_people.is_empty() {
+        meta.score("book.people", 0.90);
+    }
+
+    if let Some(publisher) = info.publisher {
+        meta.publisher = Some(publisher);
+        meta.score("book.publisher", 0.90);
+    }
+    if let Some(year) = info.year {
+        meta.year = Some(year);
+        meta.score("book.year", 0.90);
+    }
+    if let Some(summary) = info.summary {
+        meta.notes = Some(summary);
+        meta.score("book.notes", 0.90);
+    }
+    if let Some(isbn) = info.gtin.as_deref().and_then(parse_isbn) {
+        meta.isbn = Some(isbn);
+        meta.score("book.isbn", CONFIDENCE_METADATA);
+    }
+    if let Some(language) = info.language.as_deref() {
+        meta.add_language(language, "language_role.actual");
+        if !meta.languages.is_empty() {
+            meta.score("contents[0].languages", 0.90);
+        }
+    }
+    for tag in info.genres.iter().chain(info.tags.iter()) {
+        meta.add_tag(tag);
+    }
+    if !meta.tags.is_empty() {
+        meta.score("book.tags", 0.80);
+    }
+    if let Some(pages) = info.page_count {
+        meta.pages.get_or_insert(pages);
+    }
+
+    Ok(meta)
+}
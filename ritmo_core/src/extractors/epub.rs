@@ -0,0 +1,344 @@
+use super::{
+    parse_isbn, parse_year, ExtractedMetadata, CONFIDENCE_INFERRED, CONFIDENCE_METADATA,
+};
+use crate::epub_utils::extract_opf;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use ritmo_errors::{RitmoErr, RitmoResult};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Legge i metadati dall'OPF di un EPUB
+pub fn read_epub_metadata(epub_path: &Path) -> RitmoResult<ExtractedMetadata> {
+    let opf = extract_opf(epub_path)?;
+    parse_opf(&opf)
+}
+
+/// Persona letta da dc:creator/dc:contributor, prima della risoluzione dei `refines`
+struct OpfPerson {
+    id: Option<String>,
+    name: String,
+    role: Option<String>,
+    is_creator: bool,
+}
+
+/// Analizza il contenuto di un file OPF (EPUB2 e EPUB3)
+///
+/// Legge gli elementi Dublin Core, i ruoli MARC (`opf:role` o `refines`),
+/// le serie Calibre (`calibre:series`) e le collezioni EPUB3
+/// (`belongs-to-collection` + `group-position`).
+pub fn parse_opf(xml: &str) -> RitmoResult<ExtractedMetadata> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut meta = ExtractedMetadata::default();
+    let mut people: Vec<OpfPerson> = Vec::new();
+    // refines id → (property → valore)
+    let mut refines: HashMap<String, HashMap<String, String>> = HashMap::new();
+    // belongs-to-collection: (id, nome), scelta dopo aver letto i refines
+    let mut collections: Vec<(Option<String>, String)> = Vec::new();
+
+    // Elemento corrente: nome locale, attributi, testo accumulato
+    let mut current: Option<(String, HashMap<String, String>, String)> = None;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = local_name(&e);
+                if is_metadata_element(&name) {
+                    current = Some((name, attributes(&e), String::new()));
+                }
+            }
+            Ok(Event::Empty(e)) if local_name(&e) == "meta" => {
+                read_calibre_meta(&attributes(&e), &mut meta);
+            }
+            Ok(Event::Text(e)) => {
+                if let Some((_, _, text)) = current.as_mut() {
+                    let value = e
+                        .unescape()
+                        .map_err(|e| RitmoErr::Generic(format!("Errore parsing OPF: {}", e)))?;
+                    text.push_str(&value);
+                }
+            }
+            Ok(Event::End(_)) => {
+                let Some((name, attrs, text)) = current.take() else {
+                    continue;
+                };
+                let text = text.trim().to_string();
+                match name.as_str() {
+                    "title" if meta.title.is_none() && !text.is_empty() => {
+                        meta.title = Some(text);
+                        meta.score("book.title", CONFIDENCE_METADATA);
+                    }
+                    "creator" | "contributor" => people.push(OpfPerson {
+                        id: attrs.get("id").cloned(),
+                        name: text,
+                        role: attrs.get("role").cloned(),
+                        is_creator: name == "creator",
+                    }),
+                    "publisher" if meta.publisher.is_none() && !text.is_empty() => {
+                        meta.publisher = Some(text);
+                        meta.score("book.publisher", CONFIDENCE_INFERRED);
+                    }
+                    "date" if meta.year.is_none() => {
+                        if let Some(year) = parse_year(&text) {
+                            meta.year = Some(year);
+                            meta.score("book.year", 0.90);
+                        }
+                    }
+                    "identifier" if meta.isbn.is_none() => {
+                        let is_isbn = attrs
+                            .get("scheme")
+                            .is_some_and(|s| s.eq_ignore_ascii_case("isbn"))
+                            || text.to_lowercase().starts_with("urn:isbn:");
+                        if is_isbn {
+                            if let Some(isbn) = parse_isbn(&text) {
+                                meta.isbn = Some(isbn);
+                                meta.score("book.isbn", CONFIDENCE_METADATA);
+                            }
+                        }
+                    }
+                    "language" => {
+                        meta.add_language(&text, "language_role.actual");
+                        if !meta.languages.is_empty() {
+                            meta.score("contents[0].languages", 0.90);
+                        }
+                    }
+                    "subject" => {
+                        meta.add_tag(&text);
+                        if !meta.tags.is_empty() {
+                            meta.score("book.tags", CONFIDENCE_INFERRED);
+                        }
+                    }
+                    "meta" => {
+                        if let Some(property) = attrs.get("property") {
+                            if let Some(target) = attrs.get("refines") {
+                                refines
+                                    .entry(target.trim_start_matches('#').to_string())
+                                    .or_default()
+                                    .insert(property.clone(), text);
+                            } else if property == "belongs-to-collection" && !text.is_empty() {
+                                collections.push((attrs.get("id").cloned(), text));
+                            }
+                        } else {
+                            read_calibre_meta(&attrs, &mut meta);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                return Err(RitmoErr::Generic(format!("Errore parsing OPF: {}", e)));
+            }
+            _ => {}
+        }
+    }
+
+    // Collezione EPUB3: la prima di tipo "series" (o senza tipo); ha la precedenza su Calibre
+    let series_collection = collections.into_iter().find(|(id, _)| {
+        id.as_ref()
+            .and_then(|id| refines.get(id))
+            .and_then(|props| props.get("collection-type"))
+            .is_none_or(|kind| kind == "series")
+    });
+    if let Some((id, name)) = series_collection {
+        meta.series = Some(name);
+        meta.score("book.series", 0.90);
+        if let Some(position) = id
+            .and_then(|id| refines.get(&id))
+            .and_then(|props| props.get("group-position"))
+            .and_then(|p| parse_series_index(p))
+        {
+            meta.series_index = Some(position);
+        }
+    }
+
+    // Ruoli: attributo opf:role, altrimenti <meta refines="#id" property="role">
+    for person in people {
+        let role = person.role.or_else(|| {
+            person
+                .id
+                .as_ref()
+                .and_then(|id| refines.get(id))
+                .and_then(|props| props.get("role").cloned())
+        });
+        let (role_key, confidence) = match role {
+            Some(code) => (marc_role_to_ritmo(&code), 0.90),
+            None if person.is_creator => ("role.author", 0.70),
+            None => ("role.contributor", 0.70),
+        };
+        meta.add_person(&person.name, role_key);
+        let key = match role_key {
+            "role.editor" | "role.preface" => "book.people",
+            _ => "contents[0].people",
+        };
+        meta.score(key, confidence);
+    }
+
+    Ok(meta)
+}
+
+/// Serie Calibre: `<meta name="calibre:series" content="..."/>`
+fn read_calibre_meta(attrs: &HashMap<String, String>, meta: &mut ExtractedMetadata) {
+    let (Some(name), Some(content)) = (attrs.get("name"), attrs.get("content")) else {
+        return;
+    };
+    match name.as_str() {
+        "calibre:series" if meta.series.is_none() && !content.trim().is_empty() => {
+            meta.series = Some(content.trim().to_string());
+            meta.score("book.series", 0.90);
+        }
+        "calibre:series_index" if meta.series_index.is_none() => {
+            meta.series_index = parse_series_index(content);
+        }
+        _ => {}
+    }
+}
+
+/// Indice di serie, anche in forma decimale (`2.0`)
+fn parse_series_index(value: &str) -> Option<i64> {
+    value.trim().parse::<f64>().ok().map(|v| v as i64)
+}
+
+/// Maps MARC relator codes to Ritmo role keys (inverse of `map_ritmo_role_to_opf`)
+pub(crate) fn marc_role_to_ritmo(code: &str) -> &'static str {
+    match code.trim().to_lowercase().as_str() {
+        "aut" => "role.author",
+        "trl" => "role.translator",
+        "edt" => "role.editor",
+        "ill" => "role.illustrator",
+        "nrt" => "role.narrator",
+        "aui" => "role.preface",
+        _ => "role.contributor",
+    }
+}
+
+fn is_metadata_element(name: &str) -> bool {
+    matches!(
+        name,
+        "title"
+            | "creator"
+            | "contributor"
+            | "publisher"
+            | "date"
+            | "identifier"
+            | "language"
+            | "subject"
+            | "meta"
+    )
+}
+
+/// Nome dell'elemento senza prefisso (`dc:title` → `title`)
+fn local_name(e: &BytesStart) -> String {
+    String::from_utf8_lossy(e.local_name().as_ref()).to_string()
+}
+
+/// Attributi dell'elemento, con chiavi senza prefisso (`opf:role` → `role`)
+fn attributes(e: &BytesStart) -> HashMap<String, String> {
+    e.attributes()
+        .flatten()
+        .filter_map(|attr| {
+            let key = String::from_utf8_lossy(attr.key.local_name().as_ref()).to_string();
+            let value = attr.unescape_value().ok()?.to_string();
+            Some((key, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPF_EPUB2: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:opf="http://www.idpf.org/2007/opf">
+    <dc:title>Il nome della rosa</dc:title>
+    <dc:creator opf:role="aut">Umberto Eco</dc:creator>
+    <dc:contributor opf:role="edt">Mario Rossi</dc:contributor>
+    <dc:publisher>Bompiani</dc:publisher>
+    <dc:date>1980-09-01</dc:date>
+    <dc:identifier opf:scheme="ISBN">978-88-452-0000-7</dc:identifier>
+    <dc:language>it</dc:language>
+    <dc:subject>Giallo</dc:subject>
+    <meta name="calibre:series" content="Misteri"/>
+    <meta name="calibre:series_index" content="2.0"/>
+  </metadata>
+</package>"#;
+
+    const OPF_EPUB3: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:title>Foundation</dc:title>
+    <dc:creator id="c1">Isaac Asimov</dc:creator>
+    <meta refines="#c1" property="role" scheme="marc:relators">aut</meta>
+    <dc:contributor id="c2">Cesare Scaglia</dc:contributor>
+    <meta refines="#c2" property="role" scheme="marc:relators">trl</meta>
+    <dc:identifier>urn:isbn:9780553293357</dc:identifier>
+    <dc:language>en-US</dc:language>
+    <meta property="belongs-to-collection" id="coll">Foundation Series</meta>
+    <meta refines="#coll" property="group-position">1</meta>
+    <meta property="dcterms:modified">2020-01-01T00:00:00Z</meta>
+  </metadata>
+</package>"##;
+
+    #[test]
+    fn test_parse_opf_epub2() {
+        let meta = parse_opf(OPF_EPUB2).unwrap();
+
+        assert_eq!(meta.title.as_deref(), Some("Il nome della rosa"));
+        assert_eq!(meta.publisher.as_deref(), Some("Bompiani"));
+        assert_eq!(meta.year, Some(1980));
+        assert_eq!(meta.isbn.as_deref(), Some("9788845200007"));
+        assert_eq!(meta.series.as_deref(), Some("Misteri"));
+        assert_eq!(meta.series_index, Some(2));
+        assert_eq!(meta.tags, vec!["Giallo"]);
+        assert_eq!(meta.languages[0].code, "it");
+        assert_eq!(meta.people.len(), 1);
+        assert_eq!(meta.people[0].role, "role.author");
+        assert_eq!(meta.book_people[0].name, "Mario Rossi");
+        assert_eq!(meta.book_people[0].role, "role.editor");
+        assert_eq!(meta.confidence["contents[0].people"], 0.90);
+    }
+
+    #[test]
+    fn test_parse_opf_epub3_refines() {
+        let meta = parse_opf(OPF_EPUB3).unwrap();
+
+        assert_eq!(meta.title.as_deref(), Some("Foundation"));
+        assert_eq!(meta.isbn.as_deref(), Some("9780553293357"));
+        assert_eq!(meta.series.as_deref(), Some("Foundation Series"));
+        assert_eq!(meta.series_index, Some(1));
+        assert_eq!(meta.languages[0].code, "en");
+        assert_eq!(meta.people.len(), 2);
+        assert_eq!(meta.people[0].role, "role.author");
+        assert_eq!(meta.people[1].name, "Cesare Scaglia");
+        assert_eq!(meta.people[1].role, "role.translator");
+    }
+
+    #[test]
+    fn test_parse_opf_skips_set_collections() {
+        let xml = r##"<package version="3.0"><metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+            <meta property="belongs-to-collection" id="set">Omnibus</meta>
+            <meta refines="#set" property="collection-type">set</meta>
+            <meta property="belongs-to-collection" id="s">Ciclo</meta>
+            <meta refines="#s" property="collection-type">series</meta>
+            <meta refines="#s" property="group-position">4</meta>
+        </metadata></package>"##;
+        let meta = parse_opf(xml).unwrap();
+
+        assert_eq!(meta.series.as_deref(), Some("Ciclo"));
+        assert_eq!(meta.series_index, Some(4));
+    }
+
+    #[test]
+    fn test_parse_opf_creator_without_role() {
+        let xml = r#"<package><metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+            <dc:title>Senza ruolo</dc:title><dc:creator>Anonimo</dc:creator>
+        </metadata></package>"#;
+        let meta = parse_opf(xml).unwrap();
+
+        assert_eq!(meta.people[0].role, "role.author");
+        assert_eq!(meta.confidence["contents[0].people"], 0.70);
+    }
+}
@@ -0,0 +1,427 @@
+use super::{parse_isbn, parse_year, ExtractedMetadata, CONFIDENCE_METADATA};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use ritmo_errors::{RitmoErr, RitmoResult};
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::Path;
+use zip::ZipArchive;
+
+/// Legge i metadati da un file FB2 o FB2.ZIP
+pub fn read_fb2_metadata(fb2_path: &Path) -> RitmoResult<ExtractedMetadata> {
+    let is_zip = fb2_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("zip"));
+
+    let data = if is_zip {
+        read_fb2_from_zip(fb2_path)?
+    } else {
+        fs::read(fb2_path)?
+    };
+
+    parse_fb2(&decode_fb2(&data))
+}
+
+/// Estrae il primo file `.fb2` contenuto in un archivio ZIP
+fn read_fb2_from_zip(zip_path: &Path) -> RitmoResult<Vec<u8>> {
+    let file = File::open(zip_path)?;
+    let mut archive = ZipArchive::new(BufReader::new(file)).map_err(|e| {
+        RitmoErr::Generic(format!("Impossibile aprire FB2.ZIP come ZIP: {}", e))
+    })?;
+
+    let name = archive
+        .file_names()
+        .find(|n| n.to_lowercase().ends_with(".fb2"))
+        .map(|n| n.to_string())
+        .ok_or_else(|| {
+            RitmoErr::Generic(format!(
+                "Nessun file .fb2 in {}",
+                zip_path.display()
+            ))
+        })?;
+
+    let mut entry = archive
+        .by_name(&name)
+        .map_err(|e| RitmoErr::Generic(format!("Errore lettura {}: {}", name, e)))?;
+    let mut data = Vec::new();
+    entry
+        .read_to_end(&mut data)
+        .map_err(|e| RitmoErr::Generic(format!("Errore lettura {}: {}", name, e)))?;
+    Ok(data)
+}
+
+/// Decodifica un FB2 secondo l'encoding dichiarato nel prologo XML
+///
+/// Molti FB2 (soprattutto russi) sono in `windows-1251`; in assenza di
+/// dichiarazione si assume UTF-8.
+fn decode_fb2(data: &[u8]) -> String {
+    let prolog_end = data
+        .iter()
+        .position(|b| *b == b'>')
+        .unwrap_or(0)
+        .min(200);
+    let prolog = String::from_utf8_lossy(&data[..prolog_end]).to_lowercase();
+
+    let encoding = prolog
+        .find("encoding=")
+        .map(|start| &prolog[start + "encoding=".len()..])
+        .and_then(|rest| {
+            let quote = rest.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+            rest[quote.len_utf8()..].split(quote).next()
+        })
+        .and_then(|label| encoding_rs::Encoding::for_label(label.trim().as_bytes()))
+        .unwrap_or(encoding_rs::UTF_8);
+
+    let (text, _, _) = encoding.decode(data);
+    text.into_owned()
+}
+
+/// Sezione di `<description>` in cui si trova il parser
+#[derive(Clone, Copy, PartialEq)]
+enum Section {
+    None,
+    TitleInfo,
+    SrcTitleInfo,
+    PublishInfo,
+}
+
+/// Nome di una persona FB2 (`<author>`/`<translator>`) in costruzione
+#[derive(Default)]
+struct Fb2Person {
+    first: String,
+    middle: String,
+    last: String,
+    nickname: String,
+}
+
+impl Fb2Person {
+    fn full_name(&self) -> String {
+        let parts: Vec<&str> = [&self.first, &self.middle, &self.last]
+            .iter()
+            .map(|p| p.trim())
+            .filter(|p| !p.is_empty())
+            .collect();
+        if parts.is_empty() {
+            self.nickname.trim().to_string()
+        } else {
+            parts.join(" ")
+        }
+    }
+}
+
+/// Analizza la `<description>` di un documento FB2
+///
+/// - `<title-info>`: titolo, autori, traduttori, generi, parole chiave,
+///   data dell'opera, lingua (`lang`, `src-lang`) e serie (`<sequence>`)
+/// - `<src-title-info>`: titolo originale
+/// - `<publish-info>`: editore, anno, ISBN e serie editoriale
+pub fn parse_fb2(xml: &str) -> RitmoResult<ExtractedMetadata> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut meta = ExtractedMetadata::default();
+    let mut section = Section::None;
+    let mut stack: Vec<String> = Vec::new();
+    let mut person: Option<Fb2Person> = None;
+    let mut publish_series: Option<(String, Option<i64>)> = None;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = local_name(&e);
+                match name.as_str() {
+                    "title-info" => section = Section::TitleInfo,
+                    "src-title-info" => section = Section::SrcTitleInfo,
+                    "publish-info" => section = Section::PublishInfo,
+                    "document-info" => section = Section::None,
+                    "author" | "translator" if section == Section::TitleInfo => {
+                        person = Some(Fb2Person::default());
+                    }
+                    "sequence" => read_sequence(&e, section, &mut meta, &mut publish_series),
+                    "date" if section == Section::TitleInfo => read_date_value(&e, &mut meta),
+                    // Il corpo del libro non contiene metadati
+                    "body" => break,
+                    _ => {}
+                }
+                stack.push(name);
+            }
+            Ok(Event::Empty(e)) => match local_name(&e).as_str() {
+                "sequence" => read_sequence(&e, section, &mut meta, &mut publish_series),
+                "date" if section == Section::TitleInfo => read_date_value(&e, &mut meta),
+                _ => {}
+            },
+            Ok(Event::Text(e)) => {
+                let text = e
+                    .unescape()
+                    .map_err(|e| RitmoErr::Generic(format!("Errore parsing FB2: {}", e)))?;
+                let text = text.trim();
+                let element = stack.last().map(String::as_str).unwrap_or_default();
+
+                if let Some(p) = person.as_mut() {
+                    match element {
+                        "first-name" => p.first.push_str(text),
+                        "middle-name" => p.middle.push_str(text),
+                        "last-name" => p.last.push_str(text),
+                        "nickname" => p.nickname.push_str(text),
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                match (section, element) {
+                    (Section::TitleInfo, "book-title") if meta.title.is_none() => {
+                        meta.title = Some(text.to_string());
+                        meta.score("book.title", CONFIDENCE_METADATA);
+                    }
+                    (Section::TitleInfo, "genre") => {
+                        meta.add_tag(text);
+                        meta.score("book.tags", 0.70);
+                    }
+                    (Section::TitleInfo, "keywords") => {
+                        for keyword in text.split(',') {
+                            meta.add_tag(keyword);
+                        }
+                        meta.score("book.tags", 0.70);
+                    }
+                    (Section::TitleInfo, "date") if meta.content_year.is_none() => {
+                        if let Some(year) = parse_year(text) {
+                            meta.content_year = Some(year);
+                            meta.score("contents[0].year", 0.90);
+                        }
+                    }
+                    (Section::TitleInfo, "lang") => {
+                        meta.add_language(text, "language_role.actual");
+                        meta.score("contents[0].languages", 0.90);
+                    }
+                    (Section::TitleInfo, "src-lang") => {
+                        meta.add_language(text, "language_role.original");
+                        meta.score("contents[0].languages", 0.90);
+                    }
+                    (Section::SrcTitleInfo, "book-title") if meta.original_title.is_none() => {
+                        meta.original_title = Some(text.to_string());
+                        meta.score("book.original_title", 0.90);
+                    }
+                    (Section::PublishInfo, "publisher") if meta.publisher.is_none() => {
+                        meta.publisher = Some(text.to_string());
+                        meta.score("book.publisher", 0.90);
+                    }
+                    (Section::PublishInfo, "year") if meta.year.is_none() => {
+                        if let Some(year) = parse_year(text) {
+                            meta.year = Some(year);
+                            meta.score("book.year", 0.90);
+                        }
+                    }
+                    (Section::PublishInfo, "isbn") if meta.isbn.is_none() => {
+                        if let Some(isbn) = parse_isbn(text) {
+                            meta.isbn = Some(isbn);
+                            meta.score("book.isbn", CONFIDENCE_METADATA);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Event::End(_)) => {
+                let name = stack.pop().unwrap_or_default();
+                match name.as_str() {
+                    "author" | "translator" => {
+                        if let Some(p) = person.take() {
+                            let role = if name == "author" {
+                                "role.author"
+                            } else {
+                                "role.translator"
+                            };
+                            meta.add_person(&p.full_name(), role);
+                            meta.score("contents[0].people", 0.90);
+                        }
+                    }
+                    "title-info" | "src-title-info" | "publish-info" => section = Section::None,
+                    "description" => break,
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                return Err(RitmoErr::Generic(format!("Errore parsing FB2: {}", e)));
+            }
+            _ => {}
+        }
+    }
+
+    // La serie editoriale di publish-info è usata solo in assenza di quella dell'opera
+    if meta.series.is_none() {
+        if let Some((name, number)) = publish_series {
+            meta.series = Some(name);
+            meta.series_index = number;
+            meta.score("book.series", 0.75);
+        }
+    }
+
+    Ok(meta)
+}
+
+/// `<sequence name="..." number="..."/>` (serie dell'opera o dell'editore)
+fn read_sequence(
+    e: &BytesStart,
+    section: Section,
+    meta: &mut ExtractedMetadata,
+    publish_series: &mut Option<(String, Option<i64>)>,
+) {
+    let name = attribute(e, "name").filter(|n| !n.trim().is_empty());
+    let number = attribute(e, "number").and_then(|n| n.trim().parse::<i64>().ok());
+    let Some(name) = name else {
+        return;
+    };
+
+    match section {
+        Section::TitleInfo if meta.series.is_none() => {
+            meta.series = Some(name.trim().to_string());
+            meta.series_index = number;
+            meta.score("book.series", 0.85);
+        }
+        Section::PublishInfo if publish_series.is_none() => {
+            *publish_series = Some((name.trim().to_string(), number));
+        }
+        _ => {}
+    }
+}
+
+/// `<date value="2005-01-01">2005</date>`: l'attributo `value` è più preciso del testo
+fn read_date_value(e: &BytesStart, meta: &mut ExtractedMetadata) {
+    if meta.content_year.is_some() {
+        return;
+    }
+    if let Some(year) = attribute(e, "value").as_deref().and_then(parse_year) {
+        meta.content_year = Some(year);
+        meta.score("contents[0].year", 0.90);
+    }
+}
+
+fn local_name(e: &BytesStart) -> String {
+    String::from_utf8_lossy(e.local_name().as_ref()).to_string()
+}
+
+fn attribute(e: &BytesStart, name: &str) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|attr| attr.key.local_name().as_ref() == name.as_bytes())
+        .and_then(|attr| attr.unescape_value().ok())
+        .map(|v| v.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const FB2: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<FictionBook xmlns="http://www.gribuser.ru/xml/fictionbook/2.0" xmlns:l="http://www.w3.org/1999/xlink">
+  <description>
+    <title-info>
+      <genre>sf_fantasy</genre>
+      <author>
+        <first-name>Arkady</first-name>
+        <middle-name>Natanovich</middle-name>
+        <last-name>Strugatsky</last-name>
+      </author>
+      <author><first-name>Boris</first-name><last-name>Strugatsky</last-name></author>
+      <book-title>Picnic &amp; Roadside</book-title>
+      <keywords>zona, stalker</keywords>
+      <date value="1972-01-01">1972</date>
+      <lang>en</lang>
+      <src-lang>ru</src-lang>
+      <translator><first-name>Antonina</first-name><last-name>Bouis</last-name></translator>
+      <sequence name="Noon Universe" number="7"/>
+    </title-info>
+    <src-title-info>
+      <book-title>Пикник на обочине</book-title>
+    </src-title-info>
+    <document-info>
+      <author><nickname>scanner</nickname></author>
+      <date>2010</date>
+    </document-info>
+    <publish-info>
+      <book-name>Roadside Picnic</book-name>
+      <publisher>Chicago Review Press</publisher>
+      <year>2012</year>
+      <isbn>978-1-61374-341-6</isbn>
+      <sequence name="Rediscovered Classics" number="3"/>
+    </publish-info>
+  </description>
+  <body><section><p>Text</p></section></body>
+</FictionBook>"#;
+
+    #[test]
+    fn test_parse_fb2() {
+        let meta = parse_fb2(FB2).unwrap();
+
+        assert_eq!(meta.title.as_deref(), Some("Picnic & Roadside"));
+        assert_eq!(meta.original_title.as_deref(), Some("Пикник на обочине"));
+        assert_eq!(meta.people.len(), 3);
+        assert_eq!(meta.people[0].name, "Arkady Natanovich Strugatsky");
+        assert_eq!(meta.people[1].name, "Boris Strugatsky");
+        assert_eq!(meta.people[2].name, "Antonina Bouis");
+        assert_eq!(meta.people[2].role, "role.translator");
+        assert_eq!(meta.content_year, Some(1972));
+        assert_eq!(meta.year, Some(2012));
+        assert_eq!(meta.publisher.as_deref(), Some("Chicago Review Press"));
+        assert_eq!(meta.isbn.as_deref(), Some("9781613743416"));
+        assert_eq!(meta.series.as_deref(), Some("Noon Universe"));
+        assert_eq!(meta.series_index, Some(7));
+        assert_eq!(meta.tags, vec!["sf_fantasy", "zona", "stalker"]);
+        assert_eq!(meta.languages.len(), 2);
+        assert_eq!(meta.languages[1].code, "ru");
+        assert_eq!(meta.languages[1].role, "language_role.original");
+        assert_eq!(meta.confidence["book.series"], 0.85);
+    }
+
+    #[test]
+    fn test_parse_fb2_publisher_series_fallback() {
+        let xml = r#"<FictionBook><description>
+            <title-info><book-title>T</book-title></title-info>
+            <publish-info><sequence name="Oscar" number="12"/></publish-info>
+        </description></FictionBook>"#;
+        let meta = parse_fb2(xml).unwrap();
+
+        assert_eq!(meta.series.as_deref(), Some("Oscar"));
+        assert_eq!(meta.series_index, Some(12));
+        assert_eq!(meta.confidence["book.series"], 0.75);
+    }
+
+    #[test]
+    fn test_decode_fb2_windows_1251() {
+        let (encoded, _, _) = encoding_rs::WINDOWS_1251.encode("Пикник");
+        let mut data =
+            br#"<?xml version="1.0" encoding="windows-1251"?><FictionBook><description><title-info><book-title>"#
+                .to_vec();
+        data.extend_from_slice(&encoded);
+        data.extend_from_slice(b"</book-title></title-info></description></FictionBook>");
+
+        let meta = parse_fb2(&decode_fb2(&data)).unwrap();
+        assert_eq!(meta.title.as_deref(), Some("Пикник"));
+    }
+
+    #[test]
+    fn test_decode_fb2_unquoted_encoding() {
+        // Virgolette non XML nel prologo: si assume UTF-8 invece di andare in panic
+        let data = "<?xml version=\"1.0\" encoding=«utf-8»?><FictionBook><description><title-info>\
+                    <book-title>Пикник</book-title></title-info></description></FictionBook>";
+
+        let meta = parse_fb2(&decode_fb2(data.as_bytes())).unwrap();
+        assert_eq!(meta.title.as_deref(), Some("Пикник"));
+    }
+
+    #[test]
+    fn test_read_fb2_zip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("book.fb2.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        zip.start_file("book.fb2", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(FB2.as_bytes()).unwrap();
+        zip.finish().unwrap();
+
+        let meta = read_fb2_metadata(&path).unwrap();
+        assert_eq!(meta.title.as_deref(), Some("Picnic & Roadside"));
+    }
+}
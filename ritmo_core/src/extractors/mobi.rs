@@ -0,0 +1,267 @@
+use super::{parse_isbn, parse_year, ExtractedMetadata, CONFIDENCE_METADATA};
+use ritmo_errors::{RitmoErr, RitmoResult};
+use std::fs;
+use std::path::Path;
+
+// Record EXTH usati (https://wiki.mobileread.com/wiki/MOBI#EXTH_Header)
+const EXTH_AUTHOR: u32 = 100;
+const EXTH_PUBLISHER: u32 = 101;
+const EXTH_ISBN: u32 = 104;
+const EXTH_SUBJECT: u32 = 105;
+const EXTH_PUBLISHING_DATE: u32 = 106;
+const EXTH_ASIN: u32 = 113;
+const EXTH_UPDATED_TITLE: u32 = 503;
+const EXTH_LANGUAGE: u32 = 524;
+
+/// Flag del MOBI header che indica la presenza del blocco EXTH
+const EXTH_FLAG: u32 = 0x40;
+/// Codifica del testo dichiarata nel MOBI header
+const ENCODING_CP1252: u32 = 1252;
+
+/// Legge i metadati da un file MOBI/AZW/AZW3
+pub fn read_mobi_metadata(mobi_path: &Path) -> RitmoResult<ExtractedMetadata> {
+    let data = fs::read(mobi_path)?;
+    parse_mobi(&data).map_err(|e| {
+        RitmoErr::Generic(format!("{}: {}", mobi_path.display(), e))
+    })
+}
+
+/// Analizza un file MOBI/AZW3 in memoria: PalmDB → MOBI header → EXTH
+///
+/// Il titolo viene preso dal record EXTH 503, poi dal "full name" del MOBI
+/// header e infine dal nome del database PalmDB (troncato a 31 caratteri).
+pub fn parse_mobi(data: &[u8]) -> RitmoResult<ExtractedMetadata> {
+    if data.len() < 78 || &data[60..68] != b"BOOKMOBI" {
+        return Err(RitmoErr::Generic(
+            "File non MOBI: header PalmDB BOOKMOBI mancante".to_string(),
+        ));
+    }
+
+    let record0 = read_u32(data, 78).ok_or_else(|| truncated("lista record"))? as usize;
+    if read_bytes(data, record0 + 16, 4) != Some(b"MOBI".as_slice()) {
+        return Err(RitmoErr::Generic("MOBI header non trovato nel record 0".to_string()));
+    }
+
+    let header_length = read_u32(data, record0 + 20).ok_or_else(|| truncated("MOBI header"))?;
+    let encoding = read_u32(data, record0 + 28).unwrap_or(65001);
+    let decode = |bytes: &[u8]| decode_text(bytes, encoding);
+
+    let mut meta = ExtractedMetadata::default();
+
+    // Titolo dal MOBI header (full name)
+    let full_name = match (read_u32(data, record0 + 84), read_u32(data, record0 + 88)) {
+        (Some(offset), Some(length)) => {
+            read_bytes(data, record0 + offset as usize, length as usize).map(decode)
+        }
+        _ => None,
+    };
+
+    // EXTH
+    let exth_flags = read_u32(data, record0 + 128).unwrap_or(0);
+    if exth_flags & EXTH_FLAG != 0 {
+        let exth = record0 + 16 + header_length as usize;
+        for (record_type, value) in read_exth_records(data, exth)? {
+            let text = decode(value);
+            let text = text.trim();
+            if text.is_empty() {
+                continue;
+            }
+            match record_type {
+                EXTH_UPDATED_TITLE => {
+                    meta.title = Some(text.to_string());
+                    meta.score("book.title", CONFIDENCE_METADATA);
+                }
+                EXTH_AUTHOR => {
+                    meta.add_person(text, "role.author");
+                    meta.score("contents[0].people", 0.90);
+                }
+                EXTH_PUBLISHER if meta.publisher.is_none() => {
+                    meta.publisher = Some(text.to_string());
+                    meta.score("book.publisher", 0.85);
+                }
+                EXTH_ISBN if meta.isbn.is_none() => {
+                    if let Some(isbn) = parse_isbn(text) {
+                        meta.isbn = Some(isbn);
+                        meta.score("book.isbn", CONFIDENCE_METADATA);
+                    }
+                }
+                EXTH_SUBJECT => {
+                    meta.add_tag(text);
+                    meta.score("book.tags", 0.85);
+                }
+                EXTH_PUBLISHING_DATE if meta.year.is_none() => {
+                    if let Some(year) = parse_year(text) {
+                        meta.year = Some(year);
+                        meta.score("book.year", 0.90);
+                    }
+                }
+                EXTH_ASIN if meta.notes.is_none() => {
+                    meta.notes = Some(format!("ASIN: {}", text));
+                    meta.score("book.notes", CONFIDENCE_METADATA);
+                }
+                EXTH_LANGUAGE => {
+                    meta.add_language(text, "language_role.actual");
+                    if !meta.languages.is_empty() {
+                        meta.score("contents[0].languages", 0.90);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    if meta.title.is_none() {
+        if let Some(name) = full_name.filter(|n| !n.trim().is_empty()) {
+            meta.title = Some(name.trim().to_string());
+            meta.score("book.title", 0.90);
+        } else {
+            let palm_name = data[..32].split(|b| *b == 0).next().unwrap_or_default();
+            let name = String::from_utf8_lossy(palm_name).replace('_', " ");
+            if !name.trim().is_empty() {
+                meta.title = Some(name.trim().to_string());
+                meta.score("book.title", 0.60);
+            }
+        }
+    }
+
+    Ok(meta)
+}
+
+/// Record EXTH `(tipo, dati)` a partire dall'offset del blocco
+fn read_exth_records(data: &[u8], exth: usize) -> RitmoResult<Vec<(u32, &[u8])>> {
+    if read_bytes(data, exth, 4) != Some(b"EXTH".as_slice()) {
+        return Err(RitmoErr::Generic("Blocco EXTH non trovato".to_string()));
+    }
+    let count = read_u32(data, exth + 8).ok_or_else(|| truncated("EXTH"))?;
+
+    let mut records = Vec::new();
+    let mut pos = exth + 12;
+    for _ in 0..count {
+        let record_type = read_u32(data, pos).ok_or_else(|| truncated("record EXTH"))?;
+        let length = read_u32(data, pos + 4).ok_or_else(|| truncated("record EXTH"))? as usize;
+        if length < 8 {
+            return Err(RitmoErr::Generic(format!(
+                "Record EXTH {} con lunghezza non valida: {}",
+                record_type, length
+            )));
+        }
+        let value = read_bytes(data, pos + 8, length - 8).ok_or_else(|| truncated("record EXTH"))?;
+        records.push((record_type, value));
+        pos += length;
+    }
+    Ok(records)
+}
+
+fn decode_text(bytes: &[u8], encoding: u32) -> String {
+    if encoding == ENCODING_CP1252 {
+        let (text, _, _) = encoding_rs::WINDOWS_1252.decode(bytes);
+        text.into_owned()
+    } else {
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+}
+
+fn read_bytes(data: &[u8], offset: usize, length: usize) -> Option<&[u8]> {
+    data.get(offset..offset.checked_add(length)?)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    read_bytes(data, offset, 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn truncated(section: &str) -> RitmoErr {
+    RitmoErr::Generic(format!("File MOBI troncato ({})", section))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Costruisce un MOBI minimale: header PalmDB, un record 0 con MOBI header ed EXTH
+    fn build_mobi(full_name: &[u8], exth: &[(u32, &[u8])], encoding: u32) -> Vec<u8> {
+        const MOBI_HEADER_LENGTH: usize = 232;
+        let record0 = 78 + 8 + 2;
+
+        let mut data = vec![0u8; record0];
+        data[..8].copy_from_slice(b"Palm_DB\0");
+        data[60..68].copy_from_slice(b"BOOKMOBI");
+        data[76..78].copy_from_slice(&1u16.to_be_bytes());
+        data[78..82].copy_from_slice(&(record0 as u32).to_be_bytes());
+
+        // PalmDOC header (16 byte) + MOBI header
+        let mut rec = vec![0u8; 16 + MOBI_HEADER_LENGTH];
+        rec[16..20].copy_from_slice(b"MOBI");
+        rec[20..24].copy_from_slice(&(MOBI_HEADER_LENGTH as u32).to_be_bytes());
+        rec[28..32].copy_from_slice(&encoding.to_be_bytes());
+        rec[128..132].copy_from_slice(&EXTH_FLAG.to_be_bytes());
+
+        let mut exth_block = Vec::new();
+        for (record_type, value) in exth {
+            exth_block.extend_from_slice(&record_type.to_be_bytes());
+            exth_block.extend_from_slice(&((value.len() + 8) as u32).to_be_bytes());
+            exth_block.extend_from_slice(value);
+        }
+        rec.extend_from_slice(b"EXTH");
+        rec.extend_from_slice(&((exth_block.len() + 12) as u32).to_be_bytes());
+        rec.extend_from_slice(&(exth.len() as u32).to_be_bytes());
+        rec.extend_from_slice(&exth_block);
+
+        let name_offset = rec.len() as u32;
+        rec.extend_from_slice(full_name);
+        rec[84..88].copy_from_slice(&name_offset.to_be_bytes());
+        rec[88..92].copy_from_slice(&(full_name.len() as u32).to_be_bytes());
+
+        data.extend_from_slice(&rec);
+        data
+    }
+
+    #[test]
+    fn test_parse_mobi_exth() {
+        let data = build_mobi(
+            b"Full Name Title",
+            &[
+                (EXTH_AUTHOR, "Italo Calvino".as_bytes()),
+                (EXTH_PUBLISHER, "Einaudi".as_bytes()),
+                (EXTH_ISBN, "978-88-06-21910-9".as_bytes()),
+                (EXTH_PUBLISHING_DATE, "1957-01-01T00:00:00+00:00".as_bytes()),
+                (EXTH_ASIN, "B00ABCDEFG".as_bytes()),
+                (EXTH_LANGUAGE, "it".as_bytes()),
+                (EXTH_UPDATED_TITLE, "Il barone rampante".as_bytes()),
+            ],
+            65001,
+        );
+
+        let meta = parse_mobi(&data).unwrap();
+
+        assert_eq!(meta.title.as_deref(), Some("Il barone rampante"));
+        assert_eq!(meta.people[0].name, "Italo Calvino");
+        assert_eq!(meta.people[0].role, "role.author");
+        assert_eq!(meta.publisher.as_deref(), Some("Einaudi"));
+        assert_eq!(meta.isbn.as_deref(), Some("9788806219109"));
+        assert_eq!(meta.year, Some(1957));
+        assert_eq!(meta.notes.as_deref(), Some("ASIN: B00ABCDEFG"));
+        assert_eq!(meta.languages[0].code, "it");
+        assert_eq!(meta.confidence["book.title"], CONFIDENCE_METADATA);
+        assert_eq!(meta.confidence["book.isbn"], CONFIDENCE_METADATA);
+    }
+
+    #[test]
+    fn test_parse_mobi_full_name_cp1252() {
+        let data = build_mobi(
+            b"Cecit\xe0",
+            &[(EXTH_AUTHOR, b"Jos\xe9 Saramago".as_slice())],
+            ENCODING_CP1252,
+        );
+        let meta = parse_mobi(&data).unwrap();
+
+        assert_eq!(meta.people[0].name, "José Saramago");
+        assert_eq!(meta.title.as_deref(), Some("Cecità"));
+        assert_eq!(meta.confidence["book.title"], 0.90);
+    }
+
+    #[test]
+    fn test_parse_mobi_rejects_other_files() {
+        assert!(parse_mobi(b"not a mobi file").is_err());
+        assert!(parse_mobi(&[0u8; 100]).is_err());
+    }
+}
@@ -0,0 +1,310 @@
+//! Estrazione automatica dei metadati dai file ebook (Level 3)
+//!
+//! Ogni lettore di formato (EPUB, PDF, MOBI/AZW3, FB2, CBZ) produce un
+//! `ExtractedMetadata`, che viene poi convertito in un `ImportObject`
+//! compatibile con `add-batch`, completo dei punteggi di confidenza per campo
+//! (vedi `docs/book_metadata_format.md`).
+
+mod comic;
+mod epub;
+mod fb2;
+mod mobi;
+mod pdf;
+
+pub(crate) use epub::marc_role_to_ritmo;
+pub use epub::parse_opf;
+pub use fb2::parse_fb2;
+pub use mobi::parse_mobi;
+
+use crate::dto::{BookInput, ContentInput, ImportObject, LanguageInput, PersonInput};
+use ritmo_db_core::isbn::Isbn;
+use ritmo_db_core::vocabulary::{lookup_format, lookup_language};
+use ritmo_errors::{RitmoErr, RitmoResult};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Confidenza per campi letti da metadati strutturati (dc:title, EXTH, title-info, ...)
+pub const CONFIDENCE_METADATA: f32 = 0.95;
+/// Confidenza per campi letti da metadati meno affidabili (publisher, serie, date)
+pub const CONFIDENCE_INFERRED: f32 = 0.85;
+/// Confidenza per campi ricavati dal nome del file
+pub const CONFIDENCE_FILENAME: f32 = 0.50;
+
+/// Metadati estratti da un file, indipendenti dal formato di origine
+///
+/// I campi `people` e `languages` appartengono all'opera (`contents[0]`),
+/// gli altri all'edizione (`book`).
+#[derive(Debug, Clone, Default)]
+pub struct ExtractedMetadata {
+    pub title: Option<String>,
+    pub original_title: Option<String>,
+    pub people: Vec<PersonInput>,
+    /// Persone legate all'edizione (curatori, prefatori)
+    pub book_people: Vec<PersonInput>,
+    pub publisher: Option<String>,
+    /// Anno dell'edizione
+    pub year: Option<i32>,
+    /// Anno dell'opera, se distinto da quello dell'edizione
+    pub content_year: Option<i32>,
+    pub isbn: Option<String>,
+    pub series: Option<String>,
+    pub series_index: Option<i64>,
+    pub pages: Option<i64>,
+    pub languages: Vec<LanguageInput>,
+    pub tags: Vec<String>,
+    pub notes: Option<String>,
+    /// Confidenza per campo, con le chiavi del formato JSON (`book.title`, `contents[0].people`, ...)
+    pub confidence: HashMap<String, f32>,
+}
+
+impl ExtractedMetadata {
+    /// Registra la confidenza di un campo
+    ///
+    /// Per i campi con più valori (persone, lingue, tag) viene mantenuto il
+    /// punteggio più basso: il campo è affidabile quanto il suo valore meno certo.
+    pub fn score(&mut self, key: &str, confidence: f32) {
+        let entry = self.confidence.entry(key.to_string()).or_insert(confidence);
+        if confidence < *entry {
+            *entry = confidence;
+        }
+    }
+
+    /// Aggiunge una persona, ignorando nomi vuoti e duplicati
+    ///
+    /// Autori, traduttori e gli altri ruoli vanno all'opera; curatori e
+    /// prefatori (`role.editor`, `role.preface`) all'edizione.
+    pub fn add_person(&mut self, name: &str, role: &str) {
+        let name = name.trim();
+        let people = match role {
+            "role.editor" | "role.preface" => &mut self.book_people,
+            _ => &mut self.people,
+        };
+        if name.is_empty() || people.iter().any(|p| p.name == name && p.role == role) {
+            return;
+        }
+        people.push(PersonInput {
+            name: name.to_string(),
+            role: role.to_string(),
+            source: None,
+        });
+    }
+
+    /// Aggiunge una lingua all'opera; i codici non riconducibili al vocabolario ISO 639 sono scartati
+    pub fn add_language(&mut self, code: &str, role: &str) {
+        let Some(code) = normalize_language_code(code) else {
+            return;
+        };
+        if self
+            .languages
+            .iter()
+            .any(|l| l.code == code && l.role == role)
+        {
+            return;
+        }
+        self.languages.push(LanguageInput {
+            code,
+            role: role.to_string(),
+        });
+    }
+
+    /// Aggiunge un tag, ignorando valori vuoti e duplicati
+    pub fn add_tag(&mut self, tag: &str) {
+        let tag = tag.trim();
+        if !tag.is_empty() && !self.tags.iter().any(|t| t == tag) {
+            self.tags.push(tag.to_string());
+        }
+    }
+
+    /// Converte i metadati estratti in un `ImportObject` per `add-batch`
+    ///
+    /// Se manca il titolo viene usato il nome del file (confidenza bassa).
+    /// L'opera (`contents[0]`) eredita titolo e anno dell'edizione quando non
+    /// ne ha di propri.
+    pub fn into_import_object(mut self, file_path: &Path, format: &str) -> ImportObject {
+        let title = match self.title.take() {
+            Some(title) => title,
+            None => {
+                self.score("book.title", CONFIDENCE_FILENAME);
+                title_from_filename(file_path)
+            }
+        };
+
+        let title_confidence = self.confidence.get("book.title").copied();
+        if let Some(confidence) = title_confidence {
+            self.score("contents[0].title", confidence);
+        }
+        // Anno dell'opera stimato da quello dell'edizione
+        if self.content_year.is_none() && self.year.is_some() {
+            self.score("contents[0].year", 0.60);
+        }
+
+        if !self.book_people.is_empty() && !self.confidence.contains_key("book.people") {
+            self.score("book.people", 0.75);
+        }
+
+        let content = ContentInput {
+            title: title.clone(),
+            original_title: self.original_title.clone(),
+            people: self.people,
+            content_type: None,
+            year: self.content_year.or(self.year),
+            languages: self.languages,
+        };
+
+        ImportObject {
+            file_path: file_path.to_string_lossy().to_string(),
+            book: BookInput {
+                title,
+                original_title: self.original_title,
+                people: self.book_people,
+                publisher: self.publisher,
+                year: self.year,
+                isbn: self.isbn,
+                format: Some(format.to_string()),
+                series: self.series,
+                series_index: self.series_index,
+                pages: self.pages,
+                notes: self.notes,
+                tags: self.tags,
+            },
+            attach_to: None,
+            contents: vec![content],
+            confidence: Some(self.confidence),
+            sources: None,
+        }
+    }
+}
+
+/// Estrae i metadati da un file ebook, scegliendo il lettore in base all'estensione
+///
+/// Formati supportati: `epub`, `pdf`, `mobi`/`azw`/`azw3`/`prc`, `fb2`, `fb2.zip`
+/// e `cbz`/`cbr` (solo CBR in formato ZIP).
+pub fn extract_metadata(file_path: &Path) -> RitmoResult<ImportObject> {
+    let format = detect_format(file_path).ok_or_else(|| {
+        RitmoErr::Generic(format!(
+            "Formato non supportato per l'estrazione dei metadati: {}",
+            file_path.display()
+        ))
+    })?;
+
+    let metadata = match format.as_str() {
+        "epub" => epub::read_epub_metadata(file_path)?,
+        "pdf" => pdf::read_pdf(file_path)?,
+        "fb2" => fb2::read_fb2_metadata(file_path)?,
+        "cbz" | "cbr" => comic::read_comic_metadata(file_path)?,
+        _ => mobi::read_mobi_metadata(file_path)?,
+    };
+
+    Ok(metadata.into_import_object(file_path, &format))
+}
+
+/// Chiave del formato (come salvata in `formats.key`) per i file supportati
+pub fn detect_format(file_path: &Path) -> Option<String> {
+    let name = file_path.file_name()?.to_str()?.to_lowercase();
+    if name.ends_with(".fb2.zip") {
+        return Some("fb2".to_string());
+    }
+    let extension = file_path.extension()?.to_str()?.to_lowercase();
+    match extension.as_str() {
+        "epub" | "pdf" | "fb2" | "mobi" | "azw" | "azw3" | "prc" | "cbz" | "cbr" => {
+            lookup_format(&extension).map(|f| f.key.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Titolo ricavato dal nome del file (senza estensioni, `_` sostituiti da spazi)
+fn title_from_filename(file_path: &Path) -> String {
+    let name = file_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default();
+    let stem = name.split('.').next().unwrap_or(name);
+    stem.replace('_', " ").trim().to_string()
+}
+
+/// Riduce un codice o nome di lingua (`en-US`, `ita`, `fr_FR`, `Italian`) al
+/// codice canonico del vocabolario ISO 639 (639-1, o 639-3 se manca)
+pub(crate) fn normalize_language_code(code: &str) -> Option<String> {
+    lookup_language(code).map(|lang| lang.code().to_string())
+}
+
+/// Estrae l'anno dall'inizio di una data (`2010`, `2010-05-01`, `2010-05-01T00:00:00Z`)
+pub(crate) fn parse_year(date: &str) -> Option<i32> {
+    let digits: String = date.trim().chars().take(4).collect();
+    if digits.len() == 4 && digits.chars().all(|c| c.is_ascii_digit()) {
+        digits.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// ISBN letto dai metadati, in forma compatta (`ritmo_db_core::isbn`);
+/// i valori che non sono ISBN validi vengono scartati
+fn parse_isbn(value: &str) -> Option<String> {
+    Isbn::parse(value).ok().map(|isbn| isbn.as_compact().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(detect_format(Path::new("a/b.EPUB")).as_deref(), Some("epub"));
+        assert_eq!(detect_format(Path::new("b.azw3")).as_deref(), Some("azw3"));
+        assert_eq!(detect_format(Path::new("b.fb2.zip")).as_deref(), Some("fb2"));
+        assert_eq!(detect_format(Path::new("b.CBZ")).as_deref(), Some("cbz"));
+        assert_eq!(detect_format(Path::new("b.prc")).as_deref(), Some("mobi"));
+        assert_eq!(detect_format(Path::new("b.zip")), None);
+        assert_eq!(detect_format(Path::new("b")), None);
+    }
+
+    #[test]
+    fn test_normalize_language_code() {
+        assert_eq!(normalize_language_code("en-US").as_deref(), Some("en"));
+        assert_eq!(normalize_language_code("ita").as_deref(), Some("it"));
+        assert_eq!(normalize_language_code("FR_fr").as_deref(), Some("fr"));
+        assert_eq!(normalize_language_code("Italian").as_deref(), Some("it"));
+        assert_eq!(normalize_language_code("grc").as_deref(), Some("grc"));
+        assert_eq!(normalize_language_code("qqq"), None);
+        assert_eq!(normalize_language_code(""), None);
+    }
+
+    #[test]
+    fn test_parse_isbn() {
+        assert_eq!(
+            parse_isbn("urn:isbn:978-88-04-66823-7").as_deref(),
+            Some("9788804668237")
+        );
+        assert_eq!(parse_isbn("ISBN 0-306-40615-2").as_deref(), Some("0306406152"));
+        // Cifra di controllo errata o identificativi non ISBN (ASIN)
+        assert_eq!(parse_isbn("ISBN 0-306-40615-x"), None);
+        assert_eq!(parse_isbn("B00ABCDEFG"), None);
+    }
+
+    #[test]
+    fn test_into_import_object_falls_back_to_filename() {
+        let mut meta = ExtractedMetadata {
+            year: Some(1999),
+            ..Default::default()
+        };
+        meta.score("book.year", 0.90);
+        meta.add_person("Italo Calvino", "role.author");
+        meta.add_person("Italo Calvino", "role.author");
+
+        let object =
+            meta.into_import_object(&PathBuf::from("/books/Il_barone_rampante.mobi"), "mobi");
+        let confidence = object.confidence.unwrap();
+
+        assert_eq!(object.book.title, "Il barone rampante");
+        assert_eq!(object.book.format.as_deref(), Some("mobi"));
+        assert_eq!(object.contents.len(), 1);
+        assert_eq!(object.contents[0].people.len(), 1);
+        assert_eq!(object.contents[0].year, Some(1999));
+        assert_eq!(confidence["book.title"], CONFIDENCE_FILENAME);
+        assert_eq!(confidence["contents[0].title"], CONFIDENCE_FILENAME);
+        assert_eq!(confidence["contents[0].year"], 0.60);
+    }
+}
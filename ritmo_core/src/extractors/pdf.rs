@@ -0,0 +1,56 @@
+use super::{
+    parse_isbn, parse_year, ExtractedMetadata, CONFIDENCE_INFERRED, CONFIDENCE_METADATA,
+};
+use crate::pdf_metadata::read_pdf_metadata;
+use ritmo_errors::RitmoResult;
+use std::path::Path;
+
+/// Legge i metadati di un PDF (Info + XMP) e il numero di pagine
+///
+/// I metadati PDF sono spesso generati dal software di impaginazione, quindi
+/// titolo e autori hanno una confidenza più bassa rispetto a EPUB/FB2.
+pub fn read_pdf(pdf_path: &Path) -> RitmoResult<ExtractedMetadata> {
+    let pdf = read_pdf_metadata(pdf_path)?;
+    let mut meta = ExtractedMetadata::default();
+
+    if let Some(title) = pdf.title.filter(|t| !t.trim().is_empty()) {
+        meta.title = Some(title.trim().to_string());
+        meta.score("book.title", CONFIDENCE_INFERRED);
+    }
+    for author in &pdf.authors {
+        meta.add_person(author, "role.author");
+    }
+    if !meta.people.is_empty() {
+        meta.score("contents[0].people", 0.80);
+    }
+    if let Some(publisher) = pdf.publisher {
+        meta.publisher = Some(publisher);
+        meta.score("book.publisher", CONFIDENCE_INFERRED);
+    }
+    if let Some(year) = pdf.date.as_deref().and_then(parse_year) {
+        meta.year = Some(year);
+        meta.score("book.year", 0.60);
+    }
+    if let Some(isbn) = pdf.isbn.as_deref().and_then(parse_isbn) {
+        meta.isbn = Some(isbn);
+        meta.score("book.isbn", CONFIDENCE_METADATA);
+    }
+    for language in &pdf.languages {
+        meta.add_language(language, "language_role.actual");
+    }
+    if !meta.languages.is_empty() {
+        meta.score("contents[0].languages", 0.90);
+    }
+    for keyword in &pdf.keywords {
+        meta.add_tag(keyword);
+    }
+    if !meta.tags.is_empty() {
+        meta.score("book.tags", 0.70);
+    }
+    if let Some(pages) = pdf.page_count.filter(|p| *p > 0) {
+        meta.pages = Some(pages);
+        meta.score("book.pages", 1.0);
+    }
+
+    Ok(meta)
+}
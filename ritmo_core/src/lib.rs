@@ -4,3 +4,12 @@ pub use dto::*;
 pub mod service;
 pub mod epub_utils;
 pub mod epub_opf_modifier;
+pub mod epub_validator;
+pub mod text_fingerprint;
+pub mod pdf_metadata;
+pub mod comic_info;
+pub mod extractors;
+pub mod providers;
+
+#[cfg(test)]
+mod test_helpers;
@@ -0,0 +1,786 @@
+use crate::epub_opf_modifier::OPFMetadata;
+use lopdf::{decode_text_string, text_string, Dictionary, Document, Object, Stream};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use ritmo_errors::{RitmoErr, RitmoResult};
+use std::path::Path;
+
+/// Metadati di un PDF (Info dictionary + XMP)
+///
+/// In lettura i valori XMP hanno la precedenza su quelli del dizionario Info,
+/// che viene usato come fallback per i campi mancanti.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PdfMetadata {
+    pub title: Option<String>,
+    pub authors: Vec<String>,
+    pub subject: Option<String>,
+    pub keywords: Vec<String>,
+    pub producer: Option<String>,
+    pub creator_tool: Option<String>,
+    // Solo XMP (il dizionario Info non ha campi equivalenti)
+    pub publisher: Option<String>,
+    pub date: Option<String>,
+    pub languages: Vec<String>,
+    pub isbn: Option<String>,
+    // Solo lettura
+    pub page_count: Option<i64>,
+}
+
+/// Legge i metadati da un file PDF
+///
+/// # Arguments
+/// * `pdf_path` - Path al file PDF
+///
+/// # Returns
+/// `PdfMetadata` con i campi trovati (XMP con fallback su Info) e il numero di pagine
+pub fn read_pdf_metadata(pdf_path: &Path) -> RitmoResult<PdfMetadata> {
+    let doc = load_document(pdf_path)?;
+    Ok(read_document_metadata(&doc))
+}
+
+fn load_document(pdf_path: &Path) -> RitmoResult<Document> {
+    let doc = Document::load(pdf_path).map_err(|e| {
+        RitmoErr::Generic(format!("Impossibile aprire PDF {}: {}", pdf_path.display(), e))
+    })?;
+    Ok(doc)
+}
+
+fn read_document_metadata(doc: &Document) -> PdfMetadata {
+    let info = read_info_dictionary(doc);
+    let xmp = read_xmp_packet(doc)
+        .map(|xml| parse_xmp(&xml))
+        .unwrap_or_default();
+
+    let pages = doc.get_pages().len() as i64;
+
+    PdfMetadata {
+        title: xmp.title.or(info.title),
+        authors: if xmp.authors.is_empty() { info.authors } else { xmp.authors },
+        subject: xmp.subject.or(info.subject),
+        keywords: if xmp.keywords.is_empty() { info.keywords } else { xmp.keywords },
+        producer: xmp.producer.or(info.producer),
+        creator_tool: xmp.creator_tool.or(info.creator_tool),
+        publisher: xmp.publisher,
+        date: xmp.date,
+        languages: xmp.languages,
+        isbn: xmp.isbn,
+        page_count: if pages > 0 { Some(pages) } else { None },
+    }
+}
+
+/// Restituisce il dizionario Info referenziato dal trailer (se presente)
+fn info_dictionary(doc: &Document) -> Option<&Dictionary> {
+    let info = doc.trailer.get(b"Info").ok()?;
+    let (_, info) = doc.dereference(info).ok()?;
+    info.as_dict().ok()
+}
+
+fn read_info_dictionary(doc: &Document) -> PdfMetadata {
+    let Some(info) = info_dictionary(doc) else {
+        return PdfMetadata::default();
+    };
+
+    let text = |key: &[u8]| -> Option<String> {
+        info.get(key)
+            .ok()
+            .and_then(|obj| decode_text_string(obj).ok())
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+
+    PdfMetadata {
+        title: text(b"Title"),
+        authors: text(b"Author").map(|a| split_list(&a, &[';'])).unwrap_or_default(),
+        subject: text(b"Subject"),
+        keywords: text(b"Keywords")
+            .map(|k| split_list(&k, &[',', ';']))
+            .unwrap_or_default(),
+        producer: text(b"Producer"),
+        creator_tool: text(b"Creator"),
+        ..Default::default()
+    }
+}
+
+fn split_list(value: &str, separators: &[char]) -> Vec<String> {
+    value
+        .split(|c| separators.contains(&c))
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Estrae il pacchetto XMP dallo stream /Metadata del catalogo
+fn read_xmp_packet(doc: &Document) -> Option<String> {
+    let catalog = doc.catalog().ok()?;
+    let metadata = catalog.get(b"Metadata").ok()?;
+    let (_, metadata) = doc.dereference(metadata).ok()?;
+    let stream = metadata.as_stream().ok()?;
+    let content = stream
+        .decompressed_content()
+        .unwrap_or_else(|_| stream.content.clone());
+    String::from_utf8(content).ok()
+}
+
+/// Estrae i campi noti da un pacchetto XMP
+///
+/// Supporta sia la forma a elementi (`<dc:title><rdf:Alt><rdf:li>...`) sia
+/// quella ad attributi (`<rdf:Description pdf:Producer="...">`).
+pub fn parse_xmp(xml: &str) -> PdfMetadata {
+    let mut meta = PdfMetadata::default();
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    // Nome qualificato della proprietà XMP corrente (es. "dc:title")
+    let mut current_property: Option<String> = None;
+    let mut in_li = false;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+                match name.as_str() {
+                    "rdf:li" => in_li = true,
+                    "rdf:Description" => read_xmp_attributes(&e, &mut meta),
+                    "rdf:Alt" | "rdf:Seq" | "rdf:Bag" | "rdf:RDF" | "x:xmpmeta" => {}
+                    _ => current_property = Some(name),
+                }
+            }
+            Ok(Event::Empty(e)) if e.name().as_ref() == b"rdf:Description" => {
+                read_xmp_attributes(&e, &mut meta);
+            }
+            Ok(Event::Text(e)) => {
+                let Ok(text) = e.unescape() else { continue };
+                let text = text.trim().to_string();
+                if text.is_empty() {
+                    continue;
+                }
+                if let Some(property) = &current_property {
+                    apply_xmp_value(&mut meta, property, &text, in_li);
+                }
+            }
+            Ok(Event::End(e)) => {
+                let name = e.name();
+                if name.as_ref() == b"rdf:li" {
+                    in_li = false;
+                } else if current_property.as_deref().map(str::as_bytes) == Some(name.as_ref()) {
+                    current_property = None;
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+
+    meta
+}
+
+fn read_xmp_attributes(e: &quick_xml::events::BytesStart, meta: &mut PdfMetadata) {
+    for attr in e.attributes().flatten() {
+        let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
+        if let Ok(value) = attr.unescape_value() {
+            let value = value.trim();
+            if !value.is_empty() {
+                apply_xmp_value(meta, &key, value, false);
+            }
+        }
+    }
+}
+
+fn apply_xmp_value(meta: &mut PdfMetadata, property: &str, value: &str, in_li: bool) {
+    match property {
+        "dc:title" if meta.title.is_none() => meta.title = Some(value.to_string()),
+        "dc:creator" => meta.authors.push(value.to_string()),
+        "dc:description" if meta.subject.is_none() => meta.subject = Some(value.to_string()),
+        "dc:subject" if in_li => meta.keywords.push(value.to_string()),
+        "pdf:Keywords" if meta.keywords.is_empty() => {
+            meta.keywords = split_list(value, &[',', ';'])
+        }
+        "pdf:Producer" => meta.producer = Some(value.to_string()),
+        "xmp:CreatorTool" => meta.creator_tool = Some(value.to_string()),
+        "dc:publisher" if meta.publisher.is_none() => meta.publisher = Some(value.to_string()),
+        "dc:date" if meta.date.is_none() => meta.date = Some(value.to_string()),
+        "dc:language" => meta.languages.push(value.to_string()),
+        "dc:identifier" => {
+            let lower = value.to_lowercase();
+            if let Some(isbn) = lower.strip_prefix("urn:isbn:").or(lower.strip_prefix("isbn:")) {
+                meta.isbn = Some(isbn.trim().to_string());
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Costruisce i metadati PDF a partire dai metadati OPF costruiti dal database
+///
+/// Il PDF non distingue i ruoli: solo i creator (autori) finiscono in Author.
+/// Subject e Producer non hanno un equivalente in Ritmo e vengono preservati dal file.
+pub fn build_pdf_metadata(opf: &OPFMetadata) -> PdfMetadata {
+    PdfMetadata {
+        title: opf.title.clone(),
+        authors: opf.creators.iter().map(|p| p.name.clone()).collect(),
+        subject: None,
+        keywords: opf.subjects.clone(),
+        producer: None,
+        creator_tool: None,
+        publisher: opf.publisher.clone(),
+        date: opf.date.clone(),
+        languages: opf.languages.clone(),
+        isbn: opf
+            .identifiers
+            .iter()
+            .find(|id| id.scheme.eq_ignore_ascii_case("ISBN"))
+            .map(|id| id.value.clone()),
+        page_count: None,
+    }
+}
+
+/// Scrive i metadati in un PDF (Info dictionary + XMP)
+///
+/// I campi vuoti in `metadata` mantengono il valore già presente nel file.
+/// Nel pacchetto XMP esistente vengono sostituite solo le proprietà gestite da
+/// ritmo; se il file non ne ha uno (o non è leggibile) viene generato da zero.
+///
+/// # Arguments
+/// * `pdf_path` - Path al PDF originale
+/// * `output_path` - Path dove scrivere il PDF modificato
+/// * `metadata` - Metadati da applicare
+pub fn modify_pdf_metadata(
+    pdf_path: &Path,
+    output_path: &Path,
+    metadata: &PdfMetadata,
+) -> RitmoResult<()> {
+    let mut doc = load_document(pdf_path)?;
+
+    if doc.is_encrypted() {
+        return Err(RitmoErr::Generic(format!(
+            "PDF cifrato, impossibile modificare i metadati: {}",
+            pdf_path.display()
+        )));
+    }
+
+    let existing = read_document_metadata(&doc);
+    let merged = merge_metadata(&existing, metadata);
+    let now = chrono::Utc::now();
+
+    // 1. Info dictionary
+    let mut info = info_dictionary(&doc).cloned().unwrap_or_default();
+    set_text(&mut info, b"Title", merged.title.as_deref());
+    set_text(&mut info, b"Author", join_non_empty(&merged.authors, "; ").as_deref());
+    set_text(&mut info, b"Subject", merged.subject.as_deref());
+    set_text(&mut info, b"Keywords", join_non_empty(&merged.keywords, ", ").as_deref());
+    info.set(
+        "ModDate",
+        Object::string_literal(now.format("D:%Y%m%d%H%M%S+00'00'").to_string()),
+    );
+
+    match doc.trailer.get(b"Info").and_then(Object::as_reference) {
+        Ok(info_id) => doc.set_object(info_id, info),
+        Err(_) => {
+            let info_id = doc.add_object(info);
+            doc.trailer.set("Info", Object::Reference(info_id));
+        }
+    }
+
+    // 2. XMP packet
+    let modify_date = now.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let xmp = read_xmp_packet(&doc)
+        .and_then(|packet| update_xmp(&packet, &merged, &modify_date))
+        .unwrap_or_else(|| build_xmp(&merged, &modify_date));
+    let mut xmp_dict = Dictionary::new();
+    xmp_dict.set("Type", Object::Name(b"Metadata".to_vec()));
+    xmp_dict.set("Subtype", Object::Name(b"XML".to_vec()));
+    let xmp_stream = Stream::new(xmp_dict, xmp.into_bytes()).with_compression(false);
+
+    let existing_xmp_id = doc
+        .catalog()
+        .ok()
+        .and_then(|c| c.get(b"Metadata").ok())
+        .and_then(|m| m.as_reference().ok());
+    let xmp_id = match existing_xmp_id {
+        Some(id) => {
+            doc.set_object(id, xmp_stream);
+            id
+        }
+        None => doc.add_object(xmp_stream),
+    };
+    doc.catalog_mut()
+        .map_err(|e| RitmoErr::Generic(format!("PDF senza catalogo: {}", e)))?
+        .set("Metadata", Object::Reference(xmp_id));
+
+    doc.save(output_path).map_err(|e| {
+        RitmoErr::Generic(format!("Impossibile scrivere PDF {}: {}", output_path.display(), e))
+    })?;
+
+    Ok(())
+}
+
+/// Sovrappone i nuovi metadati a quelli esistenti (i campi vuoti mantengono il valore esistente)
+fn merge_metadata(existing: &PdfMetadata, new: &PdfMetadata) -> PdfMetadata {
+    let pick_vec = |new: &Vec<String>, old: &Vec<String>| {
+        if new.is_empty() { old.clone() } else { new.clone() }
+    };
+
+    PdfMetadata {
+        title: new.title.clone().or_else(|| existing.title.clone()),
+        authors: pick_vec(&new.authors, &existing.authors),
+        subject: new.subject.clone().or_else(|| existing.subject.clone()),
+        keywords: pick_vec(&new.keywords, &existing.keywords),
+        producer: new.producer.clone().or_else(|| existing.producer.clone()),
+        creator_tool: new.creator_tool.clone().or_else(|| existing.creator_tool.clone()),
+        publisher: new.publisher.clone().or_else(|| existing.publisher.clone()),
+        date: new.date.clone().or_else(|| existing.date.clone()),
+        languages: pick_vec(&new.languages, &existing.languages),
+        isbn: new.isbn.clone().or_else(|| existing.isbn.clone()),
+        page_count: existing.page_count,
+    }
+}
+
+fn set_text(dict: &mut Dictionary, key: &[u8], value: Option<&str>) {
+    if let Some(value) = value {
+        dict.set(key.to_vec(), text_string(value));
+    }
+}
+
+fn join_non_empty(values: &[String], separator: &str) -> Option<String> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.join(separator))
+    }
+}
+
+/// Proprietà XMP scritte da ritmo (`dc:identifier` solo se è un ISBN)
+const MANAGED_XMP: &[&str] = &[
+    "dc:title",
+    "dc:creator",
+    "dc:description",
+    "dc:subject",
+    "dc:publisher",
+    "dc:date",
+    "dc:language",
+    "dc:identifier",
+    "pdf:Keywords",
+    "pdf:Producer",
+    "xmp:CreatorTool",
+    "xmp:ModifyDate",
+    "xmp:MetadataDate",
+];
+
+/// Namespace delle proprietà gestite
+const XMP_NAMESPACES: &[(&str, &str)] = &[
+    ("dc", "http://purl.org/dc/elements/1.1/"),
+    ("pdf", "http://ns.adobe.com/pdf/1.3/"),
+    ("xmp", "http://ns.adobe.com/xap/1.0/"),
+];
+
+fn is_managed(property: &str, value: &str) -> bool {
+    if property == "dc:identifier" {
+        let value = value.trim().to_lowercase();
+        return value.starts_with("urn:isbn:") || value.starts_with("isbn:");
+    }
+    MANAGED_XMP.contains(&property)
+}
+
+/// Aggiorna un pacchetto XMP esistente
+///
+/// Le proprietà gestite (elementi figli di `rdf:Description` o suoi attributi)
+/// vengono tolte e riscritte nel primo `rdf:Description`; il resto del pacchetto
+/// (altre proprietà, altri namespace, padding) è copiato invariato.
+/// `None` se il pacchetto non è leggibile o non ha un `rdf:Description`.
+fn update_xmp(xml: &str, meta: &PdfMetadata, modify_date: &str) -> Option<String> {
+    let mut reader = Reader::from_str(xml);
+    let mut output = String::with_capacity(xml.len() + 1024);
+    let mut cursor = 0;
+    let mut in_description = false;
+    let mut inserted = false;
+    // Inizio degli spazi che precedono la posizione corrente (dentro rdf:Description)
+    let mut whitespace_start: Option<usize> = None;
+
+    loop {
+        let start = reader.buffer_position() as usize;
+        let event = reader.read_event().ok()?;
+        let end = reader.buffer_position() as usize;
+
+        let (e, is_empty) = match event {
+            Event::Eof => break,
+            Event::Start(e) => (e, false),
+            Event::Empty(e) => (e, true),
+            Event::End(e) if in_description && e.name().as_ref() == b"rdf:Description" => {
+                if !inserted {
+                    let insert_at = whitespace_start.unwrap_or(start);
+                    output.push_str(&xml[cursor..insert_at]);
+                    output.push_str(&xmp_properties(meta, modify_date));
+                    cursor = insert_at;
+                    inserted = true;
+                }
+                in_description = false;
+                continue;
+            }
+            Event::Text(e) if in_description => {
+                if e.iter().all(|b| b.is_ascii_whitespace()) {
+                    whitespace_start.get_or_insert(start);
+                } else {
+                    whitespace_start = None;
+                }
+                continue;
+            }
+            _ => {
+                whitespace_start = None;
+                continue;
+            }
+        };
+        let preceding_whitespace = whitespace_start.take();
+
+        if !in_description {
+            if e.name().as_ref() != b"rdf:Description" {
+                continue;
+            }
+            output.push_str(&xml[cursor..start]);
+            output.push_str(&description_tag(&e, !inserted)?);
+            cursor = end;
+            if !is_empty {
+                output.push('>');
+                in_description = true;
+            } else if inserted {
+                output.push_str("/>");
+            } else {
+                output.push('>');
+                output.push_str(&xmp_properties(meta, modify_date));
+                output.push_str("\n  </rdf:Description>");
+                inserted = true;
+            }
+            continue;
+        }
+
+        // Proprietà: legge fino alla chiusura (compresi eventuali figli annidati)
+        let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+        let (value, end) = if is_empty {
+            (String::new(), end)
+        } else {
+            let raw = reader.read_text(e.name()).ok()?;
+            (raw.to_string(), reader.buffer_position() as usize)
+        };
+        if is_managed(&name, &value) {
+            output.push_str(&xml[cursor..preceding_whitespace.unwrap_or(start)]);
+            cursor = end;
+        }
+    }
+
+    if !inserted {
+        return None;
+    }
+    output.push_str(&xml[cursor..]);
+    Some(output)
+}
+
+/// Tag di apertura di `rdf:Description` senza gli attributi gestiti (senza `>`)
+///
+/// Con `declare` aggiunge le dichiarazioni dei namespace delle proprietà gestite
+/// che il tag non ha già.
+fn description_tag(e: &quick_xml::events::BytesStart, declare: bool) -> Option<String> {
+    let mut tag = String::from("<rdf:Description");
+    let mut declared = Vec::new();
+    for attr in e.attributes() {
+        let attr = attr.ok()?;
+        let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
+        let value = String::from_utf8_lossy(&attr.value).to_string();
+        let unescaped = attr.unescape_value().map(|v| v.to_string()).unwrap_or_default();
+        if is_managed(&key, &unescaped) {
+            continue;
+        }
+        if let Some(prefix) = key.strip_prefix("xmlns:") {
+            declared.push(prefix.to_string());
+        }
+        // Il valore grezzo è già escapato per le sue virgolette originali
+        let quote = if value.contains('"') { '\'' } else { '"' };
+        tag.push_str(&format!(" {}={}{}{}", key, quote, value, quote));
+    }
+    if declare {
+        for (prefix, uri) in XMP_NAMESPACES {
+            if !declared.iter().any(|d| d == prefix) {
+                tag.push_str(&format!(" xmlns:{}=\"{}\"", prefix, uri));
+            }
+        }
+    }
+    Some(tag)
+}
+
+/// Genera un pacchetto XMP con i metadati forniti
+fn build_xmp(meta: &PdfMetadata, modify_date: &str) -> String {
+    format!(
+        "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n\
+<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n\
+ <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n\
+  <rdf:Description rdf:about=\"\"\n\
+    xmlns:dc=\"http://purl.org/dc/elements/1.1/\"\n\
+    xmlns:pdf=\"http://ns.adobe.com/pdf/1.3/\"\n\
+    xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\">{}\n\
+  </rdf:Description>\n\
+ </rdf:RDF>\n\
+</x:xmpmeta>\n\
+<?xpacket end=\"w\"?>",
+        xmp_properties(meta, modify_date)
+    )
+}
+
+/// Proprietà gestite, una per riga, nella forma a elementi
+fn xmp_properties(meta: &PdfMetadata, modify_date: &str) -> String {
+    let mut body = String::new();
+
+    if let Some(title) = &meta.title {
+        body.push_str(&format!(
+            "\n   <dc:title><rdf:Alt><rdf:li xml:lang=\"x-default\">{}</rdf:li></rdf:Alt></dc:title>",
+            escape_xml(title)
+        ));
+    }
+    if !meta.authors.is_empty() {
+        body.push_str(&xmp_list("dc:creator", "rdf:Seq", &meta.authors));
+    }
+    if let Some(subject) = &meta.subject {
+        body.push_str(&format!(
+            "\n   <dc:description><rdf:Alt><rdf:li xml:lang=\"x-default\">{}</rdf:li></rdf:Alt></dc:description>",
+            escape_xml(subject)
+        ));
+    }
+    if !meta.keywords.is_empty() {
+        body.push_str(&xmp_list("dc:subject", "rdf:Bag", &meta.keywords));
+        body.push_str(&format!(
+            "\n   <pdf:Keywords>{}</pdf:Keywords>",
+            escape_xml(&meta.keywords.join(", "))
+        ));
+    }
+    if let Some(publisher) = &meta.publisher {
+        body.push_str(&xmp_list("dc:publisher", "rdf:Bag", std::slice::from_ref(publisher)));
+    }
+    if let Some(date) = &meta.date {
+        body.push_str(&xmp_list("dc:date", "rdf:Seq", std::slice::from_ref(date)));
+    }
+    if !meta.languages.is_empty() {
+        body.push_str(&xmp_list("dc:language", "rdf:Bag", &meta.languages));
+    }
+    if let Some(isbn) = &meta.isbn {
+        body.push_str(&format!(
+            "\n   <dc:identifier>urn:isbn:{}</dc:identifier>",
+            escape_xml(isbn)
+        ));
+    }
+    if let Some(producer) = &meta.producer {
+        body.push_str(&format!("\n   <pdf:Producer>{}</pdf:Producer>", escape_xml(producer)));
+    }
+    if let Some(tool) = &meta.creator_tool {
+        body.push_str(&format!("\n   <xmp:CreatorTool>{}</xmp:CreatorTool>", escape_xml(tool)));
+    }
+    body.push_str(&format!("\n   <xmp:ModifyDate>{}</xmp:ModifyDate>", modify_date));
+    body.push_str(&format!("\n   <xmp:MetadataDate>{}</xmp:MetadataDate>", modify_date));
+    body
+}
+
+fn xmp_list(property: &str, container: &str, values: &[String]) -> String {
+    let items: String = values
+        .iter()
+        .map(|v| format!("<rdf:li>{}</rdf:li>", escape_xml(v)))
+        .collect();
+    format!(
+        "\n   <{p}><{c}>{items}</{c}></{p}>",
+        p = property,
+        c = container,
+        items = items
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::dictionary;
+
+    /// Crea un PDF minimale con `pages` pagine vuote e un dizionario Info opzionale
+    fn create_test_pdf(path: &Path, pages: usize, info: Option<Dictionary>) {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let mut kids = Vec::new();
+        for _ in 0..pages {
+            let page_id = doc.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            });
+            kids.push(Object::Reference(page_id));
+        }
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => kids,
+                "Count" => pages as i64,
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        doc.trailer.set("Root", catalog_id);
+        if let Some(info) = info {
+            let info_id = doc.add_object(info);
+            doc.trailer.set("Info", info_id);
+        }
+        doc.save(path).unwrap();
+    }
+
+    #[test]
+    fn test_read_info_and_page_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.pdf");
+        create_test_pdf(
+            &path,
+            3,
+            Some(dictionary! {
+                "Title" => text_string("Il barone rampante"),
+                "Author" => text_string("Italo Calvino"),
+                "Keywords" => text_string("romanzo, classici"),
+                "Producer" => text_string("LibreOffice"),
+            }),
+        );
+
+        let meta = read_pdf_metadata(&path).unwrap();
+        assert_eq!(meta.title.as_deref(), Some("Il barone rampante"));
+        assert_eq!(meta.authors, vec!["Italo Calvino"]);
+        assert_eq!(meta.keywords, vec!["romanzo", "classici"]);
+        assert_eq!(meta.producer.as_deref(), Some("LibreOffice"));
+        assert_eq!(meta.page_count, Some(3));
+    }
+
+    #[test]
+    fn test_parse_xmp_elements_and_attributes() {
+        let xmp = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about="" pdf:Producer="Acrobat" xmp:CreatorTool="Word">
+   <dc:title><rdf:Alt><rdf:li xml:lang="x-default">Città invisibili</rdf:li></rdf:Alt></dc:title>
+   <dc:creator><rdf:Seq><rdf:li>Italo Calvino</rdf:li><rdf:li>Altro Autore</rdf:li></rdf:Seq></dc:creator>
+   <dc:subject><rdf:Bag><rdf:li>città</rdf:li><rdf:li>viaggi</rdf:li></rdf:Bag></dc:subject>
+   <dc:identifier>urn:isbn:9788804668237</dc:identifier>
+  </rdf:Description>
+ </rdf:RDF>
+</x:xmpmeta>"#;
+
+        let meta = parse_xmp(xmp);
+        assert_eq!(meta.title.as_deref(), Some("Città invisibili"));
+        assert_eq!(meta.authors, vec!["Italo Calvino", "Altro Autore"]);
+        assert_eq!(meta.keywords, vec!["città", "viaggi"]);
+        assert_eq!(meta.producer.as_deref(), Some("Acrobat"));
+        assert_eq!(meta.creator_tool.as_deref(), Some("Word"));
+        assert_eq!(meta.isbn.as_deref(), Some("9788804668237"));
+    }
+
+    #[test]
+    fn test_modify_pdf_metadata_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = dir.path().join("original.pdf");
+        let modified = dir.path().join("modified.pdf");
+        create_test_pdf(
+            &original,
+            2,
+            Some(dictionary! {
+                "Title" => text_string("Vecchio titolo"),
+                "Subject" => text_string("Descrizione originale"),
+                "Producer" => text_string("LibreOffice"),
+            }),
+        );
+
+        let new_meta = PdfMetadata {
+            title: Some("Nuovo titolo è".to_string()),
+            authors: vec!["Primo Levi".to_string()],
+            keywords: vec!["memorie".to_string()],
+            isbn: Some("9788806219352".to_string()),
+            ..Default::default()
+        };
+        modify_pdf_metadata(&original, &modified, &new_meta).unwrap();
+
+        let meta = read_pdf_metadata(&modified).unwrap();
+        assert_eq!(meta.title.as_deref(), Some("Nuovo titolo è"));
+        assert_eq!(meta.authors, vec!["Primo Levi"]);
+        assert_eq!(meta.keywords, vec!["memorie"]);
+        assert_eq!(meta.isbn.as_deref(), Some("9788806219352"));
+        // Campi non forniti restano invariati
+        assert_eq!(meta.subject.as_deref(), Some("Descrizione originale"));
+        assert_eq!(meta.producer.as_deref(), Some("LibreOffice"));
+        assert_eq!(meta.page_count, Some(2));
+
+        // Anche il dizionario Info è aggiornato (per i lettori che ignorano XMP)
+        let doc = Document::load(&modified).unwrap();
+        let info = read_info_dictionary(&doc);
+        assert_eq!(info.title.as_deref(), Some("Nuovo titolo è"));
+        assert_eq!(info.authors, vec!["Primo Levi"]);
+    }
+
+    #[test]
+    fn test_modify_pdf_keeps_unknown_xmp_properties() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = dir.path().join("original.pdf");
+        let modified = dir.path().join("modified.pdf");
+        let again = dir.path().join("again.pdf");
+        create_test_pdf(&original, 1, None);
+
+        // XMP con proprietà che ritmo non gestisce
+        let packet = r#"<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about="" xmlns:dc="http://purl.org/dc/elements/1.1/"
+    xmlns:pdf="http://ns.adobe.com/pdf/1.3/" xmlns:xmpMM="http://ns.adobe.com/xap/1.0/mm/"
+    xmlns:prism="http://prismstandard.org/namespaces/basic/2.0/"
+    pdf:Producer="Acrobat" xmpMM:DocumentID="uuid:1234">
+   <dc:title><rdf:Alt><rdf:li xml:lang="x-default">Vecchio titolo</rdf:li></rdf:Alt></dc:title>
+   <dc:identifier>doi:10.1000/182</dc:identifier>
+   <prism:volume>3</prism:volume>
+  </rdf:Description>
+ </rdf:RDF>
+</x:xmpmeta>"#;
+        let mut doc = Document::load(&original).unwrap();
+        let stream_id = doc.add_object(Stream::new(Dictionary::new(), packet.as_bytes().to_vec()));
+        doc.catalog_mut().unwrap().set("Metadata", stream_id);
+        doc.save(&original).unwrap();
+
+        let new_meta = PdfMetadata {
+            title: Some("Nuovo titolo".to_string()),
+            ..Default::default()
+        };
+        modify_pdf_metadata(&original, &modified, &new_meta).unwrap();
+        modify_pdf_metadata(&modified, &again, &new_meta).unwrap();
+
+        let xmp = read_xmp_packet(&Document::load(&again).unwrap()).unwrap();
+        assert!(xmp.contains("<prism:volume>3</prism:volume>"));
+        assert!(xmp.contains(r#"xmpMM:DocumentID="uuid:1234""#));
+        assert!(xmp.contains("<dc:identifier>doi:10.1000/182</dc:identifier>"));
+        assert!(!xmp.contains("Vecchio titolo"));
+        assert_eq!(xmp.matches("<dc:title>").count(), 1);
+        assert!(!xmp.contains("pdf:Producer="));
+        assert_eq!(xmp.matches("<pdf:Producer>").count(), 1);
+
+        let meta = parse_xmp(&xmp);
+        assert_eq!(meta.title.as_deref(), Some("Nuovo titolo"));
+        assert_eq!(meta.producer.as_deref(), Some("Acrobat"));
+    }
+
+    #[test]
+    fn test_modify_pdf_without_info_dictionary() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = dir.path().join("original.pdf");
+        let modified = dir.path().join("modified.pdf");
+        create_test_pdf(&original, 1, None);
+
+        let new_meta = PdfMetadata {
+            title: Some("Titolo".to_string()),
+            ..Default::default()
+        };
+        modify_pdf_metadata(&original, &modified, &new_meta).unwrap();
+
+        let meta = read_pdf_metadata(&modified).unwrap();
+        assert_eq!(meta.title.as_deref(), Some("Titolo"));
+    }
+}
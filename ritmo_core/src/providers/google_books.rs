@@ -0,0 +1,178 @@
+use super::{
+    array, encode, find_year, get_json, str_field, strings, HttpClient, MetadataProvider,
+    ProviderQuery, ProviderRecord,
+};
+use ritmo_errors::RitmoResult;
+use serde_json::Value;
+use std::sync::Arc;
+
+const DEFAULT_BASE_URL: &str = "https://www.googleapis.com";
+
+/// Google Books: API `volumes`, per ISBN o per titolo e autore
+///
+/// La chiave API è facoltativa; senza chiave Google applica limiti più bassi.
+pub struct GoogleBooksProvider {
+    client: Arc<dyn HttpClient>,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl GoogleBooksProvider {
+    pub fn new(client: Arc<dyn HttpClient>) -> Self {
+        Self {
+            client,
+            base_url: DEFAULT_BASE_URL.to_string(),
+            api_key: None,
+        }
+    }
+
+    /// Usa un altro indirizzo (mirror o server di test)
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_api_key(mut self, api_key: Option<String>) -> Self {
+        self.api_key = api_key.filter(|k| !k.trim().is_empty());
+        self
+    }
+}
+
+impl MetadataProvider for GoogleBooksProvider {
+    fn name(&self) -> &'static str {
+        "googlebooks"
+    }
+
+    fn confidence(&self) -> f32 {
+        0.85
+    }
+
+    fn lookup(&self, query: &ProviderQuery) -> RitmoResult<Option<ProviderRecord>> {
+        let q = match query {
+            ProviderQuery::Isbn(isbn) => format!("isbn:{}", isbn),
+            ProviderQuery::Title { title, author } => match author {
+                Some(author) => format!("intitle:{} inauthor:{}", title, author),
+                None => format!("intitle:{}", title),
+            },
+        };
+        let mut url = format!(
+            "{}/books/v1/volumes?q={}&maxResults=1",
+            self.base_url,
+            encode(&q)
+        );
+        if let Some(key) = &self.api_key {
+            url.push_str(&format!("&key={}", encode(key)));
+        }
+
+        let Some(json) = get_json(self.client.as_ref(), &url)? else {
+            return Ok(None);
+        };
+        let Some(info) = array(&json, "items")
+            .first()
+            .and_then(|item| item.get("volumeInfo"))
+        else {
+            return Ok(None);
+        };
+
+        let mut record = ProviderRecord::new(self.name());
+        record.title = str_field(info, "title");
+        for name in strings(info, "authors") {
+            record.add_author(name);
+        }
+        record.publisher = str_field(info, "publisher");
+        record.year = info
+            .get("publishedDate")
+            .and_then(Value::as_str)
+            .and_then(find_year);
+        record.pages = info
+            .get("pageCount")
+            .and_then(Value::as_i64)
+            .filter(|p| *p > 0);
+        // Preferisce l'ISBN-13 se presenti entrambe le forme
+        let mut identifiers = array(info, "industryIdentifiers");
+        identifiers.sort_by_key(|id| id.get("type").and_then(Value::as_str) != Some("ISBN_13"));
+        for id in identifiers {
+            if let Some(value) = id.get("identifier").and_then(Value::as_str) {
+                record.set_isbn(value);
+            }
+        }
+        if let Some(code) = info.get("language").and_then(Value::as_str) {
+            record.add_language(code);
+        }
+        for category in strings(info, "categories") {
+            record.add_subject(category);
+        }
+        record.url = str_field(info, "canonicalVolumeLink").or_else(|| str_field(info, "infoLink"));
+
+        Ok(Some(record).filter(|r| !r.is_empty()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::mock::MockServer;
+    use crate::providers::UreqClient;
+
+    const VOLUMES_RESPONSE: &str = r#"{
+        "kind": "books#volumes",
+        "totalItems": 1,
+        "items": [{
+            "volumeInfo": {
+                "title": "Carrie",
+                "authors": ["Stephen King"],
+                "publisher": "Doubleday",
+                "publishedDate": "1974-04-05",
+                "industryIdentifiers": [
+                    {"type": "ISBN_10", "identifier": "0385086954"},
+                    {"type": "ISBN_13", "identifier": "9780385086950"}
+                ],
+                "pageCount": 199,
+                "categories": ["Fiction"],
+                "language": "en",
+                "canonicalVolumeLink": "https://books.google.com/books/about/Carrie.html?id=x"
+            }
+        }]
+    }"#;
+
+    #[test]
+    fn test_lookup_by_isbn_with_api_key() {
+        let server = MockServer::start(vec![("/books/v1/volumes", 200, VOLUMES_RESPONSE)]);
+        let provider = GoogleBooksProvider::new(Arc::new(UreqClient::new()))
+            .with_base_url(&server.base_url)
+            .with_api_key(Some("secret".to_string()));
+        let query = ProviderQuery::isbn("0385086954").unwrap();
+        let record = provider.lookup(&query).unwrap().unwrap();
+
+        assert_eq!(record.provider, "googlebooks");
+        assert_eq!(record.title.as_deref(), Some("Carrie"));
+        assert_eq!(record.publisher.as_deref(), Some("Doubleday"));
+        assert_eq!(record.year, Some(1974));
+        assert_eq!(record.pages, Some(199));
+        assert_eq!(record.isbn.as_deref(), Some("9780385086950"));
+        assert_eq!(record.languages, vec!["en"]);
+        assert_eq!(record.subjects, vec!["Fiction"]);
+        assert_eq!(
+            server.requests(),
+            vec!["/books/v1/volumes?q=isbn%3A9780385086950&maxResults=1&key=secret"]
+        );
+    }
+
+    #[test]
+    fn test_lookup_by_title_not_found() {
+        let server = MockServer::start(vec![(
+            "/books/v1/volumes",
+            200,
+            r#"{"kind": "books#volumes", "totalItems": 0}"#,
+        )]);
+        let provider =
+            GoogleBooksProvider::new(Arc::new(UreqClient::new())).with_base_url(&server.base_url);
+        let query = ProviderQuery::title("Carrie", Some("Stephen King"));
+
+        assert!(provider.lookup(&query).unwrap().is_none());
+        assert_eq!(
+            server.requests(),
+            vec!["/books/v1/volumes?q=intitle%3ACarrie+inauthor%3AStephen+King&maxResults=1"]
+        );
+    }
+}
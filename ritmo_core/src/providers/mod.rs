@@ -0,0 +1,853 @@
+//! Fornitori di metadati online (Open Library, Google Books, Wikidata)
+//!
+//! Un `MetadataProvider` cerca un libro per ISBN o per titolo e autore e
+//! restituisce un `ProviderRecord` normalizzato. Le chiamate HTTP passano da un
+//! `HttpClient` iniettabile: `UreqClient` in produzione, un server locale nei
+//! test. `merge_records` fonde poi i risultati in un `ImportObject`, con
+//! confidenza e origine per campo. La cache su database è gestita da
+//! `service::metadata_lookup_service`.
+
+mod google_books;
+mod open_library;
+mod wikidata;
+
+pub use google_books::GoogleBooksProvider;
+pub use open_library::OpenLibraryProvider;
+pub use wikidata::WikidataProvider;
+
+use crate::dto::{ContentInput, ImportObject, LanguageInput, PersonInput};
+use crate::extractors::{normalize_language_code, parse_year};
+use ritmo_db_core::isbn::Isbn;
+use ritmo_errors::{RitmoErr, RitmoResult};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Nomi dei fornitori disponibili, nell'ordine di priorità predefinito
+pub const PROVIDER_NAMES: &[&str] = &["openlibrary", "googlebooks", "wikidata"];
+
+/// Fattore applicato alla confidenza quando la ricerca è per titolo e autore:
+/// il risultato potrebbe essere un'altra edizione o un'opera omonima
+pub const TITLE_MATCH_FACTOR: f32 = 0.8;
+
+/// Aumento di confidenza quando un fornitore conferma il valore già presente
+pub const AGREEMENT_BONUS: f32 = 0.05;
+
+/// Numero massimo di soggetti importati come tag
+const MAX_SUBJECT_TAGS: usize = 10;
+
+/// Risposta HTTP ridotta a ciò che serve ai fornitori
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// Client HTTP usato dai fornitori (sostituibile nei test)
+pub trait HttpClient: Send + Sync {
+    /// Esegue una GET; gli stati HTTP di errore sono restituiti, non convertiti in `Err`
+    fn get(&self, url: &str) -> RitmoResult<HttpResponse>;
+}
+
+/// Client HTTP reale, bloccante
+pub struct UreqClient {
+    agent: ureq::Agent,
+}
+
+impl UreqClient {
+    pub fn new() -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout(Duration::from_secs(20))
+            .user_agent(concat!(
+                "ritmo/",
+                env!("CARGO_PKG_VERSION"),
+                " (https://github.com/pierrotbru/full-ritmo)"
+            ))
+            .build();
+        Self { agent }
+    }
+}
+
+impl Default for UreqClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HttpClient for UreqClient {
+    fn get(&self, url: &str) -> RitmoResult<HttpResponse> {
+        let response = match self.agent.get(url).set("Accept", "application/json").call() {
+            Ok(response) => response,
+            Err(ureq::Error::Status(_, response)) => response,
+            Err(e) => return Err(RitmoErr::Generic(format!("Richiesta HTTP fallita: {}", e))),
+        };
+        let status = response.status();
+        let body = response.into_string()?;
+        Ok(HttpResponse { status, body })
+    }
+}
+
+/// GET con risposta JSON; None per 404
+pub(crate) fn get_json(
+    client: &dyn HttpClient,
+    url: &str,
+) -> RitmoResult<Option<serde_json::Value>> {
+    let response = client.get(url)?;
+    match response.status {
+        200..=299 => Ok(Some(serde_json::from_str(&response.body)?)),
+        404 => Ok(None),
+        status => Err(RitmoErr::Generic(format!(
+            "Risposta HTTP {} da {}",
+            status, url
+        ))),
+    }
+}
+
+/// Codifica un valore per la query string di un URL
+pub(crate) fn encode(value: &str) -> String {
+    url::form_urlencoded::byte_serialize(value.as_bytes()).collect()
+}
+
+/// Stringa non vuota di un oggetto JSON
+pub(crate) fn str_field(value: &Value, key: &str) -> Option<String> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// Elementi di un array JSON (vuoto se assente)
+pub(crate) fn array<'a>(value: &'a Value, key: &str) -> Vec<&'a Value> {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .map(|items| items.iter().collect())
+        .unwrap_or_default()
+}
+
+/// Stringhe di un array JSON
+pub(crate) fn strings<'a>(value: &'a Value, key: &str) -> Vec<&'a str> {
+    array(value, key)
+        .into_iter()
+        .filter_map(Value::as_str)
+        .collect()
+}
+
+/// Criterio di ricerca presso un fornitore
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProviderQuery {
+    /// ISBN-13 canonico
+    Isbn(String),
+    Title {
+        title: String,
+        author: Option<String>,
+    },
+}
+
+impl ProviderQuery {
+    /// Ricerca per ISBN (ISBN-10 o ISBN-13, convertito in ISBN-13)
+    pub fn isbn(value: &str) -> RitmoResult<Self> {
+        let isbn = Isbn::parse(value)
+            .map_err(|e| RitmoErr::Generic(format!("ISBN '{}' non valido: {}", value, e)))?;
+        Ok(Self::Isbn(isbn.to_isbn13()))
+    }
+
+    /// Ricerca per titolo e, se noto, autore
+    pub fn title(title: &str, author: Option<&str>) -> Self {
+        Self::Title {
+            title: title.trim().to_string(),
+            author: author
+                .map(|a| a.trim().to_string())
+                .filter(|a| !a.is_empty()),
+        }
+    }
+
+    /// Ricerca più precisa possibile per un `ImportObject`: ISBN valido,
+    /// altrimenti titolo e primo autore
+    pub fn from_import_object(obj: &ImportObject) -> Option<Self> {
+        if let Some(query) = obj.book.isbn.as_deref().and_then(|i| Self::isbn(i).ok()) {
+            return Some(query);
+        }
+        Self::title_from_import_object(obj)
+    }
+
+    /// Ricerca per titolo e primo autore di un `ImportObject`
+    pub fn title_from_import_object(obj: &ImportObject) -> Option<Self> {
+        if obj.book.title.trim().is_empty() {
+            return None;
+        }
+        let author = obj
+            .contents
+            .first()
+            .and_then(|c| c.people.iter().find(|p| p.role == "role.author"))
+            .map(|p| p.name.as_str());
+        Some(Self::title(&obj.book.title, author))
+    }
+
+    /// Chiave della cache: indipendente da maiuscole e spazi superflui
+    pub fn cache_key(&self) -> String {
+        let normalize = |s: &str| {
+            s.split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_lowercase()
+        };
+        match self {
+            Self::Isbn(isbn) => format!("isbn:{}", isbn),
+            Self::Title { title, author } => format!(
+                "title:{}|author:{}",
+                normalize(title),
+                author.as_deref().map(normalize).unwrap_or_default()
+            ),
+        }
+    }
+
+    pub fn is_isbn(&self) -> bool {
+        matches!(self, Self::Isbn(_))
+    }
+}
+
+/// Metadati trovati da un fornitore, già normalizzati
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProviderRecord {
+    pub provider: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub year: Option<i32>,
+    /// ISBN-13 canonico
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub isbn: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pages: Option<i64>,
+    /// Codici lingua canonici (ISO 639-1, o 639-3 se manca)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub series: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub series_index: Option<i64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subjects: Vec<String>,
+    /// Pagina del libro presso il fornitore
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+impl ProviderRecord {
+    pub(crate) fn new(provider: &str) -> Self {
+        Self {
+            provider: provider.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn add_author(&mut self, name: &str) {
+        push_unique(&mut self.authors, name);
+    }
+
+    pub(crate) fn add_language(&mut self, code: &str) {
+        if let Some(code) = normalize_language_code(code) {
+            push_unique(&mut self.languages, &code);
+        }
+    }
+
+    pub(crate) fn add_subject(&mut self, subject: &str) {
+        push_unique(&mut self.subjects, subject);
+    }
+
+    /// Imposta l'ISBN se valido, nella forma ISBN-13
+    pub(crate) fn set_isbn(&mut self, value: &str) {
+        if self.isbn.is_none() {
+            self.isbn = Isbn::parse(value).ok().map(|i| i.to_isbn13());
+        }
+    }
+
+    /// True se il fornitore non ha restituito alcun dato utile
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.authors.is_empty()
+            && self.publisher.is_none()
+            && self.year.is_none()
+            && self.isbn.is_none()
+            && self.pages.is_none()
+    }
+}
+
+fn push_unique(values: &mut Vec<String>, value: &str) {
+    let value = value.trim();
+    if !value.is_empty() && !values.iter().any(|v| v.eq_ignore_ascii_case(value)) {
+        values.push(value.to_string());
+    }
+}
+
+/// Anno da una data libera ("1986", "1986-09-15", "September 1986", "+1986-01-01T00:00:00Z")
+pub(crate) fn find_year(date: &str) -> Option<i32> {
+    let date = date.trim().trim_start_matches('+');
+    if let Some(year) = parse_year(date) {
+        return Some(year);
+    }
+    date.split(|c: char| !c.is_ascii_digit())
+        .filter(|part| part.len() == 4)
+        .find_map(|part| part.parse().ok())
+}
+
+/// Fornitore di metadati online
+pub trait MetadataProvider: Send + Sync {
+    /// Nome breve, usato per la cache e per l'attribuzione (es. "openlibrary")
+    fn name(&self) -> &'static str;
+
+    /// Confidenza dei campi trovati con una ricerca per ISBN
+    fn confidence(&self) -> f32;
+
+    /// Cerca il libro; None se il fornitore non lo conosce
+    fn lookup(&self, query: &ProviderQuery) -> RitmoResult<Option<ProviderRecord>>;
+}
+
+/// Crea un fornitore dal nome (vedi `PROVIDER_NAMES`)
+pub fn provider_by_name(
+    name: &str,
+    client: Arc<dyn HttpClient>,
+) -> Option<Arc<dyn MetadataProvider>> {
+    let provider: Arc<dyn MetadataProvider> = match name {
+        "openlibrary" => Arc::new(OpenLibraryProvider::new(client)),
+        "googlebooks" => Arc::new(GoogleBooksProvider::new(client)),
+        "wikidata" => Arc::new(WikidataProvider::new(client)),
+        _ => return None,
+    };
+    Some(provider)
+}
+
+/// Tutti i fornitori, nell'ordine di priorità predefinito
+pub fn default_providers(client: Arc<dyn HttpClient>) -> Vec<Arc<dyn MetadataProvider>> {
+    PROVIDER_NAMES
+        .iter()
+        .filter_map(|name| provider_by_name(name, client.clone()))
+        .collect()
+}
+
+/// Risultato di un fornitore con la confidenza da attribuire ai suoi campi
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProviderMatch {
+    pub record: ProviderRecord,
+    pub confidence: f32,
+}
+
+impl ProviderMatch {
+    /// Confidenza del fornitore, ridotta per le ricerche per titolo
+    pub fn new(record: ProviderRecord, provider_confidence: f32, query: &ProviderQuery) -> Self {
+        let confidence = if query.is_isbn() {
+            provider_confidence
+        } else {
+            round_confidence(provider_confidence * TITLE_MATCH_FACTOR)
+        };
+        Self { record, confidence }
+    }
+}
+
+/// Fonde i risultati dei fornitori in un `ImportObject`
+///
+/// Per ogni campo il valore del fornitore viene usato se il campo è vuoto o se
+/// la sua confidenza supera quella attuale (un valore senza confidenza, scritto
+/// a mano, vale 1.0). Un fornitore che conferma il valore presente ne aumenta
+/// la confidenza di `AGREEMENT_BONUS`. L'origine di ogni campo toccato va in
+/// `sources` ("file" per il valore del file). Restituisce le chiavi dei campi
+/// modificati.
+pub fn merge_records(obj: &mut ImportObject, matches: &[ProviderMatch]) -> Vec<String> {
+    let mut ordered: Vec<&ProviderMatch> = matches.iter().collect();
+    ordered.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+
+    let mut merger = Merger {
+        confidence: obj.confidence.take().unwrap_or_default(),
+        sources: obj.sources.take().unwrap_or_default(),
+        changed: Vec::new(),
+    };
+
+    if obj.contents.is_empty() {
+        obj.contents.push(ContentInput {
+            title: obj.book.title.clone(),
+            original_title: obj.book.original_title.clone(),
+            people: Vec::new(),
+            content_type: None,
+            year: None,
+            languages: Vec::new(),
+        });
+    }
+
+    for m in ordered {
+        let record = &m.record;
+        let source = record.provider.as_str();
+        let conf = m.confidence;
+
+        // Titolo: l'opera segue il libro se ne condivideva il titolo
+        let mut title = (!obj.book.title.trim().is_empty()).then(|| obj.book.title.clone());
+        let previous_title = title.clone();
+        if merger.merge(
+            "book.title",
+            &mut title,
+            record.title.clone(),
+            source,
+            conf,
+            |a, b| text_eq(a, b),
+        ) {
+            let new_title = title.unwrap_or_default();
+            if obj.contents[0].title == previous_title.unwrap_or_default() {
+                obj.contents[0].title = new_title.clone();
+                merger.copy("book.title", "contents[0].title");
+            }
+            obj.book.title = new_title;
+        }
+
+        merger.merge(
+            "book.publisher",
+            &mut obj.book.publisher,
+            record.publisher.clone(),
+            source,
+            conf,
+            |a, b| text_eq(a, b),
+        );
+        merger.merge(
+            "book.year",
+            &mut obj.book.year,
+            record.year,
+            source,
+            conf,
+            PartialEq::eq,
+        );
+        merger.merge(
+            "book.isbn",
+            &mut obj.book.isbn,
+            record.isbn.clone(),
+            source,
+            conf,
+            |a, b| isbn_eq(a, b),
+        );
+        merger.merge(
+            "book.pages",
+            &mut obj.book.pages,
+            record.pages,
+            source,
+            conf,
+            PartialEq::eq,
+        );
+        if merger.merge(
+            "book.series",
+            &mut obj.book.series,
+            record.series.clone(),
+            source,
+            conf,
+            |a, b| text_eq(a, b),
+        ) {
+            obj.book.series_index = record.series_index;
+        }
+
+        // Autori dell'opera (gli altri ruoli restano invariati)
+        let content = &mut obj.contents[0];
+        let authors: Vec<String> = content
+            .people
+            .iter()
+            .filter(|p| p.role == "role.author")
+            .map(|p| p.name.clone())
+            .collect();
+        let mut current = (!authors.is_empty()).then_some(authors);
+        let candidate = (!record.authors.is_empty()).then(|| record.authors.clone());
+        if merger.merge(
+            "contents[0].people",
+            &mut current,
+            candidate,
+            source,
+            conf,
+            |a, b| list_eq(a, b),
+        ) {
+            content.people.retain(|p| p.role != "role.author");
+            for name in current.unwrap_or_default() {
+                content.people.push(PersonInput {
+                    name,
+                    role: "role.author".to_string(),
+                    source: Some(source.to_string()),
+                });
+            }
+        }
+
+        // Lingue dell'edizione
+        let languages: Vec<String> = content
+            .languages
+            .iter()
+            .filter(|l| l.role == "language_role.actual")
+            .map(|l| l.code.clone())
+            .collect();
+        let mut current = (!languages.is_empty()).then_some(languages);
+        let candidate = (!record.languages.is_empty()).then(|| record.languages.clone());
+        if merger.merge(
+            "contents[0].languages",
+            &mut current,
+            candidate,
+            source,
+            conf,
+            |a, b| list_eq(a, b),
+        ) {
+            content
+                .languages
+                .retain(|l| l.role != "language_role.actual");
+            for code in current.unwrap_or_default() {
+                content.languages.push(LanguageInput {
+                    code,
+                    role: "language_role.actual".to_string(),
+                });
+            }
+        }
+
+        // Soggetti come tag, solo se il file non ne ha
+        if obj.book.tags.is_empty() && !record.subjects.is_empty() {
+            obj.book.tags = record
+                .subjects
+                .iter()
+                .take(MAX_SUBJECT_TAGS)
+                .cloned()
+                .collect();
+            merger.set("book.tags", source, conf);
+        }
+    }
+
+    obj.confidence = Some(merger.confidence);
+    obj.sources = (!merger.sources.is_empty()).then_some(merger.sources);
+    merger.changed
+}
+
+struct Merger {
+    confidence: HashMap<String, f32>,
+    sources: HashMap<String, String>,
+    changed: Vec<String>,
+}
+
+impl Merger {
+    /// Applica la regola di fusione a un campo; true se il valore è cambiato
+    fn merge<T: Clone + PartialEq>(
+        &mut self,
+        key: &str,
+        current: &mut Option<T>,
+        candidate: Option<T>,
+        source: &str,
+        confidence: f32,
+        eq: fn(&T, &T) -> bool,
+    ) -> bool {
+        let Some(candidate) = candidate else {
+            return false;
+        };
+        let Some(value) = current.as_ref() else {
+            *current = Some(candidate);
+            self.set(key, source, confidence);
+            return true;
+        };
+
+        let current_confidence = self.confidence.get(key).copied().unwrap_or(1.0);
+        if eq(value, &candidate) {
+            let boosted =
+                round_confidence((current_confidence.max(confidence) + AGREEMENT_BONUS).min(0.99));
+            self.confidence
+                .insert(key.to_string(), boosted.max(current_confidence));
+            let sources = self
+                .sources
+                .entry(key.to_string())
+                .or_insert_with(|| "file".to_string());
+            if !sources.split(',').any(|s| s == source) {
+                sources.push(',');
+                sources.push_str(source);
+            }
+            // Stesso valore a meno di maiuscole e spazi: prevale la forma più affidabile
+            if confidence > current_confidence && *value != candidate {
+                *current = Some(candidate);
+                self.mark_changed(key);
+                return true;
+            }
+            false
+        } else if confidence > current_confidence {
+            *current = Some(candidate);
+            self.set(key, source, confidence);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn set(&mut self, key: &str, source: &str, confidence: f32) {
+        self.confidence.insert(key.to_string(), confidence);
+        self.sources.insert(key.to_string(), source.to_string());
+        self.mark_changed(key);
+    }
+
+    fn mark_changed(&mut self, key: &str) {
+        if !self.changed.iter().any(|k| k == key) {
+            self.changed.push(key.to_string());
+        }
+    }
+
+    /// Copia confidenza e origine di un campo su un altro
+    fn copy(&mut self, from: &str, to: &str) {
+        if let Some(confidence) = self.confidence.get(from).copied() {
+            self.confidence.insert(to.to_string(), confidence);
+        }
+        if let Some(source) = self.sources.get(from).cloned() {
+            self.sources.insert(to.to_string(), source);
+        }
+    }
+}
+
+/// Arrotonda a due decimali, come i punteggi degli estrattori
+fn round_confidence(value: f32) -> f32 {
+    (value * 100.0).round() / 100.0
+}
+
+fn normalize_text(value: &str) -> String {
+    value
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn text_eq(a: &str, b: &str) -> bool {
+    normalize_text(a) == normalize_text(b)
+}
+
+fn isbn_eq(a: &str, b: &str) -> bool {
+    match (Isbn::parse(a), Isbn::parse(b)) {
+        (Ok(a), Ok(b)) => a.to_isbn13() == b.to_isbn13(),
+        _ => text_eq(a, b),
+    }
+}
+
+fn list_eq(a: &[String], b: &[String]) -> bool {
+    let mut a: Vec<String> = a.iter().map(|s| normalize_text(s)).collect();
+    let mut b: Vec<String> = b.iter().map(|s| normalize_text(s)).collect();
+    a.sort();
+    b.sort();
+    a == b
+}
+
+#[cfg(test)]
+pub(crate) mod mock {
+    //! Server HTTP locale per i test dei fornitori
+
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    /// Risponde a ogni richiesta con la prima rotta il cui prefisso corrisponde
+    /// al percorso (404 se nessuna corrisponde) e registra i percorsi richiesti
+    pub struct MockServer {
+        pub base_url: String,
+        requests: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl MockServer {
+        pub fn start(routes: Vec<(&'static str, u16, &'static str)>) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let base_url = format!("http://{}", listener.local_addr().unwrap());
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let log = requests.clone();
+
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let Ok(mut stream) = stream else { continue };
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut request_line = String::new();
+                    if reader.read_line(&mut request_line).is_err() {
+                        continue;
+                    }
+                    // Scarta gli header
+                    let mut line = String::new();
+                    while reader.read_line(&mut line).is_ok() && line.trim() != "" {
+                        line.clear();
+                    }
+
+                    let path = request_line
+                        .split_whitespace()
+                        .nth(1)
+                        .unwrap_or_default()
+                        .to_string();
+                    log.lock().unwrap().push(path.clone());
+
+                    let (status, body) = routes
+                        .iter()
+                        .find(|(prefix, _, _)| path.starts_with(prefix))
+                        .map(|(_, status, body)| (*status, *body))
+                        .unwrap_or((404, "{}"));
+                    let response = format!(
+                        "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        body.len(),
+                        body
+                    );
+                    let _ = stream.write_all(response.as_bytes());
+                }
+            });
+
+            Self { base_url, requests }
+        }
+
+        /// Percorsi (con query string) richiesti finora
+        pub fn requests(&self) -> Vec<String> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mock::MockServer;
+    use super::*;
+    use crate::dto::BookInput;
+
+    fn import_object() -> ImportObject {
+        ImportObject {
+            file_path: "it.epub".to_string(),
+            book: BookInput {
+                title: "it".to_string(),
+                year: Some(1986),
+                ..Default::default()
+            },
+            attach_to: None,
+            contents: vec![ContentInput {
+                title: "it".to_string(),
+                original_title: None,
+                people: vec![PersonInput {
+                    name: "Stephen King".to_string(),
+                    role: "role.author".to_string(),
+                    source: None,
+                }],
+                content_type: None,
+                year: None,
+                languages: Vec::new(),
+            }],
+            confidence: Some(HashMap::from([
+                ("book.title".to_string(), 0.5),
+                ("book.year".to_string(), 0.85),
+                ("contents[0].people".to_string(), 0.95),
+            ])),
+            sources: None,
+        }
+    }
+
+    #[test]
+    fn test_query_cache_key() {
+        let query = ProviderQuery::isbn("0-306-40615-2").unwrap();
+        assert_eq!(query.cache_key(), "isbn:9780306406157");
+        assert!(ProviderQuery::isbn("123").is_err());
+
+        let query = ProviderQuery::title("  Il  Nome della Rosa ", Some("Umberto Eco"));
+        assert_eq!(
+            query.cache_key(),
+            "title:il nome della rosa|author:umberto eco"
+        );
+
+        let obj = import_object();
+        assert_eq!(
+            ProviderQuery::from_import_object(&obj),
+            Some(ProviderQuery::title("it", Some("Stephen King")))
+        );
+    }
+
+    #[test]
+    fn test_merge_fills_missing_and_boosts_agreement() {
+        let mut obj = import_object();
+        let mut record = ProviderRecord::new("openlibrary");
+        record.title = Some("It".to_string());
+        record.authors = vec!["stephen king".to_string()];
+        record.publisher = Some("Viking".to_string());
+        record.year = Some(1986);
+        record.isbn = Some("9780670813025".to_string());
+        record.languages = vec!["en".to_string()];
+        record.subjects = vec!["Horror".to_string()];
+        let matches = vec![ProviderMatch {
+            record,
+            confidence: 0.9,
+        }];
+
+        let changed = merge_records(&mut obj, &matches);
+
+        // Titolo dal nome del file (0.5) confermato e riscritto, anche per l'opera
+        assert_eq!(obj.book.title, "It");
+        assert_eq!(obj.contents[0].title, "It");
+        assert_eq!(obj.book.publisher.as_deref(), Some("Viking"));
+        assert_eq!(obj.book.isbn.as_deref(), Some("9780670813025"));
+        assert_eq!(obj.book.tags, vec!["Horror"]);
+        assert_eq!(obj.contents[0].languages[0].code, "en");
+        assert!(changed.contains(&"book.publisher".to_string()));
+        assert!(!changed.contains(&"book.year".to_string()));
+
+        let confidence = obj.confidence.as_ref().unwrap();
+        let sources = obj.sources.as_ref().unwrap();
+        assert!((confidence["book.title"] - 0.95).abs() < 1e-6);
+        assert_eq!(sources["book.title"], "file,openlibrary");
+        assert_eq!(sources["contents[0].title"], "file,openlibrary");
+        // Valori confermati: confidenza aumentata, origine condivisa
+        assert!((confidence["book.year"] - 0.95).abs() < 1e-6);
+        assert_eq!(sources["book.year"], "file,openlibrary");
+        assert_eq!(sources["contents[0].people"], "file,openlibrary");
+        assert_eq!(obj.contents[0].people[0].name, "Stephen King");
+    }
+
+    #[test]
+    fn test_merge_keeps_more_confident_values() {
+        let mut obj = import_object();
+        obj.book.publisher = Some("Sperling & Kupfer".to_string());
+
+        let mut google = ProviderRecord::new("googlebooks");
+        google.publisher = Some("Viking".to_string());
+        google.authors = vec!["Richard Bachman".to_string()];
+        google.pages = Some(1138);
+        let mut wikidata = ProviderRecord::new("wikidata");
+        wikidata.pages = Some(1100);
+
+        let matches = vec![
+            ProviderMatch {
+                record: wikidata,
+                confidence: 0.85 * TITLE_MATCH_FACTOR,
+            },
+            ProviderMatch {
+                record: google,
+                confidence: 0.9 * TITLE_MATCH_FACTOR,
+            },
+        ];
+        merge_records(&mut obj, &matches);
+
+        // Editore scritto a mano (confidenza 1.0) e autore dal file (0.95) restano
+        assert_eq!(obj.book.publisher.as_deref(), Some("Sperling & Kupfer"));
+        assert_eq!(obj.contents[0].people[0].name, "Stephen King");
+        // Le pagine vengono dal fornitore più affidabile, non dal primo della lista
+        assert_eq!(obj.book.pages, Some(1138));
+        assert_eq!(obj.sources.as_ref().unwrap()["book.pages"], "googlebooks");
+    }
+
+    #[test]
+    fn test_ureq_client_against_mock_server() {
+        let server = MockServer::start(vec![("/ok", 200, r#"{"value": 1}"#), ("/fail", 500, "{}")]);
+        let client = UreqClient::new();
+
+        let json = get_json(&client, &format!("{}/ok?q=a", server.base_url)).unwrap();
+        assert_eq!(json.unwrap()["value"], 1);
+        assert!(get_json(&client, &format!("{}/missing", server.base_url))
+            .unwrap()
+            .is_none());
+        assert!(get_json(&client, &format!("{}/fail", server.base_url)).is_err());
+        assert_eq!(server.requests()[0], "/ok?q=a");
+    }
+
+    #[test]
+    fn test_find_year() {
+        assert_eq!(find_year("1986"), Some(1986));
+        assert_eq!(find_year("September 15, 1986"), Some(1986));
+        assert_eq!(find_year("+1986-09-15T00:00:00Z"), Some(1986));
+        assert_eq!(find_year("s.d."), None);
+    }
+}
@@ -0,0 +1,223 @@
+use super::{
+    array, encode, find_year, get_json, str_field, strings, HttpClient, MetadataProvider,
+    ProviderQuery, ProviderRecord,
+};
+use ritmo_errors::RitmoResult;
+use serde_json::Value;
+use std::sync::Arc;
+
+const DEFAULT_BASE_URL: &str = "https://openlibrary.org";
+
+/// Open Library: Books API per ISBN, Search API per titolo e autore
+pub struct OpenLibraryProvider {
+    client: Arc<dyn HttpClient>,
+    base_url: String,
+}
+
+impl OpenLibraryProvider {
+    pub fn new(client: Arc<dyn HttpClient>) -> Self {
+        Self {
+            client,
+            base_url: DEFAULT_BASE_URL.to_string(),
+        }
+    }
+
+    /// Usa un altro indirizzo (mirror o server di test)
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    fn lookup_isbn(&self, isbn: &str) -> RitmoResult<Option<ProviderRecord>> {
+        let url = format!(
+            "{}/api/books?bibkeys=ISBN:{}&format=json&jscmd=data",
+            self.base_url, isbn
+        );
+        let Some(json) = get_json(self.client.as_ref(), &url)? else {
+            return Ok(None);
+        };
+        let Some(data) = json.get(format!("ISBN:{}", isbn)) else {
+            return Ok(None);
+        };
+
+        let mut record = ProviderRecord::new(self.name());
+        record.title = str_field(data, "title");
+        for author in array(data, "authors") {
+            if let Some(name) = author.get("name").and_then(Value::as_str) {
+                record.add_author(name);
+            }
+        }
+        record.publisher = array(data, "publishers")
+            .first()
+            .and_then(|p| p.get("name"))
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        record.year = data
+            .get("publish_date")
+            .and_then(Value::as_str)
+            .and_then(find_year);
+        record.pages = data.get("number_of_pages").and_then(Value::as_i64);
+        record.set_isbn(isbn);
+        for subject in array(data, "subjects") {
+            if let Some(name) = subject.get("name").and_then(Value::as_str) {
+                record.add_subject(name);
+            }
+        }
+        record.url = str_field(data, "url");
+        Ok(Some(record))
+    }
+
+    fn lookup_title(
+        &self,
+        title: &str,
+        author: Option<&str>,
+    ) -> RitmoResult<Option<ProviderRecord>> {
+        let mut url = format!("{}/search.json?title={}", self.base_url, encode(title));
+        if let Some(author) = author {
+            url.push_str(&format!("&author={}", encode(author)));
+        }
+        url.push_str("&limit=1");
+
+        let Some(json) = get_json(self.client.as_ref(), &url)? else {
+            return Ok(None);
+        };
+        let Some(doc) = array(&json, "docs").first().copied() else {
+            return Ok(None);
+        };
+
+        let mut record = ProviderRecord::new(self.name());
+        record.title = str_field(doc, "title");
+        for name in strings(doc, "author_name") {
+            record.add_author(name);
+        }
+        record.publisher = strings(doc, "publisher").first().map(|p| p.to_string());
+        record.year = doc
+            .get("first_publish_year")
+            .and_then(Value::as_i64)
+            .map(|y| y as i32);
+        record.pages = doc.get("number_of_pages_median").and_then(Value::as_i64);
+        for isbn in strings(doc, "isbn") {
+            record.set_isbn(isbn);
+        }
+        for code in strings(doc, "language") {
+            record.add_language(code);
+        }
+        for subject in strings(doc, "subject") {
+            record.add_subject(subject);
+        }
+        record.url = doc
+            .get("key")
+            .and_then(Value::as_str)
+            .map(|key| format!("{}{}", DEFAULT_BASE_URL, key));
+        Ok(Some(record))
+    }
+}
+
+impl MetadataProvider for OpenLibraryProvider {
+    fn name(&self) -> &'static str {
+        "openlibrary"
+    }
+
+    fn confidence(&self) -> f32 {
+        0.9
+    }
+
+    fn lookup(&self, query: &ProviderQuery) -> RitmoResult<Option<ProviderRecord>> {
+        let record = match query {
+            ProviderQuery::Isbn(isbn) => self.lookup_isbn(isbn)?,
+            ProviderQuery::Title { title, author } => {
+                self.lookup_title(title, author.as_deref())?
+            }
+        };
+        Ok(record.filter(|r| !r.is_empty()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::mock::MockServer;
+    use crate::providers::UreqClient;
+
+    const BOOKS_RESPONSE: &str = r#"{
+        "ISBN:9780670813025": {
+            "url": "https://openlibrary.org/books/OL2828692M/It",
+            "title": "It",
+            "authors": [{"name": "Stephen King", "url": "https://openlibrary.org/authors/OL2162284A"}],
+            "publishers": [{"name": "Viking"}],
+            "publish_date": "1986",
+            "number_of_pages": 1138,
+            "subjects": [{"name": "Horror tales"}, {"name": "Maine"}]
+        }
+    }"#;
+
+    const SEARCH_RESPONSE: &str = r#"{
+        "numFound": 1,
+        "docs": [{
+            "key": "/works/OL81613W",
+            "title": "It",
+            "author_name": ["Stephen King"],
+            "first_publish_year": 1986,
+            "publisher": ["Viking", "Sperling & Kupfer"],
+            "isbn": ["0670813028", "9780670813025"],
+            "language": ["eng", "ita"],
+            "number_of_pages_median": 1116
+        }]
+    }"#;
+
+    fn provider(server: &MockServer) -> OpenLibraryProvider {
+        OpenLibraryProvider::new(Arc::new(UreqClient::new())).with_base_url(&server.base_url)
+    }
+
+    #[test]
+    fn test_lookup_by_isbn() {
+        let server = MockServer::start(vec![("/api/books", 200, BOOKS_RESPONSE)]);
+        let query = ProviderQuery::isbn("0-670-81302-8").unwrap();
+        let record = provider(&server).lookup(&query).unwrap().unwrap();
+
+        assert_eq!(record.provider, "openlibrary");
+        assert_eq!(record.title.as_deref(), Some("It"));
+        assert_eq!(record.authors, vec!["Stephen King"]);
+        assert_eq!(record.publisher.as_deref(), Some("Viking"));
+        assert_eq!(record.year, Some(1986));
+        assert_eq!(record.pages, Some(1138));
+        assert_eq!(record.isbn.as_deref(), Some("9780670813025"));
+        assert_eq!(record.subjects, vec!["Horror tales", "Maine"]);
+        assert_eq!(
+            server.requests(),
+            vec!["/api/books?bibkeys=ISBN:9780670813025&format=json&jscmd=data"]
+        );
+    }
+
+    #[test]
+    fn test_lookup_by_title() {
+        let server = MockServer::start(vec![("/search.json", 200, SEARCH_RESPONSE)]);
+        let query = ProviderQuery::title("It", Some("Stephen King"));
+        let record = provider(&server).lookup(&query).unwrap().unwrap();
+
+        assert_eq!(record.year, Some(1986));
+        assert_eq!(record.isbn.as_deref(), Some("9780670813025"));
+        assert_eq!(record.languages, vec!["en", "it"]);
+        assert_eq!(
+            record.url.as_deref(),
+            Some("https://openlibrary.org/works/OL81613W")
+        );
+        assert_eq!(
+            server.requests(),
+            vec!["/search.json?title=It&author=Stephen+King&limit=1"]
+        );
+    }
+
+    #[test]
+    fn test_lookup_not_found() {
+        let server = MockServer::start(vec![
+            ("/api/books", 200, "{}"),
+            ("/search.json", 200, r#"{"numFound": 0, "docs": []}"#),
+        ]);
+        let provider = provider(&server);
+        let query = ProviderQuery::isbn("9780306406157").unwrap();
+        assert!(provider.lookup(&query).unwrap().is_none());
+        let query = ProviderQuery::title("Libro inesistente", None);
+        assert!(provider.lookup(&query).unwrap().is_none());
+    }
+}
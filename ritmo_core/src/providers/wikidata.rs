@@ -0,0 +1,283 @@
+use super::{
+    array, encode, find_year, get_json, HttpClient, MetadataProvider, ProviderQuery, ProviderRecord,
+};
+use ritmo_db_core::isbn::Isbn;
+use ritmo_errors::RitmoResult;
+use serde_json::Value;
+use std::sync::Arc;
+
+const DEFAULT_BASE_URL: &str = "https://query.wikidata.org";
+
+/// Proprietà lette per ogni opera o edizione: autore (anche dell'opera di cui
+/// l'elemento è un'edizione), editore, data, pagine, ISBN-13, lingua, serie e genere
+const PROPERTIES: &str = r#"
+  OPTIONAL { ?item wdt:P50|wdt:P629/wdt:P50 ?author . }
+  OPTIONAL { ?item wdt:P123 ?publisher . }
+  OPTIONAL { ?item wdt:P577 ?date . }
+  OPTIONAL { ?item wdt:P1104 ?pages . }
+  OPTIONAL { ?item wdt:P212 ?isbn13 . }
+  OPTIONAL { ?item wdt:P407 ?language . ?language wdt:P218 ?langCode . }
+  OPTIONAL {
+    ?item p:P179 ?seriesStatement . ?seriesStatement ps:P179 ?series .
+    OPTIONAL { ?seriesStatement pq:P1545 ?seriesOrdinal . }
+  }
+  OPTIONAL { ?item wdt:P136 ?genre . }
+  SERVICE wikibase:label { bd:serviceParam wikibase:language "[AUTO_LANGUAGE],en,it". }
+"#;
+
+const SELECT: &str = "SELECT ?item ?itemLabel ?authorLabel ?publisherLabel ?date ?pages ?isbn13 \
+                      ?langCode ?seriesLabel ?seriesOrdinal ?genreLabel";
+
+/// Wikidata: query SPARQL per ISBN (P212/P957) o per titolo tramite la ricerca
+/// di entità, limitata a opere letterarie, libri ed edizioni
+pub struct WikidataProvider {
+    client: Arc<dyn HttpClient>,
+    base_url: String,
+}
+
+impl WikidataProvider {
+    pub fn new(client: Arc<dyn HttpClient>) -> Self {
+        Self {
+            client,
+            base_url: DEFAULT_BASE_URL.to_string(),
+        }
+    }
+
+    /// Usa un altro indirizzo (mirror o server di test)
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    fn isbn_query(isbn13: &str) -> String {
+        let mut values = vec![format!("\"{}\"", isbn13)];
+        if let Some(isbn10) = Isbn::parse(isbn13).ok().and_then(|i| i.to_isbn10()) {
+            values.push(format!("\"{}\"", isbn10));
+        }
+        format!(
+            "{} WHERE {{\n  {{ ?item wdt:P212 ?isbn }} UNION {{ ?item wdt:P957 ?isbn }}\n  \
+             FILTER(REPLACE(STR(?isbn), \"-\", \"\") IN ({}))\n{}}}\nLIMIT 200",
+            SELECT,
+            values.join(", "),
+            PROPERTIES
+        )
+    }
+
+    fn title_query(title: &str) -> String {
+        format!(
+            "{} WHERE {{\n  SERVICE wikibase:mwapi {{\n    \
+             bd:serviceParam wikibase:endpoint \"www.wikidata.org\"; wikibase:api \"EntitySearch\";\n    \
+             mwapi:search \"{}\"; mwapi:language \"en\".\n    \
+             ?item wikibase:apiOutputItem mwapi:item.\n  }}\n  \
+             ?item wdt:P31 ?type .\n  \
+             VALUES ?type {{ wd:Q7725634 wd:Q571 wd:Q47461344 wd:Q3331189 }}\n{}}}\nLIMIT 200",
+            SELECT,
+            escape(title),
+            PROPERTIES
+        )
+    }
+}
+
+impl MetadataProvider for WikidataProvider {
+    fn name(&self) -> &'static str {
+        "wikidata"
+    }
+
+    fn confidence(&self) -> f32 {
+        0.8
+    }
+
+    fn lookup(&self, query: &ProviderQuery) -> RitmoResult<Option<ProviderRecord>> {
+        let (sparql, author) = match query {
+            ProviderQuery::Isbn(isbn) => (Self::isbn_query(isbn), None),
+            ProviderQuery::Title { title, author } => (Self::title_query(title), author.as_deref()),
+        };
+        let url = format!(
+            "{}/sparql?format=json&query={}",
+            self.base_url,
+            encode(&sparql)
+        );
+        let Some(json) = get_json(self.client.as_ref(), &url)? else {
+            return Ok(None);
+        };
+        let bindings = json
+            .get("results")
+            .map(|results| array(results, "bindings"))
+            .unwrap_or_default();
+
+        let mut records = collect_records(self.name(), &bindings);
+        if let ProviderQuery::Isbn(isbn) = query {
+            for record in &mut records {
+                record.set_isbn(isbn);
+            }
+        }
+        // Con un autore noto sceglie il primo elemento che lo riporta
+        let record = match author {
+            Some(author) => records.into_iter().find(|r| {
+                r.authors
+                    .iter()
+                    .any(|a| a.to_lowercase().contains(&author.to_lowercase()))
+            }),
+            None => records.into_iter().next(),
+        };
+        Ok(record.filter(|r| !r.is_empty()))
+    }
+}
+
+/// Raggruppa le righe SPARQL per elemento, nell'ordine in cui compaiono
+fn collect_records(provider: &str, bindings: &[&Value]) -> Vec<ProviderRecord> {
+    let mut records: Vec<ProviderRecord> = Vec::new();
+    for row in bindings {
+        let Some(item) = binding(row, "item") else {
+            continue;
+        };
+        let index = match records.iter().position(|r| r.url.as_deref() == Some(item)) {
+            Some(index) => index,
+            None => {
+                let mut record = ProviderRecord::new(provider);
+                record.url = Some(item.to_string());
+                records.push(record);
+                records.len() - 1
+            }
+        };
+        let record = &mut records[index];
+
+        // Senza etichetta il servizio restituisce l'ID (es. "Q123")
+        let label = |key: &str| binding(row, key).filter(|l| !is_entity_id(l));
+        if record.title.is_none() {
+            record.title = label("itemLabel").map(str::to_string);
+        }
+        if let Some(author) = label("authorLabel") {
+            record.add_author(author);
+        }
+        if record.publisher.is_none() {
+            record.publisher = label("publisherLabel").map(str::to_string);
+        }
+        if record.year.is_none() {
+            record.year = binding(row, "date").and_then(find_year);
+        }
+        if record.pages.is_none() {
+            record.pages = binding(row, "pages").and_then(|p| p.parse().ok());
+        }
+        if let Some(isbn) = binding(row, "isbn13") {
+            record.set_isbn(isbn);
+        }
+        if let Some(code) = binding(row, "langCode") {
+            record.add_language(code);
+        }
+        if record.series.is_none() {
+            record.series = label("seriesLabel").map(str::to_string);
+            record.series_index = binding(row, "seriesOrdinal").and_then(|o| o.parse().ok());
+        }
+        if let Some(genre) = label("genreLabel") {
+            record.add_subject(genre);
+        }
+    }
+    records
+}
+
+fn binding<'a>(row: &'a Value, key: &str) -> Option<&'a str> {
+    row.get(key)
+        .and_then(|b| b.get("value"))
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
+fn is_entity_id(value: &str) -> bool {
+    value.len() > 1 && value.starts_with('Q') && value[1..].chars().all(|c| c.is_ascii_digit())
+}
+
+/// Rende una stringa sicura dentro un letterale SPARQL tra virgolette
+fn escape(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| !c.is_control())
+        .flat_map(|c| match c {
+            '\\' | '"' => vec!['\\', c],
+            _ => vec![c],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::mock::MockServer;
+    use crate::providers::UreqClient;
+
+    const SPARQL_RESPONSE: &str = r#"{
+        "head": {"vars": []},
+        "results": {"bindings": [
+            {
+                "item": {"type": "uri", "value": "http://www.wikidata.org/entity/Q1"},
+                "itemLabel": {"type": "literal", "value": "The Dark Tower"},
+                "authorLabel": {"type": "literal", "value": "Someone Else"}
+            },
+            {
+                "item": {"type": "uri", "value": "http://www.wikidata.org/entity/Q2"},
+                "itemLabel": {"type": "literal", "value": "The Gunslinger"},
+                "authorLabel": {"type": "literal", "value": "Stephen King"},
+                "publisherLabel": {"type": "literal", "value": "Q99999"},
+                "date": {"type": "literal", "value": "1982-06-10T00:00:00Z"},
+                "pages": {"type": "literal", "value": "224"},
+                "langCode": {"type": "literal", "value": "en"},
+                "seriesLabel": {"type": "literal", "value": "The Dark Tower"},
+                "seriesOrdinal": {"type": "literal", "value": "1"},
+                "genreLabel": {"type": "literal", "value": "dark fantasy"}
+            },
+            {
+                "item": {"type": "uri", "value": "http://www.wikidata.org/entity/Q2"},
+                "authorLabel": {"type": "literal", "value": "Stephen King"},
+                "genreLabel": {"type": "literal", "value": "western"}
+            }
+        ]}
+    }"#;
+
+    fn provider(server: &MockServer) -> WikidataProvider {
+        WikidataProvider::new(Arc::new(UreqClient::new())).with_base_url(&server.base_url)
+    }
+
+    #[test]
+    fn test_lookup_by_title_picks_matching_author() {
+        let server = MockServer::start(vec![("/sparql", 200, SPARQL_RESPONSE)]);
+        let query = ProviderQuery::title("The \"Gunslinger\"", Some("stephen king"));
+        let record = provider(&server).lookup(&query).unwrap().unwrap();
+
+        assert_eq!(record.provider, "wikidata");
+        assert_eq!(record.title.as_deref(), Some("The Gunslinger"));
+        assert_eq!(record.authors, vec!["Stephen King"]);
+        // Etichetta mancante: l'ID dell'entità viene scartato
+        assert_eq!(record.publisher, None);
+        assert_eq!(record.year, Some(1982));
+        assert_eq!(record.pages, Some(224));
+        assert_eq!(record.languages, vec!["en"]);
+        assert_eq!(record.series.as_deref(), Some("The Dark Tower"));
+        assert_eq!(record.series_index, Some(1));
+        assert_eq!(record.subjects, vec!["dark fantasy", "western"]);
+
+        let requests = server.requests();
+        assert!(requests[0].starts_with("/sparql?format=json&query=SELECT"));
+        // Le virgolette del titolo sono escapate nel letterale SPARQL
+        assert!(requests[0].contains(&encode(r#""The \"Gunslinger\"""#)));
+    }
+
+    #[test]
+    fn test_lookup_by_isbn() {
+        let server = MockServer::start(vec![("/sparql", 200, SPARQL_RESPONSE)]);
+        let query = ProviderQuery::isbn("978-0-670-81302-5").unwrap();
+        let record = provider(&server).lookup(&query).unwrap().unwrap();
+
+        assert_eq!(record.title.as_deref(), Some("The Dark Tower"));
+        assert_eq!(record.isbn.as_deref(), Some("9780670813025"));
+        let request = &server.requests()[0];
+        assert!(request.contains(&encode(r#"IN ("9780670813025", "0670813028")"#)));
+    }
+
+    #[test]
+    fn test_lookup_empty_result() {
+        let server = MockServer::start(vec![("/sparql", 200, r#"{"results": {"bindings": []}}"#)]);
+        let query = ProviderQuery::title("Nessun libro", None);
+        assert!(provider(&server).lookup(&query).unwrap().is_none());
+    }
+}
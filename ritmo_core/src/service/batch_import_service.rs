@@ -1,6 +1,10 @@
 use crate::dto::{BatchImportInput, ContentInput, ImportObject};
-use crate::service::book_import_service::{import_book_with_contents, BookImportMetadata};
+use crate::service::book_import_service::{
+    attach_file_to_book, import_book_with_contents, BookAttachTarget, BookImportMetadata,
+};
 use ritmo_db::{Content, Person, Role, RunningLanguages, Type};
+use ritmo_db_core::isbn::{check_isbn, IsbnPolicy};
+use ritmo_db_core::vocabulary::{canonical_format_key, lookup_format, lookup_language};
 use ritmo_db_core::LibraryConfig;
 use ritmo_errors::{RitmoErr, RitmoResult};
 use std::path::PathBuf;
@@ -12,6 +16,8 @@ pub struct ImportResult {
     pub success: bool,
     pub book_id: Option<i64>,
     pub error_message: Option<String>,
+    /// Non-blocking validation warnings (e.g. invalid ISBN with `IsbnPolicy::Warn`)
+    pub warnings: Vec<String>,
 }
 
 /// Summary of batch import operation
@@ -35,13 +41,14 @@ impl BatchImportSummary {
         }
     }
 
-    pub fn add_success(&mut self, file_path: String, book_id: i64) {
+    pub fn add_success(&mut self, file_path: String, book_id: i64, warnings: Vec<String>) {
         self.successful += 1;
         self.results.push(ImportResult {
             file_path,
             success: true,
             book_id: Some(book_id),
             error_message: None,
+            warnings,
         });
     }
 
@@ -52,6 +59,7 @@ impl BatchImportSummary {
             success: false,
             book_id: None,
             error_message: Some(error),
+            warnings: Vec::new(),
         });
     }
 
@@ -62,6 +70,7 @@ impl BatchImportSummary {
             success: false,
             book_id: None,
             error_message: Some("Duplicate (already imported)".to_string()),
+            warnings: Vec::new(),
         });
     }
 }
@@ -73,6 +82,8 @@ impl BatchImportSummary {
 /// * `pool` - Database connection pool
 /// * `batch_input` - Deserialized JSON array of ImportObject
 /// * `stop_on_error` - If true, abort on first error; if false, continue on errors
+/// * `strict_validation` - If true, reject EPUB files with structural errors instead of warning
+/// * `isbn_policy` - Whether an invalid ISBN is a warning or an error
 ///
 /// # Returns
 /// * `BatchImportSummary` with results for each import operation
@@ -81,16 +92,25 @@ pub async fn batch_import(
     pool: &sqlx::SqlitePool,
     batch_input: BatchImportInput,
     stop_on_error: bool,
+    strict_validation: bool,
+    isbn_policy: IsbnPolicy,
 ) -> RitmoResult<BatchImportSummary> {
     let mut summary = BatchImportSummary::new();
     summary.total = batch_input.len();
 
     for import_obj in batch_input {
-        let result = import_single(config, pool, import_obj.clone()).await;
+        let result = import_single(
+            config,
+            pool,
+            import_obj.clone(),
+            strict_validation,
+            isbn_policy,
+        )
+        .await;
 
         match result {
-            Ok(book_id) => {
-                summary.add_success(import_obj.file_path.clone(), book_id);
+            Ok((book_id, warnings)) => {
+                summary.add_success(import_obj.file_path.clone(), book_id, warnings);
             }
             Err(e) => {
                 let error_msg = format!("{:?}", e);
@@ -119,10 +139,12 @@ pub async fn batch_import(
 async fn import_single(
     config: &LibraryConfig,
     pool: &sqlx::SqlitePool,
-    import_obj: ImportObject,
-) -> RitmoResult<i64> {
-    // 1. Validate import object
-    validate_import_object(&import_obj)?;
+    mut import_obj: ImportObject,
+    strict_validation: bool,
+    isbn_policy: IsbnPolicy,
+) -> RitmoResult<(i64, Vec<String>)> {
+    // 1. Validate import object (maps language and format variants to the vocabularies)
+    let warnings = validate_import_object(&mut import_obj, isbn_policy)?;
 
     // 2. Resolve file path (support both absolute and relative)
     let file_path = PathBuf::from(&import_obj.file_path);
@@ -132,7 +154,29 @@ async fn import_single(
         std::env::current_dir()?.join(file_path)
     };
 
-    // 3. Build BookImportMetadata from BookInput
+    // 3. Attach to an existing book: no new book, contents belong to the existing one
+    if let Some(attach) = &import_obj.attach_to {
+        let target = match (attach.book_id, &attach.isbn) {
+            (Some(id), _) => BookAttachTarget::Id(id),
+            (None, Some(isbn)) => BookAttachTarget::Isbn(isbn.clone()),
+            (None, None) => {
+                return Err(RitmoErr::Generic(
+                    "attach_to must specify book_id or isbn".to_string(),
+                ))
+            }
+        };
+        let book_id = attach_file_to_book(
+            config,
+            pool,
+            &file_path,
+            &target,
+            import_obj.book.format.clone(),
+        )
+        .await?;
+        return Ok((book_id, warnings));
+    }
+
+    // 4. Build BookImportMetadata from BookInput
     let book_metadata = BookImportMetadata {
         title: import_obj.book.title.clone(),
         original_title: import_obj.book.original_title.clone(),
@@ -161,9 +205,11 @@ async fn import_single(
         } else {
             Some(import_obj.book.tags.clone())
         },
+        attach_to: None,
+        strict_validation,
     };
 
-    // 4. Import book using existing service WITH contents for OPF modification
+    // 5. Import book using existing service WITH contents for OPF modification
     let book_id = import_book_with_contents(
         config,
         pool,
@@ -173,7 +219,7 @@ async fn import_single(
     )
     .await?;
 
-    // 5. Create and associate contents
+    // 6. Create and associate contents
     for content_input in import_obj.contents {
         let content_id = create_content_from_input(pool, &content_input).await?;
 
@@ -188,7 +234,9 @@ async fn import_single(
 
         // Associate content people with roles
         for person_input in &content_input.people {
-            let person_id = Person::get_or_create_by_name(pool, &person_input.name).await?;
+            let source = person_input.source.as_deref().unwrap_or("manual_import");
+            let person_id =
+                Person::get_or_create_with_source(pool, &person_input.name, source).await?;
             let role_id = Role::get_or_create_by_key(pool, &person_input.role).await?;
 
             sqlx::query!(
@@ -203,13 +251,15 @@ async fn import_single(
 
         // Associate content languages
         for lang_input in &content_input.languages {
-            // Use official name as the language code for now (can be enhanced later)
-            // ISO3 is empty string if not provided
+            // Canonical row: ISO 639-1 (or 639-3) code, 639-3 code, English name
+            let iso = lookup_language(&lang_input.code).ok_or_else(|| {
+                RitmoErr::Generic(format!("Unknown language code '{}'", lang_input.code))
+            })?;
             let language_id = RunningLanguages::get_or_create_by_iso_and_role(
                 pool,
-                &lang_input.code.to_uppercase(), // Use code as official name
-                &lang_input.code,                 // ISO 639-1 code (2 char)
-                "",                                // ISO 639-2 code (3 char) - empty for now
+                iso.name_en,
+                iso.code(),
+                iso.iso639_3,
                 &lang_input.role,
             )
             .await?;
@@ -226,7 +276,7 @@ async fn import_single(
         }
     }
 
-    Ok(book_id)
+    Ok((book_id, warnings))
 }
 
 /// Create a content from ContentInput
@@ -271,7 +321,20 @@ async fn create_content_from_input(
 }
 
 /// Validate ImportObject structure
-pub fn validate_import_object(obj: &ImportObject) -> RitmoResult<()> {
+///
+/// Returns the non-blocking warnings: an invalid ISBN is reported here with
+/// `IsbnPolicy::Warn` and rejected with `IsbnPolicy::Error`.
+///
+/// Language codes and the book format are rewritten in place to their
+/// canonical form (`"Italian"`, `"ita"`, `"it-IT"` -> `"it"`; `"EPUB"`,
+/// `"epub3"` -> `"epub"`). An unknown language is an error, an unknown
+/// format a warning.
+pub fn validate_import_object(
+    obj: &mut ImportObject,
+    isbn_policy: IsbnPolicy,
+) -> RitmoResult<Vec<String>> {
+    let mut warnings = Vec::new();
+
     // Validate file_path
     if obj.file_path.trim().is_empty() {
         return Err(RitmoErr::Generic(
@@ -279,11 +342,64 @@ pub fn validate_import_object(obj: &ImportObject) -> RitmoResult<()> {
         ));
     }
 
+    // Map book.format to the controlled format list (also used when attaching)
+    if let Some(format) = obj.book.format.as_mut() {
+        let key = canonical_format_key(format);
+        if lookup_format(&key).is_none() {
+            warnings.push(format!(
+                "book.format: unknown format '{}', stored as '{}'",
+                format, key
+            ));
+        }
+        *format = key;
+    }
+
+    // Validate attach_to: exactly one of book_id / isbn; book metadata is not needed
+    if let Some(attach) = &obj.attach_to {
+        match (attach.book_id, &attach.isbn) {
+            (Some(_), Some(_)) => {
+                return Err(RitmoErr::Generic(
+                    "attach_to must specify either book_id or isbn, not both".to_string(),
+                ));
+            }
+            (None, None) => {
+                return Err(RitmoErr::Generic(
+                    "attach_to must specify book_id or isbn".to_string(),
+                ));
+            }
+            (None, Some(isbn)) if isbn.trim().is_empty() => {
+                return Err(RitmoErr::Generic(
+                    "attach_to.isbn cannot be empty".to_string(),
+                ));
+            }
+            (None, Some(isbn)) => {
+                let checked = check_isbn(isbn, isbn_policy)
+                    .map_err(|e| RitmoErr::Generic(format!("attach_to.isbn: {}", e)))?;
+                warnings.extend(checked.warning.map(|w| format!("attach_to.isbn: {}", w)));
+            }
+            _ => {}
+        }
+        if !obj.contents.is_empty() {
+            return Err(RitmoErr::Generic(
+                "contents cannot be specified when attaching a file to an existing book"
+                    .to_string(),
+            ));
+        }
+        return Ok(warnings);
+    }
+
     // Validate book.title
     if obj.book.title.trim().is_empty() {
         return Err(RitmoErr::Generic("book.title cannot be empty".to_string()));
     }
 
+    // Validate book.isbn if present (checksum, ISBN-10 or ISBN-13)
+    if let Some(isbn) = &obj.book.isbn {
+        let checked = check_isbn(isbn, isbn_policy)
+            .map_err(|e| RitmoErr::Generic(format!("book.isbn: {}", e)))?;
+        warnings.extend(checked.warning.map(|w| format!("book.isbn: {}", w)));
+    }
+
     // Validate book.year if present
     if let Some(year) = obj.book.year {
         if !(1000..=2100).contains(&year) {
@@ -330,7 +446,7 @@ pub fn validate_import_object(obj: &ImportObject) -> RitmoResult<()> {
     }
 
     // Validate contents
-    for (idx, content) in obj.contents.iter().enumerate() {
+    for (idx, content) in obj.contents.iter_mut().enumerate() {
         // Validate content.title
         if content.title.trim().is_empty() {
             return Err(RitmoErr::Generic(format!(
@@ -376,13 +492,14 @@ pub fn validate_import_object(obj: &ImportObject) -> RitmoResult<()> {
         }
 
         // Validate content languages
-        for lang in &content.languages {
-            if lang.code.len() != 2 {
+        for lang in &mut content.languages {
+            let Some(iso) = lookup_language(&lang.code) else {
                 return Err(RitmoErr::Generic(format!(
-                    "contents[{}].languages[].code must be 2-letter ISO 639-1 code, got '{}'",
+                    "contents[{}].languages[].code must be an ISO 639 code or language name, got '{}'",
                     idx, lang.code
                 )));
-            }
+            };
+            lang.code = iso.code().to_string();
             if !lang.role.starts_with("language_role.") {
                 return Err(RitmoErr::Generic(format!(
                     "contents[{}].languages[].role must be i18n key starting with 'language_role.', got '{}'",
@@ -392,5 +509,5 @@ pub fn validate_import_object(obj: &ImportObject) -> RitmoResult<()> {
         }
     }
 
-    Ok(())
+    Ok(warnings)
 }
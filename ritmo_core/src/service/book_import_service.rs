@@ -1,13 +1,21 @@
+use crate::comic_info;
 use crate::dto::ContentInput;
 use crate::epub_opf_modifier;
 use crate::epub_utils::extract_opf;
-use ritmo_db::{Book, Format, Person, Publisher, Role, Series, Tag};
+use crate::epub_validator::{self, ValidationReport};
+use crate::extractors::{detect_format, extract_metadata};
+use crate::pdf_metadata;
+use crate::service::duplicate_books_service::{find_import_duplicates, DEFAULT_CONTENT_SIMILARITY};
+use crate::text_fingerprint::{epub_fingerprint, TextFingerprint};
+use ritmo_db::{mark_book_for_sync, Book, BookFile, Format, Person, Publisher, Role, Series, Tag};
+use ritmo_db_core::isbn::{canonical_isbn13, normalize_display};
+use ritmo_db_core::vocabulary::canonical_format_key;
 use ritmo_db_core::LibraryConfig;
 use ritmo_errors::{RitmoErr, RitmoResult};
 use sha2::{Digest, Sha256};
 use std::fs;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Metadati per l'import di un libro
 #[derive(Debug, Clone)]
@@ -24,6 +32,20 @@ pub struct BookImportMetadata {
     pub pages: Option<i64>,
     pub notes: Option<String>,
     pub tags: Option<Vec<String>>,
+    /// Se impostato, il file viene agganciato a un libro esistente invece di crearne uno nuovo.
+    /// In questo caso gli altri metadati (tranne `format`) vengono ignorati.
+    pub attach_to: Option<BookAttachTarget>,
+    /// Se true, un EPUB con errori strutturali viene rifiutato invece di essere importato con un avviso
+    pub strict_validation: bool,
+}
+
+/// Libro esistente a cui agganciare un nuovo file (es. il PDF di un libro già importato come EPUB)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BookAttachTarget {
+    /// ID del libro
+    Id(i64),
+    /// ISBN del libro (confronto con `books.isbn` o, se valido, con l'ISBN-13 canonico)
+    Isbn(String),
 }
 
 /// Importa un libro da file con metadati forniti
@@ -31,10 +53,15 @@ pub struct BookImportMetadata {
 /// Questa funzione:
 /// 1. Verifica che il file esista
 /// 2. Calcola l'hash per rilevare duplicati
-/// 3. Crea/ottiene le entità correlate (formato, publisher, series, autore)
-/// 4. Salva il libro nel database
-/// 5. Modifica metadati OPF nell'EPUB (se applicabile)
-/// 6. Copia il file nello storage
+/// 3. Valida la struttura degli EPUB (rifiuta il file in modalità `strict_validation`)
+///    e completa i campi non forniti con i metadati letti dal file (EPUB, PDF,
+///    MOBI/AZW3, FB2, CBZ)
+/// 4. Crea/ottiene le entità correlate (formato, publisher, series, autore)
+/// 5. Salva il libro nel database
+/// 6. Modifica metadati OPF nell'EPUB, Info/XMP nel PDF o ComicInfo.xml nel CBZ (se applicabile);
+///    un EPUB che la modifica renderebbe non valido viene copiato senza modifiche
+/// 7. Copia il file nello storage
+/// 8. Per i fumetti (CBZ/CBR) salva la prima pagina come copertina
 ///
 /// # Arguments
 /// * `config` - Library configuration
@@ -49,7 +76,7 @@ pub async fn import_book_with_contents(
     config: &LibraryConfig,
     pool: &sqlx::SqlitePool,
     file_path: &Path,
-    metadata: BookImportMetadata,
+    mut metadata: BookImportMetadata,
     contents: &[ContentInput],
 ) -> RitmoResult<i64> {
     // 1. Verifica che il file esista
@@ -65,27 +92,43 @@ pub async fn import_book_with_contents(
     let file_hash = calculate_hash(&file_content);
 
     // 3. Verifica duplicati (controlla se l'hash esiste già)
-    let existing = sqlx::query!(
-        "SELECT id, name FROM books WHERE file_hash = ? LIMIT 1",
-        file_hash
-    )
-    .fetch_optional(pool)
-    .await?;
+    ensure_not_imported(pool, &file_hash).await?;
 
-    if let Some(dup) = existing {
-        return Err(RitmoErr::Generic(format!(
-            "File già importato: {} (ID: {})",
-            dup.name, dup.id
-        )));
+    // 3a. Validazione strutturale degli EPUB
+    let validation = validate_before_import(file_path, metadata.strict_validation)?;
+    warn_unsupported_comic(file_path);
+
+    // 3b. Aggancio a un libro esistente: nessun nuovo record in books
+    if let Some(target) = &metadata.attach_to {
+        return attach_file_content(
+            config,
+            pool,
+            file_path,
+            &file_content,
+            file_hash,
+            target,
+            metadata.format.clone(),
+        )
+        .await;
     }
 
+    // 3c. Campi mancanti dai metadati del file
+    fill_from_file_metadata(&mut metadata, file_path, contents.is_empty());
+
+    // 3d. Impronta del testo (solo EPUB), per riconoscere duplicati con hash diverso
+    let text_fingerprint = read_text_fingerprint(file_path);
+
     // 4. Determina formato dal metadato o dall'estensione
-    let format_name = metadata.format.clone().or_else(|| {
-        file_path
-            .extension()
-            .and_then(|e| e.to_str())
-            .map(|s| s.to_lowercase())
-    });
+    let format_name = metadata
+        .format
+        .clone()
+        .or_else(|| {
+            file_path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|s| s.to_lowercase())
+        })
+        .map(|f| canonical_format_key(&f));
 
     // 5. Ottieni/crea IDs per entità correlate usando i metodi dei modelli
     let format_id = if let Some(fmt) = format_name {
@@ -123,19 +166,34 @@ pub async fn import_book_with_contents(
         .and_then(|e| e.to_str())
         .unwrap_or("epub");
 
+    // Numero di pagine dal file (PDF, fumetti) se non fornito dall'utente
+    let pages = metadata.pages.or_else(|| read_page_count(file_path, extension));
+
     // Costruisci OPF metadata PRIMA di consumare metadata nel Book struct
     // (necessario perché alcuni campi vengono spostati nel Book)
     let opf_metadata = epub_opf_modifier::build_opf_metadata(&metadata, contents);
+    let mut comic_metadata = comic_info::build_comic_info(&metadata, contents);
+    comic_metadata.page_count = pages;
 
     // Genera path hash-based gerarchico
     // Formato: books/{hash[0:2]}/{hash[2:4]}/{hash[4:]}.{ext}
-    let relative_path = format!(
-        "books/{}/{}/{}.{}",
-        &file_hash[0..2],   // Primo livello directory
-        &file_hash[2..4],   // Secondo livello directory
-        &file_hash[4..],    // Nome file (resto dell'hash)
-        extension
-    );
+    let relative_path = hashed_relative_path(&file_hash, extension);
+
+    // ISBN valido: forma di visualizzazione normalizzata più ISBN-13 canonico
+    let isbn13 = metadata.isbn.as_deref().and_then(canonical_isbn13);
+    let isbn = metadata
+        .isbn
+        .map(|value| if isbn13.is_some() { normalize_display(&value) } else { value });
+
+    // Avviso per possibili duplicati (hash diverso, stessa edizione)
+    warn_possible_duplicates(
+        pool,
+        &metadata.title,
+        metadata.people.as_deref().unwrap_or_default(),
+        isbn13.as_deref(),
+        text_fingerprint.as_deref(),
+    )
+    .await?;
 
     let book = Book {
         id: None,
@@ -147,8 +205,9 @@ pub async fn import_book_with_contents(
         series_index: metadata.series_index,
         publication_date,
         last_modified_date: now,
-        isbn: metadata.isbn,
-        pages: metadata.pages,
+        isbn,
+        isbn13,
+        pages,
         notes: metadata.notes,
         has_cover: 0,
         has_paper: 0,
@@ -158,9 +217,22 @@ pub async fn import_book_with_contents(
         created_at: now,
     };
 
-    // 7. Salva nel database
+    // 7. Salva nel database (il primo file diventa anche il file principale del libro)
     let book_id = book.save(pool).await?;
 
+    BookFile {
+        id: None,
+        book_id,
+        format_id,
+        file_link: relative_path.clone(),
+        file_hash: file_hash.clone(),
+        file_size: Some(file_content.len() as i64),
+        added_at: now,
+        text_fingerprint,
+    }
+    .save(pool)
+    .await?;
+
     // 8. Prepara directory storage
     let storage_path = config.canonical_storage_path().join(&relative_path);
     if let Some(parent) = storage_path.parent() {
@@ -169,32 +241,7 @@ pub async fn import_book_with_contents(
 
     // 9. Estrai e salva OPF originale (solo per EPUB) - BACKUP
     if extension == "epub" {
-        match extract_opf(file_path) {
-            Ok(opf_content) => {
-                // Path OPF: storage/originals_opf/{hash[0:2]}/{hash[2:4]}/{hash[4:]}.opf.xml
-                let opf_relative_path = format!(
-                    "originals_opf/{}/{}/{}.opf.xml",
-                    &file_hash[0..2],
-                    &file_hash[2..4],
-                    &file_hash[4..]
-                );
-
-                let opf_storage_path = config.canonical_storage_path().join(&opf_relative_path);
-
-                // Crea directory se non esistono
-                if let Some(parent) = opf_storage_path.parent() {
-                    fs::create_dir_all(parent)?;
-                }
-
-                // Salva OPF originale
-                let mut opf_file = fs::File::create(&opf_storage_path)?;
-                opf_file.write_all(opf_content.as_bytes())?;
-            }
-            Err(_) => {
-                // Se l'estrazione fallisce, continuiamo comunque l'import
-                // (alcuni EPUB potrebbero avere strutture non standard)
-            }
-        }
+        backup_original_opf(config, file_path, &file_hash)?;
     }
 
     // 10. Modifica EPUB con metadati utente (solo per EPUB)
@@ -204,7 +251,10 @@ pub async fn import_book_with_contents(
         // Crea temp file per EPUB modificato
         let temp_epub = storage_path.with_extension("epub.tmp");
 
-        match epub_opf_modifier::modify_epub_metadata(file_path, &temp_epub, &opf_metadata) {
+        let result = epub_opf_modifier::modify_epub_metadata(file_path, &temp_epub, &opf_metadata)
+            .and_then(|_| ensure_no_new_errors(&temp_epub, validation.as_ref()));
+
+        match result {
             Ok(_) => {
                 // Successo: sposta temp → finale
                 fs::rename(&temp_epub, &storage_path)?;
@@ -221,11 +271,47 @@ pub async fn import_book_with_contents(
                 fs::copy(file_path, &storage_path)?;
             }
         }
+    } else if extension == "pdf" {
+        // PDF: scrivi i metadati utente in Info/XMP
+        let temp_pdf = storage_path.with_extension("pdf.tmp");
+        let pdf_meta = pdf_metadata::build_pdf_metadata(&opf_metadata);
+
+        match pdf_metadata::modify_pdf_metadata(file_path, &temp_pdf, &pdf_meta) {
+            Ok(_) => {
+                fs::rename(&temp_pdf, &storage_path)?;
+            }
+            Err(e) => {
+                eprintln!("Warning: Could not modify PDF metadata: {:?}", e);
+                eprintln!("Copying original PDF without modification");
+                let _ = fs::remove_file(&temp_pdf);
+                fs::copy(file_path, &storage_path)?;
+            }
+        }
+    } else if extension == "cbz" {
+        // CBZ: scrivi i metadati utente in ComicInfo.xml
+        let temp_cbz = storage_path.with_extension("cbz.tmp");
+
+        match comic_info::modify_cbz_metadata(file_path, &temp_cbz, &comic_metadata) {
+            Ok(_) => {
+                fs::rename(&temp_cbz, &storage_path)?;
+            }
+            Err(e) => {
+                eprintln!("Warning: Could not modify CBZ metadata: {:?}", e);
+                eprintln!("Copying original CBZ without modification");
+                let _ = fs::remove_file(&temp_cbz);
+                fs::copy(file_path, &storage_path)?;
+            }
+        }
     } else {
-        // Non-EPUB: copia as-is
+        // Altri formati: copia as-is
         fs::copy(file_path, &storage_path)?;
     }
 
+    // Fumetti: la prima pagina diventa la copertina
+    if is_comic_extension(extension) {
+        save_comic_cover(config, pool, book_id, &storage_path).await;
+    }
+
     // 10. Crea persone e collegamento con i loro ruoli
     if let Some(people) = metadata.people {
         for (person_name, role_name) in people {
@@ -281,8 +367,596 @@ pub async fn import_book(
     import_book_with_contents(config, pool, file_path, metadata, &[]).await
 }
 
+/// Aggancia un file a un libro esistente (per ID o ISBN)
+///
+/// Il file viene copiato nello storage così com'è e registrato in `book_files`.
+/// Un EPUB con errori strutturali viene agganciato comunque, con un avviso.
+/// Per EPUB, PDF e CBZ il libro viene marcato per la sincronizzazione, così che i metadati
+/// del database vengano scritti anche nel nuovo file con `ritmo sync-metadata`.
+/// Un fumetto agganciato a un libro senza copertina ne fornisce la copertina.
+///
+/// # Returns
+/// ID del libro a cui il file è stato agganciato
+pub async fn attach_file_to_book(
+    config: &LibraryConfig,
+    pool: &sqlx::SqlitePool,
+    file_path: &Path,
+    target: &BookAttachTarget,
+    format: Option<String>,
+) -> RitmoResult<i64> {
+    if !file_path.exists() {
+        return Err(RitmoErr::Generic(format!(
+            "File non trovato: {}",
+            file_path.display()
+        )));
+    }
+
+    let file_content = fs::read(file_path)?;
+    let file_hash = calculate_hash(&file_content);
+    ensure_not_imported(pool, &file_hash).await?;
+    validate_before_import(file_path, false)?;
+    warn_unsupported_comic(file_path);
+
+    attach_file_content(
+        config,
+        pool,
+        file_path,
+        &file_content,
+        file_hash,
+        target,
+        format,
+    )
+    .await
+}
+
+async fn attach_file_content(
+    config: &LibraryConfig,
+    pool: &sqlx::SqlitePool,
+    file_path: &Path,
+    file_content: &[u8],
+    file_hash: String,
+    target: &BookAttachTarget,
+    format: Option<String>,
+) -> RitmoResult<i64> {
+    let book = resolve_attach_target(pool, target).await?;
+    let book_id = book
+        .id
+        .ok_or_else(|| RitmoErr::Generic("Libro senza ID".to_string()))?;
+
+    let extension = file_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|s| s.to_lowercase())
+        .unwrap_or_else(|| "bin".to_string());
+
+    let format_name = canonical_format_key(format.as_deref().unwrap_or(&extension));
+    let format_id = Format::get_or_create_by_key(pool, &format_name).await?;
+
+    let relative_path = hashed_relative_path(&file_hash, &extension);
+    let storage_path = config.canonical_storage_path().join(&relative_path);
+    if let Some(parent) = storage_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    if extension == "epub" {
+        backup_original_opf(config, file_path, &file_hash)?;
+    }
+    fs::copy(file_path, &storage_path)?;
+
+    BookFile {
+        id: None,
+        book_id,
+        format_id: Some(format_id),
+        file_link: relative_path.clone(),
+        file_hash: file_hash.clone(),
+        file_size: Some(file_content.len() as i64),
+        added_at: chrono::Utc::now().timestamp(),
+        text_fingerprint: read_text_fingerprint(file_path),
+    }
+    .save(pool)
+    .await?;
+
+    // Libro senza file principale (es. importato prima di book_files): promuovi questo
+    if book.file_link.is_none() {
+        let size = file_content.len() as i64;
+        sqlx::query!(
+            "UPDATE books SET file_link = ?, file_hash = ?, file_size = ?, format_id = ? WHERE id = ?",
+            relative_path,
+            file_hash,
+            size,
+            format_id,
+            book_id
+        )
+        .execute(pool)
+        .await?;
+    }
+
+    // Libro senza numero di pagine: usa quello del file (PDF, fumetti)
+    if book.pages.is_none() {
+        if let Some(pages) = read_page_count(file_path, &extension) {
+            sqlx::query!("UPDATE books SET pages = ? WHERE id = ?", pages, book_id)
+                .execute(pool)
+                .await?;
+        }
+    }
+
+    if is_comic_extension(&extension) && book.has_cover == 0 {
+        save_comic_cover(config, pool, book_id, &storage_path).await;
+    }
+
+    // Formati con metadati scrivibili: allinea il nuovo file al database al prossimo sync
+    if extension == "epub" || extension == "pdf" || extension == "cbz" {
+        mark_book_for_sync(pool, book_id, "file_attached").await?;
+    }
+
+    Ok(book_id)
+}
+
+/// Risolve il libro di destinazione per ID o ISBN
+async fn resolve_attach_target(
+    pool: &sqlx::SqlitePool,
+    target: &BookAttachTarget,
+) -> RitmoResult<Book> {
+    match target {
+        BookAttachTarget::Id(id) => Book::get(pool, *id)
+            .await?
+            .ok_or_else(|| RitmoErr::Generic(format!("Libro con ID {} non trovato", id))),
+        BookAttachTarget::Isbn(isbn) => Book::get_by_isbn(pool, isbn, canonical_isbn13(isbn).as_deref())
+            .await?
+            .ok_or_else(|| RitmoErr::Generic(format!("Nessun libro con ISBN {}", isbn))),
+    }
+}
+
+/// Completa i metadati forniti con quelli letti dal file
+///
+/// Solo i campi non indicati dall'utente vengono riempiti. Senza `contents`
+/// le persone dell'opera (autori, traduttori) vanno al libro, come per
+/// l'import manuale. Un file illeggibile produce solo un avviso.
+fn fill_from_file_metadata(
+    metadata: &mut BookImportMetadata,
+    file_path: &Path,
+    include_content_people: bool,
+) {
+    // CBR in formato RAR: già segnalato da warn_unsupported_comic
+    if detect_format(file_path).is_none() || comic_info::is_rar_archive(file_path) {
+        return;
+    }
+    let extracted = match extract_metadata(file_path) {
+        Ok(extracted) => extracted,
+        Err(e) => {
+            eprintln!("Warning: Could not read file metadata: {:?}", e);
+            return;
+        }
+    };
+
+    let book = extracted.book;
+    let mut people: Vec<(String, String)> = book
+        .people
+        .into_iter()
+        .map(|p| (p.name, p.role))
+        .collect();
+    if include_content_people {
+        if let Some(content) = extracted.contents.into_iter().next() {
+            people.extend(content.people.into_iter().map(|p| (p.name, p.role)));
+        }
+    }
+
+    fn fill<T>(field: &mut Option<T>, value: Option<T>) {
+        if field.is_none() {
+            *field = value;
+        }
+    }
+    fill(&mut metadata.original_title, book.original_title);
+    fill(&mut metadata.people, Some(people).filter(|p| !p.is_empty()));
+    fill(&mut metadata.publisher, book.publisher);
+    fill(&mut metadata.year, book.year);
+    fill(&mut metadata.isbn, book.isbn);
+    // Il numero nella serie vale solo insieme alla serie del file
+    if metadata.series.is_none() {
+        metadata.series = book.series;
+        fill(&mut metadata.series_index, book.series_index);
+    }
+    fill(&mut metadata.pages, book.pages);
+    fill(&mut metadata.notes, book.notes);
+    fill(&mut metadata.tags, Some(book.tags).filter(|t| !t.is_empty()));
+}
+
+/// Valida un EPUB prima dell'import (gli altri formati non vengono controllati)
+///
+/// Gli errori strutturali bloccano l'import solo se `strict` è true, altrimenti
+/// vengono stampati come avvisi. Il report serve poi a verificare che la
+/// modifica dei metadati non introduca errori nuovi.
+fn validate_before_import(file_path: &Path, strict: bool) -> RitmoResult<Option<ValidationReport>> {
+    let is_epub = file_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("epub"));
+    if !is_epub {
+        return Ok(None);
+    }
+
+    let report = epub_validator::validate_epub(file_path)?;
+    if !report.is_valid() {
+        if strict {
+            return Err(RitmoErr::Generic(format!(
+                "EPUB non valido: {} ({})",
+                file_path.display(),
+                report.error_summary()
+            )));
+        }
+        eprintln!("Warning: EPUB non valido: {}", file_path.display());
+        for issue in report.errors() {
+            eprintln!("  {}", issue);
+        }
+    }
+    Ok(Some(report))
+}
+
+/// Avvisa che un CBR in formato RAR viene importato senza metadati, numero di
+/// pagine e copertina (ritmo legge solo fumetti in formato ZIP)
+fn warn_unsupported_comic(file_path: &Path) {
+    if comic_info::is_rar_archive(file_path) {
+        eprintln!(
+            "Warning: CBR in formato RAR non supportato, importato senza metadati, pagine e copertina: {}",
+            file_path.display()
+        );
+    }
+}
+
+/// Impronta del testo di un EPUB nella forma salvata in `book_files`
+///
+/// `None` per gli altri formati o se il testo non è leggibile; stringa vuota se
+/// il testo è troppo breve.
+fn read_text_fingerprint(file_path: &Path) -> Option<String> {
+    let is_epub = file_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("epub"));
+    if !is_epub {
+        return None;
+    }
+    epub_fingerprint(file_path)
+        .ok()
+        .map(|fingerprint| fingerprint.map(|f| f.to_hex()).unwrap_or_default())
+}
+
+/// Stampa un avviso per ogni libro già presente che sembra la stessa edizione
+///
+/// L'import prosegue comunque: i duplicati si gestiscono con `ritmo find-duplicate-books`.
+async fn warn_possible_duplicates(
+    pool: &sqlx::SqlitePool,
+    title: &str,
+    people: &[(String, String)],
+    isbn13: Option<&str>,
+    text_fingerprint: Option<&str>,
+) -> RitmoResult<()> {
+    let fingerprint = text_fingerprint.and_then(TextFingerprint::from_hex);
+    let duplicates = find_import_duplicates(
+        pool,
+        title,
+        people,
+        isbn13,
+        fingerprint.as_ref(),
+        DEFAULT_CONTENT_SIMILARITY,
+    )
+    .await?;
+    for duplicate in duplicates {
+        eprintln!(
+            "Warning: possibile duplicato del libro {} '{}': {}",
+            duplicate.book_id, duplicate.title, duplicate.reason
+        );
+    }
+    Ok(())
+}
+
+/// Verifica che un EPUB riscritto non abbia errori assenti nell'originale
+pub(crate) fn ensure_no_new_errors(
+    epub_path: &Path,
+    before: Option<&ValidationReport>,
+) -> RitmoResult<()> {
+    let after = epub_validator::validate_epub(epub_path)?;
+    let empty = ValidationReport::default();
+    let new_errors = after.new_errors_since(before.unwrap_or(&empty));
+    if new_errors.is_empty() {
+        return Ok(());
+    }
+
+    let messages: Vec<&str> = new_errors.iter().map(|i| i.message.as_str()).collect();
+    Err(RitmoErr::Generic(format!(
+        "l'EPUB modificato non è valido: {}",
+        messages.join("; ")
+    )))
+}
+
+/// Verifica che un file con lo stesso hash non sia già nella libreria
+async fn ensure_not_imported(pool: &sqlx::SqlitePool, file_hash: &str) -> RitmoResult<()> {
+    let existing = sqlx::query!(
+        r#"SELECT b.id as "id!", b.name as "name!"
+           FROM books b
+           WHERE b.file_hash = ?1
+              OR b.id IN (SELECT book_id FROM book_files WHERE file_hash = ?1)
+           LIMIT 1"#,
+        file_hash
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if let Some(dup) = existing {
+        return Err(RitmoErr::Generic(format!(
+            "File già importato: {} (ID: {})",
+            dup.name, dup.id
+        )));
+    }
+    Ok(())
+}
+
+/// Path relativo hash-based: books/{hash[0:2]}/{hash[2:4]}/{hash[4:]}.{ext}
+pub(crate) fn hashed_relative_path(file_hash: &str, extension: &str) -> String {
+    format!(
+        "books/{}/{}/{}.{}",
+        &file_hash[0..2], // Primo livello directory
+        &file_hash[2..4], // Secondo livello directory
+        &file_hash[4..],  // Nome file (resto dell'hash)
+        extension
+    )
+}
+
+/// Path relativo della copertina di un libro: covers/{book_id}.{ext}
+pub(crate) fn cover_relative_path(book_id: i64, extension: &str) -> String {
+    format!("covers/{}.{}", book_id, extension)
+}
+
+/// Copertine salvate per un libro (una per estensione, normalmente una sola)
+pub(crate) fn find_covers(config: &LibraryConfig, book_id: i64) -> Vec<PathBuf> {
+    let covers_dir = config.canonical_storage_path().join("covers");
+    let prefix = format!("{}.", book_id);
+    fs::read_dir(covers_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| {
+                    p.file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|n| n.starts_with(&prefix))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn is_comic_extension(extension: &str) -> bool {
+    extension.eq_ignore_ascii_case("cbz") || extension.eq_ignore_ascii_case("cbr")
+}
+
+/// Numero di pagine letto dal file (PDF e fumetti)
+fn read_page_count(file_path: &Path, extension: &str) -> Option<i64> {
+    if extension == "pdf" {
+        pdf_metadata::read_pdf_metadata(file_path)
+            .ok()
+            .and_then(|m| m.page_count)
+    } else if is_comic_extension(extension) {
+        comic_info::read_comic_archive(file_path)
+            .ok()
+            .map(|a| a.page_count)
+            .filter(|p| *p > 0)
+    } else {
+        None
+    }
+}
+
+/// Salva la prima pagina di un fumetto come copertina e imposta `has_cover`
+///
+/// Gli errori non interrompono l'import: il libro resta senza copertina. I CBR in
+/// formato RAR sono saltati (già segnalati da `warn_unsupported_comic`).
+async fn save_comic_cover(
+    config: &LibraryConfig,
+    pool: &sqlx::SqlitePool,
+    book_id: i64,
+    archive_path: &Path,
+) {
+    if comic_info::is_rar_archive(archive_path) {
+        return;
+    }
+    let result: RitmoResult<()> = async {
+        let Some((image, extension)) = comic_info::extract_comic_cover(archive_path)? else {
+            return Ok(());
+        };
+        let cover_path = config
+            .canonical_storage_path()
+            .join(cover_relative_path(book_id, &extension));
+        if let Some(parent) = cover_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&cover_path, image)?;
+        sqlx::query!("UPDATE books SET has_cover = 1 WHERE id = ?", book_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+    .await;
+
+    if let Err(e) = result {
+        eprintln!("Warning: Could not extract comic cover: {:?}", e);
+    }
+}
+
+/// Estrae e salva l'OPF originale di un EPUB come backup
+///
+/// Path OPF: storage/originals_opf/{hash[0:2]}/{hash[2:4]}/{hash[4:]}.opf.xml
+fn backup_original_opf(config: &LibraryConfig, file_path: &Path, file_hash: &str) -> RitmoResult<()> {
+    match extract_opf(file_path) {
+        Ok(opf_content) => {
+            let opf_relative_path = format!(
+                "originals_opf/{}/{}/{}.opf.xml",
+                &file_hash[0..2],
+                &file_hash[2..4],
+                &file_hash[4..]
+            );
+
+            let opf_storage_path = config.canonical_storage_path().join(&opf_relative_path);
+
+            // Crea directory se non esistono
+            if let Some(parent) = opf_storage_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            // Salva OPF originale
+            let mut opf_file = fs::File::create(&opf_storage_path)?;
+            opf_file.write_all(opf_content.as_bytes())?;
+        }
+        Err(_) => {
+            // Se l'estrazione fallisce, continuiamo comunque l'import
+            // (alcuni EPUB potrebbero avere strutture non standard)
+        }
+    }
+    Ok(())
+}
+
 fn calculate_hash(data: &[u8]) -> String {
     let mut hasher = Sha256::new();
     hasher.update(data);
     format!("{:x}", hasher.finalize())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::create_test_db;
+
+    fn metadata(title: &str) -> BookImportMetadata {
+        BookImportMetadata {
+            title: title.to_string(),
+            original_title: None,
+            people: None,
+            publisher: None,
+            year: None,
+            isbn: None,
+            format: None,
+            series: None,
+            series_index: None,
+            pages: None,
+            notes: None,
+            tags: None,
+            attach_to: None,
+            strict_validation: false,
+        }
+    }
+
+    const FB2: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<FictionBook xmlns="http://www.gribuser.ru/xml/fictionbook/2.0">
+  <description>
+    <title-info>
+      <author><first-name>Boris</first-name><last-name>Strugatsky</last-name></author>
+      <book-title>Roadside Picnic</book-title>
+      <lang>en</lang>
+      <sequence name="Noon Universe" number="7"/>
+    </title-info>
+    <publish-info>
+      <publisher>Chicago Review Press</publisher>
+      <year>2012</year>
+      <isbn>978-1-61374-341-6</isbn>
+    </publish-info>
+  </description>
+  <body><section><p>Text</p></section></body>
+</FictionBook>"#;
+
+    #[tokio::test]
+    async fn test_import_fills_missing_fields_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = LibraryConfig::new(dir.path());
+        let pool = create_test_db().await;
+        let file = dir.path().join("picnic.fb2");
+        fs::write(&file, FB2).unwrap();
+
+        // L'editore indicato dall'utente prevale su quello del file
+        let mut user = metadata("Picnic");
+        user.publisher = Some("Mondadori".to_string());
+        let book_id = import_book(&config, &pool, &file, user).await.unwrap();
+
+        let book = Book::get(&pool, book_id).await.unwrap().unwrap();
+        assert_eq!(book.name, "Picnic");
+        assert_eq!(book.isbn13.as_deref(), Some("9781613743416"));
+        assert_eq!(book.series_index, Some(7));
+        let (publisher, series): (String, String) = sqlx::query_as(
+            "SELECT publishers.name, series.name FROM books
+             JOIN publishers ON publishers.id = books.publisher_id
+             JOIN series ON series.id = books.series_id WHERE books.id = ?",
+        )
+        .bind(book_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(publisher, "Mondadori");
+        assert_eq!(series, "Noon Universe");
+
+        let authors: Vec<String> = sqlx::query_scalar(
+            "SELECT people.name FROM x_books_people_roles
+             JOIN people ON people.id = x_books_people_roles.person_id
+             WHERE book_id = ?",
+        )
+        .bind(book_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(authors, vec!["Boris Strugatsky"]);
+    }
+
+    #[tokio::test]
+    async fn test_import_rar_cbr_without_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = LibraryConfig::new(dir.path());
+        let pool = create_test_db().await;
+        let file = dir.path().join("albo.cbr");
+        fs::write(&file, b"Rar!\x1a\x07\x00rest").unwrap();
+
+        let book_id = import_book(&config, &pool, &file, metadata("Albo"))
+            .await
+            .unwrap();
+
+        let book = Book::get(&pool, book_id).await.unwrap().unwrap();
+        assert_eq!(book.pages, None);
+        assert_eq!(book.has_cover, 0);
+        let stored = config.canonical_storage_path().join(book.file_link.unwrap());
+        assert!(stored.exists());
+    }
+
+    #[tokio::test]
+    async fn test_attach_second_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = LibraryConfig::new(dir.path());
+        let pool = create_test_db().await;
+        let fb2 = dir.path().join("picnic.fb2");
+        fs::write(&fb2, FB2).unwrap();
+        let txt = dir.path().join("picnic.txt");
+        fs::write(&txt, "Roadside Picnic").unwrap();
+
+        let book_id = import_book(&config, &pool, &fb2, metadata("Picnic"))
+            .await
+            .unwrap();
+        let primary = Book::get(&pool, book_id).await.unwrap().unwrap().file_link;
+
+        // Aggancio per ISBN, in una forma diversa da quella salvata
+        let target = BookAttachTarget::Isbn("9781613743416".to_string());
+        let attached = attach_file_to_book(&config, &pool, &txt, &target, None)
+            .await
+            .unwrap();
+        assert_eq!(attached, book_id);
+
+        let files = BookFile::list_for_book(&pool, book_id).await.unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(config
+            .canonical_storage_path()
+            .join(&files[1].file_link)
+            .exists());
+        // Il file principale non cambia
+        let book = Book::get(&pool, book_id).await.unwrap().unwrap();
+        assert_eq!(book.file_link, primary);
+
+        // Lo stesso file non si aggancia due volte
+        let target = BookAttachTarget::Id(book_id);
+        assert!(attach_file_to_book(&config, &pool, &txt, &target, None)
+            .await
+            .is_err());
+    }
+}
@@ -1,4 +1,6 @@
 use ritmo_db::{Book, Format, Person, Publisher, Role, Series, Tag};
+use ritmo_db_core::isbn::{check_isbn, IsbnPolicy};
+use ritmo_db_core::vocabulary::canonical_format_key;
 use ritmo_errors::{RitmoErr, RitmoResult};
 
 /// Metadati opzionali per l'aggiornamento di un libro
@@ -26,11 +28,18 @@ pub struct BookUpdateMetadata {
 /// 2. Applica solo le modifiche specificate (campi Some)
 /// 3. Aggiorna le relazioni (formato, publisher, series, autore) se modificate
 /// 4. Salva le modifiche nel database
+///
+/// L'ISBN viene validato secondo `isbn_policy`: con `IsbnPolicy::Error` un ISBN
+/// non valido blocca l'aggiornamento, con `IsbnPolicy::Warn` viene salvato e
+/// l'avviso è restituito al chiamante. Un ISBN vuoto cancella quello esistente.
 pub async fn update_book(
     pool: &sqlx::SqlitePool,
     book_id: i64,
     metadata: BookUpdateMetadata,
-) -> RitmoResult<()> {
+    isbn_policy: IsbnPolicy,
+) -> RitmoResult<Vec<String>> {
+    let mut warnings = Vec::new();
+
     // 1. Verifica che il libro esista e caricalo
     let mut book = Book::get(pool, book_id)
         .await?
@@ -46,7 +55,15 @@ pub async fn update_book(
     }
 
     if let Some(isbn) = metadata.isbn {
-        book.isbn = Some(isbn);
+        if isbn.trim().is_empty() {
+            book.isbn = None;
+            book.isbn13 = None;
+        } else {
+            let checked = check_isbn(&isbn, isbn_policy).map_err(RitmoErr::Generic)?;
+            book.isbn = Some(checked.display);
+            book.isbn13 = checked.isbn13;
+            warnings.extend(checked.warning);
+        }
     }
 
     if let Some(notes) = metadata.notes {
@@ -70,7 +87,8 @@ pub async fn update_book(
 
     // 3. Aggiorna relazioni foreign key
     if let Some(format_name) = metadata.format {
-        book.format_id = Some(Format::get_or_create_by_key(pool, &format_name).await?);
+        let format_key = canonical_format_key(&format_name);
+        book.format_id = Some(Format::get_or_create_by_key(pool, &format_key).await?);
     }
 
     if let Some(publisher_name) = metadata.publisher {
@@ -138,5 +156,5 @@ pub async fn update_book(
         }
     }
 
-    Ok(())
+    Ok(warnings)
 }
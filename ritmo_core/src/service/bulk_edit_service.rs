@@ -0,0 +1,688 @@
+use crate::service::metadata_sync_service::get_book_files;
+use ritmo_db::{mark_books_for_sync, Book, Person, Publisher, Role, Series, Tag};
+use ritmo_db_core::filters::PersonRoleFilter;
+use ritmo_errors::{RitmoErr, RitmoResult};
+use std::collections::HashMap;
+
+/// Modifiche da applicare a tutti i libri selezionati
+///
+/// I campi `None`/vuoti non vengono toccati. Le persone sono coppie (nome, ruolo),
+/// con il ruolo nella forma breve (`translator`) o completa (`role.translator`).
+/// Il formato non è modificabile in blocco: appartiene ai singoli file
+/// (`book_files`), non al libro.
+#[derive(Debug, Clone, Default)]
+pub struct BulkEdit {
+    pub publisher: Option<String>,
+    pub series: Option<String>,
+    pub year: Option<i32>,
+    pub pages: Option<i64>,
+    pub notes: Option<String>,
+    pub add_tags: Vec<String>,
+    pub remove_tags: Vec<String>,
+    pub add_people: Vec<(String, String)>,
+    pub remove_people: Vec<(String, String)>,
+    /// Rinumera `series_index` a partire da questo valore, nell'ordine della selezione
+    pub renumber_series_from: Option<i64>,
+}
+
+impl BulkEdit {
+    pub fn is_empty(&self) -> bool {
+        self.publisher.is_none()
+            && self.series.is_none()
+            && self.year.is_none()
+            && self.pages.is_none()
+            && self.notes.is_none()
+            && self.add_tags.is_empty()
+            && self.remove_tags.is_empty()
+            && self.add_people.is_empty()
+            && self.remove_people.is_empty()
+            && self.renumber_series_from.is_none()
+    }
+}
+
+/// Un campo che cambia: valore attuale e nuovo valore (vuoti se assenti)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldChange {
+    Set {
+        field: &'static str,
+        before: Option<String>,
+        after: String,
+    },
+    Added {
+        field: &'static str,
+        value: String,
+    },
+    Removed {
+        field: &'static str,
+        value: String,
+    },
+}
+
+impl std::fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Set {
+                field,
+                before,
+                after,
+            } => write!(
+                f,
+                "{}: {} → {}",
+                field,
+                before.as_deref().unwrap_or("(vuoto)"),
+                after
+            ),
+            Self::Added { field, value } => write!(f, "{}: + {}", field, value),
+            Self::Removed { field, value } => write!(f, "{}: - {}", field, value),
+        }
+    }
+}
+
+/// Modifiche previste per un libro
+#[derive(Debug, Clone)]
+pub struct BookChangePlan {
+    pub book_id: i64,
+    pub title: String,
+    pub changes: Vec<FieldChange>,
+    series_index: Option<i64>,
+}
+
+/// Esito di una modifica massiva
+#[derive(Debug, Clone, Default)]
+pub struct BulkEditResult {
+    pub updated_book_ids: Vec<i64>,
+    /// Libri con file EPUB/PDF/CBZ segnati per la sincronizzazione dei metadati
+    pub marked_for_sync: Vec<i64>,
+}
+
+/// Stato attuale di un libro, limitato ai campi modificabili in blocco
+struct BookSnapshot {
+    title: String,
+    publisher: Option<String>,
+    series: Option<String>,
+    series_index: Option<i64>,
+    year: Option<i32>,
+    pages: Option<i64>,
+    notes: Option<String>,
+    tags: Vec<String>,
+    people: Vec<(String, String)>,
+}
+
+async fn load_snapshot(pool: &sqlx::SqlitePool, book_id: i64) -> RitmoResult<BookSnapshot> {
+    let row = sqlx::query!(
+        r#"SELECT books.name, publishers.name AS "publisher?", series.name AS "series?",
+                  books.series_index, books.publication_date, books.pages, books.notes
+           FROM books
+           LEFT JOIN publishers ON books.publisher_id = publishers.id
+           LEFT JOIN series ON books.series_id = series.id
+           WHERE books.id = ?"#,
+        book_id
+    )
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| RitmoErr::Generic(format!("Libro con ID {} non trovato", book_id)))?;
+
+    let tags = sqlx::query_scalar!(
+        "SELECT tags.name FROM x_books_tags JOIN tags ON x_books_tags.tag_id = tags.id
+         WHERE x_books_tags.book_id = ? ORDER BY tags.name",
+        book_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let people = sqlx::query!(
+        "SELECT people.name, roles.key FROM x_books_people_roles
+         JOIN people ON x_books_people_roles.person_id = people.id
+         JOIN roles ON x_books_people_roles.role_id = roles.id
+         WHERE x_books_people_roles.book_id = ?",
+        book_id
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|r| (r.name, r.key))
+    .collect();
+
+    let year = row.publication_date.and_then(|ts| {
+        chrono::DateTime::<chrono::Utc>::from_timestamp(ts, 0)
+            .map(|dt| chrono::Datelike::year(&dt.date_naive()))
+    });
+
+    Ok(BookSnapshot {
+        title: row.name,
+        publisher: row.publisher,
+        series: row.series,
+        series_index: row.series_index,
+        year,
+        pages: row.pages,
+        notes: row.notes,
+        tags,
+        people,
+    })
+}
+
+/// Chiave completa del ruolo (`translator` → `role.translator`)
+fn role_key(role: &str) -> String {
+    PersonRoleFilter::new("", role).role_keys().1
+}
+
+/// Persona come compare nelle modifiche: `nome (role.chiave)`
+fn person_label(name: &str, role: &str) -> String {
+    format!("{} ({})", name, role_key(role))
+}
+
+/// Coppia (nome, ruolo) della modifica corrispondente a un'etichetta del piano
+fn find_person<'a>(people: &'a [(String, String)], label: &str) -> Option<&'a (String, String)> {
+    people
+        .iter()
+        .find(|(name, role)| person_label(name, role) == label)
+}
+
+fn same_role(existing: &str, wanted: &str) -> bool {
+    let (short, full) = PersonRoleFilter::new("", wanted).role_keys();
+    existing.eq_ignore_ascii_case(&short) || existing.eq_ignore_ascii_case(&full)
+}
+
+fn set_change<T: ToString + PartialEq>(
+    changes: &mut Vec<FieldChange>,
+    field: &'static str,
+    before: Option<T>,
+    after: &Option<T>,
+) {
+    if let Some(after) = after {
+        if before.as_ref() != Some(after) {
+            changes.push(FieldChange::Set {
+                field,
+                before: before.map(|b| b.to_string()),
+                after: after.to_string(),
+            });
+        }
+    }
+}
+
+fn plan_book(
+    book_id: i64,
+    current: BookSnapshot,
+    edit: &BulkEdit,
+    series_index: Option<i64>,
+) -> BookChangePlan {
+    let mut changes = Vec::new();
+
+    set_change(&mut changes, "editore", current.publisher, &edit.publisher);
+    set_change(&mut changes, "serie", current.series, &edit.series);
+    set_change(
+        &mut changes,
+        "numero serie",
+        current.series_index,
+        &series_index,
+    );
+    set_change(&mut changes, "anno", current.year, &edit.year);
+    set_change(&mut changes, "pagine", current.pages, &edit.pages);
+    set_change(&mut changes, "note", current.notes, &edit.notes);
+
+    for tag in &edit.remove_tags {
+        if let Some(existing) = current.tags.iter().find(|t| t.eq_ignore_ascii_case(tag)) {
+            changes.push(FieldChange::Removed {
+                field: "tag",
+                value: existing.clone(),
+            });
+        }
+    }
+    for tag in &edit.add_tags {
+        if !current.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            changes.push(FieldChange::Added {
+                field: "tag",
+                value: tag.clone(),
+            });
+        }
+    }
+
+    let has_person = |name: &str, role: &str| {
+        current
+            .people
+            .iter()
+            .any(|(n, r)| n.eq_ignore_ascii_case(name) && same_role(r, role))
+    };
+    for (name, role) in &edit.remove_people {
+        if has_person(name, role) {
+            changes.push(FieldChange::Removed {
+                field: "persona",
+                value: person_label(name, role),
+            });
+        }
+    }
+    for (name, role) in &edit.add_people {
+        if !has_person(name, role) {
+            changes.push(FieldChange::Added {
+                field: "persona",
+                value: person_label(name, role),
+            });
+        }
+    }
+
+    BookChangePlan {
+        book_id,
+        title: current.title,
+        changes,
+        series_index,
+    }
+}
+
+/// Calcola le modifiche previste senza toccare il database
+///
+/// I libri sono considerati nell'ordine dato (rilevante per la rinumerazione della
+/// serie); nel risultato compaiono solo quelli che cambierebbero.
+pub async fn plan_bulk_edit(
+    pool: &sqlx::SqlitePool,
+    book_ids: &[i64],
+    edit: &BulkEdit,
+) -> RitmoResult<Vec<BookChangePlan>> {
+    if edit.is_empty() {
+        return Err(RitmoErr::Generic("Nessuna modifica indicata".to_string()));
+    }
+
+    let mut plans = Vec::new();
+    for (position, &book_id) in book_ids.iter().enumerate() {
+        let current = load_snapshot(pool, book_id).await?;
+        let series_index = edit
+            .renumber_series_from
+            .map(|start| start + position as i64);
+        let plan = plan_book(book_id, current, edit, series_index);
+        if !plan.changes.is_empty() {
+            plans.push(plan);
+        }
+    }
+    Ok(plans)
+}
+
+/// Applica le modifiche pianificate in un'unica transazione
+///
+/// Di ogni libro vengono scritti solo i campi in `plan.changes`. Editori, serie,
+/// tag, persone e ruoli nuovi sono creati prima della transazione; se una
+/// modifica fallisce i libri restano invariati (le voci rimaste senza libri si
+/// tolgono con `cleanup`). Dopo il commit i libri con file sincronizzabili
+/// vengono segnati per `sync-metadata`.
+pub async fn apply_bulk_edit(
+    pool: &sqlx::SqlitePool,
+    plans: &[BookChangePlan],
+    edit: &BulkEdit,
+) -> RitmoResult<BulkEditResult> {
+    // Libro eliminato dopo la pianificazione: non creare nulla
+    for plan in plans {
+        if Book::get(pool, plan.book_id).await?.is_none() {
+            return Err(RitmoErr::Generic(format!(
+                "Libro con ID {} non trovato",
+                plan.book_id
+            )));
+        }
+    }
+
+    let changed = |field: &str| {
+        plans
+            .iter()
+            .flat_map(|plan| &plan.changes)
+            .any(|change| matches!(change, FieldChange::Set { field: f, .. } if *f == field))
+    };
+    let publisher_id = match &edit.publisher {
+        Some(name) if changed("editore") => {
+            Some(Publisher::get_or_create_by_name(pool, name).await?)
+        }
+        _ => None,
+    };
+    let series_id = match &edit.series {
+        Some(name) if changed("serie") => Some(Series::get_or_create_by_name(pool, name).await?),
+        _ => None,
+    };
+    let publication_date = edit.year.map(|year| {
+        chrono::NaiveDate::from_ymd_opt(year, 1, 1)
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map(|dt| dt.and_utc().timestamp())
+            .unwrap_or_default()
+    });
+
+    let mut tag_ids = HashMap::new();
+    let mut people_ids = HashMap::new();
+    for change in plans.iter().flat_map(|plan| &plan.changes) {
+        match change {
+            FieldChange::Added {
+                field: "tag",
+                value,
+            } if !tag_ids.contains_key(value) => {
+                let id = Tag::get_or_create_by_name(pool, value).await?;
+                tag_ids.insert(value.clone(), id);
+            }
+            FieldChange::Added {
+                field: "persona",
+                value,
+            } if !people_ids.contains_key(value) => {
+                if let Some((name, role)) = find_person(&edit.add_people, value) {
+                    let person = Person::get_or_create_by_name(pool, name).await?;
+                    let role = Role::get_or_create_by_key(pool, &role_key(role)).await?;
+                    people_ids.insert(value.clone(), (person, role));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let mut result = BulkEditResult::default();
+    let mut tx = pool.begin().await?;
+
+    for plan in plans {
+        let book_id = plan.book_id;
+        // Solo i campi che cambiano: gli altri restano NULL e quindi invariati
+        let set = |field: &str| {
+            plan.changes
+                .iter()
+                .any(|change| matches!(change, FieldChange::Set { field: f, .. } if *f == field))
+        };
+        let publisher_id = publisher_id.filter(|_| set("editore"));
+        let series_id = series_id.filter(|_| set("serie"));
+        let series_index = plan.series_index.filter(|_| set("numero serie"));
+        let publication_date = publication_date.filter(|_| set("anno"));
+        let pages = edit.pages.filter(|_| set("pagine"));
+        let notes = edit.notes.as_ref().filter(|_| set("note"));
+
+        let updated = sqlx::query!(
+            "UPDATE books SET
+                publisher_id = COALESCE(?, publisher_id),
+                series_id = COALESCE(?, series_id),
+                series_index = COALESCE(?, series_index),
+                publication_date = COALESCE(?, publication_date),
+                pages = COALESCE(?, pages),
+                notes = COALESCE(?, notes),
+                last_modified_date = ?
+             WHERE id = ?",
+            publisher_id,
+            series_id,
+            series_index,
+            publication_date,
+            pages,
+            notes,
+            now,
+            book_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        // Libro eliminato durante la modifica: annulla tutto
+        if updated.rows_affected() == 0 {
+            return Err(RitmoErr::Generic(format!(
+                "Libro con ID {} non trovato",
+                book_id
+            )));
+        }
+
+        for change in &plan.changes {
+            match change {
+                FieldChange::Removed {
+                    field: "tag",
+                    value,
+                } => {
+                    sqlx::query!(
+                        "DELETE FROM x_books_tags WHERE book_id = ?
+                         AND tag_id IN (SELECT id FROM tags WHERE name = ? COLLATE NOCASE)",
+                        book_id,
+                        value
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                FieldChange::Added {
+                    field: "tag",
+                    value,
+                } => {
+                    let tag_id = tag_ids[value];
+                    sqlx::query!(
+                        "INSERT OR IGNORE INTO x_books_tags (book_id, tag_id) VALUES (?, ?)",
+                        book_id,
+                        tag_id
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                FieldChange::Removed {
+                    field: "persona",
+                    value,
+                } => {
+                    let Some((name, role)) = find_person(&edit.remove_people, value) else {
+                        continue;
+                    };
+                    let (short, full) = PersonRoleFilter::new("", role).role_keys();
+                    sqlx::query!(
+                        "DELETE FROM x_books_people_roles WHERE book_id = ?
+                         AND person_id IN (SELECT id FROM people WHERE name = ? COLLATE NOCASE)
+                         AND role_id IN (SELECT id FROM roles WHERE key IN (?, ?))",
+                        book_id,
+                        name,
+                        short,
+                        full
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                FieldChange::Added {
+                    field: "persona",
+                    value,
+                } => {
+                    let Some((person_id, role_id)) = people_ids.get(value) else {
+                        continue;
+                    };
+                    sqlx::query!(
+                        "INSERT OR IGNORE INTO x_books_people_roles (book_id, person_id, role_id)
+                         VALUES (?, ?, ?)",
+                        book_id,
+                        person_id,
+                        role_id
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                _ => {}
+            }
+        }
+
+        result.updated_book_ids.push(book_id);
+    }
+
+    tx.commit().await?;
+
+    for &book_id in &result.updated_book_ids {
+        if let Some(book) = Book::get(pool, book_id).await? {
+            let syncable = get_book_files(pool, &book).await?.iter().any(|file| {
+                let link = file.file_link.to_lowercase();
+                link.ends_with(".epub") || link.ends_with(".pdf") || link.ends_with(".cbz")
+            });
+            if syncable {
+                result.marked_for_sync.push(book_id);
+            }
+        }
+    }
+    mark_books_for_sync(pool, &result.marked_for_sync, "bulk_edit").await?;
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{create_test_db, insert_book, tag_book};
+
+    async fn book_row(pool: &sqlx::SqlitePool, book_id: i64) -> (Option<String>, Option<i64>) {
+        sqlx::query_as(
+            "SELECT publishers.name, books.series_index FROM books
+             LEFT JOIN publishers ON books.publisher_id = publishers.id
+             WHERE books.id = ?",
+        )
+        .bind(book_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn book_tags(pool: &sqlx::SqlitePool, book_id: i64) -> Vec<String> {
+        sqlx::query_scalar(
+            "SELECT tags.name FROM x_books_tags JOIN tags ON tags.id = x_books_tags.tag_id
+             WHERE book_id = ? ORDER BY tags.name",
+        )
+        .bind(book_id)
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_plan_and_apply_bulk_edit() {
+        let pool = create_test_db().await;
+        let carrie = insert_book(&pool, "Carrie").await;
+        let it = insert_book(&pool, "It").await;
+        tag_book(&pool, carrie, "to-read").await;
+
+        let edit = BulkEdit {
+            publisher: Some("Doubleday".to_string()),
+            add_tags: vec!["to-read".to_string()],
+            remove_tags: vec!["horror".to_string()],
+            add_people: vec![("Stephen King".to_string(), "author".to_string())],
+            ..Default::default()
+        };
+        let plans = plan_bulk_edit(&pool, &[carrie, it], &edit).await.unwrap();
+        assert_eq!(plans.len(), 2);
+        assert_eq!(
+            plans[0].changes,
+            vec![
+                FieldChange::Set {
+                    field: "editore",
+                    before: None,
+                    after: "Doubleday".to_string(),
+                },
+                FieldChange::Added {
+                    field: "persona",
+                    value: "Stephen King (role.author)".to_string(),
+                },
+            ]
+        );
+        assert!(plans[1].changes.contains(&FieldChange::Added {
+            field: "tag",
+            value: "to-read".to_string(),
+        }));
+
+        // Il piano non modifica il database
+        assert_eq!(book_row(&pool, it).await, (None, None));
+
+        let result = apply_bulk_edit(&pool, &plans, &edit).await.unwrap();
+        assert_eq!(result.updated_book_ids, vec![carrie, it]);
+        assert!(result.marked_for_sync.is_empty());
+        assert_eq!(book_row(&pool, it).await.0.as_deref(), Some("Doubleday"));
+        assert_eq!(book_tags(&pool, it).await, vec!["to-read"]);
+        let authors: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM x_books_people_roles")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(authors, 2);
+
+        // Una seconda esecuzione non trova più nulla da cambiare
+        assert!(plan_bulk_edit(&pool, &[carrie, it], &edit)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(plan_bulk_edit(&pool, &[carrie], &BulkEdit::default())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_apply_writes_only_planned_changes() {
+        let pool = create_test_db().await;
+        let book = insert_book(&pool, "Il pendolo di Foucault").await;
+        // 15 ottobre 1988: l'anno coincide con quello della modifica
+        sqlx::query("UPDATE books SET publication_date = 592876800 WHERE id = ?")
+            .bind(book)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let edit = BulkEdit {
+            year: Some(1988),
+            add_tags: vec!["romanzo".to_string()],
+            ..Default::default()
+        };
+        let plans = plan_bulk_edit(&pool, &[book], &edit).await.unwrap();
+        assert_eq!(
+            plans[0].changes,
+            vec![FieldChange::Added {
+                field: "tag",
+                value: "romanzo".to_string(),
+            }]
+        );
+        apply_bulk_edit(&pool, &plans, &edit).await.unwrap();
+
+        let date: Option<i64> =
+            sqlx::query_scalar("SELECT publication_date FROM books WHERE id = ?")
+                .bind(book)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(date, Some(592876800));
+        assert_eq!(book_tags(&pool, book).await, vec!["romanzo"]);
+    }
+
+    #[tokio::test]
+    async fn test_renumber_series_follows_selection_order() {
+        let pool = create_test_db().await;
+        let first = insert_book(&pool, "Guards! Guards!").await;
+        let second = insert_book(&pool, "Men at Arms").await;
+        let third = insert_book(&pool, "Feet of Clay").await;
+
+        let edit = BulkEdit {
+            series: Some("Discworld".to_string()),
+            renumber_series_from: Some(5),
+            ..Default::default()
+        };
+        let plans = plan_bulk_edit(&pool, &[third, first, second], &edit)
+            .await
+            .unwrap();
+        apply_bulk_edit(&pool, &plans, &edit).await.unwrap();
+
+        assert_eq!(book_row(&pool, third).await.1, Some(5));
+        assert_eq!(book_row(&pool, first).await.1, Some(6));
+        assert_eq!(book_row(&pool, second).await.1, Some(7));
+        let series: Vec<i64> = sqlx::query_scalar("SELECT DISTINCT series_id FROM books")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(series.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_apply_bulk_edit_rolls_back_on_failure() {
+        let pool = create_test_db().await;
+        let kept = insert_book(&pool, "Kept").await;
+        let deleted = insert_book(&pool, "Deleted").await;
+
+        let edit = BulkEdit {
+            publisher: Some("Adelphi".to_string()),
+            add_tags: vec!["classic".to_string()],
+            ..Default::default()
+        };
+        let plans = plan_bulk_edit(&pool, &[kept, deleted], &edit)
+            .await
+            .unwrap();
+        assert_eq!(plans.len(), 2);
+
+        // Il secondo libro sparisce tra anteprima e conferma
+        sqlx::query("DELETE FROM books WHERE id = ?")
+            .bind(deleted)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(apply_bulk_edit(&pool, &plans, &edit).await.is_err());
+        assert_eq!(book_row(&pool, kept).await, (None, None));
+        assert!(book_tags(&pool, kept).await.is_empty());
+        let publishers: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM publishers")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(publishers, 0);
+    }
+}
@@ -1,4 +1,5 @@
-use ritmo_db::{Book, Content};
+use crate::service::book_import_service::find_covers;
+use ritmo_db::{Book, BookFile, Content};
 use ritmo_db_core::LibraryConfig;
 use ritmo_errors::reporter::RitmoReporter;
 use ritmo_errors::{RitmoErr, RitmoResult};
@@ -32,6 +33,8 @@ pub struct DeleteOptions {
 /// - **x_books_contents**: Associazioni libro-contenuti
 /// - **x_books_people_roles**: Associazioni libro-autori/contributori
 /// - **x_books_tags**: Associazioni libro-tag
+/// - **book_files**: File associati al libro (con `delete_file` vengono eliminati anche dallo storage,
+///   insieme alla copertina in `covers/`)
 ///
 /// Le entità referenziate (people, publishers, series, formats, tags, contents) **NON** vengono
 /// eliminate e possono diventare orfane. Utilizzare `cleanup_orphaned_entities()` per rimuoverle.
@@ -60,43 +63,33 @@ pub async fn delete_book(
         .await?
         .ok_or_else(|| RitmoErr::Generic(format!("Libro con ID {} non trovato", book_id)))?;
 
-    // 2. Se richiesto, elimina il file fisico
+    // 2. Se richiesto, elimina i file fisici (tutti i formati del libro)
     if options.delete_file {
+        let mut file_links: Vec<String> = BookFile::list_for_book(pool, book_id)
+            .await?
+            .into_iter()
+            .map(|f| f.file_link)
+            .collect();
         if let Some(file_link) = &book.file_link {
-            let file_path = config.canonical_storage_path().join(file_link);
-
-            if file_path.exists() {
-                match fs::remove_file(&file_path) {
-                    Ok(_) => {
-                        reporter.status(&format!("File eliminato: {}", file_path.display()));
-                    }
-                    Err(e) => {
-                        if !options.force {
-                            return Err(RitmoErr::Generic(format!(
-                                "Impossibile eliminare file {}: {}",
-                                file_path.display(),
-                                e
-                            )));
-                        } else {
-                            reporter.error(&format!(
-                                "Warning: impossibile eliminare file {} (continuando per --force): {}",
-                                file_path.display(),
-                                e
-                            ));
-                        }
-                    }
-                }
-            } else if !options.force {
-                return Err(RitmoErr::Generic(format!(
-                    "File non trovato: {} (usa --force per ignorare)",
-                    file_path.display()
-                )));
+            if !file_links.contains(file_link) {
+                file_links.push(file_link.clone());
+            }
+        }
+
+        for file_link in &file_links {
+            remove_stored_file(config, file_link, options, reporter)?;
+        }
+
+        // Copertine estratte (es. prima pagina dei fumetti)
+        for cover in find_covers(config, book_id) {
+            if fs::remove_file(&cover).is_ok() {
+                reporter.status(&format!("Copertina eliminata: {}", cover.display()));
             }
         }
     }
 
     // 3. Elimina record dal database
-    // Le relazioni in x_books_people_roles, x_books_tags, x_books_contents
+    // Le relazioni in x_books_people_roles, x_books_tags, x_books_contents e book_files
     // vengono eliminate automaticamente grazie a ON DELETE CASCADE
     let rows_affected = Book::delete(pool, book_id).await?;
 
@@ -110,6 +103,105 @@ pub async fn delete_book(
     Ok(())
 }
 
+/// Elimina un singolo file di un libro (es. il PDF di un libro che ha anche l'EPUB)
+///
+/// Il libro resta nel database. Se il file eliminato era il file principale del libro
+/// (`books.file_link`), il file più vecchio rimasto diventa il nuovo principale;
+/// se non restano file, le colonne file di `books` vengono azzerate.
+///
+/// # Arguments
+/// * `config` - Configurazione della libreria (per trovare i file)
+/// * `pool` - Pool di connessioni al database
+/// * `file_id` - ID del file in `book_files`
+/// * `options` - Opzioni di cancellazione (delete_file, force)
+/// * `reporter` - Reporter per messaggi di stato ed errori
+pub async fn delete_book_file(
+    config: &LibraryConfig,
+    pool: &sqlx::SqlitePool,
+    file_id: i64,
+    options: &DeleteOptions,
+    reporter: &mut impl RitmoReporter,
+) -> RitmoResult<()> {
+    let file = BookFile::get(pool, file_id)
+        .await?
+        .ok_or_else(|| RitmoErr::Generic(format!("File con ID {} non trovato", file_id)))?;
+
+    if options.delete_file {
+        remove_stored_file(config, &file.file_link, options, reporter)?;
+    }
+
+    BookFile::delete(pool, file_id).await?;
+
+    // Se era il file principale, promuovi il prossimo file rimasto
+    let book = Book::get(pool, file.book_id).await?;
+    if let Some(book) = book {
+        if book.file_link.as_deref() == Some(file.file_link.as_str()) {
+            let next = BookFile::list_for_book(pool, file.book_id)
+                .await?
+                .into_iter()
+                .next();
+            let (link, hash, size, format_id) = match next {
+                Some(f) => (Some(f.file_link), Some(f.file_hash), f.file_size, f.format_id),
+                None => (None, None, None, None),
+            };
+            sqlx::query!(
+                "UPDATE books SET file_link = ?, file_hash = ?, file_size = ?, format_id = ? WHERE id = ?",
+                link,
+                hash,
+                size,
+                format_id,
+                file.book_id
+            )
+            .execute(pool)
+            .await?;
+        }
+    }
+
+    reporter.status(&format!("File {} rimosso dal libro {}", file.file_link, file.book_id));
+
+    Ok(())
+}
+
+/// Elimina un file fisico dallo storage rispettando l'opzione `force`
+fn remove_stored_file(
+    config: &LibraryConfig,
+    file_link: &str,
+    options: &DeleteOptions,
+    reporter: &mut impl RitmoReporter,
+) -> RitmoResult<()> {
+    let file_path = config.canonical_storage_path().join(file_link);
+
+    if file_path.exists() {
+        match fs::remove_file(&file_path) {
+            Ok(_) => {
+                reporter.status(&format!("File eliminato: {}", file_path.display()));
+            }
+            Err(e) => {
+                if !options.force {
+                    return Err(RitmoErr::Generic(format!(
+                        "Impossibile eliminare file {}: {}",
+                        file_path.display(),
+                        e
+                    )));
+                } else {
+                    reporter.error(&format!(
+                        "Warning: impossibile eliminare file {} (continuando per --force): {}",
+                        file_path.display(),
+                        e
+                    ));
+                }
+            }
+        }
+    } else if !options.force {
+        return Err(RitmoErr::Generic(format!(
+            "File non trovato: {} (usa --force per ignorare)",
+            file_path.display()
+        )));
+    }
+
+    Ok(())
+}
+
 /// Elimina un contenuto dal database
 ///
 /// Questa funzione:
@@ -159,7 +251,7 @@ pub async fn delete_content(
 /// - **People** (autori, traduttori, etc.): non presenti in `x_books_people_roles` né `x_contents_people_roles`
 /// - **Publishers**: non referenziati da nessun libro (`books.publisher_id`)
 /// - **Series**: non referenziate da nessun libro (`books.series_id`)
-/// - **Formats**: non usati da nessun libro (`books.format_id`)
+/// - **Formats**: non usati da nessun libro né file (`books.format_id`, `book_files.format_id`)
 /// - **Types**: non usati da nessun contenuto (`contents.type_id`)
 /// - **Tags**: non presenti in `x_books_tags` né `x_contents_tags`
 ///
@@ -211,7 +303,11 @@ pub async fn cleanup_orphaned_entities(pool: &sqlx::SqlitePool) -> RitmoResult<C
     // 4. Rimuovi formati orfani
     let formats_deleted = sqlx::query!(
         "DELETE FROM formats
-         WHERE id NOT IN (SELECT DISTINCT format_id FROM books WHERE format_id IS NOT NULL)"
+         WHERE id NOT IN (
+             SELECT DISTINCT format_id FROM books WHERE format_id IS NOT NULL
+             UNION
+             SELECT DISTINCT format_id FROM book_files WHERE format_id IS NOT NULL
+         )"
     )
     .execute(pool)
     .await?;
@@ -276,3 +372,104 @@ impl CleanupStats {
         self.total() > 0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{create_test_db, insert_book};
+    use ritmo_errors::reporter::SilentReporter;
+
+    /// Libro con tre file nello storage; il primo è il file principale
+    async fn book_with_files(config: &LibraryConfig, pool: &sqlx::SqlitePool) -> (i64, Vec<i64>) {
+        let storage = config.canonical_storage_path();
+        fs::create_dir_all(&storage).unwrap();
+        let book_id = insert_book(pool, "Picnic").await;
+        let mut file_ids = Vec::new();
+        for (added_at, link) in [(1, "picnic.fb2"), (2, "picnic.txt"), (3, "picnic.pdf")] {
+            fs::write(storage.join(link), link).unwrap();
+            let file = BookFile {
+                id: None,
+                book_id,
+                format_id: None,
+                file_link: link.to_string(),
+                file_hash: format!("hash-{}", link),
+                file_size: Some(10),
+                added_at,
+                text_fingerprint: None,
+            };
+            file_ids.push(file.save(pool).await.unwrap());
+        }
+        sqlx::query(
+            "UPDATE books SET file_link = 'picnic.fb2', file_hash = 'hash-picnic.fb2' WHERE id = ?",
+        )
+        .bind(book_id)
+        .execute(pool)
+        .await
+        .unwrap();
+        (book_id, file_ids)
+    }
+
+    fn delete_file() -> DeleteOptions {
+        DeleteOptions {
+            delete_file: true,
+            force: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_primary_file_promotes_next() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = LibraryConfig::new(dir.path());
+        let pool = create_test_db().await;
+        let (book_id, file_ids) = book_with_files(&config, &pool).await;
+
+        // Un file secondario: il principale non cambia
+        delete_book_file(&config, &pool, file_ids[2], &delete_file(), &mut SilentReporter)
+            .await
+            .unwrap();
+        let book = Book::get(&pool, book_id).await.unwrap().unwrap();
+        assert_eq!(book.file_link.as_deref(), Some("picnic.fb2"));
+
+        // Il principale: diventa principale il file più vecchio rimasto
+        delete_book_file(&config, &pool, file_ids[0], &delete_file(), &mut SilentReporter)
+            .await
+            .unwrap();
+        let book = Book::get(&pool, book_id).await.unwrap().unwrap();
+        assert_eq!(book.file_link.as_deref(), Some("picnic.txt"));
+        assert_eq!(book.file_hash.as_deref(), Some("hash-picnic.txt"));
+
+        let storage = config.canonical_storage_path();
+        assert!(!storage.join("picnic.fb2").exists());
+        assert!(!storage.join("picnic.pdf").exists());
+        assert!(storage.join("picnic.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_delete_last_file_keeps_book() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = LibraryConfig::new(dir.path());
+        let pool = create_test_db().await;
+        let (book_id, file_ids) = book_with_files(&config, &pool).await;
+
+        for file_id in file_ids {
+            delete_book_file(&config, &pool, file_id, &delete_file(), &mut SilentReporter)
+                .await
+                .unwrap();
+        }
+
+        let book = Book::get(&pool, book_id).await.unwrap().unwrap();
+        assert_eq!(book.file_link, None);
+        assert_eq!(book.file_hash, None);
+        assert!(BookFile::list_for_book(&pool, book_id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Il file non esiste più
+        assert!(
+            delete_book_file(&config, &pool, 1, &delete_file(), &mut SilentReporter)
+                .await
+                .is_err()
+        );
+    }
+}
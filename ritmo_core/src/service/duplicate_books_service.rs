@@ -0,0 +1,1006 @@
+use crate::service::book_import_service::find_covers;
+use crate::text_fingerprint::{epub_fingerprint, TextFingerprint};
+use ritmo_db::{mark_book_for_sync, BookFile};
+use ritmo_db_core::LibraryConfig;
+use ritmo_errors::{RitmoErr, RitmoResult};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+
+/// Somiglianza minima predefinita tra le impronte del testo
+pub const DEFAULT_CONTENT_SIMILARITY: f64 = 0.8;
+
+/// Motivo per cui due libri sono considerati duplicati
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DuplicateReason {
+    /// Stesso ISBN-13 canonico
+    Isbn { isbn13: String },
+    /// Stesso titolo normalizzato e stesse persone
+    TitleAndPeople,
+    /// Testo dell'EPUB simile (somiglianza stimata delle impronte)
+    Content { similarity: f64 },
+}
+
+impl std::fmt::Display for DuplicateReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DuplicateReason::Isbn { isbn13 } => write!(f, "stesso ISBN ({})", isbn13),
+            DuplicateReason::TitleAndPeople => write!(f, "stesso titolo e stessi autori"),
+            DuplicateReason::Content { similarity } => {
+                write!(f, "testo simile ({:.0}%)", similarity * 100.0)
+            }
+        }
+    }
+}
+
+/// Libro esistente simile a quello in import
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportDuplicate {
+    pub book_id: i64,
+    pub title: String,
+    pub reason: DuplicateReason,
+}
+
+/// Libro di un gruppo di duplicati
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateBook {
+    pub book_id: i64,
+    pub title: String,
+    pub people: Vec<String>,
+    pub isbn: Option<String>,
+    pub file_count: usize,
+}
+
+/// Coppia di libri duplicati con i motivi
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateMatch {
+    pub book_a: i64,
+    pub book_b: i64,
+    pub reasons: Vec<DuplicateReason>,
+}
+
+/// Gruppo di libri che sembrano la stessa edizione
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateBookGroup {
+    /// Libro proposto come principale (il più vecchio)
+    pub primary_id: i64,
+    pub books: Vec<DuplicateBook>,
+    pub matches: Vec<DuplicateMatch>,
+}
+
+/// Esito della ricerca di libri duplicati
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DuplicateBooksReport {
+    pub groups: Vec<DuplicateBookGroup>,
+    /// File EPUB la cui impronta del testo è stata calcolata in questa ricerca
+    pub fingerprinted: usize,
+    /// File EPUB di cui non è stato possibile leggere il testo
+    pub unreadable: Vec<String>,
+}
+
+/// Esito dell'unione di libri duplicati
+#[derive(Debug, Clone, Default)]
+pub struct BookMergeResult {
+    pub primary_id: i64,
+    pub merged_ids: Vec<i64>,
+    /// File spostati sul libro principale
+    pub moved_files: usize,
+    /// File eliminati dallo storage (con `delete_files`)
+    pub deleted_files: usize,
+}
+
+/// Titolo normalizzato per il confronto: parole minuscole, solo lettere e cifre
+fn normalize_title(title: &str) -> String {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Insieme delle persone di un libro (qualsiasi ruolo), normalizzato e ordinato
+fn normalize_people<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut people: Vec<String> = names
+        .into_iter()
+        .map(normalize_title)
+        .filter(|n| !n.is_empty())
+        .collect();
+    people.sort();
+    people.dedup();
+    people
+}
+
+/// Persone di tutti i libri, per ID libro
+async fn people_by_book(pool: &sqlx::SqlitePool) -> RitmoResult<HashMap<i64, Vec<String>>> {
+    let rows = sqlx::query!(
+        r#"SELECT bpr.book_id, p.name
+           FROM x_books_people_roles bpr
+           JOIN people p ON p.id = bpr.person_id
+           ORDER BY bpr.book_id, p.name"#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut people: HashMap<i64, Vec<String>> = HashMap::new();
+    for row in rows {
+        let names = people.entry(row.book_id).or_default();
+        if !names.contains(&row.name) {
+            names.push(row.name);
+        }
+    }
+    Ok(people)
+}
+
+/// Impronte del testo salvate, per file
+async fn stored_fingerprints(pool: &sqlx::SqlitePool) -> RitmoResult<Vec<(i64, TextFingerprint)>> {
+    let rows = sqlx::query!(
+        r#"SELECT book_id, text_fingerprint as "text_fingerprint!"
+           FROM book_files
+           WHERE text_fingerprint IS NOT NULL AND text_fingerprint <> ''
+           ORDER BY book_id, id"#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            TextFingerprint::from_hex(&row.text_fingerprint).map(|f| (row.book_id, f))
+        })
+        .collect())
+}
+
+/// Libri già in libreria simili a quello in import
+///
+/// Usato come avviso durante l'import: stesso ISBN-13, stesso titolo con le stesse
+/// persone, oppure testo dell'EPUB con somiglianza almeno `min_similarity`.
+pub async fn find_import_duplicates(
+    pool: &sqlx::SqlitePool,
+    title: &str,
+    people: &[(String, String)],
+    isbn13: Option<&str>,
+    fingerprint: Option<&TextFingerprint>,
+    min_similarity: f64,
+) -> RitmoResult<Vec<ImportDuplicate>> {
+    fn push(
+        found: &mut Vec<ImportDuplicate>,
+        book_id: i64,
+        title: String,
+        reason: DuplicateReason,
+    ) {
+        if !found.iter().any(|d| d.book_id == book_id) {
+            found.push(ImportDuplicate {
+                book_id,
+                title,
+                reason,
+            });
+        }
+    }
+    let mut found: Vec<ImportDuplicate> = Vec::new();
+
+    if let Some(isbn13) = isbn13 {
+        let rows = sqlx::query!(
+            r#"SELECT id as "id!", name FROM books WHERE isbn13 = ? ORDER BY id"#,
+            isbn13
+        )
+        .fetch_all(pool)
+        .await?;
+        for row in rows {
+            let reason = DuplicateReason::Isbn {
+                isbn13: isbn13.to_string(),
+            };
+            push(&mut found, row.id, row.name, reason);
+        }
+    }
+
+    let wanted_title = normalize_title(title);
+    let wanted_people = normalize_people(people.iter().map(|(name, _)| name.as_str()));
+    if !wanted_title.is_empty() && !wanted_people.is_empty() {
+        let candidates: Vec<(i64, String)> =
+            sqlx::query!(r#"SELECT id as "id!", name FROM books ORDER BY id"#)
+                .fetch_all(pool)
+                .await?
+                .into_iter()
+                .filter(|row| normalize_title(&row.name) == wanted_title)
+                .map(|row| (row.id, row.name))
+                .collect();
+        if !candidates.is_empty() {
+            let people_by_book = people_by_book(pool).await?;
+            for (book_id, name) in candidates {
+                let book_people = people_by_book
+                    .get(&book_id)
+                    .map(|names| normalize_people(names.iter().map(String::as_str)))
+                    .unwrap_or_default();
+                if book_people == wanted_people {
+                    push(&mut found, book_id, name, DuplicateReason::TitleAndPeople);
+                }
+            }
+        }
+    }
+
+    if let Some(fingerprint) = fingerprint {
+        let mut best: BTreeMap<i64, f64> = BTreeMap::new();
+        for (book_id, stored) in stored_fingerprints(pool).await? {
+            let similarity = fingerprint.similarity(&stored);
+            if similarity >= min_similarity {
+                let entry = best.entry(book_id).or_insert(similarity);
+                *entry = entry.max(similarity);
+            }
+        }
+        for (book_id, similarity) in best {
+            let name = sqlx::query_scalar!("SELECT name FROM books WHERE id = ?", book_id)
+                .fetch_one(pool)
+                .await?;
+            push(
+                &mut found,
+                book_id,
+                name,
+                DuplicateReason::Content { similarity },
+            );
+        }
+    }
+
+    Ok(found)
+}
+
+/// Calcola l'impronta del testo degli EPUB che non ce l'hanno ancora
+/// (es. importati prima di `book_files.text_fingerprint`)
+///
+/// Un testo troppo breve viene salvato come stringa vuota, così da non
+/// rileggere il file alla ricerca successiva.
+async fn fill_missing_fingerprints(
+    config: &LibraryConfig,
+    pool: &sqlx::SqlitePool,
+    report: &mut DuplicateBooksReport,
+) -> RitmoResult<()> {
+    let rows = sqlx::query!(
+        r#"SELECT id as "id!", file_link
+           FROM book_files
+           WHERE text_fingerprint IS NULL AND LOWER(file_link) LIKE '%.epub'
+           ORDER BY id"#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for row in rows {
+        let path = config.canonical_storage_path().join(&row.file_link);
+        match epub_fingerprint(&path) {
+            Ok(fingerprint) => {
+                let hex = fingerprint.map(|f| f.to_hex()).unwrap_or_default();
+                BookFile::set_text_fingerprint(pool, row.id, &hex).await?;
+                report.fingerprinted += 1;
+            }
+            Err(_) => report.unreadable.push(row.file_link),
+        }
+    }
+    Ok(())
+}
+
+/// Unione di insiemi disgiunti sugli ID dei libri
+struct BookSets {
+    parent: HashMap<i64, i64>,
+}
+
+impl BookSets {
+    fn find(&mut self, id: i64) -> i64 {
+        let parent = *self.parent.entry(id).or_insert(id);
+        if parent == id {
+            return id;
+        }
+        let root = self.find(parent);
+        self.parent.insert(id, root);
+        root
+    }
+
+    fn union(&mut self, a: i64, b: i64) {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra != rb {
+            // La radice è sempre l'ID più basso
+            self.parent.insert(ra.max(rb), ra.min(rb));
+        }
+    }
+}
+
+/// Cerca i libri duplicati nella libreria
+///
+/// Due libri sono duplicati se hanno:
+/// - lo stesso ISBN-13 canonico
+/// - lo stesso titolo normalizzato e le stesse persone (qualsiasi ruolo; libri senza persone esclusi)
+/// - EPUB con testo simile: somiglianza delle impronte almeno `min_similarity`
+///
+/// I candidati per il confronto dei testi vengono scelti con le bande LSH delle
+/// impronte, senza confrontare tutte le coppie. Le impronte mancanti vengono
+/// calcolate e salvate prima della ricerca. I duplicati vengono raggruppati
+/// transitivamente; il libro più vecchio (ID più basso) è proposto come principale.
+pub async fn find_duplicate_books(
+    config: &LibraryConfig,
+    pool: &sqlx::SqlitePool,
+    min_similarity: f64,
+) -> RitmoResult<DuplicateBooksReport> {
+    let mut report = DuplicateBooksReport::default();
+    fill_missing_fingerprints(config, pool, &mut report).await?;
+
+    let books = sqlx::query!(
+        r#"SELECT b.id as "id!", b.name, b.isbn, b.isbn13,
+                  (SELECT COUNT(*) FROM book_files bf WHERE bf.book_id = b.id) AS "file_count!: i64"
+           FROM books b
+           ORDER BY b.id"#
+    )
+    .fetch_all(pool)
+    .await?;
+    let people = people_by_book(pool).await?;
+
+    let mut reasons: BTreeMap<(i64, i64), Vec<DuplicateReason>> = BTreeMap::new();
+    let mut add = |a: i64, b: i64, reason: DuplicateReason| {
+        if a != b {
+            let entry = reasons.entry((a.min(b), a.max(b))).or_default();
+            if !entry.contains(&reason) {
+                entry.push(reason);
+            }
+        }
+    };
+
+    // Stesso ISBN-13 e stesso titolo/persone
+    let mut by_isbn: BTreeMap<&str, Vec<i64>> = BTreeMap::new();
+    let mut by_title: BTreeMap<(String, Vec<String>), Vec<i64>> = BTreeMap::new();
+    for book in &books {
+        if let Some(isbn13) = book.isbn13.as_deref().filter(|i| !i.is_empty()) {
+            by_isbn.entry(isbn13).or_default().push(book.id);
+        }
+        let book_people = people
+            .get(&book.id)
+            .map(|names| normalize_people(names.iter().map(String::as_str)))
+            .unwrap_or_default();
+        let title = normalize_title(&book.name);
+        if !book_people.is_empty() && !title.is_empty() {
+            by_title
+                .entry((title, book_people))
+                .or_default()
+                .push(book.id);
+        }
+    }
+    for (isbn13, ids) in &by_isbn {
+        for (i, &a) in ids.iter().enumerate() {
+            for &b in &ids[i + 1..] {
+                add(
+                    a,
+                    b,
+                    DuplicateReason::Isbn {
+                        isbn13: isbn13.to_string(),
+                    },
+                );
+            }
+        }
+    }
+    for ids in by_title.values() {
+        for (i, &a) in ids.iter().enumerate() {
+            for &b in &ids[i + 1..] {
+                add(a, b, DuplicateReason::TitleAndPeople);
+            }
+        }
+    }
+
+    // Testo simile: candidati dalle bande LSH, poi confronto delle impronte
+    let fingerprints = stored_fingerprints(pool).await?;
+    let mut buckets: HashMap<(usize, &[u64]), Vec<usize>> = HashMap::new();
+    for (index, (_, fingerprint)) in fingerprints.iter().enumerate() {
+        for key in fingerprint.band_keys() {
+            buckets.entry(key).or_default().push(index);
+        }
+    }
+    let mut best: BTreeMap<(i64, i64), f64> = BTreeMap::new();
+    for members in buckets.values().filter(|m| m.len() > 1) {
+        for (i, &x) in members.iter().enumerate() {
+            for &y in &members[i + 1..] {
+                let (book_x, fp_x) = &fingerprints[x];
+                let (book_y, fp_y) = &fingerprints[y];
+                if book_x == book_y {
+                    continue;
+                }
+                let key = (*book_x.min(book_y), *book_x.max(book_y));
+                let similarity = fp_x.similarity(fp_y);
+                let entry = best.entry(key).or_insert(similarity);
+                *entry = entry.max(similarity);
+            }
+        }
+    }
+    for ((a, b), similarity) in best {
+        if similarity >= min_similarity {
+            add(a, b, DuplicateReason::Content { similarity });
+        }
+    }
+
+    // Raggruppamento transitivo
+    let mut sets = BookSets {
+        parent: HashMap::new(),
+    };
+    for &(a, b) in reasons.keys() {
+        sets.union(a, b);
+    }
+    let mut groups: BTreeMap<i64, DuplicateBookGroup> = BTreeMap::new();
+    for book in &books {
+        if !sets.parent.contains_key(&book.id) {
+            continue;
+        }
+        let root = sets.find(book.id);
+        let group = groups.entry(root).or_insert_with(|| DuplicateBookGroup {
+            primary_id: root,
+            books: Vec::new(),
+            matches: Vec::new(),
+        });
+        group.books.push(DuplicateBook {
+            book_id: book.id,
+            title: book.name.clone(),
+            people: people.get(&book.id).cloned().unwrap_or_default(),
+            isbn: book.isbn.clone(),
+            file_count: book.file_count as usize,
+        });
+    }
+    for ((a, b), reasons) in reasons {
+        let root = sets.find(a);
+        if let Some(group) = groups.get_mut(&root) {
+            group.matches.push(DuplicateMatch {
+                book_a: a,
+                book_b: b,
+                reasons,
+            });
+        }
+    }
+
+    report.groups = groups.into_values().collect();
+    Ok(report)
+}
+
+/// Unisce libri duplicati nel libro principale
+///
+/// In una transazione, per ogni duplicato:
+/// 1. i file (`book_files`, e il file principale dei libri importati prima di
+///    `book_files`) passano al libro principale, oppure vengono eliminati con `delete_files`
+/// 2. contenuti, persone, tag e scaffali vengono aggiunti al principale (senza doppioni)
+/// 3. i campi vuoti del principale vengono completati con quelli del duplicato
+/// 4. il duplicato viene eliminato
+///
+/// Se il principale non ha un file principale, il file più vecchio rimasto lo diventa.
+/// Dopo il commit le copertine dei duplicati vengono spostate (se il principale non ne ha)
+/// o eliminate, e il libro principale viene marcato per la sincronizzazione dei metadati.
+pub async fn merge_books(
+    config: &LibraryConfig,
+    pool: &sqlx::SqlitePool,
+    primary_id: i64,
+    duplicate_ids: &[i64],
+    delete_files: bool,
+) -> RitmoResult<BookMergeResult> {
+    if duplicate_ids.is_empty() {
+        return Err(RitmoErr::Generic("Nessun libro da unire".to_string()));
+    }
+    if duplicate_ids.contains(&primary_id) {
+        return Err(RitmoErr::Generic(format!(
+            "Il libro {} non può essere unito a se stesso",
+            primary_id
+        )));
+    }
+
+    let mut result = BookMergeResult {
+        primary_id,
+        merged_ids: duplicate_ids.to_vec(),
+        ..Default::default()
+    };
+    let mut files_to_delete: Vec<String> = Vec::new();
+    let mut cover_sources: Vec<i64> = Vec::new();
+
+    let mut tx = pool.begin().await?;
+
+    let primary_has_cover =
+        sqlx::query_scalar!("SELECT has_cover FROM books WHERE id = ?", primary_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| RitmoErr::Generic(format!("Libro con ID {} non trovato", primary_id)))?;
+    let mut has_cover = primary_has_cover == 1;
+
+    for &duplicate_id in duplicate_ids {
+        let duplicate = sqlx::query!(
+            "SELECT file_link, file_hash, file_size, format_id, has_cover FROM books WHERE id = ?",
+            duplicate_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| RitmoErr::Generic(format!("Libro con ID {} non trovato", duplicate_id)))?;
+
+        // 1. File
+        let files = sqlx::query!(
+            r#"SELECT file_link FROM book_files WHERE book_id = ?"#,
+            duplicate_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        let mut file_links: Vec<String> = files.into_iter().map(|f| f.file_link).collect();
+        let legacy_link = duplicate
+            .file_link
+            .clone()
+            .filter(|link| !file_links.contains(link));
+
+        if delete_files {
+            file_links.extend(legacy_link);
+            files_to_delete.extend(file_links);
+        } else {
+            result.moved_files += file_links.len();
+            sqlx::query!(
+                "UPDATE book_files SET book_id = ? WHERE book_id = ?",
+                primary_id,
+                duplicate_id
+            )
+            .execute(&mut *tx)
+            .await?;
+            if let Some(link) = legacy_link {
+                let hash = duplicate.file_hash.clone().unwrap_or_default();
+                let now = chrono::Utc::now().timestamp();
+                sqlx::query!(
+                    "INSERT INTO book_files (book_id, format_id, file_link, file_hash, file_size, added_at)
+                     VALUES (?, ?, ?, ?, ?, ?)",
+                    primary_id,
+                    duplicate.format_id,
+                    link,
+                    hash,
+                    duplicate.file_size,
+                    now
+                )
+                .execute(&mut *tx)
+                .await?;
+                result.moved_files += 1;
+            }
+        }
+
+        // 2. Relazioni
+        sqlx::query!(
+            "INSERT OR IGNORE INTO x_books_contents (book_id, content_id)
+             SELECT ?, content_id FROM x_books_contents WHERE book_id = ?",
+            primary_id,
+            duplicate_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "INSERT OR IGNORE INTO x_books_people_roles (book_id, person_id, role_id)
+             SELECT ?, person_id, role_id FROM x_books_people_roles WHERE book_id = ?",
+            primary_id,
+            duplicate_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "INSERT OR IGNORE INTO x_books_tags (book_id, tag_id)
+             SELECT ?, tag_id FROM x_books_tags WHERE book_id = ?",
+            primary_id,
+            duplicate_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "INSERT OR IGNORE INTO x_shelves_books (shelf_id, book_id, position, notes, added_at)
+             SELECT shelf_id, ?, position, notes, added_at FROM x_shelves_books WHERE book_id = ?",
+            primary_id,
+            duplicate_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        // 3. Campi vuoti del principale (ISBN e ISBN-13 sempre dallo stesso libro)
+        sqlx::query!(
+            "UPDATE books SET
+                original_title = COALESCE(books.original_title, d.original_title),
+                publisher_id = COALESCE(books.publisher_id, d.publisher_id),
+                series_id = COALESCE(books.series_id, d.series_id),
+                series_index = COALESCE(books.series_index, d.series_index),
+                publication_date = COALESCE(books.publication_date, d.publication_date),
+                isbn = CASE WHEN books.isbn IS NULL AND books.isbn13 IS NULL
+                            THEN d.isbn ELSE books.isbn END,
+                isbn13 = CASE WHEN books.isbn IS NULL AND books.isbn13 IS NULL
+                               THEN d.isbn13 ELSE books.isbn13 END,
+                pages = COALESCE(books.pages, d.pages),
+                notes = COALESCE(books.notes, d.notes),
+                has_paper = MAX(books.has_paper, d.has_paper)
+             FROM (SELECT * FROM books WHERE id = ?) AS d
+             WHERE books.id = ?",
+            duplicate_id,
+            primary_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if duplicate.has_cover == 1 {
+            cover_sources.push(duplicate_id);
+        }
+
+        // 4. Eliminazione (le relazioni rimaste vengono rimosse da ON DELETE CASCADE)
+        sqlx::query!("DELETE FROM books WHERE id = ?", duplicate_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    // Principale senza file principale: promuovi il file più vecchio
+    let primary_link = sqlx::query_scalar!("SELECT file_link FROM books WHERE id = ?", primary_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if primary_link.is_none() {
+        sqlx::query!(
+            "UPDATE books SET (file_link, file_hash, file_size, format_id) =
+                (SELECT file_link, file_hash, file_size, format_id
+                 FROM book_files WHERE book_id = books.id ORDER BY added_at, id LIMIT 1)
+             WHERE id = ? AND EXISTS (SELECT 1 FROM book_files WHERE book_id = books.id)",
+            primary_id
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    sqlx::query!(
+        "UPDATE books SET last_modified_date = ? WHERE id = ?",
+        now,
+        primary_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    // File e copertine: solo dopo il commit, così un errore del database non lascia file persi
+    for link in &files_to_delete {
+        if fs::remove_file(config.canonical_storage_path().join(link)).is_ok() {
+            result.deleted_files += 1;
+        }
+    }
+    let covers_dir = config.canonical_storage_path().join("covers");
+    for duplicate_id in cover_sources {
+        for cover in find_covers(config, duplicate_id) {
+            let target = cover
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|ext| covers_dir.join(format!("{}.{}", primary_id, ext)));
+            match target {
+                Some(target) if !has_cover && fs::rename(&cover, &target).is_ok() => {
+                    has_cover = true;
+                    sqlx::query!("UPDATE books SET has_cover = 1 WHERE id = ?", primary_id)
+                        .execute(pool)
+                        .await?;
+                }
+                _ => {
+                    let _ = fs::remove_file(&cover);
+                }
+            }
+        }
+    }
+
+    mark_book_for_sync(pool, primary_id, "book_merge").await?;
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{create_test_db, insert_book, tag_book};
+
+    async fn exec(pool: &sqlx::SqlitePool, sql: &str, binds: &[i64]) {
+        let mut query = sqlx::query(sql);
+        for value in binds {
+            query = query.bind(value);
+        }
+        query.execute(pool).await.unwrap();
+    }
+
+    async fn add_person(pool: &sqlx::SqlitePool, book_id: i64, name: &str) {
+        sqlx::query("INSERT OR IGNORE INTO roles (key) VALUES ('role.author')")
+            .execute(pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO people (name) SELECT ? WHERE NOT EXISTS (SELECT 1 FROM people WHERE name = ?)")
+            .bind(name)
+            .bind(name)
+            .execute(pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO x_books_people_roles (book_id, person_id, role_id)
+             SELECT ?, people.id, roles.id FROM people, roles
+             WHERE people.name = ? AND roles.key = 'role.author'",
+        )
+        .bind(book_id)
+        .bind(name)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    async fn add_file(
+        pool: &sqlx::SqlitePool,
+        book_id: i64,
+        link: &str,
+        added_at: i64,
+        fingerprint: Option<String>,
+    ) {
+        sqlx::query(
+            "INSERT INTO book_files (book_id, file_link, file_hash, added_at, text_fingerprint)
+             VALUES (?, ?, 'hash', ?, ?)",
+        )
+        .bind(book_id)
+        .bind(link)
+        .bind(added_at)
+        .bind(fingerprint)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    /// Testo di prova: `changed` parole su 400 sostituite
+    fn text(changed: usize) -> String {
+        (0..400)
+            .map(|i| {
+                if i % 40 == 0 && i / 40 < changed {
+                    format!("diversa{}", i)
+                } else {
+                    format!("parola{}", (i * 7) % 97 + i / 97)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn config(dir: &tempfile::TempDir) -> LibraryConfig {
+        let config = LibraryConfig::new(dir.path());
+        fs::create_dir_all(config.canonical_storage_path()).unwrap();
+        config
+    }
+
+    async fn scalar(pool: &sqlx::SqlitePool, sql: &str, id: i64) -> Vec<String> {
+        sqlx::query_scalar(sql)
+            .bind(id)
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_find_duplicate_books() {
+        let pool = create_test_db().await;
+        let dir = tempfile::tempdir().unwrap();
+        let config = config(&dir);
+
+        // Stesso ISBN-13
+        let it = insert_book(&pool, "It").await;
+        let it_copy = insert_book(&pool, "IT (Edizione 1987)").await;
+        for id in [it, it_copy] {
+            exec(
+                &pool,
+                "UPDATE books SET isbn13 = '9780306406157' WHERE id = ?",
+                &[id],
+            )
+            .await;
+        }
+        // Stesso titolo normalizzato e stesse persone; senza persone non conta
+        let shining = insert_book(&pool, "The Shining").await;
+        let shining_copy = insert_book(&pool, "the shining!").await;
+        add_person(&pool, shining, "Stephen King").await;
+        add_person(&pool, shining_copy, "Stephen King").await;
+        insert_book(&pool, "Emma").await;
+        insert_book(&pool, "emma").await;
+        // Testo quasi uguale con titoli diversi, e un testo diverso
+        let misery = insert_book(&pool, "Misery").await;
+        let misery_copy = insert_book(&pool, "Misery (bozza)").await;
+        let other = insert_book(&pool, "Altro").await;
+        let fingerprint = |text: &str| TextFingerprint::from_text(text).map(|f| f.to_hex());
+        add_file(&pool, misery, "a.epub", 1, fingerprint(&text(0))).await;
+        add_file(&pool, misery_copy, "b.epub", 1, fingerprint(&text(1))).await;
+        let unrelated = (0..400).map(|i| format!("altro{}", i)).collect::<Vec<_>>();
+        add_file(&pool, other, "c.epub", 1, fingerprint(&unrelated.join(" "))).await;
+
+        let report = find_duplicate_books(&config, &pool, DEFAULT_CONTENT_SIMILARITY)
+            .await
+            .unwrap();
+
+        let groups: Vec<(i64, Vec<i64>)> = report
+            .groups
+            .iter()
+            .map(|g| (g.primary_id, g.books.iter().map(|b| b.book_id).collect()))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                (it, vec![it, it_copy]),
+                (shining, vec![shining, shining_copy]),
+                (misery, vec![misery, misery_copy]),
+            ]
+        );
+        assert_eq!(
+            report.groups[0].matches[0].reasons,
+            vec![DuplicateReason::Isbn {
+                isbn13: "9780306406157".to_string()
+            }]
+        );
+        assert_eq!(
+            report.groups[1].matches[0].reasons,
+            vec![DuplicateReason::TitleAndPeople]
+        );
+        assert!(matches!(
+            report.groups[2].matches[0].reasons[..],
+            [DuplicateReason::Content { similarity }] if similarity >= DEFAULT_CONTENT_SIMILARITY
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_merge_books_moves_relations() {
+        let pool = create_test_db().await;
+        let dir = tempfile::tempdir().unwrap();
+        let config = config(&dir);
+
+        let primary = insert_book(&pool, "It").await;
+        let duplicate = insert_book(&pool, "It (copia)").await;
+        add_file(&pool, primary, "it.epub", 2, None).await;
+        add_file(&pool, duplicate, "it-old.epub", 1, None).await;
+        // File principale di un libro importato prima di `book_files`
+        exec(
+            &pool,
+            "UPDATE books SET file_link = 'it.pdf', file_hash = 'h' WHERE id = ?",
+            &[duplicate],
+        )
+        .await;
+        tag_book(&pool, primary, "horror").await;
+        tag_book(&pool, duplicate, "horror").await;
+        tag_book(&pool, duplicate, "classic").await;
+        exec(
+            &pool,
+            "INSERT INTO publishers (name) VALUES ('Viking')",
+            &[],
+        )
+        .await;
+        exec(
+            &pool,
+            "UPDATE books SET publisher_id = 1 WHERE id = ?",
+            &[duplicate],
+        )
+        .await;
+        exec(&pool, "INSERT INTO shelves (name) VALUES ('Comodino')", &[]).await;
+        exec(
+            &pool,
+            "INSERT INTO x_shelves_books (shelf_id, book_id, position) VALUES (1, ?, 1)",
+            &[duplicate],
+        )
+        .await;
+
+        let result = merge_books(&config, &pool, primary, &[duplicate], false)
+            .await
+            .unwrap();
+
+        assert_eq!(result.moved_files, 2);
+        assert_eq!(
+            scalar(
+                &pool,
+                "SELECT file_link FROM book_files WHERE book_id = ? ORDER BY file_link",
+                primary
+            )
+            .await,
+            vec!["it-old.epub", "it.epub", "it.pdf"]
+        );
+        // Il principale non aveva un file principale: diventa il più vecchio
+        assert_eq!(
+            scalar(&pool, "SELECT file_link FROM books WHERE id = ?", primary).await,
+            vec!["it-old.epub"]
+        );
+        assert_eq!(
+            scalar(&pool, "SELECT tags.name FROM x_books_tags JOIN tags ON tags.id = tag_id WHERE book_id = ? ORDER BY tags.name", primary).await,
+            vec!["classic", "horror"]
+        );
+        assert_eq!(
+            scalar(&pool, "SELECT shelves.name FROM x_shelves_books JOIN shelves ON shelves.id = shelf_id WHERE book_id = ?", primary).await,
+            vec!["Comodino"]
+        );
+        assert_eq!(
+            scalar(&pool, "SELECT publishers.name FROM books JOIN publishers ON publishers.id = publisher_id WHERE books.id = ?", primary).await,
+            vec!["Viking"]
+        );
+        assert!(
+            scalar(&pool, "SELECT name FROM books WHERE id = ?", duplicate)
+                .await
+                .is_empty()
+        );
+        assert_eq!(
+            scalar(
+                &pool,
+                "SELECT reason FROM pending_metadata_sync WHERE book_id = ?",
+                primary
+            )
+            .await,
+            vec!["book_merge"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_books_keeps_isbn_pair() {
+        let pool = create_test_db().await;
+        let dir = tempfile::tempdir().unwrap();
+        let config = config(&dir);
+
+        let primary = insert_book(&pool, "Dune").await;
+        let duplicate = insert_book(&pool, "Dune (copia)").await;
+        let other = insert_book(&pool, "Dune (altra copia)").await;
+        // ISBN non valido: nessun ISBN-13 canonico
+        exec(
+            &pool,
+            "UPDATE books SET isbn = 'B00B7NPRY8' WHERE id = ?",
+            &[primary],
+        )
+        .await;
+        for id in [duplicate, other] {
+            exec(
+                &pool,
+                "UPDATE books SET isbn = '0-306-40615-2', isbn13 = '9780306406157' WHERE id = ?",
+                &[id],
+            )
+            .await;
+        }
+
+        merge_books(&config, &pool, primary, &[duplicate], false)
+            .await
+            .unwrap();
+        let isbn: (Option<String>, Option<String>) =
+            sqlx::query_as("SELECT isbn, isbn13 FROM books WHERE id = ?")
+                .bind(primary)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(isbn, (Some("B00B7NPRY8".to_string()), None));
+
+        // Principale senza ISBN: entrambi dal duplicato
+        exec(&pool, "UPDATE books SET isbn = NULL WHERE id = ?", &[primary]).await;
+        merge_books(&config, &pool, primary, &[other], false)
+            .await
+            .unwrap();
+        let isbn: (Option<String>, Option<String>) =
+            sqlx::query_as("SELECT isbn, isbn13 FROM books WHERE id = ?")
+                .bind(primary)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            isbn,
+            (
+                Some("0-306-40615-2".to_string()),
+                Some("9780306406157".to_string())
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_books_deletes_files() {
+        let pool = create_test_db().await;
+        let dir = tempfile::tempdir().unwrap();
+        let config = config(&dir);
+        let storage = config.canonical_storage_path();
+
+        let primary = insert_book(&pool, "It").await;
+        let duplicate = insert_book(&pool, "It (copia)").await;
+        add_file(&pool, duplicate, "it-old.epub", 1, None).await;
+        fs::write(storage.join("it-old.epub"), b"epub").unwrap();
+
+        let result = merge_books(&config, &pool, primary, &[duplicate], true)
+            .await
+            .unwrap();
+
+        assert_eq!((result.moved_files, result.deleted_files), (0, 1));
+        assert!(!storage.join("it-old.epub").exists());
+        assert!(scalar(
+            &pool,
+            "SELECT file_link FROM book_files WHERE book_id = ?",
+            primary
+        )
+        .await
+        .is_empty());
+
+        // Un libro non si unisce a se stesso
+        assert!(merge_books(&config, &pool, primary, &[primary], false)
+            .await
+            .is_err());
+    }
+}
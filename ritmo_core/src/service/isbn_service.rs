@@ -0,0 +1,260 @@
+use ritmo_db_core::isbn::{normalize_display, Isbn};
+use ritmo_errors::RitmoResult;
+use std::collections::BTreeMap;
+
+/// Libro con un ISBN non valido
+#[derive(Debug, Clone)]
+pub struct InvalidIsbn {
+    pub book_id: i64,
+    pub title: String,
+    pub isbn: String,
+    pub reason: String,
+}
+
+/// Libro coinvolto in un ISBN duplicato
+#[derive(Debug, Clone)]
+pub struct IsbnBook {
+    pub book_id: i64,
+    pub title: String,
+    pub isbn: String,
+}
+
+/// Gruppo di libri con lo stesso ISBN (confrontato nella forma ISBN-13)
+#[derive(Debug, Clone)]
+pub struct DuplicateIsbn {
+    pub isbn13: String,
+    pub books: Vec<IsbnBook>,
+}
+
+/// Esito della verifica degli ISBN della libreria
+#[derive(Debug, Clone, Default)]
+pub struct IsbnReport {
+    /// Libri con un ISBN
+    pub checked: usize,
+    pub invalid: Vec<InvalidIsbn>,
+    pub duplicates: Vec<DuplicateIsbn>,
+    /// ID dei libri con ISBN valido ma forma canonica mancante o non aggiornata
+    /// (es. salvati prima di `books.isbn13`); `normalize_library_isbns` li corregge
+    pub not_normalized: Vec<i64>,
+}
+
+impl IsbnReport {
+    pub fn has_problems(&self) -> bool {
+        !self.invalid.is_empty() || !self.duplicates.is_empty()
+    }
+}
+
+struct IsbnRow {
+    id: i64,
+    name: String,
+    isbn: String,
+    isbn13: Option<String>,
+}
+
+async fn books_with_isbn(pool: &sqlx::SqlitePool) -> RitmoResult<Vec<IsbnRow>> {
+    let rows = sqlx::query_as!(
+        IsbnRow,
+        r#"SELECT id as "id!", name, isbn as "isbn!", isbn13
+           FROM books
+           WHERE isbn IS NOT NULL AND TRIM(isbn) <> ''
+           ORDER BY id"#
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Verifica gli ISBN di tutti i libri: checksum, duplicati e forma canonica
+///
+/// Due libri sono duplicati se il loro ISBN coincide nella forma ISBN-13, anche
+/// se uno è salvato come ISBN-10 o con trattini diversi.
+pub async fn check_library_isbns(pool: &sqlx::SqlitePool) -> RitmoResult<IsbnReport> {
+    let rows = books_with_isbn(pool).await?;
+    let mut report = IsbnReport {
+        checked: rows.len(),
+        ..Default::default()
+    };
+    let mut by_isbn13: BTreeMap<String, Vec<IsbnBook>> = BTreeMap::new();
+
+    for row in rows {
+        match Isbn::parse(&row.isbn) {
+            Ok(isbn) => {
+                let isbn13 = isbn.to_isbn13();
+                if row.isbn13.as_deref() != Some(isbn13.as_str())
+                    || normalize_display(&row.isbn) != row.isbn
+                {
+                    report.not_normalized.push(row.id);
+                }
+                by_isbn13.entry(isbn13).or_default().push(IsbnBook {
+                    book_id: row.id,
+                    title: row.name,
+                    isbn: row.isbn,
+                });
+            }
+            Err(e) => report.invalid.push(InvalidIsbn {
+                book_id: row.id,
+                title: row.name,
+                isbn: row.isbn,
+                reason: e.to_string(),
+            }),
+        }
+    }
+
+    report.duplicates = by_isbn13
+        .into_iter()
+        .filter(|(_, books)| books.len() > 1)
+        .map(|(isbn13, books)| DuplicateIsbn { isbn13, books })
+        .collect();
+
+    Ok(report)
+}
+
+/// Normalizza gli ISBN validi: forma di visualizzazione e ISBN-13 canonico
+///
+/// Gli ISBN non validi non vengono toccati. Restituisce il numero di libri aggiornati.
+pub async fn normalize_library_isbns(pool: &sqlx::SqlitePool) -> RitmoResult<usize> {
+    let rows = books_with_isbn(pool).await?;
+    let mut tx = pool.begin().await?;
+    let mut updated = 0;
+
+    for row in rows {
+        let Ok(isbn) = Isbn::parse(&row.isbn) else {
+            continue;
+        };
+        let display = normalize_display(&row.isbn);
+        let isbn13 = isbn.to_isbn13();
+        if display == row.isbn && row.isbn13.as_deref() == Some(isbn13.as_str()) {
+            continue;
+        }
+        sqlx::query!(
+            "UPDATE books SET isbn = ?, isbn13 = ? WHERE id = ?",
+            display,
+            isbn13,
+            row.id
+        )
+        .execute(&mut *tx)
+        .await?;
+        updated += 1;
+    }
+
+    tx.commit().await?;
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{create_test_db, insert_book};
+    use ritmo_db::Book;
+
+    async fn insert_isbn_book(
+        pool: &sqlx::SqlitePool,
+        name: &str,
+        isbn: &str,
+        isbn13: Option<&str>,
+    ) -> i64 {
+        let book_id = insert_book(pool, name).await;
+        sqlx::query("UPDATE books SET isbn = ?, isbn13 = ? WHERE id = ?")
+            .bind(isbn)
+            .bind(isbn13)
+            .bind(book_id)
+            .execute(pool)
+            .await
+            .unwrap();
+        book_id
+    }
+
+    async fn isbn_columns(pool: &sqlx::SqlitePool, book_id: i64) -> (String, Option<String>) {
+        sqlx::query_as("SELECT isbn, isbn13 FROM books WHERE id = ?")
+            .bind(book_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_check_groups_duplicates_by_isbn13() {
+        let pool = create_test_db().await;
+        // Stesso ISBN in forma ISBN-10, ISBN-13 e con un prefisso
+        let a = insert_isbn_book(&pool, "A", "0-306-40615-2", None).await;
+        let b = insert_isbn_book(&pool, "B", "9780306406157", Some("9780306406157")).await;
+        let c = insert_isbn_book(&pool, "C", "ISBN 978 0 306 40615 7", None).await;
+        let other = insert_isbn_book(&pool, "D", "978-88-452-0000-7", None).await;
+        let invalid = insert_isbn_book(&pool, "E", "978-88-452-0000-1", None).await;
+        insert_book(&pool, "Senza ISBN").await;
+
+        let report = check_library_isbns(&pool).await.unwrap();
+
+        assert_eq!(report.checked, 5);
+        assert_eq!(report.duplicates.len(), 1);
+        assert_eq!(report.duplicates[0].isbn13, "9780306406157");
+        let ids: Vec<i64> = report.duplicates[0]
+            .books
+            .iter()
+            .map(|b| b.book_id)
+            .collect();
+        assert_eq!(ids, vec![a, b, c]);
+        assert_eq!(report.invalid.len(), 1);
+        assert_eq!(report.invalid[0].book_id, invalid);
+        assert_eq!(report.not_normalized, vec![a, c, other]);
+        assert!(report.has_problems());
+    }
+
+    #[tokio::test]
+    async fn test_normalize_backfills_isbn13() {
+        let pool = create_test_db().await;
+        let old = insert_isbn_book(&pool, "Vecchio", "isbn 0 306 40615 2", None).await;
+        let done =
+            insert_isbn_book(&pool, "Nuovo", "978-88-452-0000-7", Some("9788845200007")).await;
+        let invalid = insert_isbn_book(&pool, "Errato", "12345", None).await;
+
+        assert_eq!(normalize_library_isbns(&pool).await.unwrap(), 1);
+
+        assert_eq!(
+            isbn_columns(&pool, old).await,
+            (
+                "0-306-40615-2".to_string(),
+                Some("9780306406157".to_string())
+            )
+        );
+        assert_eq!(
+            isbn_columns(&pool, done).await,
+            (
+                "978-88-452-0000-7".to_string(),
+                Some("9788845200007".to_string())
+            )
+        );
+        assert_eq!(
+            isbn_columns(&pool, invalid).await,
+            ("12345".to_string(), None)
+        );
+        assert!(check_library_isbns(&pool)
+            .await
+            .unwrap()
+            .not_normalized
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_by_isbn_matches_either_column() {
+        let pool = create_test_db().await;
+        let book_id = insert_isbn_book(&pool, "A", "0-306-40615-2", Some("9780306406157")).await;
+        let found = |book: Option<Book>| book.and_then(|b| b.id);
+
+        // Valore salvato in `isbn`
+        let book = Book::get_by_isbn(&pool, "0-306-40615-2", None)
+            .await
+            .unwrap();
+        assert_eq!(found(book), Some(book_id));
+        // Forma diversa, stesso ISBN-13
+        let book = Book::get_by_isbn(&pool, "978-0-306-40615-7", Some("9780306406157"))
+            .await
+            .unwrap();
+        assert_eq!(found(book), Some(book_id));
+        // Nessuna corrispondenza
+        let book = Book::get_by_isbn(&pool, "978-88-452-0000-7", Some("9788845200007"))
+            .await
+            .unwrap();
+        assert_eq!(found(book), None);
+    }
+}
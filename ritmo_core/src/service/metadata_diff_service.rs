@@ -0,0 +1,1064 @@
+use crate::dto::ContentInput;
+use crate::epub_opf_modifier::map_ritmo_role_to_opf;
+use crate::epub_utils::extract_opf;
+use crate::extractors::{marc_role_to_ritmo, parse_opf};
+use crate::service::book_import_service::BookImportMetadata;
+use crate::service::metadata_sync_service::{
+    build_book_metadata_from_db, get_book_contents, get_book_files,
+};
+use ritmo_db::{
+    get_sync_snapshot, mark_book_for_sync, save_sync_snapshot, Book, Person, Publisher, Role,
+    Series, Tag,
+};
+use ritmo_db_core::isbn::{canonical_isbn13, normalize_display};
+use ritmo_db_core::vocabulary::lookup_language;
+use ritmo_db_core::LibraryConfig;
+use ritmo_errors::{RitmoErr, RitmoResult};
+use serde::{Deserialize, Serialize};
+use sqlx::{Sqlite, Transaction};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Campi confrontati tra database e OPF, nell'ordine in cui vengono mostrati
+pub const DIFF_FIELDS: &[&str] = &[
+    "title",
+    "publisher",
+    "year",
+    "isbn",
+    "series",
+    "series_index",
+    "people",
+    "tags",
+    "languages",
+];
+
+/// Campi con più valori, per i quali è possibile l'unione
+pub const LIST_FIELDS: &[&str] = &["people", "tags", "languages"];
+
+/// Persona con ruolo, nella forma che sopravvive alla scrittura nell'OPF
+/// (es. `role.writer` diventa `role.author`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PersonRole {
+    pub name: String,
+    pub role: String,
+}
+
+impl std::fmt::Display for PersonRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.name, self.role)
+    }
+}
+
+/// Metadati di un libro confrontabili con l'OPF dei suoi file EPUB
+///
+/// Viene salvato in `metadata_sync_snapshots` a ogni sincronizzazione e fa da
+/// base comune per il confronto a tre vie.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetadataSnapshot {
+    pub title: Option<String>,
+    pub publisher: Option<String>,
+    pub year: Option<i32>,
+    pub isbn: Option<String>,
+    pub series: Option<String>,
+    pub series_index: Option<i64>,
+    #[serde(default)]
+    pub people: Vec<PersonRole>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Codici ISO 639-1
+    #[serde(default)]
+    pub languages: Vec<String>,
+}
+
+impl MetadataSnapshot {
+    /// Metadati del database, come verrebbero scritti nell'OPF
+    pub fn from_metadata(metadata: &BookImportMetadata, contents: &[ContentInput]) -> Self {
+        let mut snapshot = Self {
+            title: non_empty(Some(&metadata.title)),
+            publisher: non_empty(metadata.publisher.as_ref()),
+            year: metadata.year,
+            isbn: non_empty(metadata.isbn.as_ref()),
+            series: non_empty(metadata.series.as_ref()),
+            series_index: metadata.series_index,
+            ..Default::default()
+        };
+        for (name, role) in metadata.people.iter().flatten() {
+            snapshot.add_person(name, role);
+        }
+        for content in contents {
+            for person in &content.people {
+                snapshot.add_person(&person.name, &person.role);
+            }
+            for language in &content.languages {
+                push_unique(&mut snapshot.languages, &language.code);
+            }
+        }
+        for tag in metadata.tags.iter().flatten() {
+            push_unique(&mut snapshot.tags, tag);
+        }
+        snapshot
+    }
+
+    /// Metadati letti dall'OPF di un file EPUB
+    pub fn from_epub(path: &std::path::Path) -> RitmoResult<Self> {
+        let meta = parse_opf(&extract_opf(path)?)?;
+        let mut snapshot = Self {
+            title: non_empty(meta.title.as_ref()),
+            publisher: non_empty(meta.publisher.as_ref()),
+            year: meta.year,
+            isbn: non_empty(meta.isbn.as_ref()),
+            series: non_empty(meta.series.as_ref()),
+            series_index: meta.series_index,
+            ..Default::default()
+        };
+        for person in meta.book_people.iter().chain(&meta.people) {
+            snapshot.add_person(&person.name, &person.role);
+        }
+        for language in &meta.languages {
+            push_unique(&mut snapshot.languages, &language.code);
+        }
+        for tag in &meta.tags {
+            push_unique(&mut snapshot.tags, tag);
+        }
+        Ok(snapshot)
+    }
+
+    fn add_person(&mut self, name: &str, role: &str) {
+        let person = PersonRole {
+            name: name.trim().to_string(),
+            role: marc_role_to_ritmo(map_ritmo_role_to_opf(role)).to_string(),
+        };
+        if !person.name.is_empty()
+            && !self
+                .people
+                .iter()
+                .any(|p| person_key(p) == person_key(&person))
+        {
+            self.people.push(person);
+        }
+    }
+
+    /// Valore di un campo, per il confronto e la visualizzazione
+    pub fn field(&self, field: &str) -> FieldValue {
+        match field {
+            "title" => FieldValue::Single(self.title.clone()),
+            "publisher" => FieldValue::Single(self.publisher.clone()),
+            "year" => FieldValue::Single(self.year.map(|y| y.to_string())),
+            "isbn" => FieldValue::Single(self.isbn.clone()),
+            "series" => FieldValue::Single(self.series.clone()),
+            "series_index" => FieldValue::Single(self.series_index.map(|i| i.to_string())),
+            "people" => FieldValue::List(self.people.iter().map(|p| p.to_string()).collect()),
+            "tags" => FieldValue::List(self.tags.clone()),
+            "languages" => FieldValue::List(self.languages.clone()),
+            _ => FieldValue::Single(None),
+        }
+    }
+
+    /// True se il campo ha lo stesso valore nei due snapshot
+    ///
+    /// Gli ISBN si confrontano nella forma ISBN-13, le liste senza badare
+    /// all'ordine, tag e persone senza badare alle maiuscole.
+    pub fn same_field(&self, other: &Self, field: &str) -> bool {
+        match field {
+            "isbn" => match (&self.isbn, &other.isbn) {
+                (Some(a), Some(b)) => {
+                    a == b || canonical_isbn13(a).is_some_and(|a| canonical_isbn13(b) == Some(a))
+                }
+                (a, b) => a == b,
+            },
+            "people" => same_set(&self.people, &other.people, person_key),
+            "tags" => same_set(&self.tags, &other.tags, |t| t.to_lowercase()),
+            "languages" => same_set(&self.languages, &other.languages, |l| l.to_lowercase()),
+            _ => self.field(field) == other.field(field),
+        }
+    }
+
+    /// Copia un campo da un altro snapshot
+    fn take_field(&mut self, other: &Self, field: &str) {
+        match field {
+            "title" => self.title = other.title.clone(),
+            "publisher" => self.publisher = other.publisher.clone(),
+            "year" => self.year = other.year,
+            "isbn" => self.isbn = other.isbn.clone(),
+            "series" => self.series = other.series.clone(),
+            "series_index" => self.series_index = other.series_index,
+            "people" => self.people = other.people.clone(),
+            "tags" => self.tags = other.tags.clone(),
+            "languages" => self.languages = other.languages.clone(),
+            _ => {}
+        }
+    }
+
+    /// Unisce un campo lista con quello del file (a tre vie se c'è la base)
+    fn merge_field(&mut self, file: &Self, base: Option<&Self>, field: &str) {
+        match field {
+            "people" => {
+                self.people = merge_lists(
+                    &self.people,
+                    &file.people,
+                    base.map(|b| b.people.as_slice()),
+                    person_key,
+                )
+            }
+            "tags" => {
+                self.tags = merge_lists(
+                    &self.tags,
+                    &file.tags,
+                    base.map(|b| b.tags.as_slice()),
+                    |t| t.to_lowercase(),
+                )
+            }
+            "languages" => {
+                self.languages = merge_lists(
+                    &self.languages,
+                    &file.languages,
+                    base.map(|b| b.languages.as_slice()),
+                    |l| l.to_lowercase(),
+                )
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Valore di un campo: singolo o lista
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum FieldValue {
+    Single(Option<String>),
+    List(Vec<String>),
+}
+
+impl std::fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Single(Some(value)) => write!(f, "{}", value),
+            Self::List(values) if !values.is_empty() => write!(f, "{}", values.join(", ")),
+            _ => write!(f, "(vuoto)"),
+        }
+    }
+}
+
+/// Lato modificato rispetto all'ultima sincronizzazione
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeOrigin {
+    /// Modificato solo nel file (es. EPUB modificato con un altro programma)
+    File,
+    /// Modificato solo nel database (sincronizzazione in sospeso)
+    Database,
+    /// Modificato da entrambe le parti
+    Both,
+    /// Nessuno snapshot: il libro non è mai stato sincronizzato
+    Unknown,
+}
+
+/// Scelta per un campo in `metadata pull`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resolution {
+    KeepDb,
+    TakeFile,
+    /// Unione delle liste (persone, tag, lingue)
+    Merge,
+}
+
+/// Differenza su un campo tra database e file
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldDiff {
+    pub field: &'static str,
+    pub db: FieldValue,
+    pub file: FieldValue,
+    /// Valore all'ultima sincronizzazione
+    pub base: Option<FieldValue>,
+    pub origin: ChangeOrigin,
+    /// Scelta proposta dal confronto a tre vie; None per i conflitti su campi singoli
+    pub suggested: Option<Resolution>,
+}
+
+/// Confronto dei metadati di un libro con l'OPF del suo file EPUB
+#[derive(Debug, Clone, Serialize)]
+pub struct BookMetadataDiff {
+    pub book_id: i64,
+    pub title: String,
+    pub file_path: PathBuf,
+    /// Data dell'ultima sincronizzazione (base del confronto a tre vie)
+    pub synced_at: Option<i64>,
+    pub fields: Vec<FieldDiff>,
+    #[serde(skip)]
+    db: MetadataSnapshot,
+    #[serde(skip)]
+    file: MetadataSnapshot,
+    #[serde(skip)]
+    base: Option<MetadataSnapshot>,
+}
+
+impl BookMetadataDiff {
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn field(&self, field: &str) -> Option<&FieldDiff> {
+        self.fields.iter().find(|f| f.field == field)
+    }
+}
+
+/// Esito di `pull_book_metadata`
+#[derive(Debug, Clone, Default)]
+pub struct MetadataPullResult {
+    /// Campi aggiornati nel database con i valori del file (o con l'unione)
+    pub updated: Vec<&'static str>,
+    /// Campi rimasti con il valore del database
+    pub kept: Vec<&'static str>,
+    /// True se il libro è stato segnato per riscrivere il file con i valori del database
+    pub marked_for_sync: bool,
+    /// Scelte che non è stato possibile applicare
+    pub warnings: Vec<String>,
+}
+
+/// Confronta i metadati di un libro con l'OPF del suo (primo) file EPUB
+///
+/// Se il libro è già stato sincronizzato, ogni differenza viene attribuita al
+/// lato che si è allontanato dallo snapshot di quella sincronizzazione.
+pub async fn diff_book_metadata(
+    config: &LibraryConfig,
+    pool: &sqlx::SqlitePool,
+    book_id: i64,
+) -> RitmoResult<BookMetadataDiff> {
+    let book = Book::get(pool, book_id)
+        .await?
+        .ok_or_else(|| RitmoErr::Generic(format!("Libro con ID {} non trovato", book_id)))?;
+    let file = get_book_files(pool, &book)
+        .await?
+        .into_iter()
+        .find(|f| f.file_link.to_lowercase().ends_with(".epub"))
+        .ok_or_else(|| RitmoErr::Generic(format!("Il libro {} non ha file EPUB", book_id)))?;
+    let file_path = config.canonical_storage_path().join(&file.file_link);
+    if !file_path.exists() {
+        return Err(RitmoErr::Generic(format!(
+            "File EPUB non trovato: {}",
+            file_path.display()
+        )));
+    }
+
+    let db = db_snapshot(pool, &book).await?;
+    let file_snapshot = MetadataSnapshot::from_epub(&file_path)?;
+    let stored = get_sync_snapshot(pool, book_id).await?;
+    let base = match &stored {
+        Some(stored) => Some(serde_json::from_str::<MetadataSnapshot>(&stored.snapshot)?),
+        None => None,
+    };
+
+    let fields = diff_fields(&db, &file_snapshot, base.as_ref());
+
+    Ok(BookMetadataDiff {
+        book_id,
+        title: book.name,
+        file_path,
+        synced_at: stored.map(|s| s.synced_at),
+        fields,
+        db,
+        file: file_snapshot,
+        base,
+    })
+}
+
+/// Campi diversi tra database e file, ciascuno attribuito al lato che si è
+/// allontanato dalla base (se c'è) con la scelta proposta
+fn diff_fields(
+    db: &MetadataSnapshot,
+    file: &MetadataSnapshot,
+    base: Option<&MetadataSnapshot>,
+) -> Vec<FieldDiff> {
+    let mut fields = Vec::new();
+    for &field in DIFF_FIELDS {
+        if db.same_field(file, field) {
+            continue;
+        }
+        // Lingue assenti nel database: la sincronizzazione non tocca quelle del file
+        if field == "languages"
+            && db.languages.is_empty()
+            && base.is_some_and(|b| file.same_field(b, field))
+        {
+            continue;
+        }
+        let origin = match base {
+            None => ChangeOrigin::Unknown,
+            Some(base) if db.same_field(base, field) => ChangeOrigin::File,
+            Some(base) if file.same_field(base, field) => ChangeOrigin::Database,
+            Some(_) => ChangeOrigin::Both,
+        };
+        let suggested = match origin {
+            ChangeOrigin::File => Some(Resolution::TakeFile),
+            ChangeOrigin::Database => Some(Resolution::KeepDb),
+            _ if LIST_FIELDS.contains(&field) => Some(Resolution::Merge),
+            _ => None,
+        };
+        fields.push(FieldDiff {
+            field,
+            db: db.field(field),
+            file: file.field(field),
+            base: base.map(|b| b.field(field)),
+            origin,
+            suggested,
+        });
+    }
+    fields
+}
+
+/// ID dei libri con almeno un file EPUB, per `metadata diff --all`
+pub async fn books_with_epub(pool: &sqlx::SqlitePool) -> RitmoResult<Vec<i64>> {
+    let ids = sqlx::query_scalar!(
+        r#"SELECT id as "id!" FROM books
+           WHERE LOWER(file_link) LIKE '%.epub'
+              OR id IN (SELECT book_id FROM book_files WHERE LOWER(file_link) LIKE '%.epub')
+           ORDER BY id"#
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(ids)
+}
+
+/// Applica al database le scelte per i campi diversi
+///
+/// `choices` indica la scelta per campo; i campi senza scelta usano quella
+/// proposta dal confronto a tre vie e, se non c'è (conflitto su un campo
+/// singolo), mantengono il valore del database. Se alla fine database e file
+/// coincidono lo snapshot viene aggiornato; altrimenti il libro viene segnato
+/// per la sincronizzazione, che riscriverà il file con i valori del database.
+pub async fn pull_book_metadata(
+    pool: &sqlx::SqlitePool,
+    diff: &BookMetadataDiff,
+    choices: &HashMap<String, Resolution>,
+) -> RitmoResult<MetadataPullResult> {
+    let mut target = diff.db.clone();
+    let mut result = MetadataPullResult::default();
+    let has_contents = sqlx::query_scalar!(
+        "SELECT COUNT(*) FROM x_books_contents WHERE book_id = ?",
+        diff.book_id
+    )
+    .fetch_one(pool)
+    .await?
+        > 0;
+
+    for field_diff in &diff.fields {
+        let field = field_diff.field;
+        let resolution = choices
+            .get(field)
+            .copied()
+            .or(field_diff.suggested)
+            .unwrap_or(Resolution::KeepDb);
+        match resolution {
+            Resolution::KeepDb => {
+                result.kept.push(field);
+                continue;
+            }
+            Resolution::TakeFile => target.take_field(&diff.file, field),
+            Resolution::Merge => {
+                if !LIST_FIELDS.contains(&field) {
+                    return Err(RitmoErr::Generic(format!(
+                        "'{}' non è una lista: scegli db o file",
+                        field
+                    )));
+                }
+                target.merge_field(&diff.file, diff.base.as_ref(), field);
+            }
+        }
+        if field == "languages" && !diff.db.same_field(&target, field) && !has_contents {
+            // Le lingue sono delle opere: senza opere non c'è dove salvarle
+            target.languages = diff.db.languages.clone();
+            result.warnings.push(
+                "languages: il libro non ha opere collegate, lingue non aggiornate".to_string(),
+            );
+        }
+        if diff.db.same_field(&target, field) {
+            result.kept.push(field);
+        } else {
+            result.updated.push(field);
+        }
+    }
+
+    if !result.updated.is_empty() {
+        apply_snapshot(pool, diff.book_id, &diff.db, &target).await?;
+    }
+
+    let in_sync = DIFF_FIELDS
+        .iter()
+        .all(|field| target.same_field(&diff.file, field));
+    if in_sync {
+        save_sync_snapshot(pool, diff.book_id, &serde_json::to_string(&target)?).await?;
+    } else {
+        mark_book_for_sync(pool, diff.book_id, "metadata_pull").await?;
+        result.marked_for_sync = true;
+    }
+
+    Ok(result)
+}
+
+/// Salva lo snapshot dei metadati scritti nei file (chiamata a fine sincronizzazione)
+///
+/// Senza lingue nel database la sincronizzazione lascia quelle dell'EPUB, che
+/// entrano quindi nello snapshot.
+pub(crate) async fn save_synced_snapshot(
+    pool: &sqlx::SqlitePool,
+    book_id: i64,
+    metadata: &BookImportMetadata,
+    contents: &[ContentInput],
+    epub: Option<&std::path::Path>,
+) -> RitmoResult<()> {
+    let mut snapshot = MetadataSnapshot::from_metadata(metadata, contents);
+    if snapshot.languages.is_empty() {
+        if let Some(written) = epub.and_then(|path| MetadataSnapshot::from_epub(path).ok()) {
+            snapshot.languages = written.languages;
+        }
+    }
+    save_sync_snapshot(pool, book_id, &serde_json::to_string(&snapshot)?).await
+}
+
+async fn db_snapshot(pool: &sqlx::SqlitePool, book: &Book) -> RitmoResult<MetadataSnapshot> {
+    let metadata = build_book_metadata_from_db(pool, book).await?;
+    let contents = get_book_contents(pool, book.id.unwrap_or(0)).await?;
+    Ok(MetadataSnapshot::from_metadata(&metadata, &contents))
+}
+
+/// Scrive nel database i campi di `target` diversi da `current`
+///
+/// Editori, serie, tag, persone e ruoli nuovi sono creati prima della transazione.
+async fn apply_snapshot(
+    pool: &sqlx::SqlitePool,
+    book_id: i64,
+    current: &MetadataSnapshot,
+    target: &MetadataSnapshot,
+) -> RitmoResult<()> {
+    let publisher_id = match &target.publisher {
+        Some(name) => Some(Publisher::get_or_create_by_name(pool, name).await?),
+        None => None,
+    };
+    let series_id = match &target.series {
+        Some(name) => Some(Series::get_or_create_by_name(pool, name).await?),
+        None => None,
+    };
+    let mut added_tags = Vec::new();
+    for tag in removed(&target.tags, &current.tags, |t| t.to_lowercase()) {
+        added_tags.push(Tag::get_or_create_by_name(pool, tag).await?);
+    }
+    let mut added_people = Vec::new();
+    for person in removed(&target.people, &current.people, person_key) {
+        let person_id = Person::get_or_create_by_name(pool, &person.name).await?;
+        let role_id = Role::get_or_create_by_key(pool, &person.role).await?;
+        added_people.push((person, person_id, role_id));
+    }
+    let publication_date = target.year.and_then(|year| {
+        chrono::NaiveDate::from_ymd_opt(year, 1, 1)
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map(|dt| dt.and_utc().timestamp())
+    });
+    // ISBN valido: forma di visualizzazione normalizzata più ISBN-13 canonico
+    let isbn13 = target.isbn.as_deref().and_then(canonical_isbn13);
+    let isbn = target.isbn.clone().map(|value| {
+        if isbn13.is_some() {
+            normalize_display(&value)
+        } else {
+            value
+        }
+    });
+    let title = target.title.clone().unwrap_or_default();
+    let now = chrono::Utc::now().timestamp();
+
+    let mut transaction = pool.begin().await?;
+    let tx = &mut transaction;
+
+    sqlx::query!(
+        "UPDATE books SET name = ?, publisher_id = ?, series_id = ?, series_index = ?,
+                publication_date = ?, isbn = ?, isbn13 = ?, last_modified_date = ?
+         WHERE id = ?",
+        title,
+        publisher_id,
+        series_id,
+        target.series_index,
+        publication_date,
+        isbn,
+        isbn13,
+        now,
+        book_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    let content_ids = sqlx::query_scalar!(
+        "SELECT content_id FROM x_books_contents WHERE book_id = ? ORDER BY content_id",
+        book_id
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    // Tag del libro
+    for tag in removed(&current.tags, &target.tags, |t| t.to_lowercase()) {
+        sqlx::query!(
+            "DELETE FROM x_books_tags WHERE book_id = ?
+             AND tag_id IN (SELECT id FROM tags WHERE name = ? COLLATE NOCASE)",
+            book_id,
+            tag
+        )
+        .execute(&mut **tx)
+        .await?;
+    }
+    for tag_id in added_tags {
+        sqlx::query!(
+            "INSERT OR IGNORE INTO x_books_tags (book_id, tag_id) VALUES (?, ?)",
+            book_id,
+            tag_id
+        )
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    // Persone: tolte dal libro e dalle sue opere; le nuove vanno all'opera se è
+    // una sola, altrimenti al libro (curatori e prefatori sempre al libro)
+    for person in removed(&current.people, &target.people, person_key) {
+        remove_person(tx, book_id, &content_ids, person).await?;
+    }
+    for (person, person_id, role_id) in added_people {
+        let book_level = matches!(person.role.as_str(), "role.editor" | "role.preface");
+        match content_ids.as_slice() {
+            [content_id] if !book_level => {
+                sqlx::query!(
+                    "INSERT OR IGNORE INTO x_contents_people_roles (content_id, person_id, role_id)
+                     VALUES (?, ?, ?)",
+                    content_id,
+                    person_id,
+                    role_id
+                )
+                .execute(&mut **tx)
+                .await?;
+            }
+            _ => {
+                sqlx::query!(
+                    "INSERT OR IGNORE INTO x_books_people_roles (book_id, person_id, role_id)
+                     VALUES (?, ?, ?)",
+                    book_id,
+                    person_id,
+                    role_id
+                )
+                .execute(&mut **tx)
+                .await?;
+            }
+        }
+    }
+
+    // Lingue delle opere del libro
+    for code in removed(&current.languages, &target.languages, |l| l.to_lowercase()) {
+        for content_id in &content_ids {
+            sqlx::query!(
+                "DELETE FROM x_contents_languages WHERE content_id = ?
+                 AND language_id IN (SELECT id FROM running_languages WHERE iso_code_2char = ? COLLATE NOCASE)",
+                content_id,
+                code
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+    }
+    for code in removed(&target.languages, &current.languages, |l| l.to_lowercase()) {
+        let language_id = language_id(tx, code).await?;
+        for content_id in &content_ids {
+            sqlx::query!(
+                "INSERT OR IGNORE INTO x_contents_languages (content_id, language_id) VALUES (?, ?)",
+                content_id,
+                language_id
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+    }
+
+    transaction.commit().await?;
+    Ok(())
+}
+
+/// Toglie una persona dal libro e dalle sue opere, per ogni ruolo del database
+/// che nell'OPF diventa il ruolo indicato
+async fn remove_person(
+    tx: &mut Transaction<'_, Sqlite>,
+    book_id: i64,
+    content_ids: &[i64],
+    person: &PersonRole,
+) -> RitmoResult<()> {
+    let roles: Vec<String> = sqlx::query_scalar!("SELECT key FROM roles")
+        .fetch_all(&mut **tx)
+        .await?
+        .into_iter()
+        .filter(|key| marc_role_to_ritmo(map_ritmo_role_to_opf(key)) == person.role)
+        .collect();
+
+    for role in &roles {
+        sqlx::query!(
+            "DELETE FROM x_books_people_roles WHERE book_id = ?
+             AND person_id IN (SELECT id FROM people WHERE name = ? COLLATE NOCASE)
+             AND role_id IN (SELECT id FROM roles WHERE key = ?)",
+            book_id,
+            person.name,
+            role
+        )
+        .execute(&mut **tx)
+        .await?;
+        for content_id in content_ids {
+            sqlx::query!(
+                "DELETE FROM x_contents_people_roles WHERE content_id = ?
+                 AND person_id IN (SELECT id FROM people WHERE name = ? COLLATE NOCASE)
+                 AND role_id IN (SELECT id FROM roles WHERE key = ?)",
+                content_id,
+                person.name,
+                role
+            )
+            .execute(&mut **tx)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Lingua con ruolo `language_role.actual`, creata nella forma canonica
+/// del vocabolario ISO 639 se manca
+async fn language_id(tx: &mut Transaction<'_, Sqlite>, code: &str) -> RitmoResult<i64> {
+    let iso = lookup_language(code);
+    let code = iso.map(|l| l.code()).unwrap_or(code);
+    if let Some(id) = sqlx::query_scalar!(
+        "SELECT id FROM running_languages
+         WHERE iso_code_2char = ? COLLATE NOCASE AND language_role = 'language_role.actual'
+         ORDER BY id LIMIT 1",
+        code
+    )
+    .fetch_optional(&mut **tx)
+    .await?
+    .flatten()
+    {
+        return Ok(id);
+    }
+    let iso3 = iso.map(|l| l.iso639_3).unwrap_or_default();
+    let name = iso.map(|l| l.name_en.to_string()).unwrap_or_else(|| code.to_uppercase());
+    let result = sqlx::query!(
+        "INSERT INTO running_languages (iso_code_2char, iso_code_3char, official_name, language_role)
+         VALUES (?, ?, ?, 'language_role.actual')",
+        code,
+        iso3,
+        name
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(result.last_insert_rowid())
+}
+
+fn person_key(person: &PersonRole) -> String {
+    format!("{}|{}", person.name.to_lowercase(), person.role)
+}
+
+fn non_empty(value: Option<&String>) -> Option<String> {
+    value
+        .map(|v| v.trim())
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
+fn push_unique(values: &mut Vec<String>, value: &str) {
+    let value = value.trim();
+    if !value.is_empty() && !values.iter().any(|v| v.eq_ignore_ascii_case(value)) {
+        values.push(value.to_string());
+    }
+}
+
+fn same_set<T>(a: &[T], b: &[T], key: impl Fn(&T) -> String) -> bool {
+    let mut a: Vec<String> = a.iter().map(&key).collect();
+    let mut b: Vec<String> = b.iter().map(&key).collect();
+    a.sort();
+    a.dedup();
+    b.sort();
+    b.dedup();
+    a == b
+}
+
+/// Elementi di `from` assenti in `other`
+fn removed<'a, T>(from: &'a [T], other: &[T], key: impl Fn(&T) -> String) -> Vec<&'a T> {
+    from.iter()
+        .filter(|item| !other.iter().any(|o| key(o) == key(item)))
+        .collect()
+}
+
+/// Unione di due liste
+///
+/// Con la base dell'ultima sincronizzazione l'unione è a tre vie: un elemento
+/// tolto da una sola delle due parti resta tolto, uno aggiunto da una sola
+/// parte viene mantenuto. Senza base è l'unione semplice.
+fn merge_lists<T: Clone>(
+    db: &[T],
+    file: &[T],
+    base: Option<&[T]>,
+    key: impl Fn(&T) -> String,
+) -> Vec<T> {
+    let contains = |list: &[T], item: &T| list.iter().any(|i| key(i) == key(item));
+    let in_base = |item: &T| base.is_some_and(|b| contains(b, item));
+
+    // Dal database: tutto tranne ciò che il file ha tolto rispetto alla base
+    let mut merged: Vec<T> = db
+        .iter()
+        .filter(|item| contains(file, item) || !in_base(item))
+        .cloned()
+        .collect();
+    // Dal file: ciò che ha aggiunto (non ciò che il database ha tolto)
+    for item in file {
+        if !contains(&merged, item) && !in_base(item) {
+            merged.push(item.clone());
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{create_test_db, insert_book, tag_book};
+
+    fn snapshot(title: &str, publisher: Option<&str>, tags: &[&str]) -> MetadataSnapshot {
+        MetadataSnapshot {
+            title: Some(title.to_string()),
+            publisher: publisher.map(str::to_string),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Confronto come quello di `diff_book_metadata`, senza leggere l'EPUB
+    fn book_diff(
+        book_id: i64,
+        db: MetadataSnapshot,
+        file: MetadataSnapshot,
+        base: Option<MetadataSnapshot>,
+    ) -> BookMetadataDiff {
+        BookMetadataDiff {
+            book_id,
+            title: db.title.clone().unwrap_or_default(),
+            file_path: PathBuf::from("book.epub"),
+            synced_at: base.as_ref().map(|_| 0),
+            fields: diff_fields(&db, &file, base.as_ref()),
+            db,
+            file,
+            base,
+        }
+    }
+
+    async fn book_row(pool: &sqlx::SqlitePool, book_id: i64) -> (String, Option<String>) {
+        sqlx::query_as(
+            "SELECT books.name, publishers.name FROM books
+             LEFT JOIN publishers ON books.publisher_id = publishers.id
+             WHERE books.id = ?",
+        )
+        .bind(book_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn book_tags(pool: &sqlx::SqlitePool, book_id: i64) -> Vec<String> {
+        sqlx::query_scalar(
+            "SELECT tags.name FROM x_books_tags JOIN tags ON tags.id = x_books_tags.tag_id
+             WHERE book_id = ? ORDER BY tags.name",
+        )
+        .bind(book_id)
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn pending_sync(pool: &sqlx::SqlitePool, book_id: i64) -> bool {
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM pending_metadata_sync WHERE book_id = ?")
+                .bind(book_id)
+                .fetch_one(pool)
+                .await
+                .unwrap();
+        count > 0
+    }
+
+    #[test]
+    fn test_same_field() {
+        let mut a = snapshot("It", None, &["Horror", "classic"]);
+        let mut b = snapshot("It", None, &["CLASSIC", "horror"]);
+        assert!(a.same_field(&b, "tags"));
+
+        a.isbn = Some("0-306-40615-2".to_string());
+        b.isbn = Some("978-0-306-40615-7".to_string());
+        assert!(a.same_field(&b, "isbn"));
+
+        b.title = Some("It (ed. 2)".to_string());
+        assert!(!a.same_field(&b, "title"));
+    }
+
+    #[test]
+    fn test_diff_fields_origin_from_snapshot() {
+        let base = snapshot("It", Some("Viking"), &["horror"]);
+        // Titolo cambiato nel file, editore nel database, tag da entrambe le parti
+        let db = snapshot("It", Some("Sperling"), &["horror", "classic"]);
+        let file = snapshot("IT", Some("Viking"), &["horror", "clown"]);
+
+        let fields = diff_fields(&db, &file, Some(&base));
+        let field = |name: &str| fields.iter().find(|f| f.field == name).unwrap();
+        assert_eq!(fields.len(), 3);
+        assert_eq!(field("title").origin, ChangeOrigin::File);
+        assert_eq!(field("title").suggested, Some(Resolution::TakeFile));
+        assert_eq!(field("publisher").origin, ChangeOrigin::Database);
+        assert_eq!(field("publisher").suggested, Some(Resolution::KeepDb));
+        assert_eq!(field("tags").origin, ChangeOrigin::Both);
+        assert_eq!(field("tags").suggested, Some(Resolution::Merge));
+
+        // Senza snapshot non si sa chi ha cambiato cosa: nessuna proposta per
+        // i campi singoli
+        let fields = diff_fields(&db, &file, None);
+        assert!(fields.iter().all(|f| f.origin == ChangeOrigin::Unknown));
+        assert_eq!(fields[0].suggested, None);
+
+        // Conflitto su un campo singolo modificato da entrambe le parti
+        let file = snapshot("It", Some("Bompiani"), &["horror"]);
+        let fields = diff_fields(&db, &file, Some(&base));
+        let publisher = fields.iter().find(|f| f.field == "publisher").unwrap();
+        assert_eq!(publisher.origin, ChangeOrigin::Both);
+        assert_eq!(publisher.suggested, None);
+    }
+
+    #[test]
+    fn test_merge_lists() {
+        let list = |items: &[&str]| items.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+        let key = |t: &String| t.to_lowercase();
+        let db = list(&["horror", "classic", "to-read"]);
+        let file = list(&["Horror", "to-read", "clown"]);
+
+        // A tre vie: "classic" tolto dal file resta tolto, "clown" aggiunto
+        // dal file entra
+        let base = list(&["horror", "classic"]);
+        assert_eq!(
+            merge_lists(&db, &file, Some(&base), key),
+            list(&["horror", "to-read", "clown"])
+        );
+        // Senza base è l'unione semplice
+        assert_eq!(
+            merge_lists(&db, &file, None, key),
+            list(&["horror", "classic", "to-read", "clown"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pull_keep_db_marks_for_sync() {
+        let pool = create_test_db().await;
+        let book_id = insert_book(&pool, "It").await;
+        let diff = book_diff(
+            book_id,
+            snapshot("It", None, &[]),
+            snapshot("IT", None, &[]),
+            None,
+        );
+
+        let choices = HashMap::from([("title".to_string(), Resolution::KeepDb)]);
+        let result = pull_book_metadata(&pool, &diff, &choices).await.unwrap();
+
+        assert_eq!(result.kept, vec!["title"]);
+        assert!(result.updated.is_empty());
+        assert!(result.marked_for_sync);
+        assert!(pending_sync(&pool, book_id).await);
+        assert_eq!(book_row(&pool, book_id).await.0, "It");
+    }
+
+    #[tokio::test]
+    async fn test_pull_take_file_saves_snapshot() {
+        let pool = create_test_db().await;
+        let book_id = insert_book(&pool, "It").await;
+        let file = snapshot("IT", Some("Viking"), &[]);
+        let diff = book_diff(book_id, snapshot("It", None, &[]), file.clone(), None);
+
+        let choices = HashMap::from([
+            ("title".to_string(), Resolution::TakeFile),
+            ("publisher".to_string(), Resolution::TakeFile),
+        ]);
+        let result = pull_book_metadata(&pool, &diff, &choices).await.unwrap();
+
+        assert_eq!(result.updated, vec!["title", "publisher"]);
+        assert!(!result.marked_for_sync);
+        assert_eq!(
+            book_row(&pool, book_id).await,
+            ("IT".to_string(), Some("Viking".to_string()))
+        );
+        // Database e file coincidono: il file diventa la nuova base
+        let stored = get_sync_snapshot(&pool, book_id).await.unwrap().unwrap();
+        let stored: MetadataSnapshot = serde_json::from_str(&stored.snapshot).unwrap();
+        assert_eq!(stored, file);
+        assert!(!pending_sync(&pool, book_id).await);
+    }
+
+    #[tokio::test]
+    async fn test_pull_merges_list_field() {
+        let pool = create_test_db().await;
+        let book_id = insert_book(&pool, "It").await;
+        tag_book(&pool, book_id, "horror").await;
+        tag_book(&pool, book_id, "classic").await;
+        let diff = book_diff(
+            book_id,
+            snapshot("It", None, &["horror", "classic"]),
+            snapshot("It", None, &["horror", "clown"]),
+            Some(snapshot("It", None, &["horror"])),
+        );
+
+        let choices = HashMap::from([("tags".to_string(), Resolution::Merge)]);
+        let result = pull_book_metadata(&pool, &diff, &choices).await.unwrap();
+
+        assert_eq!(result.updated, vec!["tags"]);
+        assert_eq!(
+            book_tags(&pool, book_id).await,
+            vec!["classic", "clown", "horror"]
+        );
+        // "classic" manca ancora nel file: va riscritto
+        assert!(result.marked_for_sync);
+
+        // L'unione non ha senso per un campo singolo
+        let diff = book_diff(
+            book_id,
+            snapshot("It", None, &[]),
+            snapshot("IT", None, &[]),
+            None,
+        );
+        let choices = HashMap::from([("title".to_string(), Resolution::Merge)]);
+        assert!(pull_book_metadata(&pool, &diff, &choices).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pull_uses_snapshot_suggestions() {
+        let pool = create_test_db().await;
+        let book_id = insert_book(&pool, "It").await;
+        sqlx::query("INSERT INTO publishers (name) VALUES ('Sperling')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE books SET publisher_id = 1 WHERE id = ?")
+            .bind(book_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        // Il file ha cambiato il titolo, il database l'editore
+        let diff = book_diff(
+            book_id,
+            snapshot("It", Some("Sperling"), &[]),
+            snapshot("IT", Some("Viking"), &[]),
+            Some(snapshot("It", Some("Viking"), &[])),
+        );
+
+        let result = pull_book_metadata(&pool, &diff, &HashMap::new())
+            .await
+            .unwrap();
+
+        assert_eq!(result.updated, vec!["title"]);
+        assert_eq!(result.kept, vec!["publisher"]);
+        assert_eq!(
+            book_row(&pool, book_id).await,
+            ("IT".to_string(), Some("Sperling".to_string()))
+        );
+        // L'editore del database va ancora scritto nel file
+        assert!(result.marked_for_sync);
+    }
+}
@@ -0,0 +1,124 @@
+use crate::dto::ImportObject;
+use crate::providers::{
+    merge_records, MetadataProvider, ProviderMatch, ProviderQuery, ProviderRecord,
+};
+use ritmo_db::{get_provider_cache, put_provider_cache};
+use ritmo_errors::{RitmoErr, RitmoResult};
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+/// Opzioni della ricerca online
+#[derive(Debug, Clone)]
+pub struct LookupOptions {
+    /// Età massima delle risposte in cache, in giorni
+    pub max_age_days: i64,
+    /// Ignora la cache e interroga di nuovo i fornitori
+    pub refresh: bool,
+}
+
+impl Default for LookupOptions {
+    fn default() -> Self {
+        Self {
+            max_age_days: 30,
+            refresh: false,
+        }
+    }
+}
+
+/// Esito di una ricerca presso più fornitori
+#[derive(Debug, Clone, Default)]
+pub struct MetadataLookup {
+    /// Risultati trovati, nell'ordine dei fornitori
+    pub matches: Vec<ProviderMatch>,
+    /// Fornitori che hanno restituito un errore (nome, messaggio)
+    pub failures: Vec<(String, String)>,
+    /// Fornitori la cui risposta viene dalla cache
+    pub from_cache: Vec<String>,
+}
+
+/// Interroga i fornitori, usando la cache del database
+///
+/// Anche le ricerche senza risultato sono in cache, così un libro sconosciuto
+/// non viene richiesto di nuovo a ogni esecuzione. Gli errori di rete non sono
+/// in cache e non interrompono la ricerca presso gli altri fornitori.
+pub async fn lookup_metadata(
+    pool: &SqlitePool,
+    providers: &[Arc<dyn MetadataProvider>],
+    query: &ProviderQuery,
+    options: &LookupOptions,
+) -> RitmoResult<MetadataLookup> {
+    let key = query.cache_key();
+    let min_fetched_at = chrono::Utc::now().timestamp() - options.max_age_days * 86_400;
+    let mut lookup = MetadataLookup::default();
+
+    for provider in providers {
+        let name = provider.name();
+
+        let cached = match get_provider_cache(pool, name, &key).await? {
+            Some(entry) if !options.refresh && entry.fetched_at >= min_fetched_at => Some(entry),
+            _ => None,
+        };
+
+        let record = if let Some(entry) = cached {
+            lookup.from_cache.push(name.to_string());
+            match entry.response {
+                Some(json) => Some(serde_json::from_str::<ProviderRecord>(&json)?),
+                None => None,
+            }
+        } else {
+            let worker = provider.clone();
+            let worker_query = query.clone();
+            let result = tokio::task::spawn_blocking(move || worker.lookup(&worker_query))
+                .await
+                .map_err(|e| RitmoErr::Generic(format!("Ricerca {} interrotta: {}", name, e)))?;
+            match result {
+                Ok(record) => {
+                    let json = record.as_ref().map(serde_json::to_string).transpose()?;
+                    put_provider_cache(pool, name, &key, json.as_deref()).await?;
+                    record
+                }
+                Err(e) => {
+                    lookup.failures.push((name.to_string(), e.to_string()));
+                    None
+                }
+            }
+        };
+
+        if let Some(record) = record {
+            lookup
+                .matches
+                .push(ProviderMatch::new(record, provider.confidence(), query));
+        }
+    }
+
+    Ok(lookup)
+}
+
+/// Completa un `ImportObject` con i metadati dei fornitori
+///
+/// Cerca per ISBN se valido; se nessun fornitore lo conosce ripiega su titolo e
+/// autore. I campi vengono fusi con `merge_records`. Restituisce l'esito della
+/// ricerca e le chiavi dei campi modificati.
+pub async fn enrich_import_object(
+    pool: &SqlitePool,
+    providers: &[Arc<dyn MetadataProvider>],
+    obj: &mut ImportObject,
+    options: &LookupOptions,
+) -> RitmoResult<(MetadataLookup, Vec<String>)> {
+    let Some(query) = ProviderQuery::from_import_object(obj) else {
+        return Ok((MetadataLookup::default(), Vec::new()));
+    };
+
+    let mut lookup = lookup_metadata(pool, providers, &query, options).await?;
+    if lookup.matches.is_empty() && query.is_isbn() {
+        if let Some(title_query) = ProviderQuery::title_from_import_object(obj) {
+            let fallback = lookup_metadata(pool, providers, &title_query, options).await?;
+            lookup.matches = fallback.matches;
+            lookup.failures.extend(fallback.failures);
+            lookup.from_cache.extend(fallback.from_cache);
+        }
+    }
+
+    let changed = merge_records(obj, &lookup.matches);
+    Ok((lookup, changed))
+}
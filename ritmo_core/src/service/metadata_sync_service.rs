@@ -1,9 +1,15 @@
+use crate::comic_info::{build_comic_info, modify_cbz_metadata};
 use crate::dto::ContentInput;
 use crate::epub_opf_modifier::{build_opf_metadata, modify_epub_metadata};
-use crate::service::book_import_service::BookImportMetadata;
+use crate::pdf_metadata::{build_pdf_metadata, modify_pdf_metadata};
+use crate::epub_validator::validate_epub;
+use crate::service::metadata_diff_service::save_synced_snapshot;
+use crate::service::book_import_service::{
+    ensure_no_new_errors, hashed_relative_path, BookImportMetadata,
+};
 use chrono::Datelike;
 use ritmo_db::{
-    clear_sync_mark, Book, Content, Format, Publisher, Series, Type,
+    clear_sync_mark, Book, BookFile, Content, Format, Publisher, Series, Type,
 };
 use ritmo_db_core::LibraryConfig;
 use ritmo_errors::{RitmoErr, RitmoResult};
@@ -11,28 +17,43 @@ use sha2::{Digest, Sha256};
 use std::fs;
 use std::path::PathBuf;
 
-/// Result of syncing a single book
+/// Result of syncing a single file of a book
 #[derive(Debug)]
-pub struct SyncResult {
-    pub book_id: i64,
+pub struct FileSyncResult {
+    /// ID in `book_files` (None for legacy books without `book_files` rows)
+    pub file_id: Option<i64>,
     pub old_hash: String,
     pub new_hash: String,
     pub old_path: PathBuf,
     pub new_path: PathBuf,
 }
 
-/// Sync metadata for a single book: DB → EPUB
+/// Result of syncing a single book
+#[derive(Debug)]
+pub struct SyncResult {
+    pub book_id: i64,
+    /// Files rewritten with DB metadata
+    pub files: Vec<FileSyncResult>,
+    /// Files left untouched because their format has no metadata writer
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Sync metadata for a single book: DB → every file of the book (EPUB, PDF, CBZ)
 ///
 /// Steps:
 /// 1. Read all book metadata from DB
 /// 2. Build BookImportMetadata from DB data
 /// 3. Read contents associated with this book
 /// 4. Build OPFMetadata
-/// 5. Modify EPUB with DB metadata
-/// 6. Calculate new hash
-/// 7. Move file to new hash-based path
-/// 8. Update DB with new hash and path
-/// 9. Clear sync mark
+/// 5. For each file of the book (`book_files`):
+///    - Modify EPUB (OPF), PDF (Info + XMP) or CBZ (ComicInfo.xml) with DB metadata
+///      (other formats are skipped)
+///    - EPUB: validate the rewritten file; if it has errors the original did not have,
+///      discard it and fail, leaving the original file, the DB and the sync mark untouched
+///    - Calculate new hash
+///    - Move file to new hash-based path
+///    - Update DB with new hash and path
+/// 6. Clear sync mark
 pub async fn sync_book_metadata(
     config: &LibraryConfig,
     pool: &sqlx::SqlitePool,
@@ -43,21 +64,11 @@ pub async fn sync_book_metadata(
         .await?
         .ok_or_else(|| RitmoErr::Generic(format!("Book ID {} not found", book_id)))?;
 
-    let old_hash = book
-        .file_hash
-        .clone()
-        .ok_or_else(|| RitmoErr::Generic(format!("Book ID {} has no file_hash", book_id)))?;
-
-    let old_path = config.canonical_storage_path().join(
-        book.file_link
-            .as_ref()
-            .ok_or_else(|| RitmoErr::Generic(format!("Book ID {} has no file_link", book_id)))?,
-    );
-
-    if !old_path.exists() {
+    let files = get_book_files(pool, &book).await?;
+    if files.is_empty() {
         return Err(RitmoErr::Generic(format!(
-            "EPUB file not found: {}",
-            old_path.display()
+            "Book ID {} has no files",
+            book_id
         )));
     }
 
@@ -67,68 +78,145 @@ pub async fn sync_book_metadata(
     // Step 3: Read contents associated with this book
     let contents = get_book_contents(pool, book_id).await?;
 
-    // Step 4: Build OPFMetadata
+    // Step 4: Build OPFMetadata (and its PDF/ComicInfo counterparts)
     let opf_metadata = build_opf_metadata(&metadata, &contents);
+    let pdf_metadata = build_pdf_metadata(&opf_metadata);
+    let comic_metadata = build_comic_info(&metadata, &contents);
 
-    // Step 5: Modify EPUB
-    let temp_epub = old_path.with_extension("epub.sync.tmp");
-    modify_epub_metadata(&old_path, &temp_epub, &opf_metadata)?;
-
-    // Step 6: Calculate new hash
-    let file_content = fs::read(&temp_epub)?;
-    let new_hash = calculate_hash(&file_content);
-
-    // Step 7: Determine new path
-    let extension = old_path
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("epub");
-    let new_relative_path = format!(
-        "books/{}/{}/{}.{}",
-        &new_hash[0..2],
-        &new_hash[2..4],
-        &new_hash[4..],
-        extension
-    );
-    let new_path = config.canonical_storage_path().join(&new_relative_path);
-
-    // Step 8: Move file to new location
-    if let Some(parent) = new_path.parent() {
-        fs::create_dir_all(parent)?;
-    }
-    fs::rename(&temp_epub, &new_path)?;
+    // Step 5: Sync each file
+    let mut result = SyncResult {
+        book_id,
+        files: Vec::new(),
+        skipped: Vec::new(),
+    };
 
-    // Step 9: Delete old file if different location
-    if old_path != new_path {
-        let _ = fs::remove_file(&old_path);
+    for file in files {
+        let old_path = config.canonical_storage_path().join(&file.file_link);
+        let extension = old_path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default();
+
+        if !matches!(extension.as_str(), "epub" | "pdf" | "cbz") {
+            result.skipped.push(old_path);
+            continue;
+        }
+
+        if !old_path.exists() {
+            return Err(RitmoErr::Generic(format!(
+                "{} file not found: {}",
+                extension.to_uppercase(),
+                old_path.display()
+            )));
+        }
+
+        // Modify file metadata (EPUB: OPF, PDF: Info + XMP, CBZ: ComicInfo.xml)
+        let temp_file = old_path.with_extension(format!("{}.sync.tmp", extension));
+        let written = match extension.as_str() {
+            "pdf" => modify_pdf_metadata(&old_path, &temp_file, &pdf_metadata),
+            "cbz" => modify_cbz_metadata(&old_path, &temp_file, &comic_metadata),
+            _ => modify_epub_metadata(&old_path, &temp_file, &opf_metadata),
+        };
+        // EPUB: rollback if the rewrite introduced structural errors
+        let written = written.and_then(|_| {
+            if extension == "epub" {
+                let before = validate_epub(&old_path)?;
+                ensure_no_new_errors(&temp_file, Some(&before))
+            } else {
+                Ok(())
+            }
+        });
+        if let Err(e) = written {
+            let _ = fs::remove_file(&temp_file);
+            return Err(e);
+        }
+
+        // Calculate new hash
+        let file_content = fs::read(&temp_file)?;
+        let new_hash = calculate_hash(&file_content);
+        let new_size = file_content.len() as i64;
+
+        // Determine new path
+        let new_relative_path = hashed_relative_path(&new_hash, &extension);
+        let new_path = config.canonical_storage_path().join(&new_relative_path);
+
+        // Move file to new location
+        if let Some(parent) = new_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(&temp_file, &new_path)?;
+
+        // Delete old file if different location
+        if old_path != new_path {
+            let _ = fs::remove_file(&old_path);
+        }
+
+        // Update DB: book_files row and, for the primary file, the books row
+        if let Some(file_id) = file.id {
+            BookFile::update_file_info(pool, file_id, &new_relative_path, &new_hash, Some(new_size))
+                .await?;
+        }
+        if book.file_link.as_deref() == Some(file.file_link.as_str()) {
+            let now = chrono::Utc::now().timestamp();
+            sqlx::query!(
+                "UPDATE books SET file_hash = ?, file_link = ?, file_size = ?, last_modified_date = ? WHERE id = ?",
+                new_hash,
+                new_relative_path,
+                new_size,
+                now,
+                book_id
+            )
+            .execute(pool)
+            .await?;
+        }
+
+        result.files.push(FileSyncResult {
+            file_id: file.id,
+            old_hash: file.file_hash,
+            new_hash,
+            old_path,
+            new_path,
+        });
     }
 
-    // Step 10: Update DB
-    let now = chrono::Utc::now().timestamp();
-    sqlx::query!(
-        "UPDATE books SET file_hash = ?, file_link = ?, last_modified_date = ? WHERE id = ?",
-        new_hash,
-        new_relative_path,
-        now,
-        book_id
-    )
-    .execute(pool)
-    .await?;
-
-    // Step 11: Clear sync mark
+    // Step 6: Clear sync mark and remember what was written (base for `metadata diff`)
     clear_sync_mark(pool, book_id).await?;
+    let synced_epub = result
+        .files
+        .iter()
+        .map(|f| f.new_path.as_path())
+        .find(|path| path.extension().is_some_and(|e| e.eq_ignore_ascii_case("epub")));
+    save_synced_snapshot(pool, book_id, &metadata, &contents, synced_epub).await?;
+
+    Ok(result)
+}
 
-    Ok(SyncResult {
-        book_id,
-        old_hash,
-        new_hash,
-        old_path,
-        new_path,
-    })
+/// Files of a book; falls back to the `books` row for books imported before `book_files`
+pub(crate) async fn get_book_files(pool: &sqlx::SqlitePool, book: &Book) -> RitmoResult<Vec<BookFile>> {
+    let book_id = book.id.unwrap_or(0);
+    let files = BookFile::list_for_book(pool, book_id).await?;
+    if !files.is_empty() {
+        return Ok(files);
+    }
+
+    match (&book.file_link, &book.file_hash) {
+        (Some(link), Some(hash)) => Ok(vec![BookFile {
+            id: None,
+            book_id,
+            format_id: book.format_id,
+            file_link: link.clone(),
+            file_hash: hash.clone(),
+            file_size: book.file_size,
+            added_at: book.created_at,
+            text_fingerprint: None,
+        }]),
+        _ => Ok(Vec::new()),
+    }
 }
 
 /// Build BookImportMetadata from database
-async fn build_book_metadata_from_db(
+pub(crate) async fn build_book_metadata_from_db(
     pool: &sqlx::SqlitePool,
     book: &Book,
 ) -> RitmoResult<BookImportMetadata> {
@@ -223,11 +311,13 @@ async fn build_book_metadata_from_db(
         pages: book.pages,
         notes: book.notes.clone(),
         tags,
+        attach_to: None,
+        strict_validation: false,
     })
 }
 
 /// Get contents associated with a book (for OPF aggregation)
-async fn get_book_contents(
+pub(crate) async fn get_book_contents(
     pool: &sqlx::SqlitePool,
     book_id: i64,
 ) -> RitmoResult<Vec<ContentInput>> {
@@ -270,6 +360,7 @@ async fn get_book_contents(
             .map(|r| crate::dto::PersonInput {
                 name: r.name,
                 role: r.role_key,
+                source: None,
             })
             .collect();
 
@@ -1,19 +1,58 @@
 pub mod batch_import_service;
 pub mod book_import_service;
 pub mod book_update_service;
+pub mod bulk_edit_service;
 pub mod content_create_service;
 pub mod content_update_service;
 pub mod delete_service;
+pub mod duplicate_books_service;
+pub mod isbn_service;
+pub mod metadata_diff_service;
+pub mod metadata_lookup_service;
 pub mod metadata_sync_service;
+pub mod shelf_service;
+pub mod validation_service;
+pub mod vocabulary_service;
 
 pub use batch_import_service::{batch_import, BatchImportSummary, ImportResult};
-pub use book_import_service::{import_book, BookImportMetadata};
+pub use book_import_service::{
+    attach_file_to_book, import_book, BookAttachTarget, BookImportMetadata,
+};
 pub use book_update_service::{update_book, BookUpdateMetadata};
+pub use bulk_edit_service::{
+    apply_bulk_edit, plan_bulk_edit, BookChangePlan, BulkEdit, BulkEditResult, FieldChange,
+};
 pub use content_create_service::{
     create_content, link_content_to_book, unlink_content_from_book, ContentCreateMetadata,
 };
 pub use content_update_service::{update_content, ContentUpdateMetadata};
 pub use delete_service::{
-    cleanup_orphaned_entities, delete_book, delete_content, CleanupStats, DeleteOptions,
+    cleanup_orphaned_entities, delete_book, delete_book_file, delete_content, CleanupStats,
+    DeleteOptions,
+};
+pub use duplicate_books_service::{
+    find_duplicate_books, find_import_duplicates, merge_books, BookMergeResult, DuplicateBook,
+    DuplicateBookGroup, DuplicateBooksReport, DuplicateMatch, DuplicateReason, ImportDuplicate,
+    DEFAULT_CONTENT_SIMILARITY,
+};
+pub use isbn_service::{
+    check_library_isbns, normalize_library_isbns, DuplicateIsbn, InvalidIsbn, IsbnBook, IsbnReport,
+};
+pub use metadata_diff_service::{
+    books_with_epub, diff_book_metadata, pull_book_metadata, BookMetadataDiff, ChangeOrigin,
+    FieldDiff, FieldValue, MetadataPullResult, MetadataSnapshot, Resolution, DIFF_FIELDS,
+    LIST_FIELDS,
+};
+pub use metadata_lookup_service::{
+    enrich_import_object, lookup_metadata, LookupOptions, MetadataLookup,
+};
+pub use metadata_sync_service::{sync_book_metadata, FileSyncResult, SyncResult};
+pub use shelf_service::{
+    add_books_to_shelf, create_manual_shelf, create_smart_shelf, delete_shelf, get_shelf,
+    list_shelves, remove_books_from_shelf, shelf_books, shelf_filters, ShelfEntry, ShelfSummary,
+};
+pub use validation_service::{validate_library_epubs, FileValidation};
+pub use vocabulary_service::{
+    check_library_vocabularies, normalize_library_vocabularies, VariantGroup, VocabularyMergeStats,
+    VocabularyReport, VocabularyRow,
 };
-pub use metadata_sync_service::{sync_book_metadata, SyncResult};
@@ -0,0 +1,356 @@
+use ritmo_config::BookFilterPreset;
+use ritmo_db::{Book, Shelf, ShelfBook};
+use ritmo_db_core::filters::validate_book_filters;
+use ritmo_db_core::{execute_books_query, BookFilters, BookResult};
+use ritmo_errors::{RitmoErr, RitmoResult};
+
+/// Uno scaffale con il numero di libri che contiene
+///
+/// Per gli scaffali smart il conteggio è calcolato valutando il filtro al momento.
+#[derive(Debug, Clone)]
+pub struct ShelfSummary {
+    pub shelf: Shelf,
+    pub book_count: i64,
+}
+
+/// Un libro di uno scaffale; posizione e nota ci sono solo per gli scaffali manuali
+#[derive(Debug, Clone)]
+pub struct ShelfEntry {
+    pub book: BookResult,
+    pub position: Option<i64>,
+    pub notes: Option<String>,
+}
+
+/// Crea uno scaffale smart a partire da un preset libri
+///
+/// Il filtro viene validato e salvato come JSON; non viene memorizzato alcun libro.
+pub async fn create_smart_shelf(
+    pool: &sqlx::SqlitePool,
+    name: &str,
+    description: Option<String>,
+    filter: &BookFilterPreset,
+) -> RitmoResult<i64> {
+    filters_from_preset(filter)?;
+    let filter_json = serde_json::to_string(filter)
+        .map_err(|e| RitmoErr::Generic(format!("Impossibile salvare il filtro: {}", e)))?;
+
+    insert_shelf(
+        pool,
+        name,
+        description,
+        Shelf::KIND_SMART,
+        Some(filter_json),
+    )
+    .await
+}
+
+/// Crea uno scaffale manuale vuoto
+pub async fn create_manual_shelf(
+    pool: &sqlx::SqlitePool,
+    name: &str,
+    description: Option<String>,
+) -> RitmoResult<i64> {
+    insert_shelf(pool, name, description, Shelf::KIND_MANUAL, None).await
+}
+
+async fn insert_shelf(
+    pool: &sqlx::SqlitePool,
+    name: &str,
+    description: Option<String>,
+    kind: &str,
+    filter_json: Option<String>,
+) -> RitmoResult<i64> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(RitmoErr::Generic(
+            "Il nome dello scaffale non può essere vuoto".to_string(),
+        ));
+    }
+    if Shelf::get_by_name(pool, name).await?.is_some() {
+        return Err(RitmoErr::Generic(format!(
+            "Esiste già uno scaffale chiamato '{}'",
+            name
+        )));
+    }
+
+    let shelf = Shelf {
+        id: None,
+        name: name.to_string(),
+        description,
+        kind: kind.to_string(),
+        filter_json,
+        created_at: 0,
+        updated_at: 0,
+    };
+    Ok(shelf.save(pool).await?)
+}
+
+/// Recupera uno scaffale per nome
+pub async fn get_shelf(pool: &sqlx::SqlitePool, name: &str) -> RitmoResult<Shelf> {
+    Shelf::get_by_name(pool, name)
+        .await?
+        .ok_or_else(|| RitmoErr::Generic(format!("Scaffale '{}' non trovato", name)))
+}
+
+/// Elimina uno scaffale (i libri non vengono toccati)
+pub async fn delete_shelf(pool: &sqlx::SqlitePool, name: &str) -> RitmoResult<()> {
+    let shelf = get_shelf(pool, name).await?;
+    Shelf::delete(pool, shelf.id.unwrap_or_default()).await?;
+    Ok(())
+}
+
+/// Filtri che selezionano i libri dello scaffale
+///
+/// Per uno scaffale smart sono i filtri salvati (ordinamento e limite compresi);
+/// per uno manuale un filtro sui libri collegati, combinabile con altri filtri.
+pub fn shelf_filters(shelf: &Shelf) -> RitmoResult<BookFilters> {
+    if !shelf.is_smart() {
+        return Ok(BookFilters {
+            shelf_id: shelf.id,
+            ..Default::default()
+        });
+    }
+
+    let json = shelf.filter_json.as_deref().unwrap_or("{}");
+    let preset: BookFilterPreset = serde_json::from_str(json).map_err(|e| {
+        RitmoErr::Generic(format!(
+            "Filtro dello scaffale '{}' non leggibile: {}",
+            shelf.name, e
+        ))
+    })?;
+    filters_from_preset(&preset)
+}
+
+fn filters_from_preset(preset: &BookFilterPreset) -> RitmoResult<BookFilters> {
+    let filters = BookFilters::from_preset(preset).map_err(filter_errors)?;
+    validate_book_filters(&filters).map_err(filter_errors)?;
+    Ok(filters)
+}
+
+fn filter_errors(errors: Vec<ritmo_db_core::filters::ValidationError>) -> RitmoErr {
+    let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+    RitmoErr::Generic(format!("Filtri non validi: {}", messages.join("; ")))
+}
+
+/// Tutti gli scaffali con il numero di libri (valutato al momento per gli smart)
+pub async fn list_shelves(pool: &sqlx::SqlitePool) -> RitmoResult<Vec<ShelfSummary>> {
+    let mut summaries = Vec::new();
+    for shelf in Shelf::list_all(pool).await? {
+        let book_count = if shelf.is_smart() {
+            let filters = shelf_filters(&shelf)?;
+            execute_books_query(pool, &filters).await?.len() as i64
+        } else {
+            ShelfBook::count_for_shelf(pool, shelf.id.unwrap_or_default()).await?
+        };
+        summaries.push(ShelfSummary { shelf, book_count });
+    }
+    Ok(summaries)
+}
+
+/// Libri dello scaffale: in ordine di posizione per i manuali, secondo il filtro per gli smart
+pub async fn shelf_books(pool: &sqlx::SqlitePool, shelf: &Shelf) -> RitmoResult<Vec<ShelfEntry>> {
+    let filters = shelf_filters(shelf)?;
+    let books = execute_books_query(pool, &filters).await?;
+
+    if shelf.is_smart() {
+        return Ok(books
+            .into_iter()
+            .map(|book| ShelfEntry {
+                book,
+                position: None,
+                notes: None,
+            })
+            .collect());
+    }
+
+    let mut books = books;
+    let mut entries = Vec::new();
+    for item in ShelfBook::list_for_shelf(pool, shelf.id.unwrap_or_default()).await? {
+        if let Some(index) = books.iter().position(|b| b.id == item.book_id) {
+            entries.push(ShelfEntry {
+                book: books.swap_remove(index),
+                position: Some(item.position),
+                notes: item.notes,
+            });
+        }
+    }
+    Ok(entries)
+}
+
+/// Aggiunge libri a uno scaffale manuale
+///
+/// Con `position` i libri vengono inseriti a partire da quel punto, nell'ordine
+/// indicato; la nota viene applicata a tutti. Restituisce le posizioni assegnate.
+pub async fn add_books_to_shelf(
+    pool: &sqlx::SqlitePool,
+    name: &str,
+    book_ids: &[i64],
+    position: Option<i64>,
+    notes: Option<&str>,
+) -> RitmoResult<Vec<i64>> {
+    let shelf = manual_shelf(pool, name).await?;
+    let shelf_id = shelf.id.unwrap_or_default();
+
+    // Verifica tutto prima di modificare lo scaffale
+    for &book_id in book_ids {
+        if Book::get(pool, book_id).await?.is_none() {
+            return Err(RitmoErr::Generic(format!(
+                "Libro con ID {} non trovato",
+                book_id
+            )));
+        }
+        if ShelfBook::get(pool, shelf_id, book_id).await?.is_some() {
+            return Err(RitmoErr::Generic(format!(
+                "Il libro {} è già nello scaffale '{}'",
+                book_id, shelf.name
+            )));
+        }
+    }
+
+    let mut positions = Vec::new();
+    for (offset, &book_id) in book_ids.iter().enumerate() {
+        let wanted = position.map(|p| p + offset as i64);
+        positions.push(ShelfBook::add(pool, shelf_id, book_id, wanted, notes).await?);
+    }
+    Ok(positions)
+}
+
+/// Rimuove libri da uno scaffale manuale; restituisce quanti erano presenti
+pub async fn remove_books_from_shelf(
+    pool: &sqlx::SqlitePool,
+    name: &str,
+    book_ids: &[i64],
+) -> RitmoResult<u64> {
+    let shelf = manual_shelf(pool, name).await?;
+    let mut removed = 0;
+    for &book_id in book_ids {
+        removed += ShelfBook::remove(pool, shelf.id.unwrap_or_default(), book_id).await?;
+    }
+    Ok(removed)
+}
+
+async fn manual_shelf(pool: &sqlx::SqlitePool, name: &str) -> RitmoResult<Shelf> {
+    let shelf = get_shelf(pool, name).await?;
+    if shelf.is_smart() {
+        return Err(RitmoErr::Generic(format!(
+            "'{}' è uno scaffale smart: i libri dipendono dal filtro e non si aggiungono a mano",
+            shelf.name
+        )));
+    }
+    Ok(shelf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::delete_service::{delete_book, DeleteOptions};
+    use crate::test_helpers::{create_test_db, insert_book, tag_book};
+    use ritmo_db_core::LibraryConfig;
+    use ritmo_errors::reporter::SilentReporter;
+
+    fn ids(entries: &[ShelfEntry]) -> Vec<i64> {
+        entries.iter().map(|e| e.book.id).collect()
+    }
+
+    #[tokio::test]
+    async fn test_smart_shelf_follows_filter() {
+        let pool = create_test_db().await;
+        let carrie = insert_book(&pool, "Carrie").await;
+        let emma = insert_book(&pool, "Emma").await;
+        tag_book(&pool, carrie, "horror").await;
+
+        let filter = BookFilterPreset {
+            tags: vec!["horror".to_string()],
+            ..Default::default()
+        };
+        create_smart_shelf(&pool, "Horror", None, &filter)
+            .await
+            .unwrap();
+        let shelf = get_shelf(&pool, "Horror").await.unwrap();
+        assert_eq!(
+            ids(&shelf_books(&pool, &shelf).await.unwrap()),
+            vec![carrie]
+        );
+
+        // Il contenuto è valutato al momento: un nuovo tag basta
+        tag_book(&pool, emma, "horror").await;
+        let mut books = ids(&shelf_books(&pool, &shelf).await.unwrap());
+        books.sort();
+        assert_eq!(books, vec![carrie, emma]);
+        assert_eq!(list_shelves(&pool).await.unwrap()[0].book_count, 2);
+
+        // I libri non si aggiungono a mano a uno scaffale smart
+        assert!(add_books_to_shelf(&pool, "Horror", &[emma], None, None)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_manual_shelf_add_and_remove() {
+        let pool = create_test_db().await;
+        let carrie = insert_book(&pool, "Carrie").await;
+        let emma = insert_book(&pool, "Emma").await;
+        let it = insert_book(&pool, "It").await;
+
+        create_manual_shelf(&pool, "Comodino", None).await.unwrap();
+        assert!(create_manual_shelf(&pool, "Comodino", None).await.is_err());
+
+        add_books_to_shelf(&pool, "Comodino", &[carrie, emma], None, Some("prestito"))
+            .await
+            .unwrap();
+        // Inserito in testa, gli altri scorrono
+        add_books_to_shelf(&pool, "Comodino", &[it], Some(1), None)
+            .await
+            .unwrap();
+        // Già presente: nessuna modifica
+        assert!(add_books_to_shelf(&pool, "Comodino", &[carrie], None, None)
+            .await
+            .is_err());
+        // Libro inesistente
+        assert!(add_books_to_shelf(&pool, "Comodino", &[999], None, None)
+            .await
+            .is_err());
+
+        let shelf = get_shelf(&pool, "Comodino").await.unwrap();
+        let entries = shelf_books(&pool, &shelf).await.unwrap();
+        assert_eq!(ids(&entries), vec![it, carrie, emma]);
+        assert_eq!(entries[1].notes.as_deref(), Some("prestito"));
+
+        let removed = remove_books_from_shelf(&pool, "Comodino", &[carrie, 999])
+            .await
+            .unwrap();
+        assert_eq!(removed, 1);
+        assert_eq!(
+            ids(&shelf_books(&pool, &shelf).await.unwrap()),
+            vec![it, emma]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deleted_book_leaves_shelf() {
+        let pool = create_test_db().await;
+        let dir = tempfile::tempdir().unwrap();
+        let config = LibraryConfig::new(dir.path());
+        let carrie = insert_book(&pool, "Carrie").await;
+        let emma = insert_book(&pool, "Emma").await;
+
+        create_manual_shelf(&pool, "Comodino", None).await.unwrap();
+        add_books_to_shelf(&pool, "Comodino", &[carrie, emma], None, None)
+            .await
+            .unwrap();
+
+        delete_book(
+            &config,
+            &pool,
+            carrie,
+            &DeleteOptions::default(),
+            &mut SilentReporter,
+        )
+        .await
+        .unwrap();
+
+        let shelf = get_shelf(&pool, "Comodino").await.unwrap();
+        assert_eq!(ids(&shelf_books(&pool, &shelf).await.unwrap()), vec![emma]);
+        assert_eq!(list_shelves(&pool).await.unwrap()[0].book_count, 1);
+    }
+}
@@ -0,0 +1,71 @@
+use crate::epub_validator::{validate_epub, ValidationReport};
+use crate::service::metadata_sync_service::get_book_files;
+use ritmo_db::Book;
+use ritmo_db_core::LibraryConfig;
+use ritmo_errors::{RitmoErr, RitmoResult};
+use std::path::PathBuf;
+
+/// Esito della validazione di un file EPUB della libreria
+#[derive(Debug)]
+pub struct FileValidation {
+    pub book_id: i64,
+    pub book_name: String,
+    /// ID in `book_files` (None per i libri importati prima di `book_files`)
+    pub file_id: Option<i64>,
+    pub path: PathBuf,
+    pub report: ValidationReport,
+}
+
+/// Valida gli EPUB della libreria
+///
+/// Con `book_id` vengono validati solo i file di quel libro, altrimenti tutti.
+/// I file non EPUB vengono ignorati; un file mancante nello storage viene
+/// riportato come errore nel suo report.
+pub async fn validate_library_epubs(
+    config: &LibraryConfig,
+    pool: &sqlx::SqlitePool,
+    book_id: Option<i64>,
+) -> RitmoResult<Vec<FileValidation>> {
+    let book_ids = match book_id {
+        Some(id) => vec![id],
+        None => sqlx::query_scalar!(r#"SELECT id as "id!" FROM books ORDER BY id"#)
+            .fetch_all(pool)
+            .await?,
+    };
+
+    let mut results = Vec::new();
+    for id in book_ids {
+        let book = Book::get(pool, id)
+            .await?
+            .ok_or_else(|| RitmoErr::Generic(format!("Libro con ID {} non trovato", id)))?;
+
+        for file in get_book_files(pool, &book).await? {
+            let is_epub = PathBuf::from(&file.file_link)
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| e.eq_ignore_ascii_case("epub"));
+            if !is_epub {
+                continue;
+            }
+
+            let path = config.canonical_storage_path().join(&file.file_link);
+            let report = if path.exists() {
+                validate_epub(&path)?
+            } else {
+                let mut report = ValidationReport::default();
+                report.error("file non trovato nello storage");
+                report
+            };
+
+            results.push(FileValidation {
+                book_id: id,
+                book_name: book.name.clone(),
+                file_id: file.id,
+                path,
+                report,
+            });
+        }
+    }
+
+    Ok(results)
+}
@@ -0,0 +1,497 @@
+//! Riconduzione di lingue e formati della libreria ai vocabolari controllati
+//!
+//! Le righe di `running_languages` e `formats` create prima dei vocabolari
+//! possono contenere varianti della stessa voce ("ita", "it" e "Italian";
+//! "EPUB", "epub3" e "ePub"). `check_library_vocabularies` le raggruppa per
+//! voce canonica, `normalize_library_vocabularies` unisce ogni gruppo in una
+//! sola riga con i valori canonici.
+//!
+//! Forma canonica di una lingua: `iso_code_2char` = codice 639-1 (639-3 se
+//! manca), `iso_code_3char` = codice 639-3, `official_name` = nome inglese.
+//! Le lingue con ruoli diversi restano righe distinte.
+
+use ritmo_db_core::vocabulary::{lookup_format, lookup_language, IsoLanguage};
+use ritmo_errors::RitmoResult;
+use std::collections::BTreeMap;
+
+/// Riga di `running_languages` o `formats`
+#[derive(Debug, Clone)]
+pub struct VocabularyRow {
+    pub id: i64,
+    /// Valore attuale: `iso2/iso3 (nome, ruolo)` per le lingue, la chiave per i formati
+    pub value: String,
+    /// Contenuti (lingue) o libri e file (formati) che usano la riga
+    pub usage: i64,
+}
+
+/// Righe da ricondurre alla stessa voce canonica; la prima è quella che resta
+#[derive(Debug, Clone)]
+pub struct VariantGroup {
+    /// Voce canonica: codice e ruolo per le lingue, chiave per i formati
+    pub canonical: String,
+    pub rows: Vec<VocabularyRow>,
+}
+
+/// Esito della verifica di lingue e formati della libreria
+#[derive(Debug, Clone, Default)]
+pub struct VocabularyReport {
+    pub languages: Vec<VariantGroup>,
+    pub formats: Vec<VariantGroup>,
+    /// Righe non riconducibili ai vocabolari (lasciate invariate)
+    pub unknown_languages: Vec<VocabularyRow>,
+    pub unknown_formats: Vec<VocabularyRow>,
+}
+
+impl VocabularyReport {
+    pub fn has_variants(&self) -> bool {
+        !self.languages.is_empty() || !self.formats.is_empty()
+    }
+}
+
+/// Esito della normalizzazione di lingue e formati
+#[derive(Debug, Clone, Default)]
+pub struct VocabularyMergeStats {
+    /// Righe di `running_languages` eliminate perché unite a quella canonica
+    pub languages_merged: usize,
+    /// Righe di `running_languages` riscritte con i valori canonici
+    pub languages_updated: usize,
+    pub formats_merged: usize,
+    pub formats_updated: usize,
+    /// Libri con contenuti la cui lingua è cambiata (da sincronizzare)
+    pub affected_book_ids: Vec<i64>,
+}
+
+struct LanguageRow {
+    id: i64,
+    iso2: String,
+    iso3: String,
+    name: String,
+    role: String,
+    usage: i64,
+}
+
+impl LanguageRow {
+    fn is_canonical_key(&self, iso: &IsoLanguage) -> bool {
+        self.iso2 == iso.code() && self.iso3 == iso.iso639_3
+    }
+
+    fn is_canonical(&self, iso: &IsoLanguage) -> bool {
+        self.is_canonical_key(iso) && self.name == iso.name_en
+    }
+
+    fn to_row(&self) -> VocabularyRow {
+        VocabularyRow {
+            id: self.id,
+            value: format!("{}/{} ({}, {})", self.iso2, self.iso3, self.name, self.role),
+            usage: self.usage,
+        }
+    }
+}
+
+struct FormatRow {
+    id: i64,
+    key: String,
+    usage: i64,
+}
+
+impl FormatRow {
+    fn to_row(&self) -> VocabularyRow {
+        VocabularyRow {
+            id: self.id,
+            value: self.key.clone(),
+            usage: self.usage,
+        }
+    }
+}
+
+/// Righe di una lingua canonica con lo stesso ruolo; la prima viene mantenuta
+struct LanguagePlan {
+    iso: &'static IsoLanguage,
+    role: String,
+    rows: Vec<LanguageRow>,
+}
+
+/// Righe di un formato canonico; la prima viene mantenuta
+struct FormatPlan {
+    key: &'static str,
+    rows: Vec<FormatRow>,
+}
+
+async fn language_rows(pool: &sqlx::SqlitePool) -> RitmoResult<Vec<LanguageRow>> {
+    let rows = sqlx::query_as!(
+        LanguageRow,
+        r#"SELECT rl.id AS "id!", rl.iso_code_2char AS iso2, rl.iso_code_3char AS iso3,
+                  rl.official_name AS name, rl.language_role AS role,
+                  (SELECT COUNT(*) FROM x_contents_languages WHERE language_id = rl.id) AS "usage!: i64"
+           FROM running_languages rl
+           ORDER BY rl.id"#
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+async fn format_rows(pool: &sqlx::SqlitePool) -> RitmoResult<Vec<FormatRow>> {
+    let rows = sqlx::query_as!(
+        FormatRow,
+        r#"SELECT f.id AS "id!", f.key,
+                  (SELECT COUNT(*) FROM books WHERE format_id = f.id)
+                  + (SELECT COUNT(*) FROM book_files WHERE format_id = f.id) AS "usage!: i64"
+           FROM formats f
+           ORDER BY f.id"#
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Voce ISO 639 di una riga: prova il codice a 2, quello a 3 lettere e il nome
+fn resolve_language(row: &LanguageRow) -> Option<&'static IsoLanguage> {
+    [&row.iso2, &row.iso3, &row.name]
+        .into_iter()
+        .filter(|value| !value.trim().is_empty())
+        .find_map(|value| lookup_language(value))
+}
+
+/// Raggruppa le righe per lingua canonica e ruolo; restituisce i gruppi da
+/// correggere e le righe non riconosciute
+fn plan_languages(rows: Vec<LanguageRow>) -> (Vec<LanguagePlan>, Vec<LanguageRow>) {
+    let mut groups: BTreeMap<(&'static str, String), LanguagePlan> = BTreeMap::new();
+    let mut unknown = Vec::new();
+
+    for row in rows {
+        let Some(iso) = resolve_language(&row) else {
+            unknown.push(row);
+            continue;
+        };
+        groups
+            .entry((iso.code(), row.role.clone()))
+            .or_insert_with(|| LanguagePlan {
+                iso,
+                role: row.role.clone(),
+                rows: Vec::new(),
+            })
+            .rows
+            .push(row);
+    }
+
+    let plans = groups
+        .into_values()
+        .filter_map(|mut plan| {
+            // Resta la riga che ha già i codici canonici (vincolo UNIQUE), altrimenti la più vecchia
+            if let Some(pos) = plan.rows.iter().position(|r| r.is_canonical_key(plan.iso)) {
+                let primary = plan.rows.remove(pos);
+                plan.rows.insert(0, primary);
+            }
+            let needs_fix = plan.rows.len() > 1 || !plan.rows[0].is_canonical(plan.iso);
+            needs_fix.then_some(plan)
+        })
+        .collect();
+
+    (plans, unknown)
+}
+
+/// Raggruppa i formati per chiave canonica; restituisce i gruppi da correggere
+/// e le righe non riconosciute
+fn plan_formats(rows: Vec<FormatRow>) -> (Vec<FormatPlan>, Vec<FormatRow>) {
+    let mut groups: BTreeMap<&'static str, Vec<FormatRow>> = BTreeMap::new();
+    let mut unknown = Vec::new();
+
+    for row in rows {
+        match lookup_format(&row.key) {
+            Some(info) => groups.entry(info.key).or_default().push(row),
+            None => unknown.push(row),
+        }
+    }
+
+    let plans = groups
+        .into_iter()
+        .filter_map(|(key, mut rows)| {
+            if let Some(pos) = rows.iter().position(|r| r.key == key) {
+                let primary = rows.remove(pos);
+                rows.insert(0, primary);
+            }
+            let needs_fix = rows.len() > 1 || rows[0].key != key;
+            needs_fix.then_some(FormatPlan { key, rows })
+        })
+        .collect();
+
+    (plans, unknown)
+}
+
+/// Verifica lingue e formati della libreria rispetto ai vocabolari controllati
+///
+/// Non modifica il database: elenca i gruppi di varianti che
+/// `normalize_library_vocabularies` unirebbe e le righe non riconosciute.
+pub async fn check_library_vocabularies(pool: &sqlx::SqlitePool) -> RitmoResult<VocabularyReport> {
+    let (language_plans, unknown_languages) = plan_languages(language_rows(pool).await?);
+    let (format_plans, unknown_formats) = plan_formats(format_rows(pool).await?);
+
+    Ok(VocabularyReport {
+        languages: language_plans
+            .iter()
+            .map(|plan| VariantGroup {
+                canonical: format!(
+                    "{}/{} ({}, {})",
+                    plan.iso.code(),
+                    plan.iso.iso639_3,
+                    plan.iso.name_en,
+                    plan.role
+                ),
+                rows: plan.rows.iter().map(LanguageRow::to_row).collect(),
+            })
+            .collect(),
+        formats: format_plans
+            .iter()
+            .map(|plan| VariantGroup {
+                canonical: plan.key.to_string(),
+                rows: plan.rows.iter().map(FormatRow::to_row).collect(),
+            })
+            .collect(),
+        unknown_languages: unknown_languages.iter().map(LanguageRow::to_row).collect(),
+        unknown_formats: unknown_formats.iter().map(FormatRow::to_row).collect(),
+    })
+}
+
+/// Unisce le varianti di lingue e formati sulle righe canoniche
+///
+/// Per ogni gruppo resta una sola riga, riscritta con i valori canonici: i
+/// contenuti, i libri e i file delle varianti passano a quella riga e le
+/// varianti vengono eliminate. Le righe non riconosciute non vengono toccate.
+/// Tutto avviene in una transazione.
+pub async fn normalize_library_vocabularies(
+    pool: &sqlx::SqlitePool,
+) -> RitmoResult<VocabularyMergeStats> {
+    let (language_plans, _) = plan_languages(language_rows(pool).await?);
+    let (format_plans, _) = plan_formats(format_rows(pool).await?);
+    let mut stats = VocabularyMergeStats::default();
+    let mut tx = pool.begin().await?;
+
+    for plan in &language_plans {
+        let primary = &plan.rows[0];
+        for duplicate in &plan.rows[1..] {
+            sqlx::query!(
+                "INSERT OR IGNORE INTO x_contents_languages (content_id, language_id)
+                 SELECT content_id, ? FROM x_contents_languages WHERE language_id = ?",
+                primary.id,
+                duplicate.id
+            )
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query!(
+                "DELETE FROM x_contents_languages WHERE language_id = ?",
+                duplicate.id
+            )
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query!("DELETE FROM running_languages WHERE id = ?", duplicate.id)
+                .execute(&mut *tx)
+                .await?;
+            stats.languages_merged += 1;
+        }
+
+        if !primary.is_canonical(plan.iso) {
+            let (code, iso3, name) = (plan.iso.code(), plan.iso.iso639_3, plan.iso.name_en);
+            sqlx::query!(
+                "UPDATE running_languages SET iso_code_2char = ?, iso_code_3char = ?, official_name = ?
+                 WHERE id = ?",
+                code,
+                iso3,
+                name,
+                primary.id
+            )
+            .execute(&mut *tx)
+            .await?;
+            stats.languages_updated += 1;
+        }
+
+        let book_ids = sqlx::query_scalar!(
+            r#"SELECT DISTINCT bc.book_id AS "book_id!: i64"
+               FROM x_books_contents bc
+               JOIN x_contents_languages cl ON cl.content_id = bc.content_id
+               WHERE cl.language_id = ?"#,
+            primary.id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        stats.affected_book_ids.extend(book_ids);
+    }
+
+    for plan in &format_plans {
+        let primary = &plan.rows[0];
+        for duplicate in &plan.rows[1..] {
+            sqlx::query!(
+                "UPDATE books SET format_id = ? WHERE format_id = ?",
+                primary.id,
+                duplicate.id
+            )
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query!(
+                "UPDATE book_files SET format_id = ? WHERE format_id = ?",
+                primary.id,
+                duplicate.id
+            )
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query!("DELETE FROM formats WHERE id = ?", duplicate.id)
+                .execute(&mut *tx)
+                .await?;
+            stats.formats_merged += 1;
+        }
+
+        if primary.key != plan.key {
+            sqlx::query!(
+                "UPDATE formats SET key = ? WHERE id = ?",
+                plan.key,
+                primary.id
+            )
+            .execute(&mut *tx)
+            .await?;
+            stats.formats_updated += 1;
+        }
+    }
+
+    tx.commit().await?;
+
+    stats.affected_book_ids.sort_unstable();
+    stats.affected_book_ids.dedup();
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{create_test_db, insert_book};
+
+    async fn exec(pool: &sqlx::SqlitePool, sql: &str) {
+        sqlx::query(sql).execute(pool).await.unwrap();
+    }
+
+    async fn ids(pool: &sqlx::SqlitePool, sql: &str) -> Vec<i64> {
+        sqlx::query_scalar(sql).fetch_all(pool).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_normalize_merges_variants() {
+        let pool = create_test_db().await;
+        let book_id = insert_book(&pool, "Il nome della rosa").await;
+        let other_book = insert_book(&pool, "Altro").await;
+
+        // Lingue: 1 e 2 sono la stessa voce (2 ha già i codici canonici), 3 ha
+        // un altro ruolo, 4 non è riconoscibile
+        exec(
+            &pool,
+            "INSERT INTO running_languages (iso_code_2char, iso_code_3char, official_name, language_role) VALUES
+             ('ita', 'ita', 'Italian', 'language_role.actual'),
+             ('it', 'ita', 'Italiano', 'language_role.actual'),
+             ('it', 'ita', 'Italian', 'language_role.original'),
+             ('x1', 'x1x', 'Elfico', 'language_role.actual')",
+        )
+        .await;
+        exec(
+            &pool,
+            "INSERT INTO contents (name) VALUES ('Il nome della rosa'), ('Postille')",
+        )
+        .await;
+        exec(
+            &pool,
+            &format!(
+                "INSERT INTO x_books_contents (book_id, content_id) VALUES ({}, 1)",
+                book_id
+            ),
+        )
+        .await;
+        exec(
+            &pool,
+            "INSERT INTO x_contents_languages (content_id, language_id) VALUES (1, 1), (1, 2), (2, 1)",
+        )
+        .await;
+
+        // Formati: 1 e 2 sono varianti di epub, nessuna con la chiave canonica
+        exec(
+            &pool,
+            "INSERT INTO formats (key) VALUES ('EPUB'), ('epub3'), ('pdf'), ('tape')",
+        )
+        .await;
+        exec(
+            &pool,
+            &format!("UPDATE books SET format_id = 2 WHERE id = {}", other_book),
+        )
+        .await;
+        exec(
+            &pool,
+            &format!(
+                "INSERT INTO book_files (book_id, format_id, file_link, file_hash) VALUES ({}, 2, 'a.epub', 'h')",
+                book_id
+            ),
+        )
+        .await;
+
+        let report = check_library_vocabularies(&pool).await.unwrap();
+        let group_ids = |groups: &[VariantGroup]| -> Vec<Vec<i64>> {
+            groups
+                .iter()
+                .map(|g| g.rows.iter().map(|r| r.id).collect())
+                .collect()
+        };
+        assert_eq!(group_ids(&report.languages), vec![vec![2, 1]]);
+        assert_eq!(group_ids(&report.formats), vec![vec![1, 2]]);
+        assert_eq!(report.unknown_languages[0].id, 4);
+        assert_eq!(report.unknown_formats[0].id, 4);
+
+        let stats = normalize_library_vocabularies(&pool).await.unwrap();
+
+        assert_eq!((stats.languages_merged, stats.languages_updated), (1, 1));
+        assert_eq!((stats.formats_merged, stats.formats_updated), (1, 1));
+        assert_eq!(stats.affected_book_ids, vec![book_id]);
+
+        // Le varianti sono eliminate, i riferimenti passano alla riga canonica
+        assert_eq!(
+            ids(&pool, "SELECT id FROM running_languages ORDER BY id").await,
+            vec![2, 3, 4]
+        );
+        let name: String =
+            sqlx::query_scalar("SELECT official_name FROM running_languages WHERE id = 2")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(name, lookup_language("it").unwrap().name_en);
+        assert_eq!(
+            ids(
+                &pool,
+                "SELECT language_id FROM x_contents_languages ORDER BY content_id"
+            )
+            .await,
+            vec![2, 2]
+        );
+
+        assert_eq!(
+            ids(&pool, "SELECT id FROM formats ORDER BY id").await,
+            vec![1, 3, 4]
+        );
+        let key: String = sqlx::query_scalar("SELECT key FROM formats WHERE id = 1")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(key, "epub");
+        assert_eq!(
+            ids(
+                &pool,
+                "SELECT format_id FROM books WHERE format_id IS NOT NULL"
+            )
+            .await,
+            vec![1]
+        );
+        assert_eq!(
+            ids(&pool, "SELECT format_id FROM book_files").await,
+            vec![1]
+        );
+
+        // Una seconda passata non trova altro
+        assert!(!check_library_vocabularies(&pool)
+            .await
+            .unwrap()
+            .has_variants());
+    }
+}
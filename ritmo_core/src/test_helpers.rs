@@ -0,0 +1,54 @@
+//! Helper per i test dei servizi: database SQLite in memoria con lo schema
+//! completo di ritmo (`ritmo_db/schema/schema.sql`, lo stesso del template)
+
+use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
+
+const SCHEMA: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../ritmo_db/schema/schema.sql"
+));
+
+/// Crea un database in memoria vuoto (una sola connessione, foreign key attive)
+pub async fn create_test_db() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("database in memoria");
+
+    sqlx::raw_sql(SCHEMA)
+        .execute(&pool)
+        .await
+        .expect("schema di test");
+    sqlx::query("PRAGMA foreign_keys = ON")
+        .execute(&pool)
+        .await
+        .expect("foreign keys");
+
+    pool
+}
+
+/// Inserisce un libro con il solo titolo e ne restituisce l'ID
+pub async fn insert_book(pool: &SqlitePool, name: &str) -> i64 {
+    sqlx::query("INSERT INTO books (name) VALUES (?)")
+        .bind(name)
+        .execute(pool)
+        .await
+        .expect("insert book")
+        .last_insert_rowid()
+}
+
+/// Collega un tag (creato se manca) a un libro
+pub async fn tag_book(pool: &SqlitePool, book_id: i64, tag: &str) {
+    sqlx::query("INSERT OR IGNORE INTO tags (name) VALUES (?)")
+        .bind(tag)
+        .execute(pool)
+        .await
+        .expect("insert tag");
+    sqlx::query("INSERT INTO x_books_tags (book_id, tag_id) SELECT ?, id FROM tags WHERE name = ?")
+        .bind(book_id)
+        .bind(tag)
+        .execute(pool)
+        .await
+        .expect("tag book");
+}
@@ -0,0 +1,325 @@
+//! Impronta del testo di un EPUB, indipendente dai metadati
+//!
+//! Il testo dei documenti della spine viene diviso in parole e in sequenze di
+//! `SHINGLE_WORDS` parole consecutive; l'impronta è la firma MinHash di queste
+//! sequenze. Due EPUB con lo stesso testo hanno la stessa impronta anche se
+//! OPF, copertina o hash del file sono diversi (es. un EPUB scaricato di nuovo
+//! o riscritto da `sync-metadata`); la quota di valori uguali stima la
+//! somiglianza di Jaccard tra i due testi.
+
+use crate::epub_utils::find_opf_path_in_container;
+use crate::epub_validator::{parse_package, read_entry, resolve_href};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use ritmo_errors::{RitmoErr, RitmoResult};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use zip::ZipArchive;
+
+/// Numero di valori della firma
+pub const SIGNATURE_SIZE: usize = 64;
+
+/// Parole per sequenza (shingle)
+const SHINGLE_WORDS: usize = 5;
+
+/// Sotto questo numero di parole il testo non basta per un'impronta affidabile
+const MIN_WORDS: usize = 50;
+
+/// Bande per la ricerca di candidati (LSH): `SIGNATURE_SIZE / LSH_BANDS` valori per banda
+pub const LSH_BANDS: usize = 16;
+
+/// Firma MinHash del testo di un libro
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextFingerprint(Vec<u64>);
+
+impl TextFingerprint {
+    /// Impronta di un testo; `None` se il testo è troppo breve
+    pub fn from_text(text: &str) -> Option<Self> {
+        let words: Vec<String> = text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(|w| w.to_lowercase())
+            .collect();
+        if words.len() < MIN_WORDS {
+            return None;
+        }
+
+        let mut signature = vec![u64::MAX; SIGNATURE_SIZE];
+        for shingle in words.windows(SHINGLE_WORDS) {
+            let base = fnv1a(shingle);
+            for (k, slot) in signature.iter_mut().enumerate() {
+                let value = splitmix64(base ^ (k as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+                if value < *slot {
+                    *slot = value;
+                }
+            }
+        }
+        Some(Self(signature))
+    }
+
+    /// Quota di valori uguali: stima della somiglianza di Jaccard (0.0-1.0)
+    pub fn similarity(&self, other: &TextFingerprint) -> f64 {
+        let equal = self.0.iter().zip(&other.0).filter(|(a, b)| a == b).count();
+        equal as f64 / SIGNATURE_SIZE as f64
+    }
+
+    /// Chiavi delle bande LSH: due impronte con una banda in comune sono candidate
+    pub fn band_keys(&self) -> Vec<(usize, &[u64])> {
+        self.0
+            .chunks(SIGNATURE_SIZE / LSH_BANDS)
+            .enumerate()
+            .collect()
+    }
+
+    /// Forma esadecimale salvata in `book_files.text_fingerprint`
+    pub fn to_hex(&self) -> String {
+        self.0.iter().map(|v| format!("{:016x}", v)).collect()
+    }
+
+    pub fn from_hex(value: &str) -> Option<Self> {
+        if value.len() != SIGNATURE_SIZE * 16 || !value.is_ascii() {
+            return None;
+        }
+        (0..SIGNATURE_SIZE)
+            .map(|i| u64::from_str_radix(&value[i * 16..(i + 1) * 16], 16).ok())
+            .collect::<Option<Vec<u64>>>()
+            .map(Self)
+    }
+}
+
+/// Impronta del testo di un EPUB; `None` se il testo è troppo breve
+pub fn epub_fingerprint(epub_path: &Path) -> RitmoResult<Option<TextFingerprint>> {
+    Ok(TextFingerprint::from_text(&epub_spine_text(epub_path)?))
+}
+
+/// Testo dei documenti della spine, nell'ordine di lettura
+///
+/// Vengono letti solo i `<body>`, esclusi `<script>` e `<style>`; i documenti
+/// non leggibili vengono saltati.
+pub fn epub_spine_text(epub_path: &Path) -> RitmoResult<String> {
+    let file = File::open(epub_path)?;
+    let mut archive = ZipArchive::new(BufReader::new(file))
+        .map_err(|e| RitmoErr::Generic(format!("Impossibile aprire EPUB come ZIP: {}", e)))?;
+
+    let opf_path = find_opf_path_in_container(&mut archive)?;
+    let opf = read_entry(&mut archive, &opf_path).map_err(RitmoErr::Generic)?;
+    let package = parse_package(&opf)
+        .map_err(|e| RitmoErr::Generic(format!("OPF '{}' non valido: {}", opf_path, e)))?;
+    let opf_dir = opf_path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+
+    let mut text = String::new();
+    for idref in &package.spine {
+        let Some(item) = package.manifest.iter().find(|i| &i.id == idref) else {
+            continue;
+        };
+        if !item.media_type.contains("html") {
+            continue;
+        }
+        if let Ok(document) = read_entry(&mut archive, &resolve_href(opf_dir, &item.href)) {
+            append_body_text(&document, &mut text);
+        }
+    }
+    Ok(text)
+}
+
+/// Aggiunge a `out` il testo del `<body>` di un documento XHTML
+///
+/// Su XML non valido si ferma e tiene il testo letto fino a quel punto.
+fn append_body_text(document: &str, out: &mut String) {
+    let mut reader = Reader::from_str(document);
+    reader.config_mut().check_end_names = false;
+    let mut in_body = false;
+    let mut skipped = 0usize;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => match e.local_name().as_ref() {
+                b"body" => in_body = true,
+                b"script" | b"style" => skipped += 1,
+                _ => {}
+            },
+            Ok(Event::End(e)) => match e.local_name().as_ref() {
+                b"body" => in_body = false,
+                b"script" | b"style" => skipped = skipped.saturating_sub(1),
+                _ => {}
+            },
+            Ok(Event::Text(e)) if in_body && skipped == 0 => {
+                let text = e
+                    .unescape()
+                    .map(|t| t.into_owned())
+                    .unwrap_or_else(|_| String::from_utf8_lossy(&e).into_owned());
+                out.push_str(&text);
+                out.push(' ');
+            }
+            Ok(Event::CData(e)) if in_body && skipped == 0 => {
+                out.push_str(&String::from_utf8_lossy(&e));
+                out.push(' ');
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+    }
+}
+
+fn fnv1a(words: &[String]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for word in words {
+        for byte in word.bytes().chain(std::iter::once(b' ')) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    const CONTAINER: &str = r#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>"#;
+
+    fn opf(title: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="uid">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="uid">urn:uuid:1234</dc:identifier>
+    <dc:title>{}</dc:title>
+    <dc:language>it</dc:language>
+  </metadata>
+  <manifest>
+    <item id="css" href="style.css" media-type="text/css"/>
+    <item id="ch2" href="Text/ch2.xhtml" media-type="application/xhtml+xml"/>
+    <item id="ch1" href="Text/ch1.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine>
+    <itemref idref="ch1"/>
+    <itemref idref="ch2"/>
+  </spine>
+</package>"#,
+            title
+        )
+    }
+
+    fn chapter(heading: &str, body: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<html xmlns="http://www.w3.org/1999/xhtml"><head><title>{}</title>
+<style>p {{ margin: 0 }}</style></head>
+<body><h1>{}</h1><p>{}</p><script>var x = 1;</script></body></html>"#,
+            heading, heading, body
+        )
+    }
+
+    fn create_epub(
+        dir: &TempDir,
+        name: &str,
+        title: &str,
+        chapters: [&str; 2],
+    ) -> std::path::PathBuf {
+        let path = dir.path().join(name);
+        let mut zip = ZipWriter::new(File::create(&path).unwrap());
+        let options = SimpleFileOptions::default();
+        zip.start_file("mimetype", options).unwrap();
+        zip.write_all(b"application/epub+zip").unwrap();
+        zip.start_file("META-INF/container.xml", options).unwrap();
+        zip.write_all(CONTAINER.as_bytes()).unwrap();
+        zip.start_file("OEBPS/content.opf", options).unwrap();
+        zip.write_all(opf(title).as_bytes()).unwrap();
+        zip.start_file("OEBPS/Text/ch1.xhtml", options).unwrap();
+        zip.write_all(chapter("Uno", chapters[0]).as_bytes())
+            .unwrap();
+        zip.start_file("OEBPS/Text/ch2.xhtml", options).unwrap();
+        zip.write_all(chapter("Due", chapters[1]).as_bytes())
+            .unwrap();
+        zip.finish().unwrap();
+        path
+    }
+
+    fn sample_text(seed: usize, words: usize) -> String {
+        (0..words)
+            .map(|i| format!("parola{}", (i * 7 + seed * 13) % 97 + i / 11))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn test_epub_spine_text_reads_body_in_spine_order() {
+        let dir = TempDir::new().unwrap();
+        let path = create_epub(
+            &dir,
+            "a.epub",
+            "Titolo",
+            ["primo capitolo &amp; altro", "secondo"],
+        );
+
+        let text = epub_spine_text(&path).unwrap();
+        let words: Vec<&str> = text.split_whitespace().collect();
+        assert_eq!(
+            words,
+            vec!["Uno", "primo", "capitolo", "&", "altro", "Due", "secondo"]
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_metadata() {
+        let dir = TempDir::new().unwrap();
+        let body = [sample_text(1, 200), sample_text(2, 200)];
+        let a = create_epub(&dir, "a.epub", "Titolo", [&body[0], &body[1]]);
+        let b = create_epub(&dir, "b.epub", "Titolo modificato", [&body[0], &body[1]]);
+
+        let fa = epub_fingerprint(&a).unwrap().unwrap();
+        let fb = epub_fingerprint(&b).unwrap().unwrap();
+        assert_eq!(fa, fb);
+        assert_eq!(fa.similarity(&fb), 1.0);
+    }
+
+    #[test]
+    fn test_fingerprint_similarity() {
+        let text = sample_text(1, 400);
+        let base = TextFingerprint::from_text(&text).unwrap();
+
+        // Poche parole cambiate: testo molto simile
+        let edited = text.replacen("parola", "refuso", 3);
+        let near = TextFingerprint::from_text(&edited).unwrap();
+        assert!(base.similarity(&near) > 0.8);
+
+        // Testo diverso
+        let other =
+            TextFingerprint::from_text(&sample_text(5, 400).replace("parola", "mot")).unwrap();
+        assert!(base.similarity(&other) < 0.2);
+
+        // Troppo breve
+        assert!(TextFingerprint::from_text("poche parole").is_none());
+    }
+
+    #[test]
+    fn test_fingerprint_hex_roundtrip() {
+        let fingerprint = TextFingerprint::from_text(&sample_text(3, 100)).unwrap();
+        let hex = fingerprint.to_hex();
+        assert_eq!(hex.len(), SIGNATURE_SIZE * 16);
+        assert_eq!(TextFingerprint::from_hex(&hex), Some(fingerprint));
+        assert_eq!(TextFingerprint::from_hex(""), None);
+        assert_eq!(
+            TextFingerprint::from_hex(&"z".repeat(SIGNATURE_SIZE * 16)),
+            None
+        );
+    }
+}
@@ -0,0 +1,238 @@
+//! Round-trip tests for the OPF editor over a corpus of sample OPF files
+//!
+//! Every file in `tests/fixtures/opf/` is edited with `modify_opf_xml` and checked for:
+//! - fields missing from the metadata being removed, apart from title and language
+//! - fields cleared after an edit disappearing from the file
+//! - the new metadata being read back by the EPUB extractor
+//! - lines marked `<!-- keep -->` (metadata ritmo does not own) surviving the edit
+//! - idempotence: editing the result again gives the same OPF
+
+use ritmo_core::epub_opf_modifier::{modify_opf_xml, OPFIdentifier, OPFMetadata, OPFPerson};
+use ritmo_core::extractors::parse_opf;
+use std::fs;
+use std::path::PathBuf;
+
+const KEEP_MARKER: &str = "<!-- keep -->";
+
+fn corpus() -> Vec<(String, String)> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/opf");
+    let mut files: Vec<(String, String)> = fs::read_dir(dir)
+        .unwrap()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == "opf"))
+        .map(|path| {
+            let name = path.file_name().unwrap().to_string_lossy().to_string();
+            (name, fs::read_to_string(&path).unwrap())
+        })
+        .collect();
+    files.sort();
+    assert!(!files.is_empty(), "empty OPF corpus");
+    files
+}
+
+fn empty_metadata() -> OPFMetadata {
+    OPFMetadata {
+        title: None,
+        creators: vec![],
+        contributors: vec![],
+        publisher: None,
+        date: None,
+        identifiers: vec![],
+        subjects: vec![],
+        languages: vec![],
+        series: None,
+        series_index: None,
+        pages: None,
+        notes: None,
+    }
+}
+
+fn full_metadata() -> OPFMetadata {
+    OPFMetadata {
+        title: Some("Nuovo titolo & co".to_string()),
+        creators: vec![OPFPerson {
+            name: "Italo Calvino".to_string(),
+            role: "aut".to_string(),
+        }],
+        contributors: vec![OPFPerson {
+            name: "Mario Rossi".to_string(),
+            role: "trl".to_string(),
+        }],
+        publisher: Some("Einaudi".to_string()),
+        date: Some("1979-01-01".to_string()),
+        identifiers: vec![OPFIdentifier {
+            scheme: "ISBN".to_string(),
+            value: "9788806148638".to_string(),
+        }],
+        subjects: vec!["Romanzo".to_string()],
+        languages: vec!["it".to_string()],
+        series: Some("Opere".to_string()),
+        series_index: Some(3.0),
+        pages: None,
+        notes: None,
+    }
+}
+
+/// Removes the `dcterms:modified` element (and its leading whitespace), whose value changes on every edit
+fn strip_modified(opf: &str) -> String {
+    let open = r#"<meta property="dcterms:modified">"#;
+    let Some(start) = opf.find(open) else {
+        return opf.to_string();
+    };
+    let end = start + opf[start..].find("</meta>").unwrap() + "</meta>".len();
+    let start = opf[..start].trim_end().len();
+    format!("{}{}", &opf[..start], &opf[end..])
+}
+
+#[test]
+fn test_empty_metadata_clears_owned_fields() {
+    for (name, opf) in corpus() {
+        let edited = modify_opf_xml(&opf, &empty_metadata()).unwrap();
+        let original = parse_opf(&opf).unwrap();
+        let meta = parse_opf(&edited).unwrap_or_else(|e| panic!("{}: {}", name, e));
+
+        // Required by the EPUB spec: kept without a new value
+        assert_eq!(meta.title, original.title, "{}", name);
+        assert_eq!(meta.languages.len(), original.languages.len(), "{}", name);
+
+        assert_eq!(meta.publisher, None, "{}", name);
+        assert_eq!(meta.year, None, "{}", name);
+        assert_eq!(meta.series, None, "{}", name);
+        assert!(meta.tags.is_empty(), "{}", name);
+        assert!(meta.people.is_empty(), "{}", name);
+        assert!(meta.book_people.is_empty(), "{}", name);
+        for line in opf.lines().filter(|l| l.contains(KEEP_MARKER)) {
+            assert!(edited.contains(line.trim()), "{}: lost '{}'", name, line.trim());
+        }
+    }
+}
+
+#[test]
+fn test_cleared_fields_are_removed() {
+    let cleared = OPFMetadata {
+        publisher: None,
+        subjects: vec![],
+        ..full_metadata()
+    };
+    for (name, opf) in corpus() {
+        let synced = modify_opf_xml(&opf, &full_metadata()).unwrap();
+        let edited = modify_opf_xml(&synced, &cleared).unwrap();
+        let meta = parse_opf(&edited).unwrap_or_else(|e| panic!("{}: {}", name, e));
+
+        assert_eq!(meta.publisher, None, "{}", name);
+        assert!(meta.tags.is_empty(), "{}", name);
+        assert!(!edited.contains("Einaudi"), "{}", name);
+        assert!(!edited.contains("<dc:subject>"), "{}", name);
+        // The other fields are untouched
+        assert_eq!(meta.title.as_deref(), Some("Nuovo titolo & co"), "{}", name);
+        assert_eq!(meta.series.as_deref(), Some("Opere"), "{}", name);
+        assert_eq!(meta.people.len() + meta.book_people.len(), 2, "{}", name);
+    }
+}
+
+#[test]
+fn test_metadata_read_back() {
+    for (name, opf) in corpus() {
+        let edited = modify_opf_xml(&opf, &full_metadata()).unwrap();
+        let meta = parse_opf(&edited).unwrap_or_else(|e| panic!("{}: {}", name, e));
+
+        assert_eq!(meta.title.as_deref(), Some("Nuovo titolo & co"), "{}", name);
+        assert_eq!(meta.publisher.as_deref(), Some("Einaudi"), "{}", name);
+        assert_eq!(meta.year, Some(1979), "{}", name);
+        // The ISBN is checked in the XML: an ISBN used as unique-identifier is kept and read first
+        assert!(edited.contains("9788806148638"), "{}: ISBN missing", name);
+        assert_eq!(meta.series.as_deref(), Some("Opere"), "{}", name);
+        assert_eq!(meta.series_index, Some(3), "{}", name);
+        assert_eq!(meta.tags, vec!["Romanzo"], "{}", name);
+        assert_eq!(meta.languages.len(), 1, "{}", name);
+        assert_eq!(meta.languages[0].code, "it", "{}", name);
+
+        let people: Vec<(&str, &str)> = meta
+            .people
+            .iter()
+            .map(|p| (p.name.as_str(), p.role.as_str()))
+            .collect();
+        assert_eq!(
+            people,
+            vec![
+                ("Italo Calvino", "role.author"),
+                ("Mario Rossi", "role.translator")
+            ],
+            "{}",
+            name
+        );
+        assert!(edited.contains("Calvino, Italo"), "{}: file-as missing", name);
+    }
+}
+
+#[test]
+fn test_unowned_metadata_is_kept() {
+    for (name, opf) in corpus() {
+        let edited = modify_opf_xml(&opf, &full_metadata()).unwrap();
+        for line in opf.lines().filter(|l| l.contains(KEEP_MARKER)) {
+            assert!(edited.contains(line.trim()), "{}: lost '{}'", name, line.trim());
+        }
+
+        // Everything after the metadata section is copied byte for byte
+        let tail = &opf[opf.find("</metadata>").unwrap()..];
+        assert!(edited.ends_with(tail), "{}: manifest/spine changed", name);
+    }
+}
+
+#[test]
+fn test_edit_is_idempotent() {
+    for (name, opf) in corpus() {
+        let once = modify_opf_xml(&opf, &full_metadata()).unwrap();
+        let twice = modify_opf_xml(&once, &full_metadata()).unwrap();
+        assert_eq!(strip_modified(&twice), strip_modified(&once), "{}", name);
+    }
+}
+
+#[test]
+fn test_epub3_output() {
+    let opf = fs::read_to_string(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/opf/epub3_refines.opf"),
+    )
+    .unwrap();
+    let edited = modify_opf_xml(&opf, &full_metadata()).unwrap();
+
+    // Old people, title and series are gone together with their refinements
+    assert!(!edited.contains("Isaac Asimov"));
+    assert!(!edited.contains(r##"refines="#c1""##));
+    assert!(!edited.contains(r##"refines="#t1""##));
+    assert!(!edited.contains("Foundation Series"));
+    assert!(!edited.contains("9780553293357"));
+    assert!(!edited.contains("2020-01-01T00:00:00Z"));
+
+    // EPUB3 refinements and collections
+    assert!(edited.contains(r#"<dc:creator id="creator01">Italo Calvino</dc:creator>"#));
+    assert!(edited.contains(
+        r##"<meta refines="#creator01" property="role" scheme="marc:relators">aut</meta>"##
+    ));
+    assert!(edited.contains(r##"<meta refines="#creator01" property="file-as">Calvino, Italo</meta>"##));
+    assert!(edited.contains(r#"<meta property="belongs-to-collection" id="series01">Opere</meta>"#));
+    assert!(edited.contains(r##"<meta refines="#series01" property="group-position">3</meta>"##));
+    assert!(edited.contains(r#"<meta name="calibre:series" content="Opere"/>"#));
+    assert!(edited.contains(r#"<dc:identifier>urn:isbn:9788806148638</dc:identifier>"#));
+    assert_eq!(edited.matches("dcterms:modified").count(), 1);
+    assert!(!edited.contains("opf:role"));
+}
+
+#[test]
+fn test_epub2_output() {
+    let opf = fs::read_to_string(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/opf/epub2_no_opf_namespace.opf"),
+    )
+    .unwrap();
+    let edited = modify_opf_xml(&opf, &full_metadata()).unwrap();
+
+    assert!(edited.contains(r#"xmlns:opf="http://www.idpf.org/2007/opf""#));
+    assert!(edited.contains(
+        r#"<dc:creator opf:role="aut" opf:file-as="Calvino, Italo">Italo Calvino</dc:creator>"#
+    ));
+    assert!(edited.contains(r#"<dc:identifier opf:scheme="ISBN">9788806148638</dc:identifier>"#));
+    assert!(!edited.contains("Luigi Pirandello"));
+    assert!(!edited.contains("dcterms:modified"));
+    assert!(!edited.contains("belongs-to-collection"));
+}
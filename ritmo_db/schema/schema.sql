@@ -135,6 +135,7 @@ CREATE TABLE IF NOT EXISTS "books" (
 	"publication_date"	INTEGER,
 	"last_modified_date"	INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
 	"isbn"	TEXT,
+	"isbn13"	TEXT,
 	"pages"	INTEGER CHECK("pages" > 0),
 	"notes"	TEXT,
 	"has_cover"	INTEGER NOT NULL DEFAULT 0 CHECK("has_cover" IN (0, 1)),
@@ -148,6 +149,19 @@ CREATE TABLE IF NOT EXISTS "books" (
 	FOREIGN KEY("publisher_id") REFERENCES "publishers"("id") ON DELETE SET NULL,
 	FOREIGN KEY("series_id") REFERENCES "series"("id") ON DELETE SET NULL
 );
+CREATE TABLE IF NOT EXISTS "book_files" (
+	"id"	INTEGER,
+	"book_id"	INTEGER NOT NULL,
+	"format_id"	INTEGER,
+	"file_link"	TEXT NOT NULL UNIQUE,
+	"file_hash"	TEXT NOT NULL,
+	"file_size"	INTEGER,
+	"added_at"	INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+	"text_fingerprint"	TEXT,
+	PRIMARY KEY("id" AUTOINCREMENT),
+	FOREIGN KEY("book_id") REFERENCES "books"("id") ON DELETE CASCADE,
+	FOREIGN KEY("format_id") REFERENCES "formats"("id") ON DELETE SET NULL
+);
 CREATE TABLE IF NOT EXISTS "contents" (
 	"id"	INTEGER,
 	"name"	TEXT NOT NULL,
@@ -215,6 +229,52 @@ CREATE TABLE IF NOT EXISTS "pending_metadata_sync" (
 	PRIMARY KEY("id" AUTOINCREMENT),
 	FOREIGN KEY("book_id") REFERENCES "books"("id") ON DELETE CASCADE
 );
+CREATE TABLE IF NOT EXISTS "metadata_sync_snapshots" (
+	"book_id"	INTEGER NOT NULL,
+	"snapshot"	TEXT NOT NULL,
+	"synced_at"	INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+	PRIMARY KEY("book_id"),
+	FOREIGN KEY("book_id") REFERENCES "books"("id") ON DELETE CASCADE
+);
+CREATE TABLE IF NOT EXISTS "metadata_provider_cache" (
+	"id"	INTEGER,
+	"provider"	TEXT NOT NULL,
+	"query_key"	TEXT NOT NULL,
+	"response"	TEXT,
+	"fetched_at"	INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+	PRIMARY KEY("id" AUTOINCREMENT),
+	UNIQUE("provider","query_key")
+);
+CREATE TABLE IF NOT EXISTS "shelves" (
+	"id"	INTEGER,
+	"name"	TEXT NOT NULL UNIQUE,
+	"description"	TEXT,
+	"kind"	TEXT NOT NULL DEFAULT 'manual' CHECK("kind" IN ('smart', 'manual')),
+	"filter_json"	TEXT,
+	"created_at"	INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+	"updated_at"	INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+	PRIMARY KEY("id" AUTOINCREMENT)
+);
+CREATE TABLE IF NOT EXISTS "x_shelves_books" (
+	"shelf_id"	INTEGER NOT NULL,
+	"book_id"	INTEGER NOT NULL,
+	"position"	INTEGER NOT NULL,
+	"notes"	TEXT,
+	"added_at"	INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+	PRIMARY KEY("shelf_id","book_id"),
+	FOREIGN KEY("shelf_id") REFERENCES "shelves"("id") ON DELETE CASCADE,
+	FOREIGN KEY("book_id") REFERENCES "books"("id") ON DELETE CASCADE
+);
+CREATE TABLE IF NOT EXISTS "merge_journal" (
+	"id"	INTEGER,
+	"entity_type"	TEXT NOT NULL CHECK("entity_type" IN ('people', 'publishers', 'series', 'tags', 'roles', 'contents')),
+	"primary_id"	INTEGER NOT NULL,
+	"stats"	TEXT NOT NULL,
+	"payload"	TEXT NOT NULL,
+	"created_at"	INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+	"undone_at"	INTEGER,
+	PRIMARY KEY("id" AUTOINCREMENT)
+);
 CREATE TABLE IF NOT EXISTS "metadata" (
 	"version"		TEXT NOT NULL,
 	"updated_at"  	INTEGER NOT NULL,
@@ -298,6 +358,19 @@ CREATE INDEX IF NOT EXISTS "idx_books_file_info" ON "books" (
 	"file_size",
 	"file_hash"
 ) WHERE "file_link" IS NOT NULL;
+CREATE INDEX IF NOT EXISTS "idx_books_isbn13" ON "books" (
+	"isbn13"
+) WHERE "isbn13" IS NOT NULL;
+CREATE INDEX IF NOT EXISTS "idx_book_files_book_lookup" ON "book_files" (
+	"book_id",
+	"format_id"
+);
+CREATE INDEX IF NOT EXISTS "idx_book_files_hash" ON "book_files" (
+	"file_hash"
+);
+CREATE INDEX IF NOT EXISTS "idx_shelves_books_book_lookup" ON "x_shelves_books" (
+	"book_id"
+);
 CREATE INDEX IF NOT EXISTS "idx_books_dates_combined" ON "books" (
 	"publication_date",
 	"created_at",
@@ -365,6 +438,12 @@ CREATE TRIGGER update_series_timestamp
 BEGIN
     UPDATE series SET updated_at = strftime('%s', 'now') WHERE id = NEW.id;
 END;
+CREATE TRIGGER update_shelves_timestamp
+    AFTER UPDATE ON shelves
+    FOR EACH ROW
+BEGIN
+    UPDATE shelves SET updated_at = strftime('%s', 'now') WHERE id = NEW.id;
+END;
 CREATE TRIGGER update_publishers_timestamp
     AFTER UPDATE ON publishers
     FOR EACH ROW
@@ -686,4 +765,5 @@ SELECT
     0 as with_paper,
     0 as dummy_field
 FROM series;
+PRAGMA user_version = 1;
 COMMIT;
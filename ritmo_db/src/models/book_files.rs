@@ -0,0 +1,119 @@
+use sqlx::FromRow;
+
+/// Un file fisico associato a un libro.
+///
+/// Lo stesso libro (edizione) può avere più file in formati diversi (EPUB, PDF, MOBI...).
+/// Le colonne `file_link`/`file_hash`/`format_id` di `books` restano il file "principale"
+/// (il primo importato), mentre `book_files` contiene tutti i file, principale incluso.
+#[derive(Debug, Clone, FromRow)]
+pub struct BookFile {
+    pub id: Option<i64>,
+    pub book_id: i64,
+    pub format_id: Option<i64>,
+    pub file_link: String,
+    pub file_hash: String,
+    pub file_size: Option<i64>,
+    pub added_at: i64,
+    /// Impronta MinHash del testo della spine (solo EPUB, esadecimale); stringa
+    /// vuota se il testo è troppo breve per calcolarla
+    pub text_fingerprint: Option<String>,
+}
+
+impl BookFile {
+    pub async fn save(&self, pool: &sqlx::SqlitePool) -> Result<i64, sqlx::Error> {
+        let now = chrono::Utc::now().timestamp();
+        let result = sqlx::query!(
+            "INSERT INTO book_files (book_id, format_id, file_link, file_hash, file_size, added_at, text_fingerprint)
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+            self.book_id,
+            self.format_id,
+            self.file_link,
+            self.file_hash,
+            self.file_size,
+            now,
+            self.text_fingerprint
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    pub async fn get(pool: &sqlx::SqlitePool, id: i64) -> Result<Option<BookFile>, sqlx::Error> {
+        let file = sqlx::query_as!(BookFile, "SELECT * FROM book_files WHERE id = ?", id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(file)
+    }
+
+    /// Tutti i file di un libro, in ordine di aggiunta
+    pub async fn list_for_book(
+        pool: &sqlx::SqlitePool,
+        book_id: i64,
+    ) -> Result<Vec<BookFile>, sqlx::Error> {
+        let files = sqlx::query_as!(
+            BookFile,
+            "SELECT * FROM book_files WHERE book_id = ? ORDER BY added_at, id",
+            book_id
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(files)
+    }
+
+    pub async fn get_by_hash(
+        pool: &sqlx::SqlitePool,
+        file_hash: &str,
+    ) -> Result<Option<BookFile>, sqlx::Error> {
+        let file = sqlx::query_as!(
+            BookFile,
+            "SELECT * FROM book_files WHERE file_hash = ? LIMIT 1",
+            file_hash
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(file)
+    }
+
+    /// Aggiorna hash, path e dimensione dopo una riscrittura del file (es. sync metadati)
+    pub async fn update_file_info(
+        pool: &sqlx::SqlitePool,
+        id: i64,
+        file_link: &str,
+        file_hash: &str,
+        file_size: Option<i64>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "UPDATE book_files SET file_link = ?, file_hash = ?, file_size = ? WHERE id = ?",
+            file_link,
+            file_hash,
+            file_size,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Salva l'impronta del testo calcolata dopo l'import
+    pub async fn set_text_fingerprint(
+        pool: &sqlx::SqlitePool,
+        id: i64,
+        text_fingerprint: &str,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "UPDATE book_files SET text_fingerprint = ? WHERE id = ?",
+            text_fingerprint,
+            id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn delete(pool: &sqlx::SqlitePool, id: i64) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM book_files WHERE id = ?", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -11,6 +11,8 @@ pub struct Book {
     pub publication_date: Option<i64>,
     pub last_modified_date: i64,
     pub isbn: Option<String>,
+    /// ISBN-13 canonico senza separatori (None se `isbn` manca o non è valido)
+    pub isbn13: Option<String>,
     pub pages: Option<i64>,
     pub notes: Option<String>,
     pub has_cover: i64,
@@ -27,9 +29,9 @@ impl Book {
         let result = sqlx::query!(
             "INSERT INTO books (
                 name, original_title, publisher_id, format_id, series_id, series_index,
-                publication_date, last_modified_date, isbn, notes,
+                publication_date, last_modified_date, isbn, isbn13, pages, notes,
                 has_cover, has_paper, file_link, file_size, file_hash, created_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
             self.name,
             self.original_title,
             self.publisher_id,
@@ -39,6 +41,8 @@ impl Book {
             self.publication_date,
             now,
             self.isbn,
+            self.isbn13,
+            self.pages,
             self.notes,
             self.has_cover,
             self.has_paper,
@@ -65,7 +69,7 @@ impl Book {
             "UPDATE books SET
                 name = ?, original_title = ?, publisher_id = ?, format_id = ?, series_id = ?,
                 series_index = ?, publication_date = ?, last_modified_date = ?, isbn = ?,
-                pages = ?, notes = ?, has_cover = ?, has_paper = ?, file_link = ?,
+                isbn13 = ?, pages = ?, notes = ?, has_cover = ?, has_paper = ?, file_link = ?,
                 file_size = ?, file_hash = ?
             WHERE id = ?",
             self.name,
@@ -77,6 +81,7 @@ impl Book {
             self.publication_date,
             now,
             self.isbn,
+            self.isbn13,
             self.pages,
             self.notes,
             self.has_cover,
@@ -120,4 +125,25 @@ impl Book {
         Ok(found)
     }
 
+    /// Cerca un libro per ISBN (usato per agganciare nuovi file a un libro esistente)
+    ///
+    /// `isbn13` è la forma canonica, se l'ISBN è valido: permette di trovare il
+    /// libro anche quando è stato salvato come ISBN-10 o con altri separatori.
+    pub async fn get_by_isbn(
+        pool: &sqlx::SqlitePool,
+        isbn: &str,
+        isbn13: Option<&str>,
+    ) -> Result<Option<Book>, sqlx::Error> {
+        let book = sqlx::query_as!(
+            Book,
+            "SELECT * FROM books WHERE isbn = ? OR (? IS NOT NULL AND isbn13 = ?) ORDER BY id LIMIT 1",
+            isbn,
+            isbn13,
+            isbn13
+        )
+            .fetch_optional(pool)
+            .await?;
+        Ok(book)
+    }
+
 }
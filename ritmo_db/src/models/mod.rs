@@ -8,15 +8,18 @@
 /// La sequenza quindi è:
 /// User -> DTO data -> ML -> Models data
 pub mod aliases;
+pub mod book_files;
 pub mod books;
 pub mod contents;
 pub mod formats;
 pub mod languages;
 pub mod pending_sync;
+pub mod provider_cache;
 pub mod people;
 pub mod publishers;
 pub mod roles;
 pub mod series;
+pub mod shelves;
 pub mod tags;
 pub mod types;
 pub mod x_books_contents;
@@ -25,17 +28,21 @@ pub mod x_books_tags;
 pub mod x_contents_languages;
 pub mod x_contents_people_roles;
 pub mod x_contents_tags;
+pub mod x_shelves_books;
 
 pub use self::aliases::*;
+pub use self::book_files::*;
 pub use self::books::*;
 pub use self::contents::*;
 pub use self::formats::*;
 pub use self::languages::*;
 pub use self::pending_sync::*;
+pub use self::provider_cache::*;
 pub use self::people::*;
 pub use self::publishers::*;
 pub use self::roles::*;
 pub use self::series::*;
+pub use self::shelves::*;
 pub use self::tags::*;
 pub use self::types::*;
 pub use self::x_books_contents::*;
@@ -44,6 +51,7 @@ pub use self::x_books_tags::*;
 pub use self::x_contents_languages::*;
 pub use self::x_contents_people_roles::*;
 pub use self::x_contents_tags::*;
+pub use self::x_shelves_books::*;
 
 #[derive(Debug, Clone)]
 pub struct FullBook {
@@ -54,3 +54,38 @@ pub async fn clear_sync_mark(pool: &SqlitePool, book_id: i64) -> RitmoResult<()>
         .await?;
     Ok(())
 }
+
+/// Metadati scritti nei file all'ultima sincronizzazione di un libro
+#[derive(Debug, Clone)]
+pub struct SyncSnapshot {
+    /// JSON dei metadati confrontabili (vedi `metadata_diff_service` in ritmo_core)
+    pub snapshot: String,
+    pub synced_at: i64,
+}
+
+/// Salva (o sostituisce) lo snapshot dell'ultima sincronizzazione
+pub async fn save_sync_snapshot(pool: &SqlitePool, book_id: i64, snapshot: &str) -> RitmoResult<()> {
+    let now = chrono::Utc::now().timestamp();
+    sqlx::query!(
+        "INSERT INTO metadata_sync_snapshots (book_id, snapshot, synced_at) VALUES (?, ?, ?)
+         ON CONFLICT(book_id) DO UPDATE SET snapshot = excluded.snapshot, synced_at = excluded.synced_at",
+        book_id,
+        snapshot,
+        now
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Snapshot dell'ultima sincronizzazione, se il libro è mai stato sincronizzato
+pub async fn get_sync_snapshot(pool: &SqlitePool, book_id: i64) -> RitmoResult<Option<SyncSnapshot>> {
+    let snapshot = sqlx::query_as!(
+        SyncSnapshot,
+        "SELECT snapshot, synced_at FROM metadata_sync_snapshots WHERE book_id = ?",
+        book_id
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(snapshot)
+}
@@ -139,6 +139,16 @@ impl Person {
     pub async fn get_or_create_by_name(
         pool: &sqlx::SqlitePool,
         name: &str,
+    ) -> Result<i64, sqlx::Error> {
+        Self::get_or_create_with_source(pool, name, "manual_import").await
+    }
+
+    /// Come `get_or_create_by_name`, registrando in `source` da dove arriva la
+    /// persona se viene creata (es. "openlibrary"); una persona esistente non cambia
+    pub async fn get_or_create_with_source(
+        pool: &sqlx::SqlitePool,
+        name: &str,
+        source: &str,
     ) -> Result<i64, sqlx::Error> {
         if let Some(person) = Self::get_by_name(pool, name).await? {
             return Ok(person.id.unwrap_or(0));
@@ -158,7 +168,7 @@ impl Person {
             biography: None,
             normalized_key: None,
             confidence: 0.5,
-            source: "manual_import".to_string(),
+            source: source.to_string(),
             verified: 0,
             created_at: chrono::Utc::now().timestamp(),
             updated_at: chrono::Utc::now().timestamp(),
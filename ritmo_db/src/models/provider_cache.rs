@@ -0,0 +1,61 @@
+use ritmo_errors::RitmoResult;
+use sqlx::SqlitePool;
+
+/// Risposta in cache di un fornitore di metadati online
+#[derive(Debug, Clone)]
+pub struct ProviderCacheEntry {
+    /// JSON del risultato normalizzato; None se il fornitore non ha trovato nulla
+    pub response: Option<String>,
+    pub fetched_at: i64,
+}
+
+/// Legge la risposta in cache per un fornitore e una chiave di ricerca
+pub async fn get_provider_cache(
+    pool: &SqlitePool,
+    provider: &str,
+    query_key: &str,
+) -> RitmoResult<Option<ProviderCacheEntry>> {
+    let entry = sqlx::query_as!(
+        ProviderCacheEntry,
+        "SELECT response, fetched_at FROM metadata_provider_cache WHERE provider = ? AND query_key = ?",
+        provider,
+        query_key
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(entry)
+}
+
+/// Salva (o sostituisce) la risposta di un fornitore
+pub async fn put_provider_cache(
+    pool: &SqlitePool,
+    provider: &str,
+    query_key: &str,
+    response: Option<&str>,
+) -> RitmoResult<()> {
+    let now = chrono::Utc::now().timestamp();
+    sqlx::query!(
+        "INSERT INTO metadata_provider_cache (provider, query_key, response, fetched_at)
+         VALUES (?, ?, ?, ?)
+         ON CONFLICT(provider, query_key) DO UPDATE SET response = excluded.response, fetched_at = excluded.fetched_at",
+        provider,
+        query_key,
+        response,
+        now
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Svuota la cache, per un solo fornitore o per tutti; restituisce le righe eliminate
+pub async fn clear_provider_cache(pool: &SqlitePool, provider: Option<&str>) -> RitmoResult<u64> {
+    let result = sqlx::query!(
+        "DELETE FROM metadata_provider_cache WHERE ? IS NULL OR provider = ?",
+        provider,
+        provider
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,93 @@
+use sqlx::FromRow;
+
+/// Uno scaffale (collezione) della libreria.
+///
+/// Gli scaffali `smart` salvano una definizione di filtro (`filter_json`, un preset
+/// libri serializzato) che viene valutata a ogni lettura; gli scaffali `manual`
+/// contengono una lista ordinata di libri in `x_shelves_books`.
+#[derive(Debug, Clone, FromRow)]
+pub struct Shelf {
+    pub id: Option<i64>,
+    pub name: String,
+    pub description: Option<String>,
+    pub kind: String,
+    pub filter_json: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl Shelf {
+    pub const KIND_SMART: &'static str = "smart";
+    pub const KIND_MANUAL: &'static str = "manual";
+
+    pub fn is_smart(&self) -> bool {
+        self.kind == Self::KIND_SMART
+    }
+
+    pub async fn save(&self, pool: &sqlx::SqlitePool) -> Result<i64, sqlx::Error> {
+        let now = chrono::Utc::now().timestamp();
+        let result = sqlx::query!(
+            "INSERT INTO shelves (name, description, kind, filter_json, created_at, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?)",
+            self.name,
+            self.description,
+            self.kind,
+            self.filter_json,
+            now,
+            now
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.last_insert_rowid())
+    }
+
+    pub async fn get(pool: &sqlx::SqlitePool, id: i64) -> Result<Option<Shelf>, sqlx::Error> {
+        let shelf = sqlx::query_as!(Shelf, "SELECT * FROM shelves WHERE id = ?", id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(shelf)
+    }
+
+    /// Ricerca per nome, senza distinzione tra maiuscole e minuscole
+    pub async fn get_by_name(
+        pool: &sqlx::SqlitePool,
+        name: &str,
+    ) -> Result<Option<Shelf>, sqlx::Error> {
+        let shelf = sqlx::query_as!(
+            Shelf,
+            "SELECT * FROM shelves WHERE name = ? COLLATE NOCASE LIMIT 1",
+            name
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(shelf)
+    }
+
+    pub async fn list_all(pool: &sqlx::SqlitePool) -> Result<Vec<Shelf>, sqlx::Error> {
+        let shelves = sqlx::query_as!(Shelf, "SELECT * FROM shelves ORDER BY name COLLATE NOCASE")
+            .fetch_all(pool)
+            .await?;
+        Ok(shelves)
+    }
+
+    pub async fn update(&self, pool: &sqlx::SqlitePool) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "UPDATE shelves SET name = ?, description = ?, filter_json = ? WHERE id = ?",
+            self.name,
+            self.description,
+            self.filter_json,
+            self.id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Elimina lo scaffale; i collegamenti in `x_shelves_books` sono rimossi in cascata
+    pub async fn delete(pool: &sqlx::SqlitePool, id: i64) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM shelves WHERE id = ?", id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
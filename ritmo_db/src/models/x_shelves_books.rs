@@ -0,0 +1,164 @@
+use sqlx::FromRow;
+
+/// Un libro in uno scaffale manuale, con posizione (da 1) e nota facoltativa
+#[derive(Debug, Clone, FromRow)]
+pub struct ShelfBook {
+    pub shelf_id: i64,
+    pub book_id: i64,
+    pub position: i64,
+    pub notes: Option<String>,
+    pub added_at: i64,
+}
+
+impl ShelfBook {
+    /// Inserisce un libro nello scaffale.
+    ///
+    /// Con `position` il libro viene inserito in quel punto e i successivi scalano
+    /// di una posizione; senza, viene accodato.
+    pub async fn add(
+        pool: &sqlx::SqlitePool,
+        shelf_id: i64,
+        book_id: i64,
+        position: Option<i64>,
+        notes: Option<&str>,
+    ) -> Result<i64, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let count = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM x_shelves_books WHERE shelf_id = ?",
+            shelf_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let position = match position {
+            Some(p) => p.clamp(1, count + 1),
+            None => count + 1,
+        };
+
+        sqlx::query!(
+            "UPDATE x_shelves_books SET position = position + 1 WHERE shelf_id = ? AND position >= ?",
+            shelf_id,
+            position
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let now = chrono::Utc::now().timestamp();
+        sqlx::query!(
+            "INSERT INTO x_shelves_books (shelf_id, book_id, position, notes, added_at)
+             VALUES (?, ?, ?, ?, ?)",
+            shelf_id,
+            book_id,
+            position,
+            notes,
+            now
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(position)
+    }
+
+    /// Rimuove un libro dallo scaffale e compatta le posizioni successive
+    pub async fn remove(
+        pool: &sqlx::SqlitePool,
+        shelf_id: i64,
+        book_id: i64,
+    ) -> Result<u64, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let position = sqlx::query_scalar!(
+            "SELECT position FROM x_shelves_books WHERE shelf_id = ? AND book_id = ?",
+            shelf_id,
+            book_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(position) = position else {
+            return Ok(0);
+        };
+
+        let result = sqlx::query!(
+            "DELETE FROM x_shelves_books WHERE shelf_id = ? AND book_id = ?",
+            shelf_id,
+            book_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE x_shelves_books SET position = position - 1 WHERE shelf_id = ? AND position > ?",
+            shelf_id,
+            position
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected())
+    }
+
+    pub async fn get(
+        pool: &sqlx::SqlitePool,
+        shelf_id: i64,
+        book_id: i64,
+    ) -> Result<Option<ShelfBook>, sqlx::Error> {
+        let item = sqlx::query_as!(
+            ShelfBook,
+            "SELECT * FROM x_shelves_books WHERE shelf_id = ? AND book_id = ?",
+            shelf_id,
+            book_id
+        )
+        .fetch_optional(pool)
+        .await?;
+        Ok(item)
+    }
+
+    /// Libri dello scaffale in ordine di posizione
+    pub async fn list_for_shelf(
+        pool: &sqlx::SqlitePool,
+        shelf_id: i64,
+    ) -> Result<Vec<ShelfBook>, sqlx::Error> {
+        let items = sqlx::query_as!(
+            ShelfBook,
+            "SELECT * FROM x_shelves_books WHERE shelf_id = ? ORDER BY position",
+            shelf_id
+        )
+        .fetch_all(pool)
+        .await?;
+        Ok(items)
+    }
+
+    pub async fn count_for_shelf(
+        pool: &sqlx::SqlitePool,
+        shelf_id: i64,
+    ) -> Result<i64, sqlx::Error> {
+        let count = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM x_shelves_books WHERE shelf_id = ?",
+            shelf_id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(count)
+    }
+
+    pub async fn set_notes(
+        pool: &sqlx::SqlitePool,
+        shelf_id: i64,
+        book_id: i64,
+        notes: Option<&str>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!(
+            "UPDATE x_shelves_books SET notes = ? WHERE shelf_id = ? AND book_id = ?",
+            notes,
+            shelf_id,
+            book_id
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -54,6 +54,12 @@ fn test_all_role_translations() {
         ("role.illustrator", "Illustrator"),
         ("role.contributor", "Contributor"),
         ("role.narrator", "Narrator"),
+        ("role.writer", "Writer"),
+        ("role.penciller", "Penciller"),
+        ("role.inker", "Inker"),
+        ("role.colorist", "Colorist"),
+        ("role.letterer", "Letterer"),
+        ("role.cover_artist", "Cover Artist"),
     ];
 
     for (key, expected_en) in roles.iter() {
@@ -75,6 +81,12 @@ fn test_all_role_translations() {
         ("role.illustrator", "Illustratore"),
         ("role.contributor", "Collaboratore"),
         ("role.narrator", "Narratore"),
+        ("role.writer", "Sceneggiatore"),
+        ("role.penciller", "Disegnatore"),
+        ("role.inker", "Inchiostratore"),
+        ("role.colorist", "Colorista"),
+        ("role.letterer", "Letterista"),
+        ("role.cover_artist", "Copertinista"),
     ];
 
     for (key, expected_it) in roles_it.iter() {
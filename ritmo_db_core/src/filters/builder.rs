@@ -3,7 +3,9 @@
 //! This module contains the logic for building SQL queries from filter structures.
 //! Supports OR logic for multiple values within the same filter type.
 
-use super::types::{BookFilters, ContentFilters};
+use super::query::{QueryExpr, QueryTarget, QueryValue};
+use super::types::{BookFilters, ContentFilters, PersonRoleFilter, PresenceField, TagMatch};
+use crate::isbn::{self, Isbn};
 
 /// Helper function to build OR clauses for multiple values
 /// Returns (sql_clause, params) or None if values is empty
@@ -39,11 +41,72 @@ fn build_or_clause(
     Some((clause, params))
 }
 
+/// Filtri comuni a libri e contenuti
+struct AttributeFilters<'a> {
+    tags: &'a [String],
+    tag_match: TagMatch,
+    languages: &'a [String],
+    language_role: Option<&'a str>,
+    people_roles: &'a [PersonRoleFilter],
+    has: &'a [PresenceField],
+    missing: &'a [PresenceField],
+    pages_min: Option<i64>,
+    pages_max: Option<i64>,
+}
+
+/// Aggiunge le clausole dei filtri comuni (tag, lingue, persone con ruolo,
+/// presenza/assenza, intervallo di pagine)
+fn append_attribute_clauses(
+    target: QueryTarget,
+    filters: AttributeFilters,
+    where_clauses: &mut Vec<String>,
+    params: &mut Vec<String>,
+) {
+    if let Some((clause, mut clause_params)) =
+        build_tags_clause(target, filters.tags, filters.tag_match)
+    {
+        where_clauses.push(clause);
+        params.append(&mut clause_params);
+    }
+
+    if let Some((clause, mut clause_params)) =
+        build_languages_clause(target, filters.languages, filters.language_role)
+    {
+        where_clauses.push(clause);
+        params.append(&mut clause_params);
+    }
+
+    for person in filters.people_roles {
+        let (clause, mut clause_params) = build_person_role_clause(target, person);
+        where_clauses.push(clause);
+        params.append(&mut clause_params);
+    }
+
+    where_clauses.append(&mut build_presence_clauses(
+        target,
+        filters.has,
+        filters.missing,
+    ));
+
+    let pages_column = match target {
+        QueryTarget::Books => "books.pages",
+        QueryTarget::Contents => "contents.pages",
+    };
+    if let Some(min) = filters.pages_min {
+        where_clauses.push(format!("{} >= ?", pages_column));
+        params.push(min.to_string());
+    }
+    if let Some(max) = filters.pages_max {
+        where_clauses.push(format!("{} <= ?", pages_column));
+        params.push(max.to_string());
+    }
+}
+
 /// Costruisce la query SQL per listare libri con filtri
 ///
 /// Supports OR logic for multiple values:
 /// - Multiple authors: (author LIKE '%King%' OR author LIKE '%Tolkien%')
-/// - Multiple formats: (format LIKE '%epub%' OR format LIKE '%pdf%')
+/// - Multiple formats: (format LIKE '%epub%' OR format LIKE '%pdf%'), matched against the book format and any file of the book
 /// - Different filter types are combined with AND
 pub fn build_books_query(filters: &BookFilters) -> (String, Vec<String>) {
     let mut query = String::from(
@@ -53,14 +116,35 @@ pub fn build_books_query(filters: &BookFilters) -> (String, Vec<String>) {
             books.name,
             books.original_title,
             publishers.name as publisher_name,
-            formats.key as format_key,
+            COALESCE(
+                (SELECT GROUP_CONCAT(file_formats.key, ', ')
+                 FROM book_files
+                 JOIN formats AS file_formats ON book_files.format_id = file_formats.id
+                 WHERE book_files.book_id = books.id),
+                formats.key
+            ) as format_key,
             series.name as series_name,
             books.series_index,
             books.publication_date,
             books.isbn,
             books.pages,
             books.file_link,
-            books.created_at
+            books.created_at,
+            (SELECT GROUP_CONCAT(author_people.name, ', ')
+             FROM x_books_people_roles AS author_links
+             JOIN people AS author_people ON author_links.person_id = author_people.id
+             JOIN roles AS author_roles ON author_links.role_id = author_roles.id
+             WHERE author_links.book_id = books.id
+               AND author_roles.key IN ('role.author', 'author')) as authors,
+            (SELECT GROUP_CONCAT(book_tags.name, ', ')
+             FROM x_books_tags
+             JOIN tags AS book_tags ON x_books_tags.tag_id = book_tags.id
+             WHERE x_books_tags.book_id = books.id) as tags,
+            COALESCE(
+                (SELECT SUM(book_files.file_size) FROM book_files
+                 WHERE book_files.book_id = books.id),
+                books.file_size
+            ) as file_size
         FROM books
         LEFT JOIN publishers ON books.publisher_id = publishers.id
         LEFT JOIN formats ON books.format_id = formats.id
@@ -104,11 +188,18 @@ pub fn build_books_query(filters: &BookFilters) -> (String, Vec<String>) {
         params.append(&mut clause_params);
     }
 
-    // Filtro formati (OR logic if multiple)
+    // Filtro formati (OR logic if multiple): un libro corrisponde se il suo
+    // formato (books.format_id) o quello di uno dei suoi file (book_files) è
+    // nel formato richiesto
     if let Some((clause, mut clause_params)) =
-        build_or_clause("formats.key", &filters.formats, true)
+        build_or_clause("file_formats.key", &filters.formats, true)
     {
-        where_clauses.push(clause);
+        where_clauses.push(format!(
+            "EXISTS (SELECT 1 FROM formats AS file_formats WHERE {} \
+             AND (file_formats.id = books.format_id OR file_formats.id IN \
+             (SELECT book_files.format_id FROM book_files WHERE book_files.book_id = books.id)))",
+            clause
+        ));
         params.append(&mut clause_params);
     }
 
@@ -121,8 +212,9 @@ pub fn build_books_query(filters: &BookFilters) -> (String, Vec<String>) {
 
     // Filtro ISBN
     if let Some(isbn) = &filters.isbn {
-        where_clauses.push("books.isbn LIKE ?".to_string());
-        params.push(format!("%{}%", isbn));
+        let (clause, mut clause_params) = isbn_match(isbn);
+        where_clauses.push(clause);
+        params.append(&mut clause_params);
     }
 
     // Ricerca full-text
@@ -148,6 +240,46 @@ pub fn build_books_query(filters: &BookFilters) -> (String, Vec<String>) {
         params.push(acquired_before.to_string());
     }
 
+    // Filtri su tag, lingue, persone con ruolo, presenza e pagine
+    append_attribute_clauses(
+        QueryTarget::Books,
+        AttributeFilters {
+            tags: &filters.tags,
+            tag_match: filters.tag_match,
+            languages: &filters.languages,
+            language_role: filters.language_role.as_deref(),
+            people_roles: &filters.people_roles,
+            has: &filters.has,
+            missing: &filters.missing,
+            pages_min: filters.pages_min,
+            pages_max: filters.pages_max,
+        },
+        &mut where_clauses,
+        &mut params,
+    );
+
+    // Filtro paese dell'editore (OR logic if multiple)
+    if let Some((clause, mut clause_params)) =
+        build_or_clause("publishers.country", &filters.publisher_countries, true)
+    {
+        where_clauses.push(clause);
+        params.append(&mut clause_params);
+    }
+
+    // Scaffale manuale
+    if let Some(shelf_id) = filters.shelf_id {
+        where_clauses
+            .push("books.id IN (SELECT book_id FROM x_shelves_books WHERE shelf_id = ?)".to_string());
+        params.push(shelf_id.to_string());
+    }
+
+    // Query booleana (in AND con gli altri filtri)
+    if let Some(query) = &filters.query {
+        let (clause, mut clause_params) = build_query_clause(query, QueryTarget::Books);
+        where_clauses.push(clause);
+        params.append(&mut clause_params);
+    }
+
     // Aggiungi WHERE se ci sono filtri
     if !where_clauses.is_empty() {
         query.push_str(" WHERE ");
@@ -180,7 +312,17 @@ pub fn build_contents_query(filters: &ContentFilters) -> (String, Vec<String>) {
             types.key as type_key,
             contents.publication_date,
             contents.pages,
-            contents.created_at
+            contents.created_at,
+            (SELECT GROUP_CONCAT(author_people.name, ', ')
+             FROM x_contents_people_roles AS author_links
+             JOIN people AS author_people ON author_links.person_id = author_people.id
+             JOIN roles AS author_roles ON author_links.role_id = author_roles.id
+             WHERE author_links.content_id = contents.id
+               AND author_roles.key IN ('role.author', 'author')) as authors,
+            (SELECT GROUP_CONCAT(content_tags.name, ', ')
+             FROM x_contents_tags
+             JOIN tags AS content_tags ON x_contents_tags.tag_id = content_tags.id
+             WHERE x_contents_tags.content_id = contents.id) as tags
         FROM contents
         LEFT JOIN types ON contents.type_id = types.id
         "#,
@@ -234,6 +376,31 @@ pub fn build_contents_query(filters: &ContentFilters) -> (String, Vec<String>) {
         params.push(search_pattern);
     }
 
+    // Filtri su tag, lingue, persone con ruolo, presenza e pagine
+    append_attribute_clauses(
+        QueryTarget::Contents,
+        AttributeFilters {
+            tags: &filters.tags,
+            tag_match: filters.tag_match,
+            languages: &filters.languages,
+            language_role: filters.language_role.as_deref(),
+            people_roles: &filters.people_roles,
+            has: &filters.has,
+            missing: &filters.missing,
+            pages_min: filters.pages_min,
+            pages_max: filters.pages_max,
+        },
+        &mut where_clauses,
+        &mut params,
+    );
+
+    // Query booleana (in AND con gli altri filtri)
+    if let Some(query) = &filters.query {
+        let (clause, mut clause_params) = build_query_clause(query, QueryTarget::Contents);
+        where_clauses.push(clause);
+        params.append(&mut clause_params);
+    }
+
     // Aggiungi WHERE se ci sono filtri
     if !where_clauses.is_empty() {
         query.push_str(" WHERE ");
@@ -253,9 +420,369 @@ pub fn build_contents_query(filters: &ContentFilters) -> (String, Vec<String>) {
     (query, params)
 }
 
+/// Lingua: codice ISO 639-1/639-3 o nome ufficiale, esatti e senza distinzione
+/// di maiuscole (un confronto per sottostringa farebbe corrispondere "en" a "French")
+fn language_match(language: &str) -> (String, Vec<String>) {
+    (
+        "(q_languages.iso_code_2char = ? COLLATE NOCASE \
+         OR q_languages.iso_code_3char = ? COLLATE NOCASE \
+         OR q_languages.official_name = ? COLLATE NOCASE)"
+            .to_string(),
+        vec![language.to_string(); 3],
+    )
+}
+
+/// ISBN: un ISBN valido corrisponde al libro sia nella forma a 10 sia in quella
+/// a 13 cifre, con o senza trattini; un valore parziale è cercato come
+/// sottostringa delle cifre
+fn isbn_match(value: &str) -> (String, Vec<String>) {
+    // Cifre di books.isbn senza separatori, per i libri senza isbn13 (ISBN non valido o
+    // salvato prima dell'introduzione della colonna)
+    const COMPACT_ISBN: &str = "REPLACE(REPLACE(UPPER(COALESCE(books.isbn, '')), '-', ''), ' ', '')";
+
+    match Isbn::parse(value) {
+        Ok(parsed) => {
+            let isbn13 = parsed.to_isbn13();
+            let isbn10 = parsed.to_isbn10().unwrap_or_else(|| isbn13.clone());
+            (
+                format!("(books.isbn13 = ? OR {} IN (?, ?))", COMPACT_ISBN),
+                vec![isbn13.clone(), isbn13, isbn10],
+            )
+        }
+        Err(_) => {
+            let compact = isbn::compact(value);
+            if compact.is_empty() {
+                return ("books.isbn LIKE ?".to_string(), vec![format!("%{}%", value)]);
+            }
+            let pattern = format!("%{}%", compact);
+            (
+                format!("({} LIKE ? OR COALESCE(books.isbn13, '') LIKE ?)", COMPACT_ISBN),
+                vec![pattern.clone(), pattern],
+            )
+        }
+    }
+}
+
+/// Sottoquery sulle lingue (`q_languages`); per i libri passa dai contenuti
+fn language_subquery(target: QueryTarget, condition: &str) -> String {
+    match target {
+        QueryTarget::Books => format!(
+            "books.id IN (SELECT x_books_contents.book_id FROM x_books_contents \
+             JOIN x_contents_languages ON x_books_contents.content_id = x_contents_languages.content_id \
+             JOIN running_languages AS q_languages ON x_contents_languages.language_id = q_languages.id \
+             WHERE {})",
+            condition
+        ),
+        QueryTarget::Contents => format!(
+            "contents.id IN (SELECT x_contents_languages.content_id FROM x_contents_languages \
+             JOIN running_languages AS q_languages ON x_contents_languages.language_id = q_languages.id \
+             WHERE {})",
+            condition
+        ),
+    }
+}
+
+/// Filtro tag (nome esatto, senza distinzione di maiuscole):
+/// `Any` = una sottoquery con i tag in OR, `All` = una sottoquery per tag in AND
+fn build_tags_clause(
+    target: QueryTarget,
+    tags: &[String],
+    tag_match: TagMatch,
+) -> Option<(String, Vec<String>)> {
+    if tags.is_empty() {
+        return None;
+    }
+
+    let subquery = |condition: &str| match target {
+        QueryTarget::Books => format!(
+            "books.id IN (SELECT x_books_tags.book_id FROM x_books_tags \
+             JOIN tags AS q_tags ON x_books_tags.tag_id = q_tags.id WHERE {})",
+            condition
+        ),
+        QueryTarget::Contents => format!(
+            "contents.id IN (SELECT x_contents_tags.content_id FROM x_contents_tags \
+             JOIN tags AS q_tags ON x_contents_tags.tag_id = q_tags.id WHERE {})",
+            condition
+        ),
+    };
+
+    let clause = match tag_match {
+        TagMatch::Any => subquery(
+            &vec!["q_tags.name = ? COLLATE NOCASE"; tags.len()].join(" OR "),
+        ),
+        TagMatch::All if tags.len() == 1 => subquery("q_tags.name = ? COLLATE NOCASE"),
+        TagMatch::All => format!(
+            "({})",
+            vec![subquery("q_tags.name = ? COLLATE NOCASE"); tags.len()].join(" AND ")
+        ),
+    };
+
+    Some((clause, tags.to_vec()))
+}
+
+/// Filtro lingue (OR logic), eventualmente ristretto a un ruolo lingua
+fn build_languages_clause(
+    target: QueryTarget,
+    languages: &[String],
+    language_role: Option<&str>,
+) -> Option<(String, Vec<String>)> {
+    let mut conditions = Vec::new();
+    let mut params = Vec::new();
+
+    if !languages.is_empty() {
+        let mut matches = Vec::new();
+        for language in languages {
+            let (condition, mut condition_params) = language_match(language);
+            matches.push(condition);
+            params.append(&mut condition_params);
+        }
+        conditions.push(if matches.len() == 1 {
+            matches.remove(0)
+        } else {
+            format!("({})", matches.join(" OR "))
+        });
+    }
+
+    if let Some(role) = language_role {
+        conditions.push("q_languages.language_role = ?".to_string());
+        params.push(role.to_string());
+    }
+
+    if conditions.is_empty() {
+        return None;
+    }
+
+    Some((language_subquery(target, &conditions.join(" AND ")), params))
+}
+
+/// Filtro persona con ruolo: per i libri considera anche le persone dei contenuti
+fn build_person_role_clause(
+    target: QueryTarget,
+    person: &PersonRoleFilter,
+) -> (String, Vec<String>) {
+    let (bare_role, prefixed_role) = person.role_keys();
+    let person_params = vec![format!("%{}%", person.name), bare_role, prefixed_role];
+    let condition = "q_people.name LIKE ? AND q_roles.key COLLATE NOCASE IN (?, ?)";
+
+    let contents_select = format!(
+        "SELECT x_contents_people_roles.content_id FROM x_contents_people_roles \
+         JOIN people AS q_people ON x_contents_people_roles.person_id = q_people.id \
+         JOIN roles AS q_roles ON x_contents_people_roles.role_id = q_roles.id \
+         WHERE {}",
+        condition
+    );
+
+    match target {
+        QueryTarget::Books => (
+            format!(
+                "books.id IN (SELECT x_books_people_roles.book_id FROM x_books_people_roles \
+                 JOIN people AS q_people ON x_books_people_roles.person_id = q_people.id \
+                 JOIN roles AS q_roles ON x_books_people_roles.role_id = q_roles.id \
+                 WHERE {} \
+                 UNION SELECT x_books_contents.book_id FROM x_books_contents \
+                 WHERE x_books_contents.content_id IN ({}))",
+                condition, contents_select
+            ),
+            [person_params.clone(), person_params].concat(),
+        ),
+        QueryTarget::Contents => (format!("contents.id IN ({})", contents_select), person_params),
+    }
+}
+
+/// Condizione vera quando il campo è presente (None se il campo non si applica)
+fn presence_condition(target: QueryTarget, field: PresenceField) -> Option<&'static str> {
+    let condition = match (target, field) {
+        (QueryTarget::Books, PresenceField::Isbn) => "COALESCE(TRIM(books.isbn), '') <> ''",
+        (QueryTarget::Books, PresenceField::Cover) => "books.has_cover = 1",
+        (QueryTarget::Books, PresenceField::File) => {
+            "(books.file_link IS NOT NULL \
+             OR EXISTS (SELECT 1 FROM book_files WHERE book_files.book_id = books.id))"
+        }
+        (QueryTarget::Books, PresenceField::Series) => "books.series_id IS NOT NULL",
+        (QueryTarget::Books, PresenceField::Publisher) => "books.publisher_id IS NOT NULL",
+        (QueryTarget::Books, PresenceField::Author) => {
+            "(EXISTS (SELECT 1 FROM x_books_people_roles WHERE x_books_people_roles.book_id = books.id) \
+             OR EXISTS (SELECT 1 FROM x_books_contents \
+                        JOIN x_contents_people_roles ON x_books_contents.content_id = x_contents_people_roles.content_id \
+                        WHERE x_books_contents.book_id = books.id))"
+        }
+        (QueryTarget::Books, PresenceField::Language) => {
+            "EXISTS (SELECT 1 FROM x_books_contents \
+             JOIN x_contents_languages ON x_books_contents.content_id = x_contents_languages.content_id \
+             WHERE x_books_contents.book_id = books.id)"
+        }
+        (QueryTarget::Books, PresenceField::Pages) => "books.pages IS NOT NULL",
+        (QueryTarget::Contents, PresenceField::Author) => {
+            "EXISTS (SELECT 1 FROM x_contents_people_roles \
+             WHERE x_contents_people_roles.content_id = contents.id)"
+        }
+        (QueryTarget::Contents, PresenceField::Language) => {
+            "EXISTS (SELECT 1 FROM x_contents_languages \
+             WHERE x_contents_languages.content_id = contents.id)"
+        }
+        (QueryTarget::Contents, PresenceField::Type) => "contents.type_id IS NOT NULL",
+        (QueryTarget::Contents, PresenceField::Pages) => "contents.pages IS NOT NULL",
+        _ => return None,
+    };
+    Some(condition)
+}
+
+/// Filtri di presenza/assenza, uno per campo in AND
+fn build_presence_clauses(
+    target: QueryTarget,
+    has: &[PresenceField],
+    missing: &[PresenceField],
+) -> Vec<String> {
+    let present = has
+        .iter()
+        .filter_map(|field| presence_condition(target, *field).map(String::from));
+    let absent = missing
+        .iter()
+        .filter_map(|field| presence_condition(target, *field).map(|c| format!("NOT ({})", c)));
+    present.chain(absent).collect()
+}
+
+/// Compila un'espressione della query language in una clausola WHERE parametrizzata
+///
+/// Ogni termine è scritto in modo da non valere mai NULL (campi testuali con
+/// `COALESCE`, numerici con `IS NOT NULL`), così `NOT` include anche i record
+/// senza quel dato: `NOT tag:read` restituisce i libri senza tag.
+///
+/// L'espressione deve essere già stata controllata con
+/// [`validate_query`](super::validator::validate_query): un campo sconosciuto o un
+/// valore non valido diventa una condizione sempre falsa (`0`).
+pub fn build_query_clause(expr: &QueryExpr, target: QueryTarget) -> (String, Vec<String>) {
+    match expr {
+        QueryExpr::And(parts) | QueryExpr::Or(parts) => {
+            let separator = if matches!(expr, QueryExpr::And(_)) {
+                " AND "
+            } else {
+                " OR "
+            };
+            let mut clauses = Vec::new();
+            let mut params = Vec::new();
+            for part in parts {
+                let (clause, mut part_params) = build_query_clause(part, target);
+                clauses.push(clause);
+                params.append(&mut part_params);
+            }
+            (format!("({})", clauses.join(separator)), params)
+        }
+        QueryExpr::Not(inner) => {
+            let (clause, params) = build_query_clause(inner, target);
+            (format!("NOT ({})", clause), params)
+        }
+        QueryExpr::Term(term) => {
+            let sql = match &term.field {
+                None => text_term(target, "search", &term.value),
+                Some(field) => match field.as_str() {
+                    "year" | "pages" => numeric_term(target, field, &term.value),
+                    _ => text_term(target, field, &term.value),
+                },
+            };
+            sql.unwrap_or_else(|| ("0".to_string(), Vec::new()))
+        }
+    }
+}
+
+/// SQL per un campo testuale: il valore è cercato come sottostringa
+fn text_term(target: QueryTarget, field: &str, value: &QueryValue) -> Option<(String, Vec<String>)> {
+    let QueryValue::Text(text) = value else {
+        return None;
+    };
+    let pattern = format!("%{}%", text);
+
+    if field == "language" {
+        let (condition, params) = language_match(text);
+        return Some((language_subquery(target, &condition), params));
+    }
+    if target == QueryTarget::Books && field == "isbn" {
+        return Some(isbn_match(text));
+    }
+
+    let clause = match (target, field) {
+        (QueryTarget::Books, "search") => {
+            "(books.name LIKE ? OR COALESCE(books.original_title, '') LIKE ? OR COALESCE(books.notes, '') LIKE ?)"
+        }
+        (QueryTarget::Books, "title") => {
+            "(books.name LIKE ? OR COALESCE(books.original_title, '') LIKE ?)"
+        }
+        (QueryTarget::Books, "author") => {
+            "books.id IN (SELECT x_books_people_roles.book_id FROM x_books_people_roles \
+             JOIN people AS q_people ON x_books_people_roles.person_id = q_people.id \
+             WHERE q_people.name LIKE ?)"
+        }
+        (QueryTarget::Books, "publisher") => "COALESCE(publishers.name, '') LIKE ?",
+        (QueryTarget::Books, "series") => "COALESCE(series.name, '') LIKE ?",
+        (QueryTarget::Books, "format") => {
+            "EXISTS (SELECT 1 FROM formats AS q_formats WHERE q_formats.key LIKE ? \
+             AND (q_formats.id = books.format_id OR q_formats.id IN \
+             (SELECT book_files.format_id FROM book_files WHERE book_files.book_id = books.id)))"
+        }
+        (QueryTarget::Books, "tag") => {
+            "books.id IN (SELECT x_books_tags.book_id FROM x_books_tags \
+             JOIN tags AS q_tags ON x_books_tags.tag_id = q_tags.id \
+             WHERE q_tags.name LIKE ?)"
+        }
+        (QueryTarget::Contents, "search") => {
+            "(contents.name LIKE ? OR COALESCE(contents.original_title, '') LIKE ? OR COALESCE(contents.notes, '') LIKE ?)"
+        }
+        (QueryTarget::Contents, "title") => {
+            "(contents.name LIKE ? OR COALESCE(contents.original_title, '') LIKE ?)"
+        }
+        (QueryTarget::Contents, "author") => {
+            "contents.id IN (SELECT x_contents_people_roles.content_id FROM x_contents_people_roles \
+             JOIN people AS q_people ON x_contents_people_roles.person_id = q_people.id \
+             WHERE q_people.name LIKE ?)"
+        }
+        (QueryTarget::Contents, "type") => "COALESCE(types.key, '') LIKE ?",
+        (QueryTarget::Contents, "tag") => {
+            "contents.id IN (SELECT x_contents_tags.content_id FROM x_contents_tags \
+             JOIN tags AS q_tags ON x_contents_tags.tag_id = q_tags.id \
+             WHERE q_tags.name LIKE ?)"
+        }
+        _ => return None,
+    };
+
+    let placeholders = clause.matches('?').count();
+    Some((clause.to_string(), vec![pattern; placeholders]))
+}
+
+/// SQL per un campo numerico: valore esatto, confronto o intervallo inclusivo
+fn numeric_term(target: QueryTarget, field: &str, value: &QueryValue) -> Option<(String, Vec<String>)> {
+    let column = match (target, field) {
+        (QueryTarget::Books, "year") => {
+            "CAST(strftime('%Y', datetime(books.publication_date, 'unixepoch')) AS INTEGER)"
+        }
+        (QueryTarget::Books, "pages") => "books.pages",
+        (QueryTarget::Contents, "year") => {
+            "CAST(strftime('%Y', datetime(contents.publication_date, 'unixepoch')) AS INTEGER)"
+        }
+        (QueryTarget::Contents, "pages") => "contents.pages",
+        _ => return None,
+    };
+
+    let (condition, params) = match value {
+        QueryValue::Text(v) => ("= ?".to_string(), vec![v.clone()]),
+        QueryValue::Compare(op, v) => (format!("{} ?", op.to_sql()), vec![v.clone()]),
+        QueryValue::Range(Some(low), Some(high)) => {
+            ("BETWEEN ? AND ?".to_string(), vec![low.clone(), high.clone()])
+        }
+        QueryValue::Range(Some(low), None) => (">= ?".to_string(), vec![low.clone()]),
+        QueryValue::Range(None, Some(high)) => ("<= ?".to_string(), vec![high.clone()]),
+        QueryValue::Range(None, None) => return None,
+    };
+
+    Some((
+        format!("({column} IS NOT NULL AND {column} {condition})"),
+        params,
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::filters::query::parse_query;
     use crate::filters::types::BookSortField;
 
     #[test]
@@ -363,4 +890,207 @@ mod tests {
         assert!(query.contains("AND"));
         assert_eq!(params.len(), 4); // 2 authors + 2 formats
     }
+
+    #[test]
+    fn test_build_books_query_format_matches_book_files() {
+        let filters = BookFilters::default().with_format("pdf");
+        let (query, params) = build_books_query(&filters);
+
+        assert!(query.contains("file_formats.id = books.format_id"));
+        assert!(query.contains("SELECT book_files.format_id FROM book_files"));
+        assert!(query.contains("file_formats.key LIKE ?"));
+        assert_eq!(params, vec!["%pdf%".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_format_filter_matches_book_format_and_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("ritmo.db");
+        std::fs::write(&db, crate::DB_TEMPLATE).unwrap();
+        let pool = sqlx::SqlitePool::connect(&format!("sqlite://{}", db.display()))
+            .await
+            .unwrap();
+        sqlx::raw_sql(
+            "INSERT INTO formats (id, key) VALUES (1, 'format.pdf'), (2, 'format.epub');
+             INSERT INTO books (id, name, format_id) VALUES (1, 'Solo libro', 1), (2, 'Con file', NULL), (3, 'Altro', 2);
+             INSERT INTO book_files (book_id, format_id, file_link, file_hash) VALUES (2, 1, 'a.pdf', 'h');",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let filters = BookFilters::default().with_format("pdf");
+        let found = crate::execute_books_query(&pool, &filters).await.unwrap();
+        assert_eq!(found.iter().map(|b| b.id).collect::<Vec<_>>(), vec![2, 1]);
+
+        let filters = BookFilters::default().with_query(parse_query("format:pdf").unwrap());
+        let found = crate::execute_books_query(&pool, &filters).await.unwrap();
+        assert_eq!(found.len(), 2);
+    }
+
+    #[test]
+    fn test_build_query_clause_example() {
+        let query = parse_query(
+            "author:king AND (format:epub OR format:pdf) AND NOT tag:read AND year:1980..1990 AND pages:>400",
+        )
+        .unwrap();
+        let (clause, params) = build_query_clause(&query, QueryTarget::Books);
+
+        assert!(clause.starts_with("(books.id IN (SELECT x_books_people_roles.book_id"));
+        assert!(clause.contains(" OR "));
+        assert!(clause.contains("NOT (books.id IN (SELECT x_books_tags.book_id"));
+        assert!(clause.contains("BETWEEN ? AND ?"));
+        assert!(clause.contains("books.pages > ?"));
+        assert_eq!(
+            params,
+            vec!["%king%", "%epub%", "%pdf%", "%read%", "1980", "1990", "400"]
+        );
+    }
+
+    #[test]
+    fn test_build_query_clause_contents_and_unknown_field() {
+        let query = parse_query("type:novel language:it").unwrap();
+        let (clause, params) = build_query_clause(&query, QueryTarget::Contents);
+        assert!(clause.contains("COALESCE(types.key, '') LIKE ?"));
+        assert!(clause.contains("x_contents_languages"));
+        assert_eq!(params, vec!["%novel%", "it", "it", "it"]);
+
+        let query = parse_query("format:epub").unwrap();
+        let (clause, params) = build_query_clause(&query, QueryTarget::Contents);
+        assert_eq!(clause, "0");
+        assert!(params.is_empty());
+    }
+
+    #[test]
+    fn test_build_books_query_with_query_after_other_filters() {
+        let filters = BookFilters::default()
+            .with_author("Calvino")
+            .with_query(parse_query("pages:<=200 rampante").unwrap());
+        let (query, params) = build_books_query(&filters);
+
+        assert!(query.contains("people.name LIKE ?"));
+        assert!(query.contains("books.pages <= ?"));
+        assert_eq!(
+            params,
+            vec!["%Calvino%", "200", "%rampante%", "%rampante%", "%rampante%"]
+        );
+    }
+
+    #[test]
+    fn test_build_books_query_tags_any_and_all() {
+        let filters = BookFilters::default().with_tag("horror").with_tag("read");
+        let (query, params) = build_books_query(&filters);
+        assert_eq!(query.matches("SELECT x_books_tags.book_id").count(), 1);
+        assert!(query.contains("q_tags.name = ? COLLATE NOCASE OR q_tags.name = ? COLLATE NOCASE"));
+        assert_eq!(params, vec!["horror", "read"]);
+
+        let filters = BookFilters {
+            tag_match: TagMatch::All,
+            ..filters
+        };
+        let (query, params) = build_books_query(&filters);
+        assert_eq!(query.matches("SELECT x_books_tags.book_id").count(), 2);
+        assert_eq!(params, vec!["horror", "read"]);
+    }
+
+    #[test]
+    fn test_build_books_query_language_role_and_person_role() {
+        let filters = BookFilters {
+            languages: vec!["en".to_string()],
+            language_role: Some("language_role.original".to_string()),
+            ..Default::default()
+        }
+        .with_person_role("Fruttero", "translator");
+        let (query, params) = build_books_query(&filters);
+
+        assert!(query.contains("q_languages.language_role = ?"));
+        assert!(query.contains("UNION SELECT x_books_contents.book_id"));
+        assert_eq!(
+            params,
+            vec![
+                "en",
+                "en",
+                "en",
+                "language_role.original",
+                "%Fruttero%",
+                "translator",
+                "role.translator",
+                "%Fruttero%",
+                "translator",
+                "role.translator",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_books_query_presence_pages_and_country() {
+        let filters = BookFilters {
+            publisher_countries: vec!["IT".to_string()],
+            pages_min: Some(100),
+            pages_max: Some(300),
+            ..Default::default()
+        }
+        .with_has(PresenceField::Cover)
+        .with_missing(PresenceField::Isbn)
+        .with_missing(PresenceField::Type);
+        let (query, params) = build_books_query(&filters);
+
+        assert!(query.contains("books.has_cover = 1"));
+        assert!(query.contains("NOT (COALESCE(TRIM(books.isbn), '') <> '')"));
+        // "type" non si applica ai libri
+        assert!(!query.contains("type_id"));
+        assert!(query.contains("books.pages >= ?"));
+        assert!(query.contains("publishers.country LIKE ?"));
+        assert_eq!(params, vec!["100", "300", "%IT%"]);
+    }
+
+    #[test]
+    fn test_build_books_query_isbn_either_form() {
+        let filters = BookFilters {
+            isbn: Some("0-306-40615-2".to_string()),
+            ..Default::default()
+        };
+        let (query, params) = build_books_query(&filters);
+        assert!(query.contains("books.isbn13 = ?"));
+        assert_eq!(params, vec!["9780306406157", "9780306406157", "0306406152"]);
+
+        // Valore parziale: sottostringa delle cifre, separatori ignorati
+        let filters = BookFilters {
+            query: Some(crate::filters::parse_query("isbn:88-04").unwrap()),
+            ..Default::default()
+        };
+        let (_, params) = build_books_query(&filters);
+        assert_eq!(params, vec!["%8804%", "%8804%"]);
+    }
+
+    #[test]
+    fn test_build_books_query_shelf_before_query() {
+        let filters = BookFilters {
+            shelf_id: Some(7),
+            query: Some(crate::filters::parse_query("year:>1980").unwrap()),
+            ..Default::default()
+        };
+        let (query, params) = build_books_query(&filters);
+
+        assert!(query.contains("books.id IN (SELECT book_id FROM x_shelves_books WHERE shelf_id = ?)"));
+        assert_eq!(params, vec!["7", "1980"]);
+    }
+
+    #[test]
+    fn test_build_contents_query_attribute_filters() {
+        let filters = ContentFilters {
+            language_role: Some("language_role.original".to_string()),
+            pages_max: Some(50),
+            ..Default::default()
+        }
+        .with_tag("classic")
+        .with_missing(PresenceField::Author);
+        let (query, params) = build_contents_query(&filters);
+
+        assert!(query.contains("SELECT x_contents_tags.content_id"));
+        assert!(query.contains("contents.id IN (SELECT x_contents_languages.content_id"));
+        assert!(query.contains("NOT (EXISTS (SELECT 1 FROM x_contents_people_roles"));
+        assert!(query.contains("contents.pages <= ?"));
+        assert_eq!(params, vec!["classic", "language_role.original", "50"]);
+    }
 }
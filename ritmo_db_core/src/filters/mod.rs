@@ -3,6 +3,8 @@
 //! This module provides a complete filter system for querying the database.
 //! It includes:
 //! - Filter types and data structures (`types`)
+//! - Boolean query language parser (`query`)
+//! - Conversion from stored presets (`preset`)
 //! - SQL query building logic (`builder`)
 //! - Query execution against the database (`executor`)
 //!
@@ -14,6 +16,9 @@
 //! filters/
 //! ├── mod.rs        <- Public API (this file)
 //! ├── types.rs      <- BookFilters, ContentFilters, BookResult, ContentResult
+//! ├── query.rs      <- Query language: tokenizer, parser, AST
+//! ├── preset.rs     <- BookFilters/ContentFilters from stored presets
+//! ├── validator.rs  <- Input validation (filters and query fields)
 //! ├── builder.rs    <- SQL query construction
 //! └── executor.rs   <- Query execution
 //! ```
@@ -43,13 +48,19 @@
 
 pub mod builder;
 pub mod executor;
+pub mod preset;
+pub mod query;
 pub mod types;
 pub mod validator;
 
 // Re-export types for convenient access
-pub use builder::{build_books_query, build_contents_query};
+pub use builder::{build_books_query, build_contents_query, build_query_clause};
 pub use executor::{execute_books_query, execute_contents_query};
+pub use query::{parse_query, QueryExpr, QueryParseError, QueryTarget};
 pub use types::{
-    BookFilters, BookResult, BookSortField, ContentFilters, ContentResult, ContentSortField,
+    normalize_language_role, BookFilters, BookResult, BookSortField, ContentFilters,
+    ContentResult, ContentSortField, PersonRoleFilter, PresenceField, TagMatch,
+};
+pub use validator::{
+    validate_book_filters, validate_content_filters, validate_query, ValidationError,
 };
-pub use validator::{validate_book_filters, validate_content_filters, ValidationError};
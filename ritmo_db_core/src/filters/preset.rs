@@ -0,0 +1,202 @@
+//! Conversion from stored presets to filters
+//!
+//! Presets (`ritmo_config`) keep filter values as plain strings, so they can be
+//! saved in TOML or JSON. These conversions parse the query, the people with role
+//! and the presence fields; values are otherwise copied as-is; the result still
+//! has to go through `validate_book_filters` / `validate_content_filters`.
+
+use super::query::{parse_query, QueryExpr};
+use super::types::{
+    normalize_language_role, BookFilters, BookSortField, ContentFilters, ContentSortField,
+    PersonRoleFilter, PresenceField, TagMatch,
+};
+use super::validator::ValidationError;
+use ritmo_config::{BookFilterPreset, ContentFilterPreset};
+
+impl BookFilters {
+    /// Builds the filters stored in a book preset
+    pub fn from_preset(preset: &BookFilterPreset) -> Result<Self, Vec<ValidationError>> {
+        let mut errors = Vec::new();
+
+        let mut filters = BookFilters::default()
+            .set_author_opt(preset.author.clone())
+            .set_publisher_opt(preset.publisher.clone())
+            .set_series_opt(preset.series.clone())
+            .set_format_opt(preset.format.clone());
+
+        filters.year = preset.year;
+        filters.isbn = preset.isbn.clone();
+        filters.search = preset.search.clone();
+        filters.acquired_after = preset.acquired_after;
+        filters.acquired_before = preset.acquired_before;
+        filters.query = parse_preset_query(preset.query.as_deref(), &mut errors);
+        filters.tags = preset.tags.clone();
+        filters.tag_match = tag_match(preset.all_tags);
+        filters.languages = preset.languages.clone();
+        filters.language_role = preset_language_role(preset.language_role.as_deref());
+        filters.people_roles = parse_people(&preset.people, &mut errors);
+        filters.publisher_countries = preset.publisher_countries.clone();
+        filters.has = parse_presence("has", &preset.has, &mut errors);
+        filters.missing = parse_presence("missing", &preset.missing, &mut errors);
+        filters.pages_min = preset.pages_min;
+        filters.pages_max = preset.pages_max;
+        filters.sort = BookSortField::from_str(&preset.sort);
+        filters.limit = preset.limit;
+        filters.offset = preset.offset;
+
+        if errors.is_empty() {
+            Ok(filters)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl ContentFilters {
+    /// Builds the filters stored in a content preset
+    pub fn from_preset(preset: &ContentFilterPreset) -> Result<Self, Vec<ValidationError>> {
+        let mut errors = Vec::new();
+
+        let mut filters = ContentFilters::default()
+            .set_author_opt(preset.author.clone())
+            .set_content_type_opt(preset.content_type.clone());
+
+        filters.year = preset.year;
+        filters.search = preset.search.clone();
+        filters.query = parse_preset_query(preset.query.as_deref(), &mut errors);
+        filters.tags = preset.tags.clone();
+        filters.tag_match = tag_match(preset.all_tags);
+        filters.languages = preset.languages.clone();
+        filters.language_role = preset_language_role(preset.language_role.as_deref());
+        filters.people_roles = parse_people(&preset.people, &mut errors);
+        filters.has = parse_presence("has", &preset.has, &mut errors);
+        filters.missing = parse_presence("missing", &preset.missing, &mut errors);
+        filters.pages_min = preset.pages_min;
+        filters.pages_max = preset.pages_max;
+        filters.sort = ContentSortField::from_str(&preset.sort);
+        filters.limit = preset.limit;
+        filters.offset = preset.offset;
+
+        if errors.is_empty() {
+            Ok(filters)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+fn tag_match(all_tags: bool) -> TagMatch {
+    if all_tags {
+        TagMatch::All
+    } else {
+        TagMatch::Any
+    }
+}
+
+/// Ruolo normalizzato; un valore non riconosciuto resta com'è e sarà segnalato dalla validazione
+fn preset_language_role(role: Option<&str>) -> Option<String> {
+    role.map(|r| normalize_language_role(r).unwrap_or_else(|| r.to_string()))
+}
+
+fn parse_preset_query(query: Option<&str>, errors: &mut Vec<ValidationError>) -> Option<QueryExpr> {
+    match parse_query(query?) {
+        Ok(expr) => Some(expr),
+        Err(e) => {
+            errors.push(ValidationError::QuerySyntax {
+                message: e.to_string(),
+            });
+            None
+        }
+    }
+}
+
+fn parse_people(people: &[String], errors: &mut Vec<ValidationError>) -> Vec<PersonRoleFilter> {
+    people
+        .iter()
+        .filter_map(|p| {
+            let parsed = PersonRoleFilter::parse(p);
+            if parsed.is_none() {
+                errors.push(ValidationError::InvalidValue {
+                    field: "people".to_string(),
+                    value: p.clone(),
+                });
+            }
+            parsed
+        })
+        .collect()
+}
+
+fn parse_presence(
+    field: &str,
+    values: &[String],
+    errors: &mut Vec<ValidationError>,
+) -> Vec<PresenceField> {
+    values
+        .iter()
+        .filter_map(|v| {
+            let parsed = v.trim().parse::<PresenceField>().ok();
+            if parsed.is_none() {
+                errors.push(ValidationError::InvalidValue {
+                    field: field.to_string(),
+                    value: v.clone(),
+                });
+            }
+            parsed
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::validator::validate_book_filters;
+
+    #[test]
+    fn test_book_filters_from_preset() {
+        let preset = BookFilterPreset {
+            author: Some("King".to_string()),
+            query: Some("year:>1980".to_string()),
+            tags: vec!["horror".to_string(), "read".to_string()],
+            all_tags: true,
+            language_role: Some("original".to_string()),
+            people: vec!["Dobner:translator".to_string()],
+            has: vec!["cover".to_string()],
+            sort: "year".to_string(),
+            limit: Some(10),
+            ..Default::default()
+        };
+
+        let filters = BookFilters::from_preset(&preset).unwrap();
+        assert_eq!(filters.authors, vec!["King"]);
+        assert!(filters.query.is_some());
+        assert_eq!(filters.tag_match, TagMatch::All);
+        assert_eq!(
+            filters.language_role.as_deref(),
+            Some("language_role.original")
+        );
+        assert_eq!(filters.people_roles.len(), 1);
+        assert_eq!(filters.has, vec![PresenceField::Cover]);
+        assert_eq!(filters.limit, Some(10));
+        assert!(validate_book_filters(&filters).is_ok());
+    }
+
+    #[test]
+    fn test_filters_from_preset_errors() {
+        let preset = BookFilterPreset {
+            query: Some("author:(".to_string()),
+            people: vec!["senza ruolo".to_string()],
+            missing: vec!["colour".to_string()],
+            ..Default::default()
+        };
+        let errors = BookFilters::from_preset(&preset).unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(errors[0], ValidationError::QuerySyntax { .. }));
+
+        let preset = ContentFilterPreset {
+            language_role: Some("translated".to_string()),
+            ..Default::default()
+        };
+        let filters = ContentFilters::from_preset(&preset).unwrap();
+        assert_eq!(filters.language_role.as_deref(), Some("translated"));
+    }
+}
@@ -0,0 +1,529 @@
+//! Query language for filters
+//!
+//! A small boolean language that complements the fixed fields of
+//! `BookFilters`/`ContentFilters`:
+//!
+//! ```text
+//! author:king AND (format:epub OR format:pdf) AND NOT tag:read AND year:1980..1990 AND pages:>400
+//! ```
+//!
+//! Grammar:
+//!
+//! ```text
+//! query   := or
+//! or      := and ("OR" and)*
+//! and     := unary (["AND"] unary)*      <- adjacent terms are implicitly ANDed
+//! unary   := "NOT" unary | "(" or ")" | term
+//! term    := field ":" value | value     <- a bare value is a full-text search
+//! value   := word | "quoted text"
+//!          | (">" | ">=" | "<" | "<=") number
+//!          | [number] ".." [number]      <- inclusive range, either bound optional
+//! ```
+//!
+//! `AND`, `OR` and `NOT` are recognized only in upper case, so lower-case words
+//! remain ordinary search terms. Text values match as substrings (`LIKE %value%`),
+//! numeric fields (`year`, `pages`) accept exact values, comparisons and ranges.
+//!
+//! Parsing only checks the syntax: field names and values are checked by
+//! [`crate::filters::validator::validate_query`], the SQL is produced by
+//! [`crate::filters::builder`].
+
+/// Espressione booleana prodotta dal parser
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryExpr {
+    And(Vec<QueryExpr>),
+    Or(Vec<QueryExpr>),
+    Not(Box<QueryExpr>),
+    Term(QueryTerm),
+}
+
+/// Singolo confronto `campo:valore`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryTerm {
+    /// Nome del campo in minuscolo (None = ricerca full-text)
+    pub field: Option<String>,
+    pub value: QueryValue,
+}
+
+/// Valore di un termine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryValue {
+    /// Testo semplice o tra virgolette
+    Text(String),
+    /// Confronto numerico (`>400`, `<=1990`)
+    Compare(CompareOp, String),
+    /// Intervallo inclusivo (`1980..1990`, `..200`, `400..`)
+    Range(Option<String>, Option<String>),
+}
+
+/// Operatori di confronto numerico
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl CompareOp {
+    pub fn to_sql(&self) -> &'static str {
+        match self {
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+        }
+    }
+}
+
+/// Entità su cui viene eseguita la query (determina i campi disponibili)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryTarget {
+    Books,
+    Contents,
+}
+
+/// Tipo di un campo: testuale (sottostringa) o numerico (confronti e intervalli)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Text,
+    Number,
+}
+
+const BOOK_FIELDS: &[(&str, FieldKind)] = &[
+    ("title", FieldKind::Text),
+    ("author", FieldKind::Text),
+    ("publisher", FieldKind::Text),
+    ("series", FieldKind::Text),
+    ("format", FieldKind::Text),
+    ("tag", FieldKind::Text),
+    ("language", FieldKind::Text),
+    ("isbn", FieldKind::Text),
+    ("year", FieldKind::Number),
+    ("pages", FieldKind::Number),
+];
+
+const CONTENT_FIELDS: &[(&str, FieldKind)] = &[
+    ("title", FieldKind::Text),
+    ("author", FieldKind::Text),
+    ("type", FieldKind::Text),
+    ("tag", FieldKind::Text),
+    ("language", FieldKind::Text),
+    ("year", FieldKind::Number),
+    ("pages", FieldKind::Number),
+];
+
+impl QueryTarget {
+    /// Campi disponibili per questa entità
+    pub fn fields(&self) -> impl Iterator<Item = &'static str> {
+        let fields = match self {
+            Self::Books => BOOK_FIELDS,
+            Self::Contents => CONTENT_FIELDS,
+        };
+        fields.iter().map(|(name, _)| *name)
+    }
+
+    /// Tipo del campo, None se il campo non esiste
+    pub fn field_kind(&self, field: &str) -> Option<FieldKind> {
+        let fields = match self {
+            Self::Books => BOOK_FIELDS,
+            Self::Contents => CONTENT_FIELDS,
+        };
+        fields
+            .iter()
+            .find(|(name, _)| *name == field)
+            .map(|(_, kind)| *kind)
+    }
+}
+
+/// Errore di sintassi con la posizione (in caratteri) nella query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryParseError {
+    pub message: String,
+    pub position: usize,
+}
+
+impl std::fmt::Display for QueryParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (position {})", self.message, self.position)
+    }
+}
+
+impl std::error::Error for QueryParseError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+    Term(QueryTerm),
+}
+
+/// Analizza una query e restituisce l'AST
+///
+/// Una query vuota (o di soli spazi) è un errore: chi chiama dovrebbe
+/// semplicemente non impostare la query.
+pub fn parse_query(input: &str) -> Result<QueryExpr, QueryParseError> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        len: input.chars().count(),
+    };
+
+    if parser.tokens.is_empty() {
+        return Err(QueryParseError {
+            message: "Empty query".to_string(),
+            position: 0,
+        });
+    }
+
+    let expr = parser.parse_or()?;
+    if let Some((_, position)) = parser.tokens.get(parser.pos) {
+        return Err(QueryParseError {
+            message: "Unexpected ')'".to_string(),
+            position: *position,
+        });
+    }
+    Ok(expr)
+}
+
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, QueryParseError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        match c {
+            '(' => {
+                tokens.push((Token::LParen, start));
+                i += 1;
+            }
+            ')' => {
+                tokens.push((Token::RParen, start));
+                i += 1;
+            }
+            '"' => {
+                let (text, next) = read_quoted(&chars, i)?;
+                i = next;
+                tokens.push((
+                    Token::Term(QueryTerm {
+                        field: None,
+                        value: QueryValue::Text(text),
+                    }),
+                    start,
+                ));
+            }
+            _ => {
+                while i < chars.len()
+                    && !chars[i].is_whitespace()
+                    && !matches!(chars[i], '(' | ')' | '"')
+                {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+
+                let token = match word.as_str() {
+                    "AND" | "&&" => Token::And,
+                    "OR" | "||" => Token::Or,
+                    "NOT" => Token::Not,
+                    _ => match word.split_once(':') {
+                        Some((field, raw)) => {
+                            if field.is_empty() {
+                                return Err(QueryParseError {
+                                    message: format!("Missing field name before ':' in '{}'", word),
+                                    position: start,
+                                });
+                            }
+                            let value = if raw.is_empty() && chars.get(i) == Some(&'"') {
+                                let (text, next) = read_quoted(&chars, i)?;
+                                i = next;
+                                QueryValue::Text(text)
+                            } else if raw.is_empty() {
+                                return Err(QueryParseError {
+                                    message: format!("Missing value for field '{}'", field),
+                                    position: i,
+                                });
+                            } else {
+                                parse_value(raw)
+                            };
+                            Token::Term(QueryTerm {
+                                field: Some(field.to_lowercase()),
+                                value,
+                            })
+                        }
+                        None => Token::Term(QueryTerm {
+                            field: None,
+                            value: QueryValue::Text(word),
+                        }),
+                    },
+                };
+                tokens.push((token, start));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Legge una stringa tra virgolette a partire da `start` (che punta a `"`);
+/// `\"` e `\\` sono gli unici escape. Restituisce il testo e l'indice successivo.
+fn read_quoted(chars: &[char], start: usize) -> Result<(String, usize), QueryParseError> {
+    let mut text = String::new();
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '"' => return Ok((text, i + 1)),
+            '\\' if matches!(chars.get(i + 1), Some('"') | Some('\\')) => {
+                text.push(chars[i + 1]);
+                i += 2;
+            }
+            c => {
+                text.push(c);
+                i += 1;
+            }
+        }
+    }
+    Err(QueryParseError {
+        message: "Unterminated quoted string".to_string(),
+        position: start,
+    })
+}
+
+/// Interpreta un valore non quotato: confronto, intervallo o testo
+fn parse_value(raw: &str) -> QueryValue {
+    for (prefix, op) in [
+        (">=", CompareOp::Ge),
+        ("<=", CompareOp::Le),
+        (">", CompareOp::Gt),
+        ("<", CompareOp::Lt),
+    ] {
+        if let Some(rest) = raw.strip_prefix(prefix) {
+            return QueryValue::Compare(op, rest.to_string());
+        }
+    }
+
+    if let Some((low, high)) = raw.split_once("..") {
+        let bound = |s: &str| (!s.is_empty()).then(|| s.to_string());
+        return QueryValue::Range(bound(low), bound(high));
+    }
+
+    QueryValue::Text(raw.to_string())
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    /// Lunghezza della query, usata come posizione per "fine inattesa"
+    len: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map(|(_, position)| *position)
+            .unwrap_or(self.len)
+    }
+
+    fn parse_or(&mut self) -> Result<QueryExpr, QueryParseError> {
+        let mut parts = vec![self.parse_and()?];
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            parts.push(self.parse_and()?);
+        }
+        Ok(if parts.len() == 1 {
+            parts.remove(0)
+        } else {
+            QueryExpr::Or(parts)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<QueryExpr, QueryParseError> {
+        let mut parts = vec![self.parse_unary()?];
+        loop {
+            match self.peek() {
+                Some(Token::And) => {
+                    self.pos += 1;
+                    parts.push(self.parse_unary()?);
+                }
+                // AND implicito tra termini adiacenti
+                Some(Token::Not) | Some(Token::LParen) | Some(Token::Term(_)) => {
+                    parts.push(self.parse_unary()?);
+                }
+                _ => break,
+            }
+        }
+        Ok(if parts.len() == 1 {
+            parts.remove(0)
+        } else {
+            QueryExpr::And(parts)
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<QueryExpr, QueryParseError> {
+        let position = self.position();
+        match self.tokens.get(self.pos).map(|(token, _)| token.clone()) {
+            Some(Token::Not) => {
+                self.pos += 1;
+                Ok(QueryExpr::Not(Box::new(self.parse_unary()?)))
+            }
+            Some(Token::LParen) => {
+                self.pos += 1;
+                let expr = self.parse_or()?;
+                if self.peek() != Some(&Token::RParen) {
+                    return Err(QueryParseError {
+                        message: "Missing ')'".to_string(),
+                        position: self.position(),
+                    });
+                }
+                self.pos += 1;
+                Ok(expr)
+            }
+            Some(Token::Term(term)) => {
+                self.pos += 1;
+                Ok(QueryExpr::Term(term))
+            }
+            Some(Token::And) | Some(Token::Or) => Err(QueryParseError {
+                message: "Expected a term before operator".to_string(),
+                position,
+            }),
+            Some(Token::RParen) => Err(QueryParseError {
+                message: "Unexpected ')'".to_string(),
+                position,
+            }),
+            None => Err(QueryParseError {
+                message: "Unexpected end of query".to_string(),
+                position,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(field: &str, value: QueryValue) -> QueryExpr {
+        QueryExpr::Term(QueryTerm {
+            field: Some(field.to_string()),
+            value,
+        })
+    }
+
+    fn text(field: &str, value: &str) -> QueryExpr {
+        term(field, QueryValue::Text(value.to_string()))
+    }
+
+    #[test]
+    fn test_parse_single_term() {
+        assert_eq!(parse_query("author:king").unwrap(), text("author", "king"));
+        assert_eq!(parse_query("Author:King").unwrap(), text("author", "King"));
+    }
+
+    #[test]
+    fn test_parse_full_example() {
+        let expr = parse_query(
+            "author:king AND (format:epub OR format:pdf) AND NOT tag:read AND year:1980..1990 AND pages:>400",
+        )
+        .unwrap();
+
+        assert_eq!(
+            expr,
+            QueryExpr::And(vec![
+                text("author", "king"),
+                QueryExpr::Or(vec![text("format", "epub"), text("format", "pdf")]),
+                QueryExpr::Not(Box::new(text("tag", "read"))),
+                term(
+                    "year",
+                    QueryValue::Range(Some("1980".to_string()), Some("1990".to_string()))
+                ),
+                term("pages", QueryValue::Compare(CompareOp::Gt, "400".to_string())),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_precedence_and_binds_tighter_than_or() {
+        let expr = parse_query("a:1 OR b:2 c:3").unwrap();
+        assert_eq!(
+            expr,
+            QueryExpr::Or(vec![
+                text("a", "1"),
+                QueryExpr::And(vec![text("b", "2"), text("c", "3")]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_quoted_values_and_bare_words() {
+        let expr = parse_query(r#"author:"Stephen King" "il barone" rampante"#).unwrap();
+        assert_eq!(
+            expr,
+            QueryExpr::And(vec![
+                text("author", "Stephen King"),
+                QueryExpr::Term(QueryTerm {
+                    field: None,
+                    value: QueryValue::Text("il barone".to_string()),
+                }),
+                QueryExpr::Term(QueryTerm {
+                    field: None,
+                    value: QueryValue::Text("rampante".to_string()),
+                }),
+            ])
+        );
+
+        let expr = parse_query(r#"title:"say \"hi\"""#).unwrap();
+        assert_eq!(expr, text("title", "say \"hi\""));
+    }
+
+    #[test]
+    fn test_parse_open_ranges_and_comparisons() {
+        assert_eq!(
+            parse_query("pages:..200").unwrap(),
+            term("pages", QueryValue::Range(None, Some("200".to_string())))
+        );
+        assert_eq!(
+            parse_query("year:2000..").unwrap(),
+            term("year", QueryValue::Range(Some("2000".to_string()), None))
+        );
+        assert_eq!(
+            parse_query("year:<=1990").unwrap(),
+            term("year", QueryValue::Compare(CompareOp::Le, "1990".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_lowercase_keywords_are_search_words() {
+        let expr = parse_query("war and peace").unwrap();
+        assert!(matches!(expr, QueryExpr::And(ref parts) if parts.len() == 3));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_query("").is_err());
+        assert!(parse_query("   ").is_err());
+        assert!(parse_query("(author:king").is_err());
+        assert!(parse_query("author:king)").is_err());
+        assert!(parse_query("AND author:king").is_err());
+        assert!(parse_query("author:king OR").is_err());
+        assert!(parse_query("NOT").is_err());
+        assert!(parse_query("author:").is_err());
+        assert!(parse_query(":king").is_err());
+
+        let err = parse_query(r#"title:"unterminated"#).unwrap_err();
+        assert_eq!(err.position, 6);
+    }
+}
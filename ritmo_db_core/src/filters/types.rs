@@ -2,6 +2,7 @@
 //!
 //! This module contains all filter-related types used for querying books and contents.
 
+use super::query::QueryExpr;
 use serde::{Deserialize, Serialize};
 
 /// Filtri per la ricerca di libri
@@ -30,6 +31,23 @@ pub struct BookFilters {
     pub series_list: Vec<String>,
     /// Formats (OR logic if multiple)
     pub formats: Vec<String>,
+    /// Tags (exact, case-insensitive; any/all according to `tag_match`)
+    pub tags: Vec<String>,
+    pub tag_match: TagMatch,
+    /// Languages of the book contents (ISO code or name, OR logic if multiple)
+    pub languages: Vec<String>,
+    /// Restricts `languages` to a role (`language_role.original`, `.source`, `.actual`)
+    pub language_role: Option<String>,
+    /// People with a specific role, on the book or its contents (AND logic)
+    pub people_roles: Vec<PersonRoleFilter>,
+    /// Publisher countries (OR logic if multiple)
+    pub publisher_countries: Vec<String>,
+    /// Fields that must be present / missing
+    pub has: Vec<PresenceField>,
+    pub missing: Vec<PresenceField>,
+    /// Page range (inclusive)
+    pub pages_min: Option<i64>,
+    pub pages_max: Option<i64>,
     /// Publication year (exact match)
     pub year: Option<i32>,
     /// ISBN search pattern
@@ -39,6 +57,10 @@ pub struct BookFilters {
     /// Acquisition date filters
     pub acquired_after: Option<i64>, // Timestamp UNIX: libri acquisiti dopo questa data
     pub acquired_before: Option<i64>, // Timestamp UNIX: libri acquisiti prima di questa data
+    /// Boolean query expression (AND with the other filters)
+    pub query: Option<QueryExpr>,
+    /// Restricts results to the books of a manual shelf
+    pub shelf_id: Option<i64>,
     /// Sort configuration
     pub sort: BookSortField,
     /// Result pagination
@@ -108,6 +130,194 @@ impl BookFilters {
         }
         self
     }
+
+    /// Helper to set a parsed query expression
+    pub fn with_query(mut self, query: QueryExpr) -> Self {
+        self.query = Some(query);
+        self
+    }
+
+    /// Helper to add a single tag
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Helper to add a single language
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.languages.push(language.into());
+        self
+    }
+
+    /// Helper to add a person with a role (e.g. translated by X)
+    pub fn with_person_role(mut self, name: impl Into<String>, role: impl Into<String>) -> Self {
+        self.people_roles.push(PersonRoleFilter::new(name, role));
+        self
+    }
+
+    /// Helper to require a field to be present
+    pub fn with_has(mut self, field: PresenceField) -> Self {
+        self.has.push(field);
+        self
+    }
+
+    /// Helper to require a field to be missing
+    pub fn with_missing(mut self, field: PresenceField) -> Self {
+        self.missing.push(field);
+        self
+    }
+}
+
+/// Modalità di corrispondenza per più tag
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TagMatch {
+    /// Almeno uno dei tag
+    #[default]
+    Any,
+    /// Tutti i tag
+    All,
+}
+
+impl std::str::FromStr for TagMatch {
+    type Err = std::convert::Infallible;
+
+    /// Qualsiasi valore diverso da `all` equivale a `any`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "all" => Ok(Self::All),
+            _ => Ok(Self::Any),
+        }
+    }
+}
+
+impl TagMatch {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Any => "any",
+            Self::All => "all",
+        }
+    }
+}
+
+/// Persona con un ruolo specifico (es. "tradotto da X")
+///
+/// Il ruolo è confrontato con la chiave in `roles` sia così com'è sia con il
+/// prefisso `role.`, quindi `translator` e `role.translator` sono equivalenti.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PersonRoleFilter {
+    /// Nome della persona (sottostringa)
+    pub name: String,
+    /// Chiave del ruolo
+    pub role: String,
+}
+
+impl PersonRoleFilter {
+    pub fn new(name: impl Into<String>, role: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            role: role.into(),
+        }
+    }
+
+    /// Analizza il formato "Nome:ruolo" (il ruolo è dopo l'ultimo ':')
+    pub fn parse(s: &str) -> Option<Self> {
+        let (name, role) = s.rsplit_once(':')?;
+        let (name, role) = (name.trim(), role.trim());
+        if name.is_empty() || role.is_empty() {
+            return None;
+        }
+        Some(Self::new(name, role))
+    }
+
+    /// Chiavi di ruolo accettate: il valore dato e la forma con prefisso `role.`
+    pub fn role_keys(&self) -> (String, String) {
+        let bare = self.role.strip_prefix("role.").unwrap_or(&self.role);
+        (bare.to_string(), format!("role.{}", bare))
+    }
+}
+
+impl std::fmt::Display for PersonRoleFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.name, self.role)
+    }
+}
+
+/// Campi di cui si può richiedere la presenza (`has`) o l'assenza (`missing`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresenceField {
+    /// ISBN (solo libri)
+    Isbn,
+    /// Copertina (solo libri)
+    Cover,
+    /// File nella libreria (solo libri)
+    File,
+    /// Serie (solo libri)
+    Series,
+    /// Editore (solo libri)
+    Publisher,
+    /// Almeno un autore (per i libri anche tramite i contenuti)
+    Author,
+    /// Almeno una lingua (per i libri tramite i contenuti)
+    Language,
+    /// Tipo di contenuto (solo contenuti)
+    Type,
+    /// Numero di pagine
+    Pages,
+}
+
+impl std::str::FromStr for PresenceField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "isbn" => Ok(Self::Isbn),
+            "cover" => Ok(Self::Cover),
+            "file" => Ok(Self::File),
+            "series" => Ok(Self::Series),
+            "publisher" => Ok(Self::Publisher),
+            "author" => Ok(Self::Author),
+            "language" => Ok(Self::Language),
+            "type" => Ok(Self::Type),
+            "pages" => Ok(Self::Pages),
+            _ => Err(format!("Campo non valido: '{}'", s)),
+        }
+    }
+}
+
+impl PresenceField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Isbn => "isbn",
+            Self::Cover => "cover",
+            Self::File => "file",
+            Self::Series => "series",
+            Self::Publisher => "publisher",
+            Self::Author => "author",
+            Self::Language => "language",
+            Self::Type => "type",
+            Self::Pages => "pages",
+        }
+    }
+
+    /// Il campo ha senso per i libri
+    pub fn applies_to_books(&self) -> bool {
+        !matches!(self, Self::Type)
+    }
+
+    /// Il campo ha senso per i contenuti
+    pub fn applies_to_contents(&self) -> bool {
+        matches!(
+            self,
+            Self::Author | Self::Language | Self::Type | Self::Pages
+        )
+    }
+}
+
+/// Normalizza un ruolo lingua: `original`, `source`, `actual` o la chiave completa
+pub fn normalize_language_role(role: &str) -> Option<String> {
+    let bare = role.trim().to_lowercase();
+    let bare = bare.strip_prefix("language_role.").unwrap_or(&bare);
+    matches!(bare, "original" | "source" | "actual").then(|| format!("language_role.{}", bare))
 }
 
 /// Campi per ordinamento libri
@@ -149,10 +359,27 @@ pub struct ContentFilters {
     pub authors: Vec<String>,
     /// Content types (OR logic if multiple)
     pub content_types: Vec<String>,
+    /// Tags (exact, case-insensitive; any/all according to `tag_match`)
+    pub tags: Vec<String>,
+    pub tag_match: TagMatch,
+    /// Languages (ISO code or name, OR logic if multiple)
+    pub languages: Vec<String>,
+    /// Restricts `languages` to a role (`language_role.original`, `.source`, `.actual`)
+    pub language_role: Option<String>,
+    /// People with a specific role (AND logic)
+    pub people_roles: Vec<PersonRoleFilter>,
+    /// Fields that must be present / missing
+    pub has: Vec<PresenceField>,
+    pub missing: Vec<PresenceField>,
+    /// Page range (inclusive)
+    pub pages_min: Option<i64>,
+    pub pages_max: Option<i64>,
     /// Publication year (exact match)
     pub year: Option<i32>,
     /// Full-text search
     pub search: Option<String>,
+    /// Boolean query expression (AND with the other filters)
+    pub query: Option<QueryExpr>,
     /// Sort configuration
     pub sort: ContentSortField,
     /// Result pagination
@@ -188,6 +415,42 @@ impl ContentFilters {
         }
         self
     }
+
+    /// Helper to set a parsed query expression
+    pub fn with_query(mut self, query: QueryExpr) -> Self {
+        self.query = Some(query);
+        self
+    }
+
+    /// Helper to add a single tag
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Helper to add a single language
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.languages.push(language.into());
+        self
+    }
+
+    /// Helper to add a person with a role (e.g. translated by X)
+    pub fn with_person_role(mut self, name: impl Into<String>, role: impl Into<String>) -> Self {
+        self.people_roles.push(PersonRoleFilter::new(name, role));
+        self
+    }
+
+    /// Helper to require a field to be present
+    pub fn with_has(mut self, field: PresenceField) -> Self {
+        self.has.push(field);
+        self
+    }
+
+    /// Helper to require a field to be missing
+    pub fn with_missing(mut self, field: PresenceField) -> Self {
+        self.missing.push(field);
+        self
+    }
 }
 
 /// Campi per ordinamento contenuti
@@ -235,6 +498,12 @@ pub struct BookResult {
     pub pages: Option<i64>,
     pub file_link: Option<String>,
     pub created_at: i64,
+    /// Autori del libro separati da virgola
+    pub authors: Option<String>,
+    /// Tag del libro separati da virgola
+    pub tags: Option<String>,
+    /// Dimensione totale dei file in byte
+    pub file_size: Option<i64>,
 }
 
 impl BookResult {
@@ -280,6 +549,10 @@ pub struct ContentResult {
     pub publication_date: Option<i64>,
     pub pages: Option<i64>,
     pub created_at: i64,
+    /// Autori del contenuto separati da virgola
+    pub authors: Option<String>,
+    /// Tag del contenuto separati da virgola
+    pub tags: Option<String>,
 }
 
 impl ContentResult {
@@ -363,6 +636,44 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_person_role_filter_parse() {
+        let filter = PersonRoleFilter::parse("Italo Calvino:translator").unwrap();
+        assert_eq!(filter.name, "Italo Calvino");
+        assert_eq!(filter.role, "translator");
+        assert_eq!(
+            filter.role_keys(),
+            ("translator".to_string(), "role.translator".to_string())
+        );
+        assert_eq!(
+            PersonRoleFilter::new("X", "role.author").role_keys(),
+            ("author".to_string(), "role.author".to_string())
+        );
+        assert!(PersonRoleFilter::parse("no role").is_none());
+        assert!(PersonRoleFilter::parse(":translator").is_none());
+    }
+
+    #[test]
+    fn test_presence_field_and_language_role() {
+        assert_eq!("ISBN".parse::<PresenceField>(), Ok(PresenceField::Isbn));
+        assert!("nope".parse::<PresenceField>().is_err());
+        assert!(PresenceField::Cover.applies_to_books());
+        assert!(!PresenceField::Cover.applies_to_contents());
+        assert!(PresenceField::Type.applies_to_contents());
+
+        assert_eq!(
+            normalize_language_role("Original"),
+            Some("language_role.original".to_string())
+        );
+        assert_eq!(
+            normalize_language_role("language_role.actual"),
+            Some("language_role.actual".to_string())
+        );
+        assert_eq!(normalize_language_role("translated"), None);
+        assert_eq!("ALL".parse::<TagMatch>(), Ok(TagMatch::All));
+        assert_eq!("whatever".parse::<TagMatch>(), Ok(TagMatch::Any));
+    }
+
     #[test]
     fn test_book_result_formatting() {
         let book = BookResult {
@@ -378,6 +689,9 @@ mod tests {
             pages: Some(320),
             file_link: Some("/path/to/book.epub".to_string()),
             created_at: 1609459200, // 2021-01-01
+            authors: Some("Italo Calvino".to_string()),
+            tags: None,
+            file_size: Some(524288),
         };
 
         assert_eq!(
@@ -398,6 +712,8 @@ mod tests {
             publication_date: Some(1262304000), // 2010-01-01
             pages: Some(250),
             created_at: 1609459200,
+            authors: Some("Italo Calvino".to_string()),
+            tags: None,
         };
 
         assert_eq!(
@@ -3,7 +3,10 @@
 //! This module provides validation functions for filter inputs to ensure
 //! data integrity and prevent potential issues.
 
-use super::types::{BookFilters, ContentFilters};
+use super::query::{FieldKind, QueryExpr, QueryTarget, QueryValue};
+use super::types::{
+    normalize_language_role, BookFilters, ContentFilters, PersonRoleFilter, PresenceField,
+};
 
 /// Validation errors
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -22,6 +25,20 @@ pub enum ValidationError {
     InvalidDateRange { after: i64, before: i64 },
     /// Empty filter value
     EmptyValue { field: String },
+    /// Query text that cannot be parsed
+    QuerySyntax { message: String },
+    /// Query references a field that does not exist for the target
+    UnknownQueryField { field: String, allowed: Vec<String> },
+    /// Query value not valid for the field (e.g. non-numeric year, range on text)
+    InvalidQueryValue { field: String, value: String },
+    /// Value not accepted by a filter (e.g. unknown language role)
+    InvalidValue { field: String, value: String },
+    /// Invalid page range (min > max)
+    InvalidPageRange { min: i64, max: i64 },
+    /// Presence field that does not apply to the target (e.g. `cover` on contents)
+    UnsupportedPresenceField { field: String },
+    /// Same field required both present and missing
+    ConflictingPresence { field: String },
 }
 
 impl std::fmt::Display for ValidationError {
@@ -46,6 +63,32 @@ impl std::fmt::Display for ValidationError {
             ValidationError::EmptyValue { field } => {
                 write!(f, "Empty value provided for filter '{}'", field)
             }
+            ValidationError::QuerySyntax { message } => {
+                write!(f, "Invalid query: {}", message)
+            }
+            ValidationError::UnknownQueryField { field, allowed } => {
+                write!(
+                    f,
+                    "Unknown query field '{}' (available: {})",
+                    field,
+                    allowed.join(", ")
+                )
+            }
+            ValidationError::InvalidQueryValue { field, value } => {
+                write!(f, "Invalid value '{}' for query field '{}'", value, field)
+            }
+            ValidationError::InvalidValue { field, value } => {
+                write!(f, "Invalid value '{}' for filter '{}'", value, field)
+            }
+            ValidationError::InvalidPageRange { min, max } => {
+                write!(f, "Invalid page range: min ({}) > max ({})", min, max)
+            }
+            ValidationError::UnsupportedPresenceField { field } => {
+                write!(f, "Field '{}' cannot be used with has/missing here", field)
+            }
+            ValidationError::ConflictingPresence { field } => {
+                write!(f, "Field '{}' is required both present and missing", field)
+            }
         }
     }
 }
@@ -111,6 +154,36 @@ pub fn validate_book_filters(filters: &BookFilters) -> Result<(), Vec<Validation
         }
     }
 
+    validate_attribute_filters(
+        AttributeValues {
+            tags: &filters.tags,
+            languages: &filters.languages,
+            language_role: filters.language_role.as_deref(),
+            people_roles: &filters.people_roles,
+            has: &filters.has,
+            missing: &filters.missing,
+            pages_min: filters.pages_min,
+            pages_max: filters.pages_max,
+        },
+        QueryTarget::Books,
+        &mut errors,
+    );
+
+    if filters.publisher_countries.len() > MAX_VALUES {
+        errors.push(ValidationError::TooManyValues {
+            field: "publisher_countries".to_string(),
+            count: filters.publisher_countries.len(),
+            max: MAX_VALUES,
+        });
+    }
+
+    // Validate query expression
+    if let Some(query) = &filters.query {
+        if let Err(mut query_errors) = validate_query(query, QueryTarget::Books) {
+            errors.append(&mut query_errors);
+        }
+    }
+
     if errors.is_empty() {
         Ok(())
     } else {
@@ -153,6 +226,28 @@ pub fn validate_content_filters(filters: &ContentFilters) -> Result<(), Vec<Vali
         });
     }
 
+    validate_attribute_filters(
+        AttributeValues {
+            tags: &filters.tags,
+            languages: &filters.languages,
+            language_role: filters.language_role.as_deref(),
+            people_roles: &filters.people_roles,
+            has: &filters.has,
+            missing: &filters.missing,
+            pages_min: filters.pages_min,
+            pages_max: filters.pages_max,
+        },
+        QueryTarget::Contents,
+        &mut errors,
+    );
+
+    // Validate query expression
+    if let Some(query) = &filters.query {
+        if let Err(mut query_errors) = validate_query(query, QueryTarget::Contents) {
+            errors.append(&mut query_errors);
+        }
+    }
+
     if errors.is_empty() {
         Ok(())
     } else {
@@ -160,9 +255,200 @@ pub fn validate_content_filters(filters: &ContentFilters) -> Result<(), Vec<Vali
     }
 }
 
+/// Filter values shared by books and contents
+struct AttributeValues<'a> {
+    tags: &'a [String],
+    languages: &'a [String],
+    language_role: Option<&'a str>,
+    people_roles: &'a [PersonRoleFilter],
+    has: &'a [PresenceField],
+    missing: &'a [PresenceField],
+    pages_min: Option<i64>,
+    pages_max: Option<i64>,
+}
+
+/// Validates tags, languages, people with roles, presence fields and page range
+fn validate_attribute_filters(
+    values: AttributeValues,
+    target: QueryTarget,
+    errors: &mut Vec<ValidationError>,
+) {
+    const MAX_VALUES: usize = 50;
+
+    for (field, count) in [
+        ("tags", values.tags.len()),
+        ("languages", values.languages.len()),
+        ("people_roles", values.people_roles.len()),
+    ] {
+        if count > MAX_VALUES {
+            errors.push(ValidationError::TooManyValues {
+                field: field.to_string(),
+                count,
+                max: MAX_VALUES,
+            });
+        }
+    }
+
+    let empty_values = values
+        .tags
+        .iter()
+        .map(|v| ("tag", v))
+        .chain(values.languages.iter().map(|v| ("language", v)))
+        .chain(values.people_roles.iter().map(|p| ("person", &p.name)))
+        .chain(values.people_roles.iter().map(|p| ("role", &p.role)));
+    for (field, value) in empty_values {
+        if value.trim().is_empty() {
+            errors.push(ValidationError::EmptyValue {
+                field: field.to_string(),
+            });
+        }
+    }
+
+    if let Some(role) = values.language_role {
+        if normalize_language_role(role).as_deref() != Some(role) {
+            errors.push(ValidationError::InvalidValue {
+                field: "language_role".to_string(),
+                value: role.to_string(),
+            });
+        }
+    }
+
+    for field in values.has.iter().chain(values.missing.iter()) {
+        let applies = match target {
+            QueryTarget::Books => field.applies_to_books(),
+            QueryTarget::Contents => field.applies_to_contents(),
+        };
+        if !applies {
+            errors.push(ValidationError::UnsupportedPresenceField {
+                field: field.as_str().to_string(),
+            });
+        }
+    }
+    for field in values.has {
+        if values.missing.contains(field) {
+            errors.push(ValidationError::ConflictingPresence {
+                field: field.as_str().to_string(),
+            });
+        }
+    }
+
+    for (field, value) in [("pages_min", values.pages_min), ("pages_max", values.pages_max)] {
+        if let Some(pages) = value.filter(|p| *p < 0) {
+            errors.push(ValidationError::InvalidValue {
+                field: field.to_string(),
+                value: pages.to_string(),
+            });
+        }
+    }
+    if let (Some(min), Some(max)) = (values.pages_min, values.pages_max) {
+        if min > max {
+            errors.push(ValidationError::InvalidPageRange { min, max });
+        }
+    }
+}
+
+/// Validates a parsed query against the fields of the target entity
+///
+/// Checks that every field exists, that text fields only receive text and that
+/// numeric fields receive integers, comparisons or ordered ranges.
+pub fn validate_query(expr: &QueryExpr, target: QueryTarget) -> Result<(), Vec<ValidationError>> {
+    let mut errors = Vec::new();
+    collect_query_errors(expr, target, &mut errors);
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn collect_query_errors(expr: &QueryExpr, target: QueryTarget, errors: &mut Vec<ValidationError>) {
+    let term = match expr {
+        QueryExpr::And(parts) | QueryExpr::Or(parts) => {
+            for part in parts {
+                collect_query_errors(part, target, errors);
+            }
+            return;
+        }
+        QueryExpr::Not(inner) => return collect_query_errors(inner, target, errors),
+        QueryExpr::Term(term) => term,
+    };
+
+    // Ricerca full-text: solo testo non vuoto
+    let Some(field) = &term.field else {
+        match &term.value {
+            QueryValue::Text(text) if text.trim().is_empty() => {
+                errors.push(ValidationError::EmptyValue {
+                    field: "search".to_string(),
+                })
+            }
+            QueryValue::Text(_) => {}
+            other => errors.push(ValidationError::InvalidQueryValue {
+                field: "search".to_string(),
+                value: describe_value(other),
+            }),
+        }
+        return;
+    };
+
+    let Some(kind) = target.field_kind(field) else {
+        errors.push(ValidationError::UnknownQueryField {
+            field: field.clone(),
+            allowed: target.fields().map(String::from).collect(),
+        });
+        return;
+    };
+
+    let invalid = || ValidationError::InvalidQueryValue {
+        field: field.clone(),
+        value: describe_value(&term.value),
+    };
+    let is_number = |s: &str| s.parse::<i64>().is_ok();
+
+    match (kind, &term.value) {
+        (_, QueryValue::Text(text)) if text.trim().is_empty() => {
+            errors.push(ValidationError::EmptyValue {
+                field: field.clone(),
+            })
+        }
+        (FieldKind::Text, QueryValue::Text(_)) => {}
+        (FieldKind::Text, _) => errors.push(invalid()),
+        (FieldKind::Number, QueryValue::Text(value))
+        | (FieldKind::Number, QueryValue::Compare(_, value)) => {
+            if !is_number(value) {
+                errors.push(invalid());
+            }
+        }
+        (FieldKind::Number, QueryValue::Range(low, high)) => {
+            let bounds: Vec<&String> = low.iter().chain(high.iter()).collect();
+            if bounds.is_empty() || !bounds.iter().all(|b| is_number(b)) {
+                errors.push(invalid());
+            } else if let (Some(low), Some(high)) = (low, high) {
+                if low.parse::<i64>().ok() > high.parse::<i64>().ok() {
+                    errors.push(invalid());
+                }
+            }
+        }
+    }
+}
+
+/// Rappresentazione testuale di un valore per i messaggi di errore
+fn describe_value(value: &QueryValue) -> String {
+    match value {
+        QueryValue::Text(text) => text.clone(),
+        QueryValue::Compare(op, value) => format!("{}{}", op.to_sql(), value),
+        QueryValue::Range(low, high) => format!(
+            "{}..{}",
+            low.as_deref().unwrap_or(""),
+            high.as_deref().unwrap_or("")
+        ),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::filters::query::parse_query;
 
     #[test]
     fn test_validate_book_filters_valid() {
@@ -228,6 +514,104 @@ mod tests {
         assert!(validate_content_filters(&filters).is_ok());
     }
 
+    #[test]
+    fn test_validate_query_valid() {
+        let query = parse_query(
+            "author:king AND (format:epub OR format:pdf) AND NOT tag:read AND year:1980..1990 AND pages:>400",
+        )
+        .unwrap();
+        assert!(validate_query(&query, QueryTarget::Books).is_ok());
+
+        let filters = BookFilters {
+            query: Some(query),
+            ..Default::default()
+        };
+        assert!(validate_book_filters(&filters).is_ok());
+    }
+
+    #[test]
+    fn test_validate_query_unknown_field() {
+        let query = parse_query("format:epub").unwrap();
+        let errors = validate_query(&query, QueryTarget::Contents).unwrap_err();
+        assert!(matches!(
+            &errors[0],
+            ValidationError::UnknownQueryField { field, .. } if field == "format"
+        ));
+
+        let filters = ContentFilters {
+            query: Some(query),
+            ..Default::default()
+        };
+        assert!(validate_content_filters(&filters).is_err());
+    }
+
+    #[test]
+    fn test_validate_query_invalid_values() {
+        for q in [
+            "year:abc",
+            "pages:>many",
+            "year:1990..1980",
+            "year:..",
+            "author:>3",
+            "title:1..2",
+        ] {
+            let query = parse_query(q).unwrap();
+            let errors = validate_query(&query, QueryTarget::Books).unwrap_err();
+            assert!(
+                matches!(errors[0], ValidationError::InvalidQueryValue { .. }),
+                "{} should be invalid",
+                q
+            );
+        }
+
+        let query = parse_query("author:\"\"").unwrap();
+        let errors = validate_query(&query, QueryTarget::Books).unwrap_err();
+        assert!(matches!(errors[0], ValidationError::EmptyValue { .. }));
+    }
+
+    #[test]
+    fn test_validate_attribute_filters() {
+        let filters = BookFilters {
+            language_role: Some("language_role.original".to_string()),
+            pages_min: Some(100),
+            pages_max: Some(400),
+            ..Default::default()
+        }
+        .with_tag("read")
+        .with_person_role("Fruttero", "translator")
+        .with_missing(PresenceField::Isbn);
+        assert!(validate_book_filters(&filters).is_ok());
+
+        let filters = BookFilters {
+            language_role: Some("original".to_string()),
+            pages_min: Some(400),
+            pages_max: Some(100),
+            ..Default::default()
+        }
+        .with_tag(" ")
+        .with_has(PresenceField::Type)
+        .with_has(PresenceField::Isbn)
+        .with_missing(PresenceField::Isbn);
+        let errors = validate_book_filters(&filters).unwrap_err();
+        assert!(errors.contains(&ValidationError::InvalidValue {
+            field: "language_role".to_string(),
+            value: "original".to_string(),
+        }));
+        assert!(errors.contains(&ValidationError::InvalidPageRange { min: 400, max: 100 }));
+        assert!(errors.contains(&ValidationError::EmptyValue {
+            field: "tag".to_string()
+        }));
+        assert!(errors.contains(&ValidationError::UnsupportedPresenceField {
+            field: "type".to_string()
+        }));
+        assert!(errors.contains(&ValidationError::ConflictingPresence {
+            field: "isbn".to_string()
+        }));
+
+        let filters = ContentFilters::default().with_has(PresenceField::Cover);
+        assert!(validate_content_filters(&filters).is_err());
+    }
+
     #[test]
     fn test_validation_error_display() {
         let error = ValidationError::NegativeOffset;
@@ -0,0 +1,369 @@
+//! Validazione, normalizzazione e conversione ISBN-10/ISBN-13
+//!
+//! `books.isbn` conserva la forma di visualizzazione (con i trattini inseriti
+//! dall'utente), `books.isbn13` la forma canonica: ISBN-13 senza separatori,
+//! usata per confronti, ricerche e rilevamento dei duplicati.
+
+use std::fmt;
+
+/// Motivo per cui un ISBN non è valido
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IsbnError {
+    /// Stringa vuota dopo la rimozione di prefissi e separatori
+    Empty,
+    /// Carattere non ammesso (solo cifre, e `X` come ultima cifra di un ISBN-10)
+    InvalidCharacter(char),
+    /// Numero di cifre diverso da 10 o 13
+    InvalidLength(usize),
+    /// ISBN-13 che non inizia con 978 o 979
+    InvalidPrefix,
+    /// Cifra di controllo errata
+    InvalidChecksum { expected: char, found: char },
+}
+
+impl fmt::Display for IsbnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "ISBN vuoto"),
+            Self::InvalidCharacter(c) => write!(f, "carattere non valido '{}'", c),
+            Self::InvalidLength(len) => {
+                write!(f, "lunghezza non valida: {} cifre (attese 10 o 13)", len)
+            }
+            Self::InvalidPrefix => write!(f, "un ISBN-13 deve iniziare con 978 o 979"),
+            Self::InvalidChecksum { expected, found } => write!(
+                f,
+                "cifra di controllo errata: {} (attesa {})",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for IsbnError {}
+
+/// Come trattare un ISBN non valido in import e aggiornamento
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IsbnPolicy {
+    /// Salva comunque il valore e segnala un avviso
+    #[default]
+    Warn,
+    /// Rifiuta il valore con un errore
+    Error,
+}
+
+impl IsbnPolicy {
+    pub fn from_strict(strict: bool) -> Self {
+        if strict {
+            Self::Error
+        } else {
+            Self::Warn
+        }
+    }
+}
+
+/// ISBN validato, memorizzato come cifre senza separatori
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Isbn {
+    digits: String,
+}
+
+impl Isbn {
+    /// Analizza un ISBN-10 o ISBN-13, con o senza prefisso (`ISBN`, `urn:isbn:`)
+    /// e separatori (trattini, spazi, punti)
+    pub fn parse(value: &str) -> Result<Self, IsbnError> {
+        let digits = compact(value);
+        if digits.is_empty() {
+            return Err(IsbnError::Empty);
+        }
+        if let Some(c) = digits.chars().find(|c| !c.is_ascii_digit() && *c != 'X') {
+            return Err(IsbnError::InvalidCharacter(c));
+        }
+
+        match digits.len() {
+            10 => {
+                if digits[..9].contains('X') {
+                    return Err(IsbnError::InvalidCharacter('X'));
+                }
+                check_digit(&digits, isbn10_check_digit(&digits[..9]))?;
+            }
+            13 => {
+                if digits.contains('X') {
+                    return Err(IsbnError::InvalidCharacter('X'));
+                }
+                if !digits.starts_with("978") && !digits.starts_with("979") {
+                    return Err(IsbnError::InvalidPrefix);
+                }
+                check_digit(&digits, isbn13_check_digit(&digits[..12]))?;
+            }
+            len => return Err(IsbnError::InvalidLength(len)),
+        }
+
+        Ok(Self { digits })
+    }
+
+    /// True se l'ISBN è stato indicato nella forma a 10 cifre
+    pub fn is_isbn10(&self) -> bool {
+        self.digits.len() == 10
+    }
+
+    /// Forma compatta così come indicata (10 o 13 cifre)
+    pub fn as_compact(&self) -> &str {
+        &self.digits
+    }
+
+    /// Forma canonica ISBN-13 senza separatori
+    pub fn to_isbn13(&self) -> String {
+        if self.is_isbn10() {
+            let body = format!("978{}", &self.digits[..9]);
+            let check = isbn13_check_digit(&body);
+            format!("{}{}", body, check)
+        } else {
+            self.digits.clone()
+        }
+    }
+
+    /// Forma ISBN-10 senza separatori; None per gli ISBN-13 con prefisso 979,
+    /// che non hanno un equivalente a 10 cifre
+    pub fn to_isbn10(&self) -> Option<String> {
+        if self.is_isbn10() {
+            return Some(self.digits.clone());
+        }
+        let body = self.digits.strip_prefix("978")?;
+        let body = &body[..9];
+        Some(format!("{}{}", body, isbn10_check_digit(body)))
+    }
+}
+
+impl fmt::Display for Isbn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.digits)
+    }
+}
+
+/// Rimuove prefissi (`urn:isbn:`, `ISBN-13:`, `ISBN`) e separatori,
+/// restituendo le sole cifre (e l'eventuale `X` maiuscola)
+pub fn compact(value: &str) -> String {
+    strip_prefix(value)
+        .chars()
+        .filter(|c| !is_separator(*c))
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Forma di visualizzazione: senza prefissi, separatori uniformati a un
+/// singolo trattino, `x` finale maiuscola.
+///
+/// La suddivisione in gruppi indicata dall'utente viene mantenuta; un ISBN
+/// senza separatori resta compatto.
+pub fn normalize_display(value: &str) -> String {
+    let mut display = String::new();
+    for c in strip_prefix(value).chars() {
+        if is_separator(c) {
+            if !display.is_empty() && !display.ends_with('-') {
+                display.push('-');
+            }
+        } else {
+            display.push(c.to_ascii_uppercase());
+        }
+    }
+    display.trim_end_matches('-').to_string()
+}
+
+/// ISBN-13 canonico di un valore, se è un ISBN valido
+pub fn canonical_isbn13(value: &str) -> Option<String> {
+    Isbn::parse(value).ok().map(|isbn| isbn.to_isbn13())
+}
+
+/// Esito della verifica di un ISBN prima del salvataggio
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckedIsbn {
+    /// Valore da salvare in `books.isbn`
+    pub display: String,
+    /// Valore da salvare in `books.isbn13` (None se l'ISBN non è valido)
+    pub isbn13: Option<String>,
+    /// Avviso da mostrare quando l'ISBN non è valido e la policy è `Warn`
+    pub warning: Option<String>,
+}
+
+/// Verifica un ISBN secondo la policy indicata
+///
+/// Un ISBN valido viene normalizzato; uno non valido produce un errore con
+/// `IsbnPolicy::Error`, altrimenti viene salvato così com'è con un avviso.
+pub fn check_isbn(value: &str, policy: IsbnPolicy) -> Result<CheckedIsbn, String> {
+    match Isbn::parse(value) {
+        Ok(isbn) => Ok(CheckedIsbn {
+            display: normalize_display(value),
+            isbn13: Some(isbn.to_isbn13()),
+            warning: None,
+        }),
+        Err(e) => {
+            let message = format!("ISBN '{}' non valido: {}", value.trim(), e);
+            match policy {
+                IsbnPolicy::Error => Err(message),
+                IsbnPolicy::Warn => Ok(CheckedIsbn {
+                    display: value.trim().to_string(),
+                    isbn13: None,
+                    warning: Some(message),
+                }),
+            }
+        }
+    }
+}
+
+fn strip_prefix(value: &str) -> &str {
+    let trimmed = value.trim();
+    let lower = trimmed.to_ascii_lowercase();
+    let mut skip = 0;
+    for prefix in [
+        "urn:isbn:",
+        "isbn-13",
+        "isbn-10",
+        "isbn13",
+        "isbn10",
+        "isbn",
+    ] {
+        if lower.starts_with(prefix) {
+            skip = prefix.len();
+            break;
+        }
+    }
+    trimmed[skip..].trim_start_matches([':', ' '])
+}
+
+fn is_separator(c: char) -> bool {
+    matches!(
+        c,
+        '-' | ' ' | '.' | '_' | '\u{2010}' | '\u{2011}' | '\u{2013}'
+    )
+}
+
+fn check_digit(digits: &str, expected: char) -> Result<(), IsbnError> {
+    let found = digits.chars().last().unwrap_or_default();
+    if found == expected {
+        Ok(())
+    } else {
+        Err(IsbnError::InvalidChecksum { expected, found })
+    }
+}
+
+/// Cifra di controllo ISBN-10 (modulo 11, pesi 10..2) per le prime 9 cifre
+fn isbn10_check_digit(body: &str) -> char {
+    let sum: u32 = body
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .zip((2..=10).rev())
+        .map(|(d, w)| d * w)
+        .sum();
+    match (11 - sum % 11) % 11 {
+        10 => 'X',
+        d => char::from_digit(d, 10).unwrap_or('0'),
+    }
+}
+
+/// Cifra di controllo ISBN-13 (modulo 10, pesi alternati 1 e 3) per le prime 12 cifre
+fn isbn13_check_digit(body: &str) -> char {
+    let sum: u32 = body
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(i, d)| if i % 2 == 0 { d } else { d * 3 })
+        .sum();
+    char::from_digit((10 - sum % 10) % 10, 10).unwrap_or('0')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_valid_forms() {
+        let isbn = Isbn::parse("978-88-04-66823-7").unwrap();
+        assert_eq!(isbn.as_compact(), "9788804668237");
+        assert!(!isbn.is_isbn10());
+
+        let isbn = Isbn::parse("ISBN 0-306-40615-2").unwrap();
+        assert!(isbn.is_isbn10());
+        assert_eq!(isbn.to_isbn13(), "9780306406157");
+
+        // X come cifra di controllo, anche minuscola
+        let isbn = Isbn::parse("urn:isbn:0-8044-2957-x").unwrap();
+        assert_eq!(isbn.as_compact(), "080442957X");
+        assert!(Isbn::parse("ISBN-13: 979-10-90636-07-1").is_ok());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Isbn::parse(" - "), Err(IsbnError::Empty));
+        assert_eq!(Isbn::parse("12345"), Err(IsbnError::InvalidLength(5)));
+        assert_eq!(
+            Isbn::parse("B00ABCDEFG"),
+            Err(IsbnError::InvalidCharacter('B'))
+        );
+        assert_eq!(
+            Isbn::parse("978-88-04-66823-8"),
+            Err(IsbnError::InvalidChecksum {
+                expected: '7',
+                found: '8'
+            })
+        );
+        assert_eq!(
+            Isbn::parse("0-306-40615-3"),
+            Err(IsbnError::InvalidChecksum {
+                expected: '2',
+                found: '3'
+            })
+        );
+        assert_eq!(Isbn::parse("1234567890128"), Err(IsbnError::InvalidPrefix));
+        assert_eq!(
+            Isbn::parse("X306406152"),
+            Err(IsbnError::InvalidCharacter('X'))
+        );
+    }
+
+    #[test]
+    fn test_conversion_roundtrip() {
+        let isbn13 = Isbn::parse("9780306406157").unwrap();
+        assert_eq!(isbn13.to_isbn10().as_deref(), Some("0306406152"));
+
+        let isbn10 = Isbn::parse("080442957X").unwrap();
+        let converted = Isbn::parse(&isbn10.to_isbn13()).unwrap();
+        assert_eq!(converted.to_isbn10().as_deref(), Some("080442957X"));
+
+        // I 979 non hanno forma a 10 cifre
+        assert_eq!(Isbn::parse("9791090636071").unwrap().to_isbn10(), None);
+    }
+
+    #[test]
+    fn test_normalize_display() {
+        assert_eq!(
+            normalize_display("ISBN: 978 88 04 66823 7"),
+            "978-88-04-66823-7"
+        );
+        assert_eq!(
+            normalize_display("978--88-04-66823-7 "),
+            "978-88-04-66823-7"
+        );
+        assert_eq!(normalize_display("0-8044-2957-x"), "0-8044-2957-X");
+        assert_eq!(normalize_display("9788804668237"), "9788804668237");
+        assert_eq!(
+            canonical_isbn13("0-306-40615-2").as_deref(),
+            Some("9780306406157")
+        );
+        assert_eq!(canonical_isbn13("non un isbn"), None);
+    }
+
+    #[test]
+    fn test_check_isbn_policy() {
+        let checked = check_isbn("isbn 978 88 04 66823 7", IsbnPolicy::Error).unwrap();
+        assert_eq!(checked.display, "978-88-04-66823-7");
+        assert_eq!(checked.isbn13.as_deref(), Some("9788804668237"));
+        assert!(checked.warning.is_none());
+
+        let checked = check_isbn("978-88-04-66823-8", IsbnPolicy::Warn).unwrap();
+        assert_eq!(checked.display, "978-88-04-66823-8");
+        assert!(checked.isbn13.is_none());
+        assert!(checked.warning.unwrap().contains("cifra di controllo"));
+
+        assert!(check_isbn("978-88-04-66823-8", IsbnPolicy::Error).is_err());
+    }
+}
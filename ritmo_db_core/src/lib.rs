@@ -1,13 +1,17 @@
 pub mod config;
 pub mod database;
 pub mod filters;
+pub mod isbn;
 pub mod library_presets;
 pub mod maintenance;
+pub mod migrations;
+pub mod vocabulary;
 
 pub use database::Database;
 pub use filters::{
     build_books_query, build_contents_query, execute_books_query, execute_contents_query,
-    BookFilters, BookResult, BookSortField, ContentFilters, ContentResult, ContentSortField,
+    parse_query, BookFilters, BookResult, BookSortField, ContentFilters, ContentResult,
+    ContentSortField, QueryExpr, QueryTarget,
 };
 pub use library_presets::LibraryPresets;
 use ritmo_errors::reporter::RitmoReporter;
@@ -314,6 +318,16 @@ impl LibraryConfig {
             .await
             .map_err(|e| ritmo_errors::RitmoErr::DatabaseConnectionFailed(e.to_string()))?;
 
+        // Aggiorna le librerie create con un template precedente
+        let version = migrations::migrate(&pool).await?;
+        if version < migrations::SCHEMA_VERSION {
+            reporter.status(&format!(
+                "Schema aggiornato dalla versione {} alla {}",
+                version,
+                migrations::SCHEMA_VERSION
+            ));
+        }
+
         Ok(pool)
     }
 
@@ -23,6 +23,14 @@ pub struct LibraryPresets {
     /// Preset di default per la vista contenuti (opzionale)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub default_contents_preset: Option<String>,
+
+    /// Colonne di default per list-books (es. ["title", "authors", "year"])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub default_books_columns: Vec<String>,
+
+    /// Colonne di default per list-contents
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub default_contents_columns: Vec<String>,
 }
 
 impl LibraryPresets {
@@ -162,6 +170,16 @@ impl LibraryPresets {
     pub fn get_default_contents_preset(&self) -> Option<&str> {
         self.default_contents_preset.as_deref()
     }
+
+    /// Imposta le colonne di default per la vista libri (vuoto = colonne standard)
+    pub fn set_default_books_columns(&mut self, columns: Vec<String>) {
+        self.default_books_columns = columns;
+    }
+
+    /// Imposta le colonne di default per la vista contenuti (vuoto = colonne standard)
+    pub fn set_default_contents_columns(&mut self, columns: Vec<String>) {
+        self.default_contents_columns = columns;
+    }
 }
 
 #[cfg(test)]
@@ -249,6 +267,18 @@ mod tests {
         assert!(presets.get_default_books_preset().is_none());
     }
 
+    #[test]
+    fn test_default_columns_roundtrip() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut presets = LibraryPresets::new();
+        presets.set_default_books_columns(vec!["title".to_string(), "authors".to_string()]);
+        presets.save(temp_file.path()).unwrap();
+
+        let loaded = LibraryPresets::load_or_create(temp_file.path()).unwrap();
+        assert_eq!(loaded.default_books_columns, vec!["title", "authors"]);
+        assert!(loaded.default_contents_columns.is_empty());
+    }
+
     #[test]
     fn test_list_presets() {
         let presets = LibraryPresets::with_examples();
@@ -0,0 +1,266 @@
+//! Aggiornamento dello schema delle librerie esistenti
+//!
+//! Il database di una libreria viene creato copiando `assets/template.db`; le
+//! librerie create con un template più vecchio vengono aggiornate all'apertura
+//! (`LibraryConfig::create_pool`). La versione dello schema è in
+//! `PRAGMA user_version`: ogni migrazione porta il database da `n - 1` a `n`,
+//! in una transazione, ed è idempotente (un database creato da un template
+//! intermedio, con solo parte delle modifiche, viene completato).
+
+use ritmo_errors::{RitmoErr, RitmoResult};
+use sqlx::{Sqlite, SqlitePool, Transaction};
+
+/// Versione dello schema di `schema.sql` e del template
+pub const SCHEMA_VERSION: i64 = 1;
+
+/// Versione 1: file multipli per libro, ISBN-13 canonico, snapshot della
+/// sincronizzazione, cache dei provider, scaffali e journal delle unioni
+const V1_TABLES: &str = r#"
+CREATE TABLE IF NOT EXISTS "book_files" (
+	"id"	INTEGER,
+	"book_id"	INTEGER NOT NULL,
+	"format_id"	INTEGER,
+	"file_link"	TEXT NOT NULL UNIQUE,
+	"file_hash"	TEXT NOT NULL,
+	"file_size"	INTEGER,
+	"added_at"	INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+	"text_fingerprint"	TEXT,
+	PRIMARY KEY("id" AUTOINCREMENT),
+	FOREIGN KEY("book_id") REFERENCES "books"("id") ON DELETE CASCADE,
+	FOREIGN KEY("format_id") REFERENCES "formats"("id") ON DELETE SET NULL
+);
+CREATE TABLE IF NOT EXISTS "metadata_sync_snapshots" (
+	"book_id"	INTEGER NOT NULL,
+	"snapshot"	TEXT NOT NULL,
+	"synced_at"	INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+	PRIMARY KEY("book_id"),
+	FOREIGN KEY("book_id") REFERENCES "books"("id") ON DELETE CASCADE
+);
+CREATE TABLE IF NOT EXISTS "metadata_provider_cache" (
+	"id"	INTEGER,
+	"provider"	TEXT NOT NULL,
+	"query_key"	TEXT NOT NULL,
+	"response"	TEXT,
+	"fetched_at"	INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+	PRIMARY KEY("id" AUTOINCREMENT),
+	UNIQUE("provider","query_key")
+);
+CREATE TABLE IF NOT EXISTS "shelves" (
+	"id"	INTEGER,
+	"name"	TEXT NOT NULL UNIQUE,
+	"description"	TEXT,
+	"kind"	TEXT NOT NULL DEFAULT 'manual' CHECK("kind" IN ('smart', 'manual')),
+	"filter_json"	TEXT,
+	"created_at"	INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+	"updated_at"	INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+	PRIMARY KEY("id" AUTOINCREMENT)
+);
+CREATE TABLE IF NOT EXISTS "x_shelves_books" (
+	"shelf_id"	INTEGER NOT NULL,
+	"book_id"	INTEGER NOT NULL,
+	"position"	INTEGER NOT NULL,
+	"notes"	TEXT,
+	"added_at"	INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+	PRIMARY KEY("shelf_id","book_id"),
+	FOREIGN KEY("shelf_id") REFERENCES "shelves"("id") ON DELETE CASCADE,
+	FOREIGN KEY("book_id") REFERENCES "books"("id") ON DELETE CASCADE
+);
+CREATE TABLE IF NOT EXISTS "merge_journal" (
+	"id"	INTEGER,
+	"entity_type"	TEXT NOT NULL CHECK("entity_type" IN ('people', 'publishers', 'series', 'tags', 'roles', 'contents')),
+	"primary_id"	INTEGER NOT NULL,
+	"stats"	TEXT NOT NULL,
+	"payload"	TEXT NOT NULL,
+	"created_at"	INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+	"undone_at"	INTEGER,
+	PRIMARY KEY("id" AUTOINCREMENT)
+);
+"#;
+
+/// Indici e trigger della versione 1 (dopo le colonne aggiunte)
+const V1_INDEXES: &str = r#"
+CREATE INDEX IF NOT EXISTS "idx_books_isbn13" ON "books" (
+	"isbn13"
+) WHERE "isbn13" IS NOT NULL;
+CREATE INDEX IF NOT EXISTS "idx_book_files_book_lookup" ON "book_files" (
+	"book_id",
+	"format_id"
+);
+CREATE INDEX IF NOT EXISTS "idx_book_files_hash" ON "book_files" (
+	"file_hash"
+);
+CREATE INDEX IF NOT EXISTS "idx_shelves_books_book_lookup" ON "x_shelves_books" (
+	"book_id"
+);
+CREATE TRIGGER IF NOT EXISTS update_shelves_timestamp
+    AFTER UPDATE ON shelves
+    FOR EACH ROW
+BEGIN
+    UPDATE shelves SET updated_at = strftime('%s', 'now') WHERE id = NEW.id;
+END;
+"#;
+
+/// Porta il database alla versione `SCHEMA_VERSION`
+///
+/// Restituisce la versione di partenza. Un database più recente di questa
+/// versione di ritmo viene rifiutato.
+pub async fn migrate(pool: &SqlitePool) -> RitmoResult<i64> {
+    let version = schema_version(pool).await?;
+    if version > SCHEMA_VERSION {
+        return Err(RitmoErr::DatabaseMigration(format!(
+            "schema versione {} più recente di quella supportata ({})",
+            version, SCHEMA_VERSION
+        )));
+    }
+
+    for target in version + 1..=SCHEMA_VERSION {
+        let mut tx = pool.begin().await?;
+        match target {
+            1 => migrate_v1(&mut tx).await?,
+            _ => unreachable!("migrazione {} mancante", target),
+        }
+        // PRAGMA non accetta parametri
+        sqlx::query(&format!("PRAGMA user_version = {}", target))
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+    }
+
+    Ok(version)
+}
+
+/// Versione dello schema (`PRAGMA user_version`; 0 per i template precedenti)
+pub async fn schema_version(pool: &SqlitePool) -> RitmoResult<i64> {
+    let version: i64 = sqlx::query_scalar("PRAGMA user_version")
+        .fetch_one(pool)
+        .await?;
+    Ok(version)
+}
+
+async fn migrate_v1(tx: &mut Transaction<'_, Sqlite>) -> RitmoResult<()> {
+    add_column(tx, "books", "isbn13", "TEXT").await?;
+    sqlx::raw_sql(V1_TABLES).execute(&mut **tx).await?;
+    // book_files creata prima dell'impronta del testo
+    add_column(tx, "book_files", "text_fingerprint", "TEXT").await?;
+    sqlx::raw_sql(V1_INDEXES).execute(&mut **tx).await?;
+    Ok(())
+}
+
+/// Aggiunge una colonna se la tabella non ce l'ha già
+async fn add_column(
+    tx: &mut Transaction<'_, Sqlite>,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> RitmoResult<()> {
+    let exists: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = ?")
+            .bind(table)
+            .bind(column)
+            .fetch_one(&mut **tx)
+            .await?;
+    if exists == 0 {
+        sqlx::query(&format!(
+            "ALTER TABLE \"{}\" ADD COLUMN \"{}\" {}",
+            table, column, definition
+        ))
+        .execute(&mut **tx)
+        .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn memory_pool() -> SqlitePool {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+    }
+
+    async fn columns(pool: &SqlitePool, table: &str) -> Vec<String> {
+        sqlx::query_scalar("SELECT name FROM pragma_table_info(?)")
+            .bind(table)
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_migrate_old_library() {
+        let pool = memory_pool().await;
+        // Libreria creata prima della versione 1, con un libro
+        sqlx::raw_sql(
+            "CREATE TABLE formats (id INTEGER PRIMARY KEY, key TEXT NOT NULL UNIQUE);
+             CREATE TABLE books (id INTEGER PRIMARY KEY, name TEXT NOT NULL, isbn TEXT);
+             INSERT INTO books (name, isbn) VALUES ('Il nome della rosa', '88-452-0000-1');",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        assert_eq!(migrate(&pool).await.unwrap(), 0);
+
+        assert_eq!(schema_version(&pool).await.unwrap(), SCHEMA_VERSION);
+        assert!(columns(&pool, "books")
+            .await
+            .contains(&"isbn13".to_string()));
+        assert!(columns(&pool, "book_files")
+            .await
+            .contains(&"text_fingerprint".to_string()));
+        for table in [
+            "metadata_sync_snapshots",
+            "metadata_provider_cache",
+            "shelves",
+            "x_shelves_books",
+            "merge_journal",
+        ] {
+            assert!(!columns(&pool, table).await.is_empty(), "{}", table);
+        }
+        let name: String = sqlx::query_scalar("SELECT name FROM books")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(name, "Il nome della rosa");
+
+        // Già aggiornato: nessuna modifica
+        assert_eq!(migrate(&pool).await.unwrap(), SCHEMA_VERSION);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_partial_library() {
+        let pool = memory_pool().await;
+        // Template intermedio: isbn13 e book_files già presenti, senza impronta del testo
+        sqlx::raw_sql(
+            "CREATE TABLE books (id INTEGER PRIMARY KEY, name TEXT NOT NULL, isbn TEXT, isbn13 TEXT);
+             CREATE TABLE book_files (id INTEGER PRIMARY KEY, book_id INTEGER NOT NULL,
+                 format_id INTEGER, file_link TEXT NOT NULL UNIQUE, file_hash TEXT NOT NULL,
+                 file_size INTEGER, added_at INTEGER NOT NULL DEFAULT 0);",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        migrate(&pool).await.unwrap();
+
+        assert!(columns(&pool, "book_files")
+            .await
+            .contains(&"text_fingerprint".to_string()));
+        assert_eq!(schema_version(&pool).await.unwrap(), SCHEMA_VERSION);
+    }
+
+    #[tokio::test]
+    async fn test_newer_schema_is_rejected() {
+        let pool = memory_pool().await;
+        sqlx::query(&format!("PRAGMA user_version = {}", SCHEMA_VERSION + 1))
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(migrate(&pool).await.is_err());
+    }
+}
@@ -0,0 +1,371 @@
+//! Vocabolari controllati per lingue e formati
+//!
+//! Le lingue vengono da una tabella ISO 639 inclusa nel binario
+//! (`assets/iso639.tsv`, generata dai dati di iso-codes): ogni voce ha i codici
+//! 639-3, 639-2/B, 639-2/T e 639-1 e il nome in ogni lingua dell'interfaccia.
+//! I formati sono una lista chiusa con chiave canonica, tipo MIME ed estensioni.
+//!
+//! `lookup_language` e `lookup_format` riconducono alla voce canonica le
+//! varianti che arrivano dagli import ("ita", "it-IT", "Italian"; "EPUB",
+//! "epub3", "application/epub+zip").
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+const ISO639_TSV: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/iso639.tsv"));
+
+/// Voce della tabella ISO 639
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IsoLanguage {
+    pub iso639_3: &'static str,
+    /// Codice bibliografico 639-2/B, solo dove diverso dal terminologico
+    pub iso639_2b: Option<&'static str>,
+    pub iso639_2t: Option<&'static str>,
+    pub iso639_1: Option<&'static str>,
+    pub name_en: &'static str,
+    name_it: Option<&'static str>,
+}
+
+impl IsoLanguage {
+    /// Codice salvato in `running_languages.iso_code_2char`: ISO 639-1, oppure
+    /// 639-3 per le lingue che non lo hanno
+    pub fn code(&self) -> &'static str {
+        self.iso639_1.unwrap_or(self.iso639_3)
+    }
+
+    /// Nome nella lingua dell'interfaccia (`en`, `it`, `it-IT`); inglese se
+    /// manca la traduzione
+    pub fn name(&self, locale: &str) -> &'static str {
+        let language = locale.split(['-', '_']).next().unwrap_or_default();
+        match language.to_ascii_lowercase().as_str() {
+            "it" => self.name_it.unwrap_or(self.name_en),
+            _ => self.name_en,
+        }
+    }
+}
+
+struct LanguageTable {
+    entries: Vec<IsoLanguage>,
+    by_code: HashMap<&'static str, usize>,
+    by_name: HashMap<String, usize>,
+}
+
+fn language_table() -> &'static LanguageTable {
+    static TABLE: OnceLock<LanguageTable> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let entries: Vec<IsoLanguage> = ISO639_TSV
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+            .filter_map(|line| {
+                let fields: Vec<&'static str> = line.split('\t').collect();
+                let optional = |i: usize| fields.get(i).copied().filter(|f| !f.is_empty());
+                Some(IsoLanguage {
+                    iso639_3: optional(0)?,
+                    iso639_2b: optional(1),
+                    iso639_2t: optional(2),
+                    iso639_1: optional(3),
+                    name_en: optional(4)?,
+                    name_it: optional(5),
+                })
+            })
+            .collect();
+
+        // Le lingue con codice 639-1 o 639-2 hanno la precedenza sui nomi
+        // condivisi con lingue minori
+        let mut order: Vec<usize> = (0..entries.len()).collect();
+        order.sort_by_key(|&i| {
+            let entry = &entries[i];
+            (entry.iso639_1.is_none(), entry.iso639_2t.is_none())
+        });
+
+        let mut by_code = HashMap::new();
+        let mut by_name = HashMap::new();
+        for &i in &order {
+            let entry = &entries[i];
+            for code in [
+                Some(entry.iso639_3),
+                entry.iso639_2b,
+                entry.iso639_2t,
+                entry.iso639_1,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                by_code.entry(code).or_insert(i);
+            }
+            for name in [Some(entry.name_en), entry.name_it].into_iter().flatten() {
+                by_name.entry(name.to_lowercase()).or_insert(i);
+                // "Modern Greek (1453-)" -> "modern greek"
+                if let Some((short, _)) = name.split_once(" (") {
+                    by_name.entry(short.to_lowercase()).or_insert(i);
+                }
+            }
+        }
+
+        LanguageTable {
+            entries,
+            by_code,
+            by_name,
+        }
+    })
+}
+
+/// Tutte le lingue della tabella ISO 639
+pub fn languages() -> &'static [IsoLanguage] {
+    &language_table().entries
+}
+
+/// Riconduce un codice o un nome di lingua alla voce ISO 639
+///
+/// Accetta codici 639-1, 639-2/B, 639-2/T e 639-3 con o senza regione
+/// (`it`, `ita`, `it-IT`, `fr_FR`, `ger`) e i nomi inglesi e italiani
+/// (`Italian`, `italiano`), senza distinzione di maiuscole.
+pub fn lookup_language(input: &str) -> Option<&'static IsoLanguage> {
+    let table = language_table();
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    let primary = trimmed
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if (2..=3).contains(&primary.len()) && primary.chars().all(|c| c.is_ascii_alphabetic()) {
+        if let Some(&i) = table.by_code.get(primary.as_str()) {
+            return Some(&table.entries[i]);
+        }
+    }
+
+    table
+        .by_name
+        .get(&trimmed.to_lowercase())
+        .map(|&i| &table.entries[i])
+}
+
+/// Formato della lista controllata
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatInfo {
+    /// Chiave salvata in `formats.key`
+    pub key: &'static str,
+    pub mime_type: &'static str,
+    pub extensions: &'static [&'static str],
+    /// Altre grafie accettate in input (versioni, nomi estesi, MIME alternativi)
+    aliases: &'static [&'static str],
+}
+
+/// Formati riconosciuti, con la chiave canonica usata in `formats.key`
+pub const FORMATS: &[FormatInfo] = &[
+    FormatInfo {
+        key: "epub",
+        mime_type: "application/epub+zip",
+        extensions: &["epub"],
+        aliases: &["epub2", "epub3", "kepub", "kepub.epub"],
+    },
+    FormatInfo {
+        key: "pdf",
+        mime_type: "application/pdf",
+        extensions: &["pdf"],
+        aliases: &["application/x-pdf"],
+    },
+    FormatInfo {
+        key: "mobi",
+        mime_type: "application/x-mobipocket-ebook",
+        extensions: &["mobi", "prc"],
+        aliases: &["mobipocket"],
+    },
+    FormatInfo {
+        key: "azw",
+        mime_type: "application/vnd.amazon.ebook",
+        extensions: &["azw"],
+        aliases: &[],
+    },
+    FormatInfo {
+        key: "azw3",
+        mime_type: "application/vnd.amazon.mobi8-ebook",
+        extensions: &["azw3"],
+        aliases: &["kf8"],
+    },
+    FormatInfo {
+        key: "fb2",
+        mime_type: "application/x-fictionbook+xml",
+        extensions: &["fb2", "fb2.zip", "fbz"],
+        aliases: &["fictionbook", "fictionbook2"],
+    },
+    FormatInfo {
+        key: "djvu",
+        mime_type: "image/vnd.djvu",
+        extensions: &["djvu", "djv"],
+        aliases: &["image/x-djvu"],
+    },
+    FormatInfo {
+        key: "cbz",
+        mime_type: "application/vnd.comicbook+zip",
+        extensions: &["cbz"],
+        aliases: &["application/x-cbz"],
+    },
+    FormatInfo {
+        key: "cbr",
+        mime_type: "application/vnd.comicbook-rar",
+        extensions: &["cbr"],
+        aliases: &["application/x-cbr"],
+    },
+    FormatInfo {
+        key: "cb7",
+        mime_type: "application/x-cb7",
+        extensions: &["cb7"],
+        aliases: &[],
+    },
+    FormatInfo {
+        key: "txt",
+        mime_type: "text/plain",
+        extensions: &["txt"],
+        aliases: &["text", "plaintext"],
+    },
+    FormatInfo {
+        key: "rtf",
+        mime_type: "application/rtf",
+        extensions: &["rtf"],
+        aliases: &["text/rtf"],
+    },
+    FormatInfo {
+        key: "html",
+        mime_type: "text/html",
+        extensions: &["html", "htm", "xhtml"],
+        aliases: &["application/xhtml+xml"],
+    },
+    FormatInfo {
+        key: "doc",
+        mime_type: "application/msword",
+        extensions: &["doc"],
+        aliases: &[],
+    },
+    FormatInfo {
+        key: "docx",
+        mime_type: "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        extensions: &["docx"],
+        aliases: &[],
+    },
+    FormatInfo {
+        key: "odt",
+        mime_type: "application/vnd.oasis.opendocument.text",
+        extensions: &["odt"],
+        aliases: &[],
+    },
+];
+
+/// Riconduce una grafia di formato alla voce della lista controllata
+///
+/// Accetta chiavi ed estensioni in qualunque maiuscolo (`EPUB`, `.epub`,
+/// `ePub`), le versioni (`epub3`), le chiavi i18n (`format.epub`) e i tipi MIME.
+pub fn lookup_format(input: &str) -> Option<&'static FormatInfo> {
+    let lower = input.trim().to_lowercase();
+    let lower = lower.strip_prefix("format.").unwrap_or(&lower);
+    let value = lower.trim_start_matches('.');
+    if value.is_empty() {
+        return None;
+    }
+    // "e-pub", "plain text"
+    let compact: String = value
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '_'))
+        .collect();
+
+    FORMATS.iter().find(|format| {
+        [format.key, format.mime_type]
+            .iter()
+            .chain(format.extensions)
+            .chain(format.aliases)
+            .any(|candidate| *candidate == value || *candidate == compact)
+    })
+}
+
+/// Chiave da salvare in `formats.key`: quella canonica se il formato è nella
+/// lista, altrimenti l'input in minuscolo
+pub fn canonical_format_key(input: &str) -> String {
+    lookup_format(input)
+        .map(|format| format.key.to_string())
+        .unwrap_or_else(|| input.trim().to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_language_codes() {
+        for input in ["it", "ita", "IT", "it-IT", "it_IT", " Ita "] {
+            let lang = lookup_language(input).expect(input);
+            assert_eq!(lang.code(), "it", "{}", input);
+            assert_eq!(lang.iso639_3, "ita");
+        }
+        // Codici bibliografici 639-2/B
+        assert_eq!(lookup_language("ger").unwrap().code(), "de");
+        assert_eq!(lookup_language("fre").unwrap().code(), "fr");
+        assert_eq!(lookup_language("chi").unwrap().iso639_3, "zho");
+        // Lingua senza 639-1: il codice canonico è il 639-3
+        assert_eq!(lookup_language("grc").unwrap().code(), "grc");
+        assert!(lookup_language("").is_none());
+        assert!(lookup_language("xx").is_none());
+        assert!(lookup_language("qqq").is_none());
+    }
+
+    #[test]
+    fn test_lookup_language_names() {
+        assert_eq!(lookup_language("Italian").unwrap().code(), "it");
+        assert_eq!(lookup_language("italiano").unwrap().code(), "it");
+        assert_eq!(lookup_language("GERMAN").unwrap().code(), "de");
+        assert_eq!(lookup_language("Tedesco").unwrap().code(), "de");
+        assert_eq!(lookup_language("Modern Greek").unwrap().code(), "el");
+        assert!(lookup_language("Klingonese").is_none());
+    }
+
+    #[test]
+    fn test_language_names_by_locale() {
+        let lang = lookup_language("fr").unwrap();
+        assert_eq!(lang.name("en"), "French");
+        assert_eq!(lang.name("it"), "Francese");
+        assert_eq!(lang.name("it-IT"), "Francese");
+        assert_eq!(lang.name("de"), "French");
+        assert!(languages().len() > 7000);
+    }
+
+    #[test]
+    fn test_lookup_format_variants() {
+        for input in [
+            "EPUB",
+            "epub3",
+            "ePub",
+            ".epub",
+            "format.epub",
+            "application/epub+zip",
+        ] {
+            assert_eq!(
+                lookup_format(input).map(|f| f.key),
+                Some("epub"),
+                "{}",
+                input
+            );
+        }
+        assert_eq!(lookup_format("prc").unwrap().key, "mobi");
+        assert_eq!(lookup_format("fb2.zip").unwrap().key, "fb2");
+        assert_eq!(lookup_format("Plain Text").unwrap().key, "txt");
+        assert_eq!(
+            lookup_format("AZW3").unwrap().mime_type,
+            "application/vnd.amazon.mobi8-ebook"
+        );
+        assert!(lookup_format("").is_none());
+        assert!(lookup_format("exe").is_none());
+        assert_eq!(canonical_format_key(" ePub3 "), "epub");
+        assert_eq!(canonical_format_key("LIT"), "lit");
+    }
+
+    #[test]
+    fn test_format_keys_are_canonical() {
+        for format in FORMATS {
+            assert_eq!(lookup_format(format.key).unwrap().key, format.key);
+            assert_eq!(lookup_format(format.mime_type).unwrap().key, format.key);
+        }
+    }
+}
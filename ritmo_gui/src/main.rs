@@ -1,6 +1,12 @@
 slint::include_modules!();
 
-use ritmo_db_core::LibraryConfig;
+use ritmo_core::service::{get_shelf, list_shelves, shelf_books};
+use ritmo_db_core::filters::{validate_query, QueryTarget};
+use ritmo_db_core::{
+    execute_books_query, execute_contents_query, parse_query, BookFilters, BookResult,
+    ContentFilters, LibraryConfig, QueryExpr,
+};
+use ritmo_errors::reporter::SilentReporter;
 use slint::{Model, ModelRc, SharedString, VecModel};
 use std::rc::Rc;
 use std::sync::Arc;
@@ -11,6 +17,22 @@ fn to_model<T: Clone + 'static>(v: Vec<T>) -> ModelRc<T> {
     ModelRc::from(Rc::new(VecModel::from(v)))
 }
 
+// Riga della vista libri per un risultato del database (contenuti non caricati)
+fn book_row(book: BookResult) -> BookWithContents {
+    BookWithContents {
+        id: book.id as i32,
+        publication_date: book.formatted_publication_date().unwrap_or_default().into(),
+        name: book.name.into(),
+        original_title: book.original_title.unwrap_or_default().into(),
+        publisher: book.publisher_name.unwrap_or_default().into(),
+        format: book.format_key.unwrap_or_default().into(),
+        series: book.series_name.unwrap_or_default().into(),
+        isbn: book.isbn.unwrap_or_default().into(),
+        file_link: book.file_link.unwrap_or_default().into(),
+        contents: to_model(Vec::new()),
+    }
+}
+
 // Struttura per gestire lo stato dell'applicazione
 struct AppState {
     config: LibraryConfig,
@@ -33,6 +55,98 @@ impl AppState {
         Ok(())
     }
 
+    // Analizza e valida una query della barra di ricerca (es. "author:calvino AND year:<1960")
+    fn parse_search_query(
+        query: &str,
+        target: QueryTarget,
+    ) -> Result<QueryExpr, Box<dyn std::error::Error>> {
+        let expr = parse_query(query)?;
+        validate_query(&expr, target).map_err(|errors| {
+            errors
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+                .join("; ")
+        })?;
+        Ok(expr)
+    }
+
+    // Ricerca libri nel database con la query language
+    fn query_books(&self, query: &str) -> Result<Vec<BookWithContents>, Box<dyn std::error::Error>> {
+        let expr = Self::parse_search_query(query, QueryTarget::Books)?;
+        let filters = BookFilters::default().with_query(expr);
+
+        let books = self.runtime.block_on(async {
+            let mut reporter = SilentReporter;
+            let pool = self.config.create_pool(&mut reporter).await?;
+            let books = execute_books_query(&pool, &filters).await?;
+            Ok::<_, Box<dyn std::error::Error>>(books)
+        })?;
+
+        Ok(books.into_iter().map(book_row).collect())
+    }
+
+    // Scaffali con conteggio aggiornato (gli smart vengono valutati ora)
+    fn get_shelves(&self) -> Result<Vec<ShelfInfo>, Box<dyn std::error::Error>> {
+        let summaries = self.runtime.block_on(async {
+            let mut reporter = SilentReporter;
+            let pool = self.config.create_pool(&mut reporter).await?;
+            let summaries = list_shelves(&pool).await?;
+            Ok::<_, Box<dyn std::error::Error>>(summaries)
+        })?;
+
+        Ok(summaries
+            .into_iter()
+            .map(|summary| ShelfInfo {
+                smart: summary.shelf.is_smart(),
+                name: summary.shelf.name.into(),
+                count: summary.book_count as i32,
+            })
+            .collect())
+    }
+
+    // Libri di uno scaffale, nell'ordine dello scaffale
+    fn get_shelf_books(
+        &self,
+        name: &str,
+    ) -> Result<Vec<BookWithContents>, Box<dyn std::error::Error>> {
+        let entries = self.runtime.block_on(async {
+            let mut reporter = SilentReporter;
+            let pool = self.config.create_pool(&mut reporter).await?;
+            let shelf = get_shelf(&pool, name).await?;
+            let entries = shelf_books(&pool, &shelf).await?;
+            Ok::<_, Box<dyn std::error::Error>>(entries)
+        })?;
+
+        Ok(entries.into_iter().map(|entry| book_row(entry.book)).collect())
+    }
+
+    // Ricerca contenuti nel database con la query language
+    fn query_contents(&self, query: &str) -> Result<Vec<ContentWithBooks>, Box<dyn std::error::Error>> {
+        let expr = Self::parse_search_query(query, QueryTarget::Contents)?;
+        let filters = ContentFilters::default().with_query(expr);
+
+        let contents = self.runtime.block_on(async {
+            let mut reporter = SilentReporter;
+            let pool = self.config.create_pool(&mut reporter).await?;
+            let contents = execute_contents_query(&pool, &filters).await?;
+            Ok::<_, Box<dyn std::error::Error>>(contents)
+        })?;
+
+        Ok(contents
+            .into_iter()
+            .map(|content| ContentWithBooks {
+                id: content.id as i32,
+                publication_date: content.formatted_publication_date().unwrap_or_default().into(),
+                name: content.name.into(),
+                original_title: content.original_title.unwrap_or_default().into(),
+                type_name: content.type_key.unwrap_or_default().into(),
+                people: to_model(Vec::new()),
+                books: to_model(Vec::new()),
+            })
+            .collect())
+    }
+
     // Vista LIBRI: libri con i loro contenuti
     fn get_books_with_contents(&self) -> Result<Vec<BookWithContents>, Box<dyn std::error::Error>> {
         // TODO: Query database reale
@@ -381,6 +495,54 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         });
     }
 
+    // Callback: Refresh shelves
+    {
+        let ui_weak = ui.as_weak();
+        let app_state = app_state.clone();
+
+        ui.on_refresh_shelves(move || {
+            let ui = ui_weak.unwrap();
+            let state = app_state.blocking_lock();
+
+            match state.get_shelves() {
+                Ok(shelves) => ui.set_shelves(to_model(shelves)),
+                Err(e) => {
+                    ui.set_status_message(StatusMessage {
+                        text: format!("Errore caricamento scaffali: {}", e).into(),
+                        is_error: true,
+                    });
+                }
+            }
+        });
+    }
+
+    // Callback: Show shelf
+    {
+        let ui_weak = ui.as_weak();
+        let app_state = app_state.clone();
+
+        ui.on_show_shelf(move |name: SharedString| {
+            let ui = ui_weak.unwrap();
+            let state = app_state.blocking_lock();
+
+            match state.get_shelf_books(&name) {
+                Ok(books) => {
+                    ui.set_status_message(StatusMessage {
+                        text: format!("Scaffale '{}': {} libri", name, books.len()).into(),
+                        is_error: false,
+                    });
+                    ui.set_books(to_model(books));
+                }
+                Err(e) => {
+                    ui.set_status_message(StatusMessage {
+                        text: format!("Errore: {}", e).into(),
+                        is_error: true,
+                    });
+                }
+            }
+        });
+    }
+
     // Callback: Refresh contents
     {
         let ui_weak = ui.as_weak();
@@ -418,6 +580,26 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             let ui = ui_weak.unwrap();
             let state = app_state.blocking_lock();
 
+            // Testo con campi ("author:king AND year:1980..1990"): query language sul database
+            if search_text.contains(':') {
+                let result = if ui.get_view_mode() == 0 {
+                    state
+                        .query_books(&search_text)
+                        .map(|books| ui.set_books(to_model(books)))
+                } else {
+                    state
+                        .query_contents(&search_text)
+                        .map(|contents| ui.set_contents(to_model(contents)))
+                };
+                if let Err(e) = result {
+                    ui.set_status_message(StatusMessage {
+                        text: format!("Query non valida: {}", e).into(),
+                        is_error: true,
+                    });
+                }
+                return;
+            }
+
             // TODO: Implementare ricerca vera nel database
             // Per ora filtriamo i dati di esempio
             if ui.get_view_mode() == 0 {
@@ -509,6 +691,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Carica dati iniziali
     ui.invoke_refresh_books();
     ui.invoke_refresh_contents();
+    ui.invoke_refresh_shelves();
 
     // Avvia UI
     ui.run()?;
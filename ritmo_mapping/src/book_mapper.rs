@@ -1,6 +1,7 @@
 use crate::traits::{FromDto, FromModel};
 use ritmo_core::dto::BookDto;
 use ritmo_db::models::Book;
+use ritmo_db_core::isbn::canonical_isbn13;
 
 impl FromDto<BookDto> for Book {
     fn from_dto(dto: &BookDto) -> Self {
@@ -15,6 +16,7 @@ impl FromDto<BookDto> for Book {
             publication_date: dto.publication_date,
             last_modified_date: chrono::Utc::now().timestamp(),
             isbn: dto.isbn.clone(),
+            isbn13: dto.isbn.as_deref().and_then(canonical_isbn13),
             pages: None,
             notes: dto.notes.clone(),
             has_cover: if dto.has_cover { 1 } else { 0 },
@@ -0,0 +1,177 @@
+//! Benchmark del clustering con e senza blocking
+//!
+//! Genera nomi sintetici con varianti (iniziali, refusi, secondo nome) e
+//! confronta `create_clusters` su tutte le coppie con la versione a blocchi.
+//! Prima dei tempi stampa il recall delle coppie sopra soglia.
+//!
+//! `cargo bench -p ritmo_ml --bench blocking`
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use ritmo_ml::blocking::{candidate_pairs, score_pairs, BlockingConfig};
+use ritmo_ml::entity_learner::MLEntityLearner;
+use std::collections::HashSet;
+
+#[rustfmt::skip]
+const GIVEN: &[&str] = &[
+    "stephen", "margaret", "george", "joanne", "ursula", "isaac", "arthur", "philip", "terry",
+    "neil", "agatha", "italo", "umberto", "elena", "alessandro", "giovanni", "natalia", "primo",
+    "cesare", "dino", "grazia", "luigi", "gabriele", "elsa", "leonardo", "andrea", "beppe",
+    "carlo", "dacia", "alberto", "fyodor", "leo", "anton", "ivan", "virginia", "jane", "charles",
+    "emily", "thomas", "mark",
+];
+
+#[rustfmt::skip]
+const SURNAMES: &[&str] = &[
+    "king", "atwood", "martin", "rowling", "le guin", "asimov", "clarke", "dick", "pratchett",
+    "gaiman", "christie", "calvino", "eco", "ferrante", "manzoni", "verga", "ginzburg", "levi",
+    "pavese", "buzzati", "deledda", "pirandello", "d annunzio", "morante", "sciascia", "camilleri",
+    "fenoglio", "collodi", "maraini", "moravia", "dostoevsky", "tolstoy", "chekhov", "turgenev",
+    "woolf", "austen", "dickens", "bronte", "hardy", "twain", "rodari", "tabucchi", "baricco",
+    "saviano", "lucarelli", "carofiglio", "manfredi", "vitali", "benni", "pennac", "simenon",
+    "zola", "proust", "camus", "sartre", "hugo", "dumas", "verne", "balzac", "flaubert",
+];
+
+/// Generatore congruenziale: stessi dati a ogni esecuzione
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        ((self.0 >> 33) as usize) % bound
+    }
+}
+
+/// Nomi normalizzati con l'identificativo della persona; circa un terzo sono
+/// varianti di un nome già generato (stessa persona)
+fn synthetic_names(count: usize) -> (Vec<String>, Vec<usize>) {
+    let mut rng = Lcg(42);
+    let mut names: Vec<String> = Vec::with_capacity(count);
+    let mut people: Vec<usize> = Vec::with_capacity(count);
+    while names.len() < count {
+        if names.is_empty() || rng.next(3) > 0 {
+            let given = GIVEN[rng.next(GIVEN.len())];
+            let surname = SURNAMES[rng.next(SURNAMES.len())];
+            let middle = GIVEN[rng.next(GIVEN.len())];
+            names.push(match rng.next(4) {
+                0 => format!("{} {} {}", given, &middle[..1], surname),
+                1 => format!("{} {}{}", given, surname, rng.next(1000)),
+                _ => format!("{} {} {}", given, middle, surname),
+            });
+            people.push(names.len());
+        } else {
+            let base = rng.next(names.len());
+            names.push(variant(&names[base], &mut rng));
+            people.push(people[base]);
+        }
+    }
+    (names, people)
+}
+
+fn variant(name: &str, rng: &mut Lcg) -> String {
+    let words: Vec<&str> = name.split_whitespace().collect();
+    match rng.next(3) {
+        // Iniziale del primo nome
+        0 => {
+            let mut words: Vec<String> = words.iter().map(|w| w.to_string()).collect();
+            words[0] = words[0][..1].to_string();
+            words.join(" ")
+        }
+        // Refuso: due lettere scambiate
+        1 => {
+            let mut chars: Vec<char> = name.chars().collect();
+            let i = 1 + rng.next(chars.len().saturating_sub(2).max(1));
+            if i + 1 < chars.len() {
+                chars.swap(i, i + 1);
+            }
+            chars.into_iter().collect()
+        }
+        // Senza secondo nome
+        _ => format!("{} {}", words[0], words[words.len() - 1]),
+    }
+}
+
+fn similar_pairs(names: &[String], blocking: &BlockingConfig) -> HashSet<(usize, usize)> {
+    let pairs = candidate_pairs(names, blocking);
+    score_pairs(
+        names,
+        &pairs,
+        strsim::jaro_winkler,
+        0.85,
+        blocking.thread_count(),
+    )
+    .into_iter()
+    .filter(|&(_, _, score)| score > 0.85)
+    .map(|(i, j, _)| (i, j))
+    .collect()
+}
+
+/// Recall sulle varianti della stessa persona, con tutte le coppie e con il blocking
+fn report_recall(count: usize) {
+    let (names, people) = synthetic_names(count);
+    let duplicates: HashSet<(usize, usize)> = (0..count)
+        .flat_map(|i| ((i + 1)..count).map(move |j| (i, j)))
+        .filter(|&(i, j)| people[i] == people[j])
+        .collect();
+    let recall = |found: &HashSet<(usize, usize)>| {
+        found.intersection(&duplicates).count() as f64 * 100.0 / duplicates.len().max(1) as f64
+    };
+    let exhaustive = similar_pairs(&names, &BlockingConfig::exhaustive());
+    let blocked = similar_pairs(&names, &BlockingConfig::always());
+    let candidates = candidate_pairs(&names, &BlockingConfig::always()).len();
+    let total = count * (count - 1) / 2;
+    println!(
+        "n={}: candidate {} su {} ({:.2}%), recall varianti {:.2}% (tutte le coppie {:.2}%)",
+        count,
+        candidates,
+        total,
+        candidates as f64 * 100.0 / total as f64,
+        recall(&blocked),
+        recall(&exhaustive)
+    );
+}
+
+fn bench_create_clusters(c: &mut Criterion) {
+    for count in [1_000, 5_000] {
+        report_recall(count);
+    }
+
+    let mut group = c.benchmark_group("create_clusters");
+    group.sample_size(10);
+    for count in [1_000, 5_000] {
+        let (names, _) = synthetic_names(count);
+        for (label, blocking) in [
+            ("exhaustive", BlockingConfig::exhaustive()),
+            ("blocking", BlockingConfig::always()),
+        ] {
+            group.bench_with_input(BenchmarkId::new(label, count), &names, |b, names| {
+                b.iter(|| {
+                    let mut learner = MLEntityLearner::new();
+                    learner.blocking = blocking.clone();
+                    learner.create_clusters(names);
+                    learner.clusters.len()
+                })
+            });
+        }
+    }
+    group.finish();
+
+    // Solo con il blocking: con tutte le coppie richiederebbe minuti
+    let (names, _) = synthetic_names(40_000);
+    group = c.benchmark_group("create_clusters_large");
+    group.sample_size(10);
+    group.bench_function("blocking/40000", |b| {
+        b.iter(|| {
+            let mut learner = MLEntityLearner::new();
+            learner.blocking = BlockingConfig::always();
+            learner.create_clusters(&names);
+            learner.clusters.len()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_create_clusters);
+criterion_main!(benches);
@@ -0,0 +1,518 @@
+//! Generazione delle coppie candidate per il confronto fuzzy (blocking)
+//!
+//! Confrontare ogni coppia con Jaro-Winkler è O(n²). Il blocking restringe il
+//! confronto alle coppie che condividono almeno un indizio:
+//! - codice fonetico (Soundex) di una parola della chiave, oppure della prima
+//!   e dell'ultima parola insieme (o dell'iniziale e dell'ultima parola);
+//! - lettere ordinate dell'ultima parola (con l'iniziale) o dell'intera chiave,
+//!   per i refusi con lettere scambiate;
+//! - vicinanza nell'ordinamento della chiave e della chiave rovesciata
+//!   (sorted neighborhood);
+//! - una banda della firma MinHash dei trigrammi (LSH).
+//!
+//! I blocchi troppo grandi (parole molto comuni) non vengono espansi in tutte
+//! le coppie: i loro membri vengono confrontati solo entro una finestra
+//! dell'ordinamento.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Parametri del blocking
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlockingConfig {
+    /// Sotto questo numero di elementi si confrontano tutte le coppie
+    pub exhaustive_below: usize,
+    /// Blocchi per codice fonetico di ciascuna parola della chiave
+    pub phonetic_tokens: bool,
+    /// Finestra del sorted neighborhood (0 = disattivato)
+    pub window: usize,
+    /// Bande della firma MinHash (0 = LSH disattivato)
+    pub lsh_bands: usize,
+    /// Valori MinHash per banda
+    pub lsh_rows: usize,
+    /// Dimensione oltre la quale un blocco viene confrontato solo entro la finestra
+    pub max_block_size: usize,
+    /// Thread per il calcolo delle similarità (0 = tutti i core disponibili)
+    pub threads: usize,
+}
+
+impl Default for BlockingConfig {
+    fn default() -> Self {
+        Self {
+            exhaustive_below: 500,
+            phonetic_tokens: true,
+            window: 10,
+            lsh_bands: 20,
+            lsh_rows: 3,
+            max_block_size: 200,
+            threads: 0,
+        }
+    }
+}
+
+impl BlockingConfig {
+    /// Nessun blocking: tutte le coppie (riferimento per misurare il recall)
+    pub fn exhaustive() -> Self {
+        Self {
+            exhaustive_below: usize::MAX,
+            ..Default::default()
+        }
+    }
+
+    /// Blocking sempre attivo, anche per insiemi piccoli
+    pub fn always() -> Self {
+        Self {
+            exhaustive_below: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Numero di thread effettivo
+    pub fn thread_count(&self) -> usize {
+        if self.threads > 0 {
+            self.threads
+        } else {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        }
+    }
+}
+
+/// Coppie candidate `(i, j)` con `i < j`, ordinate e senza ripetizioni
+pub fn candidate_pairs(keys: &[String], config: &BlockingConfig) -> Vec<(usize, usize)> {
+    let n = keys.len();
+    if n < 2 {
+        return Vec::new();
+    }
+    if n < config.exhaustive_below {
+        return (0..n)
+            .flat_map(|i| ((i + 1)..n).map(move |j| (i, j)))
+            .collect();
+    }
+
+    let mut pairs: HashSet<(usize, usize)> = HashSet::new();
+
+    // Ordinamento per chiave e per chiave rovesciata
+    let sorted = sorted_indices(keys, |k| k.to_string());
+    let reversed = sorted_indices(keys, |k| k.chars().rev().collect());
+    let mut rank = vec![0; n];
+    for (position, &index) in sorted.iter().enumerate() {
+        rank[index] = position;
+    }
+    if config.window > 0 {
+        add_window_pairs(&mut pairs, &sorted, config.window);
+        add_window_pairs(&mut pairs, &reversed, config.window);
+    }
+
+    let mut blocks: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, key) in keys.iter().enumerate() {
+        if config.phonetic_tokens {
+            let tokens: Vec<&str> = key.split_whitespace().collect();
+            for token in &tokens {
+                if let Some(code) = phonetic_code(token) {
+                    blocks.entry(format!("p:{}", code)).or_default().push(index);
+                }
+            }
+            // Prima e ultima parola insieme: blocchi piccoli anche quando le
+            // singole parole sono comuni (nome e cognome con secondi nomi diversi,
+            // oppure iniziale e cognome)
+            if let [first, .., last] = tokens.as_slice() {
+                if let Some(last_code) = phonetic_code(last) {
+                    if let Some(first_code) = phonetic_code(first) {
+                        blocks
+                            .entry(format!("f:{}:{}", first_code, last_code))
+                            .or_default()
+                            .push(index);
+                    }
+                    if let Some(initial) = first.chars().next() {
+                        blocks
+                            .entry(format!("i:{}:{}", initial, last_code))
+                            .or_default()
+                            .push(index);
+                    }
+                }
+                // Lettere ordinate dell'ultima parola: resiste alle lettere scambiate
+                if let Some(initial) = first.chars().next() {
+                    blocks
+                        .entry(format!("a:{}:{}", initial, sorted_letters(last)))
+                        .or_default()
+                        .push(index);
+                }
+            }
+            // Lettere ordinate dell'intera chiave: scambi anche a cavallo degli spazi
+            blocks
+                .entry(format!("k:{}", sorted_letters(key)))
+                .or_default()
+                .push(index);
+        }
+        if config.lsh_bands > 0 && config.lsh_rows > 0 {
+            let signature = minhash_signature(key, config.lsh_bands * config.lsh_rows);
+            for (band, rows) in signature.chunks(config.lsh_rows).enumerate() {
+                let band_key = rows
+                    .iter()
+                    .map(|h| format!("{:x}", h))
+                    .collect::<Vec<_>>()
+                    .join(".");
+                blocks
+                    .entry(format!("b{}:{}", band, band_key))
+                    .or_default()
+                    .push(index);
+            }
+        }
+    }
+
+    for members in blocks.values_mut() {
+        members.dedup();
+        if members.len() < 2 {
+            continue;
+        }
+        if members.len() <= config.max_block_size {
+            for (a, &i) in members.iter().enumerate() {
+                for &j in &members[a + 1..] {
+                    pairs.insert(ordered(i, j));
+                }
+            }
+        } else if config.window > 0 {
+            // Blocco troppo grande: solo i vicini nell'ordinamento della chiave
+            members.sort_by_key(|&i| rank[i]);
+            add_window_pairs(&mut pairs, members, config.window);
+        }
+    }
+
+    let mut pairs: Vec<(usize, usize)> = pairs.into_iter().collect();
+    pairs.sort_unstable();
+    pairs
+}
+
+/// Calcola la similarità delle coppie candidate in parallelo e restituisce
+/// quelle con punteggio maggiore o uguale alla soglia
+pub fn score_pairs<F>(
+    keys: &[String],
+    pairs: &[(usize, usize)],
+    similarity: F,
+    threshold: f64,
+    threads: usize,
+) -> Vec<(usize, usize, f64)>
+where
+    F: Fn(&str, &str) -> f64 + Sync,
+{
+    score_pairs_by_index(pairs, |i, j| similarity(&keys[i], &keys[j]), threshold, threads)
+}
+
+/// Come `score_pairs`, con una funzione che riceve gli indici della coppia
+/// (per punteggi che usano anche dati diversi dalla chiave)
+pub fn score_pairs_by_index<F>(
+    pairs: &[(usize, usize)],
+    similarity: F,
+    threshold: f64,
+    threads: usize,
+) -> Vec<(usize, usize, f64)>
+where
+    F: Fn(usize, usize) -> f64 + Sync,
+{
+    let score_chunk = |chunk: &[(usize, usize)]| -> Vec<(usize, usize, f64)> {
+        chunk
+            .iter()
+            .filter_map(|&(i, j)| {
+                let score = similarity(i, j);
+                (score >= threshold).then_some((i, j, score))
+            })
+            .collect()
+    };
+
+    let threads = threads.max(1);
+    if threads == 1 || pairs.len() < 10_000 {
+        return score_chunk(pairs);
+    }
+    let chunk_size = pairs.len().div_ceil(threads);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = pairs
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || score_chunk(chunk)))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().expect("thread di confronto terminato con errore"))
+            .collect()
+    })
+}
+
+/// Componenti connesse del grafo delle coppie, ciascuna con gli indici in ordine crescente
+///
+/// Elementi di componenti diverse non sono mai simili tra loro: il clustering
+/// può procedere su ogni componente in modo indipendente.
+pub fn connected_components(n: usize, edges: &[(usize, usize, f64)]) -> Vec<Vec<usize>> {
+    let mut parent: Vec<usize> = (0..n).collect();
+    fn find(parent: &mut [usize], mut x: usize) -> usize {
+        while parent[x] != x {
+            parent[x] = parent[parent[x]];
+            x = parent[x];
+        }
+        x
+    }
+    for &(i, j, _) in edges {
+        let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+        if a != b {
+            parent[a.max(b)] = a.min(b);
+        }
+    }
+
+    let mut components: HashMap<usize, Vec<usize>> = HashMap::new();
+    for &(i, j, _) in edges {
+        for x in [i, j] {
+            let root = find(&mut parent, x);
+            components.entry(root).or_default().push(x);
+        }
+    }
+    let mut components: Vec<Vec<usize>> = components
+        .into_values()
+        .map(|mut members| {
+            members.sort_unstable();
+            members.dedup();
+            members
+        })
+        .collect();
+    components.sort_by_key(|members| members[0]);
+    components
+}
+
+/// Codice Soundex di una parola (lettera iniziale più tre cifre)
+///
+/// Per parole senza lettere latine restituisce la parola stessa, così anche
+/// le chiavi in altri alfabeti finiscono in un blocco.
+pub fn phonetic_code(token: &str) -> Option<String> {
+    let letters: Vec<char> = token
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .flat_map(|c| c.to_lowercase())
+        .collect();
+    if letters.len() < 2 {
+        return None;
+    }
+    if !letters.iter().all(|c| c.is_ascii_lowercase()) {
+        return Some(letters.into_iter().collect());
+    }
+
+    let digit = |c: char| match c {
+        'b' | 'f' | 'p' | 'v' => Some('1'),
+        'c' | 'g' | 'j' | 'k' | 'q' | 's' | 'x' | 'z' => Some('2'),
+        'd' | 't' => Some('3'),
+        'l' => Some('4'),
+        'm' | 'n' => Some('5'),
+        'r' => Some('6'),
+        _ => None,
+    };
+    let mut code = String::new();
+    code.push(letters[0].to_ascii_uppercase());
+    let mut last = digit(letters[0]);
+    for &c in &letters[1..] {
+        let d = digit(c);
+        if let Some(value) = d {
+            if d != last {
+                code.push(value);
+                if code.len() == 4 {
+                    break;
+                }
+            }
+        }
+        // 'h' e 'w' non separano consonanti con lo stesso codice
+        if c != 'h' && c != 'w' {
+            last = d;
+        }
+    }
+    while code.len() < 4 {
+        code.push('0');
+    }
+    Some(code)
+}
+
+fn sorted_letters(text: &str) -> String {
+    let mut letters: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+    letters.sort_unstable();
+    letters.into_iter().collect()
+}
+
+fn sorted_indices(keys: &[String], sort_key: impl Fn(&str) -> String) -> Vec<usize> {
+    let sort_keys: Vec<String> = keys.iter().map(|k| sort_key(k)).collect();
+    let mut indices: Vec<usize> = (0..keys.len()).collect();
+    indices.sort_by(|&a, &b| sort_keys[a].cmp(&sort_keys[b]).then(a.cmp(&b)));
+    indices
+}
+
+fn add_window_pairs(pairs: &mut HashSet<(usize, usize)>, order: &[usize], window: usize) {
+    for (position, &i) in order.iter().enumerate() {
+        for &j in order.iter().skip(position + 1).take(window) {
+            if i != j {
+                pairs.insert(ordered(i, j));
+            }
+        }
+    }
+}
+
+fn ordered(i: usize, j: usize) -> (usize, usize) {
+    if i < j {
+        (i, j)
+    } else {
+        (j, i)
+    }
+}
+
+/// Firma MinHash dei trigrammi della chiave (con spazi ai bordi)
+fn minhash_signature(key: &str, size: usize) -> Vec<u64> {
+    let chars: Vec<char> = format!(" {} ", key).chars().collect();
+    let shingles: Vec<u64> = if chars.len() < 3 {
+        vec![fnv1a(key)]
+    } else {
+        chars
+            .windows(3)
+            .map(|w| fnv1a(&w.iter().collect::<String>()))
+            .collect()
+    };
+    (0..size as u64)
+        .map(|seed| {
+            shingles
+                .iter()
+                .map(|&s| splitmix64(s ^ seed.wrapping_mul(0x9E37_79B9_7F4A_7C15)))
+                .min()
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db_loaders::{
+        load_people_from_db, load_publishers_from_db, load_roles_from_db, load_series_from_db,
+        load_tags_from_db,
+    };
+    use crate::entity_learner::MLEntityLearner;
+    use crate::traits::MLProcessable;
+
+    fn keys<T: MLProcessable>(records: &[T]) -> Vec<String> {
+        records.iter().map(|r| r.canonical_key()).collect()
+    }
+
+    fn clusters(items: &[String], blocking: BlockingConfig) -> Vec<(String, Vec<String>)> {
+        let mut learner = MLEntityLearner::new();
+        learner.blocking = blocking;
+        learner.create_clusters(items);
+        learner
+            .clusters
+            .into_iter()
+            .map(|c| (c.centroid, c.members))
+            .collect()
+    }
+
+    #[test]
+    fn test_phonetic_code() {
+        assert_eq!(phonetic_code("robert").as_deref(), Some("R163"));
+        assert_eq!(phonetic_code("rupert").as_deref(), Some("R163"));
+        assert_eq!(phonetic_code("ashcraft").as_deref(), Some("A261"));
+        assert_eq!(phonetic_code("stephen"), phonetic_code("steven"));
+        assert_eq!(phonetic_code("толстой").as_deref(), Some("толстой"));
+        assert_eq!(phonetic_code("s"), None);
+    }
+
+    #[test]
+    fn test_candidate_pairs_exhaustive_below_threshold() {
+        let items: Vec<String> = ["a b", "c d", "e f"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            candidate_pairs(&items, &BlockingConfig::default()),
+            vec![(0, 1), (0, 2), (1, 2)]
+        );
+    }
+
+    #[test]
+    fn test_large_block_uses_window() {
+        // Tutti condividono "smith": il blocco supera il limite e viene
+        // confrontato solo entro la finestra
+        let items: Vec<String> = (0..50).map(|i| format!("smith {:03}", i)).collect();
+        let config = BlockingConfig {
+            exhaustive_below: 0,
+            window: 2,
+            lsh_bands: 0,
+            max_block_size: 10,
+            ..Default::default()
+        };
+        let pairs = candidate_pairs(&items, &config);
+        assert!(pairs.contains(&(0, 1)));
+        assert!(pairs.contains(&(0, 2)));
+        assert!(!pairs.contains(&(0, 40)));
+        assert!(pairs.len() < 50 * 49 / 2);
+    }
+
+    #[test]
+    fn test_connected_components() {
+        let edges = vec![(0, 3, 0.9), (3, 5, 0.9), (1, 2, 0.9)];
+        assert_eq!(
+            connected_components(6, &edges),
+            vec![vec![0, 3, 5], vec![1, 2]]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_blocking_recall_on_fixtures() {
+        use crate::test_helpers::*;
+
+        let pool = create_full_test_db().await.unwrap();
+        let fixtures = vec![
+            keys(&load_people_from_db(&pool).await.unwrap()),
+            keys(&load_publishers_from_db(&pool).await.unwrap()),
+            keys(&load_series_from_db(&pool).await.unwrap()),
+            keys(&load_tags_from_db(&pool).await.unwrap()),
+            keys(&load_roles_from_db(&pool).await.unwrap()),
+        ];
+
+        // Finestra minima: le coppie devono venire soprattutto dai blocchi
+        let blocking = BlockingConfig {
+            exhaustive_below: 0,
+            window: 1,
+            ..Default::default()
+        };
+        let (mut total_pairs, mut total_candidates) = (0, 0);
+        for items in &fixtures {
+            let exhaustive = clusters(items, BlockingConfig::exhaustive());
+            let blocked = clusters(items, blocking.clone());
+            assert_eq!(blocked, exhaustive, "cluster diversi per {:?}", items);
+
+            // Ogni coppia sopra soglia è tra le candidate
+            let all = candidate_pairs(items, &BlockingConfig::exhaustive());
+            let expected = score_pairs(items, &all, strsim::jaro_winkler, 0.85, 1);
+            let candidates = candidate_pairs(items, &blocking);
+            total_pairs += all.len();
+            total_candidates += candidates.len();
+            for (i, j, _) in expected {
+                assert!(
+                    candidates.contains(&(i, j)),
+                    "persa {:?}",
+                    (&items[i], &items[j])
+                );
+            }
+        }
+        assert!(
+            total_candidates < total_pairs,
+            "{} candidate su {} coppie",
+            total_candidates,
+            total_pairs
+        );
+    }
+}
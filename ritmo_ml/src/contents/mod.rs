@@ -0,0 +1,3 @@
+pub mod record;
+
+pub use record::ContentRecord;
@@ -0,0 +1,185 @@
+use crate::traits::MLProcessable;
+use crate::utils::MLStringUtils;
+use serde::{Deserialize, Serialize};
+use strsim::jaro_winkler;
+
+/// Articoli iniziali ignorati nel confronto dei titoli
+const LEADING_ARTICLES: &[&str] = &[
+    "il", "lo", "la", "i", "gli", "le", "l", "un", "uno", "una", "the", "a", "an", "les", "el",
+    "los", "las", "der", "die", "das",
+];
+
+/// Un'opera (riga di `contents`) con i segnali usati per riconoscere
+/// la stessa opera importata più volte
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContentRecord {
+    pub id: i64,
+    pub title: String,
+    pub normalized_title: String,
+    pub original_title: Option<String>,
+    pub normalized_original_title: Option<String>,
+    pub type_id: Option<i64>,
+    /// Persone con ruolo `role.author`, ordinate
+    pub author_ids: Vec<i64>,
+    /// Codici ISO 639-3 delle lingue originali, ordinati
+    pub original_languages: Vec<String>,
+    /// Numero di libri (edizioni) che contengono l'opera
+    pub book_count: usize,
+}
+
+impl ContentRecord {
+    pub fn new(id: i64, title: &str, original_title: Option<&str>) -> Self {
+        let original_title = original_title
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string);
+        Self {
+            id,
+            title: title.to_string(),
+            normalized_title: Self::normalize(title),
+            normalized_original_title: original_title.as_deref().map(Self::normalize),
+            original_title,
+            type_id: None,
+            author_ids: Vec::new(),
+            original_languages: Vec::new(),
+            book_count: 0,
+        }
+    }
+
+    /// Titolo normalizzato: minuscole, senza accenti e punteggiatura,
+    /// cifre conservate e senza articolo iniziale
+    pub fn normalize(title: &str) -> String {
+        let normalizer = MLStringUtils::default();
+        let words: Vec<String> = title
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(|w| {
+                if w.chars().all(|c| c.is_ascii_digit()) {
+                    w.to_string()
+                } else {
+                    normalizer.normalize_string(w).replace(' ', "")
+                }
+            })
+            .filter(|w| !w.is_empty())
+            .collect();
+        let start = match words.first() {
+            Some(first) if words.len() > 1 && LEADING_ARTICLES.contains(&first.as_str()) => 1,
+            _ => 0,
+        };
+        words[start..].join(" ")
+    }
+
+    /// Somiglianza dei titoli: il massimo tra titolo e titolo originale,
+    /// anche incrociati (un'edizione tradotta ha come titolo originale il
+    /// titolo dell'altra). Titoli con numeri diversi ("Racconti 1" e
+    /// "Racconti 2") sono volumi distinti e valgono la metà.
+    pub fn title_similarity(&self, other: &ContentRecord) -> f64 {
+        let ours = [
+            Some(&self.normalized_title),
+            self.normalized_original_title.as_ref(),
+        ];
+        let theirs = [
+            Some(&other.normalized_title),
+            other.normalized_original_title.as_ref(),
+        ];
+        ours.iter()
+            .flatten()
+            .flat_map(|a| theirs.iter().flatten().map(move |b| (a, b)))
+            .filter(|(a, b)| !a.is_empty() && !b.is_empty())
+            .map(|(a, b)| {
+                let similarity = jaro_winkler(a, b);
+                if volume_numbers(a) == volume_numbers(b) {
+                    similarity
+                } else {
+                    similarity * 0.5
+                }
+            })
+            .fold(0.0, f64::max)
+    }
+
+    /// Quota di autori in comune (Jaccard); `None` se uno dei due non ha autori
+    pub fn author_overlap(&self, other: &ContentRecord) -> Option<f64> {
+        if self.author_ids.is_empty() || other.author_ids.is_empty() {
+            return None;
+        }
+        let shared = self
+            .author_ids
+            .iter()
+            .filter(|id| other.author_ids.contains(id))
+            .count();
+        let union = self.author_ids.len() + other.author_ids.len() - shared;
+        Some(shared as f64 / union as f64)
+    }
+
+    /// Probabilità che i due record siano la stessa opera (0.0-1.0)
+    ///
+    /// Parte dalla somiglianza dei titoli; gli autori in comune pesano un
+    /// quarto e l'assenza di autori comuni la penalizza. Tipo e lingua
+    /// originale diversi (se noti da entrambe le parti) riducono il punteggio.
+    pub fn work_similarity(&self, other: &ContentRecord) -> f64 {
+        let mut score = self.title_similarity(other);
+
+        match self.author_overlap(other) {
+            Some(overlap) => {
+                score = 0.75 * score + 0.25 * overlap;
+                if overlap == 0.0 {
+                    score *= 0.6;
+                }
+            }
+            None => score *= 0.95,
+        }
+
+        if let (Some(a), Some(b)) = (self.type_id, other.type_id) {
+            if a != b {
+                score *= 0.7;
+            }
+        }
+
+        if !self.original_languages.is_empty()
+            && !other.original_languages.is_empty()
+            && !self
+                .original_languages
+                .iter()
+                .any(|l| other.original_languages.contains(l))
+        {
+            score *= 0.8;
+        }
+
+        score
+    }
+}
+
+/// Numeri in un titolo normalizzato: cifre e numero romano finale
+fn volume_numbers(title: &str) -> Vec<&str> {
+    let words: Vec<&str> = title.split(' ').collect();
+    let last = words.len() - 1;
+    words
+        .iter()
+        .enumerate()
+        .filter(|&(i, w)| {
+            w.chars().all(|c| c.is_ascii_digit())
+                || (i == last && i > 0 && w.len() <= 4 && w.chars().all(|c| "ivx".contains(c)))
+        })
+        .map(|(_, w)| *w)
+        .collect()
+}
+
+impl MLProcessable for ContentRecord {
+    fn id(&self) -> i64 {
+        self.id
+    }
+
+    fn canonical_key(&self) -> String {
+        self.normalized_title.clone()
+    }
+
+    fn variants(&self) -> Vec<String> {
+        std::iter::once(self.title.clone())
+            .chain(self.original_title.clone())
+            .collect()
+    }
+
+    fn set_variants(&mut self, _variants: Vec<String>) {
+        // Le varianti di un'opera sono titolo e titolo originale
+    }
+}
@@ -3,6 +3,8 @@
 //! This module provides functions to load entity records from the ritmo database
 //! and convert them into ML-ready structures for deduplication.
 
+use crate::contents::record::ContentRecord;
+use crate::people::name_rules::NameParsingRules;
 use crate::people::record::PersonRecord;
 use crate::publishers::record::PublisherRecord;
 use crate::roles::record::RoleRecord;
@@ -11,6 +13,10 @@ use crate::tags::record::TagRecord;
 use crate::utils::MLStringUtils;
 use ritmo_errors::RitmoResult;
 use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+
+/// Books linked to each entity (entity id -> book ids)
+pub type EntityBooks = HashMap<i64, HashSet<i64>>;
 
 /// Load all people (authors) from the database
 ///
@@ -19,8 +25,17 @@ use sqlx::SqlitePool;
 /// - id: database ID
 /// - full_name: original name from database
 /// - normalized_key: NFC-normalized version for comparison
-/// - aliases: empty vector (to be populated by ML)
+/// - aliases: keys of the names in the `aliases` table
 pub async fn load_people_from_db(pool: &SqlitePool) -> RitmoResult<Vec<PersonRecord>> {
+    load_people_from_db_with_rules(pool, &NameParsingRules::default()).await
+}
+
+/// Load all people from the database, parsing names with custom rules
+/// (e.g. extra surname particles from the user settings)
+pub async fn load_people_from_db_with_rules(
+    pool: &SqlitePool,
+    rules: &NameParsingRules,
+) -> RitmoResult<Vec<PersonRecord>> {
     let normalizer = MLStringUtils::default();
 
     let rows = sqlx::query!(
@@ -33,15 +48,28 @@ pub async fn load_people_from_db(pool: &SqlitePool) -> RitmoResult<Vec<PersonRec
     .fetch_all(pool)
     .await?;
 
+    let alias_rows = sqlx::query!("SELECT person_id, name FROM aliases ORDER BY id")
+        .fetch_all(pool)
+        .await?;
+    let mut aliases: HashMap<i64, Vec<String>> = HashMap::new();
+    for row in alias_rows {
+        aliases.entry(row.person_id).or_default().push(row.name);
+    }
+
     let mut records = Vec::new();
 
     for row in rows {
         let id = row.id;
         let name = row.name;
 
-        // Use PersonRecord::new which handles parsing and normalization
-        match PersonRecord::new(id, &name, &normalizer) {
-            Ok(record) => records.push(record),
+        // Use PersonRecord::with_rules which handles parsing and normalization
+        match PersonRecord::with_rules(id, &name, &normalizer, rules) {
+            Ok(mut record) => {
+                for alias in aliases.get(&id).into_iter().flatten() {
+                    record.add_alias_name(alias, &normalizer, rules);
+                }
+                records.push(record)
+            }
             Err(e) => {
                 // Log error but continue with other records
                 eprintln!(
@@ -193,6 +221,235 @@ pub async fn load_roles_from_db(pool: &SqlitePool) -> RitmoResult<Vec<RoleRecord
     Ok(records)
 }
 
+/// Load all contents (works) from the database
+///
+/// Returns a vector of ContentRecord with normalized titles plus the signals
+/// used for work-level deduplication: authors (`role.author`), type,
+/// original languages and number of books containing the content.
+pub async fn load_contents_from_db(pool: &SqlitePool) -> RitmoResult<Vec<ContentRecord>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT c.id, c.name, c.original_title, c.type_id,
+               (SELECT COUNT(*) FROM x_books_contents bc WHERE bc.content_id = c.id) AS "book_count!: i64"
+        FROM contents c
+        ORDER BY c.id
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut records: Vec<ContentRecord> = rows
+        .into_iter()
+        .map(|row| {
+            let mut record = ContentRecord::new(row.id, &row.name, row.original_title.as_deref());
+            record.type_id = row.type_id;
+            record.book_count = row.book_count as usize;
+            record
+        })
+        .collect();
+    let index: HashMap<i64, usize> = records.iter().enumerate().map(|(i, r)| (r.id, i)).collect();
+
+    let authors = sqlx::query!(
+        r#"
+        SELECT DISTINCT cpr.content_id, cpr.person_id
+        FROM x_contents_people_roles cpr
+        JOIN roles r ON r.id = cpr.role_id
+        WHERE r.key = 'role.author'
+        ORDER BY cpr.content_id, cpr.person_id
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    for row in authors {
+        if let Some(&i) = index.get(&row.content_id) {
+            records[i].author_ids.push(row.person_id);
+        }
+    }
+
+    let languages = sqlx::query!(
+        r#"
+        SELECT DISTINCT cl.content_id, rl.iso_code_3char
+        FROM x_contents_languages cl
+        JOIN running_languages rl ON rl.id = cl.language_id
+        WHERE rl.language_role = 'language_role.original'
+        ORDER BY cl.content_id, rl.iso_code_3char
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    for row in languages {
+        if let Some(&i) = index.get(&row.content_id) {
+            records[i].original_languages.push(row.iso_code_3char.to_lowercase());
+        }
+    }
+
+    Ok(records)
+}
+
+/// Books of each person, as a book participant or through a content
+pub async fn load_people_books(pool: &SqlitePool) -> RitmoResult<EntityBooks> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT person_id AS "entity_id!: i64", book_id AS "book_id!: i64" FROM x_books_people_roles
+        UNION
+        SELECT cpr.person_id, bc.book_id
+        FROM x_contents_people_roles cpr
+        JOIN x_books_contents bc ON bc.content_id = cpr.content_id
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(group_books(rows.into_iter().map(|r| (r.entity_id, r.book_id))))
+}
+
+/// Books of each publisher
+pub async fn load_publisher_books(pool: &SqlitePool) -> RitmoResult<EntityBooks> {
+    let rows = sqlx::query!(
+        r#"SELECT publisher_id AS "entity_id!: i64", id AS "book_id!: i64" FROM books WHERE publisher_id IS NOT NULL"#
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(group_books(rows.into_iter().map(|r| (r.entity_id, r.book_id))))
+}
+
+/// Books of each series
+pub async fn load_series_books(pool: &SqlitePool) -> RitmoResult<EntityBooks> {
+    let rows = sqlx::query!(
+        r#"SELECT series_id AS "entity_id!: i64", id AS "book_id!: i64" FROM books WHERE series_id IS NOT NULL"#
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(group_books(rows.into_iter().map(|r| (r.entity_id, r.book_id))))
+}
+
+/// Books of each tag
+pub async fn load_tag_books(pool: &SqlitePool) -> RitmoResult<EntityBooks> {
+    let rows = sqlx::query!(
+        r#"SELECT tag_id AS "entity_id!: i64", book_id AS "book_id!: i64" FROM x_books_tags"#
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(group_books(rows.into_iter().map(|r| (r.entity_id, r.book_id))))
+}
+
+/// Books in which each role is used
+pub async fn load_role_books(pool: &SqlitePool) -> RitmoResult<EntityBooks> {
+    let rows = sqlx::query!(
+        r#"SELECT role_id AS "entity_id!: i64", book_id AS "book_id!: i64" FROM x_books_people_roles"#
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(group_books(rows.into_iter().map(|r| (r.entity_id, r.book_id))))
+}
+
+/// Books containing each content
+pub async fn load_content_books(pool: &SqlitePool) -> RitmoResult<EntityBooks> {
+    let rows = sqlx::query!(
+        r#"SELECT content_id AS "entity_id!: i64", book_id AS "book_id!: i64" FROM x_books_contents"#
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(group_books(rows.into_iter().map(|r| (r.entity_id, r.book_id))))
+}
+
+/// Co-occurrence data of a person or a series, used by the relational
+/// features of deduplication
+#[derive(Debug, Clone, Default)]
+pub struct EntityRelations {
+    pub books: HashSet<i64>,
+    pub publishers: HashSet<i64>,
+    pub series: HashSet<i64>,
+    /// People credited in the books (series only)
+    pub people: HashSet<i64>,
+    /// Birth year (people only)
+    pub birth_year: Option<i64>,
+    /// Death year (people only)
+    pub death_year: Option<i64>,
+}
+
+/// Books, publishers and series of each person, plus life dates
+pub async fn load_people_relations(pool: &SqlitePool) -> RitmoResult<HashMap<i64, EntityRelations>> {
+    let mut relations: HashMap<i64, EntityRelations> = HashMap::new();
+
+    let people = sqlx::query!(r#"SELECT id AS "id!: i64", birth_date, death_date FROM people"#)
+        .fetch_all(pool)
+        .await?;
+    for row in people {
+        let entry = relations.entry(row.id).or_default();
+        entry.birth_year = row.birth_date.map(timestamp_year);
+        entry.death_year = row.death_date.map(timestamp_year);
+    }
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT pb.person_id AS "person_id!: i64", b.id AS "book_id!: i64", b.publisher_id, b.series_id
+        FROM (
+            SELECT person_id, book_id FROM x_books_people_roles
+            UNION
+            SELECT cpr.person_id, bc.book_id
+            FROM x_contents_people_roles cpr
+            JOIN x_books_contents bc ON bc.content_id = cpr.content_id
+        ) pb
+        JOIN books b ON b.id = pb.book_id
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    for row in rows {
+        let entry = relations.entry(row.person_id).or_default();
+        entry.books.insert(row.book_id);
+        entry.publishers.extend(row.publisher_id);
+        entry.series.extend(row.series_id);
+    }
+
+    Ok(relations)
+}
+
+/// Books, publishers and people of each series
+pub async fn load_series_relations(pool: &SqlitePool) -> RitmoResult<HashMap<i64, EntityRelations>> {
+    let mut relations: HashMap<i64, EntityRelations> = HashMap::new();
+
+    let books = sqlx::query!(
+        r#"SELECT series_id AS "series_id!: i64", id AS "book_id!: i64", publisher_id FROM books WHERE series_id IS NOT NULL"#
+    )
+    .fetch_all(pool)
+    .await?;
+    for row in books {
+        let entry = relations.entry(row.series_id).or_default();
+        entry.books.insert(row.book_id);
+        entry.publishers.extend(row.publisher_id);
+    }
+
+    let people = sqlx::query!(
+        r#"
+        SELECT DISTINCT b.series_id AS "series_id!: i64", bpr.person_id
+        FROM books b
+        JOIN x_books_people_roles bpr ON bpr.book_id = b.id
+        WHERE b.series_id IS NOT NULL
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+    for row in people {
+        relations.entry(row.series_id).or_default().people.insert(row.person_id);
+    }
+
+    Ok(relations)
+}
+
+/// Year of a Unix timestamp (dates in the database are seconds since 1970)
+fn timestamp_year(timestamp: i64) -> i64 {
+    1970 + timestamp.div_euclid(31_556_952)
+}
+
+fn group_books(rows: impl Iterator<Item = (i64, i64)>) -> EntityBooks {
+    let mut books = EntityBooks::new();
+    for (entity_id, book_id) in rows {
+        books.entry(entity_id).or_default().insert(book_id);
+    }
+    books
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -319,4 +576,28 @@ mod tests {
             assert!(!role.normalized_name.is_empty());
         }
     }
+
+    #[tokio::test]
+    async fn test_load_contents_from_db() {
+        let pool = create_test_db().await.unwrap();
+        populate_test_contents(&pool).await.unwrap();
+
+        let contents = load_contents_from_db(&pool).await.unwrap();
+        assert_eq!(contents.len(), 6);
+
+        // Leading article removed, original title normalized too
+        let translation = &contents[2];
+        assert_eq!(translation.normalized_title, "name of the rose");
+        assert_eq!(translation.normalized_original_title.as_deref(), Some("nome della rosa"));
+        assert_eq!(translation.type_id, Some(1));
+
+        // Only authors count, not the translator
+        assert_eq!(translation.author_ids, vec![13]);
+        assert_eq!(translation.original_languages, vec!["ita"]);
+
+        // Content 1 is in two books
+        assert_eq!(contents[0].book_count, 2);
+        assert_eq!(contents[0].type_id, Some(1));
+        assert!(contents[1].type_id.is_none());
+    }
 }
@@ -3,13 +3,19 @@
 //! This module provides high-level functions that combine ML detection
 //! with database operations to identify and optionally merge duplicates.
 
-use crate::db_loaders::{load_people_from_db, load_publishers_from_db, load_roles_from_db, load_series_from_db, load_tags_from_db};
-use crate::entity_learner::MLEntityLearner;
-use crate::merge::{merge_people, merge_publishers, merge_roles, merge_series, merge_tags, MergeStats};
+use crate::blocking::{candidate_pairs, BlockingConfig};
+use crate::contents::record::ContentRecord;
+use crate::db_loaders::{load_content_books, load_contents_from_db, load_people_books, load_people_from_db_with_rules, load_publisher_books, load_publishers_from_db, load_role_books, load_roles_from_db, load_people_relations, load_series_books, load_series_from_db, load_series_relations, load_tag_books, load_tags_from_db, EntityBooks, EntityRelations};
+use crate::entity_learner::{EntityCluster, MLEntityLearner, VariantPatternType};
+use crate::people::name_rules::NameParsingRules;
+use crate::merge::{merge_contents, merge_people, merge_publishers, merge_roles, merge_series, merge_tags, MergeStats};
+use crate::pattern_functions::{default_classify_pattern_type, default_confidence_function};
 use crate::traits::MLProcessable;
 use ritmo_errors::RitmoResult;
+use serde::Serialize;
 use sqlx::SqlitePool;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use strsim::{jaro_winkler, levenshtein};
 
 /// Configuration for deduplication process
 #[derive(Debug, Clone)]
@@ -25,6 +31,9 @@ pub struct DeduplicationConfig {
 
     /// If true, only identify duplicates without merging
     pub dry_run: bool,
+
+    /// Weights of the co-occurrence features for people and series
+    pub relational: RelationalWeights,
 }
 
 impl Default for DeduplicationConfig {
@@ -34,12 +43,133 @@ impl Default for DeduplicationConfig {
             min_frequency: 3,
             auto_merge: false,
             dry_run: true, // Safe default: don't auto-merge
+            relational: RelationalWeights::default(),
+        }
+    }
+}
+
+/// Weights of the relational features added to the string similarity when
+/// deduplicating people and series
+///
+/// Each bonus is multiplied by the overlap of the two sets (shared items over
+/// the items of the smaller set): "J. Smith" and "John Smith" with books in
+/// the same series get the whole `series` bonus. Unknown data adds nothing.
+#[derive(Debug, Clone, Serialize)]
+pub struct RelationalWeights {
+    /// Books in common
+    pub books: f64,
+
+    /// Publishers in common
+    pub publishers: f64,
+
+    /// Series in common (people)
+    pub series: f64,
+
+    /// People in common (series)
+    pub people: f64,
+
+    /// Subtracted when both people have a birth (or death) year and they differ
+    pub life_dates_penalty: f64,
+
+    /// Years of difference still considered the same life date
+    pub life_dates_tolerance: i64,
+}
+
+impl Default for RelationalWeights {
+    fn default() -> Self {
+        Self {
+            books: 0.05,
+            publishers: 0.04,
+            series: 0.08,
+            people: 0.08,
+            life_dates_penalty: 0.3,
+            life_dates_tolerance: 1,
+        }
+    }
+}
+
+impl RelationalWeights {
+    /// No relational features: scores use the strings only
+    pub fn none() -> Self {
+        Self {
+            books: 0.0,
+            publishers: 0.0,
+            series: 0.0,
+            people: 0.0,
+            life_dates_penalty: 0.0,
+            life_dates_tolerance: 0,
+        }
+    }
+
+    /// Bonus minus penalty for two entities
+    pub fn adjustment(&self, a: &EntityRelations, b: &EntityRelations) -> f64 {
+        let mut adjustment = self.books * overlap(&a.books, &b.books)
+            + self.publishers * overlap(&a.publishers, &b.publishers)
+            + self.series * overlap(&a.series, &b.series)
+            + self.people * overlap(&a.people, &b.people);
+
+        let differ = |x: Option<i64>, y: Option<i64>| {
+            matches!((x, y), (Some(x), Some(y)) if (x - y).abs() > self.life_dates_tolerance)
+        };
+        if differ(a.birth_year, b.birth_year) || differ(a.death_year, b.death_year) {
+            adjustment -= self.life_dates_penalty;
         }
+        adjustment
+    }
+}
+
+/// Shared items over the items of the smaller set (0.0 if one is empty)
+fn overlap(a: &HashSet<i64>, b: &HashSet<i64>) -> f64 {
+    let smaller = a.len().min(b.len());
+    if smaller == 0 {
+        return 0.0;
     }
+    a.intersection(b).count() as f64 / smaller as f64
+}
+
+/// Why a duplicate was proposed: how one duplicate compares with the primary
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchEvidence {
+    /// ID of the duplicate entity
+    pub duplicate_id: i64,
+
+    /// Score used for grouping: Jaro-Winkler of the canonical keys, or
+    /// `ContentRecord::work_similarity` for contents
+    pub score: f64,
+
+    /// Jaro-Winkler similarity of the canonical keys
+    pub jaro_winkler: f64,
+
+    /// Bonus minus penalty of the relational features (people and series)
+    pub relational: f64,
+
+    /// Variant pattern matched by the pair
+    pub pattern_type: VariantPatternType,
+
+    /// Confidence of the pattern for this pair
+    pub pattern_confidence: f64,
+
+    /// Pairs with the same pattern on the same base form in this run
+    pub pattern_frequency: usize,
+
+    /// True if the pattern reached `min_confidence` and `min_frequency`
+    pub learned_pattern: bool,
+
+    /// True if both entities have the same canonical key
+    pub same_normalized_key: bool,
+
+    /// True if the canonical key of one entity is an alias of the other
+    pub alias_hit: bool,
+
+    /// Books linked to both entities
+    pub shared_books: usize,
+
+    /// Shared books over the books of the entity with fewer books (0.0-1.0)
+    pub book_overlap: f64,
 }
 
 /// A group of duplicate entities identified by ML
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DuplicateGroup {
     /// ID of the primary entity to keep
     pub primary_id: i64,
@@ -55,10 +185,13 @@ pub struct DuplicateGroup {
 
     /// Confidence score for this duplicate detection (0.0-1.0)
     pub confidence: f64,
+
+    /// Evidence for each duplicate, in the order of `duplicate_ids`
+    pub evidence: Vec<MatchEvidence>,
 }
 
 /// Result of a deduplication operation
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct DeduplicationResult {
     /// Total entities processed
     pub total_entities: usize,
@@ -86,9 +219,22 @@ pub struct DeduplicationResult {
 pub async fn deduplicate_people(
     pool: &SqlitePool,
     config: &DeduplicationConfig,
+) -> RitmoResult<DeduplicationResult> {
+    deduplicate_people_with_rules(pool, config, &NameParsingRules::default()).await
+}
+
+/// Find and optionally merge duplicate people, parsing names with custom rules
+///
+/// Same as `deduplicate_people`; `rules` controls how names are split into
+/// given name and surname (particles, family-name-first conventions), which
+/// determines the canonical keys used for clustering.
+pub async fn deduplicate_people_with_rules(
+    pool: &SqlitePool,
+    config: &DeduplicationConfig,
+    rules: &NameParsingRules,
 ) -> RitmoResult<DeduplicationResult> {
     // Step 1: Load all people from database
-    let people = load_people_from_db(pool).await?;
+    let people = load_people_from_db_with_rules(pool, rules).await?;
     let total_entities = people.len();
 
     if people.is_empty() {
@@ -103,14 +249,15 @@ pub async fn deduplicate_people(
     // Step 2: Extract canonical keys for clustering
     let canonical_keys: Vec<String> = people.iter().map(|p| p.canonical_key()).collect();
 
-    // Step 3: Run ML clustering
-    let mut learner = MLEntityLearner::new();
-    learner.minimum_confidence = config.min_confidence;
-    learner.minimum_frequency = config.min_frequency;
-    learner.create_clusters(&canonical_keys);
+    // Step 3: Run ML clustering, with co-occurrences and life dates
+    let relations = load_people_relations(pool).await?;
+    let relational = relational_fn(&relations, &config.relational);
+    let ids: Vec<i64> = people.iter().map(|p| p.id).collect();
+    let learner = learn_clusters_with(&canonical_keys, config, |i, j| relational(ids[i], ids[j]));
 
     // Step 4: Convert clusters to duplicate groups
-    let duplicate_groups = clusters_to_duplicate_groups(&learner, &people);
+    let books = load_people_books(pool).await?;
+    let duplicate_groups = clusters_to_duplicate_groups(&learner, &people, &books, &relational);
 
     // Step 5: Optionally merge duplicates
     let (merged_groups, skipped) = if !config.dry_run && config.auto_merge {
@@ -146,12 +293,10 @@ pub async fn deduplicate_publishers(
 
     let canonical_keys: Vec<String> = publishers.iter().map(|p| p.canonical_key()).collect();
 
-    let mut learner = MLEntityLearner::new();
-    learner.minimum_confidence = config.min_confidence;
-    learner.minimum_frequency = config.min_frequency;
-    learner.create_clusters(&canonical_keys);
+    let learner = learn_clusters(&canonical_keys, config);
 
-    let duplicate_groups = clusters_to_duplicate_groups(&learner, &publishers);
+    let books = load_publisher_books(pool).await?;
+    let duplicate_groups = clusters_to_duplicate_groups(&learner, &publishers, &books, &no_relations);
 
     let (merged_groups, skipped) = if !config.dry_run && config.auto_merge {
         merge_duplicate_publishers(pool, &duplicate_groups, config).await?
@@ -186,12 +331,13 @@ pub async fn deduplicate_series(
 
     let canonical_keys: Vec<String> = series.iter().map(|s| s.canonical_key()).collect();
 
-    let mut learner = MLEntityLearner::new();
-    learner.minimum_confidence = config.min_confidence;
-    learner.minimum_frequency = config.min_frequency;
-    learner.create_clusters(&canonical_keys);
+    let relations = load_series_relations(pool).await?;
+    let relational = relational_fn(&relations, &config.relational);
+    let ids: Vec<i64> = series.iter().map(|s| s.id).collect();
+    let learner = learn_clusters_with(&canonical_keys, config, |i, j| relational(ids[i], ids[j]));
 
-    let duplicate_groups = clusters_to_duplicate_groups(&learner, &series);
+    let books = load_series_books(pool).await?;
+    let duplicate_groups = clusters_to_duplicate_groups(&learner, &series, &books, &relational);
 
     let (merged_groups, skipped) = if !config.dry_run && config.auto_merge {
         merge_duplicate_series(pool, &duplicate_groups, config).await?
@@ -226,12 +372,10 @@ pub async fn deduplicate_tags(
 
     let canonical_keys: Vec<String> = tags.iter().map(|t| t.canonical_key()).collect();
 
-    let mut learner = MLEntityLearner::new();
-    learner.minimum_confidence = config.min_confidence;
-    learner.minimum_frequency = config.min_frequency;
-    learner.create_clusters(&canonical_keys);
+    let learner = learn_clusters(&canonical_keys, config);
 
-    let duplicate_groups = clusters_to_duplicate_groups(&learner, &tags);
+    let books = load_tag_books(pool).await?;
+    let duplicate_groups = clusters_to_duplicate_groups(&learner, &tags, &books, &no_relations);
 
     let (merged_groups, skipped) = if !config.dry_run && config.auto_merge {
         merge_duplicate_tags(pool, &duplicate_groups, config).await?
@@ -275,12 +419,10 @@ pub async fn deduplicate_roles(
 
     let canonical_keys: Vec<String> = roles.iter().map(|r| r.canonical_key()).collect();
 
-    let mut learner = MLEntityLearner::new();
-    learner.minimum_confidence = config.min_confidence;
-    learner.minimum_frequency = config.min_frequency;
-    learner.create_clusters(&canonical_keys);
+    let learner = learn_clusters(&canonical_keys, config);
 
-    let duplicate_groups = clusters_to_duplicate_groups(&learner, &roles);
+    let books = load_role_books(pool).await?;
+    let duplicate_groups = clusters_to_duplicate_groups(&learner, &roles, &books, &no_relations);
 
     let (merged_groups, skipped) = if !config.dry_run && config.auto_merge {
         merge_duplicate_roles(pool, &duplicate_groups, config).await?
@@ -296,20 +438,108 @@ pub async fn deduplicate_roles(
     })
 }
 
+/// Find and optionally merge duplicate contents (the same work imported
+/// once per edition)
+///
+/// Unlike the other entities, contents are not clustered on the title alone:
+/// each candidate pair is scored with `ContentRecord::work_similarity`, which
+/// combines title and original title similarity, shared authors, type and
+/// original language. The content in most books (then the oldest) is kept.
+///
+/// # Arguments
+/// * `pool` - Database connection pool
+/// * `config` - Deduplication configuration
+pub async fn deduplicate_contents(
+    pool: &SqlitePool,
+    config: &DeduplicationConfig,
+) -> RitmoResult<DeduplicationResult> {
+    let contents = load_contents_from_db(pool).await?;
+    let total_entities = contents.len();
+
+    let books = load_content_books(pool).await?;
+    let duplicate_groups = find_duplicate_contents(&contents, config.min_confidence, &books);
+
+    let (merged_groups, skipped) = if !config.dry_run && config.auto_merge {
+        merge_duplicate_contents(pool, &duplicate_groups, config).await?
+    } else {
+        (Vec::new(), 0)
+    };
+
+    Ok(DeduplicationResult {
+        total_entities,
+        duplicate_groups,
+        merged_groups,
+        skipped_low_confidence: skipped,
+    })
+}
+
 // ============================================================================
 // Helper functions
 // ============================================================================
 
+/// Cluster canonical keys and count the variant patterns inside each cluster
+fn learn_clusters(canonical_keys: &[String], config: &DeduplicationConfig) -> MLEntityLearner {
+    learn_clusters_with(canonical_keys, config, |_, _| 0.0)
+}
+
+/// Like `learn_clusters`, adding `relational(i, j)` to the Jaro-Winkler
+/// similarity of each pair of keys
+fn learn_clusters_with<F>(
+    canonical_keys: &[String],
+    config: &DeduplicationConfig,
+    relational: F,
+) -> MLEntityLearner
+where
+    F: Fn(usize, usize) -> f64 + Sync,
+{
+    let mut learner = MLEntityLearner::new();
+    learner.minimum_confidence = config.min_confidence;
+    learner.minimum_frequency = config.min_frequency;
+    learner.create_clusters_scored(canonical_keys, |i, j| {
+        (jaro_winkler(&canonical_keys[i], &canonical_keys[j]) + relational(i, j)).clamp(0.0, 1.0)
+    });
+    learner.identify_variant_patterns_with_defaults();
+    learner
+}
+
+/// Relational adjustment of two entities by id
+fn relational_fn<'a>(
+    relations: &'a HashMap<i64, EntityRelations>,
+    weights: &'a RelationalWeights,
+) -> impl Fn(i64, i64) -> f64 + Sync + 'a {
+    let none = EntityRelations::default();
+    move |a, b| {
+        weights.adjustment(
+            relations.get(&a).unwrap_or(&none),
+            relations.get(&b).unwrap_or(&none),
+        )
+    }
+}
+
+/// Relational adjustment of the entities without relational features
+fn no_relations(_: i64, _: i64) -> f64 {
+    0.0
+}
+
 /// Convert ML clusters into duplicate groups with entity details
+///
+/// Entities sharing the same canonical key appear once per entity in the
+/// cluster members and are matched to the entities in id order.
 fn clusters_to_duplicate_groups<T: MLProcessable>(
     learner: &MLEntityLearner,
     entities: &[T],
+    books: &EntityBooks,
+    relational: &dyn Fn(i64, i64) -> f64,
 ) -> Vec<DuplicateGroup> {
-    // Build a map from canonical_key to entity for quick lookup
-    let mut key_to_entity: HashMap<String, &T> = HashMap::new();
+    // Build a map from canonical_key to entities for quick lookup
+    let mut key_to_entities: HashMap<String, Vec<&T>> = HashMap::new();
     for entity in entities {
-        key_to_entity.insert(entity.canonical_key(), entity);
+        key_to_entities
+            .entry(entity.canonical_key())
+            .or_default()
+            .push(entity);
     }
+    let mut next_entity: HashMap<&str, usize> = HashMap::new();
 
     let mut groups = Vec::new();
 
@@ -318,11 +548,26 @@ fn clusters_to_duplicate_groups<T: MLProcessable>(
             continue; // Not a duplicate if only one member
         }
 
-        // First member is the centroid (primary)
+        // Entity of each member, with its position in the cluster
+        let mut members: Vec<(usize, &T)> = Vec::new();
+        for (position, member_key) in cluster.members.iter().enumerate() {
+            let Some(candidates) = key_to_entities.get(member_key) else {
+                continue;
+            };
+            let next = next_entity.entry(member_key.as_str()).or_insert(0);
+            if let Some(entity) = candidates.get(*next) {
+                members.push((position, *entity));
+                *next += 1;
+            }
+        }
+
+        // The centroid is the primary
         let primary_key = &cluster.centroid;
-        let primary_entity = match key_to_entity.get(primary_key) {
-            Some(e) => e,
-            None => continue, // Skip if entity not found
+        let Some(&(primary_position, primary_entity)) = members
+            .iter()
+            .find(|(position, _)| &cluster.members[*position] == primary_key)
+        else {
+            continue; // Skip if entity not found
         };
 
         let primary_id = primary_entity.id();
@@ -331,16 +576,22 @@ fn clusters_to_duplicate_groups<T: MLProcessable>(
         // Rest are duplicates
         let mut duplicate_ids = Vec::new();
         let mut duplicate_names = Vec::new();
+        let mut evidence = Vec::new();
 
-        for member_key in &cluster.members {
-            if member_key == primary_key {
+        for &(position, dup_entity) in &members {
+            if position == primary_position {
                 continue; // Skip primary itself
             }
-
-            if let Some(dup_entity) = key_to_entity.get(member_key) {
-                duplicate_ids.push(dup_entity.id());
-                duplicate_names.push(member_key.clone());
-            }
+            duplicate_ids.push(dup_entity.id());
+            duplicate_names.push(cluster.members[position].clone());
+            evidence.push(cluster_pair_evidence(
+                learner,
+                cluster,
+                (primary_position, primary_entity),
+                (position, dup_entity),
+                books,
+                relational(primary_id, dup_entity.id()),
+            ));
         }
 
         if !duplicate_ids.is_empty() {
@@ -350,6 +601,7 @@ fn clusters_to_duplicate_groups<T: MLProcessable>(
                 duplicate_ids,
                 duplicate_names,
                 confidence: cluster.confidence,
+                evidence,
             });
         }
     }
@@ -357,6 +609,95 @@ fn clusters_to_duplicate_groups<T: MLProcessable>(
     groups
 }
 
+/// Evidence for a primary/duplicate pair of the same cluster
+///
+/// The pattern is looked up in the order used by the learner (cluster position).
+fn cluster_pair_evidence<T: MLProcessable>(
+    learner: &MLEntityLearner,
+    cluster: &EntityCluster,
+    (primary_position, primary): (usize, &T),
+    (position, duplicate): (usize, &T),
+    books: &EntityBooks,
+    relational: f64,
+) -> MatchEvidence {
+    let (a, b) = if primary_position < position {
+        (&cluster.members[primary_position], &cluster.members[position])
+    } else {
+        (&cluster.members[position], &cluster.members[primary_position])
+    };
+    let mut evidence = pair_evidence(primary, duplicate, a, b, books);
+    evidence.relational = relational;
+    evidence.score = (evidence.jaro_winkler + relational).clamp(0.0, 1.0);
+    if a == b {
+        return evidence; // Same key: no variant pattern
+    }
+    evidence.pattern_frequency = learner
+        .pattern_frequency
+        .get(&format!("{:?}->{:?}", evidence.pattern_type, a))
+        .copied()
+        .unwrap_or(0);
+    evidence.learned_pattern = learner
+        .learned_patterns
+        .iter()
+        .any(|p| &p.base_form == a && &p.variant_form == b);
+    evidence
+}
+
+/// Evidence that does not depend on the learner: similarity, pattern,
+/// key equality, aliases and shared books
+fn pair_evidence<T: MLProcessable>(
+    primary: &T,
+    duplicate: &T,
+    a: &str,
+    b: &str,
+    books: &EntityBooks,
+) -> MatchEvidence {
+    let primary_key = primary.canonical_key();
+    let duplicate_key = duplicate.canonical_key();
+    let similarity = jaro_winkler(a, b);
+    let pattern_type = if a == b {
+        VariantPatternType::Other
+    } else {
+        default_classify_pattern_type(a, b, levenshtein(a, b))
+    };
+    let pattern_confidence = default_confidence_function(a, b, &pattern_type, similarity);
+
+    let is_alias = |owner: &T, owner_key: &str, key: &str| {
+        owner
+            .variants()
+            .iter()
+            .any(|variant| variant != owner_key && variant == key)
+    };
+    let alias_hit = is_alias(primary, &primary_key, &duplicate_key)
+        || is_alias(duplicate, &duplicate_key, &primary_key);
+
+    let empty = HashSet::new();
+    let primary_books = books.get(&primary.id()).unwrap_or(&empty);
+    let duplicate_books = books.get(&duplicate.id()).unwrap_or(&empty);
+    let shared_books = primary_books.intersection(duplicate_books).count();
+    let smaller = primary_books.len().min(duplicate_books.len());
+    let book_overlap = if smaller == 0 {
+        0.0
+    } else {
+        shared_books as f64 / smaller as f64
+    };
+
+    MatchEvidence {
+        duplicate_id: duplicate.id(),
+        score: similarity,
+        jaro_winkler: similarity,
+        relational: 0.0,
+        pattern_type,
+        pattern_confidence,
+        pattern_frequency: 0,
+        learned_pattern: false,
+        same_normalized_key: primary_key == duplicate_key,
+        alias_hit,
+        shared_books,
+        book_overlap,
+    }
+}
+
 /// Merge duplicate people based on duplicate groups
 async fn merge_duplicate_people(
     pool: &SqlitePool,
@@ -506,6 +847,131 @@ async fn merge_duplicate_roles(
     Ok((merged, skipped))
 }
 
+/// Group contents whose work similarity with the primary reaches the threshold
+///
+/// Titles and original titles go through the same blocking as the other
+/// entities, so a translation is compared with the edition it was translated
+/// from. Members are compared with the primary only, never chained.
+fn find_duplicate_contents(
+    contents: &[ContentRecord],
+    threshold: f64,
+    books: &EntityBooks,
+) -> Vec<DuplicateGroup> {
+    let mut keys = Vec::new();
+    let mut owners = Vec::new();
+    for (i, content) in contents.iter().enumerate() {
+        keys.push(content.normalized_title.clone());
+        owners.push(i);
+        if let Some(original) = &content.normalized_original_title {
+            keys.push(original.clone());
+            owners.push(i);
+        }
+    }
+
+    let mut candidates = HashSet::new();
+    for (a, b) in candidate_pairs(&keys, &BlockingConfig::default()) {
+        let (i, j) = (owners[a], owners[b]);
+        if i != j {
+            candidates.insert((i.min(j), i.max(j)));
+        }
+    }
+
+    let mut neighbours: HashMap<usize, Vec<(usize, f64)>> = HashMap::new();
+    for (i, j) in candidates {
+        let score = contents[i].work_similarity(&contents[j]);
+        if score >= threshold {
+            neighbours.entry(i).or_default().push((j, score));
+            neighbours.entry(j).or_default().push((i, score));
+        }
+    }
+
+    // Primary: the content in most books, then the oldest
+    let mut order: Vec<usize> = neighbours.keys().copied().collect();
+    order.sort_by_key(|&i| (std::cmp::Reverse(contents[i].book_count), contents[i].id));
+
+    let mut used = HashSet::new();
+    let mut groups = Vec::new();
+    for primary in order {
+        if used.contains(&primary) {
+            continue;
+        }
+        let mut members: Vec<(usize, f64)> = neighbours[&primary]
+            .iter()
+            .filter(|(j, _)| !used.contains(j))
+            .copied()
+            .collect();
+        if members.is_empty() {
+            continue;
+        }
+        members.sort_by_key(|&(j, _)| contents[j].id);
+        used.insert(primary);
+        used.extend(members.iter().map(|&(j, _)| j));
+
+        groups.push(DuplicateGroup {
+            primary_id: contents[primary].id,
+            primary_name: contents[primary].title.clone(),
+            duplicate_ids: members.iter().map(|&(j, _)| contents[j].id).collect(),
+            duplicate_names: members.iter().map(|&(j, _)| contents[j].title.clone()).collect(),
+            confidence: members.iter().map(|&(_, score)| score).fold(1.0, f64::min),
+            evidence: members
+                .iter()
+                .map(|&(j, score)| {
+                    let (primary, duplicate) = (&contents[primary], &contents[j]);
+                    // The original title of one is the title of the other (translation)
+                    let alias_hit = primary.normalized_original_title.as_ref()
+                        == Some(&duplicate.normalized_title)
+                        || duplicate.normalized_original_title.as_ref()
+                            == Some(&primary.normalized_title);
+                    MatchEvidence {
+                        score,
+                        alias_hit,
+                        ..pair_evidence(
+                            primary,
+                            duplicate,
+                            &primary.normalized_title,
+                            &duplicate.normalized_title,
+                            books,
+                        )
+                    }
+                })
+                .collect(),
+        });
+    }
+
+    groups.sort_by_key(|g| g.primary_id);
+    groups
+}
+
+/// Merge duplicate contents based on duplicate groups
+async fn merge_duplicate_contents(
+    pool: &SqlitePool,
+    groups: &[DuplicateGroup],
+    config: &DeduplicationConfig,
+) -> RitmoResult<(Vec<MergeStats>, usize)> {
+    let mut merged = Vec::new();
+    let mut skipped = 0;
+
+    for group in groups {
+        if group.confidence < config.min_confidence {
+            skipped += 1;
+            continue;
+        }
+
+        match merge_contents(pool, group.primary_id, &group.duplicate_ids).await {
+            Ok(stats) => merged.push(stats),
+            Err(e) => {
+                eprintln!(
+                    "Warning: failed to merge contents group (primary={}, duplicates={:?}): {}",
+                    group.primary_id, group.duplicate_ids, e
+                );
+                skipped += 1;
+            }
+        }
+    }
+
+    Ok((merged, skipped))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -534,6 +1000,7 @@ mod tests {
             min_frequency: 2,
             auto_merge: false, // Dry run only
             dry_run: true,
+            ..Default::default()
         };
 
         // Run deduplication
@@ -561,6 +1028,157 @@ mod tests {
         assert_eq!(count, 12);
     }
 
+    #[tokio::test]
+    async fn test_duplicate_groups_carry_evidence() {
+        use crate::test_helpers::*;
+
+        let pool = create_test_db().await.unwrap();
+        populate_test_people(&pool).await.unwrap();
+        populate_test_books_with_people(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO x_books_people_roles (book_id, person_id, role_id) VALUES (1, 3, 1);
+             INSERT INTO people (id, name) VALUES (20, 'Fyodor Dostoevsky'), (21, 'Fedor Dostoevskiy');
+             INSERT INTO aliases (name, person_id) VALUES ('Fëdor Dostoevskij', 20);",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let config = DeduplicationConfig {
+            min_confidence: 0.80,
+            min_frequency: 2,
+            auto_merge: false,
+            dry_run: true,
+            ..Default::default()
+        };
+        let result = deduplicate_people(&pool, &config).await.unwrap();
+
+        let evidence_for = |a: i64, b: i64| {
+            result
+                .duplicate_groups
+                .iter()
+                .find_map(|g| {
+                    let (primary, dup) = if g.primary_id == a { (a, b) } else { (b, a) };
+                    if g.primary_id != primary {
+                        return None;
+                    }
+                    g.evidence.iter().find(|e| e.duplicate_id == dup)
+                })
+                .unwrap_or_else(|| panic!("no group for {} and {}", a, b))
+        };
+
+        // "Stephen King" and "King, Stephen": same key, one shared book
+        let king = evidence_for(1, 3);
+        assert!(king.same_normalized_key);
+        assert_eq!(king.jaro_winkler, 1.0);
+        assert_eq!(king.shared_books, 1);
+        assert_eq!(king.book_overlap, 1.0);
+
+        // The alias of 20 is the key of 21
+        let dostoevsky = evidence_for(20, 21);
+        assert!(dostoevsky.alias_hit);
+        assert!(!dostoevsky.same_normalized_key);
+        assert_eq!(dostoevsky.shared_books, 0);
+
+        for group in &result.duplicate_groups {
+            assert_eq!(group.evidence.len(), group.duplicate_ids.len());
+        }
+        let json = serde_json::to_value(&result).unwrap();
+        assert!(json["duplicate_groups"][0]["evidence"][0]["pattern_type"].is_string());
+    }
+
+    #[test]
+    fn test_relational_adjustment() {
+        let weights = RelationalWeights::default();
+        let a = EntityRelations {
+            publishers: HashSet::from([1]),
+            series: HashSet::from([7]),
+            birth_year: Some(1900),
+            ..Default::default()
+        };
+        let b = EntityRelations {
+            publishers: HashSet::from([1, 2]),
+            series: HashSet::from([7]),
+            birth_year: Some(1901),
+            ..Default::default()
+        };
+        let bonus = weights.adjustment(&a, &b);
+        assert!((bonus - (weights.publishers + weights.series)).abs() < 1e-9);
+
+        let c = EntityRelations {
+            birth_year: Some(1950),
+            ..b.clone()
+        };
+        assert!(weights.adjustment(&a, &c) < 0.0);
+        assert_eq!(RelationalWeights::none().adjustment(&a, &c), 0.0);
+        assert_eq!(weights.adjustment(&a, &EntityRelations::default()), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_deduplicate_people_with_relations() {
+        use crate::test_helpers::*;
+
+        let pool = create_test_db().await.unwrap();
+        populate_test_roles(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO people (id, name, birth_date, death_date) VALUES
+                (1, 'Bob Dylan', NULL, NULL),
+                (2, 'Robert Dylan', NULL, NULL),
+                (3, 'Anne Bronte', -4733542800, NULL),
+                (4, 'Anna Bronte', -3786834240, NULL);
+             INSERT INTO publishers (id, name) VALUES (1, 'Tor');
+             INSERT INTO series (id, name) VALUES (1, 'The Long Road');
+             INSERT INTO books (id, name, publisher_id, series_id) VALUES
+                (1, 'Book One', 1, 1), (2, 'Book Two', 1, 1);
+             INSERT INTO x_books_people_roles (book_id, person_id, role_id) VALUES
+                (1, 1, 1), (2, 2, 1);",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let grouped = |result: &DeduplicationResult, a: i64, b: i64| {
+            result.duplicate_groups.iter().any(|g| {
+                let ids: Vec<i64> = std::iter::once(g.primary_id)
+                    .chain(g.duplicate_ids.iter().copied())
+                    .collect();
+                ids.contains(&a) && ids.contains(&b)
+            })
+        };
+
+        let strings_only = DeduplicationConfig {
+            min_confidence: 0.85,
+            min_frequency: 2,
+            relational: RelationalWeights::none(),
+            ..Default::default()
+        };
+        let result = deduplicate_people(&pool, &strings_only).await.unwrap();
+        assert!(!grouped(&result, 1, 2));
+        assert!(grouped(&result, 3, 4));
+
+        // Same series and publisher: "Bob Dylan" is "Robert Dylan"; born 1820
+        // and 1850: "Anne Bronte" is not "Anna Bronte"
+        let result = deduplicate_people(
+            &pool,
+            &DeduplicationConfig {
+                relational: RelationalWeights::default(),
+                ..strings_only
+            },
+        )
+        .await
+        .unwrap();
+        assert!(grouped(&result, 1, 2));
+        assert!(!grouped(&result, 3, 4));
+        let evidence = result
+            .duplicate_groups
+            .iter()
+            .flat_map(|g| &g.evidence)
+            .find(|e| e.duplicate_id == 1 || e.duplicate_id == 2)
+            .unwrap();
+        assert!(evidence.relational > 0.0);
+        assert!(evidence.score > evidence.jaro_winkler);
+    }
+
     #[tokio::test]
     async fn test_deduplicate_people_with_auto_merge() {
         use crate::test_helpers::*;
@@ -583,6 +1201,7 @@ mod tests {
             min_frequency: 2,
             auto_merge: true,
             dry_run: false,
+            ..Default::default()
         };
 
         // Run deduplication with merge
@@ -638,6 +1257,7 @@ mod tests {
             min_frequency: 2,
             auto_merge: false, // Dry run only
             dry_run: true,
+            ..Default::default()
         };
 
         // Run deduplication
@@ -664,4 +1284,49 @@ mod tests {
             .unwrap();
         assert_eq!(count, 8);
     }
+
+    #[tokio::test]
+    async fn test_deduplicate_contents() {
+        use crate::test_helpers::*;
+
+        let pool = create_test_db().await.unwrap();
+        populate_test_contents(&pool).await.unwrap();
+
+        let config = DeduplicationConfig {
+            min_confidence: 0.85,
+            min_frequency: 2,
+            auto_merge: false,
+            dry_run: true,
+            ..Default::default()
+        };
+
+        let result = deduplicate_contents(&pool, &config).await.unwrap();
+        assert_eq!(result.total_entities, 6);
+
+        // Same work: re-imported edition and translation; the content in two
+        // books is kept. Same title with different authors is not a duplicate.
+        assert_eq!(result.duplicate_groups.len(), 1);
+        let group = &result.duplicate_groups[0];
+        assert_eq!(group.primary_id, 1);
+        assert_eq!(group.duplicate_ids, vec![2, 3]);
+        assert!(group.confidence >= 0.85);
+
+        let merged = deduplicate_contents(
+            &pool,
+            &DeduplicationConfig {
+                auto_merge: true,
+                dry_run: false,
+                ..config
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(merged.merged_groups.len(), 1);
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM contents")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 4);
+    }
 }
@@ -1,4 +1,5 @@
-use std::collections::HashMap;
+use crate::blocking::{candidate_pairs, connected_components, score_pairs_by_index, BlockingConfig};
+use std::collections::{HashMap, HashSet};
 use strsim::jaro_winkler;
 
 /// Tipo di pattern generico
@@ -37,6 +38,9 @@ pub struct MLEntityLearner {
     pub pattern_frequency: HashMap<String, usize>,
     pub minimum_confidence: f64,
     pub minimum_frequency: usize,
+    /// Generazione delle coppie da confrontare in `create_clusters`
+    #[serde(default)]
+    pub blocking: BlockingConfig,
 }
 
 impl MLEntityLearner {
@@ -47,36 +51,96 @@ impl MLEntityLearner {
             pattern_frequency: HashMap::new(),
             minimum_confidence: 0.85,
             minimum_frequency: 3,
+            blocking: BlockingConfig::default(),
         }
     }
 
+    /// Raggruppa gli elementi con similarità Jaro-Winkler oltre 0.85
+    ///
+    /// Ogni elemento non ancora assegnato raccoglie, in ordine, gli elementi
+    /// successivi simili a lui. Si confrontano solo le coppie candidate del
+    /// blocking; le componenti connesse delle coppie simili vengono poi
+    /// raggruppate in parallelo.
     pub fn create_clusters(&mut self, items: &[String]) {
-        let mut clusters = Vec::new();
-        let mut used = vec![false; items.len()];
+        self.create_clusters_scored(items, |i, j| jaro_winkler(&items[i], &items[j]));
+    }
+
+    /// Come `create_clusters`, con un punteggio della coppia (per indici) al
+    /// posto della sola Jaro-Winkler, es. con le co-occorrenze nei libri;
+    /// la confidenza del gruppo è la media dei punteggi delle sue coppie
+    pub fn create_clusters_scored<F>(&mut self, items: &[String], score: F)
+    where
+        F: Fn(usize, usize) -> f64 + Sync,
+    {
         let threshold = 0.85;
-        for (i, a) in items.iter().enumerate() {
-            if used[i] {
-                continue;
-            }
-            let mut group = vec![a.clone()];
-            used[i] = true;
-            for (j, b) in items.iter().enumerate().skip(i + 1) {
-                if !used[j] && jaro_winkler(a, b) > threshold {
-                    group.push(b.clone());
-                    used[j] = true;
+        let threads = self.blocking.thread_count();
+        let pairs = candidate_pairs(items, &self.blocking);
+        let edges: Vec<(usize, usize, f64)> = score_pairs_by_index(&pairs, &score, threshold, threads)
+            .into_iter()
+            .filter(|&(_, _, score)| score > threshold)
+            .collect();
+
+        let mut neighbours: HashMap<usize, Vec<usize>> = HashMap::new();
+        for &(i, j, _) in &edges {
+            neighbours.entry(i).or_default().push(j);
+        }
+        let components = connected_components(items.len(), &edges);
+
+        let cluster_component = |component: &[usize]| -> Vec<(usize, EntityCluster)> {
+            let mut clusters = Vec::new();
+            let mut used: HashSet<usize> = HashSet::new();
+            for &i in component {
+                if !used.insert(i) {
+                    continue;
+                }
+                let mut indices = vec![i];
+                for &j in neighbours.get(&i).map(Vec::as_slice).unwrap_or_default() {
+                    if used.insert(j) {
+                        indices.push(j);
+                    }
+                }
+                if indices.len() > 1 {
+                    let group: Vec<String> = indices.iter().map(|&k| items[k].clone()).collect();
+                    let centroid = Self::find_centroid(&group);
+                    let confidence = Self::calc_group_confidence(&indices, &score);
+                    clusters.push((
+                        i,
+                        EntityCluster {
+                            centroid,
+                            members: group,
+                            confidence,
+                        },
+                    ));
                 }
             }
-            if group.len() > 1 {
-                let centroid = Self::find_centroid(&group);
-                let confidence = Self::calc_group_confidence(&group);
-                clusters.push(EntityCluster {
-                    centroid,
-                    members: group,
-                    confidence,
-                });
-            }
-        }
-        self.clusters = clusters;
+            clusters
+        };
+
+        let mut clusters: Vec<(usize, EntityCluster)> = if threads <= 1 || components.len() < 2 {
+            components.iter().flat_map(|c| cluster_component(c)).collect()
+        } else {
+            let chunk_size = components.len().div_ceil(threads);
+            std::thread::scope(|scope| {
+                let handles: Vec<_> = components
+                    .chunks(chunk_size)
+                    .map(|chunk| {
+                        scope.spawn(|| {
+                            chunk
+                                .iter()
+                                .flat_map(|c| cluster_component(c))
+                                .collect::<Vec<_>>()
+                        })
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .flat_map(|h| h.join().expect("thread di clustering terminato con errore"))
+                    .collect()
+            })
+        };
+        // Stesso ordine del confronto sequenziale: per primo elemento del gruppo
+        clusters.sort_by_key(|(first, _)| *first);
+        self.clusters = clusters.into_iter().map(|(_, cluster)| cluster).collect();
     }
 
     /// Identifica pattern di varianti usando funzioni custom
@@ -139,15 +203,15 @@ impl MLEntityLearner {
         centroid
     }
 
-    fn calc_group_confidence(group: &[String]) -> f64 {
-        if group.len() < 2 {
+    fn calc_group_confidence<F: Fn(usize, usize) -> f64>(indices: &[usize], score: &F) -> f64 {
+        if indices.len() < 2 {
             return 1.0;
         }
         let mut sum = 0.0;
         let mut count = 0;
-        for (i, x) in group.iter().enumerate() {
-            for y in group.iter().skip(i + 1) {
-                sum += jaro_winkler(x, y);
+        for (n, &x) in indices.iter().enumerate() {
+            for &y in indices.iter().skip(n + 1) {
+                sum += score(x.min(y), x.max(y));
                 count += 1;
             }
         }
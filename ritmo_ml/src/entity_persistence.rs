@@ -13,7 +13,8 @@ pub async fn save_ml_to_db(
     save_data(&mut **tx, &format!("{}_pattern_frequency", prefix), &ml.pattern_frequency).await?;
     let config = serde_json::json!({
         "minimum_confidence": ml.minimum_confidence,
-        "minimum_frequency": ml.minimum_frequency
+        "minimum_frequency": ml.minimum_frequency,
+        "blocking": ml.blocking
     });
     save_data(&mut **tx, &format!("{}_ml_config", prefix), &config).await?;
     Ok(())
@@ -67,6 +68,7 @@ pub async fn load_ml_from_db(
 
     let minimum_confidence = config_json["minimum_confidence"].as_f64().unwrap_or(0.85);
     let minimum_frequency = config_json["minimum_frequency"].as_u64().map(|v| v as usize).unwrap_or(3);
+    let blocking = serde_json::from_value(config_json["blocking"].clone()).unwrap_or_default();
 
     Ok(MLEntityLearner {
         clusters,
@@ -74,6 +76,7 @@ pub async fn load_ml_from_db(
         pattern_frequency,
         minimum_confidence,
         minimum_frequency,
+        blocking,
     })
 }
 
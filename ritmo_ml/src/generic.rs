@@ -1,3 +1,4 @@
+use crate::blocking::{candidate_pairs, score_pairs, BlockingConfig};
 use crate::feedback::Feedback;
 use crate::traits::MLProcessable;
 use std::collections::{HashMap, HashSet};
@@ -104,6 +105,8 @@ pub fn apply_positive_feedback<T: MLProcessable>(records: &mut [T], feedback: &F
 }
 
 /// Restituisce le coppie di indici con score di similarità superiore a una certa soglia
+///
+/// Confronta solo le coppie candidate del blocking di default.
 pub fn find_similar_pairs<T, F>(
     records: &[T],
     similarity: F,
@@ -111,16 +114,24 @@ pub fn find_similar_pairs<T, F>(
 ) -> Vec<(usize, usize, f64)>
 where
     T: MLProcessable,
-    F: Fn(&str, &str) -> f64,
+    F: Fn(&str, &str) -> f64 + Sync,
 {
-    let mut result = Vec::new();
-    for i in 0..records.len() {
-        for j in (i + 1)..records.len() {
-            let score = similarity(records[i].canonical_key().as_str(), records[j].canonical_key().as_str());
-            if score >= threshold {
-                result.push((i, j, score));
-            }
-        }
-    }
-    result
+    find_similar_pairs_with(records, similarity, threshold, &BlockingConfig::default())
+}
+
+/// Come `find_similar_pairs`, con un blocking specifico
+/// (`BlockingConfig::exhaustive()` confronta tutte le coppie)
+pub fn find_similar_pairs_with<T, F>(
+    records: &[T],
+    similarity: F,
+    threshold: f64,
+    blocking: &BlockingConfig,
+) -> Vec<(usize, usize, f64)>
+where
+    T: MLProcessable,
+    F: Fn(&str, &str) -> f64 + Sync,
+{
+    let keys: Vec<String> = records.iter().map(|r| r.canonical_key()).collect();
+    let pairs = candidate_pairs(&keys, blocking);
+    score_pairs(&keys, &pairs, similarity, threshold, blocking.thread_count())
 }
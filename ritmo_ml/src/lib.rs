@@ -1,3 +1,5 @@
+pub mod blocking;
+pub mod contents;
 pub mod core;
 pub mod db_loaders;
 pub mod deduplication;
@@ -6,6 +8,7 @@ pub mod entity_persistence;
 pub mod feedback;
 pub mod generic;
 pub mod merge;
+pub mod merge_journal;
 pub mod pattern_functions;
 pub mod people;
 pub mod publishers;
@@ -13,6 +16,7 @@ pub mod roles;
 pub mod series;
 pub mod tags;
 pub mod traits;
+pub mod transliteration;
 pub mod utils;
 
 #[cfg(test)]
@@ -1,14 +1,21 @@
 //! Merge operations for deduplicating entities
 //!
 //! This module provides safe merge operations for combining duplicate entities.
-//! All merges are executed within database transactions to ensure data integrity.
+//! All merges are executed within database transactions to ensure data integrity,
+//! and each one is recorded in the merge journal so it can be undone
+//! (see [`crate::merge_journal`]).
 
+use crate::merge_journal::{MergeEntity, MergeJournal};
+use crate::utils::MLStringUtils;
 use ritmo_errors::{RitmoErr, RitmoResult};
+use serde::{Deserialize, Serialize};
 use sqlx::{Sqlite, SqlitePool, Transaction};
 
 /// Statistics about a merge operation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MergeStats {
+    /// ID of the merge journal entry, used to undo the merge
+    pub merge_id: i64,
     pub primary_id: i64,
     pub merged_ids: Vec<i64>,
     pub books_updated: usize,
@@ -16,13 +23,29 @@ pub struct MergeStats {
     pub affected_book_ids: Vec<i64>,
 }
 
+/// Statistics about a split operation
+#[derive(Debug, Clone, Serialize)]
+pub struct SplitStats {
+    /// ID of the merge journal entry, used to undo the split
+    pub merge_id: i64,
+    pub source_id: i64,
+    pub target_id: i64,
+    /// The target person was created by the split (no person had that name)
+    pub target_created: bool,
+    pub books_moved: usize,
+    pub contents_moved: usize,
+    pub affected_book_ids: Vec<i64>,
+}
+
 /// Merge duplicate people (authors) into a single primary record
 ///
 /// This function:
 /// 1. Validates that all IDs exist
 /// 2. Updates all references in junction tables to point to primary_id
-/// 3. Deletes duplicate person records
-/// 4. Returns statistics about the merge
+/// 3. Keeps the names of the duplicates (e.g. the original-script "Достоевский")
+///    and their aliases as aliases of primary_id
+/// 4. Deletes duplicate person records
+/// 5. Returns statistics about the merge
 ///
 /// # Safety
 /// This operation is executed within a transaction. If any step fails,
@@ -52,6 +75,9 @@ pub async fn merge_people(
     // Step 1: Validate that all person IDs exist
     validate_people_exist(&mut tx, primary_id, duplicate_ids).await?;
 
+    // Record the rows about to change in the merge journal
+    let mut journal = MergeJournal::capture(&mut tx, MergeEntity::People, primary_id, duplicate_ids).await?;
+
     // Step 2: Update x_books_people_roles to point to primary_id
     let (books_updated, mut affected_book_ids) = update_books_people_roles(&mut tx, primary_id, duplicate_ids).await?;
 
@@ -63,19 +89,28 @@ pub async fn merge_people(
     affected_book_ids.sort();
     affected_book_ids.dedup();
 
-    // Step 4: Delete duplicate person records
-    delete_people(&mut tx, duplicate_ids).await?;
+    // Step 4: Keep the duplicate names as aliases of the primary record
+    let created_aliases = move_aliases_to_primary(&mut tx, primary_id, duplicate_ids).await?;
+    journal.record_created("aliases", &created_aliases);
 
-    // Commit transaction
-    tx.commit().await?;
+    // Step 5: Delete duplicate person records
+    delete_people(&mut tx, duplicate_ids).await?;
 
-    Ok(MergeStats {
+    let mut stats = MergeStats {
+        merge_id: 0,
         primary_id,
         merged_ids: duplicate_ids.to_vec(),
         books_updated,
         contents_updated,
         affected_book_ids,
-    })
+    };
+    // Write the journal entry, which assigns merge_id
+    journal.write(&mut tx, &mut stats).await?;
+
+    // Commit transaction
+    tx.commit().await?;
+
+    Ok(stats)
 }
 
 /// Merge duplicate publishers into a single primary record
@@ -101,22 +136,30 @@ pub async fn merge_publishers(
     // Step 1: Validate that all publisher IDs exist
     validate_publishers_exist(&mut tx, primary_id, duplicate_ids).await?;
 
+    // Record the rows about to change in the merge journal
+    let journal = MergeJournal::capture(&mut tx, MergeEntity::Publishers, primary_id, duplicate_ids).await?;
+
     // Step 2: Update books.publisher_id to point to primary_id
     let (books_updated, affected_book_ids) = update_books_publisher(&mut tx, primary_id, duplicate_ids).await?;
 
     // Step 3: Delete duplicate publisher records
     delete_publishers(&mut tx, duplicate_ids).await?;
 
-    // Commit transaction
-    tx.commit().await?;
-
-    Ok(MergeStats {
+    let mut stats = MergeStats {
+        merge_id: 0,
         primary_id,
         merged_ids: duplicate_ids.to_vec(),
         books_updated,
         contents_updated: 0, // Publishers not linked to contents
         affected_book_ids,
-    })
+    };
+    // Write the journal entry, which assigns merge_id
+    journal.write(&mut tx, &mut stats).await?;
+
+    // Commit transaction
+    tx.commit().await?;
+
+    Ok(stats)
 }
 
 /// Merge duplicate series into a single primary record
@@ -142,22 +185,30 @@ pub async fn merge_series(
     // Step 1: Validate that all series IDs exist
     validate_series_exist(&mut tx, primary_id, duplicate_ids).await?;
 
+    // Record the rows about to change in the merge journal
+    let journal = MergeJournal::capture(&mut tx, MergeEntity::Series, primary_id, duplicate_ids).await?;
+
     // Step 2: Update books.series_id to point to primary_id
     let (books_updated, affected_book_ids) = update_books_series(&mut tx, primary_id, duplicate_ids).await?;
 
     // Step 3: Delete duplicate series records
     delete_series(&mut tx, duplicate_ids).await?;
 
-    // Commit transaction
-    tx.commit().await?;
-
-    Ok(MergeStats {
+    let mut stats = MergeStats {
+        merge_id: 0,
         primary_id,
         merged_ids: duplicate_ids.to_vec(),
         books_updated,
         contents_updated: 0, // Series not linked to contents
         affected_book_ids,
-    })
+    };
+    // Write the journal entry, which assigns merge_id
+    journal.write(&mut tx, &mut stats).await?;
+
+    // Commit transaction
+    tx.commit().await?;
+
+    Ok(stats)
 }
 
 // ============================================================================
@@ -273,6 +324,50 @@ async fn update_contents_people_roles(
     Ok((total_updated, affected_book_ids))
 }
 
+async fn move_aliases_to_primary(
+    tx: &mut Transaction<'_, Sqlite>,
+    primary_id: i64,
+    duplicate_ids: &[i64],
+) -> RitmoResult<Vec<i64>> {
+    let normalizer = MLStringUtils::default();
+    let mut created_ids = Vec::new();
+    let primary_name = sqlx::query_scalar!("SELECT name FROM people WHERE id = ?", primary_id)
+        .fetch_one(&mut **tx)
+        .await?;
+
+    for &dup_id in duplicate_ids {
+        // Existing aliases follow the person; those already on primary are dropped by the cascade
+        sqlx::query!(
+            "UPDATE OR IGNORE aliases SET person_id = ? WHERE person_id = ?",
+            primary_id,
+            dup_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        let name = sqlx::query_scalar!("SELECT name FROM people WHERE id = ?", dup_id)
+            .fetch_one(&mut **tx)
+            .await?;
+        if name == primary_name {
+            continue;
+        }
+        let normalized = normalizer.normalize_string(&name);
+        let result = sqlx::query!(
+            "INSERT OR IGNORE INTO aliases (name, person_id, alias_normalized) VALUES (?, ?, ?)",
+            name,
+            primary_id,
+            normalized
+        )
+        .execute(&mut **tx)
+        .await?;
+        if result.rows_affected() > 0 {
+            created_ids.push(result.last_insert_rowid());
+        }
+    }
+
+    Ok(created_ids)
+}
+
 async fn delete_people(tx: &mut Transaction<'_, Sqlite>, ids: &[i64]) -> RitmoResult<()> {
     for &id in ids {
         sqlx::query!("DELETE FROM people WHERE id = ?", id)
@@ -485,6 +580,9 @@ pub async fn merge_tags(
     // Step 1: Validate that all tag IDs exist
     validate_tags_exist(&mut tx, primary_id, duplicate_ids).await?;
 
+    // Record the rows about to change in the merge journal
+    let journal = MergeJournal::capture(&mut tx, MergeEntity::Tags, primary_id, duplicate_ids).await?;
+
     // Step 2: Update x_books_tags to point to primary_id
     let (books_updated, mut affected_book_ids) = update_books_tags(&mut tx, primary_id, duplicate_ids).await?;
 
@@ -499,16 +597,21 @@ pub async fn merge_tags(
     // Step 4: Delete duplicate tag records
     delete_tags(&mut tx, duplicate_ids).await?;
 
-    // Commit transaction
-    tx.commit().await?;
-
-    Ok(MergeStats {
+    let mut stats = MergeStats {
+        merge_id: 0,
         primary_id,
         merged_ids: duplicate_ids.to_vec(),
         books_updated,
         contents_updated,
         affected_book_ids,
-    })
+    };
+    // Write the journal entry, which assigns merge_id
+    journal.write(&mut tx, &mut stats).await?;
+
+    // Commit transaction
+    tx.commit().await?;
+
+    Ok(stats)
 }
 
 // ============================================================================
@@ -674,6 +777,9 @@ pub async fn merge_roles(
     // Step 1: Validate that all role IDs exist
     validate_roles_exist(&mut tx, primary_id, duplicate_ids).await?;
 
+    // Record the rows about to change in the merge journal
+    let journal = MergeJournal::capture(&mut tx, MergeEntity::Roles, primary_id, duplicate_ids).await?;
+
     // Step 2: Update x_books_people_roles to point to primary_id
     let (books_updated, mut affected_book_ids) = update_books_people_roles_role(&mut tx, primary_id, duplicate_ids).await?;
 
@@ -688,16 +794,21 @@ pub async fn merge_roles(
     // Step 4: Delete duplicate role records
     delete_roles(&mut tx, duplicate_ids).await?;
 
-    // Commit transaction
-    tx.commit().await?;
-
-    Ok(MergeStats {
+    let mut stats = MergeStats {
+        merge_id: 0,
         primary_id,
         merged_ids: duplicate_ids.to_vec(),
         books_updated,
         contents_updated,
         affected_book_ids,
-    })
+    };
+    // Write the journal entry, which assigns merge_id
+    journal.write(&mut tx, &mut stats).await?;
+
+    // Commit transaction
+    tx.commit().await?;
+
+    Ok(stats)
 }
 
 // ============================================================================
@@ -823,6 +934,493 @@ async fn delete_roles(tx: &mut Transaction<'_, Sqlite>, ids: &[i64]) -> RitmoRes
     Ok(())
 }
 
+// ============================================================================
+// Merge contents
+// ============================================================================
+
+/// Merge duplicate contents (the same work re-created for each edition)
+/// into a single primary record
+///
+/// This function:
+/// 1. Validates that all IDs exist
+/// 2. Moves book links (`x_books_contents`), people roles, tags and
+///    languages of the duplicates onto primary_id, skipping links the
+///    primary already has
+/// 3. Fills the primary's empty fields (original title, type, date, pages)
+///    from the duplicates
+/// 4. Deletes duplicate content records
+///
+/// # Safety
+/// This operation is executed within a transaction. If any step fails,
+/// all changes are rolled back.
+///
+/// # Arguments
+/// * `pool` - Database connection pool
+/// * `primary_id` - ID of the content to keep (primary record)
+/// * `duplicate_ids` - IDs of contents to merge into primary (will be deleted)
+pub async fn merge_contents(
+    pool: &SqlitePool,
+    primary_id: i64,
+    duplicate_ids: &[i64],
+) -> RitmoResult<MergeStats> {
+    if duplicate_ids.is_empty() {
+        return Err(RitmoErr::Generic("No duplicate IDs provided".to_string()));
+    }
+
+    if duplicate_ids.contains(&primary_id) {
+        return Err(RitmoErr::Generic(
+            "Primary ID cannot be in duplicate IDs list".to_string(),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    // Step 1: Validate that all content IDs exist
+    validate_contents_exist(&mut tx, primary_id, duplicate_ids).await?;
+
+    // Record the rows about to change in the merge journal
+    let journal = MergeJournal::capture(&mut tx, MergeEntity::Contents, primary_id, duplicate_ids).await?;
+
+    // Step 2: Move x_books_contents links to primary_id
+    let books_updated = move_books_contents(&mut tx, primary_id, duplicate_ids).await?;
+
+    // Step 3: Move people roles, tags and languages to primary_id
+    let contents_updated = move_contents_relations(&mut tx, primary_id, duplicate_ids).await?;
+
+    // Step 4: Fill missing fields of the primary content
+    fill_content_fields(&mut tx, primary_id, duplicate_ids).await?;
+
+    // Step 5: Delete duplicate content records
+    delete_contents(&mut tx, duplicate_ids).await?;
+
+    // Every book of the merged work shows the primary's metadata now
+    let mut affected_book_ids: Vec<i64> = sqlx::query_scalar!(
+        "SELECT DISTINCT book_id FROM x_books_contents WHERE content_id = ?",
+        primary_id
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    affected_book_ids.sort();
+
+    let mut stats = MergeStats {
+        merge_id: 0,
+        primary_id,
+        merged_ids: duplicate_ids.to_vec(),
+        books_updated,
+        contents_updated,
+        affected_book_ids,
+    };
+    // Write the journal entry, which assigns merge_id
+    journal.write(&mut tx, &mut stats).await?;
+
+    // Commit transaction
+    tx.commit().await?;
+
+    Ok(stats)
+}
+
+// ============================================================================
+// Helper functions for content merging
+// ============================================================================
+
+async fn validate_contents_exist(
+    tx: &mut Transaction<'_, Sqlite>,
+    primary_id: i64,
+    duplicate_ids: &[i64],
+) -> RitmoResult<()> {
+    let primary_exists = sqlx::query!("SELECT id FROM contents WHERE id = ?", primary_id)
+        .fetch_optional(&mut **tx)
+        .await?;
+
+    if primary_exists.is_none() {
+        return Err(RitmoErr::Generic(format!(
+            "Primary content ID {} not found",
+            primary_id
+        )));
+    }
+
+    for &dup_id in duplicate_ids {
+        let exists = sqlx::query!("SELECT id FROM contents WHERE id = ?", dup_id)
+            .fetch_optional(&mut **tx)
+            .await?;
+
+        if exists.is_none() {
+            return Err(RitmoErr::Generic(format!(
+                "Duplicate content ID {} not found",
+                dup_id
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+async fn move_books_contents(
+    tx: &mut Transaction<'_, Sqlite>,
+    primary_id: i64,
+    duplicate_ids: &[i64],
+) -> RitmoResult<usize> {
+    let mut total_updated = 0;
+
+    for &dup_id in duplicate_ids {
+        // A book may already contain the primary content: keep one link
+        let result = sqlx::query!(
+            "INSERT OR IGNORE INTO x_books_contents (book_id, content_id)
+             SELECT book_id, ? FROM x_books_contents WHERE content_id = ?",
+            primary_id,
+            dup_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        total_updated += result.rows_affected() as usize;
+
+        sqlx::query!("DELETE FROM x_books_contents WHERE content_id = ?", dup_id)
+            .execute(&mut **tx)
+            .await?;
+    }
+
+    Ok(total_updated)
+}
+
+async fn move_contents_relations(
+    tx: &mut Transaction<'_, Sqlite>,
+    primary_id: i64,
+    duplicate_ids: &[i64],
+) -> RitmoResult<usize> {
+    let mut total_updated = 0;
+
+    for &dup_id in duplicate_ids {
+        let people = sqlx::query!(
+            "INSERT OR IGNORE INTO x_contents_people_roles (content_id, person_id, role_id)
+             SELECT ?, person_id, role_id FROM x_contents_people_roles WHERE content_id = ?",
+            primary_id,
+            dup_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        let tags = sqlx::query!(
+            "INSERT OR IGNORE INTO x_contents_tags (content_id, tag_id)
+             SELECT ?, tag_id FROM x_contents_tags WHERE content_id = ?",
+            primary_id,
+            dup_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        let languages = sqlx::query!(
+            "INSERT OR IGNORE INTO x_contents_languages (content_id, language_id)
+             SELECT ?, language_id FROM x_contents_languages WHERE content_id = ?",
+            primary_id,
+            dup_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        total_updated += (people.rows_affected() + tags.rows_affected() + languages.rows_affected()) as usize;
+
+        sqlx::query!("DELETE FROM x_contents_people_roles WHERE content_id = ?", dup_id)
+            .execute(&mut **tx)
+            .await?;
+        sqlx::query!("DELETE FROM x_contents_tags WHERE content_id = ?", dup_id)
+            .execute(&mut **tx)
+            .await?;
+        sqlx::query!("DELETE FROM x_contents_languages WHERE content_id = ?", dup_id)
+            .execute(&mut **tx)
+            .await?;
+    }
+
+    Ok(total_updated)
+}
+
+async fn fill_content_fields(
+    tx: &mut Transaction<'_, Sqlite>,
+    primary_id: i64,
+    duplicate_ids: &[i64],
+) -> RitmoResult<()> {
+    // In order: the first duplicate with a value wins
+    for &dup_id in duplicate_ids {
+        sqlx::query!(
+            "UPDATE contents SET
+                original_title = COALESCE(original_title, (SELECT original_title FROM contents WHERE id = ?1)),
+                type_id = COALESCE(type_id, (SELECT type_id FROM contents WHERE id = ?1)),
+                publication_date = COALESCE(publication_date, (SELECT publication_date FROM contents WHERE id = ?1)),
+                pages = COALESCE(pages, (SELECT pages FROM contents WHERE id = ?1)),
+                updated_at = strftime('%s', 'now')
+             WHERE id = ?2",
+            dup_id,
+            primary_id
+        )
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(())
+}
+
+async fn delete_contents(tx: &mut Transaction<'_, Sqlite>, ids: &[i64]) -> RitmoResult<()> {
+    for &id in ids {
+        sqlx::query!("DELETE FROM contents WHERE id = ?", id)
+            .execute(&mut **tx)
+            .await?;
+    }
+
+    Ok(())
+}
+
+// ============================================================================
+// Split people
+// ============================================================================
+
+/// Split wrongly conflated people: move some book and content links of a
+/// person to another person
+///
+/// This function:
+/// 1. Validates that the person exists and is linked to every given book
+///    and content
+/// 2. Finds the person named `new_name` (case-insensitive) or creates it
+/// 3. Moves the person's links (all roles) on the given books and contents
+///    to the target person, together with the links on the contents that
+///    belong only to the given books
+/// 4. Removes `new_name` from the aliases of the source person
+/// 5. Records the split in the merge journal, so it can be undone
+///
+/// # Safety
+/// This operation is executed within a transaction. If any step fails,
+/// all changes are rolled back.
+///
+/// # Arguments
+/// * `pool` - Database connection pool
+/// * `person_id` - ID of the person to split
+/// * `book_ids` - Books whose links move to the new person
+/// * `content_ids` - Contents whose links move to the new person
+/// * `new_name` - Name of the person receiving the links
+pub async fn split_person(
+    pool: &SqlitePool,
+    person_id: i64,
+    book_ids: &[i64],
+    content_ids: &[i64],
+    new_name: &str,
+) -> RitmoResult<SplitStats> {
+    let new_name = new_name.trim();
+    if new_name.is_empty() {
+        return Err(RitmoErr::Generic("No name provided for the new person".to_string()));
+    }
+
+    if book_ids.is_empty() && content_ids.is_empty() {
+        return Err(RitmoErr::Generic("No book or content IDs provided".to_string()));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    // Step 1: Validate the person and its links
+    let source_name = sqlx::query_scalar!("SELECT name FROM people WHERE id = ?", person_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| RitmoErr::Generic(format!("Person ID {} not found", person_id)))?;
+    validate_split_links(&mut tx, person_id, book_ids, content_ids).await?;
+
+    // Step 2: Find or create the target person
+    let existing = sqlx::query_scalar!(
+        r#"SELECT id AS "id!: i64" FROM people WHERE name = ? COLLATE NOCASE"#,
+        new_name
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+    let (target_id, target_created) = match existing {
+        Some(id) if id == person_id => {
+            return Err(RitmoErr::Generic(format!(
+                "'{}' is the name of the person being split",
+                source_name
+            )));
+        }
+        Some(id) => (id, false),
+        None => {
+            let result = sqlx::query!("INSERT INTO people (name) VALUES (?)", new_name)
+                .execute(&mut *tx)
+                .await?;
+            (result.last_insert_rowid(), true)
+        }
+    };
+
+    // Step 3: Move the links
+    let mut contents = content_ids.to_vec();
+    contents.extend(contents_only_in_books(&mut tx, person_id, book_ids).await?);
+    contents.sort();
+    contents.dedup();
+
+    // Record the rows about to change in the merge journal
+    let mut journal =
+        MergeJournal::capture_split(&mut tx, person_id, target_id, book_ids, &contents, new_name).await?;
+    if target_created {
+        journal.record_created("people", &[target_id]);
+    }
+
+    let mut books_moved = 0;
+    let mut affected_book_ids = book_ids.to_vec();
+    for &book_id in book_ids {
+        let result = sqlx::query!(
+            "UPDATE OR IGNORE x_books_people_roles SET person_id = ? WHERE person_id = ? AND book_id = ?",
+            target_id,
+            person_id,
+            book_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        books_moved += result.rows_affected() as usize;
+
+        // Roles the target already had on the book
+        sqlx::query!(
+            "DELETE FROM x_books_people_roles WHERE person_id = ? AND book_id = ?",
+            person_id,
+            book_id
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    let mut contents_moved = 0;
+    for &content_id in &contents {
+        let result = sqlx::query!(
+            "UPDATE OR IGNORE x_contents_people_roles SET person_id = ? WHERE person_id = ? AND content_id = ?",
+            target_id,
+            person_id,
+            content_id
+        )
+        .execute(&mut *tx)
+        .await?;
+        contents_moved += result.rows_affected() as usize;
+
+        sqlx::query!(
+            "DELETE FROM x_contents_people_roles WHERE person_id = ? AND content_id = ?",
+            person_id,
+            content_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let books = sqlx::query_scalar!("SELECT book_id FROM x_books_contents WHERE content_id = ?", content_id)
+            .fetch_all(&mut *tx)
+            .await?;
+        affected_book_ids.extend(books);
+    }
+
+    // Step 4: The new name no longer refers to the source person
+    sqlx::query!(
+        "DELETE FROM aliases WHERE person_id = ? AND name = ? COLLATE NOCASE",
+        person_id,
+        new_name
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    affected_book_ids.sort();
+    affected_book_ids.dedup();
+
+    // Step 5: Write the journal entry, which assigns merge_id
+    let mut journal_stats = MergeStats {
+        merge_id: 0,
+        primary_id: target_id,
+        merged_ids: vec![person_id],
+        books_updated: books_moved,
+        contents_updated: contents_moved,
+        affected_book_ids: affected_book_ids.clone(),
+    };
+    journal.write(&mut tx, &mut journal_stats).await?;
+
+    tx.commit().await?;
+
+    Ok(SplitStats {
+        merge_id: journal_stats.merge_id,
+        source_id: person_id,
+        target_id,
+        target_created,
+        books_moved,
+        contents_moved,
+        affected_book_ids,
+    })
+}
+
+// ============================================================================
+// Helper functions for person splitting
+// ============================================================================
+
+async fn validate_split_links(
+    tx: &mut Transaction<'_, Sqlite>,
+    person_id: i64,
+    book_ids: &[i64],
+    content_ids: &[i64],
+) -> RitmoResult<()> {
+    for &book_id in book_ids {
+        let linked = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!: i64" FROM x_books_people_roles WHERE person_id = ? AND book_id = ?"#,
+            person_id,
+            book_id
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        if linked == 0 {
+            return Err(RitmoErr::Generic(format!(
+                "Person ID {} is not linked to book ID {}",
+                person_id, book_id
+            )));
+        }
+    }
+
+    for &content_id in content_ids {
+        let linked = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!: i64" FROM x_contents_people_roles WHERE person_id = ? AND content_id = ?"#,
+            person_id,
+            content_id
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        if linked == 0 {
+            return Err(RitmoErr::Generic(format!(
+                "Person ID {} is not linked to content ID {}",
+                person_id, content_id
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Contents of `book_ids` credited to the person that appear in no other book
+async fn contents_only_in_books(
+    tx: &mut Transaction<'_, Sqlite>,
+    person_id: i64,
+    book_ids: &[i64],
+) -> RitmoResult<Vec<i64>> {
+    let mut contents = Vec::new();
+
+    for &book_id in book_ids {
+        let candidates = sqlx::query_scalar!(
+            "SELECT DISTINCT cpr.content_id FROM x_contents_people_roles cpr
+             JOIN x_books_contents bc ON bc.content_id = cpr.content_id
+             WHERE cpr.person_id = ? AND bc.book_id = ?",
+            person_id,
+            book_id
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+
+        for content_id in candidates {
+            let books = sqlx::query_scalar!("SELECT book_id FROM x_books_contents WHERE content_id = ?", content_id)
+                .fetch_all(&mut **tx)
+                .await?;
+            if books.iter().all(|b| book_ids.contains(b)) {
+                contents.push(content_id);
+            }
+        }
+    }
+
+    Ok(contents)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -962,6 +1560,44 @@ mod tests {
         assert_eq!(series_ids, vec![Some(1), Some(1)]);
     }
 
+    #[tokio::test]
+    async fn test_merge_people_keeps_names_as_aliases() {
+        let pool = create_test_db().await.unwrap();
+        populate_test_people(&pool).await.unwrap();
+        let primary_id: i64 =
+            sqlx::query_scalar("INSERT INTO people (name) VALUES ('Fëdor Dostoevskij') RETURNING id")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        let cyrillic_id: i64 =
+            sqlx::query_scalar("INSERT INTO people (name) VALUES ('Фёдор Достоевский') RETURNING id")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        sqlx::query("INSERT INTO aliases (name, person_id) VALUES ('F. M. Dostoevskij', ?)")
+            .bind(cyrillic_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        merge_people(&pool, primary_id, &[cyrillic_id]).await.unwrap();
+
+        let aliases: Vec<(String, String)> = sqlx::query_as(
+            "SELECT name, COALESCE(alias_normalized, '') FROM aliases WHERE person_id = ? ORDER BY name",
+        )
+        .bind(primary_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            aliases,
+            vec![
+                ("F. M. Dostoevskij".to_string(), String::new()),
+                ("Фёдор Достоевский".to_string(), "fedor dostoevskiy".to_string()),
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_merge_people_validation_errors() {
         let pool = create_test_db().await.unwrap();
@@ -1064,4 +1700,123 @@ mod tests {
         .unwrap();
         assert_eq!(role_ids, vec![1, 1]);
     }
+
+    #[tokio::test]
+    async fn test_merge_contents() {
+        let pool = create_test_db().await.unwrap();
+        populate_test_contents(&pool).await.unwrap();
+
+        // Merge the re-imported edition (2) and the translation (3) into 1
+        let stats = merge_contents(&pool, 1, &[2, 3]).await.unwrap();
+
+        assert_eq!(stats.primary_id, 1);
+        assert_eq!(stats.merged_ids, vec![2, 3]);
+        // Book 2 already contained content 1: only book 3 gets a new link
+        assert_eq!(stats.books_updated, 1);
+        assert_eq!(stats.affected_book_ids, vec![1, 2, 3]);
+
+        let contents: Vec<i64> = sqlx::query_scalar("SELECT id FROM contents ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(contents, vec![1, 4, 5, 6]);
+
+        let book_links: Vec<(i64, i64)> =
+            sqlx::query_as("SELECT book_id, content_id FROM x_books_contents WHERE book_id <= 3 ORDER BY book_id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(book_links, vec![(1, 1), (2, 1), (3, 1)]);
+
+        // Author kept once, translator and extra tag/language moved over
+        let people: Vec<(i64, i64)> = sqlx::query_as(
+            "SELECT person_id, role_id FROM x_contents_people_roles WHERE content_id = 1 ORDER BY person_id",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(people, vec![(13, 1), (14, 4)]);
+
+        let tags: Vec<i64> = sqlx::query_scalar("SELECT tag_id FROM x_contents_tags WHERE content_id = 1 ORDER BY tag_id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(tags, vec![1, 7]);
+
+        let languages: Vec<i64> =
+            sqlx::query_scalar("SELECT language_id FROM x_contents_languages WHERE content_id = 1 ORDER BY language_id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(languages, vec![1, 3]);
+
+        let orphans: i64 = sqlx::query_scalar(
+            "SELECT (SELECT COUNT(*) FROM x_contents_people_roles WHERE content_id IN (2, 3))
+                  + (SELECT COUNT(*) FROM x_contents_tags WHERE content_id IN (2, 3))
+                  + (SELECT COUNT(*) FROM x_contents_languages WHERE content_id IN (2, 3))",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(orphans, 0);
+
+        // Empty fields filled from the duplicates
+        let (original_title, publication_date): (Option<String>, Option<i64>) =
+            sqlx::query_as("SELECT original_title, publication_date FROM contents WHERE id = 1")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(original_title.as_deref(), Some("Il nome della rosa"));
+        assert_eq!(publication_date, Some(1980));
+    }
+
+    #[tokio::test]
+    async fn test_split_person() {
+        let pool = create_test_db().await.unwrap();
+        populate_test_people(&pool).await.unwrap();
+        populate_test_books_with_people(&pool).await.unwrap();
+        sqlx::query(
+            "INSERT INTO contents (id, name) VALUES (1, 'It'), (2, 'Short story');
+             INSERT INTO x_books_contents (book_id, content_id) VALUES (2, 1), (2, 2), (3, 2);
+             INSERT INTO x_contents_people_roles (content_id, person_id, role_id) VALUES (1, 2, 1), (2, 2, 1);",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // A wrong merge conflated Stephen Edwin King with Stephen King
+        merge_people(&pool, 1, &[2]).await.unwrap();
+
+        let stats = split_person(&pool, 1, &[2], &[], "Stephen Edwin King").await.unwrap();
+        assert!(stats.target_created);
+        assert_eq!(stats.books_moved, 1);
+        // Content 2 is also in book 3, which stays with person 1
+        assert_eq!(stats.contents_moved, 1);
+        assert_eq!(stats.affected_book_ids, vec![2]);
+
+        let book_people: Vec<(i64, i64)> =
+            sqlx::query_as("SELECT book_id, person_id FROM x_books_people_roles ORDER BY book_id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(book_people, vec![(1, 1), (2, stats.target_id), (3, 3)]);
+
+        let content_people: Vec<(i64, i64)> =
+            sqlx::query_as("SELECT content_id, person_id FROM x_contents_people_roles ORDER BY content_id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(content_people, vec![(1, stats.target_id), (2, 1)]);
+
+        let aliases: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM aliases WHERE person_id = 1")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(aliases, 0);
+
+        // Books not linked to the person and the person's own name are refused
+        assert!(split_person(&pool, 1, &[3], &[], "Someone").await.is_err());
+        assert!(split_person(&pool, 1, &[1], &[], "stephen king").await.is_err());
+        assert!(split_person(&pool, 1, &[], &[], "Someone").await.is_err());
+    }
 }
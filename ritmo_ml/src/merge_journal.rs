@@ -0,0 +1,956 @@
+//! Merge journal: undo support for the merge operations
+//!
+//! Every merge in [`crate::merge`] records, inside its own transaction, the
+//! entity rows it deletes and the exact rows it re-points (junction rows such
+//! as `x_books_people_roles`, aliases, `books.publisher_id`/`series_id`).
+//! [`undo_merge`] replays that entry backwards to split the entities apart
+//! again; [`merge_history`] lists past merges with their [`MergeStats`].
+//! Person splits ([`crate::merge::split_person`]) are journaled the same way,
+//! with the receiving person as primary, so a wrong split can be undone too.
+//!
+//! Rows are stored as JSON objects (column -> value), so the journal follows
+//! schema changes without per-table code. AUTOINCREMENT ids are never reused,
+//! which lets undo re-insert the deleted rows with their original ids.
+
+use crate::merge::MergeStats;
+use ritmo_errors::{RitmoErr, RitmoResult};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::query::Query;
+use sqlx::sqlite::SqliteArguments;
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use std::collections::HashSet;
+
+/// A database row as column -> value
+type Row = Map<String, Value>;
+
+/// Entity types handled by the merge functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeEntity {
+    People,
+    Publishers,
+    Series,
+    Tags,
+    Roles,
+    Contents,
+}
+
+/// How a merge changes a row that references a duplicate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ReferenceKind {
+    /// The row is re-pointed (or dropped, if primary already has it):
+    /// undo re-inserts the captured row
+    Junction,
+    /// A foreign key column updated in place (`books.publisher_id`):
+    /// undo sets it back
+    Column,
+}
+
+/// A table column that references the merged entity
+struct Reference {
+    table: &'static str,
+    column: &'static str,
+    /// Columns identifying the row besides `column`
+    key: &'static [&'static str],
+    kind: ReferenceKind,
+}
+
+impl MergeEntity {
+    /// Name of the entity table (also stored in the journal)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MergeEntity::People => "people",
+            MergeEntity::Publishers => "publishers",
+            MergeEntity::Series => "series",
+            MergeEntity::Tags => "tags",
+            MergeEntity::Roles => "roles",
+            MergeEntity::Contents => "contents",
+        }
+    }
+
+    fn references(&self) -> &'static [Reference] {
+        use ReferenceKind::*;
+        match self {
+            MergeEntity::People => &[
+                Reference { table: "x_books_people_roles", column: "person_id", key: &["book_id", "role_id"], kind: Junction },
+                Reference { table: "x_contents_people_roles", column: "person_id", key: &["content_id", "role_id"], kind: Junction },
+                Reference { table: "aliases", column: "person_id", key: &["id"], kind: Junction },
+            ],
+            MergeEntity::Publishers => &[
+                Reference { table: "books", column: "publisher_id", key: &["id"], kind: Column },
+            ],
+            MergeEntity::Series => &[
+                Reference { table: "books", column: "series_id", key: &["id"], kind: Column },
+            ],
+            MergeEntity::Tags => &[
+                Reference { table: "x_books_tags", column: "tag_id", key: &["book_id"], kind: Junction },
+                Reference { table: "x_contents_tags", column: "tag_id", key: &["content_id"], kind: Junction },
+            ],
+            MergeEntity::Roles => &[
+                Reference { table: "x_books_people_roles", column: "role_id", key: &["book_id", "person_id"], kind: Junction },
+                Reference { table: "x_contents_people_roles", column: "role_id", key: &["content_id", "person_id"], kind: Junction },
+            ],
+            MergeEntity::Contents => &[
+                Reference { table: "x_books_contents", column: "content_id", key: &["book_id"], kind: Junction },
+                Reference { table: "x_contents_people_roles", column: "content_id", key: &["person_id", "role_id"], kind: Junction },
+                Reference { table: "x_contents_tags", column: "content_id", key: &["tag_id"], kind: Junction },
+                Reference { table: "x_contents_languages", column: "content_id", key: &["language_id"], kind: Junction },
+            ],
+        }
+    }
+
+    /// Merges that also rewrite fields of the primary record
+    fn updates_primary(&self) -> bool {
+        matches!(self, MergeEntity::Contents)
+    }
+}
+
+impl std::str::FromStr for MergeEntity {
+    type Err = RitmoErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "people" => Ok(MergeEntity::People),
+            "publishers" => Ok(MergeEntity::Publishers),
+            "series" => Ok(MergeEntity::Series),
+            "tags" => Ok(MergeEntity::Tags),
+            "roles" => Ok(MergeEntity::Roles),
+            "contents" => Ok(MergeEntity::Contents),
+            other => Err(RitmoErr::Generic(format!("Unknown merge entity type: {}", other))),
+        }
+    }
+}
+
+/// Operation recorded by a journal entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalOperation {
+    #[default]
+    Merge,
+    /// A person split: `stats.primary_id` is the person that received the
+    /// links, `stats.merged_ids` the person they were taken from
+    Split,
+}
+
+/// A row that referenced a duplicate before the merge
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LinkChange {
+    table: String,
+    column: String,
+    duplicate_id: i64,
+    key: Vec<String>,
+    kind: ReferenceKind,
+    /// The captured row (key columns only for `Column` references)
+    row: Row,
+    /// The primary already had an identical link: the merge dropped this row
+    /// instead of re-pointing it, and undo must not remove the primary's one
+    primary_had: bool,
+}
+
+/// A row inserted by the merge (e.g. the alias keeping a duplicate's name)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CreatedRow {
+    table: String,
+    id: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct JournalPayload {
+    #[serde(default)]
+    operation: JournalOperation,
+    deleted_rows: Vec<Row>,
+    links: Vec<LinkChange>,
+    created_rows: Vec<CreatedRow>,
+    primary_before: Option<Row>,
+}
+
+/// A past merge as listed by [`merge_history`]
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeJournalEntry {
+    pub id: i64,
+    pub entity_type: String,
+    pub operation: JournalOperation,
+    pub stats: MergeStats,
+    /// Names of the merged (deleted) records, in `stats.merged_ids` order
+    pub merged_names: Vec<String>,
+    pub created_at: i64,
+    pub undone_at: Option<i64>,
+}
+
+/// Journal entry being built during a merge transaction
+pub(crate) struct MergeJournal {
+    entity: MergeEntity,
+    primary_id: i64,
+    payload: JournalPayload,
+}
+
+impl MergeJournal {
+    /// Captures the rows a merge is about to change. Must run inside the
+    /// merge transaction, after validation and before any update.
+    pub(crate) async fn capture(
+        tx: &mut Transaction<'_, Sqlite>,
+        entity: MergeEntity,
+        primary_id: i64,
+        duplicate_ids: &[i64],
+    ) -> RitmoResult<Self> {
+        let mut payload = JournalPayload::default();
+        let entity_columns = table_columns(tx, entity.as_str()).await?;
+
+        for &dup_id in duplicate_ids {
+            payload
+                .deleted_rows
+                .extend(fetch_rows(tx, entity.as_str(), &entity_columns, "id", dup_id).await?);
+
+            for reference in entity.references() {
+                let columns = match reference.kind {
+                    ReferenceKind::Junction => table_columns(tx, reference.table).await?,
+                    ReferenceKind::Column => reference.key.iter().map(|c| c.to_string()).collect(),
+                };
+                for row in fetch_rows(tx, reference.table, &columns, reference.column, dup_id).await? {
+                    let primary_had = reference.kind == ReferenceKind::Junction
+                        && link_exists(tx, reference.table, reference.column, primary_id, reference.key, &row).await?;
+                    payload.links.push(LinkChange {
+                        table: reference.table.to_string(),
+                        column: reference.column.to_string(),
+                        duplicate_id: dup_id,
+                        key: reference.key.iter().map(|c| c.to_string()).collect(),
+                        kind: reference.kind,
+                        row,
+                        primary_had,
+                    });
+                }
+            }
+        }
+
+        if entity.updates_primary() {
+            payload.primary_before = fetch_rows(tx, entity.as_str(), &entity_columns, "id", primary_id)
+                .await?
+                .into_iter()
+                .next();
+        }
+
+        Ok(Self {
+            entity,
+            primary_id,
+            payload,
+        })
+    }
+
+    /// Captures the rows a person split is about to change: the links of
+    /// `source_id` to the given books and contents, which move to
+    /// `target_id`, and the alias named `alias`, which is removed. Must run
+    /// inside the split transaction, before any update.
+    pub(crate) async fn capture_split(
+        tx: &mut Transaction<'_, Sqlite>,
+        source_id: i64,
+        target_id: i64,
+        book_ids: &[i64],
+        content_ids: &[i64],
+        alias: &str,
+    ) -> RitmoResult<Self> {
+        let mut payload = JournalPayload {
+            operation: JournalOperation::Split,
+            ..Default::default()
+        };
+        let id_in = |row: &Row, column: &str, ids: &[i64]| {
+            row.get(column).and_then(Value::as_i64).is_some_and(|id| ids.contains(&id))
+        };
+
+        for reference in MergeEntity::People.references() {
+            let columns = table_columns(tx, reference.table).await?;
+            for row in fetch_rows(tx, reference.table, &columns, reference.column, source_id).await? {
+                let affected = match reference.table {
+                    "x_books_people_roles" => id_in(&row, "book_id", book_ids),
+                    "x_contents_people_roles" => id_in(&row, "content_id", content_ids),
+                    _ => row
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .is_some_and(|name| name.eq_ignore_ascii_case(alias)),
+                };
+                if !affected {
+                    continue;
+                }
+                // The alias is dropped rather than moved: undo only re-inserts it
+                let primary_had = reference.table == "aliases"
+                    || link_exists(tx, reference.table, reference.column, target_id, reference.key, &row).await?;
+                payload.links.push(LinkChange {
+                    table: reference.table.to_string(),
+                    column: reference.column.to_string(),
+                    duplicate_id: source_id,
+                    key: reference.key.iter().map(|c| c.to_string()).collect(),
+                    kind: reference.kind,
+                    row,
+                    primary_had,
+                });
+            }
+        }
+
+        Ok(Self {
+            entity: MergeEntity::People,
+            primary_id: target_id,
+            payload,
+        })
+    }
+
+    /// Records rows inserted by the merge, deleted again on undo
+    pub(crate) fn record_created(&mut self, table: &str, ids: &[i64]) {
+        self.payload.created_rows.extend(ids.iter().map(|&id| CreatedRow {
+            table: table.to_string(),
+            id,
+        }));
+    }
+
+    /// Writes the entry and sets `stats.merge_id`
+    pub(crate) async fn write(
+        self,
+        tx: &mut Transaction<'_, Sqlite>,
+        stats: &mut MergeStats,
+    ) -> RitmoResult<()> {
+        let entity_type = self.entity.as_str();
+        let payload = serde_json::to_string(&self.payload)?;
+        let merge_id = sqlx::query_scalar!(
+            r#"INSERT INTO merge_journal (entity_type, primary_id, stats, payload)
+               VALUES (?, ?, '{}', ?) RETURNING id AS "id!: i64""#,
+            entity_type,
+            self.primary_id,
+            payload
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        stats.merge_id = merge_id;
+        let stats_json = serde_json::to_string(stats)?;
+        sqlx::query!(
+            "UPDATE merge_journal SET stats = ? WHERE id = ?",
+            stats_json,
+            merge_id
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Lists past merges, most recent first
+///
+/// # Arguments
+/// * `pool` - Database connection pool
+/// * `entity` - Only merges of this entity type
+/// * `limit` - Maximum number of entries
+pub async fn merge_history(
+    pool: &SqlitePool,
+    entity: Option<MergeEntity>,
+    limit: Option<i64>,
+) -> RitmoResult<Vec<MergeJournalEntry>> {
+    let entity_type = entity.map(|e| e.as_str());
+    let limit = limit.unwrap_or(-1);
+    let rows = sqlx::query!(
+        r#"SELECT id AS "id!: i64", entity_type, stats, payload, created_at, undone_at
+           FROM merge_journal
+           WHERE ?1 IS NULL OR entity_type = ?1
+           ORDER BY id DESC
+           LIMIT ?2"#,
+        entity_type,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            let payload: JournalPayload = serde_json::from_str(&row.payload)?;
+            Ok(MergeJournalEntry {
+                id: row.id,
+                merged_names: deleted_names(&payload),
+                operation: payload.operation,
+                entity_type: row.entity_type,
+                stats: serde_json::from_str(&row.stats)?,
+                created_at: row.created_at,
+                undone_at: row.undone_at,
+            })
+        })
+        .collect()
+}
+
+/// Undoes a merge: re-creates the deleted records with their original ids,
+/// moves the re-pointed rows back, removes the rows the merge added and
+/// restores the fields it filled on the primary record.
+///
+/// Later merges that involve the same records, or records the restored
+/// rows point to, must be undone first.
+///
+/// # Safety
+/// This operation is executed within a transaction. If any step fails,
+/// all changes are rolled back.
+///
+/// # Returns
+/// The statistics of the undone merge; `affected_book_ids` are the books
+/// whose metadata changes again.
+pub async fn undo_merge(pool: &SqlitePool, merge_id: i64) -> RitmoResult<MergeStats> {
+    let mut tx = pool.begin().await?;
+
+    let entry = sqlx::query!(
+        "SELECT entity_type, stats, payload, undone_at FROM merge_journal WHERE id = ?",
+        merge_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| RitmoErr::Generic(format!("Merge {} not found", merge_id)))?;
+
+    if entry.undone_at.is_some() {
+        return Err(RitmoErr::Generic(format!("Merge {} was already undone", merge_id)));
+    }
+
+    let entity: MergeEntity = entry.entity_type.parse()?;
+    let stats: MergeStats = serde_json::from_str(&entry.stats)?;
+    let payload: JournalPayload = serde_json::from_str(&entry.payload)?;
+
+    check_no_later_merges(&mut tx, merge_id, entity, &stats, &payload).await?;
+
+    // Step 1: Re-create the deleted records
+    for row in &payload.deleted_rows {
+        let id = row.get("id").and_then(Value::as_i64).unwrap_or_default();
+        let taken = sqlx::query_scalar::<_, i64>(&format!(
+            "SELECT COUNT(*) FROM \"{}\" WHERE id = ?",
+            entity.as_str()
+        ))
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if taken > 0 {
+            return Err(RitmoErr::Generic(format!(
+                "Cannot undo merge {}: {} ID {} exists again",
+                merge_id,
+                entity.as_str(),
+                id
+            )));
+        }
+        insert_row(&mut tx, entity.as_str(), row).await?;
+    }
+
+    // Step 2: Remove the rows added by the merge
+    for created in &payload.created_rows {
+        sqlx::query(&format!("DELETE FROM \"{}\" WHERE id = ?", created.table))
+            .bind(created.id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    // Step 3: Point the captured rows back to the duplicates
+    for link in &payload.links {
+        restore_link(&mut tx, link, stats.primary_id).await?;
+    }
+
+    // Step 4: Restore the fields filled on the primary record
+    if let Some(primary_before) = &payload.primary_before {
+        update_row(&mut tx, entity.as_str(), primary_before).await?;
+    }
+
+    sqlx::query!(
+        "UPDATE merge_journal SET undone_at = strftime('%s', 'now') WHERE id = ?",
+        merge_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(stats)
+}
+
+// ============================================================================
+// Helper functions
+// ============================================================================
+
+/// Refuses to undo a merge when a later, still active merge touched one of
+/// the records the undo restores: the same entities, or any record the
+/// captured rows point to (a later contents merge that deleted a content
+/// whose people links this merge moved)
+async fn check_no_later_merges(
+    tx: &mut Transaction<'_, Sqlite>,
+    merge_id: i64,
+    entity: MergeEntity,
+    stats: &MergeStats,
+    payload: &JournalPayload,
+) -> RitmoResult<()> {
+    let later = sqlx::query!(
+        r#"SELECT id AS "id!: i64", entity_type, stats FROM merge_journal
+           WHERE id > ? AND undone_at IS NULL
+           ORDER BY id DESC"#,
+        merge_id
+    )
+    .fetch_all(&mut **tx)
+    .await?;
+
+    let restored = restored_records(entity, stats, payload);
+
+    for row in later {
+        let later_stats: MergeStats = serde_json::from_str(&row.stats)?;
+        let mut touched: Vec<i64> = later_stats.merged_ids.clone();
+        // A later merge into one of the same entities changed its links too
+        if row.entity_type == entity.as_str() {
+            touched.push(later_stats.primary_id);
+        }
+        if touched
+            .iter()
+            .any(|id| restored.contains(&(row.entity_type.clone(), *id)))
+        {
+            return Err(RitmoErr::Generic(format!(
+                "Cannot undo merge {}: blocked by merge #{} ({}), undo it first",
+                merge_id, row.id, row.entity_type
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Records (entity table, id) that undoing the entry re-creates or points
+/// rows back to
+fn restored_records(
+    entity: MergeEntity,
+    stats: &MergeStats,
+    payload: &JournalPayload,
+) -> HashSet<(String, i64)> {
+    const ALL: [MergeEntity; 6] = [
+        MergeEntity::People,
+        MergeEntity::Publishers,
+        MergeEntity::Series,
+        MergeEntity::Tags,
+        MergeEntity::Roles,
+        MergeEntity::Contents,
+    ];
+
+    let mut records: HashSet<(String, i64)> = std::iter::once(stats.primary_id)
+        .chain(stats.merged_ids.iter().copied())
+        .map(|id| (entity.as_str().to_string(), id))
+        .collect();
+
+    for link in &payload.links {
+        for (column, value) in &link.row {
+            let Some(id) = value.as_i64() else { continue };
+            for other in ALL {
+                let references_other = other.references().iter().any(|r| {
+                    r.kind == ReferenceKind::Junction && r.table == link.table && r.column == column
+                });
+                if references_other {
+                    records.insert((other.as_str().to_string(), id));
+                }
+            }
+        }
+    }
+
+    records
+}
+
+async fn restore_link(
+    tx: &mut Transaction<'_, Sqlite>,
+    link: &LinkChange,
+    primary_id: i64,
+) -> RitmoResult<()> {
+    let key_filter: String = link
+        .key
+        .iter()
+        .map(|column| format!(" AND \"{}\" = ?", column))
+        .collect();
+
+    match link.kind {
+        ReferenceKind::Column => {
+            // Only books still pointing to the primary: later edits win
+            let sql = format!(
+                "UPDATE \"{table}\" SET \"{column}\" = ? WHERE \"{column}\" = ?{key_filter}",
+                table = link.table,
+                column = link.column,
+            );
+            let mut query = sqlx::query(&sql).bind(link.duplicate_id).bind(primary_id);
+            for column in &link.key {
+                query = bind_value(query, link.row.get(column).unwrap_or(&Value::Null));
+            }
+            query.execute(&mut **tx).await?;
+        }
+        ReferenceKind::Junction => {
+            if !link.primary_had {
+                let sql = format!(
+                    "DELETE FROM \"{table}\" WHERE \"{column}\" = ?{key_filter}",
+                    table = link.table,
+                    column = link.column,
+                );
+                let mut query = sqlx::query(&sql).bind(primary_id);
+                for column in &link.key {
+                    query = bind_value(query, link.row.get(column).unwrap_or(&Value::Null));
+                }
+                query.execute(&mut **tx).await?;
+            }
+            insert_row(tx, &link.table, &link.row).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Names of the deleted records, as shown in the history
+fn deleted_names(payload: &JournalPayload) -> Vec<String> {
+    payload
+        .deleted_rows
+        .iter()
+        .map(|row| {
+            row.get("name")
+                .or_else(|| row.get("key"))
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string()
+        })
+        .collect()
+}
+
+async fn table_columns(tx: &mut Transaction<'_, Sqlite>, table: &str) -> RitmoResult<Vec<String>> {
+    let columns: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info(?) ORDER BY cid")
+        .bind(table)
+        .fetch_all(&mut **tx)
+        .await?;
+
+    if columns.is_empty() {
+        return Err(RitmoErr::Generic(format!("Table {} not found", table)));
+    }
+    Ok(columns)
+}
+
+/// Rows of `table` where `column = value`, as JSON objects
+async fn fetch_rows(
+    tx: &mut Transaction<'_, Sqlite>,
+    table: &str,
+    columns: &[String],
+    column: &str,
+    value: i64,
+) -> RitmoResult<Vec<Row>> {
+    let fields: Vec<String> = columns
+        .iter()
+        .map(|c| format!("'{0}', \"{0}\"", c))
+        .collect();
+    let sql = format!(
+        "SELECT json_object({}) FROM \"{}\" WHERE \"{}\" = ?",
+        fields.join(", "),
+        table,
+        column
+    );
+
+    let rows: Vec<String> = sqlx::query_scalar(&sql)
+        .bind(value)
+        .fetch_all(&mut **tx)
+        .await?;
+
+    rows.iter()
+        .map(|json| match serde_json::from_str(json)? {
+            Value::Object(row) => Ok(row),
+            _ => Err(RitmoErr::Generic(format!("Unexpected row in {}", table))),
+        })
+        .collect()
+}
+
+/// True if `table` has a row with `column = primary_id` and the same key as `row`
+async fn link_exists(
+    tx: &mut Transaction<'_, Sqlite>,
+    table: &str,
+    column: &str,
+    primary_id: i64,
+    key: &[&str],
+    row: &Row,
+) -> RitmoResult<bool> {
+    let key_filter: String = key.iter().map(|c| format!(" AND \"{}\" = ?", c)).collect();
+    let sql = format!(
+        "SELECT COUNT(*) FROM \"{}\" WHERE \"{}\" = ?{}",
+        table, column, key_filter
+    );
+
+    let mut query = sqlx::query_scalar::<_, i64>(&sql).bind(primary_id);
+    for c in key {
+        query = match row.get(*c) {
+            Some(Value::Number(n)) => query.bind(n.as_i64()),
+            Some(Value::String(s)) => query.bind(s.clone()),
+            _ => query.bind(None::<i64>),
+        };
+    }
+
+    Ok(query.fetch_one(&mut **tx).await? > 0)
+}
+
+async fn insert_row(tx: &mut Transaction<'_, Sqlite>, table: &str, row: &Row) -> RitmoResult<()> {
+    let columns: Vec<String> = row.keys().map(|c| format!("\"{}\"", c)).collect();
+    let placeholders = vec!["?"; row.len()].join(", ");
+    let sql = format!(
+        "INSERT OR IGNORE INTO \"{}\" ({}) VALUES ({})",
+        table,
+        columns.join(", "),
+        placeholders
+    );
+
+    let mut query = sqlx::query(&sql);
+    for value in row.values() {
+        query = bind_value(query, value);
+    }
+    query.execute(&mut **tx).await?;
+
+    Ok(())
+}
+
+async fn update_row(tx: &mut Transaction<'_, Sqlite>, table: &str, row: &Row) -> RitmoResult<()> {
+    let id = row.get("id").and_then(Value::as_i64).unwrap_or_default();
+    let assignments: Vec<String> = row
+        .keys()
+        .filter(|c| c.as_str() != "id")
+        .map(|c| format!("\"{}\" = ?", c))
+        .collect();
+    let sql = format!(
+        "UPDATE \"{}\" SET {} WHERE id = ?",
+        table,
+        assignments.join(", ")
+    );
+
+    let mut query = sqlx::query(&sql);
+    for (_, value) in row.iter().filter(|(c, _)| c.as_str() != "id") {
+        query = bind_value(query, value);
+    }
+    query.bind(id).execute(&mut **tx).await?;
+
+    Ok(())
+}
+
+fn bind_value<'q>(
+    query: Query<'q, Sqlite, SqliteArguments<'q>>,
+    value: &Value,
+) -> Query<'q, Sqlite, SqliteArguments<'q>> {
+    match value {
+        Value::Null => query.bind(None::<i64>),
+        Value::Bool(b) => query.bind(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => query.bind(i),
+            None => query.bind(n.as_f64()),
+        },
+        Value::String(s) => query.bind(s.clone()),
+        other => query.bind(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merge::{merge_contents, merge_people, merge_publishers, merge_tags, split_person};
+    use crate::test_helpers::*;
+
+    async fn people_links(pool: &SqlitePool) -> Vec<(i64, i64, i64)> {
+        sqlx::query_as("SELECT book_id, person_id, role_id FROM x_books_people_roles ORDER BY book_id, person_id")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_undo_merge_people() {
+        let pool = create_test_db().await.unwrap();
+        populate_test_people(&pool).await.unwrap();
+        populate_test_books_with_people(&pool).await.unwrap();
+        sqlx::query("INSERT INTO aliases (name, person_id) VALUES ('Steve King', 2), ('Stephen King', 3)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let people_before: Vec<(i64, String)> = sqlx::query_as("SELECT id, name FROM people ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        let links_before = people_links(&pool).await;
+
+        let stats = merge_people(&pool, 1, &[2, 3]).await.unwrap();
+        assert!(stats.merge_id > 0);
+        assert_eq!(people_links(&pool).await, vec![(1, 1, 1), (2, 1, 1), (3, 1, 1)]);
+
+        let undone = undo_merge(&pool, stats.merge_id).await.unwrap();
+        assert_eq!(undone.affected_book_ids, vec![2, 3]);
+
+        let people_after: Vec<(i64, String)> = sqlx::query_as("SELECT id, name FROM people ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(people_after, people_before);
+        assert_eq!(people_links(&pool).await, links_before);
+
+        // Aliases back on their owners, the merge's name aliases gone
+        let aliases: Vec<(String, i64)> = sqlx::query_as("SELECT name, person_id FROM aliases ORDER BY person_id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            aliases,
+            vec![("Steve King".to_string(), 2), ("Stephen King".to_string(), 3)]
+        );
+
+        assert!(undo_merge(&pool, stats.merge_id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_undo_merge_contents_keeps_primary_links() {
+        let pool = create_test_db().await.unwrap();
+        populate_test_contents(&pool).await.unwrap();
+        let snapshot = |pool: SqlitePool| async move {
+            let books: Vec<(i64, i64)> = sqlx::query_as("SELECT book_id, content_id FROM x_books_contents ORDER BY 1, 2")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+            let people: Vec<(i64, i64, i64)> =
+                sqlx::query_as("SELECT content_id, person_id, role_id FROM x_contents_people_roles ORDER BY 1, 2, 3")
+                    .fetch_all(&pool)
+                    .await
+                    .unwrap();
+            let primary: (Option<String>, Option<i64>) =
+                sqlx::query_as("SELECT original_title, publication_date FROM contents WHERE id = 1")
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+            (books, people, primary)
+        };
+        let before = snapshot(pool.clone()).await;
+
+        // Book 2 and the author link were already on content 1
+        let stats = merge_contents(&pool, 1, &[2, 3]).await.unwrap();
+        undo_merge(&pool, stats.merge_id).await.unwrap();
+
+        assert_eq!(snapshot(pool.clone()).await, before);
+    }
+
+    #[tokio::test]
+    async fn test_undo_merge_publishers_and_tags() {
+        let pool = create_full_test_db().await.unwrap();
+        sqlx::query(
+            "INSERT INTO books (id, name, publisher_id) VALUES (1, 'Book 1', 2), (2, 'Book 2', 1);
+             INSERT INTO x_books_tags (book_id, tag_id) VALUES (1, 1), (2, 2);",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let publishers = merge_publishers(&pool, 1, &[2]).await.unwrap();
+        undo_merge(&pool, publishers.merge_id).await.unwrap();
+        let publisher_ids: Vec<Option<i64>> = sqlx::query_scalar("SELECT publisher_id FROM books ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(publisher_ids, vec![Some(2), Some(1)]);
+
+        let tags = merge_tags(&pool, 1, &[2]).await.unwrap();
+        undo_merge(&pool, tags.merge_id).await.unwrap();
+        let tag_links: Vec<(i64, i64)> = sqlx::query_as("SELECT book_id, tag_id FROM x_books_tags ORDER BY 1, 2")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(tag_links, vec![(1, 1), (2, 2)]);
+    }
+
+    #[tokio::test]
+    async fn test_merge_history_and_undo_order() {
+        let pool = create_test_db().await.unwrap();
+        populate_test_people(&pool).await.unwrap();
+
+        let first = merge_people(&pool, 1, &[2]).await.unwrap();
+        let second = merge_people(&pool, 1, &[3, 4]).await.unwrap();
+
+        let history = merge_history(&pool, Some(MergeEntity::People), None).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].id, second.merge_id);
+        assert_eq!(history[0].stats.merged_ids, vec![3, 4]);
+        assert_eq!(history[0].merged_names, vec!["King, Stephen", "S. King"]);
+        assert!(history[0].undone_at.is_none());
+        assert!(merge_history(&pool, Some(MergeEntity::Tags), None).await.unwrap().is_empty());
+
+        // The later merge into the same person must be undone first
+        assert!(undo_merge(&pool, first.merge_id).await.is_err());
+        undo_merge(&pool, second.merge_id).await.unwrap();
+        undo_merge(&pool, first.merge_id).await.unwrap();
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM people")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 12);
+        let history = merge_history(&pool, None, Some(1)).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert!(history[0].undone_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_undo_split_person() {
+        let pool = create_test_db().await.unwrap();
+        populate_test_people(&pool).await.unwrap();
+        populate_test_books_with_people(&pool).await.unwrap();
+
+        let merge = merge_people(&pool, 1, &[2]).await.unwrap();
+        let links_after_merge = people_links(&pool).await;
+        let aliases_after_merge: Vec<(String, i64)> = sqlx::query_as("SELECT name, person_id FROM aliases ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+
+        let split = split_person(&pool, 1, &[2], &[], "Stephen Edwin King").await.unwrap();
+        assert!(split.merge_id > merge.merge_id);
+
+        let history = merge_history(&pool, Some(MergeEntity::People), None).await.unwrap();
+        assert_eq!(history[0].id, split.merge_id);
+        assert_eq!(history[0].operation, JournalOperation::Split);
+        assert_eq!(history[0].stats.primary_id, split.target_id);
+        assert_eq!(history[0].stats.merged_ids, vec![1]);
+        assert_eq!(history[1].operation, JournalOperation::Merge);
+
+        // The split involves person 1: undo it before the merge
+        assert!(undo_merge(&pool, merge.merge_id).await.is_err());
+        undo_merge(&pool, split.merge_id).await.unwrap();
+
+        assert_eq!(people_links(&pool).await, links_after_merge);
+        let aliases: Vec<(String, i64)> = sqlx::query_as("SELECT name, person_id FROM aliases ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(aliases, aliases_after_merge);
+        let target: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM people WHERE id = ?")
+            .bind(split.target_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(target, 0);
+
+        undo_merge(&pool, merge.merge_id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_undo_blocked_by_later_merge_of_other_entity() {
+        let pool = create_test_db().await.unwrap();
+        populate_test_contents(&pool).await.unwrap();
+
+        // Moves the author link of content 6 from person 5 to person 1,
+        // then content 6 is merged away
+        let people = merge_people(&pool, 1, &[5]).await.unwrap();
+        let contents = merge_contents(&pool, 5, &[6]).await.unwrap();
+
+        let err = undo_merge(&pool, people.merge_id).await.unwrap_err();
+        assert!(
+            err.to_string().contains(&format!("blocked by merge #{}", contents.merge_id)),
+            "{}",
+            err
+        );
+
+        // Nothing was restored by the refused undo
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM people WHERE id = 5")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+
+        undo_merge(&pool, contents.merge_id).await.unwrap();
+        undo_merge(&pool, people.merge_id).await.unwrap();
+        let links: Vec<(i64, i64)> =
+            sqlx::query_as("SELECT content_id, person_id FROM x_contents_people_roles WHERE content_id IN (5, 6) ORDER BY 1")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(links, vec![(5, 1), (6, 5)]);
+    }
+}
@@ -1,4 +1,6 @@
 use crate::entity_learner::VariantPatternType;
+use crate::transliteration::{fold_romanization_variants, has_non_latin_script};
+use crate::utils::MLStringUtils;
 use strsim::levenshtein;
 
 /// Classifies the pattern type between two strings based on their structure
@@ -10,6 +12,11 @@ pub fn default_classify_pattern_type(
     let base_lower = base.to_lowercase();
     let variant_lower = variant.to_lowercase();
 
+    // Transliteration across scripts or conventions ("Достоевский", "Dostoevskij", "Dostoyevsky")
+    if base_lower != variant_lower && is_romanization_variant(base, variant) {
+        return VariantPatternType::Transliteration;
+    }
+
     // Abbreviation: if variant is much shorter
     if variant_lower.len() < base_lower.len() / 2 {
         return VariantPatternType::Abbreviation;
@@ -50,6 +57,28 @@ pub fn default_classify_pattern_type(
     VariantPatternType::Other
 }
 
+/// True if the two strings romanize to the same key, or to similar keys when
+/// only one of them is written in a non-Latin script
+fn is_romanization_variant(base: &str, variant: &str) -> bool {
+    let normalizer = MLStringUtils::default();
+    let base_key = fold_romanization_variants(
+        &normalizer.normalize_string(base),
+        has_non_latin_script(base),
+    );
+    let variant_key = fold_romanization_variants(
+        &normalizer.normalize_string(variant),
+        has_non_latin_script(variant),
+    );
+    if base_key.is_empty() || variant_key.is_empty() {
+        return false;
+    }
+    if base_key == variant_key {
+        return true;
+    }
+    has_non_latin_script(base) != has_non_latin_script(variant)
+        && normalizer.normalized_levenshtein(&base_key, &variant_key) >= 0.8
+}
+
 /// Calculates the confidence of a pattern based on various factors
 pub fn default_confidence_function(
     base: &str,
@@ -148,6 +177,16 @@ mod tests {
         assert_eq!(pattern, VariantPatternType::Prefix);
     }
 
+    #[test]
+    fn test_classify_cross_script_transliteration() {
+        let pattern = default_classify_pattern_type("Dostoevsky", "Достоевский", 11);
+        assert_eq!(pattern, VariantPatternType::Transliteration);
+        let pattern = default_classify_pattern_type("Dostoevskij", "Dostoyevsky", 3);
+        assert_eq!(pattern, VariantPatternType::Transliteration);
+        let pattern = default_classify_pattern_type("Tolstoj", "Чехов", 5);
+        assert_ne!(pattern, VariantPatternType::Transliteration);
+    }
+
     #[test]
     fn test_initials_matching() {
         assert!(are_initials_matching("John Ronald Reuel", "J.R.R."));
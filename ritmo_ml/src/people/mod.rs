@@ -1,2 +1,3 @@
 pub mod record;
+pub mod name_rules;
 pub mod parse_names;
@@ -0,0 +1,148 @@
+use crate::utils::MLStringUtils;
+use serde::{Deserialize, Serialize};
+
+/// Ordine delle parti di un nome riconosciuto dal parser
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NameOrder {
+    /// Nome e poi cognome ("Gabriel García Márquez")
+    #[default]
+    GivenFirst,
+    /// Cognome e poi nome, senza virgola ("Della Porta Giambattista", "Murakami Haruki")
+    FamilyFirst,
+    /// Forma invertita con la virgola ("García Márquez, Gabriel")
+    Inverted,
+    /// Una sola parola ("Voltaire")
+    Mononym,
+}
+
+/// Regole di parsing dei nomi per convenzioni diverse da quella inglese
+///
+/// Tutti i confronti ignorano maiuscole e accenti. Le particelle composte
+/// ("van der", "de la") sono sequenze di particelle singole.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NameParsingRules {
+    /// Particelle che fanno parte del cognome ("della", "van", "de", "von"...)
+    pub particles: Vec<String>,
+    /// Congiunzioni dei cognomi composti ("Ortega y Gasset")
+    pub surname_connectors: Vec<String>,
+    /// Cognomi dell'Asia orientale: un nome di due parole che inizia con uno
+    /// di questi (e non finisce con uno di questi) è in ordine cognome-nome
+    pub family_first_surnames: Vec<String>,
+    /// Titoli iniziali ignorati ("Dr.", "Prof.", "Sir")
+    pub honorifics: Vec<String>,
+    /// Suffissi finali ("Jr.", "III")
+    pub suffixes: Vec<String>,
+}
+
+const DEFAULT_PARTICLES: &[&str] = &[
+    // Italiano
+    "di", "de", "da", "del", "della", "dello", "dei", "degli", "delle", "dal", "dalla", "dalle",
+    "lo", "la", "le",
+    // Spagnolo e portoghese
+    "los", "las", "do", "dos", "das",
+    // Olandese e tedesco
+    "van", "von", "der", "den", "ter", "ten", "zu", "vom",
+    // Francese
+    "du", "des",
+    // Arabo
+    "ibn",
+];
+
+const DEFAULT_CONNECTORS: &[&str] = &["y"];
+
+/// Cognomi giapponesi, cinesi e coreani frequenti; esclusi quelli che sono
+/// anche nomi occidentali comuni ("Lee", "Kim", "Park", "Lin", "Mo", "Han")
+/// o troppo brevi per essere riconosciuti con sicurezza ("Li", "He", "Ma")
+const DEFAULT_FAMILY_FIRST_SURNAMES: &[&str] = &[
+    // Giapponesi
+    "abe", "akutagawa", "endo", "hayashi", "higashino", "inoue", "ishiguro", "ito", "kato",
+    "kawabata", "kawakami", "kimura", "kirino", "kobayashi", "matsumoto", "mishima", "miyabe",
+    "miyazaki", "murakami", "murata", "nakamura", "natsume", "oe", "ogawa", "sasaki", "sato",
+    "shimizu", "suzuki", "takahashi", "tanaka", "tanizaki", "watanabe", "yamada", "yamaguchi",
+    "yamamoto", "yoshida", "yoshimoto",
+    // Cinesi
+    "cao", "chen", "gao", "guo", "hu", "huang", "jin", "lao", "liu", "lu", "luo", "qian",
+    "sun", "wang", "wu", "xu", "yang", "yu", "zhang", "zhao", "zhou", "zhu",
+    // Coreani
+    "choi", "hwang", "jung", "shin", "yoon",
+];
+
+const DEFAULT_HONORIFICS: &[&str] = &[
+    "dr", "prof", "sir", "dame", "mr", "mrs", "ms", "dott", "dottssa", "sig", "sra",
+];
+
+const DEFAULT_SUFFIXES: &[&str] = &["jr", "sr", "ii", "iii", "iv"];
+
+fn to_strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+impl Default for NameParsingRules {
+    fn default() -> Self {
+        Self {
+            particles: to_strings(DEFAULT_PARTICLES),
+            surname_connectors: to_strings(DEFAULT_CONNECTORS),
+            family_first_surnames: to_strings(DEFAULT_FAMILY_FIRST_SURNAMES),
+            honorifics: to_strings(DEFAULT_HONORIFICS),
+            suffixes: to_strings(DEFAULT_SUFFIXES),
+        }
+    }
+}
+
+impl NameParsingRules {
+    /// Regole predefinite con particelle aggiuntive (es. da `settings.toml`)
+    pub fn with_particles<S: AsRef<str>>(mut self, particles: &[S]) -> Self {
+        for particle in particles {
+            let key = fold(particle.as_ref());
+            if !key.is_empty() && !self.particles.iter().any(|p| fold(p) == key) {
+                self.particles.push(key);
+            }
+        }
+        self
+    }
+
+    pub fn is_particle(&self, token: &str) -> bool {
+        contains(&self.particles, token)
+    }
+
+    pub fn is_connector(&self, token: &str) -> bool {
+        contains(&self.surname_connectors, token)
+    }
+
+    pub fn is_family_first_surname(&self, token: &str) -> bool {
+        contains(&self.family_first_surnames, token)
+    }
+
+    pub fn is_honorific(&self, token: &str) -> bool {
+        contains(&self.honorifics, token)
+    }
+
+    pub fn is_suffix(&self, token: &str) -> bool {
+        contains(&self.suffixes, token)
+    }
+}
+
+/// Forma di confronto di una parola: minuscole, senza accenti e punteggiatura
+fn fold(token: &str) -> String {
+    MLStringUtils::default()
+        .normalize_string(token)
+        .replace(' ', "")
+}
+
+fn contains(list: &[String], token: &str) -> bool {
+    let key = fold(token);
+    !key.is_empty() && list.iter().any(|item| fold(item) == key)
+}
+
+/// True se il testo contiene ideogrammi o sillabari dell'Asia orientale
+pub(crate) fn is_cjk(text: &str) -> bool {
+    text.chars().any(|c| {
+        matches!(c as u32,
+            0x3040..=0x30FF     // Hiragana, Katakana
+            | 0x3400..=0x4DBF   // CJK Extension A
+            | 0x4E00..=0x9FFF   // CJK Unified Ideographs
+            | 0xAC00..=0xD7AF)  // Hangul
+    })
+}
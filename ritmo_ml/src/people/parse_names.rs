@@ -1,3 +1,5 @@
+use crate::people::name_rules::{is_cjk, NameOrder, NameParsingRules};
+use crate::transliteration::{fold_romanization_variants, has_non_latin_script};
 use crate::utils::MLStringUtils;
 use ritmo_errors::RitmoErr;
 use human_name::Name;
@@ -13,21 +15,102 @@ pub struct ParsedName {
     pub title: Option<String>,
     pub suffix: Option<String>,
     pub display_name: String,
+    /// Ordine riconosciuto nel testo originale
+    #[serde(default)]
+    pub order: NameOrder,
+    /// Affidabilità della divisione in nome e cognome (0.0-1.0)
+    #[serde(default)]
+    pub confidence: f64,
 }
 
 impl ParsedName {
+    /// Parsing con le regole predefinite (`NameParsingRules::default()`)
     pub fn from_string(input: &str) -> Result<Self, RitmoErr> {
+        Self::from_string_with_rules(input, &NameParsingRules::default())
+    }
+
+    /// Parsing di un nome secondo le convenzioni di più lingue
+    ///
+    /// Nell'ordine:
+    /// 1. "Cognome, Nome" (anche "García Márquez, Gabriel" e "Beethoven, Ludwig van")
+    /// 2. una sola parola (nome d'arte, pseudonimo)
+    /// 3. scrittura dell'Asia orientale con spazi ("村上 春樹"): cognome per primo
+    /// 4. nome che inizia con una particella ("Della Porta Giambattista", "van der Berg"):
+    ///    cognome per primo
+    /// 5. due parole con un cognome dell'Asia orientale in testa ("Murakami Haruki")
+    /// 6. particella o congiunzione interna ("Jan van der Berg", "José Ortega y Gasset"):
+    ///    il cognome parte da lì
+    /// 7. altrimenti `human_name`, adatto ai nomi inglesi
+    pub fn from_string_with_rules(input: &str, rules: &NameParsingRules) -> Result<Self, RitmoErr> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Ok(ParsedName::default());
+        }
+
+        if let Some(parsed) = Self::parse_inverted(input, rules) {
+            return Ok(parsed);
+        }
+
+        let mut tokens: Vec<&str> = input
+            .split_whitespace()
+            .map(|t| t.trim_end_matches(','))
+            .filter(|t| !t.is_empty())
+            .collect();
+        // Solo separatori (es. ", ,"): nessun nome
+        if tokens.is_empty() {
+            return Ok(ParsedName::default());
+        }
+        let mut title = None;
+        while tokens.len() > 1 && rules.is_honorific(tokens[0]) {
+            title = Some(tokens.remove(0).to_string());
+        }
+        let mut suffix = None;
+        while tokens.len() > 1 && rules.is_suffix(tokens[tokens.len() - 1]) {
+            suffix = tokens.pop().map(|s| s.trim_start_matches(',').to_string());
+        }
+
         // Gestione caso singola parola
         if input.split(|c: char| c.is_whitespace() || c == '.')
            .filter(|s| !s.is_empty())
            .count() == 1 {
             return Ok(ParsedName {
-                given_name: input.trim().to_string(),
-                display_name: input.trim().to_string(),
+                given_name: input.to_string(),
+                display_name: input.to_string(),
+                order: NameOrder::Mononym,
+                confidence: 0.6,
                 ..Default::default()
             });
         }
 
+        let last = tokens.len() - 1;
+        let split = if tokens.len() < 2 {
+            None
+        } else if is_cjk(input) {
+            Some(Split::east_asian(0.9))
+        } else if rules.is_particle(tokens[0]) {
+            // Particelle iniziali più la parola successiva (e le congiunzioni) formano il cognome
+            let mut end = tokens.iter().take_while(|t| rules.is_particle(t)).count() + 1;
+            while end + 1 < tokens.len() && rules.is_connector(tokens[end]) {
+                end += 2;
+            }
+            let end = end.min(tokens.len());
+            let confidence = if end < tokens.len() { 0.85 } else { 0.7 };
+            Some(Split::family_first(end, confidence))
+        } else if tokens.len() == 2 && rules.is_family_first_surname(tokens[0]) {
+            let confidence = if rules.is_family_first_surname(tokens[1]) { 0.6 } else { 0.8 };
+            Some(Split::east_asian(confidence))
+        } else if let Some(i) = (1..last).find(|&i| rules.is_particle(tokens[i])) {
+            Some(Split::given_first(i, 0.9))
+        } else {
+            (2..last)
+                .find(|&i| rules.is_connector(tokens[i]))
+                .map(|i| Split::given_first(i - 1, 0.9))
+        };
+
+        if let Some(split) = split {
+            return Ok(split.apply(&tokens, title, suffix));
+        }
+
         let pparsed = Name::parse(input);
         if let Some(parsed) = pparsed {
             let given_name = parsed.given_name().unwrap_or("").to_string();
@@ -46,16 +129,68 @@ impl ParsedName {
                 title,
                 suffix,
                 display_name: display_name.to_string(),
+                order: NameOrder::GivenFirst,
+                confidence: 0.9,
             })
         }
         else {
             Ok(ParsedName {
                 display_name: input.to_string(),
+                confidence: 0.3,
                 ..Default::default()
             })
         }
     }
 
+    /// "Cognome, Nome[, Suffisso]"; `None` se non c'è una virgola che separa due parti
+    fn parse_inverted(input: &str, rules: &NameParsingRules) -> Option<Self> {
+        let mut parts = input.split(',').map(str::trim);
+        let surname_part = parts.next()?;
+        let given_part = parts.next()?;
+        let suffix = parts.next().filter(|s| !s.is_empty()).map(str::to_string);
+        if surname_part.is_empty() || given_part.is_empty() || rules.is_suffix(given_part) {
+            return None;
+        }
+
+        let mut surname: Vec<&str> = surname_part.split_whitespace().collect();
+        let mut given: Vec<&str> = given_part.split_whitespace().collect();
+        let mut title = None;
+        while given.len() > 1 && rules.is_honorific(given[0]) {
+            title = Some(given.remove(0).to_string());
+        }
+        // "Beethoven, Ludwig van": le particelle finali appartengono al cognome
+        while given.len() > 1 && rules.is_particle(given[given.len() - 1]) {
+            surname.insert(0, given.pop()?);
+        }
+
+        let mut parsed = ParsedName {
+            given_name: given[0].to_string(),
+            surname: surname.join(" "),
+            middle_names: given[1..].iter().map(|s| s.to_string()).collect(),
+            title,
+            suffix,
+            order: NameOrder::Inverted,
+            confidence: 0.95,
+            ..Default::default()
+        };
+        parsed.display_name = parsed.given_first_display();
+        Some(parsed)
+    }
+
+    /// Nome, secondi nomi e cognome nell'ordine occidentale
+    fn given_first_display(&self) -> String {
+        std::iter::once(self.given_name.as_str())
+            .chain(self.middle_names.iter().map(String::as_str))
+            .chain(std::iter::once(self.surname.as_str()))
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Chiave di confronto: nome, secondi nomi e cognome normalizzati, con
+    /// cirillico e greco traslitterati e le varianti di traslitterazione
+    /// ("-skij", "-sky", "-ski") ricondotte a una sola forma; i nomi latini
+    /// senza desinenza slava restano come sono ("Henry" ≠ "Henri")
     pub fn to_normalized_key(&self, normalizer: &MLStringUtils) -> String {
         let mut full_name_parts = Vec::new();
         
@@ -74,6 +209,220 @@ impl ParsedName {
         }
         
         let combined_name = full_name_parts.join(" ");
-        normalizer.normalize_string(&combined_name)
+        fold_romanization_variants(
+            &normalizer.normalize_string(&combined_name),
+            has_non_latin_script(&combined_name),
+        )
+    }
+}
+
+/// Divisione delle parole di un nome tra cognome e nome
+struct Split {
+    order: NameOrder,
+    /// Per `FamilyFirst`: parole del cognome in testa; per `GivenFirst`: inizio del cognome
+    boundary: usize,
+    confidence: f64,
+    /// Visualizzazione nell'ordine originale (nomi dell'Asia orientale)
+    keep_order: bool,
+}
+
+impl Split {
+    fn family_first(surname_len: usize, confidence: f64) -> Self {
+        Self { order: NameOrder::FamilyFirst, boundary: surname_len, confidence, keep_order: false }
+    }
+
+    fn east_asian(confidence: f64) -> Self {
+        Self { order: NameOrder::FamilyFirst, boundary: 1, confidence, keep_order: true }
+    }
+
+    fn given_first(surname_start: usize, confidence: f64) -> Self {
+        Self { order: NameOrder::GivenFirst, boundary: surname_start, confidence, keep_order: false }
+    }
+
+    fn apply(&self, tokens: &[&str], title: Option<String>, suffix: Option<String>) -> ParsedName {
+        let (surname, given) = match self.order {
+            NameOrder::FamilyFirst => (&tokens[..self.boundary], &tokens[self.boundary..]),
+            _ => (&tokens[self.boundary..], &tokens[..self.boundary]),
+        };
+        let mut parsed = ParsedName {
+            given_name: given.first().map(|s| s.to_string()).unwrap_or_default(),
+            surname: surname.join(" "),
+            middle_names: given.iter().skip(1).map(|s| s.to_string()).collect(),
+            title,
+            suffix,
+            order: self.order,
+            confidence: self.confidence,
+            ..Default::default()
+        };
+        parsed.display_name = if self.keep_order {
+            tokens.join(" ")
+        } else {
+            parsed.given_first_display()
+        };
+        parsed
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> ParsedName {
+        ParsedName::from_string(input).unwrap()
+    }
+
+    fn key(input: &str) -> String {
+        parse(input).to_normalized_key(&MLStringUtils::default())
+    }
+
+    #[test]
+    fn test_separators_only() {
+        for input in [", ,", " , , ", ","] {
+            assert_eq!(parse(input).display_name, "", "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_italian_particles() {
+        let name = parse("Della Porta Giambattista");
+        assert_eq!(name.surname, "Della Porta");
+        assert_eq!(name.given_name, "Giambattista");
+        assert_eq!(name.order, NameOrder::FamilyFirst);
+        assert_eq!(name.display_name, "Giambattista Della Porta");
+
+        let name = parse("Giambattista della Porta");
+        assert_eq!(name.surname, "della Porta");
+        assert_eq!(name.given_name, "Giambattista");
+        assert_eq!(name.order, NameOrder::GivenFirst);
+
+        assert_eq!(key("Della Porta Giambattista"), key("Giambattista Della Porta"));
+        assert_eq!(parse("Lorenzo Da Ponte").surname, "Da Ponte");
+    }
+
+    #[test]
+    fn test_spanish_compound_surnames() {
+        let name = parse("García Márquez, Gabriel");
+        assert_eq!(name.surname, "García Márquez");
+        assert_eq!(name.given_name, "Gabriel");
+        assert_eq!(name.order, NameOrder::Inverted);
+        assert_eq!(name.display_name, "Gabriel García Márquez");
+        assert_eq!(key("García Márquez, Gabriel"), key("Gabriel García Márquez"));
+
+        let name = parse("José Ortega y Gasset");
+        assert_eq!(name.surname, "Ortega y Gasset");
+        assert_eq!(name.given_name, "José");
+
+        let name = parse("Miguel de Cervantes Saavedra");
+        assert_eq!(name.surname, "de Cervantes Saavedra");
+        assert_eq!(name.given_name, "Miguel");
+    }
+
+    #[test]
+    fn test_dutch_and_german_particles() {
+        let name = parse("van der Berg");
+        assert_eq!(name.surname, "van der Berg");
+        assert!(name.given_name.is_empty());
+        assert!(name.confidence < 0.8);
+
+        let name = parse("Jan van der Berg");
+        assert_eq!(name.surname, "van der Berg");
+        assert_eq!(name.given_name, "Jan");
+
+        let name = parse("Beethoven, Ludwig van");
+        assert_eq!(name.surname, "van Beethoven");
+        assert_eq!(name.given_name, "Ludwig");
+        assert_eq!(key("Beethoven, Ludwig van"), key("Ludwig van Beethoven"));
+
+        let name = parse("Ursula K. Le Guin");
+        assert_eq!(name.surname, "Le Guin");
+        assert_eq!(name.middle_names, vec!["K."]);
+    }
+
+    #[test]
+    fn test_family_name_first() {
+        let name = parse("Murakami Haruki");
+        assert_eq!(name.surname, "Murakami");
+        assert_eq!(name.given_name, "Haruki");
+        assert_eq!(name.order, NameOrder::FamilyFirst);
+        assert_eq!(name.display_name, "Murakami Haruki");
+        assert_eq!(key("Murakami Haruki"), key("Haruki Murakami"));
+        assert_eq!(key("Murakami, Haruki"), key("Haruki Murakami"));
+
+        let name = parse("Haruki Murakami");
+        assert_eq!(name.surname, "Murakami");
+        assert_eq!(name.order, NameOrder::GivenFirst);
+
+        let name = parse("村上 春樹");
+        assert_eq!(name.surname, "村上");
+        assert_eq!(name.given_name, "春樹");
+        assert_eq!(name.display_name, "村上 春樹");
+
+        // Due cognomi possibili: ordine incerto
+        assert!(parse("Wang Yang").confidence < parse("Wang Anyi").confidence);
+    }
+
+    #[test]
+    fn test_english_names_unchanged() {
+        let name = parse("Stephen Edwin King");
+        assert_eq!(name.given_name, "Stephen");
+        assert_eq!(name.surname, "King");
+        assert_eq!(name.middle_names, vec!["Edwin"]);
+
+        let name = parse("King, Stephen");
+        assert_eq!(name.given_name, "Stephen");
+        assert_eq!(name.surname, "King");
+
+        let name = parse("King, Stephen, Jr.");
+        assert_eq!(name.suffix.as_deref(), Some("Jr."));
+
+        // Un nome occidentale che coincide con un cognome asiatico non è invertito
+        assert_eq!(parse("Kim Stanley Robinson").surname, "Robinson");
+        assert_eq!(parse("Mo Hayder").surname, "Hayder");
+        assert_eq!(parse("Lin Carter").surname, "Carter");
+        assert_eq!(parse("Lin Carter").order, NameOrder::GivenFirst);
+
+        let name = parse("Voltaire");
+        assert_eq!(name.given_name, "Voltaire");
+        assert_eq!(name.order, NameOrder::Mononym);
+    }
+
+    #[test]
+    fn test_multilingual_author_list() {
+        // Stessa persona scritta in forme diverse: stessa chiave normalizzata
+        let same_person = [
+            ("Gabriel García Márquez", "García Márquez, Gabriel"),
+            ("Giambattista Della Porta", "Della Porta, Giambattista"),
+            ("Vincent van Gogh", "van Gogh, Vincent"),
+            ("Haruki Murakami", "Murakami Haruki"),
+            ("Antoine de Saint-Exupéry", "Saint-Exupéry, Antoine de"),
+            ("Yu Hua", "Yu, Hua"),
+        ];
+        for (a, b) in same_person {
+            assert_eq!(key(a), key(b), "{} / {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_custom_particles() {
+        assert!(!NameParsingRules::default().is_particle("af"));
+        let rules = NameParsingRules::default().with_particles(&["Af"]);
+        assert!(rules.is_particle("af"));
+
+        let name = ParsedName::from_string_with_rules("Af Geijerstam Gustaf", &rules).unwrap();
+        assert_eq!(name.surname, "Af Geijerstam");
+        assert_eq!(name.given_name, "Gustaf");
+    }
+
+    #[test]
+    fn test_cross_script_names() {
+        let name = parse("Фёдор Достоевский");
+        assert_eq!(name.order, NameOrder::GivenFirst);
+        assert_eq!(key("Фёдор Достоевский"), "fedor dostoevski");
+
+        let expected = key("Fëdor Dostoevskij");
+        for variant in ["Фёдор Достоевский", "Dostoevskij, Fëdor", "Fëdor Dostoïevski"] {
+            assert_eq!(key(variant), expected, "{}", variant);
+        }
+        assert_eq!(key("Νίκος Καζαντζάκης"), key("Nikos Kazantzakis"));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::people::name_rules::NameParsingRules;
 use crate::people::parse_names::ParsedName;
 use crate::traits::MLProcessable;
 use crate::utils::MLStringUtils;
@@ -17,15 +18,26 @@ pub struct PersonRecord {
 
 impl PersonRecord {
     pub fn new(id: i64, input: &str, normalizer: &MLStringUtils) -> Result<Self, RitmoErr> {
-        let parsed_name = ParsedName::from_string(input)?;
+        Self::with_rules(id, input, normalizer, &NameParsingRules::default())
+    }
+
+    /// Come `new`, con regole di parsing personalizzate (es. particelle aggiuntive);
+    /// `confidence` parte dall'affidabilità del parsing del nome
+    pub fn with_rules(
+        id: i64,
+        input: &str,
+        normalizer: &MLStringUtils,
+        rules: &NameParsingRules,
+    ) -> Result<Self, RitmoErr> {
+        let parsed_name = ParsedName::from_string_with_rules(input, rules)?;
         let normalized_key = parsed_name.to_normalized_key(normalizer);
 
         Ok(PersonRecord {
             id,
             original_input: input.to_string(),
+            confidence: parsed_name.confidence,
             parsed_name,
             normalized_key,
-            confidence: 1.0,
             verified: false,
             aliases: Vec::new(),
         })
@@ -38,6 +50,18 @@ impl PersonRecord {
         }
     }
 
+    /// Aggiunge un alias (es. dalla tabella `aliases`) con la stessa chiave
+    /// di `normalized_key`, così un alias può coincidere con la chiave di un'altra persona
+    pub fn add_alias_name(&mut self, alias: &str, normalizer: &MLStringUtils, rules: &NameParsingRules) {
+        let Ok(parsed) = ParsedName::from_string_with_rules(alias, rules) else {
+            return;
+        };
+        let key = parsed.to_normalized_key(normalizer);
+        if !key.is_empty() && key != self.normalized_key && !self.aliases.contains(&key) {
+            self.aliases.push(key);
+        }
+    }
+
     pub fn update_confidence(&mut self, new_confidence: f64) {
         self.confidence = new_confidence.clamp(0.0, 1.0);
     }
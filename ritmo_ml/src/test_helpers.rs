@@ -38,6 +38,17 @@ pub async fn create_test_db() -> RitmoResult<SqlitePool> {
             "updated_at" INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
         );
 
+        CREATE TABLE IF NOT EXISTS "aliases" (
+            "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+            "name" TEXT NOT NULL,
+            "person_id" INTEGER NOT NULL,
+            "alias_normalized" TEXT,
+            "confidence" REAL NOT NULL DEFAULT 0.9,
+            "created_at" INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+            FOREIGN KEY("person_id") REFERENCES "people"("id") ON DELETE CASCADE,
+            UNIQUE("person_id", "name")
+        );
+
         CREATE TABLE IF NOT EXISTS "publishers" (
             "id" INTEGER PRIMARY KEY AUTOINCREMENT,
             "name" TEXT NOT NULL,
@@ -81,10 +92,32 @@ pub async fn create_test_db() -> RitmoResult<SqlitePool> {
             FOREIGN KEY("series_id") REFERENCES "series"("id") ON DELETE SET NULL
         );
 
+        CREATE TABLE IF NOT EXISTS "types" (
+            "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+            "key" TEXT NOT NULL UNIQUE,
+            "created_at" INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS "running_languages" (
+            "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+            "iso_code_2char" TEXT NOT NULL,
+            "iso_code_3char" TEXT NOT NULL,
+            "official_name" TEXT NOT NULL,
+            "language_role" TEXT NOT NULL,
+            UNIQUE("iso_code_2char", "iso_code_3char", "language_role")
+        );
+
         CREATE TABLE IF NOT EXISTS "contents" (
             "id" INTEGER PRIMARY KEY AUTOINCREMENT,
             "name" TEXT NOT NULL,
-            "created_at" INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+            "original_title" TEXT,
+            "type_id" INTEGER,
+            "publication_date" INTEGER,
+            "pages" INTEGER,
+            "notes" TEXT,
+            "created_at" INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+            "updated_at" INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+            FOREIGN KEY("type_id") REFERENCES "types"("id") ON DELETE SET NULL
         );
 
         CREATE TABLE IF NOT EXISTS "x_books_people_roles" (
@@ -114,6 +147,36 @@ pub async fn create_test_db() -> RitmoResult<SqlitePool> {
             FOREIGN KEY("book_id") REFERENCES "books"("id") ON DELETE CASCADE,
             FOREIGN KEY("content_id") REFERENCES "contents"("id") ON DELETE CASCADE
         );
+
+        CREATE TABLE IF NOT EXISTS "x_books_tags" (
+            "book_id" INTEGER NOT NULL,
+            "tag_id" INTEGER NOT NULL,
+            PRIMARY KEY("book_id", "tag_id")
+        );
+
+        CREATE TABLE IF NOT EXISTS "x_contents_tags" (
+            "content_id" INTEGER NOT NULL,
+            "tag_id" INTEGER NOT NULL,
+            PRIMARY KEY("content_id", "tag_id"),
+            FOREIGN KEY("content_id") REFERENCES "contents"("id") ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS "x_contents_languages" (
+            "content_id" INTEGER NOT NULL,
+            "language_id" INTEGER NOT NULL,
+            PRIMARY KEY("content_id", "language_id"),
+            FOREIGN KEY("content_id") REFERENCES "contents"("id") ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS "merge_journal" (
+            "id" INTEGER PRIMARY KEY AUTOINCREMENT,
+            "entity_type" TEXT NOT NULL,
+            "primary_id" INTEGER NOT NULL,
+            "stats" TEXT NOT NULL,
+            "payload" TEXT NOT NULL,
+            "created_at" INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+            "undone_at" INTEGER
+        );
         "#,
     )
     .execute(&pool)
@@ -274,3 +337,63 @@ pub async fn populate_test_books_with_people(pool: &SqlitePool) -> RitmoResult<(
 
     Ok(())
 }
+
+/// Create contents (works) re-created for each edition, linked to books,
+/// authors, tags and original languages (for content deduplication testing)
+pub async fn populate_test_contents(pool: &SqlitePool) -> RitmoResult<()> {
+    populate_test_people(pool).await?;
+    populate_test_roles(pool).await?;
+    populate_test_tags(pool).await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO people (id, name, normalized_key) VALUES
+        (13, 'Umberto Eco', 'umberto eco'),
+        (14, 'William Weaver', 'william weaver');
+
+        INSERT INTO types (id, key) VALUES
+        (1, 'type.novel'),
+        (2, 'type.short_story');
+
+        INSERT INTO running_languages (id, iso_code_2char, iso_code_3char, official_name, language_role) VALUES
+        (1, 'it', 'ita', 'Italian', 'language_role.original'),
+        (2, 'en', 'eng', 'English', 'language_role.original'),
+        (3, 'en', 'eng', 'English', 'language_role.actual');
+
+        INSERT INTO books (id, name) VALUES
+        (1, 'Il nome della rosa (Bompiani 1980)'),
+        (2, 'Il nome della rosa (Bompiani 2012)'),
+        (3, 'The Name of the Rose'),
+        (4, 'Il pendolo di Foucault'),
+        (5, 'Racconti scelti'),
+        (6, 'Racconti');
+
+        -- 1, 2 e 3 sono la stessa opera; 5 e 6 hanno lo stesso titolo ma autori diversi
+        INSERT INTO contents (id, name, original_title, type_id, publication_date) VALUES
+        (1, 'Il nome della rosa', NULL, 1, NULL),
+        (2, 'Il Nome della Rosa', NULL, NULL, 1980),
+        (3, 'The Name of the Rose', 'Il nome della rosa', 1, NULL),
+        (4, 'Il pendolo di Foucault', NULL, 1, NULL),
+        (5, 'Racconti', NULL, 2, NULL),
+        (6, 'Racconti', NULL, 2, NULL);
+
+        INSERT INTO x_books_contents (book_id, content_id) VALUES
+        (1, 1), (2, 2), (3, 3), (4, 4), (5, 5), (6, 6),
+        (2, 1);
+
+        INSERT INTO x_contents_people_roles (content_id, person_id, role_id) VALUES
+        (1, 13, 1), (2, 13, 1), (3, 13, 1), (3, 14, 4), (4, 13, 1),
+        (5, 1, 1), (6, 5, 1);
+
+        INSERT INTO x_contents_tags (content_id, tag_id) VALUES
+        (1, 1), (2, 1), (2, 7);
+
+        INSERT INTO x_contents_languages (content_id, language_id) VALUES
+        (1, 1), (2, 1), (3, 1), (3, 3), (4, 1);
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,298 @@
+//! Romanization of non-Latin scripts for entity matching
+//!
+//! `romanize` converts Cyrillic and Greek letters to Latin (a simplified
+//! BGN/PCGN-style table, one spelling per letter) and leaves every other
+//! character untouched, so it can run in front of
+//! `MLStringUtils::normalize_string`. CJK scripts are not romanized: kanji and
+//! hanzi readings need a dictionary, not a table.
+//!
+//! Romanizing is not enough on its own: "Dostoevskij", "Dostoyevsky" and
+//! "Dostoïevski" are three conventions for the same Cyrillic spelling.
+//! `fold_romanization_variants` collapses the usual differences (`j`/`y`/`i`
+//! for й, glides between vowels, `kh`/`h`, `x`/`ks`) into a single key. The
+//! folding only touches romanized names and Latin words with a Slavic ending,
+//! so "Henry"/"Henri" or "Alex"/"Aleks" keep distinct keys.
+
+use unicode_normalization::UnicodeNormalization;
+
+/// Converts Cyrillic and Greek letters to Latin, keeping the case of the
+/// first letter. Other characters are returned unchanged.
+pub fn romanize(text: &str) -> String {
+    let chars: Vec<char> = text.nfc().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let lower = c.to_lowercase().next().unwrap_or(c);
+        let upper = c != lower;
+
+        let (latin, consumed) = if is_cyrillic(c) {
+            (cyrillic_to_latin(lower), 1)
+        } else if is_greek(c) {
+            greek_to_latin(&chars, i)
+        } else {
+            out.push(c);
+            i += 1;
+            continue;
+        };
+
+        if upper {
+            let mut letters = latin.chars();
+            if let Some(first) = letters.next() {
+                out.extend(first.to_uppercase());
+                out.push_str(letters.as_str());
+            }
+        } else {
+            out.push_str(latin);
+        }
+        i += consumed;
+    }
+
+    out
+}
+
+/// True if the text contains Cyrillic or Greek letters
+pub fn has_non_latin_script(text: &str) -> bool {
+    text.chars().any(|c| is_cyrillic(c) || is_greek(c))
+}
+
+/// Collapses the romanization conventions of a normalized name (lowercase,
+/// no accents, words separated by single spaces) into a common key.
+///
+/// `romanized` tells whether the source was written in a non-Latin script
+/// (see `has_non_latin_script`): then every word is folded, otherwise only
+/// words with a Slavic ending ("Dostoevskij", "Tolstoy", "Chekhov").
+pub fn fold_romanization_variants(normalized: &str, romanized: bool) -> String {
+    normalized
+        .split(' ')
+        .map(|word| {
+            if romanized || has_slavic_ending(word) {
+                fold_word(word)
+            } else {
+                word.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Endings of Russian, Ukrainian and Polish surnames as spelled in the
+/// common Latin conventions
+const SLAVIC_ENDINGS: &[&str] = &[
+    "skij", "skiy", "skii", "sky", "ski", "ckij", "cky", "cki", "vich", "vych", "ov", "ova", "ev",
+    "eva", "oj", "oy", "oi", "ij", "iy", "yj", "yi",
+];
+
+fn has_slavic_ending(word: &str) -> bool {
+    SLAVIC_ENDINGS
+        .iter()
+        .any(|ending| word.len() > ending.len() + 2 && word.ends_with(ending))
+}
+
+fn fold_word(word: &str) -> String {
+    let word = word
+        .replace('x', "ks")
+        .replace("kh", "h")
+        .replace("tch", "ch")
+        .replace("wsk", "vsk");
+
+    // й: "j" (Italian, German) and "y" (English) after the first letter
+    let chars: Vec<char> = word
+        .chars()
+        .enumerate()
+        .map(|(i, c)| if i > 0 && c == 'j' { 'y' } else { c })
+        .collect();
+
+    // Glides between vowels: "Dostoyevsky", "Dostoïevski" -> "dostoevsk..."
+    let mut folded = String::with_capacity(chars.len());
+    for (i, &c) in chars.iter().enumerate() {
+        let prev_vowel = i > 0 && is_vowel(chars[i - 1]);
+        let next = chars.get(i + 1).copied();
+        let glide = (c == 'y' && prev_vowel && next.is_some_and(is_vowel))
+            || (c == 'i' && prev_vowel && next == Some('e'));
+        if !glide {
+            folded.push(c);
+        }
+    }
+
+    // Endings: "-ij", "-iy", "-yi", "-ii", "-y" -> "-i"
+    for ending in ["iy", "yy", "yi", "ii"] {
+        if folded.len() > ending.len() && folded.ends_with(ending) {
+            folded.truncate(folded.len() - ending.len());
+            folded.push('i');
+            return folded;
+        }
+    }
+    if folded.len() > 1 && folded.ends_with('y') {
+        folded.pop();
+        folded.push('i');
+    }
+    folded
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'e' | 'i' | 'o' | 'u')
+}
+
+fn is_cyrillic(c: char) -> bool {
+    matches!(c as u32, 0x0400..=0x04FF)
+}
+
+fn is_greek(c: char) -> bool {
+    matches!(c as u32, 0x0370..=0x03FF | 0x1F00..=0x1FFF)
+}
+
+fn cyrillic_to_latin(c: char) -> &'static str {
+    match c {
+        'а' => "a",
+        'б' => "b",
+        'в' => "v",
+        'г' => "g",
+        'д' => "d",
+        'е' | 'ё' | 'э' => "e",
+        'ж' => "zh",
+        'з' => "z",
+        'и' | 'і' => "i",
+        'й' | 'ы' => "y",
+        'к' => "k",
+        'л' => "l",
+        'м' => "m",
+        'н' => "n",
+        'о' => "o",
+        'п' => "p",
+        'р' => "r",
+        'с' => "s",
+        'т' => "t",
+        'у' | 'ў' => "u",
+        'ф' => "f",
+        'х' => "kh",
+        'ц' => "ts",
+        'ч' => "ch",
+        'ш' => "sh",
+        'щ' => "shch",
+        'ъ' | 'ь' => "",
+        'ю' => "yu",
+        'я' => "ya",
+        // Ukrainian
+        'ї' => "yi",
+        'є' => "ye",
+        'ґ' => "g",
+        // Serbian and Macedonian
+        'ђ' => "dj",
+        'ј' => "j",
+        'љ' => "lj",
+        'њ' => "nj",
+        'ћ' => "c",
+        'џ' | 'ѕ' => "dz",
+        'ѓ' => "gj",
+        'ќ' => "kj",
+        _ => "",
+    }
+}
+
+/// Romanizes the Greek letter at `i`; returns the Latin text and how many
+/// characters were consumed (digraphs "ου", "μπ", "ντ")
+fn greek_to_latin(chars: &[char], i: usize) -> (&'static str, usize) {
+    let base = |c: char| {
+        let c = c.nfd().next().unwrap_or(c);
+        c.to_lowercase().next().unwrap_or(c)
+    };
+    let c = base(chars[i]);
+    let next = chars.get(i + 1).map(|&n| base(n));
+    let word_start = i == 0 || !chars[i - 1].is_alphabetic();
+
+    match (c, next) {
+        ('ο', Some('υ')) => return ("ou", 2),
+        ('μ', Some('π')) if word_start => return ("b", 2),
+        ('ν', Some('τ')) if word_start => return ("d", 2),
+        _ => {}
+    }
+
+    let latin = match c {
+        'α' => "a",
+        'β' => "v",
+        'γ' => "g",
+        'δ' => "d",
+        'ε' => "e",
+        'ζ' => "z",
+        'η' | 'ι' => "i",
+        'θ' => "th",
+        'κ' => "k",
+        'λ' => "l",
+        'μ' => "m",
+        'ν' => "n",
+        'ξ' => "x",
+        'ο' | 'ω' => "o",
+        'π' => "p",
+        'ρ' => "r",
+        'σ' | 'ς' => "s",
+        'τ' => "t",
+        'υ' => "y",
+        'φ' => "f",
+        'χ' => "ch",
+        'ψ' => "ps",
+        _ => "",
+    };
+    (latin, 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::MLStringUtils;
+
+    fn key(name: &str) -> String {
+        fold_romanization_variants(
+            &MLStringUtils::default().normalize_string(name),
+            has_non_latin_script(name),
+        )
+    }
+
+    #[test]
+    fn test_romanize_cyrillic() {
+        assert_eq!(romanize("Фёдор Достоевский"), "Fedor Dostoevskiy");
+        assert_eq!(romanize("Лев Толстой"), "Lev Tolstoy");
+        assert_eq!(romanize("Чехов"), "Chekhov");
+        assert_eq!(romanize("Stephen King"), "Stephen King");
+    }
+
+    #[test]
+    fn test_romanize_greek() {
+        assert_eq!(romanize("Νίκος Καζαντζάκης"), "Nikos Kazantzakis");
+        assert_eq!(romanize("Κωνσταντίνος Καβάφης"), "Konstantinos Kavafis");
+    }
+
+    #[test]
+    fn test_cross_script_variants_share_key() {
+        let expected = key("Dostoevskij");
+        for variant in ["Dostoyevsky", "Dostoïevski", "Достоевский", "Dostoevsky"] {
+            assert_eq!(key(variant), expected, "{}", variant);
+        }
+        assert_eq!(key("Tolstoj"), key("Лев Толстой").replace("lev ", ""));
+        assert_eq!(key("Tolstoy"), key("Tolstoi"));
+        assert_eq!(key("Tchekhov"), key("Чехов"));
+    }
+
+    #[test]
+    fn test_western_names_keep_distinct_keys() {
+        for (a, b) in [
+            ("Henry", "Henri"),
+            ("Alex", "Aleks"),
+            ("Max", "Maks"),
+            ("Jay", "Jai"),
+            ("Khan", "Han"),
+            ("Anja", "Anya"),
+        ] {
+            assert_ne!(key(a), key(b), "{} / {}", a, b);
+        }
+        assert_eq!(key("Henry James"), "henry james");
+    }
+
+    #[test]
+    fn test_has_non_latin_script() {
+        assert!(has_non_latin_script("Достоевский"));
+        assert!(has_non_latin_script("Καβάφης"));
+        assert!(!has_non_latin_script("Dostoïevski"));
+    }
+}
@@ -1,5 +1,5 @@
 use crate::people::parse_names::ParsedName;
-use human_name::Name;
+use crate::transliteration::romanize;
 use strsim::levenshtein;
 use unicode_normalization::UnicodeNormalization;
 
@@ -19,8 +19,10 @@ impl MLStringUtils {
         }
     }
 
+    /// Minuscole, senza accenti e punteggiatura; cirillico e greco sono
+    /// traslitterati in caratteri latini
     pub fn normalize_string(&self, text: &str) -> String {
-        let normalized = text
+        let normalized = romanize(text)
             .nfc()
             .collect::<String>()
             .to_lowercase()
@@ -79,47 +81,9 @@ impl MLStringUtils {
 
     /// Parsing avanzato di un nome, compatibile con la logica precedente
     pub fn parse_name(input: &str) -> ParsedName {
-        // Caso nome singolo (es: "Mozart")
-        if input
-            .split(|c: char| c.is_whitespace() || c == '.')
-            .filter(|s| !s.is_empty())
-            .count()
-            == 1
-        {
-            return ParsedName {
-                given_name: input.trim().to_string(),
-                display_name: input.trim().to_string(),
-                ..Default::default()
-            };
-        }
-
-        // Usa la crate human-name per parsing avanzato
-        let parsed = Name::parse(input);
-
-        // Se il parsing fallisce, fallback a tutto input come display_name
-        if let Some(p) = parsed {
-            let given_name = p.given_name().unwrap_or("").to_string();
-            let surname = p.surname().to_string();
-            let middle_names: Vec<String> = p
-                .middle_names()
-                .map(|names| names.iter().map(|s| s.to_string()).collect())
-                .unwrap_or_default();
-            let title = p.honorific_prefix().map(|s| s.to_string());
-            let suffix = p.generational_suffix().map(|s| s.to_string());
-            let display_name = p.display_first_last();
-            ParsedName {
-                given_name,
-                surname,
-                middle_names,
-                title,
-                suffix,
-                display_name: display_name.to_string(),
-            }
-        } else {
-            ParsedName {
-                display_name: input.to_string(),
-                ..Default::default()
-            }
-        }
+        ParsedName::from_string(input).unwrap_or_else(|_| ParsedName {
+            display_name: input.to_string(),
+            ..Default::default()
+        })
     }
 }